@@ -0,0 +1,475 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response,
+    StdResult,
+};
+use cw2::set_contract_version;
+use cw20::Cw20ReceiveMsg;
+use cw_asset_lite::{Asset, AssetInfo};
+use cw_storage_plus::Bound;
+
+use crate::error::ContractError;
+use crate::msg::{
+    CreateMsg, ExecuteMsg, InstantiateMsg, ListResponse, QueryMsg, ReceiveMsg, SwapResponse,
+};
+use crate::state::{Swap, SWAPS, SWAP_COUNT};
+
+// Version info, for migration info
+const CONTRACT_NAME: &str = "crates.io:cw-otc-swap";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: InstantiateMsg,
+) -> StdResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    SWAP_COUNT.save(deps.storage, &0)?;
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Create(msg) => {
+            let deposit = native_deposit(&info)?;
+            execute_create(deps, env, info.sender, msg, deposit)
+        }
+        ExecuteMsg::Accept { id } => {
+            let deposit = native_deposit(&info)?;
+            execute_accept(deps, env, info.sender, id, deposit)
+        }
+        ExecuteMsg::Reclaim { id } => execute_reclaim(deps, env, id),
+        ExecuteMsg::Cancel { id } => execute_cancel(deps, info.sender, id),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+    }
+}
+
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let msg: ReceiveMsg = from_binary(&wrapper.msg)?;
+    if wrapper.amount.is_zero() {
+        return Err(ContractError::EmptyBalance {});
+    }
+    let deposit = Asset::cw20(info.sender, wrapper.amount);
+    // the original sender is the one authorizing with these tokens
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+    match msg {
+        ReceiveMsg::Create(create) => execute_create(deps, env, sender, create, deposit),
+        ReceiveMsg::Accept { id } => execute_accept(deps, env, sender, id, deposit),
+    }
+}
+
+pub fn execute_create(
+    deps: DepsMut,
+    env: Env,
+    maker: Addr,
+    msg: CreateMsg,
+    offer: Asset,
+) -> Result<Response, ContractError> {
+    if msg.ask.amount.is_zero() {
+        return Err(ContractError::EmptyAsk {});
+    }
+    if msg.expires.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+
+    let counterparty = deps.api.addr_validate(&msg.counterparty)?;
+    let ask_info = match msg.ask.info {
+        AssetInfo::Cw20(addr) => AssetInfo::Cw20(deps.api.addr_validate(addr.as_str())?),
+        native => native,
+    };
+
+    let swap = Swap {
+        maker,
+        counterparty,
+        offer,
+        ask: Asset::new(ask_info, msg.ask.amount),
+        expires: msg.expires,
+    };
+
+    let id = SWAP_COUNT.load(deps.storage)? + 1;
+    SWAP_COUNT.save(deps.storage, &id)?;
+    SWAPS.save(deps.storage, id, &swap)?;
+
+    let res = Response::new()
+        .add_attribute("action", "create")
+        .add_attribute("id", id.to_string())
+        .add_attribute("maker", swap.maker)
+        .add_attribute("counterparty", swap.counterparty)
+        .add_attribute("offer", swap.offer.to_string())
+        .add_attribute("ask", swap.ask.to_string());
+    Ok(res)
+}
+
+pub fn execute_accept(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    id: u64,
+    deposit: Asset,
+) -> Result<Response, ContractError> {
+    let swap = SWAPS.load(deps.storage, id)?;
+    if sender != swap.counterparty {
+        return Err(ContractError::Unauthorized {});
+    }
+    if swap.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+    if deposit != swap.ask {
+        return Err(ContractError::AskMismatch {
+            expected: swap.ask.to_string(),
+            received: deposit.to_string(),
+        });
+    }
+
+    // Delete the swap
+    SWAPS.remove(deps.storage, id);
+
+    // Exchange the deposits
+    let res = Response::new()
+        .add_message(swap.offer.transfer_msg(&swap.counterparty)?)
+        .add_message(swap.ask.transfer_msg(&swap.maker)?)
+        .add_attribute("action", "accept")
+        .add_attribute("id", id.to_string())
+        .add_attribute("maker", swap.maker)
+        .add_attribute("counterparty", swap.counterparty);
+    Ok(res)
+}
+
+pub fn execute_reclaim(deps: DepsMut, env: Env, id: u64) -> Result<Response, ContractError> {
+    let swap = SWAPS.load(deps.storage, id)?;
+    // Anyone can try to reclaim, as long as the swap is expired
+    if !swap.is_expired(&env.block) {
+        return Err(ContractError::NotExpired {});
+    }
+
+    // Delete the swap
+    SWAPS.remove(deps.storage, id);
+
+    let res = Response::new()
+        .add_message(swap.offer.transfer_msg(&swap.maker)?)
+        .add_attribute("action", "reclaim")
+        .add_attribute("id", id.to_string())
+        .add_attribute("to", swap.maker);
+    Ok(res)
+}
+
+pub fn execute_cancel(deps: DepsMut, sender: Addr, id: u64) -> Result<Response, ContractError> {
+    let swap = SWAPS.load(deps.storage, id)?;
+    // Only the maker can withdraw an open swap, expired or not
+    if sender != swap.maker {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Delete the swap
+    SWAPS.remove(deps.storage, id);
+
+    let res = Response::new()
+        .add_message(swap.offer.transfer_msg(&swap.maker)?)
+        .add_attribute("action", "cancel")
+        .add_attribute("id", id.to_string())
+        .add_attribute("to", swap.maker);
+    Ok(res)
+}
+
+/// Single native coin attached to the message.
+fn native_deposit(info: &MessageInfo) -> Result<Asset, ContractError> {
+    match info.funds.as_slice() {
+        [coin] if !coin.amount.is_zero() => Ok(coin.into()),
+        [] | [_] => Err(ContractError::EmptyBalance {}),
+        _ => Err(ContractError::MultipleAssets {}),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::List { start_after, limit } => to_binary(&query_list(deps, start_after, limit)?),
+        QueryMsg::Swap { id } => to_binary(&query_swap(deps, id)?),
+    }
+}
+
+fn swap_response(id: u64, swap: Swap) -> SwapResponse {
+    SwapResponse {
+        id,
+        maker: swap.maker.into(),
+        counterparty: swap.counterparty.into(),
+        offer: swap.offer,
+        ask: swap.ask,
+        expires: swap.expires,
+    }
+}
+
+fn query_swap(deps: Deps, id: u64) -> StdResult<SwapResponse> {
+    let swap = SWAPS.load(deps.storage, id)?;
+    Ok(swap_response(id, swap))
+}
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+fn query_list(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let swaps = SWAPS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(id, swap)| swap_response(id, swap)))
+        .collect::<StdResult<_>>()?;
+    Ok(ListResponse { swaps })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coin, coins, from_binary, to_binary, Uint128};
+    use cw20::Expiration;
+
+    use super::*;
+
+    fn mock_env_height(height: u64) -> Env {
+        let mut env = mock_env();
+        env.block.height = height;
+        env
+    }
+
+    fn create_msg(expires: u64) -> CreateMsg {
+        CreateMsg {
+            counterparty: String::from("taker"),
+            ask: Asset::native("ujuno", 50u128),
+            expires: Expiration::AtHeight(expires),
+        }
+    }
+
+    #[test]
+    fn test_create() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        let create = ExecuteMsg::Create(create_msg(123456));
+
+        // Exactly one non-zero coin must be deposited
+        let cases = vec![
+            (vec![], ContractError::EmptyBalance {}),
+            (vec![coin(0, "uatom")], ContractError::EmptyBalance {}),
+            (
+                vec![coin(10, "uatom"), coin(10, "ujuno")],
+                ContractError::MultipleAssets {},
+            ),
+        ];
+        for (funds, expected) in cases {
+            let info = mock_info("maker", &funds);
+            let err = execute(deps.as_mut(), mock_env(), info, create.clone()).unwrap_err();
+            assert_eq!(err, expected);
+        }
+
+        // Cannot ask for nothing
+        let mut msg = create_msg(123456);
+        msg.ask.amount = Uint128::zero();
+        let info = mock_info("maker", &coins(100, "uatom"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(msg)).unwrap_err();
+        assert_eq!(err, ContractError::EmptyAsk {});
+
+        // Cannot create an expired swap
+        let info = mock_info("maker", &coins(100, "uatom"));
+        let err = execute(deps.as_mut(), mock_env_height(123456), info, create.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Expired {});
+
+        let info = mock_info("maker", &coins(100, "uatom"));
+        let res = execute(deps.as_mut(), mock_env(), info, create).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(("id", "1"), res.attributes[1]);
+
+        let swap = query_swap(deps.as_ref(), 1).unwrap();
+        assert_eq!(
+            swap,
+            SwapResponse {
+                id: 1,
+                maker: String::from("maker"),
+                counterparty: String::from("taker"),
+                offer: Asset::native("uatom", 100u128),
+                ask: Asset::native("ujuno", 50u128),
+                expires: Expiration::AtHeight(123456),
+            }
+        );
+    }
+
+    #[test]
+    fn test_accept() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        let info = mock_info("maker", &coins(100, "uatom"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create_msg(123456))).unwrap();
+
+        // Only the counterparty can accept
+        let accept = ExecuteMsg::Accept { id: 1 };
+        let info = mock_info("someone", &coins(50, "ujuno"));
+        let err = execute(deps.as_mut(), mock_env(), info, accept.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // The deposit must match the ask
+        let info = mock_info("taker", &coins(40, "ujuno"));
+        let err = execute(deps.as_mut(), mock_env(), info, accept.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::AskMismatch {
+                expected: String::from("50ujuno"),
+                received: String::from("40ujuno"),
+            }
+        );
+
+        // A cw20 deposit does not match a native ask
+        let receive = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("taker"),
+            amount: Uint128::new(50),
+            msg: to_binary(&ReceiveMsg::Accept { id: 1 }).unwrap(),
+        });
+        let info = mock_info("token", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, receive).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::AskMismatch {
+                expected: String::from("50ujuno"),
+                received: String::from("50token"),
+            }
+        );
+
+        let info = mock_info("taker", &coins(50, "ujuno"));
+        let res = execute(deps.as_mut(), mock_env(), info, accept.clone()).unwrap();
+        assert_eq!(
+            res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>(),
+            vec![
+                Asset::native("uatom", 100u128).transfer_msg("taker").unwrap(),
+                Asset::native("ujuno", 50u128).transfer_msg("maker").unwrap(),
+            ]
+        );
+
+        // The swap is closed
+        let info = mock_info("taker", &coins(50, "ujuno"));
+        execute(deps.as_mut(), mock_env(), info, accept).unwrap_err();
+        query_swap(deps.as_ref(), 1).unwrap_err();
+    }
+
+    #[test]
+    fn test_reclaim() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        let info = mock_info("maker", &coins(100, "uatom"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create_msg(123456))).unwrap();
+
+        let info = mock_info("anyone", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Reclaim { id: 1 },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotExpired {});
+
+        // Cannot be accepted once expired
+        let taker = mock_info("taker", &coins(50, "ujuno"));
+        let err = execute(
+            deps.as_mut(),
+            mock_env_height(123456),
+            taker,
+            ExecuteMsg::Accept { id: 1 },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Expired {});
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env_height(123456),
+            info,
+            ExecuteMsg::Reclaim { id: 1 },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            Asset::native("uatom", 100u128).transfer_msg("maker").unwrap()
+        );
+        query_swap(deps.as_ref(), 1).unwrap_err();
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        // A swap that never expires cannot be reclaimed, only cancelled by its maker
+        let mut create = create_msg(123456);
+        create.expires = Expiration::Never {};
+        let info = mock_info("maker", &coins(100, "uatom"));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+
+        let info = mock_info("anyone", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Reclaim { id: 1 }).unwrap_err();
+        assert_eq!(err, ContractError::NotExpired {});
+
+        for sender in ["anyone", "taker"] {
+            let info = mock_info(sender, &[]);
+            let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Cancel { id: 1 }).unwrap_err();
+            assert_eq!(err, ContractError::Unauthorized {});
+        }
+
+        let info = mock_info("maker", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Cancel { id: 1 }).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            Asset::native("uatom", 100u128).transfer_msg("maker").unwrap()
+        );
+
+        // The swap is closed
+        query_swap(deps.as_ref(), 1).unwrap_err();
+        let taker = mock_info("taker", &coins(50, "ujuno"));
+        execute(deps.as_mut(), mock_env(), taker, ExecuteMsg::Accept { id: 1 }).unwrap_err();
+    }
+
+    #[test]
+    fn test_list() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, InstantiateMsg {}).unwrap();
+
+        for _ in 0..3 {
+            let info = mock_info("maker", &coins(100, "uatom"));
+            execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create_msg(123456)))
+                .unwrap();
+        }
+
+        let list = |start_after, limit| {
+            let msg = QueryMsg::List { start_after, limit };
+            let res: ListResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.swaps.into_iter().map(|s| s.id).collect::<Vec<_>>()
+        };
+        assert_eq!(list(None, None), vec![1, 2, 3]);
+        assert_eq!(list(Some(1), Some(1)), vec![2]);
+        assert_eq!(list(Some(3), None), Vec::<u64>::new());
+    }
+}
@@ -0,0 +1,29 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Send some coins to deposit in the swap")]
+    EmptyBalance {},
+
+    #[error("Only one asset can be deposited in a swap")]
+    MultipleAssets {},
+
+    #[error("The asked amount must be greater than zero")]
+    EmptyAsk {},
+
+    #[error("Deposit {received} does not match the asked {expected}")]
+    AskMismatch { expected: String, received: String },
+
+    #[error("Swap not yet expired")]
+    NotExpired {},
+
+    #[error("Expired swap")]
+    Expired {},
+}
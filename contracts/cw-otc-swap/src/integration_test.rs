@@ -0,0 +1,121 @@
+#![cfg(test)]
+
+use cosmwasm_std::{coins, to_binary, Addr, Empty, Uint128};
+use cw20::{Cw20Coin, Cw20Contract, Cw20ExecuteMsg, Expiration};
+use cw_asset_lite::Asset;
+use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+
+use crate::msg::{CreateMsg, ExecuteMsg, InstantiateMsg, ReceiveMsg};
+
+pub fn contract_otc_swap() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+pub fn contract_cw20() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    Box::new(contract)
+}
+
+#[test]
+// swap cw20 tokens of the maker with native tokens of the counterparty and back
+fn otc_swap_cw20_for_native() {
+    let maker = Addr::unchecked("maker");
+    let taker = Addr::unchecked("taker");
+
+    let mut router = App::new(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &taker, coins(2000, "ujuno"))
+            .unwrap();
+    });
+
+    // set up cw20 contract with some tokens
+    let cw20_id = router.store_code(contract_cw20());
+    let msg = cw20_base::msg::InstantiateMsg {
+        name: "Cash Money".to_string(),
+        symbol: "CASH".to_string(),
+        decimals: 2,
+        initial_balances: vec![Cw20Coin {
+            address: maker.to_string(),
+            amount: Uint128::new(5000),
+        }],
+        mint: None,
+        marketing: None,
+    };
+    let cash_addr = router
+        .instantiate_contract(cw20_id, maker.clone(), &msg, &[], "CASH", None)
+        .unwrap();
+    let cash = Cw20Contract(cash_addr.clone());
+
+    let swap_id = router.store_code(contract_otc_swap());
+    let swap_addr = router
+        .instantiate_contract(swap_id, maker.clone(), &InstantiateMsg {}, &[], "OTC", None)
+        .unwrap();
+
+    // the maker offers 1200 CASH for 300ujuno
+    let create_msg = ReceiveMsg::Create(CreateMsg {
+        counterparty: taker.to_string(),
+        ask: Asset::native("ujuno", 300u128),
+        expires: Expiration::AtHeight(router.block_info().height + 100),
+    });
+    let send_msg = Cw20ExecuteMsg::Send {
+        contract: swap_addr.to_string(),
+        amount: Uint128::new(1200),
+        msg: to_binary(&create_msg).unwrap(),
+    };
+    router
+        .execute_contract(maker.clone(), cash_addr.clone(), &send_msg, &[])
+        .unwrap();
+    let swap_balance = cash.balance::<_, _, Empty>(&router, swap_addr.clone()).unwrap();
+    assert_eq!(swap_balance, Uint128::new(1200));
+
+    // the counterparty accepts depositing the native tokens
+    router
+        .execute_contract(
+            taker.clone(),
+            swap_addr.clone(),
+            &ExecuteMsg::Accept { id: 1 },
+            &coins(300, "ujuno"),
+        )
+        .unwrap();
+
+    let taker_cash = cash.balance::<_, _, Empty>(&router, taker.clone()).unwrap();
+    assert_eq!(taker_cash, Uint128::new(1200));
+    let maker_native = router.wrap().query_balance(&maker, "ujuno").unwrap();
+    assert_eq!(maker_native.amount, Uint128::new(300));
+
+    // the counterparty offers native tokens for CASH, accepted with a cw20 deposit
+    let create_msg = ExecuteMsg::Create(CreateMsg {
+        counterparty: maker.to_string(),
+        ask: Asset::cw20(cash_addr.clone(), 500u128),
+        expires: Expiration::AtHeight(router.block_info().height + 100),
+    });
+    router
+        .execute_contract(taker.clone(), swap_addr.clone(), &create_msg, &coins(100, "ujuno"))
+        .unwrap();
+
+    let send_msg = Cw20ExecuteMsg::Send {
+        contract: swap_addr.to_string(),
+        amount: Uint128::new(500),
+        msg: to_binary(&ReceiveMsg::Accept { id: 2 }).unwrap(),
+    };
+    router
+        .execute_contract(maker.clone(), cash_addr, &send_msg, &[])
+        .unwrap();
+
+    let taker_cash = cash.balance::<_, _, Empty>(&router, taker).unwrap();
+    assert_eq!(taker_cash, Uint128::new(1700));
+    let maker_native = router.wrap().query_balance(&maker, "ujuno").unwrap();
+    assert_eq!(maker_native.amount, Uint128::new(400));
+    let swap_balance = cash.balance::<_, _, Empty>(&router, swap_addr).unwrap();
+    assert_eq!(swap_balance, Uint128::zero());
+}
@@ -0,0 +1,70 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cw20::{Cw20ReceiveMsg, Expiration};
+use cw_asset_lite::Asset;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Opens a swap depositing the single native coin attached to the message.
+    Create(CreateMsg),
+    /// Completes a swap depositing the asked native coin. Both deposits are exchanged
+    /// in the same transaction.
+    Accept { id: u64 },
+    /// Returns the offer of an expired swap to its maker. Anyone can trigger it.
+    Reclaim { id: u64 },
+    /// Withdraws an open swap and returns the offer to its maker. Only the maker can cancel.
+    Cancel { id: u64 },
+    /// This accepts a properly-encoded ReceiveMsg from a cw20 contract
+    Receive(Cw20ReceiveMsg),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    Create(CreateMsg),
+    Accept { id: u64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CreateMsg {
+    /// The only address that can accept the swap.
+    pub counterparty: String,
+    /// Asset, native or cw20, that the counterparty must deposit.
+    pub ask: Asset,
+    /// You can set expiration at time or at block height the swap is valid at.
+    /// After the swap is expired, the offer can be returned to the maker. Until then only
+    /// the maker can withdraw it with `Cancel`.
+    pub expires: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Show the open swaps, by id. Return type is ListResponse.
+    List {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the details of a swap, error if not open. Return type: SwapResponse.
+    Swap { id: u64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SwapResponse {
+    pub id: u64,
+    pub maker: String,
+    pub counterparty: String,
+    pub offer: Asset,
+    pub ask: Asset,
+    pub expires: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ListResponse {
+    pub swaps: Vec<SwapResponse>,
+}
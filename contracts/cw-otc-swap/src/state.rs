@@ -0,0 +1,32 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, BlockInfo};
+use cw20::Expiration;
+use cw_asset_lite::Asset;
+use cw_storage_plus::{Item, Map};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Swap {
+    /// Party that opened the swap depositing the offer.
+    pub maker: Addr,
+    /// The only party that can accept the swap.
+    pub counterparty: Addr,
+    /// Asset deposited by the maker.
+    pub offer: Asset,
+    /// Asset the counterparty must deposit to complete the swap.
+    pub ask: Asset,
+    /// Once expired, the swap cannot be accepted and the offer can be reclaimed.
+    pub expires: Expiration,
+}
+
+impl Swap {
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        self.expires.is_expired(block)
+    }
+}
+
+pub const SWAPS: Map<u64, Swap> = Map::new("swaps");
+
+/// Number of swaps created, used as id of the next one.
+pub const SWAP_COUNT: Item<u64> = Item::new("swap_count");
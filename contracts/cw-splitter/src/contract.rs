@@ -0,0 +1,738 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult, Storage, Uint128, Uint256,
+};
+use cw2::set_contract_version;
+use cw20::{BalanceResponse, Cw20QueryMsg, Cw20ReceiveMsg};
+use cw_asset_lite::{Asset, AssetInfo};
+use std::convert::TryFrom;
+
+use crate::error::ContractError;
+use crate::msg::{
+    ClaimableResponse, ConfigResponse, Cw4QueryMsg, ExecuteMsg, InstantiateMsg,
+    MemberChangedHookMsg, MemberListResponse, MemberResponse, QueryMsg, ReceiveMsg,
+};
+use crate::state::{
+    asset_key, Config, Distribution, Share, CONFIG, DISTRIBUTIONS, INDEX_SCALE, MEMBERS, SHARES,
+    TOTAL_WEIGHT,
+};
+
+// Version info, for migration info
+const CONTRACT_NAME: &str = "crates.io:cw-splitter";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Maximum number of assets split, every member change settles all of them.
+pub const MAX_ASSETS: usize = 10;
+
+// Page size used to copy the members of the group
+const MEMBERS_PAGE: u32 = 30;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    if msg.assets.is_empty() || msg.assets.len() > MAX_ASSETS {
+        return Err(ContractError::InvalidAssets { max: MAX_ASSETS });
+    }
+    let mut assets: Vec<AssetInfo> = vec![];
+    for info in msg.assets {
+        let info = match info {
+            AssetInfo::Cw20(addr) => AssetInfo::Cw20(deps.api.addr_validate(addr.as_str())?),
+            native => native,
+        };
+        if assets.contains(&info) {
+            return Err(ContractError::InvalidAssets { max: MAX_ASSETS });
+        }
+        assets.push(info);
+    }
+    let group = deps.api.addr_validate(&msg.group)?;
+
+    // Members joining later are followed through the hooks of the group.
+    let mut total_weight: u64 = 0;
+    let mut start_after = None;
+    loop {
+        let page: MemberListResponse = deps.querier.query_wasm_smart(
+            &group,
+            &Cw4QueryMsg::ListMembers {
+                start_after: start_after.clone(),
+                limit: Some(MEMBERS_PAGE),
+            },
+        )?;
+        for member in &page.members {
+            let addr = deps.api.addr_validate(&member.addr)?;
+            MEMBERS.save(deps.storage, &addr, &member.weight)?;
+            total_weight = total_weight
+                .checked_add(member.weight)
+                .ok_or_else(|| StdError::generic_err("Total weight overflow"))?;
+        }
+        if page.members.len() < MEMBERS_PAGE as usize {
+            break;
+        }
+        start_after = page.members.last().map(|member| member.addr.clone());
+    }
+    TOTAL_WEIGHT.save(deps.storage, &total_weight)?;
+    CONFIG.save(deps.storage, &Config { group, assets })?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Distribute {} => execute_distribute(deps, env),
+        ExecuteMsg::Claim {} => execute_claim(deps, info),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+        ExecuteMsg::MemberChangedHook(msg) => execute_member_changed(deps, info, msg),
+    }
+}
+
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let msg: ReceiveMsg = from_binary(&wrapper.msg)?;
+    let cfg = CONFIG.load(deps.storage)?;
+    let token = AssetInfo::Cw20(info.sender);
+    if !cfg.assets.contains(&token) {
+        return Err(ContractError::UnknownAsset {
+            asset: token.to_string(),
+        });
+    }
+    match msg {
+        ReceiveMsg::Distribute {} => execute_distribute(deps, env),
+    }
+}
+
+pub fn execute_distribute(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let total_weight = TOTAL_WEIGHT.load(deps.storage)?;
+    if total_weight == 0 {
+        return Err(ContractError::NoMembers {});
+    }
+
+    // Whatever the contract holds beyond the funds owed has not been distributed yet,
+    // plain transfers included.
+    let mut distributed = vec![];
+    for info in cfg.assets {
+        let key = asset_key(&info);
+        let mut distribution = DISTRIBUTIONS
+            .may_load(deps.storage, &key)?
+            .unwrap_or_default();
+        let balance = query_asset_balance(deps.as_ref(), &env, &info)?;
+        let amount = balance.checked_sub(distribution.owed)?;
+        if amount.is_zero() {
+            continue;
+        }
+        // The total weight is not zero, checked above.
+        let increment = Uint256::from(amount).checked_mul(Uint256::from(INDEX_SCALE))?
+            / Uint256::from(total_weight);
+        distribution.index = distribution.index.checked_add(increment)?;
+        distribution.owed = distribution.owed.checked_add(amount)?;
+        DISTRIBUTIONS.save(deps.storage, &key, &distribution)?;
+        distributed.push(Asset::new(info, amount));
+    }
+    if distributed.is_empty() {
+        return Err(ContractError::NothingToDistribute {});
+    }
+
+    let distributed: Vec<_> = distributed.iter().map(Asset::to_string).collect();
+    let res = Response::new()
+        .add_attribute("action", "distribute")
+        .add_attribute("distributed", distributed.join(","))
+        .add_attribute("total_weight", total_weight.to_string());
+    Ok(res)
+}
+
+pub fn execute_claim(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let weight = MEMBERS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+
+    let mut claimed = vec![];
+    for asset_info in cfg.assets {
+        let key = asset_key(&asset_info);
+        let mut distribution = DISTRIBUTIONS
+            .may_load(deps.storage, &key)?
+            .unwrap_or_default();
+        let mut share = SHARES
+            .may_load(deps.storage, (&info.sender, &key))?
+            .unwrap_or_default();
+        settle(&mut share, weight, &distribution)?;
+        // Shares left unsettled keep the fractions of a unit not paid yet.
+        if share.pending.is_zero() {
+            continue;
+        }
+        distribution.owed = distribution.owed.checked_sub(share.pending)?;
+        DISTRIBUTIONS.save(deps.storage, &key, &distribution)?;
+        claimed.push(Asset::new(asset_info, share.pending));
+        share.pending = Uint128::zero();
+        SHARES.save(deps.storage, (&info.sender, &key), &share)?;
+    }
+    if claimed.is_empty() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    let msgs = claimed
+        .iter()
+        .map(|asset| asset.transfer_msg(&info.sender))
+        .collect::<StdResult<Vec<_>>>()?;
+    let claimed: Vec<_> = claimed.iter().map(Asset::to_string).collect();
+    let res = Response::new()
+        .add_messages(msgs)
+        .add_attribute("action", "claim")
+        .add_attribute("member", info.sender)
+        .add_attribute("claimed", claimed.join(","));
+    Ok(res)
+}
+
+pub fn execute_member_changed(
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: MemberChangedHookMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if info.sender != cfg.group {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut total_weight = TOTAL_WEIGHT.load(deps.storage)?;
+    for diff in msg.diffs {
+        let member = deps.api.addr_validate(&diff.key)?;
+        // The shares accrued with the previous weight are settled before it changes,
+        // and new members start from the current distributions.
+        let old = MEMBERS.may_load(deps.storage, &member)?.unwrap_or_default();
+        settle_all(deps.storage, &cfg.assets, &member, old)?;
+        let new = diff.new.unwrap_or_default();
+        match diff.new {
+            Some(weight) => MEMBERS.save(deps.storage, &member, &weight)?,
+            None => MEMBERS.remove(deps.storage, &member),
+        }
+        total_weight = (total_weight - old)
+            .checked_add(new)
+            .ok_or_else(|| StdError::generic_err("Total weight overflow"))?;
+    }
+    TOTAL_WEIGHT.save(deps.storage, &total_weight)?;
+
+    let res = Response::new()
+        .add_attribute("action", "member_changed")
+        .add_attribute("total_weight", total_weight.to_string());
+    Ok(res)
+}
+
+/// Moves to `pending` the amount accrued by `weight` since the share was last settled.
+fn settle(share: &mut Share, weight: u64, distribution: &Distribution) -> StdResult<()> {
+    let accrued = Uint256::from(weight)
+        .checked_mul(distribution.index.checked_sub(share.index)?)?
+        / Uint256::from(INDEX_SCALE);
+    let accrued =
+        Uint128::try_from(accrued).map_err(|err| StdError::generic_err(err.to_string()))?;
+    share.pending = share.pending.checked_add(accrued)?;
+    share.index = distribution.index;
+    Ok(())
+}
+
+fn settle_all(
+    storage: &mut dyn Storage,
+    assets: &[AssetInfo],
+    member: &Addr,
+    weight: u64,
+) -> StdResult<()> {
+    for info in assets {
+        let key = asset_key(info);
+        let distribution = DISTRIBUTIONS.may_load(storage, &key)?.unwrap_or_default();
+        let mut share = SHARES
+            .may_load(storage, (member, &key))?
+            .unwrap_or_default();
+        settle(&mut share, weight, &distribution)?;
+        SHARES.save(storage, (member, &key), &share)?;
+    }
+    Ok(())
+}
+
+/// Balance of the contract in the asset.
+fn query_asset_balance(deps: Deps, env: &Env, info: &AssetInfo) -> StdResult<Uint128> {
+    match info {
+        AssetInfo::Native(denom) => Ok(deps
+            .querier
+            .query_balance(&env.contract.address, denom)?
+            .amount),
+        AssetInfo::Cw20(token) => {
+            let res: BalanceResponse = deps.querier.query_wasm_smart(
+                token,
+                &Cw20QueryMsg::Balance {
+                    address: env.contract.address.to_string(),
+                },
+            )?;
+            Ok(res.balance)
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Member { address } => to_binary(&query_member(deps, address)?),
+        QueryMsg::Claimable { address } => to_binary(&query_claimable(deps, address)?),
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        group: cfg.group.into(),
+        assets: cfg.assets,
+        total_weight: TOTAL_WEIGHT.load(deps.storage)?,
+    })
+}
+
+fn query_member(deps: Deps, address: String) -> StdResult<MemberResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let weight = MEMBERS.may_load(deps.storage, &address)?;
+    Ok(MemberResponse { weight })
+}
+
+fn query_claimable(deps: Deps, address: String) -> StdResult<ClaimableResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let cfg = CONFIG.load(deps.storage)?;
+    let weight = MEMBERS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+
+    let mut claimable = vec![];
+    for info in cfg.assets {
+        let key = asset_key(&info);
+        let distribution = DISTRIBUTIONS
+            .may_load(deps.storage, &key)?
+            .unwrap_or_default();
+        let mut share = SHARES
+            .may_load(deps.storage, (&address, &key))?
+            .unwrap_or_default();
+        settle(&mut share, weight, &distribution)?;
+        if !share.pending.is_zero() {
+            claimable.push(Asset::new(info, share.pending));
+        }
+    }
+    Ok(ClaimableResponse { claimable })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+        MOCK_CONTRACT_ADDR,
+    };
+    use cosmwasm_std::{
+        coins, BankMsg, ContractResult, CosmosMsg, OwnedDeps, SubMsg, SystemResult, WasmMsg,
+        WasmQuery,
+    };
+    use cw20::Cw20ExecuteMsg;
+
+    use super::*;
+    use crate::msg::{Member, MemberDiff};
+
+    // Group with `members`, and a cw20 `token` held by the splitter.
+    fn mock_group(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        members: &[(&str, u64)],
+        token_balance: u128,
+    ) {
+        let members: Vec<_> = members
+            .iter()
+            .map(|(addr, weight)| Member {
+                addr: addr.to_string(),
+                weight: *weight,
+            })
+            .collect();
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "group" => {
+                let Cw4QueryMsg::ListMembers { start_after, limit } = from_binary(msg).unwrap();
+                let members: Vec<_> = members
+                    .iter()
+                    .filter(|member| {
+                        start_after
+                            .as_ref()
+                            .is_none_or(|start| &member.addr > start)
+                    })
+                    .take(limit.unwrap() as usize)
+                    .cloned()
+                    .collect();
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&MemberListResponse { members }).unwrap(),
+                ))
+            }
+            WasmQuery::Smart { contract_addr, .. } if contract_addr == "token" => {
+                let balance = Uint128::new(token_balance);
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&BalanceResponse { balance }).unwrap(),
+                ))
+            }
+            _ => panic!("unexpected query"),
+        });
+    }
+
+    fn instantiate_msg() -> InstantiateMsg {
+        InstantiateMsg {
+            group: String::from("group"),
+            assets: vec![
+                AssetInfo::Native(String::from("ujuno")),
+                AssetInfo::Cw20(Addr::unchecked("token")),
+            ],
+        }
+    }
+
+    fn claimable(deps: Deps, address: &str) -> Vec<Asset> {
+        query_claimable(deps, String::from(address))
+            .unwrap()
+            .claimable
+    }
+
+    #[test]
+    fn test_instantiate() {
+        let mut deps = mock_dependencies();
+        mock_group(&mut deps, &[("alice", 1), ("bob", 3)], 0);
+        let info = mock_info("creator", &[]);
+
+        let invalid = vec![
+            vec![],
+            vec![AssetInfo::Native(String::from("ujuno")); 2],
+            vec![AssetInfo::Native(String::from("ujuno")); MAX_ASSETS + 1],
+        ];
+        for assets in invalid {
+            let msg = InstantiateMsg {
+                assets,
+                ..instantiate_msg()
+            };
+            let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+            assert_eq!(err, ContractError::InvalidAssets { max: MAX_ASSETS });
+        }
+
+        instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+        let config = query_config(deps.as_ref()).unwrap();
+        assert_eq!(config.group, "group");
+        assert_eq!(config.total_weight, 4);
+        let member = query_member(deps.as_ref(), String::from("bob")).unwrap();
+        assert_eq!(member.weight, Some(3));
+    }
+
+    #[test]
+    fn test_instantiate_paginates_members() {
+        // One member more than a page
+        let names: Vec<_> = (0..=MEMBERS_PAGE)
+            .map(|i| format!("member{:02}", i))
+            .collect();
+        let members: Vec<_> = names.iter().map(|name| (name.as_str(), 1)).collect();
+        let mut deps = mock_dependencies();
+        mock_group(&mut deps, &members, 0);
+
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            instantiate_msg(),
+        )
+        .unwrap();
+        assert_eq!(query_config(deps.as_ref()).unwrap().total_weight, 31);
+    }
+
+    #[test]
+    fn test_distribute_and_claim() {
+        let mut deps = mock_dependencies();
+        mock_group(&mut deps, &[("alice", 1), ("bob", 3)], 0);
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            instantiate_msg(),
+        )
+        .unwrap();
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::Distribute {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NothingToDistribute {});
+
+        // Funds sent with a plain transfer are split as well
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(101, "ujuno"));
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::Distribute {},
+        )
+        .unwrap();
+        assert_eq!(("distributed", "101ujuno"), res.attributes[1]);
+        assert_eq!(
+            claimable(deps.as_ref(), "alice"),
+            vec![Asset::native("ujuno", 25u128)]
+        );
+        assert_eq!(
+            claimable(deps.as_ref(), "bob"),
+            vec![Asset::native("ujuno", 75u128)]
+        );
+        assert_eq!(claimable(deps.as_ref(), "carol"), vec![]);
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            ExecuteMsg::Claim {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: String::from("alice"),
+                amount: coins(25, "ujuno"),
+            })]
+        );
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(76, "ujuno"));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            ExecuteMsg::Claim {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+
+        // The funds owed are not distributed twice, the rounding dust stays owed
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::Distribute {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NothingToDistribute {});
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(116, "ujuno"));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::Distribute {},
+        )
+        .unwrap();
+        assert_eq!(
+            claimable(deps.as_ref(), "alice"),
+            vec![Asset::native("ujuno", 10u128)]
+        );
+        assert_eq!(
+            claimable(deps.as_ref(), "bob"),
+            vec![Asset::native("ujuno", 105u128)]
+        );
+    }
+
+    #[test]
+    fn test_distribute_18_decimals() {
+        let mut deps = mock_dependencies();
+        mock_group(&mut deps, &[("alice", 1), ("bob", 3)], 0);
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            instantiate_msg(),
+        )
+        .unwrap();
+
+        // A million tokens of 18 decimals per unit of weight is beyond a `Decimal` index
+        let million: u128 = 1_000_000 * 1_000_000_000_000_000_000;
+        for balance in [4 * million, 8 * million + 3] {
+            deps.querier
+                .update_balance(MOCK_CONTRACT_ADDR, coins(balance, "ujuno"));
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("anyone", &[]),
+                ExecuteMsg::Distribute {},
+            )
+            .unwrap();
+        }
+        assert_eq!(
+            claimable(deps.as_ref(), "alice"),
+            vec![Asset::native("ujuno", 2 * million)]
+        );
+        assert_eq!(
+            claimable(deps.as_ref(), "bob"),
+            vec![Asset::native("ujuno", 6 * million + 2)]
+        );
+    }
+
+    #[test]
+    fn test_member_changes() {
+        let mut deps = mock_dependencies();
+        mock_group(&mut deps, &[("alice", 1), ("bob", 3)], 0);
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            instantiate_msg(),
+        )
+        .unwrap();
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(100, "ujuno"));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::Distribute {},
+        )
+        .unwrap();
+
+        // Bob leaves and carol joins: bob keeps what he accrued, carol starts from now
+        let hook = ExecuteMsg::MemberChangedHook(MemberChangedHookMsg {
+            diffs: vec![
+                MemberDiff {
+                    key: String::from("bob"),
+                    old: Some(3),
+                    new: None,
+                },
+                MemberDiff {
+                    key: String::from("carol"),
+                    old: None,
+                    new: Some(4),
+                },
+            ],
+        });
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("bob", &[]),
+            hook.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("group", &[]), hook).unwrap();
+        assert_eq!(query_config(deps.as_ref()).unwrap().total_weight, 5);
+        assert_eq!(
+            query_member(deps.as_ref(), String::from("bob"))
+                .unwrap()
+                .weight,
+            None
+        );
+
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(150, "ujuno"));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::Distribute {},
+        )
+        .unwrap();
+        assert_eq!(
+            claimable(deps.as_ref(), "alice"),
+            vec![Asset::native("ujuno", 35u128)]
+        );
+        assert_eq!(
+            claimable(deps.as_ref(), "bob"),
+            vec![Asset::native("ujuno", 75u128)]
+        );
+        assert_eq!(
+            claimable(deps.as_ref(), "carol"),
+            vec![Asset::native("ujuno", 40u128)]
+        );
+
+        // Without members nothing can be distributed
+        let hook = ExecuteMsg::MemberChangedHook(MemberChangedHookMsg {
+            diffs: [("alice", 1), ("carol", 4)]
+                .iter()
+                .map(|(member, weight)| MemberDiff {
+                    key: member.to_string(),
+                    old: Some(*weight),
+                    new: None,
+                })
+                .collect(),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("group", &[]), hook).unwrap();
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(200, "ujuno"));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::Distribute {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoMembers {});
+        assert_eq!(
+            claimable(deps.as_ref(), "carol"),
+            vec![Asset::native("ujuno", 40u128)]
+        );
+    }
+
+    #[test]
+    fn test_receive_cw20() {
+        let mut deps = mock_dependencies();
+        mock_group(&mut deps, &[("alice", 1), ("bob", 1)], 50);
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            instantiate_msg(),
+        )
+        .unwrap();
+
+        let receive = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("game"),
+            amount: Uint128::new(50),
+            msg: to_binary(&ReceiveMsg::Distribute {}).unwrap(),
+        });
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other", &[]),
+            receive.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::UnknownAsset {
+                asset: String::from("other")
+            }
+        );
+        execute(deps.as_mut(), mock_env(), mock_info("token", &[]), receive).unwrap();
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("bob", &[]),
+            ExecuteMsg::Claim {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: String::from("token"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("bob"),
+                    amount: Uint128::new(25),
+                })
+                .unwrap(),
+                funds: vec![],
+            }))]
+        );
+    }
+}
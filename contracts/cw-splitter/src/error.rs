@@ -0,0 +1,29 @@
+use cosmwasm_std::{OverflowError, StdError};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Between 1 and {max} distinct assets must be split")]
+    InvalidAssets { max: usize },
+
+    #[error("Asset {asset} is not split by this contract")]
+    UnknownAsset { asset: String },
+
+    #[error("The group has no members with a weight")]
+    NoMembers {},
+
+    #[error("No new funds to distribute")]
+    NothingToDistribute {},
+
+    #[error("Nothing to claim")]
+    NothingToClaim {},
+}
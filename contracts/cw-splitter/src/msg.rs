@@ -0,0 +1,100 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cw20::Cw20ReceiveMsg;
+use cw_asset_lite::{Asset, AssetInfo};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    /// cw4 group whose members share the funds, by weight.
+    pub group: String,
+    /// Native denoms and cw20 tokens split by the contract.
+    pub assets: Vec<AssetInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Splits among the members the funds received since the last distribution, native
+    /// coins attached to the message included. Anyone can call it.
+    Distribute {},
+    /// Pays the sender its share of every asset.
+    Claim {},
+    /// This accepts a properly-encoded ReceiveMsg from a cw20 contract
+    Receive(Cw20ReceiveMsg),
+    /// Sent by the group when its members change, once the splitter is added to its hooks.
+    MemberChangedHook(MemberChangedHookMsg),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    /// Splits the tokens sent, along with the other funds not distributed yet.
+    Distribute {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Returns ConfigResponse.
+    Config {},
+    /// Weight of an address in the split. Returns MemberResponse.
+    Member { address: String },
+    /// Funds an address can claim. Returns ClaimableResponse.
+    Claimable { address: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ConfigResponse {
+    pub group: String,
+    pub assets: Vec<AssetInfo>,
+    pub total_weight: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MemberResponse {
+    pub weight: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ClaimableResponse {
+    /// Only the assets with something to claim are listed.
+    pub claimable: Vec<Asset>,
+}
+
+/// Hook message of the cw4 group, mirrored here along with the queries below to keep
+/// the splitter independent from the cw4 crates.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MemberChangedHookMsg {
+    pub diffs: Vec<MemberDiff>,
+}
+
+/// Change of weight of a member: `old` is None for new members, `new` for removed ones.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MemberDiff {
+    pub key: String,
+    pub old: Option<u64>,
+    pub new: Option<u64>,
+}
+
+/// Query sent to the cw4 group to copy its members at instantiation.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw4QueryMsg {
+    ListMembers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+/// Response of the cw4 `ListMembers` query.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MemberListResponse {
+    pub members: Vec<Member>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Member {
+    pub addr: String,
+    pub weight: u64,
+}
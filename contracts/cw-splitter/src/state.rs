@@ -0,0 +1,58 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, Uint128, Uint256};
+use cw_asset_lite::AssetInfo;
+use cw_storage_plus::{Item, Map};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// cw4 group whose members share the funds, the only sender of the member hooks.
+    pub group: Addr,
+    /// Assets split by the contract, bounded so that every member change can settle
+    /// all of them.
+    pub assets: Vec<AssetInfo>,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// Weights of the members, copied from the group at instantiation and kept in sync by
+/// the member hooks.
+pub const MEMBERS: Map<&Addr, u64> = Map::new("members");
+
+pub const TOTAL_WEIGHT: Item<u64> = Item::new("total_weight");
+
+/// Scale of the distribution indexes, enough to split any `Uint128` amount to the
+/// smallest unit of an 18-decimal token.
+pub const INDEX_SCALE: u128 = 1_000_000_000_000_000_000;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Distribution {
+    /// Amount distributed to a unit of weight since the instantiation, times `INDEX_SCALE`.
+    pub index: Uint256,
+    /// Funds distributed and not claimed yet, rounding dust included. The balance
+    /// beyond it is distributed by the next `Distribute`.
+    pub owed: Uint128,
+}
+
+/// Distribution of each asset, by `asset_key`.
+pub const DISTRIBUTIONS: Map<&str, Distribution> = Map::new("distributions");
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Share {
+    /// Index of the distribution when the share was last settled.
+    pub index: Uint256,
+    /// Settled and not claimed yet.
+    pub pending: Uint128,
+}
+
+/// Share of each member in each asset, settled whenever its weight changes or it claims.
+pub const SHARES: Map<(&Addr, &str), Share> = Map::new("shares");
+
+/// Storage key of an asset, telling native denoms from cw20 addresses.
+pub fn asset_key(info: &AssetInfo) -> String {
+    match info {
+        AssetInfo::Native(denom) => format!("native:{}", denom),
+        AssetInfo::Cw20(addr) => format!("cw20:{}", addr),
+    }
+}
@@ -0,0 +1,470 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Order,
+    Response, StdResult,
+};
+use cw2::set_contract_version;
+use cw20::Cw20ReceiveMsg;
+use cw_asset_lite::{Asset, AssetInfo};
+use cw_storage_plus::Bound;
+
+use crate::error::ContractError;
+use crate::msg::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg, TicketResponse,
+    TicketsResponse, VerifyTicketResponse,
+};
+use crate::state::{Config, CONFIG, OWNER_TICKETS, SOLD, TICKETS};
+
+// Version info, for migration info
+const CONTRACT_NAME: &str = "crates.io:cw-ticket-vendor";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    if msg.supply == 0 || msg.price.amount.is_zero() {
+        return Err(ContractError::InvalidConfig {});
+    }
+    let price_info = match msg.price.info {
+        AssetInfo::Cw20(addr) => AssetInfo::Cw20(deps.api.addr_validate(addr.as_str())?),
+        native => native,
+    };
+
+    let config = Config {
+        price: Asset::new(price_info, msg.price.amount),
+        supply: msg.supply,
+        sale_start: msg.sale_start,
+        sale_end: msg.sale_end,
+        recipient: deps.api.addr_validate(&msg.recipient)?,
+    };
+    CONFIG.save(deps.storage, &config)?;
+    SOLD.save(deps.storage, &0)?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Buy {} => {
+            let payment = info.funds.iter().map(Asset::from).collect();
+            execute_buy(deps, env, info.sender, payment)
+        }
+        ExecuteMsg::Transfer {
+            ticket_id,
+            recipient,
+        } => execute_transfer(deps, info, ticket_id, recipient),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+    }
+}
+
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let msg: ReceiveMsg = from_binary(&wrapper.msg)?;
+    let payment = Asset::cw20(info.sender, wrapper.amount);
+    // the original sender is the one buying the ticket
+    let buyer = deps.api.addr_validate(&wrapper.sender)?;
+    match msg {
+        ReceiveMsg::Buy {} => execute_buy(deps, env, buyer, vec![payment]),
+    }
+}
+
+pub fn execute_buy(
+    deps: DepsMut,
+    env: Env,
+    buyer: Addr,
+    payment: Vec<Asset>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.sale_start.is_triggered(&env.block) {
+        return Err(ContractError::SaleNotStarted {
+            start: config.sale_start,
+        });
+    }
+    if config.sale_end.is_expired(&env.block) {
+        return Err(ContractError::SaleEnded {
+            end: config.sale_end,
+        });
+    }
+    if payment != [config.price.clone()] {
+        let received: Vec<_> = payment.iter().map(Asset::to_string).collect();
+        return Err(ContractError::PaymentMismatch {
+            expected: config.price.to_string(),
+            received: received.join(","),
+        });
+    }
+
+    let ticket_id = SOLD.load(deps.storage)? + 1;
+    if ticket_id > config.supply {
+        return Err(ContractError::SoldOut {});
+    }
+    SOLD.save(deps.storage, &ticket_id)?;
+    TICKETS.save(deps.storage, ticket_id, &buyer)?;
+    OWNER_TICKETS.save(deps.storage, (&buyer, ticket_id), &Empty {})?;
+
+    let res = Response::new()
+        .add_message(config.price.transfer_msg(&config.recipient)?)
+        .add_attribute("action", "buy")
+        .add_attribute("ticket_id", ticket_id.to_string())
+        .add_attribute("buyer", buyer)
+        .add_attribute("price", config.price.to_string());
+    Ok(res)
+}
+
+pub fn execute_transfer(
+    deps: DepsMut,
+    info: MessageInfo,
+    ticket_id: u64,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let owner = TICKETS.load(deps.storage, ticket_id)?;
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    TICKETS.save(deps.storage, ticket_id, &recipient)?;
+    OWNER_TICKETS.remove(deps.storage, (&owner, ticket_id));
+    OWNER_TICKETS.save(deps.storage, (&recipient, ticket_id), &Empty {})?;
+
+    let res = Response::new()
+        .add_attribute("action", "transfer")
+        .add_attribute("ticket_id", ticket_id.to_string())
+        .add_attribute("from", owner)
+        .add_attribute("to", recipient);
+    Ok(res)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Ticket { ticket_id } => to_binary(&query_ticket(deps, ticket_id)?),
+        QueryMsg::Tickets {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_tickets(deps, owner, start_after, limit)?),
+        QueryMsg::VerifyTicket { ticket_id, owner } => {
+            to_binary(&query_verify_ticket(deps, ticket_id, owner)?)
+        }
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        price: config.price,
+        supply: config.supply,
+        sold: SOLD.load(deps.storage)?,
+        sale_start: config.sale_start,
+        sale_end: config.sale_end,
+        recipient: config.recipient.into(),
+    })
+}
+
+fn query_ticket(deps: Deps, ticket_id: u64) -> StdResult<TicketResponse> {
+    let owner = TICKETS.load(deps.storage, ticket_id)?;
+    Ok(TicketResponse {
+        ticket_id,
+        owner: owner.into(),
+    })
+}
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+fn query_tickets(
+    deps: Deps,
+    owner: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<TicketsResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let tickets = OWNER_TICKETS
+        .prefix(&owner)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+    Ok(TicketsResponse { tickets })
+}
+
+fn query_verify_ticket(
+    deps: Deps,
+    ticket_id: u64,
+    owner: String,
+) -> StdResult<VerifyTicketResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let valid = TICKETS
+        .may_load(deps.storage, ticket_id)?
+        .is_some_and(|ticket_owner| ticket_owner == owner);
+    Ok(VerifyTicketResponse { valid })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coin, coins, BankMsg, CosmosMsg, SubMsg, Uint128, WasmMsg};
+    use cw20::Cw20ExecuteMsg;
+    use cw_utils::{Expiration, Scheduled};
+
+    use super::*;
+
+    fn mock_env_height(height: u64) -> Env {
+        let mut env = mock_env();
+        env.block.height = height;
+        env
+    }
+
+    fn instantiate_msg(price: Asset) -> InstantiateMsg {
+        InstantiateMsg {
+            price,
+            supply: 2,
+            sale_start: Scheduled::AtHeight(1000),
+            sale_end: Expiration::AtHeight(2000),
+            recipient: String::from("treasury"),
+        }
+    }
+
+    #[test]
+    fn test_instantiate() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+
+        let mut msg = instantiate_msg(Asset::native("ujuno", 100u128));
+        msg.supply = 0;
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidConfig {});
+
+        let msg = instantiate_msg(Asset::native("ujuno", 0u128));
+        let err = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidConfig {});
+
+        let msg = instantiate_msg(Asset::native("ujuno", 100u128));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let config = query_config(deps.as_ref()).unwrap();
+        assert_eq!(
+            config,
+            ConfigResponse {
+                price: Asset::native("ujuno", 100u128),
+                supply: 2,
+                sold: 0,
+                sale_start: Scheduled::AtHeight(1000),
+                sale_end: Expiration::AtHeight(2000),
+                recipient: String::from("treasury"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_buy_native() {
+        let mut deps = mock_dependencies();
+        let msg = instantiate_msg(Asset::native("ujuno", 100u128));
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // Only during the sale window
+        let info = mock_info("buyer", &coins(100, "ujuno"));
+        let err = execute(
+            deps.as_mut(),
+            mock_env_height(999),
+            info.clone(),
+            ExecuteMsg::Buy {},
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::SaleNotStarted {
+                start: Scheduled::AtHeight(1000)
+            }
+        );
+        let err = execute(
+            deps.as_mut(),
+            mock_env_height(2000),
+            info,
+            ExecuteMsg::Buy {},
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::SaleEnded {
+                end: Expiration::AtHeight(2000)
+            }
+        );
+
+        // Exactly the price must be paid
+        let cases = vec![
+            (vec![], ""),
+            (coins(99, "ujuno"), "99ujuno"),
+            (coins(100, "uatom"), "100uatom"),
+            (vec![coin(100, "ujuno"), coin(1, "uatom")], "100ujuno,1uatom"),
+        ];
+        for (funds, received) in cases {
+            let info = mock_info("buyer", &funds);
+            let err = execute(
+                deps.as_mut(),
+                mock_env_height(1500),
+                info,
+                ExecuteMsg::Buy {},
+            )
+            .unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::PaymentMismatch {
+                    expected: String::from("100ujuno"),
+                    received: String::from(received),
+                }
+            );
+        }
+
+        let info = mock_info("buyer", &coins(100, "ujuno"));
+        let res = execute(
+            deps.as_mut(),
+            mock_env_height(1500),
+            info.clone(),
+            ExecuteMsg::Buy {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: String::from("treasury"),
+                amount: coins(100, "ujuno"),
+            })]
+        );
+        assert_eq!(("ticket_id", "1"), res.attributes[1]);
+        execute(
+            deps.as_mut(),
+            mock_env_height(1500),
+            info.clone(),
+            ExecuteMsg::Buy {},
+        )
+        .unwrap();
+
+        // Supply is capped
+        let err = execute(
+            deps.as_mut(),
+            mock_env_height(1500),
+            info,
+            ExecuteMsg::Buy {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::SoldOut {});
+
+        let tickets = query_tickets(deps.as_ref(), String::from("buyer"), None, None).unwrap();
+        assert_eq!(tickets.tickets, vec![1, 2]);
+        assert_eq!(query_config(deps.as_ref()).unwrap().sold, 2);
+    }
+
+    #[test]
+    fn test_buy_cw20() {
+        let mut deps = mock_dependencies();
+        let msg = instantiate_msg(Asset::cw20(Addr::unchecked("token"), 100u128));
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // Paying with another token
+        let receive = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("buyer"),
+            amount: Uint128::new(100),
+            msg: to_binary(&ReceiveMsg::Buy {}).unwrap(),
+        });
+        let info = mock_info("other", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env_height(1500),
+            info,
+            receive.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::PaymentMismatch {
+                expected: String::from("100token"),
+                received: String::from("100other"),
+            }
+        );
+
+        let info = mock_info("token", &[]);
+        let res = execute(deps.as_mut(), mock_env_height(1500), info, receive).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: String::from("token"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("treasury"),
+                    amount: Uint128::new(100),
+                })
+                .unwrap(),
+                funds: vec![],
+            }))]
+        );
+        let ticket = query_ticket(deps.as_ref(), 1).unwrap();
+        assert_eq!(ticket.owner, "buyer");
+    }
+
+    #[test]
+    fn test_transfer_and_verify() {
+        let mut deps = mock_dependencies();
+        let msg = instantiate_msg(Asset::native("ujuno", 100u128));
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let info = mock_info("buyer", &coins(100, "ujuno"));
+        execute(deps.as_mut(), mock_env_height(1500), info, ExecuteMsg::Buy {}).unwrap();
+
+        let verify = |deps: Deps, ticket_id: u64, owner: &str| {
+            query_verify_ticket(deps, ticket_id, String::from(owner))
+                .unwrap()
+                .valid
+        };
+        assert!(verify(deps.as_ref(), 1, "buyer"));
+        assert!(!verify(deps.as_ref(), 1, "friend"));
+        assert!(!verify(deps.as_ref(), 2, "buyer"));
+
+        let transfer = ExecuteMsg::Transfer {
+            ticket_id: 1,
+            recipient: String::from("friend"),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("friend", &[]),
+            transfer.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // Tickets can be transferred also after the sale
+        execute(
+            deps.as_mut(),
+            mock_env_height(3000),
+            mock_info("buyer", &[]),
+            transfer,
+        )
+        .unwrap();
+        assert!(!verify(deps.as_ref(), 1, "buyer"));
+        assert!(verify(deps.as_ref(), 1, "friend"));
+
+        let tickets = query_tickets(deps.as_ref(), String::from("buyer"), None, None).unwrap();
+        assert!(tickets.tickets.is_empty());
+        let tickets = query_tickets(deps.as_ref(), String::from("friend"), None, None).unwrap();
+        assert_eq!(tickets.tickets, vec![1]);
+    }
+}
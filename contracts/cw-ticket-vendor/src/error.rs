@@ -0,0 +1,27 @@
+use cosmwasm_std::StdError;
+use cw_utils::{Expiration, Scheduled};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Supply and price must be greater than zero")]
+    InvalidConfig {},
+
+    #[error("Sale begins at {start}")]
+    SaleNotStarted { start: Scheduled },
+
+    #[error("Sale ended at {end}")]
+    SaleEnded { end: Expiration },
+
+    #[error("All the tickets have been sold")]
+    SoldOut {},
+
+    #[error("Payment {received} does not match the ticket price {expected}")]
+    PaymentMismatch { expected: String, received: String },
+}
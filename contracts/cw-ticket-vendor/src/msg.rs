@@ -0,0 +1,80 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cw20::Cw20ReceiveMsg;
+use cw_asset_lite::Asset;
+use cw_utils::{Expiration, Scheduled};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    /// Price of a ticket, in native or cw20 tokens.
+    pub price: Asset,
+    /// Maximum number of tickets sold.
+    pub supply: u64,
+    pub sale_start: Scheduled,
+    pub sale_end: Expiration,
+    /// Address receiving the payments.
+    pub recipient: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Buy a ticket paying the native price attached to the message.
+    Buy {},
+    /// Transfer a ticket owned by the sender.
+    Transfer { ticket_id: u64, recipient: String },
+    /// This accepts a properly-encoded ReceiveMsg from a cw20 contract
+    Receive(Cw20ReceiveMsg),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    /// Buy a ticket paying the cw20 price.
+    Buy {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Returns ConfigResponse.
+    Config {},
+    /// Owner of a ticket. Returns TicketResponse.
+    Ticket { ticket_id: u64 },
+    /// Tickets of an address. Returns TicketsResponse.
+    Tickets {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Checks that a ticket exists and is owned by an address, used by the games
+    /// accepting the tickets. Returns VerifyTicketResponse.
+    VerifyTicket { ticket_id: u64, owner: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ConfigResponse {
+    pub price: Asset,
+    pub supply: u64,
+    pub sold: u64,
+    pub sale_start: Scheduled,
+    pub sale_end: Expiration,
+    pub recipient: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TicketResponse {
+    pub ticket_id: u64,
+    pub owner: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TicketsResponse {
+    pub tickets: Vec<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct VerifyTicketResponse {
+    pub valid: bool,
+}
@@ -0,0 +1,31 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, Empty};
+use cw_asset_lite::Asset;
+use cw_storage_plus::{Item, Map};
+use cw_utils::{Expiration, Scheduled};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// Price of a ticket, in native or cw20 tokens.
+    pub price: Asset,
+    /// Maximum number of tickets sold.
+    pub supply: u64,
+    /// Tickets can be bought from `sale_start` until `sale_end`.
+    pub sale_start: Scheduled,
+    pub sale_end: Expiration,
+    /// Address receiving the payments.
+    pub recipient: Addr,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// Number of tickets sold, ids go from 1 to `SOLD`.
+pub const SOLD: Item<u64> = Item::new("sold");
+
+/// Owner of each ticket.
+pub const TICKETS: Map<u64, Addr> = Map::new("tickets");
+
+/// Tickets indexed by owner, used to list the tickets of an address.
+pub const OWNER_TICKETS: Map<(&Addr, u64), Empty> = Map::new("owner_tickets");
@@ -275,11 +275,11 @@ mod tests {
     }
 
     fn real_hash() -> String {
-        hex::encode(&Sha256::digest(&hex::decode(preimage()).unwrap()))
+        hex::encode(Sha256::digest(&hex::decode(preimage()).unwrap()))
     }
 
     fn custom_hash(int: u16) -> String {
-        hex::encode(&Sha256::digest(&hex::decode(custom_preimage(int)).unwrap()))
+        hex::encode(Sha256::digest(&hex::decode(custom_preimage(int)).unwrap()))
     }
 
     fn mock_env_height(height: u64) -> Env {
@@ -510,7 +510,7 @@ mod tests {
             id: "swap0001".to_string(),
         };
         let err = execute(deps.as_mut(), mock_env(), info, refund).unwrap_err();
-        assert!(matches!(err, ContractError::NotExpired { .. }));
+        assert!(matches!(err, ContractError::NotExpired));
 
         // Anyone can refund, if already expired
         let env = mock_env_height(123457);
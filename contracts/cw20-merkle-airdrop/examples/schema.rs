@@ -4,7 +4,7 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 use cw20_merkle_airdrop::msg::{
     ConfigResponse, ExecuteMsg, InstantiateMsg, IsClaimedResponse, LatestStageResponse,
-    MerkleRootResponse, QueryMsg,
+    MerkleRootResponse, QueryMsg, StageStatusResponse,
 };
 
 fn main() {
@@ -20,4 +20,5 @@ fn main() {
     export_schema(&schema_for!(MerkleRootResponse), &out_dir);
     export_schema(&schema_for!(IsClaimedResponse), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(StageStatusResponse), &out_dir);
 }
@@ -1,8 +1,8 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128,
-    WasmMsg,
+    attr, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage,
+    Uint128, WasmMsg,
 };
 use cw2::{get_contract_version, set_contract_version};
 use cw20::Cw20ExecuteMsg;
@@ -13,11 +13,12 @@ use std::convert::TryInto;
 use crate::error::ContractError;
 use crate::msg::{
     ConfigResponse, ExecuteMsg, InstantiateMsg, IsClaimedResponse, LatestStageResponse,
-    MerkleRootResponse, MigrateMsg, QueryMsg, TotalClaimedResponse,
+    MerkleRootResponse, MigrateMsg, QueryMsg, StageStatus, StageStatusResponse,
+    TotalClaimedResponse,
 };
 use crate::state::{
     Config, CLAIM, CONFIG, LATEST_STAGE, MERKLE_ROOT, STAGE_AMOUNT, STAGE_AMOUNT_CLAIMED,
-    STAGE_EXPIRATION, STAGE_START,
+    STAGE_CARRIED_IN, STAGE_CARRIED_OUT, STAGE_EXPIRATION, STAGE_LEFTOVER, STAGE_START,
 };
 
 // Version info, for migration info
@@ -63,6 +64,7 @@ pub fn execute(
             expiration,
             start,
             total_amount,
+            carry_over,
         } => execute_register_merkle_root(
             deps,
             env,
@@ -71,6 +73,7 @@ pub fn execute(
             expiration,
             start,
             total_amount,
+            carry_over,
         ),
         ExecuteMsg::Claim {
             stage,
@@ -81,6 +84,11 @@ pub fn execute(
         ExecuteMsg::Withdraw { stage, address } => {
             execute_withdraw(deps, env, info, stage, address)
         }
+        ExecuteMsg::ExpireStage { stage } => execute_expire_stage(deps, env, info, stage),
+        ExecuteMsg::Rollover { stage, to_stage } => {
+            execute_rollover(deps, env, info, stage, to_stage)
+        }
+        ExecuteMsg::CarryOver { stage } => execute_carry_over(deps, env, info, stage),
     }
 }
 
@@ -111,14 +119,16 @@ pub fn execute_update_config(
     Ok(Response::new().add_attribute("action", "update_config"))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_register_merkle_root(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     merkle_root: String,
     expiration: Option<Expiration>,
     start: Option<Scheduled>,
     total_amount: Option<Uint128>,
+    carry_over: Option<bool>,
 ) -> Result<Response, ContractError> {
     let cfg = CONFIG.load(deps.storage)?;
 
@@ -132,7 +142,13 @@ pub fn execute_register_merkle_root(
     let mut root_buf: [u8; 32] = [0; 32];
     hex::decode_to_slice(&merkle_root, &mut root_buf)?;
 
-    let stage = LATEST_STAGE.update(deps.storage, |stage| -> StdResult<_> { Ok(stage + 1) })?;
+    let stage = LATEST_STAGE.load(deps.storage)? + 1;
+
+    // only a previous stage can be carried over
+    let carry_over = carry_over.unwrap_or(false);
+    if carry_over && stage == 1 {
+        return Err(ContractError::InvalidInput {});
+    }
 
     MERKLE_ROOT.save(deps.storage, stage, &merkle_root)?;
     LATEST_STAGE.save(deps.storage, &stage)?;
@@ -147,11 +163,29 @@ pub fn execute_register_merkle_root(
     }
 
     // save total airdropped amount
-    let amount = total_amount.unwrap_or_else(Uint128::zero);
+    let mut amount = total_amount.unwrap_or_else(Uint128::zero);
     STAGE_AMOUNT.save(deps.storage, stage, &amount)?;
     STAGE_AMOUNT_CLAIMED.save(deps.storage, stage, &Uint128::zero())?;
 
-    Ok(Response::new().add_attributes(vec![
+    let mut res = Response::new();
+    if carry_over {
+        // the previous stage closes at this block, its allocations not claimed are
+        // topped up in the new tree
+        let previous = stage - 1;
+        let expiration = STAGE_EXPIRATION.load(deps.storage, previous)?;
+        if !expiration.is_expired(&env.block) {
+            let expiration = Expiration::AtHeight(env.block.height);
+            STAGE_EXPIRATION.save(deps.storage, previous, &expiration)?;
+        }
+
+        let carried = stage_leftover(deps.storage, previous)?;
+        STAGE_LEFTOVER.save(deps.storage, previous, &Uint128::zero())?;
+        carry(deps.storage, previous, stage, carried)?;
+        amount += carried;
+        res = res.add_attribute("carried_over", carried);
+    }
+
+    Ok(res.add_attributes(vec![
         attr("action", "register_merkle_root"),
         attr("stage", stage.to_string()),
         attr("merkle_root", merkle_root),
@@ -257,17 +291,8 @@ pub fn execute_burn(
         return Err(ContractError::StageNotExpired { stage, expiration });
     }
 
-    // Get total amount per stage and total claimed
-    let total_amount = STAGE_AMOUNT.load(deps.storage, stage)?;
-    let claimed_amount = STAGE_AMOUNT_CLAIMED.load(deps.storage, stage)?;
-
-    // impossible but who knows
-    if claimed_amount > total_amount {
-        return Err(ContractError::Unauthorized {});
-    }
-
-    // Get balance
-    let balance_to_burn = total_amount - claimed_amount;
+    // Get balance, only burnt once
+    let balance_to_burn = take_leftover(deps.storage, stage)?;
 
     // Burn the tokens and response
     let res = Response::new()
@@ -307,17 +332,8 @@ pub fn execute_withdraw(
         return Err(ContractError::StageNotExpired { stage, expiration });
     }
 
-    // Get total amount per stage and total claimed
-    let total_amount = STAGE_AMOUNT.load(deps.storage, stage)?;
-    let claimed_amount = STAGE_AMOUNT_CLAIMED.load(deps.storage, stage)?;
-
-    // impossible but who knows
-    if claimed_amount > total_amount {
-        return Err(ContractError::Unauthorized {});
-    }
-
-    // Get balance
-    let balance_to_withdraw = total_amount - claimed_amount;
+    // Get balance, only withdrawn once
+    let balance_to_withdraw = take_leftover(deps.storage, stage)?;
 
     // Validate address
     let recipient = deps.api.addr_validate(&address)?;
@@ -342,8 +358,155 @@ pub fn execute_withdraw(
     Ok(res)
 }
 
+pub fn execute_expire_stage(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u8,
+) -> Result<Response, ContractError> {
+    // authorize owner
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if STAGE_LEFTOVER.has(deps.storage, stage) {
+        return Err(ContractError::StageClosed { stage });
+    }
+
+    // claims stop at this block, unless the stage already expired
+    let expiration = STAGE_EXPIRATION.load(deps.storage, stage)?;
+    if !expiration.is_expired(&env.block) {
+        let expiration = Expiration::AtHeight(env.block.height);
+        STAGE_EXPIRATION.save(deps.storage, stage, &expiration)?;
+    }
+
+    let leftover = stage_leftover(deps.storage, stage)?;
+    STAGE_LEFTOVER.save(deps.storage, stage, &leftover)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "expire_stage"),
+        attr("stage", stage.to_string()),
+        attr("leftover", leftover),
+    ]))
+}
+
+pub fn execute_rollover(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u8,
+    to_stage: u8,
+) -> Result<Response, ContractError> {
+    // authorize owner
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let amount = roll_over(deps.storage, &env, stage, to_stage)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "rollover"),
+        attr("stage", stage.to_string()),
+        attr("to_stage", to_stage.to_string()),
+        attr("amount", amount),
+    ]))
+}
+
+pub fn execute_carry_over(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u8,
+) -> Result<Response, ContractError> {
+    // authorize owner
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let to_stage = stage.checked_add(1).ok_or(ContractError::InvalidInput {})?;
+    let amount = roll_over(deps.storage, &env, stage, to_stage)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "carry_over"),
+        attr("stage", stage.to_string()),
+        attr("to_stage", to_stage.to_string()),
+        attr("amount", amount),
+    ]))
+}
+
+/// Moves the leftover of an expired stage to the total amount of a stage not expired.
+fn roll_over(
+    storage: &mut dyn Storage,
+    env: &Env,
+    stage: u8,
+    to_stage: u8,
+) -> Result<Uint128, ContractError> {
+    // make sure the source is expired and the target still open to claims
+    let expiration = STAGE_EXPIRATION.load(storage, stage)?;
+    if !expiration.is_expired(&env.block) {
+        return Err(ContractError::StageNotExpired { stage, expiration });
+    }
+    let to_expiration = STAGE_EXPIRATION.load(storage, to_stage)?;
+    if to_stage == stage || to_expiration.is_expired(&env.block) {
+        return Err(ContractError::StageExpired {
+            stage: to_stage,
+            expiration: to_expiration,
+        });
+    }
+
+    let amount = take_leftover(storage, stage)?;
+    carry(storage, stage, to_stage, amount)?;
+    Ok(amount)
+}
+
+/// Adds `amount` from the leftover of `stage` to the total amount of `to_stage`, and
+/// records it on both sides.
+fn carry(storage: &mut dyn Storage, stage: u8, to_stage: u8, amount: Uint128) -> StdResult<()> {
+    let add = |total: Option<Uint128>| -> StdResult<_> {
+        Ok(total.unwrap_or_default().checked_add(amount)?)
+    };
+    STAGE_AMOUNT.update(storage, to_stage, add)?;
+    STAGE_CARRIED_IN.update(storage, to_stage, add)?;
+    STAGE_CARRIED_OUT.update(storage, stage, add)?;
+    Ok(())
+}
+
+/// Amount of an expired stage not burnt, withdrawn nor rolled over yet: what was not
+/// claimed until the stage closed.
+fn stage_leftover(storage: &dyn Storage, stage: u8) -> Result<Uint128, ContractError> {
+    if let Some(leftover) = STAGE_LEFTOVER.may_load(storage, stage)? {
+        return Ok(leftover);
+    }
+
+    // Get total amount per stage and total claimed
+    let total_amount = STAGE_AMOUNT.load(storage, stage)?;
+    let claimed_amount = STAGE_AMOUNT_CLAIMED.load(storage, stage)?;
+
+    // impossible but who knows
+    if claimed_amount > total_amount {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(total_amount - claimed_amount)
+}
+
+/// Empties the leftover of an expired stage, which is then paid out once.
+fn take_leftover(storage: &mut dyn Storage, stage: u8) -> Result<Uint128, ContractError> {
+    let leftover = stage_leftover(storage, stage)?;
+    if leftover.is_zero() {
+        return Err(ContractError::NothingLeft { stage });
+    }
+    STAGE_LEFTOVER.save(storage, stage, &Uint128::zero())?;
+    Ok(leftover)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::MerkleRoot { stage } => to_binary(&query_merkle_root(deps, stage)?),
@@ -352,6 +515,7 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_binary(&query_is_claimed(deps, stage, address)?)
         }
         QueryMsg::TotalClaimed { stage } => to_binary(&query_total_claimed(deps, stage)?),
+        QueryMsg::StageStatus { stage } => to_binary(&query_stage_status(deps, env, stage)?),
     }
 }
 
@@ -402,6 +566,34 @@ pub fn query_total_claimed(deps: Deps, stage: u8) -> StdResult<TotalClaimedRespo
     Ok(resp)
 }
 
+pub fn query_stage_status(deps: Deps, env: Env, stage: u8) -> StdResult<StageStatusResponse> {
+    let total_amount = STAGE_AMOUNT.load(deps.storage, stage)?;
+    let claimed_amount = STAGE_AMOUNT_CLAIMED.load(deps.storage, stage)?;
+    let expiration = STAGE_EXPIRATION.load(deps.storage, stage)?;
+    let start = STAGE_START.may_load(deps.storage, stage)?;
+
+    let (status, leftover) = if expiration.is_expired(&env.block) {
+        let leftover = STAGE_LEFTOVER
+            .may_load(deps.storage, stage)?
+            .unwrap_or_else(|| total_amount.saturating_sub(claimed_amount));
+        (StageStatus::Expired, Some(leftover))
+    } else if start.is_some_and(|start| !start.is_triggered(&env.block)) {
+        (StageStatus::NotStarted, None)
+    } else {
+        (StageStatus::Open, None)
+    };
+
+    Ok(StageStatusResponse {
+        stage,
+        status,
+        total_amount,
+        claimed_amount,
+        leftover,
+        carried_in: STAGE_CARRIED_IN.may_load(deps.storage, stage)?.unwrap_or_default(),
+        carried_out: STAGE_CARRIED_OUT.may_load(deps.storage, stage)?.unwrap_or_default(),
+    })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
     let version = get_contract_version(deps.storage)?;
@@ -505,6 +697,7 @@ mod tests {
             expiration: None,
             start: None,
             total_amount: None,
+            carry_over: None,
         };
 
         let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
@@ -573,6 +766,7 @@ mod tests {
             expiration: None,
             start: None,
             total_amount: None,
+            carry_over: None,
         };
         let _res = execute(deps.as_mut(), env, info, msg).unwrap();
 
@@ -653,6 +847,7 @@ mod tests {
             expiration: None,
             start: None,
             total_amount: None,
+            carry_over: None,
         };
         let _res = execute(deps.as_mut(), env, info, msg).unwrap();
 
@@ -737,6 +932,7 @@ mod tests {
             expiration: None,
             start: None,
             total_amount: None,
+            carry_over: None,
         };
         let _res = execute(deps.as_mut(), env, info, msg).unwrap();
 
@@ -808,6 +1004,7 @@ mod tests {
             expiration: Some(Expiration::AtHeight(100)),
             start: None,
             total_amount: None,
+            carry_over: None,
         };
         execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
@@ -850,6 +1047,7 @@ mod tests {
             expiration: Some(Expiration::AtHeight(12346)),
             start: None,
             total_amount: Some(Uint128::new(100000)),
+            carry_over: None,
         };
         execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
@@ -886,6 +1084,7 @@ mod tests {
             expiration: Some(Expiration::AtHeight(12500)),
             start: None,
             total_amount: Some(Uint128::new(10000)),
+            carry_over: None,
         };
         execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -971,6 +1170,7 @@ mod tests {
             expiration: Some(Expiration::AtHeight(12346)),
             start: None,
             total_amount: Some(Uint128::new(100000)),
+            carry_over: None,
         };
         execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
@@ -1010,6 +1210,7 @@ mod tests {
             expiration: Some(Expiration::AtHeight(12500)),
             start: None,
             total_amount: Some(Uint128::new(10000)),
+            carry_over: None,
         };
         execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
@@ -1053,7 +1254,7 @@ mod tests {
         };
 
         let info = mock_info("owner0000", &[]);
-        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
 
         let expected = SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: "token0000".to_string(),
@@ -1076,6 +1277,226 @@ mod tests {
                 attr("recipient", "addr0005")
             ]
         );
+
+        // Can't withdraw twice
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(res, ContractError::NothingLeft { stage: 1 });
+        let msg = ExecuteMsg::Burn { stage: 1u8 };
+        let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert_eq!(res, ContractError::NothingLeft { stage: 1 });
+    }
+
+    #[test]
+    fn expire_stage() {
+        let mut deps = mock_dependencies();
+        let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "token0000".to_string(),
+        };
+
+        let env = mock_env();
+        let info = mock_info("addr0000", &[]);
+        let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // two stages open to claims
+        let info = mock_info("owner0000", &[]);
+        for total_amount in [10000, 500] {
+            let msg = ExecuteMsg::RegisterMerkleRoot {
+                merkle_root: test_data.root.clone(),
+                expiration: Some(Expiration::AtHeight(12500)),
+                start: None,
+                total_amount: Some(Uint128::new(total_amount)),
+                carry_over: None,
+            };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        let claim = |stage| ExecuteMsg::Claim {
+            amount: test_data.amount,
+            stage,
+            proof: test_data.proofs.clone(),
+        };
+        let claimer = mock_info(test_data.account.as_str(), &[]);
+        execute(deps.as_mut(), env.clone(), claimer.clone(), claim(1)).unwrap();
+
+        let status = query_stage_status(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(
+            status,
+            StageStatusResponse {
+                stage: 1,
+                status: StageStatus::Open,
+                total_amount: Uint128::new(10000),
+                claimed_amount: test_data.amount,
+                leftover: None,
+                carried_in: Uint128::zero(),
+                carried_out: Uint128::zero(),
+            }
+        );
+
+        // Only the owner closes a stage
+        let msg = ExecuteMsg::ExpireStage { stage: 1 };
+        let res = execute(deps.as_mut(), env.clone(), claimer.clone(), msg.clone()).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "expire_stage"),
+                attr("stage", "1"),
+                attr("leftover", Uint128::new(9900)),
+            ]
+        );
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(res, ContractError::StageClosed { stage: 1 });
+
+        // The other stage stays open
+        let res = execute(deps.as_mut(), env.clone(), claimer.clone(), claim(1)).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::StageExpired {
+                stage: 1,
+                expiration: Expiration::AtHeight(env.block.height)
+            }
+        );
+        let status = query_stage_status(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(status.status, StageStatus::Expired);
+        assert_eq!(status.leftover, Some(Uint128::new(9900)));
+        let status = query_stage_status(deps.as_ref(), env.clone(), 2).unwrap();
+        assert_eq!(status.status, StageStatus::Open);
+
+        // Can't roll over to an expired stage
+        let msg = ExecuteMsg::Rollover {
+            stage: 1,
+            to_stage: 1,
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::StageExpired {
+                stage: 1,
+                expiration: Expiration::AtHeight(env.block.height)
+            }
+        );
+
+        // The leftover goes to the second stage once
+        let msg = ExecuteMsg::Rollover {
+            stage: 1,
+            to_stage: 2,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(res, ContractError::NothingLeft { stage: 1 });
+        let msg = ExecuteMsg::Burn { stage: 1 };
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert_eq!(res, ContractError::NothingLeft { stage: 1 });
+
+        let status = query_stage_status(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(status.leftover, Some(Uint128::zero()));
+        let status = query_stage_status(deps.as_ref(), env.clone(), 2).unwrap();
+        assert_eq!(status.total_amount, Uint128::new(10400));
+        execute(deps.as_mut(), env, claimer, claim(2)).unwrap();
+    }
+
+    #[test]
+    fn carry_over() {
+        let mut deps = mock_dependencies();
+        let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "token0000".to_string(),
+        };
+
+        let env = mock_env();
+        let info = mock_info("addr0000", &[]);
+        let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let register = |total_amount: u128, carry_over| ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: test_data.root.clone(),
+            expiration: Some(Expiration::AtHeight(12500)),
+            start: None,
+            total_amount: Some(Uint128::new(total_amount)),
+            carry_over: Some(carry_over),
+        };
+        let info = mock_info("owner0000", &[]);
+
+        // The first stage has nothing to carry over
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), register(10000, true));
+        assert_eq!(res.unwrap_err(), ContractError::InvalidInput {});
+        execute(deps.as_mut(), env.clone(), info.clone(), register(10000, false)).unwrap();
+
+        let claim = |stage| ExecuteMsg::Claim {
+            amount: test_data.amount,
+            stage,
+            proof: test_data.proofs.clone(),
+        };
+        let claimer = mock_info(test_data.account.as_str(), &[]);
+        execute(deps.as_mut(), env.clone(), claimer.clone(), claim(1)).unwrap();
+
+        // Registering the second stage closes the first one and tops up its total
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), register(500, true)).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("carried_over", Uint128::new(9900)),
+                attr("action", "register_merkle_root"),
+                attr("stage", "2"),
+                attr("merkle_root", test_data.root.clone()),
+                attr("total_amount", Uint128::new(10400)),
+            ]
+        );
+        let res = execute(deps.as_mut(), env.clone(), claimer.clone(), claim(1)).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::StageExpired {
+                stage: 1,
+                expiration: Expiration::AtHeight(env.block.height)
+            }
+        );
+        let msg = ExecuteMsg::Burn { stage: 1 };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(res, ContractError::NothingLeft { stage: 1 });
+
+        let status = query_stage_status(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(status.leftover, Some(Uint128::zero()));
+        assert_eq!(status.carried_out, Uint128::new(9900));
+        let status = query_stage_status(deps.as_ref(), env.clone(), 2).unwrap();
+        assert_eq!(status.total_amount, Uint128::new(10400));
+        assert_eq!(status.carried_in, Uint128::new(9900));
+
+        // The leftover of an expired stage is carried over explicitly to the next one
+        execute(deps.as_mut(), env.clone(), claimer.clone(), claim(2)).unwrap();
+        execute(deps.as_mut(), env.clone(), info.clone(), register(1000, false)).unwrap();
+        let msg = ExecuteMsg::CarryOver { stage: 2 };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::StageNotExpired {
+                stage: 2,
+                expiration: Expiration::AtHeight(12500)
+            }
+        );
+        let expire = ExecuteMsg::ExpireStage { stage: 2 };
+        execute(deps.as_mut(), env.clone(), info.clone(), expire).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "carry_over"),
+                attr("stage", "2"),
+                attr("to_stage", "3"),
+                attr("amount", Uint128::new(10300)),
+            ]
+        );
+
+        let status = query_stage_status(deps.as_ref(), env.clone(), 2).unwrap();
+        assert_eq!(status.carried_in, Uint128::new(9900));
+        assert_eq!(status.carried_out, Uint128::new(10300));
+        let status = query_stage_status(deps.as_ref(), env, 3).unwrap();
+        assert_eq!(status.total_amount, Uint128::new(11300));
+        assert_eq!(status.carried_in, Uint128::new(10300));
     }
 
     #[test]
@@ -1100,6 +1521,7 @@ mod tests {
             expiration: None,
             start: Some(Scheduled::AtHeight(200_000)),
             total_amount: None,
+            carry_over: None,
         };
         execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
@@ -1142,6 +1564,7 @@ mod tests {
             expiration: None,
             start: None,
             total_amount: None,
+            carry_over: None,
         };
         let _res = execute(deps.as_mut(), env, info, msg).unwrap();
 
@@ -1172,6 +1595,7 @@ mod tests {
             expiration: None,
             start: None,
             total_amount: None,
+            carry_over: None,
         };
         let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
         assert_eq!(res, ContractError::Unauthorized {});
@@ -1185,6 +1609,7 @@ mod tests {
             expiration: None,
             start: None,
             total_amount: None,
+            carry_over: None,
         };
         let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
         assert_eq!(res, ContractError::Unauthorized {});
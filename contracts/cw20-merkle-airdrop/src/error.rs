@@ -37,4 +37,10 @@ pub enum ContractError {
 
     #[error("Airdrop stage {stage} begins at {start}")]
     StageNotBegun { stage: u8, start: Scheduled },
+
+    #[error("Airdrop stage {stage} is already closed")]
+    StageClosed { stage: u8 },
+
+    #[error("Nothing left in airdrop stage {stage}")]
+    NothingLeft { stage: u8 },
 }
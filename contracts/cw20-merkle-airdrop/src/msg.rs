@@ -25,6 +25,9 @@ pub enum ExecuteMsg {
         expiration: Option<Expiration>,
         start: Option<Scheduled>,
         total_amount: Option<Uint128>,
+        /// Closes the previous stage and adds what it did not distribute to the total
+        /// amount, for a tree topping up the allocations not claimed.
+        carry_over: Option<bool>,
     },
     /// Claim does not check if contract has enough funds, owner must ensure it.
     Claim {
@@ -37,6 +40,14 @@ pub enum ExecuteMsg {
     Burn { stage: u8 },
     /// Withdraw the remaining tokens after expire time (only owner)
     Withdraw { stage: u8, address: String },
+    /// Close the claims of a stage now and set its remaining tokens aside (only owner)
+    ExpireStage { stage: u8 },
+    /// Add the remaining tokens of an expired stage to the total amount of another
+    /// stage, not expired (only owner)
+    Rollover { stage: u8, to_stage: u8 },
+    /// Add the remaining tokens of an expired stage to the total amount of the next
+    /// stage, not expired (only owner)
+    CarryOver { stage: u8 },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -47,6 +58,7 @@ pub enum QueryMsg {
     LatestStage {},
     IsClaimed { stage: u8, address: String },
     TotalClaimed { stage: u8 },
+    StageStatus { stage: u8 },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -81,5 +93,27 @@ pub struct TotalClaimedResponse {
     pub total_claimed: Uint128,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StageStatus {
+    NotStarted,
+    Open,
+    Expired,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StageStatusResponse {
+    pub stage: u8,
+    pub status: StageStatus,
+    pub total_amount: Uint128,
+    pub claimed_amount: Uint128,
+    /// Remaining tokens to burn, withdraw or roll over, once the stage expired.
+    pub leftover: Option<Uint128>,
+    /// Remaining tokens of other stages added to the total amount.
+    pub carried_in: Uint128,
+    /// Remaining tokens added to the total amount of other stages.
+    pub carried_out: Uint128,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MigrateMsg {}
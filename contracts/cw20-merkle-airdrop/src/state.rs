@@ -30,6 +30,19 @@ pub const STAGE_AMOUNT: Map<u8, Uint128> = Map::new(STAGE_AMOUNT_KEY);
 pub const STAGE_AMOUNT_CLAIMED_KEY: &str = "stage_claimed_amount";
 pub const STAGE_AMOUNT_CLAIMED: Map<u8, Uint128> = Map::new(STAGE_AMOUNT_CLAIMED_KEY);
 
+/// Amount a closed stage can still burn, withdraw or roll over: what was not claimed
+/// when it closed, then zero once swept.
+pub const STAGE_LEFTOVER_KEY: &str = "stage_leftover";
+pub const STAGE_LEFTOVER: Map<u8, Uint128> = Map::new(STAGE_LEFTOVER_KEY);
+
+/// Leftover carried over from other stages into each stage, included in its total amount.
+pub const STAGE_CARRIED_IN_KEY: &str = "stage_carried_in";
+pub const STAGE_CARRIED_IN: Map<u8, Uint128> = Map::new(STAGE_CARRIED_IN_KEY);
+
+/// Leftover of each stage carried over to other stages.
+pub const STAGE_CARRIED_OUT_KEY: &str = "stage_carried_out";
+pub const STAGE_CARRIED_OUT: Map<u8, Uint128> = Map::new(STAGE_CARRIED_OUT_KEY);
+
 pub const MERKLE_ROOT_PREFIX: &str = "merkle_root";
 pub const MERKLE_ROOT: Map<u8, String> = Map::new(MERKLE_ROOT_PREFIX);
 
@@ -0,0 +1,201 @@
+//! Generates all the messages needed to deploy a game from a spreadsheet of allocations.
+//!
+//! Usage:
+//!
+//! ```shell
+//! cargo run --example deploy_fixture -- allocations.csv params.json out_dir [contract_address]
+//! ```
+//!
+//! `allocations.csv` has an `address,amount,bin` header followed by one row per eligible
+//! address, where `bin` is the bin the address falls in. `params.json` is the
+//! `InstantiateMsg` of the game with an additional `total_amount_game` field holding the
+//! amount of tokens reserved to the winners. The sha256 checksum of `allocations.csv` is
+//! registered with the roots so that anyone can verify the published file. With the
+//! `contract_address` leaf format the leaves are bound to the game, whose address must be
+//! passed as the last argument, e.g. predicted with `instantiate2`. With `claim_index` the
+//! airdrop leaves carry the row of the address, starting from 0, and every proof file
+//! holds the `index` to claim with.
+use std::env::args;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::{Path, PathBuf};
+
+use cosmwasm_std::Uint128;
+use cw20_merkle_bidding_airdrop::merkle::{domain_leaf_hash, MerkleTree};
+use cw20_merkle_bidding_airdrop::msg::{ExecuteMsg, InstantiateMsg};
+use cw20_merkle_bidding_airdrop::state::{LeafFormat, TreeMetadata};
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+
+#[derive(Deserialize)]
+struct GameParams {
+    #[serde(flatten)]
+    instantiate_msg: InstantiateMsg,
+    total_amount_game: Uint128,
+}
+
+struct Allocation {
+    address: String,
+    amount: Uint128,
+    bin: u8,
+}
+
+#[derive(Serialize)]
+struct UserProofs {
+    account: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index: Option<u32>,
+    amount: Uint128,
+    bin: u8,
+    proof_airdrop: Vec<String>,
+    proof_game: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct FundingPlan {
+    /// cw20 contract address or native denom to send to the game contract.
+    asset: String,
+    total_amount_airdrop: Uint128,
+    total_amount_game: Uint128,
+    total_amount: Uint128,
+}
+
+fn parse_allocations(csv: &str, bins: u8) -> Vec<Allocation> {
+    csv.lines()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if fields.len() != 3 {
+                panic!("row {}: expected address,amount,bin", i + 1);
+            }
+            let bin: u8 = fields[2]
+                .parse()
+                .unwrap_or_else(|_| panic!("row {}: invalid bin", i + 1));
+            if bin > bins {
+                panic!("row {}: bin {} exceeds the {} bins of the game", i + 1, bin, bins);
+            }
+            Allocation {
+                address: fields[0].to_string(),
+                amount: Uint128::new(
+                    fields[1]
+                        .parse()
+                        .unwrap_or_else(|_| panic!("row {}: invalid amount", i + 1)),
+                ),
+                bin,
+            }
+        })
+        .collect()
+}
+
+/// Prefix of the preimage of the leaves, as computed by the contract from its config.
+fn leaf_domain(leaf_format: &Option<LeafFormat>, contract_address: Option<&String>) -> String {
+    match leaf_format {
+        None | Some(LeafFormat::Plain) => String::new(),
+        Some(LeafFormat::ContractAddress) => contract_address
+            .expect("the contract_address leaf format needs the address of the game")
+            .clone(),
+        Some(LeafFormat::CampaignId { campaign_id }) => campaign_id.clone(),
+    }
+}
+
+fn write_json<T: Serialize>(path: PathBuf, value: &T) {
+    write(&path, serde_json::to_string_pretty(value).unwrap())
+        .unwrap_or_else(|e| panic!("cannot write {}: {}", path.display(), e));
+}
+
+fn main() {
+    let args: Vec<String> = args().collect();
+    if args.len() != 4 && args.len() != 5 {
+        eprintln!("Usage: deploy_fixture <allocations.csv> <params.json> <out_dir> [contract_address]");
+        std::process::exit(1);
+    }
+
+    let params: GameParams = serde_json::from_str(&read_to_string(&args[2]).unwrap()).unwrap();
+    let csv = read_to_string(&args[1]).unwrap();
+    let allocations = parse_allocations(&csv, params.instantiate_msg.bins);
+    let domain = leaf_domain(&params.instantiate_msg.leaf_format, args.get(4));
+    let claim_index = params.instantiate_msg.claim_index.unwrap_or(false);
+
+    // Build both trees with the same hashing used by the contract on claim. Indexed
+    // airdrop leaves hash the index right after the domain.
+    let airdrop_domain = |index: usize| match claim_index {
+        true => format!("{}{}", domain, index),
+        false => domain.clone(),
+    };
+    let airdrop_tree = MerkleTree::new(
+        allocations
+            .iter()
+            .enumerate()
+            .map(|(i, a)| domain_leaf_hash(&airdrop_domain(i), &a.address, &a.amount.to_string()))
+            .collect(),
+    );
+    let game_tree = MerkleTree::new(
+        allocations
+            .iter()
+            .map(|a| domain_leaf_hash(&domain, &a.address, &a.bin.to_string()))
+            .collect(),
+    );
+
+    let out_dir = Path::new(&args[3]);
+    let proofs_dir = out_dir.join("proofs");
+    create_dir_all(&proofs_dir).unwrap();
+
+    let total_amount_airdrop: Uint128 = allocations.iter().map(|a| a.amount).sum();
+
+    write_json(out_dir.join("instantiate_msg.json"), &params.instantiate_msg);
+    write_json(
+        out_dir.join("register_merkle_roots_msg.json"),
+        &ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: airdrop_tree.root(),
+            total_amount_airdrop: Some(total_amount_airdrop),
+            merkle_root_game: game_tree.root(),
+            total_amount_game: Some(params.total_amount_game),
+            tree_metadata: TreeMetadata {
+                airdrop_leaf_count: allocations.len() as u64,
+                game_leaf_count: allocations.len() as u64,
+                allocation_checksum: hex::encode(sha2::Sha256::digest(csv.as_bytes())),
+            },
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        },
+    );
+    write_json(
+        out_dir.join("funding_plan.json"),
+        &FundingPlan {
+            asset: params
+                .instantiate_msg
+                .airdrop_native_denom
+                .clone()
+                .unwrap_or_else(|| params.instantiate_msg.cw20_token_address.clone()),
+            total_amount_airdrop,
+            total_amount_game: params.total_amount_game,
+            total_amount: total_amount_airdrop + params.total_amount_game,
+        },
+    );
+
+    for (i, a) in allocations.iter().enumerate() {
+        let proof_airdrop = airdrop_tree
+            .proof(&domain_leaf_hash(&airdrop_domain(i), &a.address, &a.amount.to_string()))
+            .unwrap();
+        let proof_game = game_tree
+            .proof(&domain_leaf_hash(&domain, &a.address, &a.bin.to_string()))
+            .unwrap();
+        write_json(
+            proofs_dir.join(format!("{}.json", a.address)),
+            &UserProofs {
+                account: a.address.clone(),
+                index: claim_index.then_some(i as u32),
+                amount: a.amount,
+                bin: a.bin,
+                proof_airdrop,
+                proof_game,
+            },
+        );
+    }
+
+    println!("merkle_root_airdrop: {}", airdrop_tree.root());
+    println!("merkle_root_game: {}", game_tree.root());
+    println!("{} proofs written to {}", allocations.len(), proofs_dir.display());
+}
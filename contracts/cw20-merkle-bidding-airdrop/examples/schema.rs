@@ -1,22 +1,114 @@
+use std::collections::BTreeMap;
 use std::env::current_dir;
-use std::fs::create_dir_all;
+use std::fs::{create_dir_all, write};
+use std::path::Path;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 use cw20_merkle_bidding_airdrop::msg::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg,
-    MerkleRootsResponse, QueryMsg, StagesResponse
+    AdminActionsResponse, AllBidsResponse, BidResponse, BinBoostResponse, BinDistributionResponse, BinPoolsResponse,
+    ClaimsFreezeResponse, ClaimsPageResponse, CohortStatsResponse, ConfigResponse, EligibleAmountResponse, ExclusionsResponse, ExecuteMsg, FailsafeResponse, GameAmountsResponse, InstantiateMsg, IsClaimedByIndexResponse, IsClaimedResponse, IsRoundClaimedResponse,
+    LatestRoundResponse, LeafHashResponse, LegacyBidResponse, LimitsResponse, MerkleRootInfo, PayoutReceiveMsg, PayoutSplitResponse, PendingActionsResponse,
+    MerkleRootsResponse, MigrateMsg, OperatorsResponse, OutcomeResponse, PrizePotResponse, QueryMsg, ReceiveMsg,
+    RegistryExecuteMsg, RoundResponse, StagesResponse, TopBoostersResponse, TreasuriesResponse,
+    WithdrawalProposalsResponse, VerifyTreeMetadataResponse,
 };
+use cw20_merkle_bidding_airdrop::state::GameMetadata;
+use cw_game_interface::{IsWinnerResponse, StageStatusResponse};
+use schemars::schema::RootSchema;
+use serde_json::json;
+
+/// Writes a schema as pretty printed JSON.
+fn write_schema(path: &Path, schema: &impl serde::Serialize) {
+    write(path, serde_json::to_string_pretty(schema).unwrap()).unwrap();
+    println!("Created {}", path.display());
+}
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
     out_dir.push("schema");
-    create_dir_all(&out_dir).unwrap();
+    let raw_dir = out_dir.join("raw");
+    create_dir_all(&raw_dir).unwrap();
     remove_schemas(&out_dir).unwrap();
+    remove_schemas(&raw_dir).unwrap();
+
+    // Response of every query, keyed by the snake_case name of the QueryMsg variant.
+    let responses: BTreeMap<&str, RootSchema> = vec![
+        ("config", schema_for!(ConfigResponse)),
+        ("metadata", schema_for!(GameMetadata)),
+        ("stages", schema_for!(StagesResponse)),
+        ("bid", schema_for!(BidResponse)),
+        ("is_winner", schema_for!(IsWinnerResponse)),
+        ("stage_status", schema_for!(StageStatusResponse)),
+        ("legacy_bid", schema_for!(LegacyBidResponse)),
+        ("is_claimed", schema_for!(IsClaimedResponse)),
+        ("is_claimed_by_index", schema_for!(IsClaimedByIndexResponse)),
+        ("eligible_amount", schema_for!(EligibleAmountResponse)),
+        ("verify_tree_metadata", schema_for!(VerifyTreeMetadataResponse)),
+        ("leaf_hash", schema_for!(LeafHashResponse)),
+        ("all_bids", schema_for!(AllBidsResponse)),
+        ("merkle_roots", schema_for!(MerkleRootsResponse)),
+        ("merkle_root", schema_for!(MerkleRootInfo)),
+        ("latest_round", schema_for!(LatestRoundResponse)),
+        ("round", schema_for!(RoundResponse)),
+        ("is_round_claimed", schema_for!(IsRoundClaimedResponse)),
+        ("claims_freeze", schema_for!(ClaimsFreezeResponse)),
+        ("game_amounts", schema_for!(GameAmountsResponse)),
+        ("prize_pot", schema_for!(PrizePotResponse)),
+        ("bin_pools", schema_for!(BinPoolsResponse)),
+        ("bin_distribution", schema_for!(BinDistributionResponse)),
+        ("bin_boost", schema_for!(BinBoostResponse)),
+        ("top_boosters", schema_for!(TopBoostersResponse)),
+        ("operators", schema_for!(OperatorsResponse)),
+        ("treasuries", schema_for!(TreasuriesResponse)),
+        ("withdrawal_proposals", schema_for!(WithdrawalProposalsResponse)),
+        ("admin_actions", schema_for!(AdminActionsResponse)),
+        ("claims_page", schema_for!(ClaimsPageResponse)),
+        ("outcome", schema_for!(OutcomeResponse)),
+        ("exclusions", schema_for!(ExclusionsResponse)),
+        ("cohort_stats", schema_for!(CohortStatsResponse)),
+        ("failsafe", schema_for!(FailsafeResponse)),
+        ("limits", schema_for!(LimitsResponse)),
+        ("pending_actions", schema_for!(PendingActionsResponse)),
+        ("payout_split", schema_for!(PayoutSplitResponse)),
+    ]
+    .into_iter()
+    .collect();
 
+    // One file per message and response type.
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
-    export_schema(&schema_for!(StagesResponse), &out_dir);
-    export_schema(&schema_for!(MerkleRootsResponse), &out_dir);
-    export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
+    // Hook message of the cw20 `Send` funding the game.
+    export_schema(&schema_for!(ReceiveMsg), &out_dir);
+    // Hook message of the cw20 `Send` delivering a payout to a contract.
+    export_schema(&schema_for!(PayoutReceiveMsg), &out_dir);
+    // Message announcing the game to the registry.
+    export_schema(&schema_for!(RegistryExecuteMsg), &out_dir);
+    for response in responses.values() {
+        export_schema(response, &out_dir);
+    }
+
+    // Raw schemas, as expected by @cosmwasm/ts-codegen.
+    write_schema(&raw_dir.join("instantiate.json"), &schema_for!(InstantiateMsg));
+    write_schema(&raw_dir.join("execute.json"), &schema_for!(ExecuteMsg));
+    write_schema(&raw_dir.join("query.json"), &schema_for!(QueryMsg));
+    write_schema(&raw_dir.join("migrate.json"), &schema_for!(MigrateMsg));
+    for (query, response) in &responses {
+        write_schema(&raw_dir.join(format!("response_to_{}.json", query)), response);
+    }
+
+    // Full contract API in a single file.
+    let api = json!({
+        "contract_name": env!("CARGO_PKG_NAME"),
+        "contract_version": env!("CARGO_PKG_VERSION"),
+        "idl_version": "1.0.0",
+        "instantiate": schema_for!(InstantiateMsg),
+        "execute": schema_for!(ExecuteMsg),
+        "query": schema_for!(QueryMsg),
+        "migrate": schema_for!(MigrateMsg),
+        "sudo": null,
+        "responses": responses,
+    });
+    write_schema(&out_dir.join(format!("{}.json", env!("CARGO_PKG_NAME"))), &api);
 }
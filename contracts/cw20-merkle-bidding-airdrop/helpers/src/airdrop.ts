@@ -1,32 +1,40 @@
 import sha256 from 'crypto-js/sha256'
 import { MerkleTree } from 'merkletreejs';
 
+// `index` is the sequential index of the leaves of the games in the claim index mode.
+type Account = { address: string; amount: string; index?: string };
+
 class Airdrop {
   private tree: MerkleTree;
+  private domain: string;
 
-  constructor(accounts: Array<{ address: string; amount: string }>) {
-    const leaves = accounts.map((a) => sha256(a.address + a.amount));
+  // `domain` is the contract address or the campaign id of the `leaf_format` of the
+  // game, empty for the plain format.
+  constructor(accounts: Array<Account>, domain: string = '') {
+    this.domain = domain;
+    const leaves = accounts.map((a) => this.leaf(a));
     this.tree = new MerkleTree(leaves, sha256, { sort: true });
   }
 
+  private leaf(account: Account) {
+    return sha256(this.domain + (account.index || '') + account.address + account.amount);
+  }
+
   public getMerkleRoot(): string {
     return this.tree.getHexRoot().replace('0x', '');
   }
 
-  public getMerkleProof(account: {
-    address: string;
-    amount: string;
-  }): string[] {
+  public getMerkleProof(account: Account): string[] {
     return this.tree
-      .getHexProof(sha256(account.address + account.amount).toString())
+      .getHexProof(this.leaf(account).toString())
       .map((v) => v.replace('0x', ''));
   }
 
   public verify(
     proof: string[],
-    account: { address: string; amount: string }
+    account: Account
   ): boolean {
-    let hashBuf = Buffer.from(sha256(account.address + account.amount).toString())
+    let hashBuf = Buffer.from(this.leaf(account).toString())
 
     proof.forEach((proofElem) => {
       const proofBuf = Buffer.from(proofElem, 'hex');
@@ -14,9 +14,11 @@ export default class GenerateProof extends Command {
 
   static flags = {
     help: flags.help({char: 'h'}),
+    domain: flags.string({char: 'd', description: 'leaf domain: contract address or campaign id', default: ''}),
     file: flags.string({char: 'f', description: 'airdrop file location'}),
     address: flags.string({char: 'a', description: 'address'}),
     amount: flags.string({char: 'b', description: 'amount'}),
+    index: flags.string({char: 'i', description: 'leaf index, in the claim index mode'}),
   }
 
   async run() {
@@ -39,10 +41,10 @@ export default class GenerateProof extends Command {
       this.error(e)
     }
 
-    let receivers: Array<{ address: string; amount: string }> = JSON.parse(file);
+    let receivers: Array<{ address: string; amount: string; index?: string }> = JSON.parse(file);
 
-    let airdrop = new Airdrop(receivers)
-    let proof = airdrop.getMerkleProof({address: flags.address, amount: flags.amount})
+    let airdrop = new Airdrop(receivers, flags.domain)
+    let proof = airdrop.getMerkleProof({address: flags.address, amount: flags.amount, index: flags.index})
     console.log(proof)
   }
 }
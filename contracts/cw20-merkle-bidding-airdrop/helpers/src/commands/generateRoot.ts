@@ -12,6 +12,7 @@ export default class GenerateRoot extends Command {
 
   static flags = {
     help: flags.help({char: 'h'}),
+    domain: flags.string({char: 'd', description: 'leaf domain: contract address or campaign id', default: ''}),
     file: flags.string({char: 'f', description: 'Airdrop file location'}),
   }
 
@@ -29,9 +30,9 @@ export default class GenerateRoot extends Command {
       this.error(e)
     }
 
-    let receivers: Array<{ address: string; amount: string }> = JSON.parse(file);
+    let receivers: Array<{ address: string; amount: string; index?: string }> = JSON.parse(file);
 
-    let airdrop = new Airdrop(receivers)
+    let airdrop = new Airdrop(receivers, flags.domain)
     console.log(airdrop.getMerkleRoot())
   }
 }
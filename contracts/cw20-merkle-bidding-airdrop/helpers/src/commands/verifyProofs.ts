@@ -16,10 +16,12 @@ export default class VerifyProof extends Command {
 
   static flags = {
     help: flags.help({char: 'h'}),
+    domain: flags.string({char: 'd', description: 'leaf domain: contract address or campaign id', default: ''}),
     file: flags.string({char: 'f', description: 'airdrop file location'}),
     proofs: flags.string({char: 'p', description: 'proofs in json format'}),
     address: flags.string({char: 'a', description: 'address'}),
     amount: flags.string({char: 'b', description: 'amount'}),
+    index: flags.string({char: 'i', description: 'leaf index, in the claim index mode'}),
   }
 
   async run() {
@@ -45,11 +47,11 @@ export default class VerifyProof extends Command {
       this.error(e)
     }
 
-    let receivers: Array<{ address: string; amount: string }> = JSON.parse(file);
+    let receivers: Array<{ address: string; amount: string; index?: string }> = JSON.parse(file);
 
-    let airdrop = new Airdrop(receivers)
+    let airdrop = new Airdrop(receivers, flags.domain)
     let proofs: string[] = JSON.parse(flags.proofs)
 
-    console.log(airdrop.verify(proofs, {address: flags.address, amount: flags.amount}))
+    console.log(airdrop.verify(proofs, {address: flags.address, amount: flags.amount, index: flags.index}))
   }
 }
@@ -0,0 +1,158 @@
+use cosmwasm_std::CanonicalAddr;
+use sha2::Digest;
+
+/// Canonical address of a secp256k1 public key, as derived by the Cosmos SDK:
+/// `ripemd160(sha256(compressed pubkey))`. Uncompressed keys are compressed first.
+/// `None` if the bytes are not a compressed (33 bytes) or uncompressed (65 bytes) key.
+pub fn pubkey_canonical_address(pubkey: &[u8]) -> Option<CanonicalAddr> {
+    let compressed = match pubkey {
+        [0x02 | 0x03, ..] if pubkey.len() == 33 => pubkey.to_vec(),
+        [0x04, point @ ..] if pubkey.len() == 65 => {
+            let (x, y) = point.split_at(32);
+            let mut key = vec![0x02 | (y[31] & 1)];
+            key.extend_from_slice(x);
+            key
+        }
+        _ => return None,
+    };
+    Some(ripemd160(&sha2::Sha256::digest(&compressed)).to_vec().into())
+}
+
+const WORDS_LEFT: [usize; 80] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, //
+    7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8, //
+    3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12, //
+    1, 9, 11, 10, 0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2, //
+    4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+const WORDS_RIGHT: [usize; 80] = [
+    5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12, //
+    6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12, 4, 9, 1, 2, //
+    15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13, //
+    8, 6, 4, 1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14, //
+    12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+const SHIFTS_LEFT: [u32; 80] = [
+    11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8, //
+    7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15, 9, 11, 7, 13, 12, //
+    11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5, //
+    11, 12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12, //
+    9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+const SHIFTS_RIGHT: [u32; 80] = [
+    8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6, //
+    9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12, 7, 6, 15, 13, 11, //
+    9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5, //
+    15, 5, 8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8, //
+    8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
+const CONSTANTS_LEFT: [u32; 5] = [0x0000_0000, 0x5a82_7999, 0x6ed9_eba1, 0x8f1b_bcdc, 0xa953_fd4e];
+const CONSTANTS_RIGHT: [u32; 5] = [0x50a2_8be6, 0x5c4d_d124, 0x6d70_3ef3, 0x7a6d_76e9, 0x0000_0000];
+
+/// Boolean function of the given round.
+fn round_function(round: usize, x: u32, y: u32, z: u32) -> u32 {
+    match round {
+        0 => x ^ y ^ z,
+        1 => (x & y) | (!x & z),
+        2 => (x | !y) ^ z,
+        3 => (x & z) | (y & !z),
+        _ => x ^ (y | !z),
+    }
+}
+
+/// RIPEMD-160 digest of `data`. Only used to derive addresses, so it is kept to the
+/// single-shot form instead of pulling a hashing crate in the contract.
+pub fn ripemd160(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476, 0xc3d2_e1f0];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64).wrapping_mul(8)).to_le_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut words = [0u32; 16];
+        for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+
+        let [mut al, mut bl, mut cl, mut dl, mut el] = state;
+        let [mut ar, mut br, mut cr, mut dr, mut er] = state;
+        for j in 0..80 {
+            let round = j / 16;
+            let t = al
+                .wrapping_add(round_function(round, bl, cl, dl))
+                .wrapping_add(words[WORDS_LEFT[j]])
+                .wrapping_add(CONSTANTS_LEFT[round])
+                .rotate_left(SHIFTS_LEFT[j])
+                .wrapping_add(el);
+            al = el;
+            el = dl;
+            dl = cl.rotate_left(10);
+            cl = bl;
+            bl = t;
+
+            let t = ar
+                .wrapping_add(round_function(4 - round, br, cr, dr))
+                .wrapping_add(words[WORDS_RIGHT[j]])
+                .wrapping_add(CONSTANTS_RIGHT[round])
+                .rotate_left(SHIFTS_RIGHT[j])
+                .wrapping_add(er);
+            ar = er;
+            er = dr;
+            dr = cr.rotate_left(10);
+            cr = br;
+            br = t;
+        }
+
+        let t = state[1].wrapping_add(cl).wrapping_add(dr);
+        state[1] = state[2].wrapping_add(dl).wrapping_add(er);
+        state[2] = state[3].wrapping_add(el).wrapping_add(ar);
+        state[3] = state[4].wrapping_add(al).wrapping_add(br);
+        state[4] = state[0].wrapping_add(bl).wrapping_add(cr);
+        state[0] = t;
+    }
+
+    let mut digest = [0u8; 20];
+    for (bytes, word) in digest.chunks_exact_mut(4).zip(state.iter()) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ripemd160_vectors() {
+        let vectors: [(&[u8], &str); 4] = [
+            (b"", "9c1185a5c5e9fc54612808977ee8f548b2258d31"),
+            (b"abc", "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"),
+            (
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "12a053384a9c0c88e405a06c27dcf49ada62eb2b",
+            ),
+            (&[b'a'; 1000], "aa69deee9a8922e92f8105e007f76110f381e9cf"),
+        ];
+        for (data, expected) in vectors {
+            assert_eq!(hex::encode(ripemd160(data)), expected);
+        }
+    }
+
+    #[test]
+    fn compressed_and_uncompressed_keys() {
+        // Generator point of secp256k1, i.e. the key of the secret 1.
+        let x = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+        let y = "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+        let compressed = hex::decode(format!("02{}", x)).unwrap();
+        let uncompressed = hex::decode(format!("04{}{}", x, y)).unwrap();
+
+        let address = pubkey_canonical_address(&compressed).unwrap();
+        assert_eq!(hex::encode(address.as_slice()), "751e76e8199196d454941c45d1b3a323f1433bd6");
+        assert_eq!(pubkey_canonical_address(&uncompressed), Some(address));
+        assert_eq!(pubkey_canonical_address(&compressed[1..]), None);
+    }
+}
@@ -0,0 +1,252 @@
+// ======================================================================================
+// Storage keys
+// ======================================================================================
+pub const CONFIG_KEY: &str = "config";
+pub const OPERATORS_PREFIX: &str = "operators";
+pub const GAME_METADATA_KEY: &str = "game_metadata";
+pub const GAME_PARAMS_KEY: &str = "game_params";
+pub const COUNTERS_KEY: &str = "counters";
+pub const PUBKEY_BIDS_PREFIX: &str = "pubkey_bids";
+pub const BID_KEYS_PREFIX: &str = "bid_keys";
+pub const BIDS_PREFIX: &str = "bids";
+pub const BID_RECEIPTS_PREFIX: &str = "bid_receipts";
+pub const BID_CHANGES_PREFIX: &str = "bid_changes";
+pub const BID_IMPORTS_PREFIX: &str = "bid_imports";
+pub const USED_TICKETS_PREFIX: &str = "used_tickets";
+pub const TICKET_BIDS_PREFIX: &str = "ticket_bids";
+pub const MERKLE_ROOT_AIRDROP_PREFIX: &str = "merkle_root_airdrop";
+pub const OUTCOME_COMMITMENT_KEY: &str = "outcome_commitment";
+pub const REVEALED_OUTCOME_KEY: &str = "revealed_outcome";
+pub const CLAIMS_FUNDED_KEY: &str = "claims_funded";
+pub const CLAIMS_FROZEN_KEY: &str = "claims_frozen";
+pub const PRIZE_VETO_KEY: &str = "prize_veto";
+pub const FAILSAFE_KEY: &str = "failsafe";
+pub const PAYOUT_RATE_KEY: &str = "payout_rate";
+pub const SECONDARY_PAYOUT_KEY: &str = "secondary_payout";
+pub const CLAIM_CONDITION_KEY: &str = "claim_condition";
+pub const PENDING_BADGE_KEY: &str = "pending_badge";
+pub const PAYOUT_SPLITS_PREFIX: &str = "payout_splits";
+pub const WITHHELD_AIRDROP_PREFIX: &str = "withheld_airdrop";
+pub const CLAIM_HISTORY_PREFIX: &str = "claim_history";
+pub const TREE_METADATA_KEY: &str = "tree_metadata";
+pub const MERKLE_ROOT_GAME_PREFIX: &str = "merkle_root_game";
+pub const BOOSTS_PREFIX: &str = "boosts";
+pub const BOOSTERS_BY_AMOUNT_PREFIX: &str = "boosters_by_amount";
+pub const BIN_BIDS_PREFIX: &str = "bin_bids";
+pub const BIN_BOOSTS_PREFIX: &str = "bin_boosts";
+pub const BIN_POOLS_PREFIX: &str = "bin_pools";
+pub const BIN_WINNERS_PREFIX: &str = "bin_winners";
+pub const BIN_CLAIMED_PRIZE_PREFIX: &str = "bin_claimed_prize";
+pub const DUST_PAID_PREFIX: &str = "dust_paid";
+pub const CLOSED_SNAPSHOT_KEY: &str = "closed_snapshot";
+pub const CACHED_PROOFS_PREFIX: &str = "cached_proofs";
+pub const CLAIM_AIRDROP_PREFIX: &str = "claim_airdrop";
+pub const CLAIMED_BITMAP_PREFIX: &str = "claimed_bitmap";
+pub const EXCLUDED_PREFIX: &str = "excluded";
+pub const COHORTS_PREFIX: &str = "cohorts";
+pub const COHORT_STATS_PREFIX: &str = "cohort_stats";
+pub const EXCLUDED_COUNT_KEY: &str = "excluded_count";
+pub const CLAIM_PRIZE_PREFIX: &str = "claim_prize";
+pub const AIRDROP_CLAIMED_AT_PREFIX: &str = "airdrop_claimed_at";
+pub const PRIZE_CLAIMED_AT_PREFIX: &str = "prize_claimed_at";
+pub const CLAIM_USAGE_PREFIX: &str = "claim_usage";
+pub const ADMIN_ACTIONS_PREFIX: &str = "admin_actions";
+pub const ADMIN_ACTIONS_COUNT_KEY: &str = "admin_actions_count";
+pub const CLAIM_LOG_PREFIX: &str = "claim_log";
+pub const CLAIM_LOG_COUNT_KEY: &str = "claim_log_count";
+pub const ROUNDS_PREFIX: &str = "rounds";
+pub const ROUND_CLAIMED_PREFIX: &str = "round_claimed";
+pub const LATEST_ROUND_KEY: &str = "latest_round";
+pub const TREASURIES_PREFIX: &str = "treasuries";
+pub const WITHDRAWAL_PROPOSALS_PREFIX: &str = "withdrawal_proposals";
+pub const WITHDRAWAL_PROPOSAL_COUNT_KEY: &str = "withdrawal_proposal_count";
+pub const RETIRED_PAYOUT_TOKENS_PREFIX: &str = "retired_payout_tokens";
+
+/// Key under which the versions up to 0.12 stored the number of bins, relocated to
+/// `BINS_KEY` on migration.
+pub const LEGACY_BINS_KEY: &str = "bids";
+
+/// Keys under which the versions up to 0.12 stored the parameters of the game, packed
+/// under `GAME_PARAMS_KEY` on migration.
+pub const STAGE_BID_KEY: &str = "stage_bid";
+pub const STAGE_CLAIM_AIRDROP_KEY: &str = "stage_claim_airdrop";
+pub const STAGE_CLAIM_PRIZE_KEY: &str = "stage_claim_prize";
+pub const TICKET_PRICE_KEY: &str = "ticket_price";
+pub const BINS_KEY: &str = "bins";
+
+/// Keys under which the versions up to 0.12 stored the running totals of the game,
+/// packed under `COUNTERS_KEY` on migration.
+pub const CLAIMED_AIRDROP_AMOUNT_PREFIX: &str = "claimed_amount";
+pub const CLAIMED_PRIZE_AMOUNT_PREFIX: &str = "claimed_prize";
+pub const WINNERS_PREFIX: &str = "winners";
+pub const TOTAL_TICKET_PRIZE_KEY: &str = "total_ticket_prize";
+pub const TOTAL_BIN_BOOST_KEY: &str = "total_bin_boost";
+pub const CLAIMED_BIN_BOOST_KEY: &str = "claimed_bin_boost";
+pub const FUNDED_AIRDROP_NATIVE_AMOUNT_KEY: &str = "funded_airdrop_native_amount";
+pub const WITHDRAWN_AIRDROP_AMOUNT_KEY: &str = "withdrawn_airdrop_amount";
+pub const TOTAL_AIRDROP_AMOUNT_PREFIX: &str = "total_amount_airdrop";
+pub const TOTAL_AIRDROP_GAME_AMOUNT_PREFIX: &str = "total_amount_game";
+pub const LEGACY_COUNTER_KEYS: [&str; 10] = [
+    WINNERS_PREFIX,
+    TOTAL_TICKET_PRIZE_KEY,
+    TOTAL_BIN_BOOST_KEY,
+    CLAIMED_BIN_BOOST_KEY,
+    TOTAL_AIRDROP_AMOUNT_PREFIX,
+    TOTAL_AIRDROP_GAME_AMOUNT_PREFIX,
+    CLAIMED_AIRDROP_AMOUNT_PREFIX,
+    CLAIMED_PRIZE_AMOUNT_PREFIX,
+    FUNDED_AIRDROP_NATIVE_AMOUNT_KEY,
+    WITHDRAWN_AIRDROP_AMOUNT_KEY,
+];
+
+// ======================================================================================
+// Stages
+// ======================================================================================
+/// Name of a stage of the game, as reported by the stage errors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StageName {
+    Bid,
+    ClaimAirdrop,
+    ClaimPrize,
+}
+
+impl StageName {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StageName::Bid => "bid",
+            StageName::ClaimAirdrop => "claim airdrop",
+            StageName::ClaimPrize => "claim prize",
+        }
+    }
+}
+
+impl From<StageName> for String {
+    fn from(stage_name: StageName) -> Self {
+        stage_name.as_str().to_string()
+    }
+}
+
+// ======================================================================================
+// Prize pools and claim rate limit
+// ======================================================================================
+/// Prize pools whose dust is assigned once by the rounding policy.
+pub const POOL_TICKETS: &str = "tickets";
+pub const POOL_AIRDROP_GAME: &str = "airdrop_game";
+
+/// Side-pool attached by the sponsors to a bin.
+pub fn bin_pool_name(bin: u8) -> String {
+    format!("bin_{}", bin)
+}
+
+/// Share of the ticket prize of a bin in the `per_bin` prize mode.
+pub fn bin_tickets_pool_name(bin: u8) -> String {
+    format!("tickets_bin_{}", bin)
+}
+
+/// Reply of the badge minted on every claim.
+pub const BADGE_REPLY_ID: u64 = 1;
+
+/// Claims counted by the claim rate limit.
+pub const RATE_LIMIT_CLAIM_AIRDROP: &str = "claim_airdrop";
+pub const RATE_LIMIT_CLAIM_PRIZE: &str = "claim_prize";
+
+/// Placeholder replaced with the claimer address in the eligibility query template.
+/// Basis points of a whole payout split.
+pub const TOTAL_BPS: u16 = 10_000;
+
+pub const ADDRESS_PLACEHOLDER: &str = "{address}";
+
+/// Merkle trees named by the verification errors.
+pub const ROOT_AIRDROP: &str = "airdrop";
+pub const ROOT_GAME: &str = "game";
+pub const ROOT_ROUND: &str = "round";
+
+/// Invariants of the storage checked before the operations moving funds.
+pub const INVARIANT_AIRDROP_CLAIMED: &str = "claimed airdrop exceeds the airdrop total";
+pub const INVARIANT_PRIZE_CLAIMED: &str = "claimed prize exceeds the prize pool";
+pub const INVARIANT_ROOTS_MISSING: &str = "merkle roots missing in the claim stages";
+
+// ======================================================================================
+// Response attributes
+// ======================================================================================
+pub const ATTR_ACTION: &str = "action";
+pub const ATTR_ACTIVE_AT: &str = "active_at";
+pub const ATTR_ADDRESS: &str = "address";
+pub const ATTR_AIRDROP_AMOUNT: &str = "airdrop_amount";
+pub const ATTR_ALLOCATION_CHECKSUM: &str = "allocation_checksum";
+pub const ATTR_AMOUNT: &str = "amount";
+pub const ATTR_BALANCE: &str = "balance";
+pub const ATTR_BIN: &str = "bin";
+pub const ATTR_BIN_POOL: &str = "bin_pool";
+pub const ATTR_CACHED: &str = "cached";
+pub const ATTR_COHORT: &str = "cohort";
+pub const ATTR_COMMITMENT: &str = "commitment";
+pub const ATTR_CONDITION: &str = "condition";
+pub const ATTR_EXPIRES: &str = "expires";
+pub const ATTR_FINISHED: &str = "finished";
+pub const ATTR_IMPORTED: &str = "imported";
+pub const ATTR_INDEX: &str = "index";
+pub const ATTR_LATE_FEE: &str = "late_fee";
+pub const ATTR_LEVEL: &str = "level";
+pub const ATTR_MERKLE_ROOT_AIRDROP: &str = "merkle_root_airdrop";
+pub const ATTR_MERKLE_ROOT_GAME: &str = "merkle_root_game";
+pub const ATTR_NEW_CW20: &str = "new_cw20";
+pub const ATTR_NEW_BIN: &str = "new_bin";
+pub const ATTR_NEW_END: &str = "new_end";
+pub const ATTR_NEW_START: &str = "new_start";
+pub const ATTR_OLD_END: &str = "old_end";
+pub const ATTR_OLD_START: &str = "old_start";
+pub const ATTR_OPERATOR: &str = "operator";
+pub const ATTR_OVERWRITE: &str = "overwrite";
+pub const ATTR_PLAYER: &str = "player";
+pub const ATTR_PROPOSAL_ID: &str = "proposal_id";
+pub const ATTR_PURPOSE: &str = "purpose";
+pub const ATTR_PRIZE_FROM_AIRDROP: &str = "prize_from_airdrop";
+pub const ATTR_PRIZE_FROM_BIN_BOOST: &str = "prize_from_bin_boost";
+pub const ATTR_PRIZE_FROM_TICKETS: &str = "prize_from_tickets";
+pub const ATTR_RATE: &str = "rate";
+pub const ATTR_REASON: &str = "reason";
+pub const ATTR_RECIPIENT: &str = "recipient";
+pub const ATTR_REMAINING: &str = "remaining";
+pub const ATTR_REQUIRED: &str = "required";
+pub const ATTR_ROUND: &str = "round";
+pub const ATTR_SECONDARY_AMOUNT: &str = "secondary_amount";
+pub const ATTR_SENDER: &str = "sender";
+pub const ATTR_SOURCE_CONTRACT: &str = "source_contract";
+pub const ATTR_SPONSOR: &str = "sponsor";
+pub const ATTR_STAGE: &str = "stage";
+pub const ATTR_TICKET_ID: &str = "ticket_id";
+pub const ATTR_TICKET_PRICE_PAYBACK: &str = "ticket_price_payback";
+pub const ATTR_TOKEN_ID: &str = "token_id";
+pub const ATTR_TOTAL_AMOUNT_AIRDROP: &str = "total_amount_airdrop";
+pub const ATTR_TOTAL_BOOST: &str = "total_boost";
+pub const ATTR_TOTAL_EXCLUDED: &str = "total_excluded";
+pub const ATTR_TOTAL_FUNDED: &str = "total_funded";
+pub const ATTR_WINNERS: &str = "winners";
+pub const ATTR_WITHHELD: &str = "withheld";
+
+/// Attributes carrying hashes and free text, omitted with `minimal_attributes`.
+pub const VERBOSE_ATTRIBUTES: [&str; 5] = [
+    ATTR_ALLOCATION_CHECKSUM,
+    ATTR_COMMITMENT,
+    ATTR_MERKLE_ROOT_AIRDROP,
+    ATTR_MERKLE_ROOT_GAME,
+    ATTR_REASON,
+];
+
+// ======================================================================================
+// Events
+// ======================================================================================
+/// Emitted by `ClaimAirdrop` when the claimer is proven to be a winner of the game.
+pub const EVENT_WINNER_REGISTERED: &str = "winner_registered";
+/// Emitted by `SetTreasury`, `ActivateTreasury` and `RemoveTreasury`, for monitoring the
+/// withdrawal destinations of the owner.
+pub const EVENT_TREASURY_REGISTERED: &str = "treasury_registered";
+pub const EVENT_TREASURY_ACTIVATED: &str = "treasury_activated";
+pub const EVENT_TREASURY_REMOVED: &str = "treasury_removed";
+/// Emitted by `ExtendClaimStages` for every stage rescheduled.
+pub const EVENT_STAGE_EXTENDED: &str = "stage_extended";
+
+#[cfg(test)]
+/// Denom of the tickets and of the native airdrops in the tests.
+pub const TEST_DENOM: &str = "ujuno";
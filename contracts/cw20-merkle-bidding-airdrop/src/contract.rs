@@ -1,24 +1,68 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, to_binary, Addr, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, BankMsg, Binary, Coin, ContractInfoResponse, CosmosMsg, Decimal, Deps, DepsMut, Empty, Env, Event,
+    Fraction, MessageInfo, Order, OverflowError, OverflowOperation, QueryRequest, Reply, Response, StdError, StdResult, Storage, SubMsg,
+    SubMsgResult, Uint128, WasmMsg, WasmQuery,
 };
+#[cfg(feature = "cosmwasm_1_1")]
+use cosmwasm_std::{to_vec, ContractResult, SystemResult};
 use cw2::{get_contract_version, set_contract_version};
-use cw20::Cw20ExecuteMsg;
-use sha2::Digest;
-use std::convert::TryInto;
+use cw20::{BalanceResponse, Cw20QueryMsg, Cw20ReceiveMsg};
+use cw_storage_plus::{Bound, Item};
+use cw_asset_lite::Asset;
+use cw_game_interface::{GamePhase, IsWinnerResponse, StageStatusResponse};
+use cw_utils::{must_pay, nonpayable, Duration, Scheduled};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
 
+use crate::address::pubkey_canonical_address;
+use crate::constants::{
+    ATTR_ACTION, ATTR_ACTIVE_AT, ATTR_ADDRESS, ATTR_AIRDROP_AMOUNT, ATTR_ALLOCATION_CHECKSUM, ATTR_AMOUNT,
+    ATTR_BALANCE, ATTR_BIN, ATTR_BIN_POOL, ATTR_CACHED, ATTR_COHORT, ATTR_COMMITMENT, ATTR_EXPIRES, ATTR_FINISHED,
+    ATTR_IMPORTED, ATTR_INDEX, ATTR_LATE_FEE, ATTR_LEVEL, ATTR_MERKLE_ROOT_AIRDROP, ATTR_MERKLE_ROOT_GAME, ATTR_NEW_BIN, ATTR_NEW_CW20, ATTR_NEW_END, ATTR_NEW_START, ATTR_OLD_END, ATTR_OLD_START,
+    ATTR_OPERATOR, ATTR_OVERWRITE, ATTR_PLAYER, ATTR_PROPOSAL_ID, ATTR_PURPOSE, ATTR_PRIZE_FROM_AIRDROP,
+    ATTR_PRIZE_FROM_BIN_BOOST, ATTR_PRIZE_FROM_TICKETS, ATTR_RATE, ATTR_REASON, ATTR_RECIPIENT,
+    ATTR_REQUIRED, ATTR_ROUND, ATTR_SECONDARY_AMOUNT, ATTR_SENDER, ATTR_SOURCE_CONTRACT, ATTR_SPONSOR, ATTR_STAGE, ATTR_TICKET_ID,
+    ATTR_TICKET_PRICE_PAYBACK, ATTR_TOTAL_AMOUNT_AIRDROP, ATTR_TOTAL_BOOST, ATTR_TOTAL_EXCLUDED,
+    ATTR_TOTAL_FUNDED, ATTR_WINNERS, ATTR_WITHHELD, EVENT_STAGE_EXTENDED, EVENT_TREASURY_ACTIVATED, EVENT_TREASURY_REGISTERED, EVENT_TREASURY_REMOVED, EVENT_WINNER_REGISTERED, VERBOSE_ATTRIBUTES, POOL_AIRDROP_GAME, POOL_TICKETS, RATE_LIMIT_CLAIM_AIRDROP,
+    RATE_LIMIT_CLAIM_PRIZE, ROOT_AIRDROP, BADGE_REPLY_ID, ATTR_TOKEN_ID, BINS_KEY, LEGACY_BINS_KEY, STAGE_BID_KEY,
+    LEGACY_COUNTER_KEYS, WINNERS_PREFIX, TOTAL_TICKET_PRIZE_KEY, TOTAL_BIN_BOOST_KEY, CLAIMED_BIN_BOOST_KEY,
+    TOTAL_AIRDROP_AMOUNT_PREFIX, TOTAL_AIRDROP_GAME_AMOUNT_PREFIX, CLAIMED_AIRDROP_AMOUNT_PREFIX,
+    CLAIMED_PRIZE_AMOUNT_PREFIX, FUNDED_AIRDROP_NATIVE_AMOUNT_KEY, WITHDRAWN_AIRDROP_AMOUNT_KEY, STAGE_CLAIM_AIRDROP_KEY, STAGE_CLAIM_PRIZE_KEY, TICKET_PRICE_KEY, ROOT_GAME, ROOT_ROUND, StageName, bin_pool_name,
+    bin_tickets_pool_name, ADDRESS_PLACEHOLDER, ATTR_REMAINING, TOTAL_BPS, ATTR_CONDITION, INVARIANT_AIRDROP_CLAIMED, INVARIANT_PRIZE_CLAIMED,
+    INVARIANT_ROOTS_MISSING,
+};
 use crate::error::ContractError;
+use crate::limits::{
+    check_addresses_count, check_batch_size, check_condition_size, check_limit,
+    check_max_proof_length, check_memo_length, check_proof_length, MAX_ADDRESSES_PER_CALL,
+    MAX_BATCH_SIZE, MAX_PROOF_NODES,
+};
+use crate::math::{amount_band, split_evenly, PrizeSplit};
+use crate::merkle::{compute_leaf, domain_leaf_hash, verify_airdrop_proof, verify_proof};
 use crate::msg::{
-    BidResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, MerkleRootsResponse,
-    MigrateMsg, QueryMsg, StagesResponse, GameAmountsResponse,
+    AdminActionResponse, AdminActionsResponse, ClaimRecordResponse, ClaimsPageResponse, AttestationQueryMsg, AttestationResponse, BadgeExecuteMsg, BadgeMetadata, BadgeMintMsg, IsClaimedByIndexResponse, Cw721QueryMsg, CustodyResponse, OperatorsResponse, OutcomeResponse, ProxyQueryMsg,
+    PayoutShareMsg, PayoutSplitResponse, PubkeyProof, RegistryEntry, RegistryExecuteMsg, BinBoostResponse, BinBidsResponse, BinDistributionResponse, BinPoolResponse, BinPoolsResponse, CohortResponse, CohortStatsResponse, BoosterResponse, TopBoostersResponse, EligibilityResponse, ExclusionsResponse, BidResponse, EligibleAmountResponse, LegacyBidResponse, ProofToCache, PrizeHookMsg, SecondaryPayoutMsg, AllBidsResponse, BidInfo, IsClaimedResponse, LeafHashResponse, VerifyTreeMetadataResponse, ClaimsFreezeResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, MerkleRootsResponse,
+    FailsafeResponse, Limits, LimitsResponse, MigrateMsg, PendingAction, PendingActionKind, PendingActionsResponse, PayoutReceiveMsg, QueryMsg, FundPurpose, MerkleRootInfo, ReceiveMsg, RootKind, IsRoundClaimedResponse, LatestRoundResponse, RoundResponse, StageMsg, StagesResponse, TreasuriesResponse, TreasuryResponse, WithdrawalProposalResponse, WithdrawalProposalsResponse, GameAmountsResponse, PrizePotResponse, TicketVendorQueryMsg, TokensResponse,
+    VerifyTicketResponse,
 };
+#[cfg(feature = "cosmwasm_1_1")]
+use crate::msg::{BankSupplyQuery, SupplyQuery, SupplyResponse};
+use crate::stage_math::{after_block, checked_add};
+use crate::validation::{validate_metadata, validate_stages, ValidatedInstantiateMsg};
 use crate::state::{
-    Config, Stage, BIDS, CLAIMED_AIRDROP_AMOUNT, CLAIM_AIRDROP, CONFIG, STAGE_BID,
-    STAGE_CLAIM_AIRDROP, STAGE_CLAIM_PRIZE, TICKET_PRICE, TOTAL_AIRDROP_AMOUNT, BINS,
-    MERKLE_ROOT_AIRDROP, MERKLE_ROOT_GAME, CLAIM_PRIZE, WINNERS, TOTAL_TICKET_PRIZE,
-    TOTAL_AIRDROP_GAME_AMOUNT, CLAIMED_PRIZE_AMOUNT,
+    BadgeRole, BidReceipt, CachedProof, ClaimHistory, ClaimRateLimit, ClaimUsage, ClaimedAt, Config, Counters, EligibilityHook, GameMetadata, GameParams, PayoutMode, PrizeMode, RoundingPolicy, Stage, StageStatus, GAME_METADATA, GAME_PARAMS, COUNTERS, BIDS, CLAIM_AIRDROP, CONFIG,
+    MERKLE_ROOT_AIRDROP, MERKLE_ROOT_GAME, CLAIM_PRIZE,
+    AdminAction,
+    ADMIN_ACTIONS, ADMIN_ACTIONS_COUNT, BOOSTS, BOOSTERS_BY_AMOUNT, BIN_BOOSTS, BIN_POOLS, BIN_CLAIMED_PRIZE,
+    BIN_WINNERS, DUST_PAID, OPERATORS, PUBKEY_BIDS, CLAIM_USAGE,
+    OUTCOME_COMMITMENT, REVEALED_OUTCOME, TICKET_BIDS, BID_RECEIPTS, BID_KEYS, CACHED_PROOFS, USED_TICKETS, CLAIMS_FUNDED, EXCLUDED, EXCLUDED_COUNT,
+    AIRDROP_CLAIMED_AT, PRIZE_CLAIMED_AT, BID_IMPORTS, TREE_METADATA, TreeMetadata, CLOSED_SNAPSHOT,
+    ClosedSnapshot, CLAIMS_FROZEN, PRIZE_VETO, PAYOUT_RATE, SecondaryPayout, SECONDARY_PAYOUT,
+    PendingBadge, PENDING_BADGE, CLAIM_HISTORY, BID_CHANGES, ClaimCondition, Failsafe, FAILSAFE, CLAIM_CONDITION, GovernanceMode, KycGate, NoWinnersPolicy, PayoutShare, PAYOUT_SPLITS, WITHHELD_AIRDROP, is_index_claimed, set_index_claimed,
+    BIN_BIDS, add_bin_bid, remove_bin_bid, CohortStats, COHORTS, COHORT_STATS, record_cohort_claim,
+    record_claim, ClaimKind, CLAIM_LOG, AirdropRound, LATEST_ROUND, ROUNDS, ROUND_CLAIMED, Treasury, TREASURIES, Withdrawal, WithdrawalProposal, WITHDRAWAL_PROPOSALS, WITHDRAWAL_PROPOSAL_COUNT, RETIRED_PAYOUT_TOKENS,
 };
 
 // Version info, for migration info
@@ -33,6 +77,68 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, C
             previous_contract: version.contract,
         });
     }
+
+    // The number of bins was stored under the key of the bids, relocate it.
+    if let Some(bins) = deps.storage.get(LEGACY_BINS_KEY.as_bytes()) {
+        if deps.storage.get(BINS_KEY.as_bytes()).is_none() {
+            deps.storage.set(BINS_KEY.as_bytes(), &bins);
+        }
+        deps.storage.remove(LEGACY_BINS_KEY.as_bytes());
+    }
+
+    // The parameters of the game were stored under a key each, pack them.
+    if GAME_PARAMS.may_load(deps.storage)?.is_none() {
+        let params = GameParams {
+            stage_bid: Item::new(STAGE_BID_KEY).load(deps.storage)?,
+            stage_claim_airdrop: Item::new(STAGE_CLAIM_AIRDROP_KEY).load(deps.storage)?,
+            stage_claim_prize: Item::new(STAGE_CLAIM_PRIZE_KEY).load(deps.storage)?,
+            ticket_price: Item::new(TICKET_PRICE_KEY).load(deps.storage)?,
+            bins: Item::new(BINS_KEY).load(deps.storage)?,
+        };
+        GAME_PARAMS.save(deps.storage, &params)?;
+        for key in [STAGE_BID_KEY, STAGE_CLAIM_AIRDROP_KEY, STAGE_CLAIM_PRIZE_KEY, TICKET_PRICE_KEY, BINS_KEY] {
+            deps.storage.remove(key.as_bytes());
+        }
+    }
+
+    // The running totals too, missing ones were never updated.
+    if COUNTERS.may_load(deps.storage)?.is_none() {
+        let total = |key: &'static str| -> StdResult<Uint128> {
+            Ok(Item::new(key).may_load(deps.storage)?.unwrap_or_default())
+        };
+        let counters = Counters {
+            winners: total(WINNERS_PREFIX)?,
+            total_ticket_prize: total(TOTAL_TICKET_PRIZE_KEY)?,
+            total_bin_boost: total(TOTAL_BIN_BOOST_KEY)?,
+            claimed_bin_boost: total(CLAIMED_BIN_BOOST_KEY)?,
+            total_airdrop_amount: total(TOTAL_AIRDROP_AMOUNT_PREFIX)?,
+            total_airdrop_game_amount: total(TOTAL_AIRDROP_GAME_AMOUNT_PREFIX)?,
+            claimed_airdrop_amount: total(CLAIMED_AIRDROP_AMOUNT_PREFIX)?,
+            claimed_prize_amount: total(CLAIMED_PRIZE_AMOUNT_PREFIX)?,
+            funded_airdrop_native_amount: total(FUNDED_AIRDROP_NATIVE_AMOUNT_KEY)?,
+            withdrawn_airdrop_amount: total(WITHDRAWN_AIRDROP_AMOUNT_KEY)?,
+            ..Counters::default()
+        };
+        COUNTERS.save(deps.storage, &counters)?;
+        for key in LEGACY_COUNTER_KEYS {
+            deps.storage.remove(key.as_bytes());
+        }
+    }
+
+    // The bids per bin were not counted, count the bids placed so far once.
+    let counted = BIN_BIDS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_some();
+    if !counted {
+        let bins = BIDS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|bid| bid.map(|(_, bin)| bin))
+            .collect::<StdResult<Vec<_>>>()?;
+        for bin in bins {
+            add_bin_bid(deps.storage, bin)?;
+        }
+    }
     Ok(Response::default())
 }
 
@@ -44,58 +150,47 @@ pub fn instantiate(
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    // ======================================================================================
-    // Contract configuration
-    // ======================================================================================
-    // If owner not in message, set it as sender.
-    let owner = msg
-        .owner
-        .map_or(Ok(info.sender), |o| deps.api.addr_validate(&o))?;
-
-    let config = Config {
-        owner: Some(owner),
-        cw20_token_address: deps.api.addr_validate(&msg.cw20_token_address)?,
-    };
-
-    // ======================================================================================
-    // Stages validity checks
-    // ======================================================================================
-    let stage_bid_end = (msg.stage_bid.start + msg.stage_bid.duration)?;
-    let stage_claim_airdrop_end =
-        (msg.stage_claim_airdrop.start + msg.stage_claim_airdrop.duration)?;
-
-    // Bid stage haa to start after contract instantiation.
-    if msg.stage_bid.start.is_triggered(&env.block) {
-        return Err(ContractError::BidStartPassed {});
-    }
-
-    // Airdrop claim stage has to start after bidding stage end.
-    if stage_bid_end > msg.stage_claim_airdrop.start {
-        let first = String::from("bid");
-        let second = String::from("Claim airdrop");
-        return Err(ContractError::StagesOverlap { first, second });
-    }
+    let ValidatedInstantiateMsg {
+        mut config,
+        metadata,
+        params,
+        registry,
+    } = msg.validate(deps.api, &env)?;
 
-    // Game prize claim has to start after airdrop claim stage end.
-    if stage_claim_airdrop_end > msg.stage_claim_prize.start {
-        let first = String::from("claim aidrop");
-        let second = String::from("Claim prize");
-        return Err(ContractError::StagesOverlap { first, second });
-    }
+    // If owner not in message, set it as sender.
+    config.owner.get_or_insert(info.sender);
 
     // ======================================================================================
     // Contract initial state
     // ======================================================================================
     CONFIG.save(deps.storage, &config)?;
-    STAGE_BID.save(deps.storage, &msg.stage_bid)?;
-    STAGE_CLAIM_AIRDROP.save(deps.storage, &msg.stage_claim_airdrop)?;
-    STAGE_CLAIM_PRIZE.save(deps.storage, &msg.stage_claim_prize)?;
-    TICKET_PRICE.save(deps.storage, &msg.ticket_price)?;
-    BINS.save(deps.storage, &msg.bins)?;
-    WINNERS.save(deps.storage, &Uint128::new(0))?;
-    TOTAL_TICKET_PRIZE.save(deps.storage, &Uint128::new(0))?;
+    GAME_METADATA.save(deps.storage, &metadata)?;
+    GAME_PARAMS.save(deps.storage, &params)?;
+    COUNTERS.save(deps.storage, &Counters::default())?;
 
-    Ok(Response::default())
+    // Announce the game to the registry, if any.
+    let mut res = Response::new();
+    if let Some(registry) = registry {
+        let entry = RegistryEntry {
+            game: env.contract.address.to_string(),
+            cw20_token_address: config.cw20_token_address.to_string(),
+            airdrop_native_denom: config.airdrop_native_denom,
+            ticket_price: params.ticket_price,
+            bins: params.bins,
+            prize_mode: config.prize_mode,
+            stage_bid: params.stage_bid,
+            stage_claim_airdrop: params.stage_claim_airdrop,
+            stage_claim_prize: params.stage_claim_prize,
+            metadata,
+        };
+        res = res.add_message(WasmMsg::Execute {
+            contract_addr: registry.to_string(),
+            msg: to_binary(&RegistryExecuteMsg::Register(entry))?,
+            funds: vec![],
+        });
+    }
+
+    Ok(res)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -105,43 +200,190 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // Coins attached to messages that do not pay anything would be stranded.
+    let payable = matches!(
+        msg,
+        ExecuteMsg::Bid { .. }
+            | ExecuteMsg::ImportBids { .. }
+            | ExecuteMsg::FundAirdropNative {}
+            | ExecuteMsg::BoostPrize {}
+            | ExecuteMsg::BoostBin { .. }
+            | ExecuteMsg::RegisterRound { .. }
+    );
+    if !payable {
+        nonpayable(&info)?;
+    }
+
+    // Read once for the whole message: the bids and the claims take them from here.
+    let params = GAME_PARAMS.load(deps.storage)?;
+    let cfg = CONFIG.load(deps.storage)?;
+
+    // Whoever touches the contract first after the airdrop claims freezes the totals.
+    close_claim_window(deps.storage, &params, &env)?;
+
+    // Operations moving funds stop at the first inconsistency found in the storage. The
+    // detection is not reverted, so that the failsafe holds until the owner clears it.
+    if moves_funds(&msg) {
+        if let Some(failsafe) = FAILSAFE.may_load(deps.storage)? {
+            return Err(ContractError::FailsafeActive { condition: failsafe.condition });
+        }
+        if let Some(condition) = violated_invariant(deps.as_ref(), &cfg, &params, &env, &msg)? {
+            return trigger_failsafe(deps.storage, &env, condition);
+        }
+    }
+
     match msg {
         ExecuteMsg::UpdateConfig {
             new_owner
         } => execute_update_config(deps, env, info, new_owner),
+        ExecuteMsg::AddOperator { address } => execute_add_operator(deps, env, info, address),
+        ExecuteMsg::RemoveOperator { address } => {
+            execute_remove_operator(deps, env, info, address)
+        }
+        ExecuteMsg::UpdateMetadata {
+            metadata
+        } => execute_update_metadata(deps, env, info, metadata),
+        ExecuteMsg::UpdateStages {
+            stage_bid,
+            stage_claim_airdrop,
+            stage_claim_prize
+        } => execute_update_stages(deps, env, info, stage_bid, stage_claim_airdrop, stage_claim_prize),
+        ExecuteMsg::ExtendClaimStages {
+            claim_airdrop,
+            claim_prize
+        } => execute_extend_claim_stages(deps, env, info, claim_airdrop, claim_prize),
         ExecuteMsg::Bid {
-            bin 
-        } => execute_bid(deps, env, info, bin),
+            bin,
+            pubkey_proof,
+            ticket_id
+        } => execute_bid(deps, env, info, cfg, params, bin, pubkey_proof, ticket_id),
         ExecuteMsg::ChangeBid {
             bin
         } => execute_change_bid(deps, env, info, bin),
         ExecuteMsg::RemoveBid {} => execute_remove_bid(deps, env, info),
+        ExecuteMsg::ImportBids { source_contract, limit } => {
+            execute_import_bids(deps, env, info, source_contract, limit)
+        }
+        ExecuteMsg::FundAirdropNative {} => execute_fund_airdrop_native(deps, env, info),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+        ExecuteMsg::BoostPrize {} => execute_boost_prize(deps, env, info),
+        ExecuteMsg::BoostBin { bin } => execute_boost_bin(deps, env, info, bin),
+        ExecuteMsg::RefundBinBoost { bin } => execute_refund_bin_boost(deps, env, info, bin),
+        ExecuteMsg::RefundPrizeBoost {} => execute_refund_prize_boost(deps, env, info),
+        ExecuteMsg::CommitOutcome { commitment } => {
+            execute_commit_outcome(deps, env, info, commitment)
+        }
+        ExecuteMsg::RevealOutcome {
+            merkle_root_game,
+            salt
+        } => execute_reveal_outcome(deps, env, merkle_root_game, salt),
         ExecuteMsg::RegisterMerkleRoots {
             merkle_root_airdrop,
             total_amount_airdrop,
             merkle_root_game,
-            total_amount_game
+            total_amount_game,
+            tree_metadata,
+            overwrite,
+            secondary_payout,
+            claim_condition,
         } => execute_register_merkle_roots(
-            deps, env, info, merkle_root_airdrop, total_amount_airdrop, merkle_root_game, total_amount_game
+            deps, env, info, merkle_root_airdrop, total_amount_airdrop, merkle_root_game, total_amount_game,
+            tree_metadata, overwrite.unwrap_or(false), secondary_payout, claim_condition,
+        ),
+        ExecuteMsg::OpenClaims {} => execute_open_claims(deps, env),
+        ExecuteMsg::FreezeClaims { reason } => execute_freeze_claims(deps, env, info, reason),
+        ExecuteMsg::UnfreezeClaims {} => execute_unfreeze_claims(deps, env, info),
+        ExecuteMsg::VetoPrize { reason } => execute_veto_prize(deps, env, info, reason),
+        ExecuteMsg::LiftPrizeVeto {} => execute_lift_prize_veto(deps, env, info),
+        ExecuteMsg::ClearFailsafe {} => execute_clear_failsafe(deps, env, info),
+        #[cfg(feature = "test_mode")]
+        ExecuteMsg::FastForwardStage {} => execute_fast_forward_stage(deps, env, info),
+        #[cfg(not(feature = "test_mode"))]
+        ExecuteMsg::FastForwardStage {} => Err(ContractError::TestModeDisabled {}),
+        ExecuteMsg::UpdateLimits {
+            max_batch_size,
+            max_addresses_per_call,
+            max_proof_length,
+        } => execute_update_limits(
+            deps,
+            env,
+            info,
+            max_batch_size,
+            max_addresses_per_call,
+            max_proof_length,
         ),
+        ExecuteMsg::ImportExclusions { addresses } => {
+            execute_import_exclusions(deps, env, info, addresses)
+        }
+        ExecuteMsg::TagCohort { cohort, addresses } => {
+            execute_tag_cohort(deps, env, info, cohort, addresses)
+        }
+        ExecuteMsg::SwapPayoutToken { new_cw20, rate } => {
+            execute_swap_payout_token(deps, env, info, new_cw20, rate)
+        }
         ExecuteMsg::ClaimAirdrop {
             amount,
             proof_airdrop,
             proof_game
-        } => execute_claim_airdrop(deps, env, info, amount, proof_airdrop, proof_game),
-        ExecuteMsg::ClaimPrize {} => execute_claim_prize(deps, env, info),
+        } => execute_claim_airdrop(deps, env, info, cfg, params, amount, proof_airdrop, proof_game),
+        ExecuteMsg::CacheProof { proofs } => execute_cache_proof(deps, env, info, proofs),
+        ExecuteMsg::ClaimCachedAirdrop {} => execute_claim_cached_airdrop(deps, env, info, cfg, params),
+        ExecuteMsg::ClaimAirdropFor {
+            address,
+            amount,
+            proof_airdrop,
+            proof_game
+        } => execute_claim_airdrop_for(deps, env, info, cfg, params, address, amount, proof_airdrop, proof_game),
+        ExecuteMsg::ClaimAirdropByIndex {
+            index,
+            amount,
+            proof_airdrop,
+            proof_game,
+        } => execute_claim_airdrop_by_index(deps, env, info, cfg, params, index, amount, proof_airdrop, proof_game),
+        ExecuteMsg::ClaimWithheldAirdrop {} => execute_claim_withheld_airdrop(deps, env, info, cfg, params),
+        ExecuteMsg::ClaimPrize {
+            amount,
+            proof,
+            then
+        } => execute_claim_prize(deps, env, info, cfg, params, amount, proof, then),
+        ExecuteMsg::SettleNoWinners {} => execute_settle_no_winners(deps, env),
+        ExecuteMsg::ClaimTicketRefund {} => execute_claim_ticket_refund(deps, env, info),
+        ExecuteMsg::RegisterRound { merkle_root, stage } => {
+            execute_register_round(deps, env, info, merkle_root, stage)
+        }
+        ExecuteMsg::ClaimRound { round, amount, proof } => {
+            execute_claim_round(deps, env, info, round, amount, proof)
+        }
+        ExecuteMsg::SetPayoutSplit { shares } => execute_set_payout_split(deps, env, info, shares),
+        ExecuteMsg::SetTreasury { addr } => execute_set_treasury(deps, env, info, addr),
+        ExecuteMsg::ActivateTreasury { addr } => execute_activate_treasury(deps, env, addr),
+        ExecuteMsg::RemoveTreasury { addr } => execute_remove_treasury(deps, env, info, addr),
         ExecuteMsg::WithdrawAirdrop {
-            address 
-        } => execute_withdraw_airdrop(deps, env, info, &address),
+            address,
+            amount
+        } => execute_withdraw_airdrop(deps, env, info, &address, amount, false),
+        ExecuteMsg::WithdrawSecondaryPayout { address } => {
+            execute_withdraw_secondary_payout(deps, env, info, &address, false)
+        }
         ExecuteMsg::WithdrawPrize {
             address
-        } => execute_withdraw_prize(deps, env, info, &address)
+        } => execute_withdraw_prize(deps, env, info, &address, false),
+        ExecuteMsg::WithdrawRound { round, address } => {
+            execute_withdraw_round(deps, env, info, round, &address, false)
+        }
+        ExecuteMsg::WithdrawRetiredPayout { cw20_token_address, address } => {
+            execute_withdraw_retired_payout(deps, env, info, &cw20_token_address, &address, false)
+        }
+        ExecuteMsg::ProposeWithdrawal { withdrawal } => {
+            execute_propose_withdrawal(deps, env, info, withdrawal)
+        }
+        ExecuteMsg::ApproveWithdrawal { id } => execute_approve_withdrawal(deps, env, info, id),
     }
 }
 
 pub fn execute_update_config(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     new_owner: Option<String>,
 ) -> Result<Response, ContractError> {
@@ -157,68 +399,346 @@ pub fn execute_update_config(
         tmp_owner = Some(deps.api.addr_validate(&addr)?)
     }
 
+    let summary = format!(
+        "new_owner: {}",
+        tmp_owner.as_ref().map_or("none", |addr| addr.as_str())
+    );
+
     CONFIG.update(deps.storage, |mut exists| -> StdResult<_> {
         exists.owner = tmp_owner;
         Ok(exists)
     })?;
+    record_admin_action(deps.storage, &env, &info.sender, "update_config", summary)?;
 
-    Ok(Response::new().add_attribute("action", "update_config"))
+    Ok(Response::new().add_attribute(ATTR_ACTION, "update_config"))
 }
 
-// TODO: add tests:
-// - send a fund different from the tiket.
-pub fn execute_bid(
+pub fn execute_add_operator(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    bin: u8,
+    address: String,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can manage the operators.
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let operator = deps.api.addr_validate(&address)?;
+    OPERATORS.save(deps.storage, &operator, &Empty {})?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "add_operator",
+        format!("operator: {}", operator),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute(ATTR_ACTION, "add_operator")
+        .add_attribute(ATTR_OPERATOR, operator))
+}
+
+pub fn execute_remove_operator(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can manage the operators.
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let operator = deps.api.addr_validate(&address)?;
+    OPERATORS.remove(deps.storage, &operator);
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "remove_operator",
+        format!("operator: {}", operator),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute(ATTR_ACTION, "remove_operator")
+        .add_attribute(ATTR_OPERATOR, operator))
+}
+
+pub fn execute_update_metadata(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    metadata: GameMetadata,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can update the metadata.
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Metadata are frozen once players can start bidding.
+    let stage_bid = GAME_PARAMS.load(deps.storage)?.stage_bid;
+    if stage_bid.has_started(&env.block) {
+        return Err(ContractError::StageStarted { stage_name: StageName::Bid.into() });
+    }
+
+    validate_metadata(&metadata)?;
+    GAME_METADATA.save(deps.storage, &metadata)?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "update_metadata",
+        format!(
+            "title: {}, terms_hash: {}",
+            metadata.title.as_deref().unwrap_or("none"),
+            metadata.terms_hash.as_deref().unwrap_or("none")
+        ),
+    )?;
+
+    Ok(Response::new().add_attribute(ATTR_ACTION, "update_metadata"))
+}
+
+pub fn execute_update_stages(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage_bid: Option<StageMsg>,
+    stage_claim_airdrop: Option<StageMsg>,
+    stage_claim_prize: Option<StageMsg>,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can reschedule the game.
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.clone().ok_or(ContractError::Unauthorized {})?;
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Players may have planned around the schedule once bidding is open.
+    let mut params = GAME_PARAMS.load(deps.storage)?;
+    if params.stage_bid.has_started(&env.block) {
+        return Err(ContractError::StageStarted { stage_name: StageName::Bid.into() });
+    }
+
+    let (stage_bid, stage_claim_airdrop, stage_claim_prize) = validate_stages(
+        &cfg,
+        &env,
+        stage_bid.unwrap_or(StageMsg::Absolute(params.stage_bid)),
+        stage_claim_airdrop.unwrap_or(StageMsg::Absolute(params.stage_claim_airdrop)),
+        stage_claim_prize.unwrap_or(StageMsg::Absolute(params.stage_claim_prize)),
+    )?;
+    params.stage_bid = stage_bid;
+    params.stage_claim_airdrop = stage_claim_airdrop;
+    params.stage_claim_prize = stage_claim_prize;
+    GAME_PARAMS.save(deps.storage, &params)?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "update_stages",
+        format!(
+            "bid: {} for {}, claim_airdrop: {} for {}, claim_prize: {} for {}",
+            params.stage_bid.start,
+            params.stage_bid.duration,
+            params.stage_claim_airdrop.start,
+            params.stage_claim_airdrop.duration,
+            params.stage_claim_prize.start,
+            params.stage_claim_prize.duration
+        ),
+    )?;
+
+    Ok(Response::new().add_attribute(ATTR_ACTION, "update_stages"))
+}
+
+pub fn execute_extend_claim_stages(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    claim_airdrop: Option<Duration>,
+    claim_prize: Option<Duration>,
 ) -> Result<Response, ContractError> {
-    let stage_bid = STAGE_BID.load(deps.storage)?;
-    let stage_name = String::from("bid");
-    check_if_valid_stage(env, stage_bid, stage_name)?;
+    // Just the contract owner can extend the claim stages.
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Stages can only be lengthened, an empty extension is a mistake.
+    let extensions = [claim_airdrop, claim_prize];
+    let is_zero = |extension: &Duration| matches!(extension, Duration::Height(0) | Duration::Time(0));
+    if extensions.iter().all(Option::is_none) || extensions.iter().flatten().any(is_zero) {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let mut params = GAME_PARAMS.load(deps.storage)?;
+    let old_claim_airdrop = params.stage_claim_airdrop.clone();
+    let old_claim_prize = params.stage_claim_prize.clone();
+    if let Some(extension) = claim_airdrop {
+        if params.stage_claim_airdrop.has_ended(&env.block)? {
+            return Err(ContractError::StageEnded { stage_name: StageName::ClaimAirdrop.into() });
+        }
+        params.stage_claim_airdrop.extend(extension)?;
+
+        // The claim prize stage moves only as far as needed not to overlap.
+        let claim_airdrop_end = params.stage_claim_airdrop.end()?;
+        if claim_airdrop_end > params.stage_claim_prize.start {
+            params.stage_claim_prize.start = claim_airdrop_end;
+        }
+    }
+    if let Some(extension) = claim_prize {
+        if params.stage_claim_prize.has_ended(&env.block)? {
+            return Err(ContractError::StageEnded { stage_name: StageName::ClaimPrize.into() });
+        }
+        params.stage_claim_prize.extend(extension)?;
+    }
+    GAME_PARAMS.save(deps.storage, &params)?;
+
+    let changes = [
+        (StageName::ClaimAirdrop, &old_claim_airdrop, &params.stage_claim_airdrop),
+        (StageName::ClaimPrize, &old_claim_prize, &params.stage_claim_prize),
+    ];
+    let events = changes
+        .iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(name, old, new)| -> StdResult<_> {
+            Ok(Event::new(EVENT_STAGE_EXTENDED)
+                .add_attribute(ATTR_STAGE, name.as_str())
+                .add_attribute(ATTR_OLD_START, old.start.to_string())
+                .add_attribute(ATTR_OLD_END, old.end()?.to_string())
+                .add_attribute(ATTR_NEW_START, new.start.to_string())
+                .add_attribute(ATTR_NEW_END, new.end()?.to_string()))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "extend_claim_stages",
+        format!(
+            "claim_airdrop_end: {}, claim_prize_end: {}",
+            params.stage_claim_airdrop.end()?,
+            params.stage_claim_prize.end()?
+        ),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute(ATTR_ACTION, "extend_claim_stages")
+        .add_events(events))
+}
 
-    let ticket_price = TICKET_PRICE.load(deps.storage)?;
+#[allow(clippy::too_many_arguments)]
+pub fn execute_bid(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cfg: Config,
+    params: GameParams,
+    bin: u8,
+    pubkey_proof: Option<PubkeyProof>,
+    ticket_id: Option<u64>,
+) -> Result<Response, ContractError> {
+    // Stage, bins and price come from a single read: the bid is the hottest message.
+    let GameParams { stage_bid, ticket_price, bins, .. } = params;
+    stage_bid.ensure_active(&env.block, StageName::Bid)?;
 
     // If a bid is already present for the sender, no other bids can be placed.
     if BIDS.has(deps.storage, &info.sender) {
         return Err(ContractError::CannotBidMoreThanOnce {});
     };
 
-    // If ticket price not paid, bid is not allowed.
-    let funds_sent = get_amount_for_denom(&info.funds, &ticket_price.denom);
-    if funds_sent.amount < ticket_price.amount {
-        return Err(ContractError::TicketPriceNotPaid {});
+    if cfg.pubkey_bids && pubkey_proof.is_none() {
+        return Err(ContractError::PubkeyProofRequired {});
+    }
+    if let Some(proof) = pubkey_proof.filter(|_| cfg.pubkey_bids || cfg.hidden_bids) {
+        check_pubkey_proof(deps.as_ref(), &env, &info.sender, &proof)?;
+        let key_hash = Sha256::digest(&proof.pubkey);
+
+        // In anti-sybil mode every key can bid from a single address.
+        if cfg.pubkey_bids {
+            match PUBKEY_BIDS.may_load(deps.storage, &key_hash)? {
+                Some(bidder) if bidder != info.sender => {
+                    return Err(ContractError::PubkeyAlreadyUsed {})
+                }
+                _ => PUBKEY_BIDS.save(deps.storage, &key_hash, &info.sender)?,
+            }
+        }
+
+        // With hidden bids the key discloses the bin to the bidder.
+        if cfg.hidden_bids {
+            BID_KEYS.save(deps.storage, &info.sender, &key_hash.to_vec())?;
+        }
     }
 
     // If selected bin not permitted, bid not allowed.
-    let bins = BINS.load(deps.storage)?;
     if bin > bins {
         return Err(ContractError::BinDoesNotExist { bins });
     }
 
-    // If sender sent funds higher than ticket price, return change.
-    let mut transfer_msg: Vec<CosmosMsg> = vec![];
-    if funds_sent.amount > ticket_price.amount {
-        transfer_msg.push(get_bank_transfer_to_msg(
-            &info.sender,
-            &funds_sent.denom,
-            funds_sent.amount - ticket_price.amount,
-        ))
-    }
-
     BIDS.save(deps.storage, &info.sender, &bin)?;
+    add_bin_bid(deps.storage, bin)?;
+    let receipt = BidReceipt {
+        paid: ticket_id.is_none().then(|| ticket_price.clone()),
+        placed_at: env.block.height,
+    };
+    BID_RECEIPTS.save(deps.storage, &info.sender, &receipt)?;
+
+    // Bids placed in the final part of the stage pay the late fee, tickets included.
+    let late_bid_penalty = match &cfg.late_bid_penalty {
+        Some(penalty) if penalty.is_due(&stage_bid, &env.block)? => Some(penalty),
+        _ => None,
+    };
+    let late_fee = late_bid_penalty.map_or_else(Uint128::zero, |penalty| penalty.fee);
+
+    let ticket_paid = match ticket_id {
+        // A ticket bought from the vendor replaces the payment. Its price has been
+        // collected by the vendor, so it is not added to the prize.
+        Some(ticket_id) => {
+            use_vendor_ticket(deps.branch(), &cfg, &info, ticket_id)?;
+            Uint128::zero()
+        }
+        None => ticket_price.amount,
+    };
+    let due = Coin {
+        denom: ticket_price.denom.clone(),
+        amount: ticket_paid.checked_add(late_fee)?,
+    };
+
+    // If ticket price not paid, bid is not allowed. Everything sent beyond the due
+    // amount, unrelated coins included, is returned.
+    let change = split_payment(&info, &due)?;
+    let mut transfer_msg: Vec<CosmosMsg> = vec![];
+    transfer_msg.extend(refund_msg(&info.sender, change));
 
-    // Add payed ticket to the final prize.
-    TOTAL_TICKET_PRIZE.update(deps.storage, |mut actual_prize| -> StdResult<_> {
-        actual_prize += ticket_price.amount;
-        Ok(actual_prize)
+    // Add payed ticket to the final prize, along with the late fee if not sent to the treasury.
+    let mut prize = ticket_paid;
+    match late_bid_penalty.and_then(|penalty| penalty.treasury.as_ref()) {
+        Some(treasury) => {
+            let fee = Asset::native(ticket_price.denom.clone(), late_fee);
+            transfer_msg.push(fee.transfer_msg(treasury)?)
+        }
+        None => prize = prize.checked_add(late_fee)?,
+    }
+    COUNTERS.update(deps.storage, |mut counters| -> Result<_, ContractError> {
+        counters.total_ticket_prize = counters.total_ticket_prize.checked_add(prize)?;
+        counters.total_ticket_payments = counters.total_ticket_payments.checked_add(ticket_paid)?;
+        Ok(counters)
     })?;
 
-    let res = Response::new()
+    let mut res = Response::new()
         .add_messages(transfer_msg)
-        .add_attribute("action", "bid")
-        .add_attribute("player", info.sender)
-        .add_attribute("bin", bin.to_string());
+        .add_attribute(ATTR_ACTION, "bid")
+        .add_attribute(ATTR_PLAYER, info.sender)
+        .add_attribute(ATTR_BIN, bin.to_string());
+    if !late_fee.is_zero() {
+        res = res.add_attribute(ATTR_LATE_FEE, late_fee);
+    }
     Ok(res)
 }
 
@@ -228,25 +748,36 @@ pub fn execute_change_bid(
     info: MessageInfo,
     bin: u8,
 ) -> Result<Response, ContractError> {
-    let stage_bid = STAGE_BID.load(deps.storage)?;
-    let stage_name = String::from("bid");
-    check_if_valid_stage(env, stage_bid, stage_name)?;
+    let GameParams { stage_bid, bins, .. } = GAME_PARAMS.load(deps.storage)?;
+    stage_bid.ensure_active(&env.block, StageName::Bid)?;
 
     // If a previous bid doesn't exists for the sender, nothing can be changed.
-    if !BIDS.has(deps.storage, &info.sender) {
-        return Err(ContractError::BidNotPresent {});
-    };
+    let old_bin = BIDS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::BidNotPresent {})?;
 
-    BIDS.update(
-        deps.storage,
-        &info.sender,
-        |_bin: Option<u8>| -> StdResult<u8> { Ok(bin) },
-    )?;
+    // As for a new bid, the selected bin must exist.
+    if bin > bins {
+        return Err(ContractError::BinDoesNotExist { bins });
+    }
+
+    // Changes are counted per address, so removing the bid does not reset them.
+    let changes = BID_CHANGES.may_load(deps.storage, &info.sender)?.unwrap_or_default();
+    if let Some(max) = CONFIG.load(deps.storage)?.max_bid_changes {
+        if changes >= max {
+            return Err(ContractError::TooManyBidChanges { max });
+        }
+    }
+    BID_CHANGES.save(deps.storage, &info.sender, &changes.saturating_add(1))?;
+
+    BIDS.save(deps.storage, &info.sender, &bin)?;
+    remove_bin_bid(deps.storage, old_bin)?;
+    add_bin_bid(deps.storage, bin)?;
 
     let res = Response::new()
-        .add_attribute("action", "change_bid")
-        .add_attribute("player", info.sender)
-        .add_attribute("new_bin", bin.to_string());
+        .add_attribute(ATTR_ACTION, "change_bid")
+        .add_attribute(ATTR_PLAYER, info.sender)
+        .add_attribute(ATTR_NEW_BIN, bin.to_string());
     Ok(res)
 }
 
@@ -255,555 +786,10270 @@ pub fn execute_remove_bid(
     env: Env,
     info: MessageInfo,
 ) -> Result<Response, ContractError> {
-    let stage_bid = STAGE_BID.load(deps.storage)?;
-    let stage_name = String::from("bid");
-    check_if_valid_stage(env, stage_bid, stage_name)?;
+    let GameParams { stage_bid, ticket_price, .. } = GAME_PARAMS.load(deps.storage)?;
+    stage_bid.ensure_active(&env.block, StageName::Bid)?;
 
     // IF: check if a bid for the sender is not present.
     // ELSE: if the bid is present, remove it and send back the ticket price to the sender.
-    if !BIDS.has(deps.storage, &info.sender) {
-        return Err(ContractError::BidNotPresent {});
-    }
+    let bin = BIDS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::BidNotPresent {})?;
 
+    let receipt = BID_RECEIPTS.may_load(deps.storage, &info.sender)?;
     BIDS.remove(deps.storage, &info.sender);
+    remove_bin_bid(deps.storage, bin)?;
+    BID_RECEIPTS.remove(deps.storage, &info.sender);
+    BID_KEYS.remove(deps.storage, &info.sender);
+
+    // A bid placed with a vendor ticket frees the ticket instead of refunding the price.
+    if let Some(ticket_id) = TICKET_BIDS.may_load(deps.storage, &info.sender)? {
+        TICKET_BIDS.remove(deps.storage, &info.sender);
+        USED_TICKETS.remove(deps.storage, ticket_id);
+        let res = Response::new()
+            .add_attribute(ATTR_ACTION, "remove_bid")
+            .add_attribute(ATTR_PLAYER, info.sender)
+            .add_attribute(ATTR_TICKET_ID, ticket_id.to_string());
+        return Ok(res);
+    }
+
+    // Imported bids have been paid by the owner: their ticket stays in the prize.
+    if let Some(BidReceipt { paid: None, .. }) = receipt {
+        let res = Response::new()
+            .add_attribute(ATTR_ACTION, "remove_bid")
+            .add_attribute(ATTR_PLAYER, info.sender)
+            .add_attribute(ATTR_TICKET_PRICE_PAYBACK, Uint128::zero());
+        return Ok(res);
+    }
 
     // Remove from ticket prize a ticket.
-    let ticket_price = TICKET_PRICE.load(deps.storage)?;
-    TOTAL_TICKET_PRIZE.update(deps.storage, |mut actual_prize| -> StdResult<_> {
-        actual_prize -= ticket_price.amount;
-        Ok(actual_prize)
+    COUNTERS.update(deps.storage, |mut counters| -> Result<_, ContractError> {
+        counters.total_ticket_prize = counters.total_ticket_prize.checked_sub(ticket_price.amount)?;
+        counters.total_ticket_payments = counters.total_ticket_payments.checked_sub(ticket_price.amount)?;
+        Ok(counters)
     })?;
 
-    let msg = get_bank_transfer_to_msg(
-        &info.sender,
-        &ticket_price.denom,
-        ticket_price.amount,
-    );
+    let msg = Asset::from(&ticket_price).transfer_msg(&info.sender)?;
 
     let res = Response::new()
         .add_message(msg)
-        .add_attribute("action", "remove_bid")
-        .add_attribute("player", info.sender)
-        .add_attribute("ticket_price_payback", ticket_price.amount);
+        .add_attribute(ATTR_ACTION, "remove_bid")
+        .add_attribute(ATTR_PLAYER, info.sender)
+        .add_attribute(ATTR_TICKET_PRICE_PAYBACK, ticket_price.amount);
     Ok(res)
 }
 
-// ======================================================================================
-// Merkle root and claiming phase
-// ======================================================================================
-pub fn execute_register_merkle_roots(
+pub fn execute_import_bids(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
-    merkle_root_airdrop: String,
-    total_amount_airdrop: Option<Uint128>,
-    merkle_root_game: String,
-    total_amount_game: Option<Uint128>,
+    source_contract: String,
+    limit: Option<u32>,
 ) -> Result<Response, ContractError> {
-    // Just the contract owner can load the Merkle root.
     let cfg = CONFIG.load(deps.storage)?;
+    let limit = limit.unwrap_or_else(|| DEFAULT_LIMIT.min(cfg.max_batch_size));
+    check_batch_size(limit, cfg.max_batch_size)?;
+
+    // Just the contract owner can import bids.
     let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
     if info.sender != owner {
         return Err(ContractError::Unauthorized {});
     }
 
-    // TODO: check sul periodo in cui poter depositare la merkle root. 
-    // Fissiamo che è possibile solo fino alll'inizio del claim?
+    // Bids are imported while setting up the game, before players can bid.
+    let GameParams { stage_bid, ticket_price, bins, .. } = GAME_PARAMS.load(deps.storage)?;
+    if stage_bid.has_started(&env.block) {
+        return Err(ContractError::StageStarted { stage_name: StageName::Bid.into() });
+    }
 
-    // Check merkle root airdrop length.
-    let mut root_buf: [u8; 32] = [0; 32];
-    hex::decode_to_slice(&merkle_root_airdrop, &mut root_buf)?;
+    let source = deps.api.addr_validate(&source_contract)?;
+    if source == env.contract.address {
+        return Err(ContractError::InvalidInput {});
+    }
 
-    // Check merkle root game length.
-    let mut root_buf: [u8; 32] = [0; 32];
-    hex::decode_to_slice(&merkle_root_game, &mut root_buf)?;
+    // While the source accepts bids, a player could remove its bid there and here too.
+    let source_stages: StagesResponse = deps.querier.query_wasm_smart(&source, &QueryMsg::Stages {})?;
+    if !source_stages.stage_bid_end.is_triggered(&env.block) {
+        return Err(ContractError::SourceBidStageActive {});
+    }
 
-    // Save total amount of tokens to be airdropped.
-    let amount_airdrop = total_amount_airdrop.unwrap_or_else(Uint128::zero);
+    // Every call continues from the last imported address, so a replayed message
+    // cannot import the same bids twice.
+    let start_after = BID_IMPORTS.may_load(deps.storage, &source)?;
+    let page: AllBidsResponse = deps.querier.query_wasm_smart(
+        &source,
+        &QueryMsg::AllBids { start_after, limit: Some(limit) },
+    )?;
+
+    // The players paid their ticket to the source, so they have nothing to be refunded here.
+    let receipt = BidReceipt {
+        paid: None,
+        placed_at: env.block.height,
+    };
+    let mut imported: u128 = 0;
+    for bid in &page.bids {
+        if bid.bin > bins {
+            return Err(ContractError::BinDoesNotExist { bins });
+        }
+        // A bid already imported from another contract is kept.
+        let bidder = deps.api.addr_validate(&bid.address)?;
+        if BIDS.has(deps.storage, &bidder) {
+            continue;
+        }
+        BIDS.save(deps.storage, &bidder, &bid.bin)?;
+        add_bin_bid(deps.storage, bid.bin)?;
+        BID_RECEIPTS.save(deps.storage, &bidder, &receipt)?;
+        imported += 1;
+    }
+    if let Some(last) = page.bids.last() {
+        BID_IMPORTS.save(deps.storage, &source, &last.address)?;
+    }
 
-    // Save total amount of token to be airdropped to game winners.
-    let amount_game = total_amount_game.unwrap_or_else(Uint128::zero);
+    // The tickets of the imported bids are paid by the owner, so that they can be won as
+    // the ones placed in this contract.
+    let tickets = Coin {
+        denom: ticket_price.denom.clone(),
+        amount: ticket_price.amount.checked_mul(Uint128::new(imported))?,
+    };
+    let change = split_payment(&info, &tickets)?;
+    COUNTERS.update(deps.storage, |mut counters| -> Result<_, ContractError> {
+        counters.total_ticket_prize = counters.total_ticket_prize.checked_add(tickets.amount)?;
+        counters.total_ticket_payments = counters.total_ticket_payments.checked_add(tickets.amount)?;
+        Ok(counters)
+    })?;
+    let transfer_msg: Vec<CosmosMsg> = refund_msg(&info.sender, change).into_iter().collect();
 
-    MERKLE_ROOT_AIRDROP.save(deps.storage, &merkle_root_airdrop)?;
-    MERKLE_ROOT_GAME.save(deps.storage, &merkle_root_game)?;
-    TOTAL_AIRDROP_AMOUNT.save(deps.storage, &amount_airdrop)?;
-    TOTAL_AIRDROP_GAME_AMOUNT.save(deps.storage, &amount_game)?;
-    CLAIMED_AIRDROP_AMOUNT.save(deps.storage, &Uint128::zero())?;
-    CLAIMED_PRIZE_AMOUNT.save(deps.storage, &Uint128::zero())?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "import_bids",
+        format!("source_contract: {}, imported: {}", source, imported),
+    )?;
 
-    Ok(Response::new().add_attributes(vec![
-        attr("action", "register_merkle_roots"),
-        attr("merkle_root_airdrop", merkle_root_airdrop),
-        attr("total_amount_airdrop", amount_airdrop),
-        attr("merkle_root_game", merkle_root_game),
-    ]))
+    Ok(Response::new()
+        .add_messages(transfer_msg)
+        .add_attribute(ATTR_ACTION, "import_bids")
+        .add_attribute(ATTR_SOURCE_CONTRACT, source)
+        .add_attribute(ATTR_IMPORTED, imported.to_string())
+        .add_attribute(ATTR_FINISHED, (page.bids.len() < limit as usize).to_string()))
 }
 
-pub fn execute_claim_airdrop(
+/// Checks with the ticket vendor that the sender owns the ticket and marks it as used.
+fn use_vendor_ticket(
     deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    amount: Uint128,
-    proof_airdrop: Vec<String>,
-    proof_game: Vec<String>
-) -> Result<Response, ContractError> {
-    // Check that the correct stage is active.
-    let stage_claim_airdrop = STAGE_CLAIM_AIRDROP.load(deps.storage)?;
-    let stage_name = String::from("claim airdrop");
-    check_if_valid_stage(env, stage_claim_airdrop, stage_name)?;
+    cfg: &Config,
+    info: &MessageInfo,
+    ticket_id: u64,
+) -> Result<(), ContractError> {
+    let vendor = cfg
+        .ticket_vendor
+        .as_ref()
+        .ok_or(ContractError::TicketVendorNotSet {})?;
+    if USED_TICKETS.has(deps.storage, ticket_id) {
+        return Err(ContractError::TicketAlreadyUsed { ticket_id });
+    }
 
-    // Verify that the user has not already made the claim.
-    let claimed = CLAIM_AIRDROP.may_load(deps.storage, &info.sender)?;
-    if claimed.is_some() {
-        return Err(ContractError::AlreadyClaimed {});
+    let res: VerifyTicketResponse = deps.querier.query_wasm_smart(
+        vendor,
+        &TicketVendorQueryMsg::VerifyTicket {
+            ticket_id,
+            owner: info.sender.to_string(),
+        },
+    )?;
+    if !res.valid {
+        return Err(ContractError::InvalidTicket { ticket_id });
     }
 
-    let cfg = CONFIG.load(deps.storage)?;
-    let merkle_root_airdrop = MERKLE_ROOT_AIRDROP.load(deps.storage)?;
-    let merkle_root_game = MERKLE_ROOT_GAME.load(deps.storage)?;
+    USED_TICKETS.save(deps.storage, ticket_id, &info.sender)?;
+    TICKET_BIDS.save(deps.storage, &info.sender, &ticket_id)?;
+    Ok(())
+}
 
-    // Compare proofs: the proof sent by the user must be the same of the one
-    // produced with info.sender address.
-    let user_input = format!("{}{}", info.sender, amount);
-    let hash = sha2::Sha256::digest(user_input.as_bytes())
-        .as_slice()
-        .try_into()
-        .map_err(|_| ContractError::WrongLength {})?;
-
-    let hash = proof_airdrop.into_iter().try_fold(hash, |hash, p| {
-        let mut proof_buf = [0; 32];
-        hex::decode_to_slice(p, &mut proof_buf)?;
-        let mut hashes = [hash, proof_buf];
-        hashes.sort_unstable();
-        sha2::Sha256::digest(&hashes.concat())
-            .as_slice()
-            .try_into()
-            .map_err(|_| ContractError::WrongLength {})
-    })?;
+// ======================================================================================
+// Merkle root and claiming phase
+// ======================================================================================
+pub fn execute_fund_airdrop_native(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let denom = cfg
+        .airdrop_native_denom
+        .ok_or(ContractError::NativeAirdropNotEnabled {})?;
 
-    let mut root_buf: [u8; 32] = [0; 32];
-    hex::decode_to_slice(merkle_root_airdrop, &mut root_buf)?;
-    if root_buf != hash {
-        return Err(ContractError::VerificationFailed { merkle_root: "airdrop".to_string() });
+    // Funding is not accepted once the airdrop cannot be claimed anymore.
+    let stage_claim_airdrop = GAME_PARAMS.load(deps.storage)?.stage_claim_airdrop;
+    if stage_claim_airdrop.has_ended(&env.block)? {
+        return Err(ContractError::StageEnded { stage_name: StageName::ClaimAirdrop.into() });
     }
 
-    // If the sender has an active bid, check if it wins or not.
-    let sender_bid = BIDS.may_load(deps.storage, &info.sender)?;
-    if sender_bid.is_some() {
-        let sender_bid = sender_bid.unwrap();
+    let amount = must_pay(&info, &denom)?;
+    let funded = COUNTERS.update(deps.storage, |mut counters| -> Result<_, ContractError> {
+        counters.funded_airdrop_native_amount = counters.funded_airdrop_native_amount.checked_add(amount)?;
+        Ok(counters)
+    })?.funded_airdrop_native_amount;
 
-        // The proof is computed by using as a leaf the value bidded by the sender.
-        let user_input = format!("{}{}", info.sender, sender_bid);
-        let hash = sha2::Sha256::digest(user_input.as_bytes())
-            .as_slice()
-            .try_into()
-            .map_err(|_| ContractError::WrongLength {})?;
-
-        let hash = proof_game.into_iter().try_fold(hash, |hash, p| {
-            let mut proof_buf = [0; 32];
-            hex::decode_to_slice(p, &mut proof_buf)?;
-            let mut hashes = [hash, proof_buf];
-            hashes.sort_unstable();
-            sha2::Sha256::digest(&hashes.concat())
-                .as_slice()
-                .try_into()
-                .map_err(|_| ContractError::WrongLength {})
-        })?;
-
-        let mut root_buf: [u8; 32] = [0; 32];
-        hex::decode_to_slice(merkle_root_game, &mut root_buf)?;
-        // If the two root are equal:
-        // - Save the sender as a winner with unclaimed prize.
-        // - Increase the number of winners.
-        if root_buf == hash {
-            CLAIM_PRIZE.save(deps.storage, &info.sender, &false)?;
-            WINNERS.update(deps.storage, |mut winners_number| -> StdResult<_> {
-                winners_number += Uint128::new(1);
-                Ok(winners_number)
-            })?;
-        }
+    let res = Response::new()
+        .add_attribute(ATTR_ACTION, "fund_airdrop_native")
+        .add_attribute(ATTR_SENDER, info.sender)
+        .add_attribute(ATTR_AMOUNT, amount)
+        .add_attribute(ATTR_TOTAL_FUNDED, funded);
+    Ok(res)
+}
+
+/// Credits the cw20 tokens sent with a `Fund` hook to the total of the chosen pool, so
+/// that the registered totals always match the tokens received.
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if info.sender != cfg.cw20_token_address {
+        return Err(ContractError::Unauthorized {});
+    }
+    // Totals are counted in tokens only while the allocations are paid one to one with them.
+    if cfg.airdrop_native_denom.is_some() || PAYOUT_RATE.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::Cw20FundingNotSupported {});
+    }
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+    let amount = wrapper.amount;
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
     }
-        
-    // Mark the sender as a user that has received the airdrop.
-    CLAIM_AIRDROP.save(deps.storage, &info.sender, &true)?;
+    let purpose = match from_binary(&wrapper.msg)? {
+        ReceiveMsg::Fund { purpose } => purpose,
+        ReceiveMsg::RegisterRound { merkle_root, stage } => {
+            return register_round(deps, &env, &cfg, &sender, merkle_root, stage, amount);
+        }
+    };
 
-    // Increase the amount of airdropped tokens claimed.
-    CLAIMED_AIRDROP_AMOUNT.update(deps.storage, |mut claimed_amount| -> StdResult<_> {
-        claimed_amount += amount;
-        Ok(claimed_amount)
-    })?;
+    // Funding is not accepted once the airdrop cannot be claimed anymore.
+    let params = GAME_PARAMS.load(deps.storage)?;
+    if params.stage_claim_airdrop.has_ended(&env.block)? {
+        return Err(ContractError::StageEnded { stage_name: StageName::ClaimAirdrop.into() });
+    }
 
-    let msg = get_cw20_transfer_to_msg(
-        &info.sender,
-        &cfg.cw20_token_address,
-        amount,
-    )?;
+    let airdrop = match purpose {
+        FundPurpose::Airdrop | FundPurpose::GameBonus => {
+            let is_admin = cfg.owner.as_ref() == Some(&sender)
+                || OPERATORS.has(deps.storage, &sender);
+            if !is_admin {
+                return Err(ContractError::Unauthorized {});
+            }
+            record_admin_action(
+                deps.storage,
+                &env,
+                &sender,
+                "fund",
+                format!("purpose: {}, amount: {}", purpose.as_str(), amount),
+            )?;
+            purpose == FundPurpose::Airdrop
+        }
+        FundPurpose::Boost => {
+            // Sponsors can boost the game airdrop along with the prize.
+            if !params.stage_bid.has_started(&env.block) {
+                return Err(ContractError::StageNotStarted { stage_name: StageName::Bid.into() });
+            }
+            false
+        }
+    };
+    let mut counters = COUNTERS.load(deps.storage)?;
+    let total = match airdrop {
+        true => &mut counters.total_airdrop_amount,
+        false => &mut counters.total_airdrop_game_amount,
+    };
+    *total = total.checked_add(amount)?;
+    let funded = *total;
+    COUNTERS.save(deps.storage, &counters)?;
 
     let res = Response::new()
-        .add_message(msg)
-        .add_attribute("action", "claim_airdrop")
-        .add_attribute("player", info.sender)
-        .add_attribute("airdrop_amount", amount);
+        .add_attribute(ATTR_ACTION, "fund")
+        .add_attribute(ATTR_SENDER, sender)
+        .add_attribute(ATTR_PURPOSE, purpose.as_str())
+        .add_attribute(ATTR_AMOUNT, amount)
+        .add_attribute(ATTR_TOTAL_FUNDED, funded);
     Ok(res)
 }
 
-pub fn execute_claim_prize(
+pub fn execute_boost_prize(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo
+    info: MessageInfo,
 ) -> Result<Response, ContractError> {
-    let stage_claim_prize = STAGE_CLAIM_PRIZE.load(deps.storage)?;
-    let stage_name = String::from("claim prize");
-    check_if_valid_stage(env, stage_claim_prize, stage_name)?;
+    // Sponsors can boost the prize from the start of the bids to the end of the airdrop claims.
+    let GameParams { stage_bid, stage_claim_airdrop, ticket_price, .. } = GAME_PARAMS.load(deps.storage)?;
+    if !stage_bid.has_started(&env.block) {
+        return Err(ContractError::StageNotStarted { stage_name: StageName::Bid.into() });
+    }
+    if stage_claim_airdrop.has_ended(&env.block)? {
+        return Err(ContractError::StageEnded { stage_name: StageName::ClaimAirdrop.into() });
+    }
+    // No prize is paid once the tickets are known to be below the minimum.
+    let cfg = CONFIG.load(deps.storage)?;
+    if is_refund_mode(deps.storage, &cfg, &stage_bid, &env)? {
+        return Err(prize_pool_below_minimum(deps.storage, &cfg)?);
+    }
 
-    // Verify that the user has not already made the claim.
-    let claimed = CLAIM_PRIZE.may_load(deps.storage, &info.sender)?;
-    if let Some(already_claimed) = claimed {
-        if already_claimed {
-            return Err(ContractError::AlreadyClaimed {});
-        }
-    } else {
-        return Err(ContractError::NoteEligible {});
-    };
+    let amount = must_pay(&info, &ticket_price.denom)?;
+
+    let previous = BOOSTS.may_load(deps.storage, &info.sender)?.unwrap_or_default();
+    let total_boost = previous.checked_add(amount)?;
+    BOOSTS.save(deps.storage, &info.sender, &total_boost)?;
+    BOOSTERS_BY_AMOUNT.remove(deps.storage, (previous.u128(), &info.sender));
+    BOOSTERS_BY_AMOUNT.save(deps.storage, (total_boost.u128(), &info.sender), &Empty {})?;
+
+    COUNTERS.update(deps.storage, |mut counters| -> Result<_, ContractError> {
+        counters.total_ticket_prize = counters.total_ticket_prize.checked_add(amount)?;
+        Ok(counters)
+    })?;
 
+    let res = Response::new()
+        .add_attribute(ATTR_ACTION, "boost_prize")
+        .add_attribute(ATTR_SPONSOR, info.sender)
+        .add_attribute(ATTR_AMOUNT, amount)
+        .add_attribute(ATTR_TOTAL_BOOST, total_boost);
+    Ok(res)
+}
+
+pub fn execute_boost_bin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    bin: u8,
+) -> Result<Response, ContractError> {
+    // Winners of a bin are not known with the Merkle amounts prize mode.
     let cfg = CONFIG.load(deps.storage)?;
-    let winners = WINNERS.load(deps.storage)?;
-    let ticket_price = TICKET_PRICE.load(deps.storage)?;
-    let ticket_prize = TOTAL_TICKET_PRIZE.load(deps.storage)?;
-    let airdrop_prize = TOTAL_AIRDROP_GAME_AMOUNT.load(deps.storage)?;
+    if cfg.prize_mode == PrizeMode::MerkleAmounts {
+        return Err(ContractError::BinBoostNotAvailable {});
+    }
 
-    // Every winner will receive two prize: one given by the tickets of the game and
-    // one given by an incentive from the tokens airdrop. For both of them the
-    // amount received is given by the total divided by the number of winners.
-    let sender_ticket_prize = ticket_prize.checked_div(winners).unwrap();
-    let sender_airdrop_prize = airdrop_prize.checked_div(winners).unwrap();
+    // Bins can be boosted from the start of the bids until winners start to be revealed.
+    let GameParams { stage_bid, stage_claim_airdrop, ticket_price, bins, .. } =
+        GAME_PARAMS.load(deps.storage)?;
+    if !stage_bid.has_started(&env.block) {
+        return Err(ContractError::StageNotStarted { stage_name: StageName::Bid.into() });
+    }
+    if stage_claim_airdrop.has_started(&env.block) {
+        return Err(ContractError::StageStarted { stage_name: StageName::ClaimAirdrop.into() });
+    }
 
-    let mut transfer_msgs: Vec<CosmosMsg> = vec![];
-    transfer_msgs.push(get_bank_transfer_to_msg(
-        &info.sender,
-        &ticket_price.denom,
-        sender_ticket_prize,
-    ));
-    transfer_msgs.push(get_cw20_transfer_to_msg(
-        &info.sender,
-        &cfg.cw20_token_address,
-        sender_airdrop_prize,
-    )?);
+    if bin > bins {
+        return Err(ContractError::BinDoesNotExist { bins });
+    }
 
-    CLAIM_PRIZE.update(deps.storage, &info.sender, |mut _already_claimed| -> StdResult<_>{
-        Ok(true)
-    })?;
+    let amount = must_pay(&info, &ticket_price.denom)?;
 
-    // Update botht the airdrop and the prize claimed amount.
-    CLAIMED_AIRDROP_AMOUNT.update(deps.storage, |mut claimed_amount| -> StdResult<_> {
-        claimed_amount += sender_airdrop_prize;
-        Ok(claimed_amount)
+    BIN_BOOSTS.update(deps.storage, (bin, &info.sender), |boost| -> Result<_, ContractError> {
+        Ok(boost.unwrap_or_default().checked_add(amount)?)
+    })?;
+    let bin_pool = BIN_POOLS.update(deps.storage, bin, |pool| -> Result<_, ContractError> {
+        Ok(pool.unwrap_or_default().checked_add(amount)?)
     })?;
-    CLAIMED_PRIZE_AMOUNT.update(deps.storage, |mut claimed_amount| -> StdResult<_> {
-        claimed_amount += sender_ticket_prize;
-        Ok(claimed_amount)
+    COUNTERS.update(deps.storage, |mut counters| -> Result<_, ContractError> {
+        counters.total_bin_boost = counters.total_bin_boost.checked_add(amount)?;
+        Ok(counters)
     })?;
 
     let res = Response::new()
-        .add_messages(transfer_msgs)
-        .add_attribute("action", "claim_prize")
-        .add_attribute("player", info.sender)
-        .add_attribute("prize_from_tickets", sender_ticket_prize)
-        .add_attribute("prize_from_airdrop", sender_airdrop_prize);
+        .add_attribute(ATTR_ACTION, "boost_bin")
+        .add_attribute(ATTR_SPONSOR, info.sender)
+        .add_attribute(ATTR_BIN, bin.to_string())
+        .add_attribute(ATTR_AMOUNT, amount)
+        .add_attribute(ATTR_BIN_POOL, bin_pool);
     Ok(res)
 }
 
-// ======================================================================================
-// Withdraw of unclaimed tokens
-// ======================================================================================
-pub fn execute_withdraw_airdrop(
+pub fn execute_refund_bin_boost(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
-    address: &Addr,
+    bin: u8,
 ) -> Result<Response, ContractError> {
-    // Just the contract owner can withdraw the remaining tokens.
-    let cfg = CONFIG.load(deps.storage)?;
-    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
-    if info.sender != owner {
-        return Err(ContractError::Unauthorized {});
-    }
+    // Winners are all known once the claim airdrop stage is over.
+    let GameParams { stage_claim_prize, ticket_price, .. } = GAME_PARAMS.load(deps.storage)?;
+    stage_claim_prize.ensure_active(&env.block, StageName::ClaimPrize)?;
 
-    // Check that the claiming prize stage has ended.
-    let stage_claim_prize = STAGE_CLAIM_PRIZE.load(deps.storage)?;
-    let stage_claim_prize_end = (stage_claim_prize.start + stage_claim_prize.duration)?;
-    if !stage_claim_prize_end.is_triggered(&_env.block) {
-        return Err(ContractError::ClaimPrizeStageNotFinished {});
+    // No side-pool is paid when the tickets are refunded.
+    let snapshot = CLOSED_SNAPSHOT.load(deps.storage)?;
+    if snapshot.bin_winners(bin).is_some() && !snapshot.refund_mode {
+        return Err(ContractError::BinWon { bin });
     }
 
-    let total_amount_airdrop = TOTAL_AIRDROP_AMOUNT.load(deps.storage)?;
-    let total_amount_prize = TOTAL_AIRDROP_GAME_AMOUNT.load(deps.storage)?;
-    let claimed_amount = CLAIMED_AIRDROP_AMOUNT.load(deps.storage)?;
-    let amount = total_amount_airdrop + total_amount_prize - claimed_amount;
+    let amount = BIN_BOOSTS
+        .may_load(deps.storage, (bin, &info.sender))?
+        .ok_or(ContractError::NothingToRefund {})?;
+    BIN_BOOSTS.remove(deps.storage, (bin, &info.sender));
+    COUNTERS.update(deps.storage, |mut counters| -> Result<_, ContractError> {
+        counters.claimed_bin_boost = counters.claimed_bin_boost.checked_add(amount)?;
+        Ok(counters)
+    })?;
 
-    let msg = get_cw20_transfer_to_msg(
-        &address,
-        &cfg.cw20_token_address,
-        amount,
-    )?;
+    let msg = Asset::native(ticket_price.denom, amount).transfer_msg(&info.sender)?;
 
     let res = Response::new()
         .add_message(msg)
-        .add_attribute("action", "withdraw_airdrop")
-        .add_attribute("address", address)
-        .add_attribute("amount", amount);
+        .add_attribute(ATTR_ACTION, "refund_bin_boost")
+        .add_attribute(ATTR_SPONSOR, info.sender)
+        .add_attribute(ATTR_BIN, bin.to_string())
+        .add_attribute(ATTR_AMOUNT, amount);
+    Ok(res)
+}
+
+pub fn execute_refund_prize_boost(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // Boosts are paid back along with the tickets, when no prize is paid.
+    let GameParams { stage_claim_prize, ticket_price, .. } = GAME_PARAMS.load(deps.storage)?;
+    stage_claim_prize.ensure_active(&env.block, StageName::ClaimPrize)?;
+    if !CLOSED_SNAPSHOT.load(deps.storage)?.refund_mode {
+        return Err(ContractError::NothingToRefund {});
+    }
 
+    let amount = BOOSTS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::NothingToRefund {})?;
+    BOOSTS.remove(deps.storage, &info.sender);
+    BOOSTERS_BY_AMOUNT.remove(deps.storage, (amount.u128(), &info.sender));
+    COUNTERS.update(deps.storage, |mut counters| -> Result<_, ContractError> {
+        counters.claimed_prize_amount = counters.claimed_prize_amount.checked_add(amount)?;
+        Ok(counters)
+    })?;
+
+    let msg = Asset::native(ticket_price.denom, amount).transfer_msg(&info.sender)?;
+
+    let res = Response::new()
+        .add_message(msg)
+        .add_attribute(ATTR_ACTION, "refund_prize_boost")
+        .add_attribute(ATTR_SPONSOR, info.sender)
+        .add_attribute(ATTR_AMOUNT, amount);
     Ok(res)
 }
 
-// TODO: si potrebbe unire a quello sopra.
-pub fn execute_withdraw_prize(
+pub fn execute_commit_outcome(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
-    address: &Addr,
+    commitment: String,
 ) -> Result<Response, ContractError> {
-    // Just the contract owner can withdraw the remaining tokens.
+    // Just the contract owner or an operator can commit the outcome.
     let cfg = CONFIG.load(deps.storage)?;
     let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
-    if info.sender != owner {
+    if info.sender != owner && !OPERATORS.has(deps.storage, &info.sender) {
         return Err(ContractError::Unauthorized {});
     }
 
-    // Check that the claiming prize stage has ended.
-    let stage_claim_prize = STAGE_CLAIM_PRIZE.load(deps.storage)?;
-    let stage_claim_prize_end = (stage_claim_prize.start + stage_claim_prize.duration)?;
-    if !stage_claim_prize_end.is_triggered(&_env.block) {
-        return Err(ContractError::ClaimPrizeStageNotFinished {});
+    if OUTCOME_COMMITMENT.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::OutcomeAlreadyCommitted {});
     }
 
-    let total_prize = TOTAL_TICKET_PRIZE.load(deps.storage)?;
-    let claimed_prize = CLAIMED_PRIZE_AMOUNT.load(deps.storage)?;
-    let amount = total_prize - claimed_prize;
+    // The outcome cannot be chosen once the bids are known.
+    let stage_bid = GAME_PARAMS.load(deps.storage)?.stage_bid;
+    if stage_bid.has_ended(&env.block)? {
+        return Err(ContractError::StageEnded { stage_name: StageName::Bid.into() });
+    }
 
-    let ticket_price = TICKET_PRICE.load(deps.storage)?;
+    // Check commitment length.
+    let mut commitment_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(&commitment, &mut commitment_buf)?;
 
-    let msg = get_bank_transfer_to_msg(
-        &address,
-        &ticket_price.denom,
-        amount,
-    );
+    OUTCOME_COMMITMENT.save(deps.storage, &commitment)?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "commit_outcome",
+        format!("commitment: {}", commitment),
+    )?;
 
     let res = Response::new()
-        .add_message(msg)
-        .add_attribute("action", "withdraw_prize")
-        .add_attribute("address", address)
-        .add_attribute("amount", amount);
-
-    Ok(res)
+        .add_attribute(ATTR_ACTION, "commit_outcome")
+        .add_attribute(ATTR_COMMITMENT, commitment);
+    Ok(trim_attributes(deps.storage, res)?)
 }
 
-// ======================================================================================
-// Queries
-// ======================================================================================
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::Config {} => to_binary(&query_config(deps)?),
-        QueryMsg::Stages {} => to_binary(&query_stages(deps)?),
-        QueryMsg::Bid { address } => to_binary(&query_bid(deps, address)?),
-        QueryMsg::MerkleRoots {} => to_binary(&query_merkle_root(deps)?),
-        QueryMsg::GameAmounts {} => to_binary(&query_game_amounts(deps)?),
+pub fn execute_reveal_outcome(
+    deps: DepsMut,
+    env: Env,
+    merkle_root_game: String,
+    salt: String,
+) -> Result<Response, ContractError> {
+    let commitment = OUTCOME_COMMITMENT
+        .may_load(deps.storage)?
+        .ok_or(ContractError::OutcomeNotCommitted {})?;
+
+    // Revealing during the bid stage would allow informed bids.
+    let stage_bid = GAME_PARAMS.load(deps.storage)?.stage_bid;
+    if !stage_bid.has_ended(&env.block)? {
+        return Err(ContractError::BidStageNotFinished {});
+    }
+
+    let hash = Sha256::digest(format!("{}{}", merkle_root_game, salt).as_bytes());
+    if hex::encode(hash) != commitment.to_lowercase() {
+        return Err(ContractError::CommitmentMismatch {});
     }
+    REVEALED_OUTCOME.save(deps.storage, &merkle_root_game)?;
+
+    let res = Response::new()
+        .add_attribute(ATTR_ACTION, "reveal_outcome")
+        .add_attribute(ATTR_MERKLE_ROOT_GAME, merkle_root_game);
+    Ok(trim_attributes(deps.storage, res)?)
 }
 
-pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+#[allow(clippy::too_many_arguments)]
+pub fn execute_register_merkle_roots(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    merkle_root_airdrop: String,
+    total_amount_airdrop: Option<Uint128>,
+    merkle_root_game: String,
+    total_amount_game: Option<Uint128>,
+    tree_metadata: TreeMetadata,
+    overwrite: bool,
+    secondary_payout: Option<SecondaryPayoutMsg>,
+    claim_condition: Option<ClaimCondition>,
+) -> Result<Response, ContractError> {
+    // Just the contract owner or an operator can load the Merkle root, or the multisig
+    // alone in governance mode.
     let cfg = CONFIG.load(deps.storage)?;
-    Ok(ConfigResponse {
-        owner: cfg.owner.map(|o| o.to_string()),
-        cw20_token_address: cfg.cw20_token_address.to_string(),
-    })
-}
+    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
+    match &cfg.governance_mode {
+        GovernanceMode::Multisig { address } => {
+            if info.sender != *address {
+                return Err(ContractError::GovernanceRequired {});
+            }
+        }
+        GovernanceMode::Disabled => {
+            if info.sender != owner && !OPERATORS.has(deps.storage, &info.sender) {
+                return Err(ContractError::Unauthorized {});
+            }
+        }
+    }
 
-/// Returns stages's information.
-pub fn query_stages(deps: Deps) -> StdResult<StagesResponse> {
-    let stage_bid = STAGE_BID.load(deps.storage)?;
-    let stage_claim_airdrop = STAGE_CLAIM_AIRDROP.load(deps.storage)?;
-    let stage_claim_prize = STAGE_CLAIM_PRIZE.load(deps.storage)?;
-    Ok(StagesResponse {
-        stage_bid,
-        stage_claim_airdrop,
-        stage_claim_prize,
-    })
-}
+    // TODO: check sul periodo in cui poter depositare la merkle root. 
+    // Fissiamo che è possibile solo fino alll'inizio del claim?
+    if CLOSED_SNAPSHOT.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::ClaimWindowClosed {});
+    }
 
-pub fn query_bid(deps: Deps, address: String) -> StdResult<BidResponse> {
-    let bid = BIDS.may_load(deps.storage, &deps.api.addr_validate(&address)?)?;
-    Ok(BidResponse { bid })
-}
+    // Registered roots are replaced only on purpose and before anyone relied on them.
+    if MERKLE_ROOT_AIRDROP.may_load(deps.storage)?.is_some() {
+        if !overwrite {
+            return Err(ContractError::MerkleRootAlreadyRegistered {});
+        }
+        let claimed = CLAIM_AIRDROP
+            .keys(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_some()
+            || CLAIM_PRIZE
+                .keys(deps.storage, None, None, Order::Ascending)
+                .next()
+                .is_some();
+        if claimed {
+            return Err(ContractError::ClaimsAlreadyStarted {});
+        }
+    }
 
-pub fn query_merkle_root(deps: Deps) -> StdResult<MerkleRootsResponse> {
-    let merkle_root_airdrop = MERKLE_ROOT_AIRDROP.load(deps.storage)?;
-    let total_amount = TOTAL_AIRDROP_AMOUNT.load(deps.storage)?;
-    let merkle_root_game = MERKLE_ROOT_GAME.load(deps.storage)?;
+    // Check merkle root airdrop length.
+    let mut root_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(&merkle_root_airdrop, &mut root_buf)?;
 
-    let resp = MerkleRootsResponse {
-        merkle_root_airdrop,
-        total_amount,
-        merkle_root_game
+    // Check merkle root game length.
+    let mut root_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(&merkle_root_game, &mut root_buf)?;
+
+    // Check allocation checksum length.
+    let mut checksum_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(&tree_metadata.allocation_checksum, &mut checksum_buf)?;
+    let tree_metadata = TreeMetadata {
+        allocation_checksum: tree_metadata.allocation_checksum.to_lowercase(),
+        ..tree_metadata
     };
 
-    Ok(resp)
-}
+    // A committed outcome cannot be replaced.
+    if OUTCOME_COMMITMENT.may_load(deps.storage)?.is_some() {
+        let revealed = REVEALED_OUTCOME
+            .may_load(deps.storage)?
+            .ok_or(ContractError::OutcomeNotRevealed {})?;
+        if revealed != merkle_root_game {
+            return Err(ContractError::CommitmentMismatch {});
+        }
+    }
 
-pub fn query_game_amounts(deps: Deps) -> StdResult<GameAmountsResponse> {
-    // Prizes
-    let total_ticket_prize = TOTAL_TICKET_PRIZE.load(deps.storage)?;
-    let total_airdrop_amount = TOTAL_AIRDROP_AMOUNT.load(deps.storage)?;
-    let total_airdrop_game_amount = TOTAL_AIRDROP_GAME_AMOUNT.load(deps.storage)?;
-    // Number of winners
-    let winners_amount = WINNERS.load(deps.storage)?;
-    // Claimed amount.
-    let total_claimed_airdrop = CLAIMED_AIRDROP_AMOUNT.load(deps.storage)?;
-    let total_claimed_prize = CLAIMED_PRIZE_AMOUNT.load(deps.storage)?;
+    // The secondary asset is a different cw20 paid in proportion to the allocations.
+    let primary_token = &cfg.cw20_token_address;
+    let secondary_payout = secondary_payout
+        .map(|secondary| -> Result<_, ContractError> {
+            let cw20_token_address = deps.api.addr_validate(&secondary.cw20_token_address)?;
+            if secondary.rate.is_zero() || cw20_token_address == *primary_token {
+                return Err(ContractError::InvalidInput {});
+            }
+            Ok(SecondaryPayout {
+                cw20_token_address,
+                rate: secondary.rate,
+                total_amount: secondary.total_amount,
+                claimed_amount: Uint128::zero(),
+            })
+        })
+        .transpose()?;
 
-    let resp = GameAmountsResponse {
-        total_ticket_prize,
-        total_airdrop_amount,
-        total_airdrop_game_amount,
-        winners_amount,
-        total_claimed_airdrop,
-        total_claimed_prize
-     };
+    if let Some(condition) = &claim_condition {
+        check_condition_size(condition)?;
+        validate_condition(deps.as_ref(), condition)?;
+    }
 
-    Ok(resp)
-}
+    // Totals not given are the ones funded with the cw20 `Fund` hook.
+    let mut counters = COUNTERS.load(deps.storage)?;
+    let amount_airdrop = total_amount_airdrop.unwrap_or(counters.total_airdrop_amount);
+    let amount_game = total_amount_game.unwrap_or(counters.total_airdrop_game_amount);
 
-// ======================================================================================
-// Utils
-// ======================================================================================
-pub fn check_if_valid_stage(
-    env: Env,
-    stage: Stage,
-    stage_name: String,
-) -> Result<(), ContractError> {
-    // The stage has not started.
-    if !stage.start.is_triggered(&env.block) {
-        return Err(ContractError::StageNotStarted { stage_name });
+    #[cfg(feature = "cosmwasm_1_1")]
+    if let Some(denom) = &cfg.airdrop_native_denom {
+        let required = amount_airdrop.checked_add(amount_game)?;
+        let supply = query_supply(deps.as_ref(), denom)?;
+        if supply < required {
+            return Err(ContractError::ExceedsSupply { supply, required });
+        }
     }
 
-    // The stage has ended.
-    let stage_end = (stage.start + stage.duration)?;
-    if stage_end.is_triggered(&env.block) {
-        return Err(ContractError::StageEnded { stage_name });
+    MERKLE_ROOT_AIRDROP.save(deps.storage, &merkle_root_airdrop)?;
+    MERKLE_ROOT_GAME.save(deps.storage, &merkle_root_game)?;
+    TREE_METADATA.save(deps.storage, &tree_metadata)?;
+    counters.total_airdrop_amount = amount_airdrop;
+    counters.total_airdrop_game_amount = amount_game;
+    counters.claimed_airdrop_amount = Uint128::zero();
+    counters.claimed_prize_amount = Uint128::zero();
+    COUNTERS.save(deps.storage, &counters)?;
+    match &secondary_payout {
+        Some(secondary) => SECONDARY_PAYOUT.save(deps.storage, secondary)?,
+        None => SECONDARY_PAYOUT.remove(deps.storage),
+    }
+    match &claim_condition {
+        Some(condition) => CLAIM_CONDITION.save(deps.storage, condition)?,
+        None => CLAIM_CONDITION.remove(deps.storage),
     }
+    // New totals must be funded again.
+    CLAIMS_FUNDED.save(deps.storage, &false)?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "register_merkle_roots",
+        format!(
+            "merkle_root_airdrop: {}, total_amount_airdrop: {}, merkle_root_game: {}, total_amount_game: {}",
+            merkle_root_airdrop, amount_airdrop, merkle_root_game, amount_game
+        ),
+    )?;
 
-    Ok(())
+    let res = Response::new().add_attributes(vec![
+        attr(ATTR_ACTION, "register_merkle_roots"),
+        attr(ATTR_MERKLE_ROOT_AIRDROP, merkle_root_airdrop),
+        attr(ATTR_TOTAL_AMOUNT_AIRDROP, amount_airdrop),
+        attr(ATTR_MERKLE_ROOT_GAME, merkle_root_game),
+        attr(ATTR_ALLOCATION_CHECKSUM, tree_metadata.allocation_checksum),
+        attr(ATTR_OVERWRITE, overwrite.to_string()),
+    ]);
+    Ok(trim_attributes(deps.storage, res)?)
 }
 
-fn get_amount_for_denom(coins: &[Coin], denom: &str) -> Coin {
-    let amount: Uint128 = coins
-        .iter()
-        .filter(|c| c.denom == denom)
-        .map(|c| c.amount)
-        .sum();
-    Coin {
-        amount,
-        denom: denom.to_string(),
+pub fn execute_open_claims(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if cfg.airdrop_native_denom.is_some() {
+        return Err(ContractError::OpenClaimsNotRequired {});
     }
-}
 
-fn get_bank_transfer_to_msg(recipient: &Addr, denom: &str, native_amount: Uint128) -> CosmosMsg {
-    let transfer_bank_msg = cosmwasm_std::BankMsg::Send {
-        to_address: recipient.into(),
-        amount: vec![Coin {
-            denom: denom.to_string(),
-            amount: native_amount,
-        }],
-    };
+    let stage_claim_airdrop = GAME_PARAMS.load(deps.storage)?.stage_claim_airdrop;
+    if stage_claim_airdrop.has_ended(&env.block)? {
+        return Err(ContractError::StageEnded { stage_name: StageName::ClaimAirdrop.into() });
+    }
+
+    // Both the airdrop and the game airdrop are paid with the cw20 token.
+    let counters = COUNTERS.load(deps.storage)?;
+    let required = counters.total_airdrop_amount.checked_add(counters.total_airdrop_game_amount)?;
+    let required = payout_amount(deps.storage, required)?;
+    let res: BalanceResponse = deps.querier.query_wasm_smart(
+        &cfg.cw20_token_address,
+        &Cw20QueryMsg::Balance { address: env.contract.address.to_string() },
+    )?;
+    if res.balance < required {
+        return Err(ContractError::AirdropUnderfunded { balance: res.balance, required });
+    }
+    if let Some(secondary) = SECONDARY_PAYOUT.may_load(deps.storage)? {
+        let secondary_res: BalanceResponse = deps.querier.query_wasm_smart(
+            &secondary.cw20_token_address,
+            &Cw20QueryMsg::Balance { address: env.contract.address.to_string() },
+        )?;
+        if secondary_res.balance < secondary.total_amount {
+            return Err(ContractError::AirdropUnderfunded {
+                balance: secondary_res.balance,
+                required: secondary.total_amount,
+            });
+        }
+    }
+
+    CLAIMS_FUNDED.save(deps.storage, &true)?;
 
-    let transfer_bank_cosmos_msg: CosmosMsg = transfer_bank_msg.into();
-    transfer_bank_cosmos_msg
+    Ok(Response::new().add_attributes(vec![
+        attr(ATTR_ACTION, "open_claims"),
+        attr(ATTR_BALANCE, res.balance),
+        attr(ATTR_REQUIRED, required),
+    ]))
 }
 
-fn get_cw20_transfer_to_msg(
+pub fn execute_freeze_claims(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    reason: String,
+) -> Result<Response, ContractError> {
+    // The contract owner or an operator can freeze the claims.
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
+    if info.sender != owner && !OPERATORS.has(deps.storage, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    check_memo_length("reason", &Some(reason.clone()))?;
+
+    CLAIMS_FROZEN.save(deps.storage, &reason)?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "freeze_claims",
+        format!("reason: {}", reason),
+    )?;
+
+    let res = Response::new()
+        .add_attribute(ATTR_ACTION, "freeze_claims")
+        .add_attribute(ATTR_REASON, reason);
+    Ok(trim_attributes(deps.storage, res)?)
+}
+
+pub fn execute_unfreeze_claims(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can unfreeze the claims.
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    CLAIMS_FROZEN.remove(deps.storage);
+    record_admin_action(deps.storage, &env, &info.sender, "unfreeze_claims", String::new())?;
+
+    Ok(Response::new().add_attribute(ATTR_ACTION, "unfreeze_claims"))
+}
+
+pub fn execute_veto_prize(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    reason: String,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can veto the prizes.
+    let cfg = CONFIG.load(deps.storage)?;
+    if cfg.owner.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    check_memo_length("reason", &Some(reason.clone()))?;
+
+    // Released prizes are irrevocable.
+    let timelock = cfg.prize_timelock.ok_or(ContractError::InvalidInput {})?;
+    if prize_release(deps.storage, timelock)?.is_triggered(&env.block) {
+        return Err(ContractError::VetoPeriodEnded {});
+    }
+
+    PRIZE_VETO.save(deps.storage, &reason)?;
+    record_admin_action(deps.storage, &env, &info.sender, "veto_prize", format!("reason: {}", reason))?;
+
+    let res = Response::new()
+        .add_attribute(ATTR_ACTION, "veto_prize")
+        .add_attribute(ATTR_REASON, reason);
+    Ok(trim_attributes(deps.storage, res)?)
+}
+
+pub fn execute_lift_prize_veto(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can lift the veto.
+    let cfg = CONFIG.load(deps.storage)?;
+    if cfg.owner.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    PRIZE_VETO.remove(deps.storage);
+    record_admin_action(deps.storage, &env, &info.sender, "lift_prize_veto", String::new())?;
+
+    Ok(Response::new().add_attribute(ATTR_ACTION, "lift_prize_veto"))
+}
+
+pub fn execute_clear_failsafe(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can clear the failsafe.
+    let cfg = CONFIG.load(deps.storage)?;
+    if cfg.owner.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let condition = FAILSAFE.may_load(deps.storage)?.map(|failsafe| failsafe.condition);
+    FAILSAFE.remove(deps.storage);
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "clear_failsafe",
+        format!("condition: {}", condition.as_deref().unwrap_or("none")),
+    )?;
+
+    Ok(Response::new().add_attribute(ATTR_ACTION, "clear_failsafe"))
+}
+
+/// Compiled only for local devnets: the stages of a game deployed on a public chain
+/// cannot be moved.
+#[cfg(feature = "test_mode")]
+pub fn execute_fast_forward_stage(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can fast forward a game instantiated in test mode.
+    let cfg = CONFIG.load(deps.storage)?;
+    if !cfg.test_mode {
+        return Err(ContractError::TestModeDisabled {});
+    }
+    if cfg.owner.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut params = GAME_PARAMS.load(deps.storage)?;
+    let mut stages = [
+        (StageName::Bid, &mut params.stage_bid),
+        (StageName::ClaimAirdrop, &mut params.stage_claim_airdrop),
+        (StageName::ClaimPrize, &mut params.stage_claim_prize),
+    ];
+    let mut next = None;
+    for (index, (_, stage)) in stages.iter().enumerate() {
+        match stage.status(&env.block)? {
+            StageStatus::Ended => continue,
+            StageStatus::Active => next = Some((index, true)),
+            StageStatus::NotStarted => next = Some((index, false)),
+        }
+        break;
+    }
+    let (mut index, active) = next.ok_or(ContractError::StageEnded {
+        stage_name: StageName::ClaimPrize.into(),
+    })?;
+
+    // The stage in progress ends with this block and the next one starts with it. Stages
+    // only move earlier, so they never overlap.
+    if active {
+        stages[index].1.end_at(&env.block);
+        index += 1;
+    }
+    let started = stages.get_mut(index).map(|(name, stage)| {
+        stage.start_at(&env.block);
+        *name
+    });
+    GAME_PARAMS.save(deps.storage, &params)?;
+
+    let stage = started.map_or("none", StageName::as_str);
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "fast_forward_stage",
+        format!("stage: {}", stage),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute(ATTR_ACTION, "fast_forward_stage")
+        .add_attribute(ATTR_STAGE, stage))
+}
+
+pub fn execute_update_limits(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    max_batch_size: Option<u32>,
+    max_addresses_per_call: Option<u32>,
+    max_proof_length: Option<u32>,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can tune the limits.
+    let mut cfg = CONFIG.load(deps.storage)?;
+    if cfg.owner.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(max) = max_batch_size {
+        check_limit("max_batch_size", max, MAX_BATCH_SIZE)?;
+        cfg.max_batch_size = max;
+    }
+    if let Some(max) = max_addresses_per_call {
+        check_limit("max_addresses_per_call", max, MAX_ADDRESSES_PER_CALL)?;
+        cfg.max_addresses_per_call = max;
+    }
+    if let Some(max) = max_proof_length {
+        check_max_proof_length(max)?;
+        cfg.max_proof_length = max;
+    }
+    CONFIG.save(deps.storage, &cfg)?;
+
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "update_limits",
+        format!(
+            "max_batch_size: {}, max_addresses_per_call: {}, max_proof_length: {}",
+            cfg.max_batch_size, cfg.max_addresses_per_call, cfg.max_proof_length
+        ),
+    )?;
+
+    Ok(Response::new().add_attribute(ATTR_ACTION, "update_limits"))
+}
+
+pub fn execute_import_exclusions(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addresses: Vec<String>,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can exclude addresses.
+    let cfg = CONFIG.load(deps.storage)?;
+    if cfg.owner.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    check_addresses_count(&addresses, cfg.max_addresses_per_call)?;
+
+    let stage_claim_airdrop = GAME_PARAMS.load(deps.storage)?.stage_claim_airdrop;
+    if stage_claim_airdrop.has_ended(&env.block)? {
+        return Err(ContractError::StageEnded { stage_name: StageName::ClaimAirdrop.into() });
+    }
+
+    // Addresses already excluded or that already claimed are skipped.
+    let mut imported = 0u64;
+    for address in &addresses {
+        let address = deps.api.addr_validate(address)?;
+        if EXCLUDED.has(deps.storage, &address) || CLAIM_AIRDROP.has(deps.storage, &address) {
+            continue;
+        }
+        EXCLUDED.save(deps.storage, &address, &Empty {})?;
+        imported += 1;
+    }
+    let count = EXCLUDED_COUNT.may_load(deps.storage)?.unwrap_or_default() + imported;
+    EXCLUDED_COUNT.save(deps.storage, &count)?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "import_exclusions",
+        format!("addresses: {}, imported: {}", addresses.len(), imported),
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr(ATTR_ACTION, "import_exclusions"),
+        attr(ATTR_IMPORTED, imported.to_string()),
+        attr(ATTR_TOTAL_EXCLUDED, count.to_string()),
+    ]))
+}
+
+pub fn execute_tag_cohort(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cohort: u8,
+    addresses: Vec<String>,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can tag addresses.
+    let cfg = CONFIG.load(deps.storage)?;
+    if cfg.owner.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    check_addresses_count(&addresses, cfg.max_addresses_per_call)?;
+
+    // The cohorts are fixed before the first claim, so that every claim is aggregated
+    // in the cohort of its address.
+    let stage_claim_airdrop = GAME_PARAMS.load(deps.storage)?.stage_claim_airdrop;
+    if stage_claim_airdrop.has_started(&env.block) {
+        return Err(ContractError::StageStarted { stage_name: StageName::ClaimAirdrop.into() });
+    }
+
+    // Addresses already in the cohort are skipped, the others are moved to it.
+    let mut stats = COHORT_STATS.may_load(deps.storage, cohort)?.unwrap_or_default();
+    let mut tagged = 0u32;
+    for address in &addresses {
+        let address = deps.api.addr_validate(address)?;
+        match COHORTS.may_load(deps.storage, &address)? {
+            Some(previous) if previous == cohort => continue,
+            Some(previous) => {
+                let mut previous_stats = COHORT_STATS.load(deps.storage, previous)?;
+                previous_stats.tagged = previous_stats
+                    .tagged
+                    .checked_sub(1)
+                    .ok_or_else(|| OverflowError::new(OverflowOperation::Sub, previous_stats.tagged, 1))?;
+                if previous_stats == CohortStats::default() {
+                    COHORT_STATS.remove(deps.storage, previous);
+                } else {
+                    COHORT_STATS.save(deps.storage, previous, &previous_stats)?;
+                }
+            }
+            None => {}
+        }
+        COHORTS.save(deps.storage, &address, &cohort)?;
+        tagged += 1;
+    }
+    stats.tagged = stats
+        .tagged
+        .checked_add(tagged)
+        .ok_or_else(|| OverflowError::new(OverflowOperation::Add, stats.tagged, tagged))?;
+    COHORT_STATS.save(deps.storage, cohort, &stats)?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "tag_cohort",
+        format!("cohort: {}, addresses: {}, tagged: {}", cohort, addresses.len(), tagged),
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr(ATTR_ACTION, "tag_cohort"),
+        attr(ATTR_COHORT, cohort.to_string()),
+        attr(ATTR_IMPORTED, tagged.to_string()),
+    ]))
+}
+
+pub fn execute_swap_payout_token(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_cw20: String,
+    rate: Decimal,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can swap the payout token.
+    let mut cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.clone().ok_or(ContractError::Unauthorized {})?;
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if cfg.airdrop_native_denom.is_some() {
+        return Err(ContractError::PayoutSwapNotSupported {});
+    }
+    // Claims are frozen so that no claim is paid while the tokens are moved.
+    if CLAIMS_FROZEN.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::ClaimsNotFrozen {});
+    }
+    let new_cw20 = deps.api.addr_validate(&new_cw20)?;
+    // A retired token still held would count towards the balance of the new one.
+    if rate.is_zero() || new_cw20 == cfg.cw20_token_address || RETIRED_PAYOUT_TOKENS.has(deps.storage, &new_cw20) {
+        return Err(ContractError::InvalidInput {});
+    }
+
+    // The old balance backs the pending allocations: the game must hold its conversion.
+    let old_balance: BalanceResponse = deps.querier.query_wasm_smart(
+        &cfg.cw20_token_address,
+        &Cw20QueryMsg::Balance { address: env.contract.address.to_string() },
+    )?;
+    let new_balance: BalanceResponse = deps.querier.query_wasm_smart(
+        &new_cw20,
+        &Cw20QueryMsg::Balance { address: env.contract.address.to_string() },
+    )?;
+    let required = checked_mul_rate(old_balance.balance, rate)?;
+    if new_balance.balance < required {
+        return Err(ContractError::AirdropUnderfunded { balance: new_balance.balance, required });
+    }
+
+    // Successive swaps compose their rates, allocations are always in the original token.
+    let payout_rate = match PAYOUT_RATE.may_load(deps.storage)? {
+        Some(previous) => previous.checked_mul(rate)?,
+        None => rate,
+    };
+    PAYOUT_RATE.save(deps.storage, &payout_rate)?;
+    let old_cw20 = std::mem::replace(&mut cfg.cw20_token_address, new_cw20.clone());
+    CONFIG.save(deps.storage, &cfg)?;
+    // The old tokens stay in the game, the owner withdraws them to a treasury.
+    if !old_balance.balance.is_zero() {
+        RETIRED_PAYOUT_TOKENS.save(deps.storage, &old_cw20, &old_balance.balance)?;
+    }
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "swap_payout_token",
+        format!("new_cw20: {}, rate: {}", new_cw20, rate),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute(ATTR_ACTION, "swap_payout_token")
+        .add_attribute(ATTR_NEW_CW20, new_cw20)
+        .add_attribute(ATTR_RATE, rate.to_string())
+        .add_attribute(ATTR_AMOUNT, old_balance.balance))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_claim_airdrop(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cfg: Config,
+    params: GameParams,
+    amount: Uint128,
+    proof_airdrop: Vec<String>,
+    proof_game: Vec<String>
+) -> Result<Response, ContractError> {
+    let claim_proof = ClaimProof::Proofs { index: None, amount, proof_airdrop, proof_game };
+    claim_airdrop(deps, env, cfg, params, &info.sender, &info.sender, claim_proof)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_claim_airdrop_by_index(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cfg: Config,
+    params: GameParams,
+    index: u32,
+    amount: Uint128,
+    proof_airdrop: Vec<String>,
+    proof_game: Vec<String>
+) -> Result<Response, ContractError> {
+    let claim_proof = ClaimProof::Proofs { index: Some(index), amount, proof_airdrop, proof_game };
+    claim_airdrop(deps, env, cfg, params, &info.sender, &info.sender, claim_proof)
+}
+
+pub fn execute_cache_proof(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proofs: Vec<ProofToCache>,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    check_batch_size(proofs.len() as u32, cfg.max_batch_size)?;
+    // Cached proofs are keyed by address, indexed leaves are claimed directly.
+    if cfg.claim_index {
+        return Err(ContractError::ClaimIndexRequired {});
+    }
+
+    // Proofs are verified against the registered roots until the claim window closes.
+    let stage_claim_airdrop = GAME_PARAMS.load(deps.storage)?.stage_claim_airdrop;
+    if stage_claim_airdrop.has_ended(&env.block)? {
+        return Err(ContractError::StageEnded { stage_name: StageName::ClaimAirdrop.into() });
+    }
+    let merkle_root_airdrop = MERKLE_ROOT_AIRDROP.load(deps.storage)?;
+    let merkle_root_game = MERKLE_ROOT_GAME.load(deps.storage)?;
+
+    let is_admin = cfg.owner.as_ref() == Some(&info.sender)
+        || OPERATORS.has(deps.storage, &info.sender);
+    let domain = cfg.leaf_domain(&env.contract.address);
+    for entry in &proofs {
+        let address = deps.api.addr_validate(&entry.address)?;
+        if address != info.sender && !is_admin {
+            return Err(ContractError::Unauthorized {});
+        }
+        if entry.amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        check_proof_length(&entry.proof_airdrop, cfg.max_proof_length)?;
+        check_proof_length(&entry.proof_game, cfg.max_proof_length)?;
+        if CLAIM_AIRDROP.has(deps.storage, &address) || EXCLUDED.has(deps.storage, &address) {
+            return Err(ContractError::AlreadyClaimed {});
+        }
+
+        if !verify_airdrop_proof(&domain, address.as_str(), entry.amount, &entry.proof_airdrop, &merkle_root_airdrop)? {
+            return Err(ContractError::VerificationFailed { merkle_root: ROOT_AIRDROP.to_string() });
+        }
+        let winning_bin = verified_winning_bin(
+            deps.as_ref(),
+            &cfg,
+            &domain,
+            &address,
+            entry.proof_game.clone(),
+            &merkle_root_game,
+        )?;
+
+        let cached = CachedProof {
+            amount: entry.amount,
+            winning_bin,
+            merkle_root_airdrop: merkle_root_airdrop.clone(),
+            merkle_root_game: merkle_root_game.clone(),
+            cached_at: env.block.height,
+        };
+        CACHED_PROOFS.save(deps.storage, &address, &cached)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute(ATTR_ACTION, "cache_proof")
+        .add_attribute(ATTR_CACHED, proofs.len().to_string()))
+}
+
+pub fn execute_claim_cached_airdrop(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cfg: Config,
+    params: GameParams,
+) -> Result<Response, ContractError> {
+    claim_airdrop(deps, env, cfg, params, &info.sender, &info.sender, ClaimProof::Cached)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_claim_airdrop_for(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cfg: Config,
+    params: GameParams,
+    address: String,
+    amount: Uint128,
+    proof_airdrop: Vec<String>,
+    proof_game: Vec<String>
+) -> Result<Response, ContractError> {
+    if !cfg.approved_proxies.contains(&info.sender) {
+        return Err(ContractError::NotApprovedProxy { proxy: info.sender.to_string() });
+    }
+
+    // The proxy must confirm that it custodies the address.
+    let address = deps.api.addr_validate(&address)?;
+    let res: CustodyResponse = deps.querier.query_wasm_smart(
+        &info.sender,
+        &ProxyQueryMsg::Custodies { address: address.to_string() },
+    )?;
+    if !res.custodied {
+        return Err(ContractError::NotCustodied {
+            address: address.to_string(),
+            proxy: info.sender.to_string(),
+        });
+    }
+
+    let claim_proof = ClaimProof::Proofs { index: None, amount, proof_airdrop, proof_game };
+    claim_airdrop(deps, env, cfg, params, &address, &info.sender, claim_proof)
+}
+
+/// Eligibility of an airdrop claim.
+enum ClaimProof {
+    /// Proofs sent along with the claim.
+    Proofs {
+        /// Leaf index, in the claim index mode.
+        index: Option<u32>,
+        amount: Uint128,
+        proof_airdrop: Vec<String>,
+        proof_game: Vec<String>,
+    },
+    /// Proofs verified in advance with `CacheProof`.
+    Cached,
+}
+
+/// Claims the airdrop of `claimer` and sends the tokens to `recipient`.
+fn claim_airdrop(
+    mut deps: DepsMut,
+    env: Env,
+    cfg: Config,
+    params: GameParams,
+    claimer: &Addr,
     recipient: &Addr,
-    token_addr: &Addr,
-    token_amount: Uint128,
-) -> StdResult<CosmosMsg> {
-    let transfer_cw20_msg = Cw20ExecuteMsg::Transfer {
-        recipient: recipient.into(),
-        amount: token_amount,
+    claim_proof: ClaimProof,
+) -> Result<Response, ContractError> {
+    let game = env.contract.address.clone();
+    // The claim index mode tracks the claims by leaf instead of by address.
+    let index = match &claim_proof {
+        ClaimProof::Proofs { index, .. } => *index,
+        ClaimProof::Cached => None,
     };
-    let exec_cw20_transfer = WasmMsg::Execute {
-        contract_addr: token_addr.into(),
-        msg: to_binary(&transfer_cw20_msg)?,
-        funds: vec![],
+    match (cfg.claim_index, index) {
+        (true, None) => return Err(ContractError::ClaimIndexRequired {}),
+        (false, Some(_)) => return Err(ContractError::ClaimIndexDisabled {}),
+        _ => {}
+    }
+    if let ClaimProof::Proofs { amount, proof_airdrop, proof_game, .. } = &claim_proof {
+        // Zero leaves would verify and waste the claim on an empty transfer.
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        check_proof_length(proof_airdrop, cfg.max_proof_length)?;
+        check_proof_length(proof_game, cfg.max_proof_length)?;
+    }
+    check_claims_not_frozen(deps.as_ref())?;
+
+    // Check that the correct stage is active.
+    let claimed_at = ClaimedAt::new(&env.block);
+    params.stage_claim_airdrop.ensure_active(&env.block, StageName::ClaimAirdrop)?;
+
+    // Native airdrops are bounded by the deposits when paying the claim.
+    if cfg.airdrop_native_denom.is_none()
+        && !CLAIMS_FUNDED.may_load(deps.storage)?.unwrap_or(false)
+    {
+        return Err(ContractError::ClaimsNotFunded {});
+    }
+
+    // Verify that the user has not already made the claim, nor has been excluded.
+    let claimed = match index {
+        Some(index) => is_index_claimed(deps.storage, index)?,
+        None => CLAIM_AIRDROP.has(deps.storage, claimer),
     };
-    let cw20_transfer_cosmos_msg: CosmosMsg = exec_cw20_transfer.into();
-    Ok(cw20_transfer_cosmos_msg)
-}
+    if claimed || EXCLUDED.has(deps.storage, claimer) {
+        return Err(ContractError::AlreadyClaimed {});
+    }
 
-#[cfg(test)]
-mod tests {
-    use crate::state::Stage;
+    if let Some(hook) = &cfg.eligibility_hook {
+        check_eligibility(deps.as_ref(), hook, claimer)?;
+    }
+
+    if let Some(condition) = CLAIM_CONDITION.may_load(deps.storage)? {
+        if !evaluate_condition(deps.as_ref(), &condition, claimer)? {
+            return Err(ContractError::ConditionNotMet {});
+        }
+    }
+
+    let merkle_root_airdrop = MERKLE_ROOT_AIRDROP.load(deps.storage)?;
+    let merkle_root_game = MERKLE_ROOT_GAME.load(deps.storage)?;
+
+    let (amount, winning_bin) = match claim_proof {
+        ClaimProof::Proofs { amount, proof_airdrop, proof_game, .. } => {
+            // Compare proofs: the proof sent by the user must be the same of the one
+            // produced with the claimer address. Indexed leaves hash the index right
+            // after the domain.
+            let domain = cfg.leaf_domain(&game);
+            let airdrop_domain = match index {
+                Some(index) => format!("{}{}", domain, index),
+                None => domain.clone(),
+            };
+            if !verify_airdrop_proof(&airdrop_domain, claimer.as_str(), amount, &proof_airdrop, &merkle_root_airdrop)? {
+                return Err(ContractError::VerificationFailed { merkle_root: ROOT_AIRDROP.to_string() });
+            }
+            let winning_bin = verified_winning_bin(
+                deps.as_ref(),
+                &cfg,
+                &domain,
+                claimer,
+                proof_game,
+                &merkle_root_game,
+            )?;
+            (amount, winning_bin)
+        }
+        ClaimProof::Cached => {
+            // Proofs cached before the roots were replaced are not valid anymore.
+            let cached = CACHED_PROOFS
+                .may_load(deps.storage, claimer)?
+                .filter(|cached| {
+                    cached.merkle_root_airdrop == merkle_root_airdrop
+                        && cached.merkle_root_game == merkle_root_game
+                })
+                .ok_or(ContractError::ProofNotCached {})?;
+            CACHED_PROOFS.remove(deps.storage, claimer);
+            (cached.amount, cached.winning_bin)
+        }
+    };
+
+    // If the two root are equal and the proven bin is still the one of the bid:
+    // - Save the sender as a winner with unclaimed prize.
+    // - Increase the number of winners.
+    // - Notify the new winner with a dedicated event.
+    let mut counters = COUNTERS.load(deps.storage)?;
+    let mut winner_event = None;
+    if let Some(sender_bid) = winning_bin {
+        // An address owning several indexed leaves wins once.
+        if BIDS.may_load(deps.storage, claimer)? == Some(sender_bid) && !CLAIM_PRIZE.has(deps.storage, claimer) {
+            CLAIM_PRIZE.save(deps.storage, claimer, &false)?;
+            counters.winners = counters.winners.checked_add(Uint128::new(1))?;
+            BIN_WINNERS.update(deps.storage, sender_bid, |bin_winners| -> Result<_, ContractError> {
+                Ok(bin_winners.unwrap_or_default().checked_add(Uint128::new(1))?)
+            })?;
+            winner_event = Some(
+                Event::new(EVENT_WINNER_REGISTERED)
+                    .add_attribute(ATTR_ADDRESS, claimer)
+                    .add_attribute(ATTR_BIN, sender_bid.to_string())
+                    .add_attribute(ATTR_WINNERS, counters.winners),
+            );
+        }
+    }
+
+    // Without an attestation the claim is paid up to the threshold, the rest is held
+    // back until the claimer gets one.
+    let mut withheld = Uint128::zero();
+    if let Some(gate) = &cfg.kyc_gate {
+        if amount > gate.threshold && !has_attestation(deps.as_ref(), gate, claimer)? {
+            withheld = amount - gate.threshold;
+            WITHHELD_AIRDROP.update(deps.storage, claimer, |total| -> StdResult<_> {
+                Ok(total.unwrap_or_default().checked_add(withheld)?)
+            })?;
+        }
+    }
+    let amount = amount - withheld;
+
+    let (msgs, secondary_amount) =
+        pay_airdrop(deps.branch(), &cfg, &mut counters, &claimed_at, claimer, recipient, amount)?;
+    COUNTERS.save(deps.storage, &counters)?;
+
+    // Mark the sender as a user that has received the airdrop, or only the leaf in the
+    // claim index mode.
+    match index {
+        Some(index) => set_index_claimed(deps.storage, index)?,
+        None => {
+            CLAIM_AIRDROP.save(deps.storage, claimer, &true)?;
+            AIRDROP_CLAIMED_AT.save(deps.storage, claimer, &claimed_at)?;
+        }
+    }
+    record_cohort_claim(deps.storage, claimer, |stats| {
+        stats.airdrop_claims = stats
+            .airdrop_claims
+            .checked_add(1)
+            .ok_or_else(|| OverflowError::new(OverflowOperation::Add, stats.airdrop_claims, 1))?;
+        stats.claimed_airdrop_amount = stats.claimed_airdrop_amount.checked_add(amount)?;
+        Ok(())
+    })?;
+    record_claim(deps.storage, claimer, ClaimKind::Airdrop, amount, &claimed_at)?;
+
+    let badge = badge_submsg(deps.storage, &cfg, &game, claimer, BadgeRole::Airdrop, amount)?;
+
+    let res = Response::new()
+        .add_messages(msgs)
+        .add_submessages(badge)
+        .add_attribute(ATTR_ACTION, "claim_airdrop")
+        .add_attribute(ATTR_PLAYER, claimer)
+        .add_attribute(ATTR_RECIPIENT, recipient)
+        .add_attribute(ATTR_AIRDROP_AMOUNT, amount)
+        .add_attribute(ATTR_SECONDARY_AMOUNT, secondary_amount)
+        .add_attributes((!withheld.is_zero()).then(|| attr(ATTR_WITHHELD, withheld)))
+        .add_attributes(index.map(|index| attr(ATTR_INDEX, index.to_string())))
+        .add_events(winner_event);
+    Ok(res)
+}
+
+/// Accounts `amount` of the airdrop of `claimer` as claimed and returns the messages
+/// paying it to `recipient`, secondary payout included, with the secondary amount. The
+/// caller saves the `counters`.
+fn pay_airdrop(
+    deps: DepsMut,
+    cfg: &Config,
+    counters: &mut Counters,
+    claimed_at: &ClaimedAt,
+    claimer: &Addr,
+    recipient: &Addr,
+    amount: Uint128,
+) -> Result<(Vec<CosmosMsg>, Uint128), ContractError> {
+    if let Some(limit) = &cfg.claim_rate_limit {
+        consume_claim_rate(deps.storage, limit, RATE_LIMIT_CLAIM_AIRDROP, claimed_at.height, amount)?;
+    }
+
+    // Increase the amount of airdropped tokens claimed.
+    counters.claimed_airdrop_amount = counters.claimed_airdrop_amount.checked_add(amount)?;
+    check_native_airdrop_funds(cfg, counters)?;
+
+    let payout = payout_amount(deps.storage, amount)?;
+    let claim_id = format!("airdrop/{}", claimer);
+    let payout = Asset::new(cfg.airdrop_asset_info(), payout);
+    let mut msgs = vec![payout_msg(deps.as_ref(), cfg, &payout, recipient, &claim_id)?];
+
+    // The secondary asset is delivered in the same claim, bounded by its total.
+    let mut secondary_amount = Uint128::zero();
+    if let Some(mut secondary) = SECONDARY_PAYOUT.may_load(deps.storage)? {
+        secondary_amount = checked_mul_rate(amount, secondary.rate)?;
+        secondary.claimed_amount = secondary.claimed_amount.checked_add(secondary_amount)?;
+        if secondary.claimed_amount > secondary.total_amount {
+            return Err(ContractError::SecondaryPayoutExceeded {});
+        }
+        SECONDARY_PAYOUT.save(deps.storage, &secondary)?;
+        if !secondary_amount.is_zero() {
+            let secondary = Asset::cw20(secondary.cw20_token_address, secondary_amount);
+            msgs.push(payout_msg(deps.as_ref(), cfg, &secondary, recipient, &claim_id)?);
+        }
+    }
+    Ok((msgs, secondary_amount))
+}
+
+/// Pays the airdrop withheld from the claim of the sender once it has been attested.
+pub fn execute_claim_withheld_airdrop(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cfg: Config,
+    params: GameParams,
+) -> Result<Response, ContractError> {
+    check_claims_not_frozen(deps.as_ref())?;
+
+    // After the claim airdrop stage the withheld amounts are part of the leftover.
+    let claimed_at = ClaimedAt::new(&env.block);
+    params.stage_claim_airdrop.ensure_active(&env.block, StageName::ClaimAirdrop)?;
+
+    let withheld = WITHHELD_AIRDROP
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::NothingWithheld {})?;
+    // The gate is never removed, so it is set whenever something is withheld.
+    if let Some(gate) = &cfg.kyc_gate {
+        if !has_attestation(deps.as_ref(), gate, &info.sender)? {
+            return Err(ContractError::AttestationRequired { registry: gate.registry.to_string() });
+        }
+    }
+    WITHHELD_AIRDROP.remove(deps.storage, &info.sender);
+    record_cohort_claim(deps.storage, &info.sender, |stats| {
+        stats.claimed_airdrop_amount = stats.claimed_airdrop_amount.checked_add(withheld)?;
+        Ok(())
+    })?;
+    record_claim(deps.storage, &info.sender, ClaimKind::WithheldAirdrop, withheld, &claimed_at)?;
+
+    let mut counters = COUNTERS.load(deps.storage)?;
+    let (msgs, secondary_amount) =
+        pay_airdrop(deps.branch(), &cfg, &mut counters, &claimed_at, &info.sender, &info.sender, withheld)?;
+    COUNTERS.save(deps.storage, &counters)?;
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute(ATTR_ACTION, "claim_withheld_airdrop")
+        .add_attribute(ATTR_PLAYER, &info.sender)
+        .add_attribute(ATTR_AIRDROP_AMOUNT, withheld)
+        .add_attribute(ATTR_SECONDARY_AMOUNT, secondary_amount))
+}
+
+/// Returns the bin of the claimer if the game proof shows that it won. With
+/// amount-bearing leaves the winners are proven directly when claiming the prize.
+fn verified_winning_bin(
+    deps: Deps,
+    cfg: &Config,
+    domain: &str,
+    claimer: &Addr,
+    proof_game: Vec<String>,
+    merkle_root_game: &str,
+) -> Result<Option<u8>, ContractError> {
+    let sender_bid = match cfg.prize_mode {
+        PrizeMode::EqualSplit | PrizeMode::PerBin => BIDS.may_load(deps.storage, claimer)?,
+        PrizeMode::MerkleAmounts => None,
+    };
+    match sender_bid {
+        // The proof is computed by using as a leaf the value bidded by the sender.
+        Some(sender_bid) => {
+            let leaf = domain_leaf_hash(domain, claimer.as_str(), &sender_bid.to_string());
+            Ok(verify_proof(leaf, proof_game, merkle_root_game)?.then_some(sender_bid))
+        }
+        None => Ok(None),
+    }
+}
+
+pub fn execute_set_payout_split(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    shares: Vec<PayoutShareMsg>,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if shares.len() > cfg.max_addresses_per_call as usize {
+        return Err(ContractError::TooManyAddresses { max: cfg.max_addresses_per_call });
+    }
+
+    // The split can change until the prize is claimed.
+    if !BIDS.has(deps.storage, &info.sender) {
+        return Err(ContractError::BidNotPresent {});
+    }
+    if CLAIM_PRIZE.may_load(deps.storage, &info.sender)? == Some(true) {
+        return Err(ContractError::AlreadyClaimed {});
+    }
+    let stage_claim_prize = GAME_PARAMS.load(deps.storage)?.stage_claim_prize;
+    if stage_claim_prize.has_ended(&env.block)? {
+        return Err(ContractError::StageEnded { stage_name: StageName::ClaimPrize.into() });
+    }
+
+    let mut split: Vec<PayoutShare> = Vec::with_capacity(shares.len());
+    let mut total_bps = 0u16;
+    for share in shares {
+        let address = deps.api.addr_validate(&share.address)?;
+        if share.bps == 0 || split.iter().any(|other| other.address == address) {
+            return Err(ContractError::InvalidPayoutSplit { total: TOTAL_BPS });
+        }
+        total_bps = total_bps.saturating_add(share.bps);
+        split.push(PayoutShare { address, bps: share.bps });
+    }
+
+    if split.is_empty() {
+        PAYOUT_SPLITS.remove(deps.storage, &info.sender);
+    } else if total_bps != TOTAL_BPS {
+        return Err(ContractError::InvalidPayoutSplit { total: TOTAL_BPS });
+    } else {
+        PAYOUT_SPLITS.save(deps.storage, &info.sender, &split)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute(ATTR_ACTION, "set_payout_split")
+        .add_attribute(ATTR_PLAYER, info.sender))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_claim_prize(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cfg: Config,
+    params: GameParams,
+    amount: Option<Uint128>,
+    proof: Option<Vec<String>>,
+    then: Option<PrizeHookMsg>,
+) -> Result<Response, ContractError> {
+    if let Some(proof) = &proof {
+        check_proof_length(proof, cfg.max_proof_length)?;
+    }
+    match cfg.prize_timelock {
+        // With a timelock only the owner veto can block the prizes, until they are released.
+        Some(timelock) => check_prize_released(deps.as_ref(), &env, timelock)?,
+        None => check_claims_not_frozen(deps.as_ref())?,
+    }
+    let then = then
+        .map(|hook| -> StdResult<_> {
+            Ok(PrizeHook { contract_addr: deps.api.addr_validate(&hook.contract_addr)?, msg: hook.msg })
+        })
+        .transpose()?;
+    if then.is_some() && PAYOUT_SPLITS.has(deps.storage, &info.sender) {
+        return Err(ContractError::PayoutSplitWithHook {});
+    }
+
+    let claimed_at = ClaimedAt::new(&env.block);
+    params.stage_claim_prize.ensure_active(&env.block, StageName::ClaimPrize)?;
+    check_prize_pool_minimum(deps.as_ref(), &cfg)?;
+    let counters = COUNTERS.load(deps.storage)?;
+
+    // The contract must still hold every prize not claimed yet.
+    #[cfg(feature = "cosmwasm_1_1")]
+    {
+        let snapshot = CLOSED_SNAPSHOT.load(deps.storage)?;
+        let required = snapshot
+            .total_ticket_prize
+            .checked_add(snapshot.total_bin_boost)?
+            .checked_sub(counters.claimed_prize_amount)?
+            .checked_sub(counters.claimed_bin_boost)?;
+        check_prize_balance(deps.as_ref(), &env, &params.ticket_price.denom, required)?;
+    }
+
+    match cfg.prize_mode {
+        PrizeMode::EqualSplit | PrizeMode::PerBin => {
+            claim_equal_split_prize(deps, &env, info, cfg, params, counters, claimed_at, then)
+        }
+        PrizeMode::MerkleAmounts => {
+            let (amount, proof) = amount.zip(proof).ok_or(ContractError::InvalidInput {})?;
+            claim_merkle_amount_prize(deps, &env, info, cfg, params, counters, claimed_at, amount, proof, then)
+        }
+    }
+}
+
+/// Validated `PrizeHookMsg`.
+struct PrizeHook {
+    contract_addr: Addr,
+    msg: Binary,
+}
+
+/// Message paying a claim to `recipient`. In the `send_with_memo` payout mode the cw20
+/// tokens reach contracts with a `Send`, so that they can account for the payout.
+fn payout_msg(
+    deps: Deps,
+    cfg: &Config,
+    payout: &Asset,
+    recipient: &Addr,
+    claim_id: &str,
+) -> StdResult<CosmosMsg> {
+    match &cfg.payout_mode {
+        PayoutMode::SendWithMemo { campaign_id } if !payout.info.is_native() && is_contract(deps, recipient) => {
+            let memo = PayoutReceiveMsg::PayoutMemo {
+                campaign_id: campaign_id.clone(),
+                claim_id: claim_id.to_string(),
+            };
+            payout.send_msg(recipient, to_binary(&memo)?)
+        }
+        _ => payout.transfer_msg(recipient),
+    }
+}
+
+/// True if a contract is instantiated at `address`.
+fn is_contract(deps: Deps, address: &Addr) -> bool {
+    let query = WasmQuery::ContractInfo { contract_addr: address.to_string() };
+    deps.querier.query::<ContractInfoResponse>(&query.into()).is_ok()
+}
+
+/// Pays the native prize to the winner, to the contract of `then` along with its message,
+/// or to the addresses of the payout split of the winner.
+fn prize_transfer_msgs(
+    storage: &dyn Storage,
+    denom: &str,
+    amount: Uint128,
+    winner: &Addr,
+    then: Option<PrizeHook>,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    if let Some(hook) = then {
+        let funds = if amount.is_zero() { vec![] } else { vec![Coin { denom: denom.to_string(), amount }] };
+        return Ok(vec![WasmMsg::Execute { contract_addr: hook.contract_addr.into_string(), msg: hook.msg, funds }.into()]);
+    }
+    let split = match PAYOUT_SPLITS.may_load(storage, winner)? {
+        Some(split) => split,
+        None => return Ok(vec![Asset::native(denom, amount).transfer_msg(winner)?]),
+    };
+
+    // Every share is rounded down, the first one also receives the remainder.
+    let mut amounts = split
+        .iter()
+        .map(|share| amount.multiply_ratio(share.bps, TOTAL_BPS))
+        .collect::<Vec<_>>();
+    let distributed = amounts.iter().try_fold(Uint128::zero(), |total, share| total.checked_add(*share))?;
+    amounts[0] = amounts[0].checked_add(amount.checked_sub(distributed)?)?;
+
+    let msgs = split
+        .iter()
+        .zip(amounts)
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|(share, amount)| Asset::native(denom, amount).transfer_msg(&share.address))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(msgs)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn claim_equal_split_prize(
+    deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+    cfg: Config,
+    params: GameParams,
+    mut counters: Counters,
+    claimed_at: ClaimedAt,
+    then: Option<PrizeHook>,
+) -> Result<Response, ContractError> {
+    // Verify that the user has not already made the claim.
+    let claimed = CLAIM_PRIZE.may_load(deps.storage, &info.sender)?;
+    if let Some(already_claimed) = claimed {
+        if already_claimed {
+            return Err(ContractError::AlreadyClaimed {});
+        }
+    } else {
+        return Err(ContractError::NoteEligible {});
+    };
+
+    let snapshot = CLOSED_SNAPSHOT.load(deps.storage)?;
+    let winners = snapshot.winners;
+    let ticket_price = params.ticket_price;
+    let ticket_prize = snapshot.total_ticket_prize;
+    let airdrop_prize = snapshot.total_airdrop_game_amount;
+
+    // Every winner will receive two prize: one given by the tickets of the game and
+    // one given by an incentive from the tokens airdrop. For both of them the
+    // amount received is given by the total divided by the number of winners.
+    // In the per bin mode the tickets are split among the winners of the same bin.
+    // Dust is sent only to an active treasury, otherwise it stays in the pools as with
+    // `FloorToWinner`.
+    let policy = match &cfg.rounding_policy {
+        RoundingPolicy::DustToTreasury { address } if !is_active_treasury(deps.storage, address)? => {
+            &RoundingPolicy::FloorToWinner
+        }
+        policy => policy,
+    };
+    let sender_bid = BIDS.load(deps.storage, &info.sender)?;
+    let ticket_split = match cfg.prize_mode {
+        PrizeMode::PerBin => {
+            let bin_winners = snapshot.bin_winners(sender_bid).unwrap_or_default();
+            let bin_prize = snapshot.bin_ticket_prize(sender_bid);
+            let pool = bin_tickets_pool_name(sender_bid);
+            split_prize(deps.storage, policy, &pool, bin_prize, bin_winners)?
+        }
+        _ => split_prize(deps.storage, policy, POOL_TICKETS, ticket_prize, winners)?,
+    };
+    let airdrop_split = split_prize(deps.storage, policy, POOL_AIRDROP_GAME, airdrop_prize, winners)?;
+
+    // Winners also share the side-pool of the bin they bid on.
+    let bin_split = match BIN_POOLS.may_load(deps.storage, sender_bid)? {
+        Some(bin_pool) => {
+            let bin_winners = snapshot.bin_winners(sender_bid).unwrap_or_default();
+            let pool = bin_pool_name(sender_bid);
+            split_prize(deps.storage, policy, &pool, bin_pool, bin_winners)?
+        }
+        None => PrizeSplit { to_claimer: Uint128::zero(), to_treasury: Uint128::zero() },
+    };
+    counters.claimed_bin_boost = counters.claimed_bin_boost.checked_add(bin_split.total())?;
+    BIN_CLAIMED_PRIZE.update(deps.storage, sender_bid, |claimed| -> Result<_, ContractError> {
+        let bin_prize = ticket_split.total().checked_add(bin_split.total())?;
+        Ok(claimed.unwrap_or_default().checked_add(bin_prize)?)
+    })?;
+
+    let sender_ticket_prize = ticket_split.to_claimer;
+    let sender_airdrop_prize = airdrop_split.to_claimer;
+    let sender_bin_prize = bin_split.to_claimer;
+
+    if let Some(limit) = &cfg.claim_rate_limit {
+        consume_claim_rate(deps.storage, limit, RATE_LIMIT_CLAIM_PRIZE, claimed_at.height, airdrop_split.total())?;
+    }
+
+    let recipient = then.as_ref().map_or(&info.sender, |hook| &hook.contract_addr).clone();
+    let mut transfer_msgs = prize_transfer_msgs(
+        deps.storage,
+        &ticket_price.denom,
+        sender_ticket_prize.checked_add(sender_bin_prize)?,
+        &info.sender,
+        then,
+    )?;
+    transfer_msgs.push(payout_msg(
+        deps.as_ref(),
+        &cfg,
+        &Asset::new(cfg.airdrop_asset_info(), payout_amount(deps.storage, sender_airdrop_prize)?),
+        &info.sender,
+        &format!("prize/{}", info.sender),
+    )?);
+
+    // Dust assigned to the treasury is paid along with the claim.
+    if let RoundingPolicy::DustToTreasury { address } = policy {
+        let native_dust = ticket_split.to_treasury.checked_add(bin_split.to_treasury)?;
+        if !native_dust.is_zero() {
+            transfer_msgs.push(Asset::native(&ticket_price.denom, native_dust).transfer_msg(address)?);
+        }
+        if !airdrop_split.to_treasury.is_zero() {
+            let airdrop_dust = payout_amount(deps.storage, airdrop_split.to_treasury)?;
+            transfer_msgs.push(Asset::new(cfg.airdrop_asset_info(), airdrop_dust).transfer_msg(address)?);
+        }
+    }
+
+    CLAIM_PRIZE.update(deps.storage, &info.sender, |mut _already_claimed| -> StdResult<_>{
+        Ok(true)
+    })?;
+    PRIZE_CLAIMED_AT.save(deps.storage, &info.sender, &claimed_at)?;
+
+    // Update botht the airdrop and the prize claimed amount.
+    counters.claimed_airdrop_amount = counters.claimed_airdrop_amount.checked_add(airdrop_split.total())?;
+    check_native_airdrop_funds(&cfg, &counters)?;
+    counters.claimed_prize_amount = counters.claimed_prize_amount.checked_add(ticket_split.total())?;
+    COUNTERS.save(deps.storage, &counters)?;
+
+    let prize = sender_ticket_prize.checked_add(sender_bin_prize)?;
+    record_cohort_claim(deps.storage, &info.sender, |stats| {
+        stats.prize_claims = stats
+            .prize_claims
+            .checked_add(1)
+            .ok_or_else(|| OverflowError::new(OverflowOperation::Add, stats.prize_claims, 1))?;
+        stats.claimed_airdrop_amount = stats.claimed_airdrop_amount.checked_add(sender_airdrop_prize)?;
+        stats.claimed_prize_amount = stats.claimed_prize_amount.checked_add(prize)?;
+        Ok(())
+    })?;
+    record_claim(deps.storage, &info.sender, ClaimKind::Prize, prize, &claimed_at)?;
+    record_claim(deps.storage, &info.sender, ClaimKind::PrizeAirdrop, sender_airdrop_prize, &claimed_at)?;
+    let badge = badge_submsg(deps.storage, &cfg, &env.contract.address, &info.sender, BadgeRole::Prize, prize)?;
+
+    let res = Response::new()
+        .add_messages(transfer_msgs)
+        .add_submessages(badge)
+        .add_attribute(ATTR_ACTION, "claim_prize")
+        .add_attribute(ATTR_PLAYER, info.sender)
+        .add_attribute(ATTR_PRIZE_FROM_TICKETS, sender_ticket_prize)
+        .add_attribute(ATTR_PRIZE_FROM_AIRDROP, sender_airdrop_prize)
+        .add_attribute(ATTR_PRIZE_FROM_BIN_BOOST, sender_bin_prize)
+        .add_attribute(ATTR_RECIPIENT, recipient);
+    Ok(res)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn claim_merkle_amount_prize(
+    deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+    cfg: Config,
+    params: GameParams,
+    mut counters: Counters,
+    claimed_at: ClaimedAt,
+    amount: Uint128,
+    proof: Vec<String>,
+    then: Option<PrizeHook>,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    // Verify that the user has not already made the claim.
+    if CLAIM_PRIZE.may_load(deps.storage, &info.sender)? == Some(true) {
+        return Err(ContractError::AlreadyClaimed {});
+    }
+
+    // The leaf of the game tree encodes the prize computed off-chain for the sender.
+    let merkle_root_game = MERKLE_ROOT_GAME.load(deps.storage)?;
+    let domain = cfg.leaf_domain(&env.contract.address);
+    let leaf = domain_leaf_hash(&domain, info.sender.as_str(), &amount.to_string());
+    if !verify_proof(leaf, proof, &merkle_root_game)? {
+        return Err(ContractError::VerificationFailed { merkle_root: ROOT_GAME.to_string() });
+    }
+
+    // A wrong tree cannot drain more than the tickets collected.
+    let ticket_prize = CLOSED_SNAPSHOT.load(deps.storage)?.total_ticket_prize;
+    counters.claimed_prize_amount = counters.claimed_prize_amount.checked_add(amount)?;
+    if counters.claimed_prize_amount > ticket_prize {
+        return Err(ContractError::PrizePoolExceeded {});
+    }
+
+    // No airdropped token is released by this kind of claim.
+    if let Some(limit) = &cfg.claim_rate_limit {
+        consume_claim_rate(deps.storage, limit, RATE_LIMIT_CLAIM_PRIZE, claimed_at.height, Uint128::zero())?;
+    }
+
+    CLAIM_PRIZE.save(deps.storage, &info.sender, &true)?;
+    PRIZE_CLAIMED_AT.save(deps.storage, &info.sender, &claimed_at)?;
+    counters.winners = counters.winners.checked_add(Uint128::new(1))?;
+    COUNTERS.save(deps.storage, &counters)?;
+    record_cohort_claim(deps.storage, &info.sender, |stats| {
+        stats.prize_claims = stats
+            .prize_claims
+            .checked_add(1)
+            .ok_or_else(|| OverflowError::new(OverflowOperation::Add, stats.prize_claims, 1))?;
+        stats.claimed_prize_amount = stats.claimed_prize_amount.checked_add(amount)?;
+        Ok(())
+    })?;
+    record_claim(deps.storage, &info.sender, ClaimKind::Prize, amount, &claimed_at)?;
+
+    let recipient = then.as_ref().map_or(&info.sender, |hook| &hook.contract_addr).clone();
+    let msgs = prize_transfer_msgs(deps.storage, &params.ticket_price.denom, amount, &info.sender, then)?;
+    let badge = badge_submsg(deps.storage, &cfg, &env.contract.address, &info.sender, BadgeRole::Prize, amount)?;
+
+    let res = Response::new()
+        .add_messages(msgs)
+        .add_submessages(badge)
+        .add_attribute(ATTR_ACTION, "claim_prize")
+        .add_attribute(ATTR_PLAYER, info.sender)
+        .add_attribute(ATTR_PRIZE_FROM_TICKETS, amount)
+        .add_attribute(ATTR_RECIPIENT, recipient);
+    Ok(res)
+}
+
+// ======================================================================================
+// Games without winners
+// ======================================================================================
+/// Fails unless the game has no winners and its policy is accepted by `applies`. Winners
+/// are all known once the claim airdrop stage is over.
+fn ensure_no_winners(
+    deps: Deps,
+    env: &Env,
+    applies: impl Fn(&NoWinnersPolicy) -> bool,
+) -> Result<Config, ContractError> {
+    let stage_claim_prize = GAME_PARAMS.load(deps.storage)?.stage_claim_prize;
+    stage_claim_prize.ensure_active(&env.block, StageName::ClaimPrize)?;
+
+    let cfg = CONFIG.load(deps.storage)?;
+    check_prize_pool_minimum(deps, &cfg)?;
+    if !applies(&cfg.no_winners_policy) {
+        return Err(ContractError::NoWinnersPolicyNotApplicable {});
+    }
+    if !CLOSED_SNAPSHOT.load(deps.storage)?.winners.is_zero() {
+        return Err(ContractError::GameHasWinners {});
+    }
+    Ok(cfg)
+}
+
+pub fn execute_settle_no_winners(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let cfg = ensure_no_winners(deps.as_ref(), &env, |policy| {
+        matches!(policy, NoWinnersPolicy::Treasury { .. } | NoWinnersPolicy::Rollover { .. })
+    })?;
+
+    if let NoWinnersPolicy::Treasury { address } = &cfg.no_winners_policy {
+        ensure_active_treasury(deps.storage, address)?;
+    }
+
+    // The whole ticket prize is settled at once.
+    let ticket_prize = CLOSED_SNAPSHOT.load(deps.storage)?.total_ticket_prize;
+    let mut counters = COUNTERS.load(deps.storage)?;
+    let amount = ticket_prize.checked_sub(counters.claimed_prize_amount)?;
+    if amount.is_zero() {
+        return Err(ContractError::AlreadyClaimed {});
+    }
+    counters.claimed_prize_amount = ticket_prize;
+    COUNTERS.save(deps.storage, &counters)?;
+
+    let ticket_price = GAME_PARAMS.load(deps.storage)?.ticket_price;
+    let (msg, recipient) = match cfg.no_winners_policy {
+        NoWinnersPolicy::Treasury { address } => {
+            (Asset::native(ticket_price.denom, amount).transfer_msg(&address)?, address)
+        }
+        // The following game must accept boosts in the same denom at this time.
+        NoWinnersPolicy::Rollover { game } => {
+            let msg = WasmMsg::Execute {
+                contract_addr: game.to_string(),
+                msg: to_binary(&ExecuteMsg::BoostPrize {})?,
+                funds: vec![Coin::new(amount.u128(), ticket_price.denom)],
+            };
+            (msg.into(), game)
+        }
+        NoWinnersPolicy::Withdraw | NoWinnersPolicy::Refund => {
+            return Err(ContractError::NoWinnersPolicyNotApplicable {})
+        }
+    };
+
+    let res = Response::new()
+        .add_message(msg)
+        .add_attribute(ATTR_ACTION, "settle_no_winners")
+        .add_attribute(ATTR_RECIPIENT, recipient)
+        .add_attribute(ATTR_AMOUNT, amount);
+    Ok(res)
+}
+
+pub fn execute_claim_ticket_refund(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // Below the minimum prize pool every bidder is refunded, winners included.
+    if CLOSED_SNAPSHOT.may_load(deps.storage)?.is_some_and(|snapshot| snapshot.refund_mode) {
+        let stage_claim_prize = GAME_PARAMS.load(deps.storage)?.stage_claim_prize;
+        stage_claim_prize.ensure_active(&env.block, StageName::ClaimPrize)?;
+    } else {
+        ensure_no_winners(deps.as_ref(), &env, |policy| *policy == NoWinnersPolicy::Refund)?;
+    }
+
+    if !BIDS.has(deps.storage, &info.sender) || CLAIM_PRIZE.may_load(deps.storage, &info.sender)? == Some(true) {
+        return Err(ContractError::NothingToRefund {});
+    }
+
+    // Bids placed before the receipts were recorded paid the ticket price, vendor
+    // tickets paid nothing. Late fees stay in the prize withdrawn by the owner.
+    let ticket_price = GAME_PARAMS.load(deps.storage)?.ticket_price;
+    let paid = match BID_RECEIPTS.may_load(deps.storage, &info.sender)? {
+        Some(receipt) => receipt.paid.ok_or(ContractError::NothingToRefund {})?,
+        None => ticket_price,
+    };
+
+    CLAIM_PRIZE.save(deps.storage, &info.sender, &true)?;
+    COUNTERS.update(deps.storage, |mut counters| -> Result<_, ContractError> {
+        counters.claimed_prize_amount = counters.claimed_prize_amount.checked_add(paid.amount)?;
+        Ok(counters)
+    })?;
+    record_claim(deps.storage, &info.sender, ClaimKind::TicketRefund, paid.amount, &ClaimedAt::new(&env.block))?;
+
+    let msg = Asset::native(&paid.denom, paid.amount).transfer_msg(&info.sender)?;
+
+    let res = Response::new()
+        .add_message(msg)
+        .add_attribute(ATTR_ACTION, "claim_ticket_refund")
+        .add_attribute(ATTR_PLAYER, info.sender)
+        .add_attribute(ATTR_AMOUNT, paid.amount);
+    Ok(res)
+}
+
+// ======================================================================================
+// Withdraw of unclaimed tokens
+// ======================================================================================
+pub fn execute_set_treasury(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addr: String,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can register the withdrawal destinations.
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // A new destination waits for the timelock, leaving time to react to a
+    // compromised owner key before any funds can reach it.
+    let address = deps.api.addr_validate(&addr)?;
+    if TREASURIES.has(deps.storage, &address) {
+        return Err(ContractError::TreasuryAlreadyRegistered { address: address.into() });
+    }
+    let active_at = after_block(&env.block, cfg.treasury_timelock)?;
+    TREASURIES.save(deps.storage, &address, &Treasury { active_at, active: false })?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "set_treasury",
+        format!("address: {}, active_at: {}", address, active_at),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute(ATTR_ACTION, "set_treasury")
+        .add_event(
+            Event::new(EVENT_TREASURY_REGISTERED)
+                .add_attribute(ATTR_ADDRESS, &address)
+                .add_attribute(ATTR_ACTIVE_AT, active_at.to_string()),
+        ))
+}
+
+pub fn execute_activate_treasury(
+    deps: DepsMut,
+    env: Env,
+    addr: String,
+) -> Result<Response, ContractError> {
+    let address = deps.api.addr_validate(&addr)?;
+    let mut treasury = TREASURIES
+        .may_load(deps.storage, &address)?
+        .ok_or_else(|| ContractError::TreasuryNotActive { address: address.to_string() })?;
+    if treasury.active {
+        return Err(ContractError::TreasuryAlreadyRegistered { address: address.into() });
+    }
+    if !treasury.active_at.is_triggered(&env.block) {
+        return Err(ContractError::TreasuryTimelocked { active_at: treasury.active_at.to_string() });
+    }
+
+    treasury.active = true;
+    TREASURIES.save(deps.storage, &address, &treasury)?;
+
+    Ok(Response::new()
+        .add_attribute(ATTR_ACTION, "activate_treasury")
+        .add_event(Event::new(EVENT_TREASURY_ACTIVATED).add_attribute(ATTR_ADDRESS, &address)))
+}
+
+pub fn execute_remove_treasury(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addr: String,
+) -> Result<Response, ContractError> {
+    // The guardian can also remove a destination, e.g. one registered with a compromised
+    // owner key while its timelock runs.
+    let cfg = CONFIG.load(deps.storage)?;
+    let guardian = cfg.withdrawal_guard.map(|guard| guard.guardian);
+    if cfg.owner.as_ref() != Some(&info.sender) && guardian.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let address = deps.api.addr_validate(&addr)?;
+    if !TREASURIES.has(deps.storage, &address) {
+        return Err(ContractError::TreasuryNotRegistered { address: address.into() });
+    }
+    TREASURIES.remove(deps.storage, &address);
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "remove_treasury",
+        format!("address: {}", address),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute(ATTR_ACTION, "remove_treasury")
+        .add_event(Event::new(EVENT_TREASURY_REMOVED).add_attribute(ATTR_ADDRESS, &address)))
+}
+
+/// Fails unless the address is a treasury activated after its timelock.
+fn ensure_active_treasury(storage: &dyn Storage, address: &Addr) -> Result<(), ContractError> {
+    if !is_active_treasury(storage, address)? {
+        return Err(ContractError::TreasuryNotActive { address: address.to_string() });
+    }
+    Ok(())
+}
+
+fn is_active_treasury(storage: &dyn Storage, address: &Addr) -> StdResult<bool> {
+    Ok(TREASURIES.may_load(storage, address)?.is_some_and(|treasury| treasury.active))
+}
+
+pub fn execute_propose_withdrawal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    withdrawal: Withdrawal,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can propose a withdrawal.
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    let guard = cfg.withdrawal_guard.ok_or(ContractError::WithdrawalGuardDisabled {})?;
+
+    let id = WITHDRAWAL_PROPOSAL_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    let expires = after_block(&env.block, guard.window)?;
+    let proposal = WithdrawalProposal { withdrawal, proposer: info.sender.clone(), expires };
+    WITHDRAWAL_PROPOSALS.save(deps.storage, id, &proposal)?;
+    WITHDRAWAL_PROPOSAL_COUNT.save(deps.storage, &id)?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "propose_withdrawal",
+        format!("id: {}, expires: {}", id, expires),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute(ATTR_ACTION, "propose_withdrawal")
+        .add_attribute(ATTR_PROPOSAL_ID, id.to_string())
+        .add_attribute(ATTR_EXPIRES, expires.to_string()))
+}
+
+pub fn execute_approve_withdrawal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    // Just the guardian can approve a withdrawal.
+    let cfg = CONFIG.load(deps.storage)?;
+    let guard = cfg.withdrawal_guard.ok_or(ContractError::WithdrawalGuardDisabled {})?;
+    if info.sender != guard.guardian {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let proposal = WITHDRAWAL_PROPOSALS
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::WithdrawalProposalNotFound { id })?;
+    if proposal.expires.is_triggered(&env.block) {
+        return Err(ContractError::WithdrawalProposalExpired { id });
+    }
+    WITHDRAWAL_PROPOSALS.remove(deps.storage, id);
+
+    // The withdrawal runs on behalf of the proposer, who must still be the owner.
+    let info = MessageInfo { sender: proposal.proposer, funds: vec![] };
+    let res = match proposal.withdrawal {
+        Withdrawal::Airdrop { address, amount } => {
+            execute_withdraw_airdrop(deps, env, info, &address, amount, true)
+        }
+        Withdrawal::SecondaryPayout { address } => {
+            execute_withdraw_secondary_payout(deps, env, info, &address, true)
+        }
+        Withdrawal::Prize { address } => execute_withdraw_prize(deps, env, info, &address, true),
+        Withdrawal::Round { round, address } => {
+            execute_withdraw_round(deps, env, info, round, &address, true)
+        }
+        Withdrawal::RetiredPayout { cw20_token_address, address } => {
+            execute_withdraw_retired_payout(deps, env, info, &cw20_token_address, &address, true)
+        }
+    }?;
+
+    Ok(res.add_attribute(ATTR_PROPOSAL_ID, id.to_string()))
+}
+
+/// Fails if the amount withdrawn is above the threshold the withdrawal guard sets for its
+/// asset and the guardian has not approved it. Games without a guard pass `None`.
+fn check_withdrawal_guard(
+    threshold: Option<Uint128>,
+    amount: Uint128,
+    approved: bool,
+) -> Result<(), ContractError> {
+    match threshold {
+        Some(threshold) if amount > threshold && !approved => {
+            Err(ContractError::WithdrawalNeedsApproval { amount, threshold })
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn execute_withdraw_airdrop(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: &Addr,
+    amount: Option<Uint128>,
+    approved: bool,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can withdraw the remaining tokens.
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.as_ref().ok_or(ContractError::Unauthorized {})?;
+    if info.sender != *owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Check that the claiming prize stage has ended.
+    let stage_claim_prize = GAME_PARAMS.load(deps.storage)?.stage_claim_prize;
+    if !stage_claim_prize.has_ended(&env.block)? {
+        return Err(ContractError::ClaimPrizeStageNotFinished {});
+    }
+
+    // Funds leave only towards a treasury registered ahead of time.
+    ensure_active_treasury(deps.storage, address)?;
+
+    let snapshot = CLOSED_SNAPSHOT.load(deps.storage)?;
+    let total_amount_airdrop = snapshot.total_airdrop_amount;
+    let total_amount_prize = snapshot.total_airdrop_game_amount;
+    let mut counters = COUNTERS.load(deps.storage)?;
+    let claimed_amount = counters.claimed_airdrop_amount;
+    let leftover = match cfg.airdrop_native_denom {
+        // Native deposits are not bound to the registered totals, sweep all the leftover.
+        Some(_) => counters.funded_airdrop_native_amount.checked_sub(claimed_amount)?,
+        None => total_amount_airdrop
+            .checked_add(total_amount_prize)?
+            .checked_sub(claimed_amount)?,
+    };
+
+    // The leftover can be withdrawn in tranches, the last one sweeps the remainder.
+    let remaining = leftover.checked_sub(counters.withdrawn_airdrop_amount)?;
+    let allocation = amount.unwrap_or(remaining);
+    if allocation.is_zero() || allocation > remaining {
+        return Err(ContractError::InvalidWithdrawAmount { remaining });
+    }
+    let amount = payout_amount(deps.storage, allocation)?;
+
+    // Large withdrawals need the approval of the guardian, the tranches are summed so that
+    // splitting the leftover does not skip it.
+    let withdrawn_airdrop_amount = counters.withdrawn_airdrop_amount.checked_add(allocation)?;
+    check_withdrawal_guard(
+        cfg.withdrawal_guard.as_ref().map(|guard| guard.threshold),
+        payout_amount(deps.storage, withdrawn_airdrop_amount)?,
+        approved,
+    )?;
+
+    counters.withdrawn_airdrop_amount = withdrawn_airdrop_amount;
+    COUNTERS.save(deps.storage, &counters)?;
+
+    let msg = Asset::new(cfg.airdrop_asset_info(), amount).transfer_msg(address)?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "withdraw_airdrop",
+        format!("address: {}, amount: {}", address, amount),
+    )?;
+
+    let res = Response::new()
+        .add_message(msg)
+        .add_attribute(ATTR_ACTION, "withdraw_airdrop")
+        .add_attribute(ATTR_ADDRESS, address)
+        .add_attribute(ATTR_AMOUNT, amount)
+        .add_attribute(ATTR_REMAINING, remaining - allocation);
+
+    Ok(res)
+}
+
+pub fn execute_withdraw_secondary_payout(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: &Addr,
+    approved: bool,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can withdraw the remaining tokens.
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.as_ref().ok_or(ContractError::Unauthorized {})?;
+    if info.sender != *owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Check that the claiming prize stage has ended.
+    let stage_claim_prize = GAME_PARAMS.load(deps.storage)?.stage_claim_prize;
+    if !stage_claim_prize.has_ended(&env.block)? {
+        return Err(ContractError::ClaimPrizeStageNotFinished {});
+    }
+
+    // Funds leave only towards a treasury registered ahead of time.
+    ensure_active_treasury(deps.storage, address)?;
+
+    let secondary = SECONDARY_PAYOUT
+        .may_load(deps.storage)?
+        .ok_or(ContractError::SecondaryPayoutNotRegistered {})?;
+    let mut counters = COUNTERS.load(deps.storage)?;
+    let amount = secondary
+        .total_amount
+        .checked_sub(secondary.claimed_amount)?
+        .checked_sub(counters.withdrawn_secondary_amount)?;
+    if amount.is_zero() {
+        return Err(ContractError::InvalidWithdrawAmount { remaining: amount });
+    }
+
+    // Large withdrawals need the approval of the guardian.
+    check_withdrawal_guard(
+        cfg.withdrawal_guard.as_ref().map(|guard| guard.secondary_threshold),
+        amount,
+        approved,
+    )?;
+
+    counters.withdrawn_secondary_amount = counters.withdrawn_secondary_amount.checked_add(amount)?;
+    COUNTERS.save(deps.storage, &counters)?;
+
+    let msg = Asset::cw20(secondary.cw20_token_address, amount).transfer_msg(address)?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "withdraw_secondary_payout",
+        format!("address: {}, amount: {}", address, amount),
+    )?;
+
+    let res = Response::new()
+        .add_message(msg)
+        .add_attribute(ATTR_ACTION, "withdraw_secondary_payout")
+        .add_attribute(ATTR_ADDRESS, address)
+        .add_attribute(ATTR_AMOUNT, amount);
+
+    Ok(res)
+}
+
+// TODO: si potrebbe unire a quello sopra.
+pub fn execute_withdraw_prize(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: &Addr,
+    approved: bool,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can withdraw the remaining tokens.
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.as_ref().ok_or(ContractError::Unauthorized {})?;
+    if info.sender != *owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Check that the claiming prize stage has ended.
+    let GameParams { stage_claim_prize, ticket_price, .. } = GAME_PARAMS.load(deps.storage)?;
+    if !stage_claim_prize.has_ended(&env.block)? {
+        return Err(ContractError::ClaimPrizeStageNotFinished {});
+    }
+
+    // Funds leave only towards a treasury registered ahead of time.
+    ensure_active_treasury(deps.storage, address)?;
+
+    // Unclaimed bin boosts are swept along with the ticket prize.
+    let snapshot = CLOSED_SNAPSHOT.load(deps.storage)?;
+    let total_prize = snapshot.total_ticket_prize.checked_add(snapshot.total_bin_boost)?;
+    let mut counters = COUNTERS.load(deps.storage)?;
+    let claimed_prize = counters.claimed_prize_amount.checked_add(counters.claimed_bin_boost)?;
+    let amount = total_prize
+        .checked_sub(claimed_prize)?
+        .checked_sub(counters.withdrawn_prize_amount)?;
+    if amount.is_zero() {
+        return Err(ContractError::InvalidWithdrawAmount { remaining: amount });
+    }
+
+    #[cfg(feature = "cosmwasm_1_1")]
+    check_prize_balance(deps.as_ref(), &env, &ticket_price.denom, amount)?;
+
+    // Large withdrawals need the approval of the guardian.
+    check_withdrawal_guard(
+        cfg.withdrawal_guard.as_ref().map(|guard| guard.prize_threshold),
+        amount,
+        approved,
+    )?;
+
+    counters.withdrawn_prize_amount = counters.withdrawn_prize_amount.checked_add(amount)?;
+    COUNTERS.save(deps.storage, &counters)?;
+
+    let msg = Asset::native(ticket_price.denom, amount).transfer_msg(address)?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "withdraw_prize",
+        format!("address: {}, amount: {}", address, amount),
+    )?;
+
+    let res = Response::new()
+        .add_message(msg)
+        .add_attribute(ATTR_ACTION, "withdraw_prize")
+        .add_attribute(ATTR_ADDRESS, address)
+        .add_attribute(ATTR_AMOUNT, amount);
+
+    Ok(res)
+}
+
+pub fn execute_withdraw_retired_payout(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_token_address: &Addr,
+    address: &Addr,
+    approved: bool,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can withdraw the retired tokens.
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.as_ref().ok_or(ContractError::Unauthorized {})?;
+    if info.sender != *owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Funds leave only towards a treasury registered ahead of time.
+    ensure_active_treasury(deps.storage, address)?;
+
+    let amount = RETIRED_PAYOUT_TOKENS
+        .may_load(deps.storage, cw20_token_address)?
+        .unwrap_or_default();
+    if amount.is_zero() {
+        return Err(ContractError::InvalidWithdrawAmount { remaining: amount });
+    }
+
+    // Large withdrawals need the approval of the guardian, the retired token was the
+    // airdrop asset.
+    check_withdrawal_guard(cfg.withdrawal_guard.as_ref().map(|guard| guard.threshold), amount, approved)?;
+
+    RETIRED_PAYOUT_TOKENS.remove(deps.storage, cw20_token_address);
+
+    let msg = Asset::cw20(cw20_token_address.clone(), amount).transfer_msg(address)?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "withdraw_retired_payout",
+        format!("cw20_token_address: {}, address: {}, amount: {}", cw20_token_address, address, amount),
+    )?;
+
+    let res = Response::new()
+        .add_message(msg)
+        .add_attribute(ATTR_ACTION, "withdraw_retired_payout")
+        .add_attribute(ATTR_ADDRESS, address)
+        .add_attribute(ATTR_AMOUNT, amount);
+
+    Ok(res)
+}
+
+// ======================================================================================
+// Airdrop rounds
+// ======================================================================================
+pub fn execute_register_round(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    merkle_root: String,
+    stage: StageMsg,
+) -> Result<Response, ContractError> {
+    // Airdrops paid with the cw20 token fund their rounds with the `RegisterRound` hook.
+    let cfg = CONFIG.load(deps.storage)?;
+    let denom = cfg
+        .airdrop_native_denom
+        .clone()
+        .ok_or(ContractError::NativeAirdropNotEnabled {})?;
+    let amount = must_pay(&info, &denom)?;
+    register_round(deps, &env, &cfg, &info.sender, merkle_root, stage, amount)
+}
+
+/// Registers the next airdrop round, funded with `amount` allocation units.
+fn register_round(
+    deps: DepsMut,
+    env: &Env,
+    cfg: &Config,
+    sender: &Addr,
+    merkle_root: String,
+    stage: StageMsg,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can register a round.
+    if cfg.owner.as_ref() != Some(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Check merkle root length.
+    let mut root_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(&merkle_root, &mut root_buf)?;
+
+    // The game airdrop is round 0, the following rounds are numbered from 1.
+    let round = LATEST_ROUND
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .checked_add(1)
+        .ok_or(ContractError::TooManyRounds {})?;
+    let stage = stage.resolve(&env.block)?;
+    if stage.has_ended(&env.block)? {
+        return Err(ContractError::StageEnded { stage_name: round_stage_name(round) });
+    }
+
+    let airdrop_round = AirdropRound {
+        merkle_root: merkle_root.clone(),
+        stage,
+        total_amount: amount,
+        claimed_amount: Uint128::zero(),
+        withdrawn: false,
+    };
+    ROUNDS.save(deps.storage, round, &airdrop_round)?;
+    LATEST_ROUND.save(deps.storage, &round)?;
+    record_admin_action(
+        deps.storage,
+        env,
+        sender,
+        "register_round",
+        format!("round: {}, merkle_root: {}, amount: {}", round, merkle_root, amount),
+    )?;
+
+    let res = Response::new()
+        .add_attribute(ATTR_ACTION, "register_round")
+        .add_attribute(ATTR_ROUND, round.to_string())
+        .add_attribute(ATTR_MERKLE_ROOT_AIRDROP, merkle_root)
+        .add_attribute(ATTR_TOTAL_AMOUNT_AIRDROP, amount);
+    Ok(res)
+}
+
+pub fn execute_claim_round(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    round: u8,
+    amount: Uint128,
+    proof: Vec<String>,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    check_proof_length(&proof, cfg.max_proof_length)?;
+    check_claims_not_frozen(deps.as_ref())?;
+
+    let mut airdrop_round = ROUNDS
+        .may_load(deps.storage, round)?
+        .ok_or(ContractError::RoundNotFound { round })?;
+    match airdrop_round.stage.status(&env.block)? {
+        StageStatus::NotStarted => {
+            return Err(ContractError::StageNotStarted { stage_name: round_stage_name(round) })
+        }
+        StageStatus::Ended => return Err(ContractError::StageEnded { stage_name: round_stage_name(round) }),
+        StageStatus::Active => {}
+    }
+
+    // Verify that the user has not already made the claim.
+    if ROUND_CLAIMED.has(deps.storage, (round, &info.sender)) {
+        return Err(ContractError::AlreadyClaimed {});
+    }
+
+    // The leaves of the rounds are built like the ones of the airdrop tree.
+    let domain = cfg.leaf_domain(&env.contract.address);
+    if !verify_airdrop_proof(&domain, info.sender.as_str(), amount, &proof, &airdrop_round.merkle_root)? {
+        return Err(ContractError::VerificationFailed { merkle_root: ROOT_ROUND.to_string() });
+    }
+
+    // A wrong tree cannot drain more than the round has been funded with.
+    airdrop_round.claimed_amount = airdrop_round.claimed_amount.checked_add(amount)?;
+    if airdrop_round.claimed_amount > airdrop_round.total_amount {
+        return Err(ContractError::RoundAmountExceeded { round });
+    }
+    ROUNDS.save(deps.storage, round, &airdrop_round)?;
+    ROUND_CLAIMED.save(deps.storage, (round, &info.sender), &true)?;
+    record_claim(deps.storage, &info.sender, ClaimKind::RoundAirdrop, amount, &ClaimedAt::new(&env.block))?;
+
+    let payout = Asset::new(cfg.airdrop_asset_info(), payout_amount(deps.storage, amount)?);
+    let claim_id = format!("round/{}/{}", round, info.sender);
+    let msg = payout_msg(deps.as_ref(), &cfg, &payout, &info.sender, &claim_id)?;
+
+    let res = Response::new()
+        .add_message(msg)
+        .add_attribute(ATTR_ACTION, "claim_round")
+        .add_attribute(ATTR_ROUND, round.to_string())
+        .add_attribute(ATTR_PLAYER, info.sender)
+        .add_attribute(ATTR_AIRDROP_AMOUNT, amount);
+    Ok(res)
+}
+
+pub fn execute_withdraw_round(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    round: u8,
+    address: &Addr,
+    approved: bool,
+) -> Result<Response, ContractError> {
+    // Just the contract owner can withdraw the remaining tokens.
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.as_ref().ok_or(ContractError::Unauthorized {})?;
+    if info.sender != *owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut airdrop_round = ROUNDS
+        .may_load(deps.storage, round)?
+        .ok_or(ContractError::RoundNotFound { round })?;
+    if !airdrop_round.stage.has_ended(&env.block)? {
+        return Err(ContractError::RoundNotFinished { round });
+    }
+
+    // Funds leave only towards a treasury registered ahead of time.
+    ensure_active_treasury(deps.storage, address)?;
+
+    // The leftover of a round is withdrawn at once.
+    let remaining = match airdrop_round.withdrawn {
+        true => Uint128::zero(),
+        false => airdrop_round.total_amount.checked_sub(airdrop_round.claimed_amount)?,
+    };
+    if remaining.is_zero() {
+        return Err(ContractError::InvalidWithdrawAmount { remaining });
+    }
+    let amount = payout_amount(deps.storage, remaining)?;
+
+    // Large withdrawals need the approval of the guardian.
+    check_withdrawal_guard(cfg.withdrawal_guard.as_ref().map(|guard| guard.threshold), amount, approved)?;
+
+    airdrop_round.withdrawn = true;
+    ROUNDS.save(deps.storage, round, &airdrop_round)?;
+
+    let msg = Asset::new(cfg.airdrop_asset_info(), amount).transfer_msg(address)?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        &info.sender,
+        "withdraw_round",
+        format!("round: {}, address: {}, amount: {}", round, address, amount),
+    )?;
+
+    let res = Response::new()
+        .add_message(msg)
+        .add_attribute(ATTR_ACTION, "withdraw_round")
+        .add_attribute(ATTR_ROUND, round.to_string())
+        .add_attribute(ATTR_ADDRESS, address)
+        .add_attribute(ATTR_AMOUNT, amount);
+    Ok(res)
+}
+
+/// Name of the claim stage of a round in the stage errors.
+fn round_stage_name(round: u8) -> String {
+    format!("airdrop round {}", round)
+}
+
+// ======================================================================================
+// Badges
+// ======================================================================================
+/// Mint of the badge of a claim, if a badge contract is configured. The reply records
+/// the token id in the claim history.
+fn badge_submsg(
+    storage: &mut dyn Storage,
+    cfg: &Config,
+    game: &Addr,
+    claimer: &Addr,
+    role: BadgeRole,
+    amount: Uint128,
+) -> StdResult<Vec<SubMsg>> {
+    let badge_contract = match &cfg.badge_contract {
+        Some(badge_contract) => badge_contract,
+        None => return Ok(vec![]),
+    };
+
+    let token_id = format!("{}/{}/{}", game, role.as_str(), claimer);
+    let mint = BadgeExecuteMsg::Mint(BadgeMintMsg {
+        token_id: token_id.clone(),
+        owner: claimer.to_string(),
+        token_uri: None,
+        extension: BadgeMetadata {
+            game: game.to_string(),
+            role,
+            amount_band: amount_band(amount),
+        },
+    });
+    PENDING_BADGE.save(storage, &PendingBadge { address: claimer.clone(), role, token_id })?;
+
+    let msg = WasmMsg::Execute {
+        contract_addr: badge_contract.to_string(),
+        msg: to_binary(&mint)?,
+        funds: vec![],
+    };
+    // A failing badge contract must not block the claims.
+    Ok(vec![SubMsg::reply_always(msg, BADGE_REPLY_ID)])
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    if msg.id != BADGE_REPLY_ID {
+        return Err(ContractError::UnknownReplyId { id: msg.id });
+    }
+    let PendingBadge { address, role, token_id } = PENDING_BADGE.load(deps.storage)?;
+    PENDING_BADGE.remove(deps.storage);
+
+    let res = match msg.result {
+        SubMsgResult::Ok(res) => {
+            // Badge contracts assigning their own ids report them in the `token_id` attribute.
+            let token_id = res
+                .events
+                .iter()
+                .filter(|event| event.ty == "wasm")
+                .flat_map(|event| &event.attributes)
+                .find(|attr| attr.key == ATTR_TOKEN_ID)
+                .map_or(token_id, |attr| attr.value.clone());
+            CLAIM_HISTORY.update(deps.storage, &address, |history| -> StdResult<_> {
+                let mut history = history.unwrap_or_default();
+                match role {
+                    BadgeRole::Airdrop => history.airdrop_badge = Some(token_id.clone()),
+                    BadgeRole::Prize => history.prize_badge = Some(token_id.clone()),
+                }
+                Ok(history)
+            })?;
+            Response::new()
+                .add_attribute(ATTR_ACTION, "mint_badge")
+                .add_attribute(ATTR_TOKEN_ID, token_id)
+        }
+        SubMsgResult::Err(reason) => Response::new()
+            .add_attribute(ATTR_ACTION, "mint_badge_failed")
+            .add_attribute(ATTR_REASON, reason),
+    };
+    Ok(trim_attributes(deps.storage, res)?)
+}
+
+// ======================================================================================
+// Queries
+// ======================================================================================
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Metadata {} => to_binary(&query_metadata(deps)?),
+        QueryMsg::Stages {} => to_binary(&query_stages(deps)?),
+        QueryMsg::Bid { address, viewer_proof } => {
+            to_binary(&query_bid(deps, env, address, viewer_proof)?)
+        }
+        QueryMsg::IsWinner { address } => to_binary(&query_is_winner(deps, address)?),
+        QueryMsg::StageStatus {} => to_binary(&query_stage_status(deps, env)?),
+        QueryMsg::EligibleAmount { address } => to_binary(&query_eligible_amount(deps, address)?),
+        QueryMsg::LegacyBid { address } => {
+            to_binary(&query_legacy_bid(deps, env, address)?)
+        }
+        QueryMsg::IsClaimed { address } => to_binary(&query_is_claimed(deps, address)?),
+        QueryMsg::IsClaimedByIndex { index } => to_binary(&query_is_claimed_by_index(deps, index)?),
+        QueryMsg::ClaimsFreeze {} => to_binary(&query_claims_freeze(deps)?),
+        QueryMsg::VerifyTreeMetadata {
+            merkle_root_airdrop,
+            merkle_root_game,
+            tree_metadata,
+        } => to_binary(&query_verify_tree_metadata(
+            deps,
+            merkle_root_airdrop,
+            merkle_root_game,
+            tree_metadata,
+        )?),
+        QueryMsg::LeafHash { account, amount } => to_binary(&query_leaf_hash(deps, env, account, amount)?),
+        QueryMsg::AllBids { start_after, limit } => {
+            to_binary(&query_all_bids(deps, env, start_after, limit)?)
+        }
+        QueryMsg::MerkleRoots {} => to_binary(&query_merkle_root(deps)?),
+        QueryMsg::MerkleRoot { kind } => to_binary(&query_merkle_root_info(deps, kind)?),
+        QueryMsg::LatestRound {} => to_binary(&query_latest_round(deps)?),
+        QueryMsg::Round { round } => to_binary(&query_round(deps, round)?),
+        QueryMsg::IsRoundClaimed { round, address } => {
+            to_binary(&query_is_round_claimed(deps, round, address)?)
+        }
+        QueryMsg::GameAmounts {} => to_binary(&query_game_amounts(deps)?),
+        QueryMsg::PrizePot {} => to_binary(&query_prize_pot(deps, env)?),
+        QueryMsg::BinPools {} => to_binary(&query_bin_pools(deps)?),
+        QueryMsg::BinDistribution {} => to_binary(&query_bin_distribution(deps, env)?),
+        QueryMsg::BinBoost { bin, address } => to_binary(&query_bin_boost(deps, bin, address)?),
+        QueryMsg::TopBoosters { limit } => to_binary(&query_top_boosters(deps, limit)?),
+        QueryMsg::Outcome {} => to_binary(&query_outcome(deps)?),
+        QueryMsg::Exclusions {} => to_binary(&query_exclusions(deps)?),
+        QueryMsg::CohortStats {} => to_binary(&query_cohort_stats(deps)?),
+        QueryMsg::Failsafe {} => to_binary(&query_failsafe(deps)?),
+        QueryMsg::Limits {} => to_binary(&query_limits(deps)?),
+        QueryMsg::PendingActions { address } => {
+            to_binary(&query_pending_actions(deps, env, address)?)
+        }
+        QueryMsg::PayoutSplit { address } => to_binary(&query_payout_split(deps, address)?),
+        QueryMsg::Operators { start_after, limit } => {
+            to_binary(&query_operators(deps, start_after, limit)?)
+        }
+        QueryMsg::Treasuries { start_after, limit } => {
+            to_binary(&query_treasuries(deps, start_after, limit)?)
+        }
+        QueryMsg::WithdrawalProposals { start_after, limit } => {
+            to_binary(&query_withdrawal_proposals(deps, start_after, limit)?)
+        }
+        QueryMsg::AdminActions { start_after, limit } => {
+            to_binary(&query_admin_actions(deps, start_after, limit)?)
+        }
+        QueryMsg::ClaimsPage { start_after, limit } => {
+            to_binary(&query_claims_page(deps, start_after, limit)?)
+        }
+    }
+}
+
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let GameParams { ticket_price, bins, .. } = GAME_PARAMS.load(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: cfg.owner.map(|o| o.to_string()),
+        cw20_token_address: cfg.cw20_token_address.to_string(),
+        airdrop_native_denom: cfg.airdrop_native_denom,
+        ticket_price,
+        bins,
+        eligibility_hook: cfg.eligibility_hook,
+        prize_mode: cfg.prize_mode,
+        rounding_policy: cfg.rounding_policy,
+        pubkey_bids: cfg.pubkey_bids,
+        approved_proxies: cfg.approved_proxies,
+        claim_rate_limit: cfg.claim_rate_limit,
+        ticket_vendor: cfg.ticket_vendor.map(|vendor| vendor.to_string()),
+        max_proof_length: cfg.max_proof_length,
+        badge_contract: cfg.badge_contract.map(|badge| badge.to_string()),
+        max_bid_changes: cfg.max_bid_changes,
+        late_bid_penalty: cfg.late_bid_penalty,
+        private_bins: cfg.private_bins,
+        hidden_bids: cfg.hidden_bids,
+        minimal_attributes: cfg.minimal_attributes,
+        prize_timelock: cfg.prize_timelock,
+        payout_mode: cfg.payout_mode,
+        no_winners_policy: cfg.no_winners_policy,
+        governance_mode: cfg.governance_mode,
+        kyc_gate: cfg.kyc_gate,
+        leaf_format: cfg.leaf_format,
+        claim_index: cfg.claim_index,
+        test_mode: cfg.test_mode,
+        min_prize_pool: cfg.min_prize_pool,
+        treasury_timelock: cfg.treasury_timelock,
+        withdrawal_guard: cfg.withdrawal_guard,
+        payout_rate: PAYOUT_RATE.may_load(deps.storage)?,
+    })
+}
+
+/// Returns the game metadata.
+pub fn query_metadata(deps: Deps) -> StdResult<GameMetadata> {
+    GAME_METADATA.load(deps.storage)
+}
+
+/// Returns stages's information.
+pub fn query_stages(deps: Deps) -> StdResult<StagesResponse> {
+    let GameParams { stage_bid, stage_claim_airdrop, stage_claim_prize, .. } =
+        GAME_PARAMS.load(deps.storage)?;
+    Ok(StagesResponse {
+        stage_bid_end: stage_bid.end()?,
+        stage_claim_airdrop_end: stage_claim_airdrop.end()?,
+        stage_claim_prize_end: stage_claim_prize.end()?,
+        stage_bid,
+        stage_claim_airdrop,
+        stage_claim_prize,
+    })
+}
+
+/// Fails while the bins are private, i.e. until the bid stage ends. Bids are recorded as
+/// usual meanwhile.
+fn ensure_bins_disclosed(deps: Deps, env: &Env) -> StdResult<()> {
+    let stage_bid = GAME_PARAMS.load(deps.storage)?.stage_bid;
+    if CONFIG.load(deps.storage)?.private_bins && !stage_bid.has_ended(&env.block)? {
+        return Err(StdError::generic_err(ContractError::PrivateBins {}.to_string()));
+    }
+    Ok(())
+}
+
+/// Fails while the bids are private or hidden, for the queries disclosing the bin of
+/// any address.
+fn ensure_bids_disclosed(deps: Deps, env: &Env) -> StdResult<()> {
+    ensure_bins_disclosed(deps, env)?;
+    let stage_bid = GAME_PARAMS.load(deps.storage)?.stage_bid;
+    if CONFIG.load(deps.storage)?.hidden_bids && !stage_bid.has_ended(&env.block)? {
+        return Err(StdError::generic_err(ContractError::PrivateBins {}.to_string()));
+    }
+    Ok(())
+}
+
+/// Whether the bin of `bidder` is hidden from the caller: the bids are hidden until the
+/// bid stage ends, except to the holder of the key attached to the bid.
+fn is_bin_hidden(
+    deps: Deps,
+    env: &Env,
+    bidder: &Addr,
+    viewer_proof: Option<&PubkeyProof>,
+) -> StdResult<bool> {
+    let stage_bid = GAME_PARAMS.load(deps.storage)?.stage_bid;
+    if !CONFIG.load(deps.storage)?.hidden_bids || stage_bid.has_ended(&env.block)? {
+        return Ok(false);
+    }
+    let proof = match viewer_proof {
+        Some(proof) => proof,
+        None => return Ok(true),
+    };
+    let key_hash = Sha256::digest(&proof.pubkey);
+    if BID_KEYS.may_load(deps.storage, bidder)?.as_deref() != Some(key_hash.as_slice()) {
+        return Err(StdError::generic_err(ContractError::InvalidSignature {}.to_string()));
+    }
+    let message = format!("view{}{}", env.contract.address, bidder);
+    check_signature(deps, message.as_bytes(), proof)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    Ok(false)
+}
+
+pub fn query_bid(
+    deps: Deps,
+    env: Env,
+    address: String,
+    viewer_proof: Option<PubkeyProof>,
+) -> StdResult<BidResponse> {
+    ensure_bins_disclosed(deps, &env)?;
+    let address = deps.api.addr_validate(&address)?;
+    let mut bin = BIDS.may_load(deps.storage, &address)?;
+    let has_bid = bin.is_some();
+    if has_bid && is_bin_hidden(deps, &env, &address, viewer_proof.as_ref())? {
+        bin = None;
+    }
+    let receipt = BID_RECEIPTS.may_load(deps.storage, &address)?;
+    let claim_prize = CLAIM_PRIZE.may_load(deps.storage, &address)?;
+
+    Ok(BidResponse {
+        has_bid,
+        bin,
+        paid: receipt.as_ref().and_then(|receipt| receipt.paid.clone()),
+        placed_at: receipt.map(|receipt| receipt.placed_at),
+        is_winner: bid_outcome(deps, has_bid, claim_prize)?,
+        prize_claimed: claim_prize.unwrap_or(false),
+    })
+}
+
+/// Winners are recorded when they claim. With equal split every winner has claimed the
+/// airdrop once the claim airdrop stage is closed, so the other bids lost.
+fn bid_outcome(deps: Deps, has_bid: bool, claim_prize: Option<bool>) -> StdResult<Option<bool>> {
+    Ok(match claim_prize {
+        Some(_) => Some(true),
+        None if !has_bid => None,
+        None => {
+            let cfg = CONFIG.load(deps.storage)?;
+            let closed = CLOSED_SNAPSHOT.may_load(deps.storage)?.is_some();
+            (cfg.prize_mode != PrizeMode::MerkleAmounts && closed).then_some(false)
+        }
+    })
+}
+
+/// Unlike `Bid`, the outcome is answered also while the bins are private: nothing is
+/// known before the end of the bid stage anyway.
+pub fn query_is_winner(deps: Deps, address: String) -> StdResult<IsWinnerResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let has_bid = BIDS.has(deps.storage, &address);
+    let claim_prize = CLAIM_PRIZE.may_load(deps.storage, &address)?;
+    Ok(IsWinnerResponse { is_winner: bid_outcome(deps, has_bid, claim_prize)? })
+}
+
+/// The claims run from the start of the claim airdrop stage to the end of the claim
+/// prize stage, the gap between them included.
+pub fn query_stage_status(deps: Deps, env: Env) -> StdResult<StageStatusResponse> {
+    let GameParams { stage_bid, stage_claim_airdrop, stage_claim_prize, .. } =
+        GAME_PARAMS.load(deps.storage)?;
+    let stages = [
+        (StageName::Bid, stage_bid.status(&env.block)?),
+        (StageName::ClaimAirdrop, stage_claim_airdrop.status(&env.block)?),
+        (StageName::ClaimPrize, stage_claim_prize.status(&env.block)?),
+    ];
+    let stage = stages
+        .iter()
+        .find(|(_, status)| *status == StageStatus::Active)
+        .map(|(name, _)| String::from(*name));
+
+    let phase = match stages.map(|(_, status)| status) {
+        [StageStatus::NotStarted, _, _] => GamePhase::NotStarted,
+        [StageStatus::Active, _, _] => GamePhase::Bidding,
+        [_, StageStatus::NotStarted, _] => GamePhase::Settling,
+        [_, _, StageStatus::Ended] => GamePhase::Ended,
+        _ => GamePhase::Claiming,
+    };
+    Ok(StageStatusResponse { phase, stage })
+}
+
+pub fn query_eligible_amount(deps: Deps, address: String) -> StdResult<EligibleAmountResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let merkle_root_airdrop = MERKLE_ROOT_AIRDROP.may_load(deps.storage)?;
+    let merkle_root_game = MERKLE_ROOT_GAME.may_load(deps.storage)?;
+    let cached = CACHED_PROOFS.may_load(deps.storage, &address)?.filter(|cached| {
+        Some(&cached.merkle_root_airdrop) == merkle_root_airdrop.as_ref()
+            && Some(&cached.merkle_root_game) == merkle_root_game.as_ref()
+    });
+    Ok(EligibleAmountResponse {
+        amount: cached.as_ref().map(|cached| cached.amount),
+        winning_bin: cached.as_ref().and_then(|cached| cached.winning_bin),
+        cached_at: cached.map(|cached| cached.cached_at),
+    })
+}
+
+pub fn query_legacy_bid(deps: Deps, env: Env, address: String) -> StdResult<LegacyBidResponse> {
+    ensure_bids_disclosed(deps, &env)?;
+    let bid = BIDS.may_load(deps.storage, &deps.api.addr_validate(&address)?)?;
+    Ok(LegacyBidResponse { bid })
+}
+
+pub fn query_is_claimed(deps: Deps, address: String) -> StdResult<IsClaimedResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let history: ClaimHistory = CLAIM_HISTORY.may_load(deps.storage, &address)?.unwrap_or_default();
+    Ok(IsClaimedResponse {
+        airdrop_claimed: CLAIM_AIRDROP.may_load(deps.storage, &address)?.unwrap_or(false),
+        excluded: EXCLUDED.has(deps.storage, &address),
+        prize_claimed: CLAIM_PRIZE.may_load(deps.storage, &address)?.unwrap_or(false),
+        airdrop_claimed_at: AIRDROP_CLAIMED_AT.may_load(deps.storage, &address)?,
+        prize_claimed_at: PRIZE_CLAIMED_AT.may_load(deps.storage, &address)?,
+        airdrop_badge: history.airdrop_badge,
+        prize_badge: history.prize_badge,
+        withheld_airdrop: WITHHELD_AIRDROP.may_load(deps.storage, &address)?.unwrap_or_default(),
+    })
+}
+
+pub fn query_is_claimed_by_index(deps: Deps, index: u32) -> StdResult<IsClaimedByIndexResponse> {
+    Ok(IsClaimedByIndexResponse { claimed: is_index_claimed(deps.storage, index)? })
+}
+
+/// Answers before the roots are registered too, so that clients can poll it throughout
+/// the game.
+pub fn query_merkle_root(deps: Deps) -> StdResult<MerkleRootsResponse> {
+    let merkle_root_airdrop = MERKLE_ROOT_AIRDROP.may_load(deps.storage)?;
+    let total_amount = COUNTERS.load(deps.storage)?.total_airdrop_amount;
+    let merkle_root_game = MERKLE_ROOT_GAME.may_load(deps.storage)?;
+
+    let resp = MerkleRootsResponse {
+        merkle_root_airdrop,
+        total_amount,
+        merkle_root_game,
+        tree_metadata: TREE_METADATA.may_load(deps.storage)?,
+        claim_condition: CLAIM_CONDITION.may_load(deps.storage)?,
+    };
+
+    Ok(resp)
+}
+
+pub fn query_merkle_root_info(deps: Deps, kind: RootKind) -> StdResult<MerkleRootInfo> {
+    let counters = COUNTERS.load(deps.storage)?;
+    let (merkle_root, total_amount) = match kind {
+        RootKind::Airdrop => (MERKLE_ROOT_AIRDROP, counters.total_airdrop_amount),
+        RootKind::Game => (MERKLE_ROOT_GAME, counters.total_airdrop_game_amount),
+    };
+    let leaf_count = TREE_METADATA.may_load(deps.storage)?.map(|metadata| match kind {
+        RootKind::Airdrop => metadata.airdrop_leaf_count,
+        RootKind::Game => metadata.game_leaf_count,
+    });
+
+    Ok(MerkleRootInfo {
+        kind,
+        merkle_root: merkle_root.may_load(deps.storage)?,
+        // Totals can be funded before the roots are registered.
+        total_amount,
+        leaf_count,
+    })
+}
+
+pub fn query_latest_round(deps: Deps) -> StdResult<LatestRoundResponse> {
+    let latest_round = LATEST_ROUND.may_load(deps.storage)?.unwrap_or_default();
+    Ok(LatestRoundResponse { latest_round })
+}
+
+pub fn query_round(deps: Deps, round: u8) -> StdResult<RoundResponse> {
+    let airdrop_round = ROUNDS.load(deps.storage, round)?;
+    Ok(RoundResponse {
+        round,
+        merkle_root: airdrop_round.merkle_root,
+        stage: airdrop_round.stage,
+        total_amount: airdrop_round.total_amount,
+        claimed_amount: airdrop_round.claimed_amount,
+        withdrawn: airdrop_round.withdrawn,
+    })
+}
+
+pub fn query_is_round_claimed(deps: Deps, round: u8, address: String) -> StdResult<IsRoundClaimedResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let is_claimed = ROUND_CLAIMED.has(deps.storage, (round, &address));
+    Ok(IsRoundClaimedResponse { is_claimed })
+}
+
+pub fn query_claims_freeze(deps: Deps) -> StdResult<ClaimsFreezeResponse> {
+    let reason = CLAIMS_FROZEN.may_load(deps.storage)?;
+    let prize_veto = PRIZE_VETO.may_load(deps.storage)?;
+    Ok(ClaimsFreezeResponse { frozen: reason.is_some(), reason, prize_veto })
+}
+
+pub fn query_verify_tree_metadata(
+    deps: Deps,
+    merkle_root_airdrop: String,
+    merkle_root_game: String,
+    tree_metadata: TreeMetadata,
+) -> StdResult<VerifyTreeMetadataResponse> {
+    let registered = TREE_METADATA.load(deps.storage)?;
+    let checks = [
+        ("merkle_root_airdrop", MERKLE_ROOT_AIRDROP.load(deps.storage)? == merkle_root_airdrop),
+        ("merkle_root_game", MERKLE_ROOT_GAME.load(deps.storage)? == merkle_root_game),
+        ("airdrop_leaf_count", registered.airdrop_leaf_count == tree_metadata.airdrop_leaf_count),
+        ("game_leaf_count", registered.game_leaf_count == tree_metadata.game_leaf_count),
+        (
+            "allocation_checksum",
+            registered.allocation_checksum == tree_metadata.allocation_checksum.to_lowercase(),
+        ),
+    ];
+    let mismatches: Vec<String> = checks
+        .iter()
+        .filter(|(_, matches)| !matches)
+        .map(|(field, _)| field.to_string())
+        .collect();
+
+    Ok(VerifyTreeMetadataResponse { valid: mismatches.is_empty(), mismatches })
+}
+
+/// The account is not validated, so that any encoding can be compared.
+pub fn query_leaf_hash(deps: Deps, env: Env, account: String, amount: Uint128) -> StdResult<LeafHashResponse> {
+    let domain = CONFIG.load(deps.storage)?.leaf_domain(&env.contract.address);
+    Ok(LeafHashResponse { leaf: hex::encode(compute_leaf(&domain, &account, amount)) })
+}
+
+pub fn query_outcome(deps: Deps) -> StdResult<OutcomeResponse> {
+    Ok(OutcomeResponse {
+        commitment: OUTCOME_COMMITMENT.may_load(deps.storage)?,
+        merkle_root_game: REVEALED_OUTCOME.may_load(deps.storage)?,
+    })
+}
+
+pub fn query_game_amounts(deps: Deps) -> StdResult<GameAmountsResponse> {
+    let counters = COUNTERS.load(deps.storage)?;
+    let claims_funded = CLAIMS_FUNDED.may_load(deps.storage)?.unwrap_or(false);
+    let closed_snapshot = CLOSED_SNAPSHOT.may_load(deps.storage)?;
+    let secondary_payout = SECONDARY_PAYOUT.may_load(deps.storage)?;
+
+    let resp = GameAmountsResponse {
+        // Prizes
+        total_ticket_prize: counters.total_ticket_prize,
+        total_airdrop_amount: counters.total_airdrop_amount,
+        total_airdrop_game_amount: counters.total_airdrop_game_amount,
+        total_funded_native_airdrop: counters.funded_airdrop_native_amount,
+        // Number of winners
+        winners_amount: counters.winners,
+        // Claimed amount.
+        total_claimed_airdrop: counters.claimed_airdrop_amount,
+        total_claimed_prize: counters.claimed_prize_amount,
+        total_withdrawn_airdrop: counters.withdrawn_airdrop_amount,
+        claims_funded,
+        closed_snapshot,
+        secondary_payout,
+     };
+
+    Ok(resp)
+}
+
+/// The pot is read from the counters: the contract balance also holds the side-pools
+/// and the coins of other denoms.
+pub fn query_prize_pot(deps: Deps, env: Env) -> StdResult<PrizePotResponse> {
+    let counters = COUNTERS.load(deps.storage)?;
+    let GameParams { stage_bid, ticket_price, .. } = GAME_PARAMS.load(deps.storage)?;
+    let pot = counters.total_ticket_prize.checked_sub(counters.claimed_prize_amount)?;
+    let cfg = CONFIG.load(deps.storage)?;
+    Ok(PrizePotResponse {
+        pot: Coin { denom: ticket_price.denom, amount: pot },
+        total_ticket_prize: counters.total_ticket_prize,
+        total_ticket_payments: counters.total_ticket_payments,
+        total_claimed_prize: counters.claimed_prize_amount,
+        refund_mode: is_refund_mode(deps.storage, &cfg, &stage_bid, &env)?,
+    })
+}
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+pub fn query_bin_pools(deps: Deps) -> StdResult<BinPoolsResponse> {
+    // Bins are at most 256, no need to paginate.
+    let mut bins = BIN_POOLS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<BTreeSet<_>>>()?;
+
+    // In the per bin mode every winning bin has a share of the ticket prize.
+    let per_bin = CONFIG.load(deps.storage)?.prize_mode == PrizeMode::PerBin;
+    let mut snapshot = None;
+    if per_bin {
+        snapshot = CLOSED_SNAPSHOT.may_load(deps.storage)?;
+        for bin in BIN_WINNERS.keys(deps.storage, None, None, Order::Ascending) {
+            bins.insert(bin?);
+        }
+    }
+
+    let pools = bins
+        .into_iter()
+        .map(|bin| {
+            Ok(BinPoolResponse {
+                bin,
+                amount: BIN_POOLS.may_load(deps.storage, bin)?.unwrap_or_default(),
+                winners: BIN_WINNERS.may_load(deps.storage, bin)?.unwrap_or_default(),
+                ticket_prize: snapshot
+                    .as_ref()
+                    .map(|snapshot| snapshot.bin_ticket_prize(bin))
+                    .unwrap_or_default(),
+                claimed: BIN_CLAIMED_PRIZE.may_load(deps.storage, bin)?.unwrap_or_default(),
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(BinPoolsResponse { pools })
+}
+
+pub fn query_bin_distribution(deps: Deps, env: Env) -> StdResult<BinDistributionResponse> {
+    ensure_bins_disclosed(deps, &env)?;
+
+    // Bins are at most 256, no need to paginate.
+    let bins = (0..=GAME_PARAMS.load(deps.storage)?.bins)
+        .map(|bin| {
+            Ok(BinBidsResponse {
+                bin,
+                bids: BIN_BIDS.may_load(deps.storage, bin)?.unwrap_or_default(),
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    let total_bids = bins.iter().map(|bin| bin.bids).sum();
+
+    Ok(BinDistributionResponse { bins, total_bids })
+}
+
+pub fn query_bin_boost(deps: Deps, bin: u8, address: String) -> StdResult<BinBoostResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let amount = BIN_BOOSTS.may_load(deps.storage, (bin, &address))?.unwrap_or_default();
+    Ok(BinBoostResponse { amount })
+}
+
+pub fn query_top_boosters(deps: Deps, limit: Option<u32>) -> StdResult<TopBoostersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let boosters = BOOSTERS_BY_AMOUNT
+        .keys(deps.storage, None, None, Order::Descending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(amount, address)| BoosterResponse {
+                address: address.to_string(),
+                amount: Uint128::new(amount),
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TopBoostersResponse { boosters })
+}
+
+pub fn query_all_bids(
+    deps: Deps,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllBidsResponse> {
+    ensure_bids_disclosed(deps, &env)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let bids = BIDS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(address, bin)| BidInfo { address: address.to_string(), bin }))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AllBidsResponse { bids })
+}
+
+pub fn query_exclusions(deps: Deps) -> StdResult<ExclusionsResponse> {
+    let count = EXCLUDED_COUNT.may_load(deps.storage)?.unwrap_or_default();
+    Ok(ExclusionsResponse { count })
+}
+
+pub fn query_cohort_stats(deps: Deps) -> StdResult<CohortStatsResponse> {
+    // Cohorts are at most 256, no need to paginate.
+    let cohorts = COHORT_STATS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(cohort, stats)| CohortResponse { cohort, stats }))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(CohortStatsResponse { cohorts })
+}
+
+pub fn query_limits(deps: Deps) -> StdResult<LimitsResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    Ok(LimitsResponse {
+        limits: Limits {
+            max_batch_size: cfg.max_batch_size,
+            max_addresses_per_call: cfg.max_addresses_per_call,
+            max_proof_length: cfg.max_proof_length,
+        },
+        ceilings: Limits {
+            max_batch_size: MAX_BATCH_SIZE,
+            max_addresses_per_call: MAX_ADDRESSES_PER_CALL,
+            max_proof_length: MAX_PROOF_NODES,
+        },
+    })
+}
+
+pub fn query_failsafe(deps: Deps) -> StdResult<FailsafeResponse> {
+    Ok(FailsafeResponse { failsafe: FAILSAFE.may_load(deps.storage)? })
+}
+
+pub fn query_payout_split(deps: Deps, address: String) -> StdResult<PayoutSplitResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let shares = PAYOUT_SPLITS.may_load(deps.storage, &address)?.unwrap_or_default();
+    Ok(PayoutSplitResponse { shares })
+}
+
+/// Without a proof the contract cannot tell who is in the airdrop tree, so the airdrop
+/// claim is listed only for bidders and for addresses with cached proofs.
+pub fn query_pending_actions(deps: Deps, env: Env, address: String) -> StdResult<PendingActionsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let cfg = CONFIG.load(deps.storage)?;
+    let has_bid = BIDS.has(deps.storage, &address);
+    let mut actions = vec![];
+
+    let GameParams { stage_claim_airdrop, stage_claim_prize, .. } = GAME_PARAMS.load(deps.storage)?;
+    let airdrop_ended = stage_claim_airdrop.has_ended(&env.block)?;
+    let known = has_bid || CACHED_PROOFS.has(deps.storage, &address);
+    let claimed = CLAIM_AIRDROP.has(deps.storage, &address) || EXCLUDED.has(deps.storage, &address);
+    if known && !claimed && !airdrop_ended {
+        actions.push(PendingAction {
+            action: PendingActionKind::ClaimAirdrop,
+            opens_at: stage_claim_airdrop.start,
+            deadline: stage_claim_airdrop.end()?,
+        });
+    }
+    // Listed also while the address is not attested yet.
+    if WITHHELD_AIRDROP.has(deps.storage, &address) && !airdrop_ended {
+        actions.push(PendingAction {
+            action: PendingActionKind::ClaimWithheldAirdrop,
+            opens_at: stage_claim_airdrop.start,
+            deadline: stage_claim_airdrop.end()?,
+        });
+    }
+
+    // Winners are registered by their airdrop claim, except with merkle amounts: bids
+    // not registered by the end of the claim airdrop stage lost.
+    let settled = match CLAIM_PRIZE.may_load(deps.storage, &address)? {
+        Some(prize_claimed) => prize_claimed,
+        None => cfg.prize_mode != PrizeMode::MerkleAmounts && airdrop_ended,
+    };
+    if has_bid && !settled && !stage_claim_prize.has_ended(&env.block)? {
+        let opens_at = match cfg.prize_timelock {
+            Some(timelock) => prize_release(deps.storage, timelock)?,
+            None => stage_claim_prize.start,
+        };
+        actions.push(PendingAction {
+            action: PendingActionKind::ClaimPrize,
+            opens_at,
+            deadline: stage_claim_prize.end()?,
+        });
+    }
+
+    Ok(PendingActionsResponse { actions })
+}
+
+pub fn query_operators(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<OperatorsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let operators = OPERATORS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|addr| addr.to_string()))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(OperatorsResponse { operators })
+}
+
+pub fn query_treasuries(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TreasuriesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let treasuries = TREASURIES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(address, treasury)| TreasuryResponse {
+                address: address.to_string(),
+                active_at: treasury.active_at,
+                active: treasury.active,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TreasuriesResponse { treasuries })
+}
+
+pub fn query_withdrawal_proposals(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<WithdrawalProposalsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let proposals = WITHDRAWAL_PROPOSALS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(id, proposal)| WithdrawalProposalResponse {
+                id,
+                withdrawal: proposal.withdrawal,
+                proposer: proposal.proposer.to_string(),
+                expires: proposal.expires,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(WithdrawalProposalsResponse { proposals })
+}
+
+pub fn query_admin_actions(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<AdminActionsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let actions = ADMIN_ACTIONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(id, action)| AdminActionResponse {
+                id,
+                height: action.height,
+                sender: action.sender.to_string(),
+                action: action.action,
+                summary: action.summary,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AdminActionsResponse { actions })
+}
+
+pub fn query_claims_page(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ClaimsPageResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let claims = CLAIM_LOG
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(seq, claim)| ClaimRecordResponse {
+                seq,
+                address: claim.address.to_string(),
+                kind: claim.kind,
+                amount: claim.amount,
+                height: claim.height,
+                time: claim.time,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ClaimsPageResponse { claims })
+}
+
+// ======================================================================================
+// Utils
+// ======================================================================================
+/// Splits a prize pool among its winners. The dust of each pool is assigned only once,
+/// with the first claim.
+fn split_prize(
+    storage: &mut dyn Storage,
+    policy: &RoundingPolicy,
+    pool: &str,
+    total: Uint128,
+    winners: Uint128,
+) -> StdResult<PrizeSplit> {
+    let first_claim = !DUST_PAID.has(storage, pool);
+    if first_claim {
+        DUST_PAID.save(storage, pool, &true)?;
+    }
+    split_evenly(total, winners, policy, first_claim)
+}
+
+/// Records a claim of `amount` airdropped tokens in the sliding window of the stage.
+/// Fails with the first height at which enough claims leave the window when the claim
+/// would exceed the limit. A claim is always accepted when the window is empty.
+fn consume_claim_rate(
+    storage: &mut dyn Storage,
+    limit: &ClaimRateLimit,
+    stage: &str,
+    height: u64,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let window_start = (height + 1).saturating_sub(limit.window);
+    let usages = CLAIM_USAGE
+        .prefix(stage)
+        .range(storage, Some(Bound::inclusive(window_start)), None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut used = ClaimUsage::default();
+    for (_, usage) in &usages {
+        used.claims += usage.claims;
+        used.amount = used.amount.checked_add(usage.amount)?;
+    }
+
+    let exceeds = |used: &ClaimUsage| -> Result<bool, ContractError> {
+        if used.claims == 0 {
+            return Ok(false);
+        }
+        let too_many = limit.max_claims.is_some_and(|max| used.claims + 1 > max);
+        let too_much = match limit.max_amount {
+            Some(max) => used.amount.checked_add(amount)? > max,
+            None => false,
+        };
+        Ok(too_many || too_much)
+    };
+
+    if exceeds(&used)? {
+        // Find the oldest block whose exit from the window makes room for the claim.
+        for (usage_height, usage) in &usages {
+            used.claims -= usage.claims;
+            used.amount = used.amount.checked_sub(usage.amount)?;
+            if !exceeds(&used)? {
+                return Err(ContractError::RetryLater { next_height: usage_height + limit.window });
+            }
+        }
+    }
+
+    // Blocks that left the window are not read anymore: drop them, so that the usage
+    // stored never spans more than the window.
+    let expired = CLAIM_USAGE
+        .prefix(stage)
+        .keys(storage, None, Some(Bound::exclusive(window_start)), Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for usage_height in expired {
+        CLAIM_USAGE.remove(storage, (stage, usage_height));
+    }
+
+    CLAIM_USAGE.update(storage, (stage, height), |usage| -> Result<_, ContractError> {
+        let mut usage = usage.unwrap_or_default();
+        usage.claims += 1;
+        usage.amount = usage.amount.checked_add(amount)?;
+        Ok(usage)
+    })?;
+    Ok(())
+}
+
+/// Verifies that the key is the account key of the bidder and that the bidder owns it, by
+/// checking the signature of the sha256 hash of the contract address concatenated to the
+/// bidder address.
+fn check_pubkey_proof(
+    deps: Deps,
+    env: &Env,
+    bidder: &Addr,
+    proof: &PubkeyProof,
+) -> Result<(), ContractError> {
+    // The key must be the account key of the bidder, otherwise anyone holding a spare key
+    // could bid from as many addresses as they want.
+    let key_address = pubkey_canonical_address(&proof.pubkey).ok_or(ContractError::InvalidSignature {})?;
+    if deps.api.addr_canonicalize(bidder.as_str())? != key_address {
+        return Err(ContractError::PubkeyNotSender {});
+    }
+    check_signature(deps, format!("{}{}", env.contract.address, bidder).as_bytes(), proof)
+}
+
+/// Verifies the signature of the sha256 hash of `message` with the key of the proof.
+fn check_signature(deps: Deps, message: &[u8], proof: &PubkeyProof) -> Result<(), ContractError> {
+    let message_hash = Sha256::digest(message);
+    let verified = deps
+        .api
+        .secp256k1_verify(&message_hash, &proof.signature, &proof.pubkey)
+        .map_err(|_| ContractError::InvalidSignature {})?;
+    if !verified {
+        return Err(ContractError::InvalidSignature {});
+    }
+    Ok(())
+}
+
+/// Drops the `VERBOSE_ATTRIBUTES` of the response if the deployment asked for minimal
+/// attributes. Their values can still be read from the queries.
+fn trim_attributes(storage: &dyn Storage, mut res: Response) -> StdResult<Response> {
+    if CONFIG.load(storage)?.minimal_attributes {
+        res.attributes.retain(|attr| !VERBOSE_ATTRIBUTES.contains(&attr.key.as_str()));
+    }
+    Ok(res)
+}
+
+/// Appends an owner action to the admin log.
+fn record_admin_action(
+    storage: &mut dyn Storage,
+    env: &Env,
+    sender: &Addr,
+    action: &str,
+    summary: String,
+) -> StdResult<()> {
+    let id = ADMIN_ACTIONS_COUNT.may_load(storage)?.unwrap_or_default();
+    ADMIN_ACTIONS.save(
+        storage,
+        id,
+        &AdminAction {
+            height: env.block.height,
+            sender: sender.clone(),
+            action: action.to_string(),
+            summary,
+        },
+    )?;
+    ADMIN_ACTIONS_COUNT.save(storage, &(id + 1))
+}
+
+/// Fails if the tickets paid were below the minimum prize pool, so that they are refunded
+/// instead of paying a prize.
+fn check_prize_pool_minimum(deps: Deps, cfg: &Config) -> Result<(), ContractError> {
+    if CLOSED_SNAPSHOT.load(deps.storage)?.refund_mode {
+        return Err(prize_pool_below_minimum(deps.storage, cfg)?);
+    }
+    Ok(())
+}
+
+fn prize_pool_below_minimum(storage: &dyn Storage, cfg: &Config) -> StdResult<ContractError> {
+    Ok(ContractError::PrizePoolBelowMinimum {
+        pool: COUNTERS.load(storage)?.total_ticket_payments,
+        minimum: cfg.min_prize_pool.unwrap_or_default(),
+    })
+}
+
+/// Whether the game is in refund mode: the ticket payments are below the minimum prize
+/// pool. It is decided at the end of the bid stage, when no ticket can be paid or refunded
+/// anymore, and the boosts do not count, so that they cannot change it afterwards.
+fn is_refund_mode(storage: &dyn Storage, cfg: &Config, stage_bid: &Stage, env: &Env) -> StdResult<bool> {
+    let minimum = match cfg.min_prize_pool {
+        Some(minimum) => minimum,
+        None => return Ok(false),
+    };
+    Ok(stage_bid.has_ended(&env.block)? && COUNTERS.load(storage)?.total_ticket_payments < minimum)
+}
+
+fn check_claims_not_frozen(deps: Deps) -> Result<(), ContractError> {
+    match CLAIMS_FROZEN.may_load(deps.storage)? {
+        Some(reason) => Err(ContractError::ClaimsFrozen { reason }),
+        None => Ok(()),
+    }
+}
+
+/// Event releasing the prizes: `timelock` after the start of the claim prize stage.
+fn prize_release(storage: &dyn Storage, timelock: Duration) -> StdResult<Scheduled> {
+    Ok(checked_add(GAME_PARAMS.load(storage)?.stage_claim_prize.start, timelock)?)
+}
+
+fn check_prize_released(deps: Deps, env: &Env, timelock: Duration) -> Result<(), ContractError> {
+    if let Some(reason) = PRIZE_VETO.may_load(deps.storage)? {
+        return Err(ContractError::PrizeVetoed { reason });
+    }
+    if !prize_release(deps.storage, timelock)?.is_triggered(&env.block) {
+        return Err(ContractError::PrizeTimelocked {});
+    }
+    Ok(())
+}
+
+/// Total supply of a native denom.
+#[cfg(feature = "cosmwasm_1_1")]
+fn query_supply(deps: Deps, denom: &str) -> StdResult<Uint128> {
+    let request = to_vec(&SupplyQuery::Bank(BankSupplyQuery::Supply { denom: denom.to_string() }))?;
+    match deps.querier.raw_query(&request) {
+        SystemResult::Ok(ContractResult::Ok(value)) => {
+            Ok(from_binary::<SupplyResponse>(&value)?.amount.amount)
+        }
+        SystemResult::Ok(ContractResult::Err(err)) => {
+            Err(StdError::generic_err(format!("Querier contract error: {}", err)))
+        }
+        SystemResult::Err(err) => {
+            Err(StdError::generic_err(format!("Querier system error: {}", err)))
+        }
+    }
+}
+
+/// Fails if the contract holds less than `required` of the ticket denom.
+#[cfg(feature = "cosmwasm_1_1")]
+fn check_prize_balance(deps: Deps, env: &Env, denom: &str, required: Uint128) -> Result<(), ContractError> {
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, denom)?
+        .amount;
+    if balance < required {
+        return Err(ContractError::PrizeBalanceTooLow { balance, required });
+    }
+    Ok(())
+}
+
+/// Records the closed snapshot if the claim airdrop stage is over and no snapshot has
+/// been recorded yet.
+fn close_claim_window(storage: &mut dyn Storage, params: &GameParams, env: &Env) -> Result<(), ContractError> {
+    if CLOSED_SNAPSHOT.may_load(storage)?.is_some() {
+        return Ok(());
+    }
+    if !params.stage_claim_airdrop.has_ended(&env.block)? {
+        return Ok(());
+    }
+
+    let bin_winners = BIN_WINNERS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let counters = COUNTERS.load(storage)?;
+    let refund_mode = is_refund_mode(storage, &CONFIG.load(storage)?, &params.stage_bid, env)?;
+    let snapshot = ClosedSnapshot {
+        height: env.block.height,
+        winners: counters.winners,
+        bin_winners,
+        total_ticket_prize: counters.total_ticket_prize,
+        total_bin_boost: counters.total_bin_boost,
+        total_airdrop_amount: counters.total_airdrop_amount,
+        total_airdrop_game_amount: counters.total_airdrop_game_amount,
+        claimed_airdrop_amount: counters.claimed_airdrop_amount,
+        refund_mode,
+    };
+    CLOSED_SNAPSHOT.save(storage, &snapshot)?;
+
+    Ok(())
+}
+
+/// Splits the funds attached to a payable message between the `due` amount and the
+/// change. Repeated coins are summed by denom, and the change holds the excess of the
+/// due denom along with every other denom sent.
+fn split_payment(info: &MessageInfo, due: &Coin) -> Result<Vec<Coin>, ContractError> {
+    let mut sent: BTreeMap<&str, Uint128> = BTreeMap::new();
+    for coin in &info.funds {
+        let amount = sent.entry(coin.denom.as_str()).or_default();
+        *amount = amount.checked_add(coin.amount)?;
+    }
+
+    let paid = sent.remove(due.denom.as_str()).unwrap_or_default();
+    let excess = paid
+        .checked_sub(due.amount)
+        .map_err(|_| ContractError::TicketPriceNotPaid {})?;
+    sent.insert(due.denom.as_str(), excess);
+
+    Ok(sent
+        .into_iter()
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|(denom, amount)| Coin::new(amount.u128(), denom))
+        .collect())
+}
+
+/// Returns the change of a payment to the sender in a single transfer.
+fn refund_msg(sender: &Addr, change: Vec<Coin>) -> Option<CosmosMsg> {
+    if change.is_empty() {
+        return None;
+    }
+    Some(
+        BankMsg::Send {
+            to_address: sender.to_string(),
+            amount: change,
+        }
+        .into(),
+    )
+}
+
+/// Queries the attestation registry of the KYC gate for the address.
+fn has_attestation(deps: Deps, gate: &KycGate, address: &Addr) -> StdResult<bool> {
+    let res: AttestationResponse = deps.querier.query_wasm_smart(
+        &gate.registry,
+        &AttestationQueryMsg::Attestation { address: address.to_string() },
+    )?;
+    Ok(res.valid)
+}
+
+/// Queries the eligibility hook contract for the claimer address.
+fn check_eligibility(
+    deps: Deps,
+    hook: &EligibilityHook,
+    address: &Addr,
+) -> Result<(), ContractError> {
+    let msg = hook.query_template.replace(ADDRESS_PLACEHOLDER, address.as_str());
+    let res: EligibilityResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: hook.contract_addr.to_string(),
+        msg: Binary::from(msg.into_bytes()),
+    }))?;
+
+    if !res.eligible {
+        return Err(ContractError::NotEligibleByHook {
+            contract_addr: hook.contract_addr.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Checks the addresses of the condition and rejects empty combinators.
+fn validate_condition(deps: Deps, condition: &ClaimCondition) -> Result<(), ContractError> {
+    match condition {
+        ClaimCondition::MinBidPlaced {} => {}
+        ClaimCondition::HoldsNft { contract_addr }
+        | ClaimCondition::HoldsCw20 { contract_addr, .. } => {
+            deps.api.addr_validate(contract_addr)?;
+        }
+        ClaimCondition::And(conditions) | ClaimCondition::Or(conditions) => {
+            if conditions.is_empty() {
+                return Err(ContractError::InvalidInput {});
+            }
+            for condition in conditions {
+                validate_condition(deps, condition)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Evaluates the condition for the address. Combinators stop at the first condition
+/// deciding the result, sparing the remaining queries.
+fn evaluate_condition(
+    deps: Deps,
+    condition: &ClaimCondition,
+    address: &Addr,
+) -> Result<bool, ContractError> {
+    let satisfied = match condition {
+        ClaimCondition::MinBidPlaced {} => BIDS.has(deps.storage, address),
+        ClaimCondition::HoldsNft { contract_addr } => {
+            let res: TokensResponse = deps.querier.query_wasm_smart(
+                contract_addr,
+                &Cw721QueryMsg::Tokens {
+                    owner: address.to_string(),
+                    start_after: None,
+                    limit: Some(1),
+                },
+            )?;
+            !res.tokens.is_empty()
+        }
+        ClaimCondition::HoldsCw20 { contract_addr, amount } => {
+            let res: BalanceResponse = deps.querier.query_wasm_smart(
+                contract_addr,
+                &Cw20QueryMsg::Balance { address: address.to_string() },
+            )?;
+            res.balance >= *amount
+        }
+        ClaimCondition::And(conditions) => {
+            for condition in conditions {
+                if !evaluate_condition(deps, condition, address)? {
+                    return Ok(false);
+                }
+            }
+            true
+        }
+        ClaimCondition::Or(conditions) => {
+            for condition in conditions {
+                if evaluate_condition(deps, condition, address)? {
+                    return Ok(true);
+                }
+            }
+            false
+        }
+    };
+    Ok(satisfied)
+}
+
+/// Ensures that the native deposits cover all the airdrop claimed so far. Nothing to
+/// check when the airdrop is paid with the cw20 token.
+fn check_native_airdrop_funds(cfg: &Config, counters: &Counters) -> Result<(), ContractError> {
+    if cfg.airdrop_native_denom.is_some()
+        && counters.claimed_airdrop_amount > counters.funded_airdrop_native_amount
+    {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    Ok(())
+}
+
+/// True for the messages sending funds out of the contract, beyond the change of a payment.
+fn moves_funds(msg: &ExecuteMsg) -> bool {
+    matches!(
+        msg,
+        ExecuteMsg::RemoveBid {}
+            | ExecuteMsg::RefundBinBoost { .. }
+            | ExecuteMsg::RefundPrizeBoost {}
+            | ExecuteMsg::SwapPayoutToken { .. }
+            | ExecuteMsg::ClaimAirdrop { .. }
+            | ExecuteMsg::ClaimCachedAirdrop {}
+            | ExecuteMsg::ClaimAirdropFor { .. }
+            | ExecuteMsg::ClaimAirdropByIndex { .. }
+            | ExecuteMsg::ClaimWithheldAirdrop {}
+            | ExecuteMsg::ClaimPrize { .. }
+            | ExecuteMsg::SettleNoWinners {}
+            | ExecuteMsg::ClaimTicketRefund {}
+            | ExecuteMsg::WithdrawAirdrop { .. }
+            | ExecuteMsg::WithdrawSecondaryPayout { .. }
+            | ExecuteMsg::WithdrawPrize { .. }
+            | ExecuteMsg::ClaimRound { .. }
+            | ExecuteMsg::WithdrawRound { .. }
+            | ExecuteMsg::WithdrawRetiredPayout { .. }
+            | ExecuteMsg::ApproveWithdrawal { .. }
+    )
+}
+
+/// First invariant of the storage violated, checked before an operation moving funds.
+/// None of them can break through the messages of the contract, so a violation means
+/// corrupted storage or a bug: paying anything more could drain the contract.
+fn violated_invariant(
+    deps: Deps,
+    cfg: &Config,
+    params: &GameParams,
+    env: &Env,
+    msg: &ExecuteMsg,
+) -> StdResult<Option<&'static str>> {
+    let counters = COUNTERS.load(deps.storage)?;
+    // Native airdrops are bound to the deposits, their roots may not register any total.
+    let total_airdrop = match &cfg.airdrop_native_denom {
+        Some(_) => counters.funded_airdrop_native_amount,
+        None => counters.total_airdrop_amount.checked_add(counters.total_airdrop_game_amount)?,
+    };
+    if counters.claimed_airdrop_amount > total_airdrop {
+        return Ok(Some(INVARIANT_AIRDROP_CLAIMED));
+    }
+
+    let prize_pool = counters.total_ticket_prize.checked_add(counters.total_bin_boost)?;
+    let claimed_prize = counters.claimed_prize_amount.checked_add(counters.claimed_bin_boost)?;
+    if claimed_prize > prize_pool {
+        return Ok(Some(INVARIANT_PRIZE_CLAIMED));
+    }
+
+    // The claims cannot be paid without the roots once their stages have started.
+    let claim = matches!(
+        msg,
+        ExecuteMsg::ClaimAirdrop { .. }
+            | ExecuteMsg::ClaimCachedAirdrop {}
+            | ExecuteMsg::ClaimAirdropFor { .. }
+            | ExecuteMsg::ClaimAirdropByIndex { .. }
+            | ExecuteMsg::ClaimWithheldAirdrop {}
+            | ExecuteMsg::ClaimPrize { .. }
+            | ExecuteMsg::SettleNoWinners {}
+            | ExecuteMsg::ClaimTicketRefund {}
+    );
+    let roots_registered = MERKLE_ROOT_AIRDROP.may_load(deps.storage)?.is_some()
+        && MERKLE_ROOT_GAME.may_load(deps.storage)?.is_some();
+    if claim && !roots_registered && params.stage_claim_airdrop.has_started(&env.block) {
+        return Ok(Some(INVARIANT_ROOTS_MISSING));
+    }
+
+    Ok(None)
+}
+
+/// Records the violated invariant instead of running the operation. The response
+/// succeeds, otherwise the detection would be reverted along with the transaction, so
+/// the sender sees a successful transaction that did nothing: the `level` attribute set
+/// to `error` flags it to clients and indexers.
+fn trigger_failsafe(
+    storage: &mut dyn Storage,
+    env: &Env,
+    condition: &str,
+) -> Result<Response, ContractError> {
+    let failsafe = Failsafe {
+        condition: condition.to_string(),
+        triggered_at: env.block.height,
+    };
+    FAILSAFE.save(storage, &failsafe)?;
+
+    Ok(Response::new()
+        .add_attribute(ATTR_ACTION, "failsafe_triggered")
+        .add_attribute(ATTR_CONDITION, condition)
+        .add_attribute(ATTR_LEVEL, "error"))
+}
+
+/// Converts an allocation of the Merkle trees into the amount of cw20 token paid, after a
+/// swap of the payout token.
+fn payout_amount(storage: &dyn Storage, allocation: Uint128) -> Result<Uint128, ContractError> {
+    match PAYOUT_RATE.may_load(storage)? {
+        Some(rate) => Ok(checked_mul_rate(allocation, rate)?),
+        None => Ok(allocation),
+    }
+}
+
+/// `amount * rate` rounded down, like `Uint128 * Decimal`, failing instead of panicking
+/// when the result does not fit.
+fn checked_mul_rate(amount: Uint128, rate: Decimal) -> Result<Uint128, OverflowError> {
+    amount
+        .checked_multiply_ratio(rate.numerator(), rate.denominator())
+        .map_err(|_| OverflowError::new(OverflowOperation::Mul, amount, rate))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::limits::MAX_PROOF_NODES;
+    use crate::merkle::leaf_hash;
+    use crate::msg::{KycGateMsg, LateBidPenaltyMsg, RelativeStage, StageMsg, WithdrawalGuardMsg};
+    use crate::state::{LeafFormat, Stage, CLAIMED_BITMAP};
+
+    use super::*;
+    use cosmwasm_std::{from_binary, from_slice, to_vec, Api, CanonicalAddr, ContractResult, OverflowError, OverflowOperation, OwnedDeps, RecoverPubkeyError, Record, SystemError, SystemResult, VerificationError};
+    use cosmwasm_std::testing::{mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+    use cw20::TokenInfoResponse;
+    use std::cell::{Cell, RefCell};
+    use std::collections::HashMap;
+    use std::marker::PhantomData;
+    use cw_utils::{Duration, PaymentError, Scheduled};
+    use hex::FromHexError;
+    use crate::constants::{CONFIG_KEY, COUNTERS_KEY, GAME_PARAMS_KEY, TEST_DENOM};
+    use crate::limits::{MAX_ADDRESSES_PER_CALL, MAX_BATCH_SIZE, MAX_CONDITION_SIZE, MAX_MEMO_LENGTH};
+
+    fn valid_stages() -> (Stage, Stage, Stage) {
+        let stage_bid = Stage {
+            start: Scheduled::AtHeight(200_000),
+            duration: Duration::Height(2),
+        };
+
+        let stage_claim_airdrop = Stage {
+            start: Scheduled::AtHeight(203_000),
+            duration: Duration::Height(2),
+        };
+
+        let stage_claim_prize = Stage {
+            start: Scheduled::AtHeight(206_000),
+            duration: Duration::Height(2),
+        };
+
+        (stage_bid, stage_claim_airdrop, stage_claim_prize)
+    }
+
+    fn set_active_treasury(storage: &mut dyn Storage, address: &str) {
+        let treasury = Treasury { active_at: Scheduled::AtHeight(0), active: true };
+        TREASURIES.save(storage, &Addr::unchecked(address), &treasury).unwrap();
+    }
+
+    fn set_counter(storage: &mut dyn Storage, set: impl FnOnce(&mut Counters)) {
+        let mut counters = COUNTERS.may_load(storage).unwrap().unwrap_or_default();
+        set(&mut counters);
+        COUNTERS.save(storage, &counters).unwrap();
+    }
+
+    /// Mock api with account addresses derived from the keys as on chain, hex encoded
+    /// after the `cosmos` prefix in place of bech32. Other addresses use the mock encoding.
+    struct AccountApi(MockApi);
+
+    impl Api for AccountApi {
+        fn addr_validate(&self, human: &str) -> StdResult<Addr> {
+            self.addr_humanize(&self.addr_canonicalize(human)?)
+        }
+
+        fn addr_canonicalize(&self, human: &str) -> StdResult<CanonicalAddr> {
+            match human.strip_prefix("cosmos").map(hex::decode) {
+                Some(Ok(bytes)) if bytes.len() == 20 => Ok(bytes.into()),
+                _ => self.0.addr_canonicalize(human),
+            }
+        }
+
+        fn addr_humanize(&self, canonical: &CanonicalAddr) -> StdResult<Addr> {
+            match canonical.len() {
+                20 => Ok(Addr::unchecked(format!("cosmos{}", hex::encode(canonical.as_slice())))),
+                _ => self.0.addr_humanize(canonical),
+            }
+        }
+
+        fn secp256k1_verify(&self, hash: &[u8], signature: &[u8], pubkey: &[u8]) -> Result<bool, VerificationError> {
+            self.0.secp256k1_verify(hash, signature, pubkey)
+        }
+
+        fn secp256k1_recover_pubkey(&self, hash: &[u8], signature: &[u8], param: u8) -> Result<Vec<u8>, RecoverPubkeyError> {
+            self.0.secp256k1_recover_pubkey(hash, signature, param)
+        }
+
+        fn ed25519_verify(&self, message: &[u8], signature: &[u8], pubkey: &[u8]) -> Result<bool, VerificationError> {
+            self.0.ed25519_verify(message, signature, pubkey)
+        }
+
+        fn ed25519_batch_verify(
+            &self,
+            messages: &[&[u8]],
+            signatures: &[&[u8]],
+            pubkeys: &[&[u8]],
+        ) -> Result<bool, VerificationError> {
+            self.0.ed25519_batch_verify(messages, signatures, pubkeys)
+        }
+
+        fn debug(&self, message: &str) {
+            self.0.debug(message)
+        }
+    }
+
+    fn account_dependencies() -> OwnedDeps<MockStorage, AccountApi, MockQuerier> {
+        let deps = mock_dependencies();
+        OwnedDeps {
+            storage: deps.storage,
+            api: AccountApi(deps.api),
+            querier: deps.querier,
+            custom_query_type: PhantomData,
+        }
+    }
+
+    /// Address of the account of `key` for the `AccountApi`.
+    fn account_address(key: &k256::ecdsa::SigningKey) -> String {
+        let pubkey = key.verifying_key().to_bytes();
+        let canonical = crate::address::pubkey_canonical_address(pubkey.as_slice()).unwrap();
+        format!("cosmos{}", hex::encode(canonical.as_slice()))
+    }
+
+    fn tree_metadata() -> TreeMetadata {
+        TreeMetadata {
+            airdrop_leaf_count: 3,
+            game_leaf_count: 3,
+            allocation_checksum: "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08".to_string(),
+        }
+    }
+
+    /// Mock the cw20 contract `token`, answering the `Balance` and `TokenInfo` queries
+    /// with the given balances. Any other contract or query fails.
+    fn mock_cw20(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        token: &str,
+        balances: &[(&str, u128)],
+    ) {
+        let token = token.to_string();
+        let balances: HashMap<String, Uint128> = balances
+            .iter()
+            .map(|(address, balance)| (address.to_string(), Uint128::new(*balance)))
+            .collect();
+        deps.querier.update_wasm(move |query| {
+            let msg = match query {
+                WasmQuery::Smart { contract_addr, msg } if *contract_addr == token => msg,
+                WasmQuery::Smart { contract_addr, .. } | WasmQuery::Raw { contract_addr, .. } => {
+                    return SystemResult::Err(SystemError::NoSuchContract { addr: contract_addr.clone() });
+                }
+                _ => return SystemResult::Err(SystemError::UnsupportedRequest { kind: "wasm".to_string() }),
+            };
+            let res = match from_binary(msg) {
+                Ok(Cw20QueryMsg::Balance { address }) => to_binary(&BalanceResponse {
+                    balance: balances.get(&address).copied().unwrap_or_default(),
+                }),
+                Ok(Cw20QueryMsg::TokenInfo {}) => to_binary(&TokenInfoResponse {
+                    name: "Airdrop Token".to_string(),
+                    symbol: "DROP".to_string(),
+                    decimals: 6,
+                    total_supply: balances.values().copied().sum(),
+                }),
+                _ => return SystemResult::Err(SystemError::UnsupportedRequest { kind: "cw20".to_string() }),
+            };
+            SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+        });
+    }
+
+    /// Mock the cw20 balance of the game contract and try to open the claims.
+    fn open_claims(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        balance: u128,
+    ) -> Result<Response, ContractError> {
+        mock_cw20(deps, "random0000", &[(MOCK_CONTRACT_ADDR, balance)]);
+        execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), ExecuteMsg::OpenClaims {})
+    }
+
+    #[test]
+    fn stage_status() {
+        let (stage_bid, _, _) = valid_stages();
+        assert_eq!(stage_bid.end().unwrap(), Scheduled::AtHeight(200_002));
+
+        let mut env = mock_env();
+        for (height, status) in [
+            (199_999, StageStatus::NotStarted),
+            (200_000, StageStatus::Active),
+            (200_001, StageStatus::Active),
+            (200_002, StageStatus::Ended),
+        ] {
+            env.block.height = height;
+            assert_eq!(stage_bid.status(&env.block).unwrap(), status);
+            assert_eq!(stage_bid.is_active(&env.block).unwrap(), status == StageStatus::Active);
+        }
+
+        env.block.height = 199_999;
+        let res = stage_bid.ensure_active(&env.block, StageName::Bid).unwrap_err();
+        assert_eq!(res, ContractError::StageNotStarted { stage_name: "bid".to_string() });
+        env.block.height = 200_002;
+        let res = stage_bid.ensure_active(&env.block, StageName::Bid).unwrap_err();
+        assert_eq!(res, ContractError::StageEnded { stage_name: "bid".to_string() });
+
+        // Stages cannot mix heights and times.
+        let stage = Stage { start: Scheduled::AtHeight(1), duration: Duration::Time(1) };
+        assert!(stage.has_ended(&env.block).is_err());
+    }
+
+    #[test]
+    fn game_interface() {
+        use cw_game_interface::{BidResponse as GameBidResponse, GameQueryMsg};
+
+        let mut deps = mock_dependencies();
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin::new(10, TEST_DENOM),
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        // The queries of the interface are answered as the ones of the game.
+        let game_query = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, env: &Env, msg: GameQueryMsg| {
+            let msg: QueryMsg = from_binary(&to_binary(&msg).unwrap()).unwrap();
+            query(deps.as_ref(), env.clone(), msg).unwrap()
+        };
+
+        let mut env = mock_env();
+        for (height, phase, stage) in [
+            (199_999, GamePhase::NotStarted, None),
+            (200_000, GamePhase::Bidding, Some("bid")),
+            (200_002, GamePhase::Settling, None),
+            (203_000, GamePhase::Claiming, Some("claim airdrop")),
+            (203_002, GamePhase::Claiming, None),
+            (206_001, GamePhase::Claiming, Some("claim prize")),
+            (206_002, GamePhase::Ended, None),
+        ] {
+            env.block.height = height;
+            let res: StageStatusResponse = from_binary(&game_query(&deps, &env, GameQueryMsg::StageStatus {})).unwrap();
+            assert_eq!(res, StageStatusResponse { phase, stage: stage.map(String::from) });
+        }
+
+        env.block.height = 200_000;
+        let info = mock_info("player0000", &[Coin::new(10, TEST_DENOM)]);
+        execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin: 3, pubkey_proof: None, ticket_id: None }).unwrap();
+
+        // The extra fields of the bid of the game are ignored.
+        let msg = GameQueryMsg::Bid { address: "player0000".to_string() };
+        let res: GameBidResponse = from_binary(&game_query(&deps, &env, msg)).unwrap();
+        assert_eq!(res, GameBidResponse { has_bid: true, bin: Some(3) });
+        let msg = GameQueryMsg::Bid { address: "player0001".to_string() };
+        let res: GameBidResponse = from_binary(&game_query(&deps, &env, msg)).unwrap();
+        assert_eq!(res, GameBidResponse { has_bid: false, bin: None });
+
+        // The outcome is known once the winner claims.
+        let is_winner = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, address: &str| {
+            let msg = GameQueryMsg::IsWinner { address: address.to_string() };
+            let res: IsWinnerResponse = from_binary(&game_query(deps, &mock_env(), msg)).unwrap();
+            res.is_winner
+        };
+        assert_eq!(is_winner(&deps, "player0000"), None);
+        CLAIM_PRIZE.save(deps.as_mut().storage, &Addr::unchecked("player0000"), &false).unwrap();
+        assert_eq!(is_winner(&deps, "player0000"), Some(true));
+        assert_eq!(is_winner(&deps, "player0001"), None);
+    }
+
+    #[test]
+    fn nonpayable_messages() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let info = mock_info("player0000", &[Coin::new(10, TEST_DENOM)]);
+        let msg = ExecuteMsg::Bid { bin: 3, pubkey_proof: None, ticket_id: None };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Messages that do not pay anything reject the coins attached.
+        let msgs = vec![
+            ExecuteMsg::ChangeBid { bin: 4 },
+            ExecuteMsg::RemoveBid {},
+            ExecuteMsg::UpdateConfig { new_owner: None },
+            ExecuteMsg::ClaimAirdrop {
+                amount: Uint128::new(100),
+                proof_airdrop: vec![],
+                proof_game: vec![],
+            },
+        ];
+        for msg in msgs {
+            let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            assert_eq!(res, ContractError::Payment(PaymentError::NonPayable {}));
+        }
+        let res = execute(deps.as_mut(), env, mock_info("player0000", &[]), ExecuteMsg::ChangeBid { bin: 4 });
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn bid_change_limit() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: Some(2),
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let bid = ExecuteMsg::Bid { bin: 3, pubkey_proof: None, ticket_id: None };
+        let paid = mock_info("player0000", &[Coin::new(10, TEST_DENOM)]);
+        let info = mock_info("player0000", &[]);
+        execute(deps.as_mut(), env.clone(), paid.clone(), bid.clone()).unwrap();
+        for bin in [4, 5] {
+            execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::ChangeBid { bin }).unwrap();
+        }
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::ChangeBid { bin: 6 }).unwrap_err();
+        assert_eq!(res, ContractError::TooManyBidChanges { max: 2 });
+
+        // A new bid does not reset the changes.
+        execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::RemoveBid {}).unwrap();
+        execute(deps.as_mut(), env.clone(), paid, bid).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::ChangeBid { bin: 6 }).unwrap_err();
+        assert_eq!(res, ContractError::TooManyBidChanges { max: 2 });
+
+        // Other addresses have their own changes.
+        let info = mock_info("player0001", &[Coin::new(10, TEST_DENOM)]);
+        execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin: 3, pubkey_proof: None, ticket_id: None }).unwrap();
+        execute(deps.as_mut(), env, mock_info("player0001", &[]), ExecuteMsg::ChangeBid { bin: 6 }).unwrap();
+    }
+
+    #[test]
+    fn late_bid_penalty() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+        assert_eq!(stage_bid.final_part_start(Decimal::percent(50)).unwrap(), Scheduled::AtHeight(200_001));
+
+        let penalty = LateBidPenaltyMsg {
+            window: Decimal::percent(50),
+            fee: Uint128::new(5),
+            treasury: None,
+        };
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: Some(penalty.clone()),
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        let invalid = LateBidPenaltyMsg { window: Decimal::percent(101), ..penalty.clone() };
+        let res = instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner0000", &[]),
+            InstantiateMsg { late_bid_penalty: Some(invalid), ..msg.clone() },
+        )
+        .unwrap_err();
+        assert_eq!(res, ContractError::InvalidLateBidPenalty {});
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg.clone()).unwrap();
+
+        let bid = ExecuteMsg::Bid { bin: 3, pubkey_proof: None, ticket_id: None };
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let info = mock_info("player0000", &[Coin::new(10, TEST_DENOM)]);
+        let res = execute(deps.as_mut(), env.clone(), info, bid.clone()).unwrap();
+        assert_eq!(res.attributes.len(), 3);
+
+        // The fee is due in the second half of the bid stage and added to the prize.
+        env.block.height = 200_001;
+        let info = mock_info("player0001", &[Coin::new(10, TEST_DENOM)]);
+        let res = execute(deps.as_mut(), env.clone(), info, bid.clone()).unwrap_err();
+        assert_eq!(res, ContractError::TicketPriceNotPaid {});
+        let info = mock_info("player0002", &[Coin::new(16, TEST_DENOM)]);
+        let res = execute(deps.as_mut(), env.clone(), info, bid.clone()).unwrap();
+        assert_eq!(res.attributes[3], attr("late_fee", "5"));
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send { to_address: "player0002".to_string(), amount: vec![Coin::new(1, TEST_DENOM)] })]
+        );
+        assert_eq!(COUNTERS.load(&deps.storage).unwrap().total_ticket_prize, Uint128::new(25));
+
+        // Fees sent to the treasury are not part of the prize.
+        let mut deps = mock_dependencies();
+        let penalty = LateBidPenaltyMsg { treasury: Some("treasury0000".to_string()), ..penalty };
+        let msg = InstantiateMsg { late_bid_penalty: Some(penalty), ..msg };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+        let info = mock_info("player0001", &[Coin::new(15, TEST_DENOM)]);
+        let res = execute(deps.as_mut(), env, info, bid).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send { to_address: "treasury0000".to_string(), amount: vec![Coin::new(5, TEST_DENOM)] })]
+        );
+        assert_eq!(COUNTERS.load(&deps.storage).unwrap().total_ticket_prize, Uint128::new(10));
+    }
+
+    #[test]
+    fn private_bins() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: Some(true),
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let info = mock_info("player0000", &[Coin::new(10, TEST_DENOM)]);
+        execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin: 3, pubkey_proof: None, ticket_id: None }).unwrap();
+
+        let queries = [
+            QueryMsg::Bid { address: "player0000".to_string(), viewer_proof: None },
+            QueryMsg::LegacyBid { address: "player0000".to_string() },
+            QueryMsg::AllBids { start_after: None, limit: None },
+            QueryMsg::BinDistribution {},
+        ];
+        for msg in queries.clone() {
+            let err = query(deps.as_ref(), env.clone(), msg).unwrap_err();
+            assert_eq!(err, StdError::generic_err(ContractError::PrivateBins {}.to_string()));
+        }
+
+        // The bins are disclosed once the bid stage ends.
+        env.block.height = 200_002;
+        for msg in queries {
+            query(deps.as_ref(), env.clone(), msg).unwrap();
+        }
+        let res: AllBidsResponse =
+            from_binary(&query(deps.as_ref(), env, QueryMsg::AllBids { start_after: None, limit: None }).unwrap())
+                .unwrap();
+        assert_eq!(res.bids[0].bin, 3);
+    }
+
+    #[test]
+    fn hidden_bids() {
+        use k256::ecdsa::signature::Signer;
+        use k256::ecdsa::{Signature, SigningKey};
+
+        let mut deps = account_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: Some(true),
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let signing_key = SigningKey::from_bytes(&[1u8; 32]).unwrap();
+        let player = account_address(&signing_key);
+        let proof_for = |message: String| {
+            let signature: Signature = signing_key.sign(message.as_bytes());
+            PubkeyProof {
+                pubkey: Binary::from(signing_key.verifying_key().to_bytes().as_slice()),
+                signature: Binary::from(signature.as_ref()),
+            }
+        };
+        let bid_proof = proof_for(format!("{}{}", env.contract.address, player));
+        let viewer_proof = proof_for(format!("view{}{}", env.contract.address, player));
+
+        // The key is optional, bids without it are hidden from their bidder too.
+        let info = mock_info(&player, &[Coin::new(10, TEST_DENOM)]);
+        let msg = ExecuteMsg::Bid { bin: 3, pubkey_proof: Some(bid_proof.clone()), ticket_id: None };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let info = mock_info("player0001", &[Coin::new(10, TEST_DENOM)]);
+        execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin: 5, pubkey_proof: None, ticket_id: None }).unwrap();
+
+        let query_bid = |env: &Env, address: &str, viewer_proof: Option<PubkeyProof>| {
+            let msg = QueryMsg::Bid { address: address.to_string(), viewer_proof };
+            query(deps.as_ref(), env.clone(), msg).map(|res| from_binary::<BidResponse>(&res).unwrap())
+        };
+        let res = query_bid(&env, &player, None).unwrap();
+        assert!(res.has_bid);
+        assert_eq!(res.bin, None);
+        assert_eq!(query_bid(&env, &player, Some(viewer_proof.clone())).unwrap().bin, Some(3));
+
+        // The proof of the bid cannot be replayed, nor the key used for another bid.
+        let invalid_signature = StdError::generic_err(ContractError::InvalidSignature {}.to_string());
+        assert_eq!(query_bid(&env, &player, Some(bid_proof)).unwrap_err(), invalid_signature);
+        assert_eq!(query_bid(&env, "player0001", Some(viewer_proof)).unwrap_err(), invalid_signature);
+
+        let queries = [
+            QueryMsg::LegacyBid { address: player.clone() },
+            QueryMsg::AllBids { start_after: None, limit: None },
+        ];
+        for msg in queries.clone() {
+            let err = query(deps.as_ref(), env.clone(), msg).unwrap_err();
+            assert_eq!(err, StdError::generic_err(ContractError::PrivateBins {}.to_string()));
+        }
+
+        // The bins are disclosed once the bid stage ends.
+        env.block.height = 200_002;
+        assert_eq!(query_bid(&env, "player0001", None).unwrap().bin, Some(5));
+        for msg in queries {
+            query(deps.as_ref(), env.clone(), msg).unwrap();
+        }
+    }
+
+    #[test]
+    fn bin_distribution() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 3,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        for (player, bin) in [("player0000", 1), ("player0001", 1), ("player0002", 2)] {
+            let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+            let msg = ExecuteMsg::Bid { bin, pubkey_proof: None, ticket_id: None };
+            execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        }
+        let distribution = |deps: Deps| -> Vec<u32> {
+            let res: BinDistributionResponse =
+                from_binary(&query(deps, env.clone(), QueryMsg::BinDistribution {}).unwrap()).unwrap();
+            assert_eq!(res.total_bids, res.bins.iter().map(|bin| bin.bids).sum::<u32>());
+            res.bins.iter().map(|bin| bin.bids).collect()
+        };
+        // Every bin is listed, the empty ones included.
+        assert_eq!(distribution(deps.as_ref()), vec![0, 2, 1, 0]);
+
+        // Changed and removed bids move out of their bin.
+        let info = mock_info("player0001", &[]);
+        execute(deps.as_mut(), env.clone(), info, ExecuteMsg::ChangeBid { bin: 3 }).unwrap();
+        assert_eq!(distribution(deps.as_ref()), vec![0, 1, 1, 1]);
+        let info = mock_info("player0002", &[]);
+        execute(deps.as_mut(), env.clone(), info, ExecuteMsg::RemoveBid {}).unwrap();
+        assert_eq!(distribution(deps.as_ref()), vec![0, 1, 0, 1]);
+        assert!(!BIN_BIDS.has(&deps.storage, 2));
+    }
+
+    #[test]
+    fn claim_prize_then() {
+        let mut deps = mock_dependencies_with_balance(&[Coin::new(1_000, TEST_DENOM)]);
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: Some(PrizeMode::MerkleAmounts),
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        for player in ["player0000", "player0001"] {
+            let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: None }).unwrap();
+        }
+
+        let leaves = vec![leaf_hash("player0000", "15"), leaf_hash("player0001", "5")];
+        let tree = crate::merkle::MerkleTree::new(leaves.clone());
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: "b45c1ea28b26adb13e412933c9e055b01fdf7585304b00cd8f1cb220aa6c5e88".to_string(),
+            total_amount_airdrop: None,
+            merkle_root_game: tree.root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+
+        env.block.height = 206_000;
+
+        // The prize is attached to the message of the target contract.
+        let stake = to_binary(&"stake").unwrap();
+        let msg = ExecuteMsg::ClaimPrize {
+            amount: Some(Uint128::new(15)),
+            proof: tree.proof(&leaves[0]),
+            then: Some(PrizeHookMsg { contract_addr: "staking0000".to_string(), msg: stake.clone() }),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(WasmMsg::Execute {
+                contract_addr: "staking0000".to_string(),
+                msg: stake,
+                funds: vec![Coin::new(15, TEST_DENOM)],
+            })]
+        );
+        assert_eq!(res.attributes.last(), Some(&attr("recipient", "staking0000")));
+
+        // Without `then` the prize goes to the winner.
+        let msg = ExecuteMsg::ClaimPrize { amount: Some(Uint128::new(5)), proof: tree.proof(&leaves[1]), then: None };
+        let res = execute(deps.as_mut(), env, mock_info("player0001", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send { to_address: "player0001".to_string(), amount: vec![Coin::new(5, TEST_DENOM)] })]
+        );
+        assert_eq!(res.attributes.last(), Some(&attr("recipient", "player0001")));
+    }
+
+    #[test]
+    fn payout_split() {
+        let mut deps = mock_dependencies_with_balance(&[Coin::new(1_000, TEST_DENOM)]);
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: Some(PrizeMode::MerkleAmounts),
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        for player in ["player0000", "player0001"] {
+            let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: None }).unwrap();
+        }
+
+        let leaves = vec![leaf_hash("player0000", "15"), leaf_hash("player0001", "5")];
+        let tree = crate::merkle::MerkleTree::new(leaves.clone());
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: "b45c1ea28b26adb13e412933c9e055b01fdf7585304b00cd8f1cb220aa6c5e88".to_string(),
+            total_amount_airdrop: None,
+            merkle_root_game: tree.root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let share = |address: &str, bps: u16| PayoutShareMsg { address: address.to_string(), bps };
+        let split = |shares: Vec<PayoutShareMsg>| ExecuteMsg::SetPayoutSplit { shares };
+
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0002", &[]), split(vec![share("team0000", 10_000)])).unwrap_err();
+        assert_eq!(res, ContractError::BidNotPresent {});
+        for shares in [
+            vec![share("team0000", 0), share("team0001", 10_000)],
+            vec![share("team0000", 5_000), share("team0000", 5_000)],
+            vec![share("team0000", 5_000), share("team0001", 4_999)],
+            vec![share("team0000", 5_000), share("team0001", 5_001)],
+        ] {
+            let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), split(shares)).unwrap_err();
+            assert_eq!(res, ContractError::InvalidPayoutSplit { total: TOTAL_BPS });
+        }
+        let shares = (0..=MAX_ADDRESSES_PER_CALL).map(|i| share(&format!("team{:04}", i), 1)).collect();
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), split(shares)).unwrap_err();
+        assert_eq!(res, ContractError::TooManyAddresses { max: MAX_ADDRESSES_PER_CALL });
+
+        execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), split(vec![share("team0000", 3_333), share("team0001", 6_667)])).unwrap();
+        let msg = QueryMsg::PayoutSplit { address: "player0000".to_string() };
+        let res: PayoutSplitResponse = from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(
+            res.shares,
+            vec![
+                PayoutShare { address: Addr::unchecked("team0000"), bps: 3_333 },
+                PayoutShare { address: Addr::unchecked("team0001"), bps: 6_667 }
+            ]
+        );
+
+        // An empty split pays the whole prize to the winner again.
+        execute(deps.as_mut(), env.clone(), mock_info("player0001", &[]), split(vec![share("team0002", 10_000)])).unwrap();
+        execute(deps.as_mut(), env.clone(), mock_info("player0001", &[]), split(vec![])).unwrap();
+
+        env.block.height = 206_000;
+
+        // The split cannot be combined with a prize hook.
+        let msg = ExecuteMsg::ClaimPrize {
+            amount: Some(Uint128::new(15)),
+            proof: tree.proof(&leaves[0]),
+            then: Some(PrizeHookMsg { contract_addr: "staking0000".to_string(), msg: to_binary(&"stake").unwrap() }),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), msg).unwrap_err();
+        assert_eq!(res, ContractError::PayoutSplitWithHook {});
+
+        // Shares are rounded down, the first one receives the remainder.
+        let msg = ExecuteMsg::ClaimPrize { amount: Some(Uint128::new(15)), proof: tree.proof(&leaves[0]), then: None };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send { to_address: "team0000".to_string(), amount: vec![Coin::new(5, TEST_DENOM)] }),
+                SubMsg::new(BankMsg::Send { to_address: "team0001".to_string(), amount: vec![Coin::new(10, TEST_DENOM)] })
+            ]
+        );
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), split(vec![])).unwrap_err();
+        assert_eq!(res, ContractError::AlreadyClaimed {});
+
+        let msg = ExecuteMsg::ClaimPrize { amount: Some(Uint128::new(5)), proof: tree.proof(&leaves[1]), then: None };
+        let res = execute(deps.as_mut(), env, mock_info("player0001", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send { to_address: "player0001".to_string(), amount: vec![Coin::new(5, TEST_DENOM)] })]
+        );
+    }
+
+    #[test]
+    fn minimal_attributes() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: Some(true),
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+        let info = mock_info("owner0000", &[]);
+
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: "b45c1ea28b26adb13e412933c9e055b01fdf7585304b00cd8f1cb220aa6c5e88".to_string(),
+            total_amount_airdrop: None,
+            merkle_root_game: "b45c1ea28b26adb13e412933c9e055b01fdf7585304b00cd8f1cb220aa6c5e88".to_string(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let keys: Vec<_> = res.attributes.iter().map(|attr| attr.key.as_str()).collect();
+        assert_eq!(keys, vec!["action", "total_amount_airdrop", "overwrite"]);
+
+        let msg = ExecuteMsg::CommitOutcome { commitment: "ab".repeat(32) };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(res.attributes, vec![attr("action", "commit_outcome")]);
+
+        // The reason is still returned by the query.
+        let msg = ExecuteMsg::FreezeClaims { reason: "Suspicious root".to_string() };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.attributes, vec![attr("action", "freeze_claims")]);
+        let freeze: ClaimsFreezeResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ClaimsFreeze {}).unwrap()).unwrap();
+        assert_eq!(freeze.reason, Some("Suspicious root".to_string()));
+    }
+
+    #[test]
+    fn prize_timelock() {
+        let mut deps = mock_dependencies_with_balance(&[Coin::new(1_000, TEST_DENOM)]);
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: Some(PrizeMode::MerkleAmounts),
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: Some(Duration::Height(2)),
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        // Prizes must be released before the end of the claim prize stage.
+        let info = mock_info("owner0000", &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        assert_eq!(res, ContractError::PrizeTimelockTooLong {});
+        let msg = InstantiateMsg { prize_timelock: Some(Duration::Height(1)), ..msg };
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        for player in ["player0000", "player0001"] {
+            let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: None }).unwrap();
+        }
+
+        let leaves = vec![leaf_hash("player0000", "10"), leaf_hash("player0001", "10")];
+        let tree = crate::merkle::MerkleTree::new(leaves.clone());
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: "b45c1ea28b26adb13e412933c9e055b01fdf7585304b00cd8f1cb220aa6c5e88".to_string(),
+            total_amount_airdrop: None,
+            merkle_root_game: tree.root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let claim = |leaf: usize| ExecuteMsg::ClaimPrize {
+            amount: Some(Uint128::new(10)),
+            proof: tree.proof(&leaves[leaf]),
+            then: None,
+        };
+
+        // Prizes are locked during the veto period.
+        env.block.height = 206_000;
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim(0)).unwrap_err();
+        assert_eq!(res, ContractError::PrizeTimelocked {});
+
+        let veto = ExecuteMsg::VetoPrize { reason: "Disputed result".to_string() };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), veto.clone()).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+        execute(deps.as_mut(), env.clone(), info.clone(), veto.clone()).unwrap();
+        let freeze: ClaimsFreezeResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::ClaimsFreeze {}).unwrap()).unwrap();
+        assert_eq!(freeze.prize_veto, Some("Disputed result".to_string()));
+
+        // The veto outlasts the timelock, but cannot be cast once the prizes are released.
+        env.block.height = 206_001;
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim(0)).unwrap_err();
+        assert_eq!(res, ContractError::PrizeVetoed { reason: "Disputed result".to_string() });
+        execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::LiftPrizeVeto {}).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), veto).unwrap_err();
+        assert_eq!(res, ContractError::VetoPeriodEnded {});
+        execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim(0)).unwrap();
+
+        // Released prizes are not blocked by a claims freeze.
+        let freeze = ExecuteMsg::FreezeClaims { reason: "Suspicious root".to_string() };
+        execute(deps.as_mut(), env.clone(), info, freeze).unwrap();
+        execute(deps.as_mut(), env, mock_info("player0001", &[]), claim(1)).unwrap();
+    }
+
+    #[test]
+    fn import_exclusions() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let airdrop_leaves = vec![leaf_hash("player0000", "100"), leaf_hash("player0001", "100")];
+        let airdrop_tree = crate::merkle::MerkleTree::new(airdrop_leaves.clone());
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: airdrop_tree.root(),
+            total_amount_airdrop: Some(Uint128::new(200)),
+            merkle_root_game: "b45c1ea28b26adb13e412933c9e055b01fdf7585304b00cd8f1cb220aa6c5e88".to_string(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+        open_claims(&mut deps, 200).unwrap();
+
+        let exclude = |addresses: &[&str]| ExecuteMsg::ImportExclusions {
+            addresses: addresses.iter().map(|address| address.to_string()).collect(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("player0000", &[]), exclude(&["player0000"])).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+        let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), exclude(&["player0000"; 21])).unwrap_err();
+        assert_eq!(res, ContractError::TooManyAddresses { max: 20 });
+
+        // Addresses already excluded are not counted twice.
+        let info = mock_info("owner0000", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), exclude(&["player0000", "player0000"])).unwrap();
+        assert_eq!(res.attributes[1..], [attr("imported", "1"), attr("total_excluded", "1")]);
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), exclude(&["player0000", "player0002"])).unwrap();
+        assert_eq!(res.attributes[1..], [attr("imported", "1"), attr("total_excluded", "2")]);
+        let res: ExclusionsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Exclusions {}).unwrap()).unwrap();
+        assert_eq!(res.count, 2);
+        let res = query_is_claimed(deps.as_ref(), "player0000".to_string()).unwrap();
+        assert!(res.excluded && !res.airdrop_claimed);
+
+        // Excluded addresses are treated as if they had already claimed.
+        let mut env = mock_env();
+        env.block.height = 203_000;
+        let claim = |i: usize| ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::new(100),
+            proof_airdrop: airdrop_tree.proof(&airdrop_leaves[i]).unwrap(),
+            proof_game: vec![],
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim(0)).unwrap_err();
+        assert_eq!(res, ContractError::AlreadyClaimed {});
+        execute(deps.as_mut(), env.clone(), mock_info("player0001", &[]), claim(1)).unwrap();
+
+        // Addresses that already claimed are skipped.
+        let res = execute(deps.as_mut(), env, info, exclude(&["player0001"])).unwrap();
+        assert_eq!(res.attributes[1..], [attr("imported", "0"), attr("total_excluded", "2")]);
+    }
+
+    #[test]
+    fn cohort_stats() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let airdrop_leaves = vec![
+            leaf_hash("player0000", "100"),
+            leaf_hash("player0001", "50"),
+            leaf_hash("player0002", "30"),
+        ];
+        let airdrop_tree = crate::merkle::MerkleTree::new(airdrop_leaves.clone());
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: airdrop_tree.root(),
+            total_amount_airdrop: Some(Uint128::new(180)),
+            merkle_root_game: "b45c1ea28b26adb13e412933c9e055b01fdf7585304b00cd8f1cb220aa6c5e88".to_string(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+        open_claims(&mut deps, 180).unwrap();
+
+        let tag = |cohort, addresses: &[&str]| ExecuteMsg::TagCohort {
+            cohort,
+            addresses: addresses.iter().map(|address| address.to_string()).collect(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("player0000", &[]), tag(1, &["player0000"])).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+
+        // Tagging again moves the address to the new cohort, and empty cohorts are dropped.
+        let info = mock_info("owner0000", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), tag(1, &["player0000", "player0001"])).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), tag(3, &["player0002"])).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), tag(2, &["player0001", "player0002"])).unwrap();
+        assert_eq!(res.attributes[1..], [attr("cohort", "2"), attr("imported", "2")]);
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), tag(2, &["player0001"])).unwrap();
+        assert_eq!(res.attributes[1..], [attr("cohort", "2"), attr("imported", "0")]);
+
+        // Stats out of sync with the tags fail instead of wrapping around.
+        let stray = Addr::unchecked("player0003");
+        COHORTS.save(&mut deps.storage, &stray, &4).unwrap();
+        COHORT_STATS.save(&mut deps.storage, 4, &CohortStats { airdrop_claims: 1, ..CohortStats::default() }).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), tag(1, &["player0003"])).unwrap_err();
+        assert_eq!(res, ContractError::Overflow(OverflowError::new(OverflowOperation::Sub, 0u32, 1u32)));
+        COHORTS.remove(&mut deps.storage, &stray);
+        COHORT_STATS.remove(&mut deps.storage, 4);
+
+        // Every claim is aggregated in the cohort of its address.
+        let mut env = mock_env();
+        env.block.height = 203_000;
+        let claim = |i: usize, amount| ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::new(amount),
+            proof_airdrop: airdrop_tree.proof(&airdrop_leaves[i]).unwrap(),
+            proof_game: vec![],
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim(0, 100)).unwrap();
+        execute(deps.as_mut(), env.clone(), mock_info("player0001", &[]), claim(1, 50)).unwrap();
+
+        let res: CohortStatsResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::CohortStats {}).unwrap()).unwrap();
+        let stats = |tagged, claimed_airdrop_amount| CohortStats {
+            tagged,
+            airdrop_claims: 1,
+            claimed_airdrop_amount: Uint128::new(claimed_airdrop_amount),
+            prize_claims: 0,
+            claimed_prize_amount: Uint128::zero(),
+        };
+        assert_eq!(
+            res.cohorts,
+            vec![
+                CohortResponse { cohort: 1, stats: stats(1, 100) },
+                CohortResponse { cohort: 2, stats: stats(2, 50) },
+            ]
+        );
+
+        // A full counter fails the claim instead of saturating.
+        COHORT_STATS.save(&mut deps.storage, 2, &CohortStats { airdrop_claims: u32::MAX, ..stats(2, 50) }).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0002", &[]), claim(2, 30)).unwrap_err();
+        assert_eq!(res, ContractError::Std(OverflowError::new(OverflowOperation::Add, u32::MAX, 1u32).into()));
+
+        // The cohorts are fixed once the claims started.
+        let res = execute(deps.as_mut(), env, info, tag(1, &["player0002"])).unwrap_err();
+        assert_eq!(res, ContractError::StageStarted { stage_name: StageName::ClaimAirdrop.into() });
+    }
+
+    #[test]
+    fn leaf_hash_query() {
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+        let leaf_formats = [
+            (None, ""),
+            (Some(LeafFormat::ContractAddress), MOCK_CONTRACT_ADDR),
+            (Some(LeafFormat::CampaignId { campaign_id: "spring".to_string() }), "spring"),
+        ];
+        for (leaf_format, domain) in leaf_formats {
+            let mut deps = mock_dependencies();
+            let msg = InstantiateMsg {
+                owner: Some("owner0000".to_string()),
+                cw20_token_address: "random0000".to_string(),
+                airdrop_native_denom: None,
+                eligibility_hook: None,
+                ticket_price: Coin {
+                    denom: TEST_DENOM.into(),
+                    amount: Uint128::new(10)
+                },
+                bins: 10,
+                stage_bid: stage_bid.clone().into(),
+                stage_claim_airdrop: stage_claim_airdrop.clone().into(),
+                stage_claim_prize: stage_claim_prize.clone().into(),
+                metadata: None,
+                prize_mode: None,
+                rounding_policy: None,
+                pubkey_bids: None,
+                approved_proxies: None,
+                claim_rate_limit: None,
+                ticket_vendor: None,
+                max_proof_length: None,
+                badge_contract: None,
+                max_bid_changes: None,
+                late_bid_penalty: None,
+                private_bins: None,
+                hidden_bids: None,
+                minimal_attributes: None,
+                prize_timelock: None,
+                payout_mode: None,
+                no_winners_policy: None,
+                governance_mode: None,
+                kyc_gate: None,
+                leaf_format,
+                claim_index: None,
+                registry: None,
+                test_mode: None,
+                min_prize_pool: None,
+                treasury_timelock: None,
+                withdrawal_guard: None,
+            };
+            instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+            // Leaves are hashed in the leaf format of the game, without validating the account.
+            let msg = QueryMsg::LeafHash { account: "player0000".to_string(), amount: Uint128::new(100) };
+            let res: LeafHashResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            assert_eq!(res.leaf, hex::encode(Sha256::digest(format!("{}player0000100", domain).as_bytes())));
+
+            let msg = QueryMsg::LeafHash { account: "Player".to_string(), amount: Uint128::zero() };
+            let res: LeafHashResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            assert_eq!(res.leaf, hex::encode(Sha256::digest(format!("{}Player0", domain).as_bytes())));
+        }
+        assert_eq!(hex::encode(leaf_hash("player0000", "100")), hex::encode(Sha256::digest(b"player0000100")));
+    }
+
+    #[test]
+    fn stage_boundaries() {
+        let mut deps = mock_dependencies_with_balance(&[Coin::new(1_000, TEST_DENOM)]);
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let airdrop_leaves = vec![leaf_hash("player0000", "100"), leaf_hash("player0001", "100")];
+        let game_leaves = vec![leaf_hash("player0000", "4"), leaf_hash("player0002", "4")];
+        let airdrop_tree = crate::merkle::MerkleTree::new(airdrop_leaves.clone());
+        let game_tree = crate::merkle::MerkleTree::new(game_leaves.clone());
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: airdrop_tree.root(),
+            total_amount_airdrop: Some(Uint128::new(200)),
+            merkle_root_game: game_tree.root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+        open_claims(&mut deps, 200).unwrap();
+
+        let at = |height: u64| {
+            let mut env = mock_env();
+            env.block.height = height;
+            env
+        };
+        let bid = |bin: u8| ExecuteMsg::Bid { bin, pubkey_proof: None, ticket_id: None };
+        let paid = |player: &str| mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+        let stage_ended = |stage_name: &str| ContractError::StageEnded { stage_name: stage_name.to_string() };
+        let not_started = |stage_name: &str| ContractError::StageNotStarted { stage_name: stage_name.to_string() };
+
+        // The bid stage covers the blocks from 200_000 to 200_001.
+        let res = execute(deps.as_mut(), at(199_999), paid("player0002"), bid(3)).unwrap_err();
+        assert_eq!(res, not_started("bid"));
+        execute(deps.as_mut(), at(200_000), paid("player0000"), bid(3)).unwrap();
+        execute(deps.as_mut(), at(200_000), paid("player0001"), bid(5)).unwrap();
+        execute(deps.as_mut(), at(200_001), mock_info("player0000", &[]), ExecuteMsg::ChangeBid { bin: 4 }).unwrap();
+        let res = execute(deps.as_mut(), at(200_002), paid("player0002"), bid(3)).unwrap_err();
+        assert_eq!(res, stage_ended("bid"));
+        let info = mock_info("player0001", &[]);
+        let res = execute(deps.as_mut(), at(200_002), info.clone(), ExecuteMsg::ChangeBid { bin: 6 }).unwrap_err();
+        assert_eq!(res, stage_ended("bid"));
+        let res = execute(deps.as_mut(), at(200_002), info, ExecuteMsg::RemoveBid {}).unwrap_err();
+        assert_eq!(res, stage_ended("bid"));
+
+        // The claim airdrop stage covers the blocks from 203_000 to 203_001.
+        let claim = |i: usize| ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::new(100),
+            proof_airdrop: airdrop_tree.proof(&airdrop_leaves[i]).unwrap(),
+            proof_game: game_leaves.get(i).map(|leaf| game_tree.proof(leaf).unwrap()).unwrap_or_default(),
+        };
+        let res = execute(deps.as_mut(), at(202_999), mock_info("player0000", &[]), claim(0)).unwrap_err();
+        assert_eq!(res, not_started("claim airdrop"));
+        execute(deps.as_mut(), at(203_000), mock_info("player0000", &[]), claim(0)).unwrap();
+        execute(deps.as_mut(), at(203_001), mock_info("player0001", &[]), claim(1)).unwrap();
+        let res = execute(deps.as_mut(), at(203_002), mock_info("player0002", &[]), claim(0)).unwrap_err();
+        assert_eq!(res, stage_ended("claim airdrop"));
+
+        // The claim prize stage covers the blocks from 206_000 to 206_001.
+        let claim_prize = ExecuteMsg::ClaimPrize { amount: None, proof: None, then: None };
+        let info = mock_info("player0000", &[]);
+        let res = execute(deps.as_mut(), at(205_999), info.clone(), claim_prize.clone()).unwrap_err();
+        assert_eq!(res, not_started("claim prize"));
+        let res = execute(deps.as_mut(), at(206_002), info.clone(), claim_prize.clone()).unwrap_err();
+        assert_eq!(res, stage_ended("claim prize"));
+        execute(deps.as_mut(), at(206_000), info, claim_prize).unwrap();
+    }
+
+    #[test]
+    fn split_payment_change() {
+        let due = Coin::new(10, TEST_DENOM);
+        let info = mock_info(
+            "player0000",
+            &[Coin::new(4, TEST_DENOM), Coin::new(3, "ubtc"), Coin::new(8, TEST_DENOM), Coin::new(2, "ubtc")],
+        );
+        assert_eq!(
+            split_payment(&info, &due).unwrap(),
+            vec![Coin::new(5, "ubtc"), Coin::new(2, TEST_DENOM)]
+        );
+
+        // Nothing to return for the exact amount, everything when nothing is due.
+        let info = mock_info("player0000", &[Coin::new(10, TEST_DENOM)]);
+        assert_eq!(split_payment(&info, &due).unwrap(), vec![]);
+        let free = Coin::new(0, TEST_DENOM);
+        assert_eq!(split_payment(&info, &free).unwrap(), info.funds);
+
+        // Other denoms do not count toward the due amount.
+        let info = mock_info("player0000", &[Coin::new(9, TEST_DENOM), Coin::new(10, "ubtc")]);
+        assert_eq!(split_payment(&info, &due).unwrap_err(), ContractError::TicketPriceNotPaid {});
+
+        assert_eq!(refund_msg(&Addr::unchecked("player0000"), vec![]), None);
+    }
+
+    #[test]
+    fn pending_actions() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: Some(Duration::Height(1)),
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        for player in ["player0000", "player0001"] {
+            let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin: 3, pubkey_proof: None, ticket_id: None }).unwrap();
+        }
+
+        let pending_actions = |deps: &OwnedDeps<_, _, _>, height: u64, address: &str| {
+            let mut env = mock_env();
+            env.block.height = height;
+            let msg = QueryMsg::PendingActions { address: address.to_string() };
+            let res: PendingActionsResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+            res.actions
+        };
+        let claim_airdrop = PendingAction {
+            action: PendingActionKind::ClaimAirdrop,
+            opens_at: Scheduled::AtHeight(203_000),
+            deadline: Scheduled::AtHeight(203_002),
+        };
+        let claim_prize = PendingAction {
+            action: PendingActionKind::ClaimPrize,
+            opens_at: Scheduled::AtHeight(206_001),
+            deadline: Scheduled::AtHeight(206_002),
+        };
+
+        // Nothing pending for an address unknown to the contract.
+        assert_eq!(pending_actions(&deps, 200_000, "random0000"), vec![]);
+        assert_eq!(
+            pending_actions(&deps, 200_000, "player0000"),
+            vec![claim_airdrop.clone(), claim_prize.clone()]
+        );
+
+        // A winner registered by its airdrop claim keeps the prize claim after the end of
+        // the claim airdrop stage, the other bids lost.
+        let player = Addr::unchecked("player0000");
+        CLAIM_AIRDROP.save(deps.as_mut().storage, &player, &true).unwrap();
+        CLAIM_PRIZE.save(deps.as_mut().storage, &player, &false).unwrap();
+        assert_eq!(pending_actions(&deps, 203_001, "player0000"), vec![claim_prize.clone()]);
+        assert_eq!(pending_actions(&deps, 203_001, "player0001"), vec![claim_airdrop, claim_prize.clone()]);
+        assert_eq!(pending_actions(&deps, 203_002, "player0000"), vec![claim_prize.clone()]);
+        assert_eq!(pending_actions(&deps, 203_002, "player0001"), vec![]);
+
+        assert_eq!(pending_actions(&deps, 206_002, "player0000"), vec![]);
+        CLAIM_PRIZE.save(deps.as_mut().storage, &player, &true).unwrap();
+        assert_eq!(pending_actions(&deps, 206_001, "player0000"), vec![]);
+    }
+
+    #[test]
+    fn payout_send_with_memo() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: Some(PayoutMode::SendWithMemo { campaign_id: "spring".to_string() }),
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let airdrop_leaves = vec![leaf_hash("vault0000", "100"), leaf_hash("player0001", "100")];
+        let game_leaves = vec![leaf_hash("player0002", "4"), leaf_hash("player0003", "4")];
+        let airdrop_tree = crate::merkle::MerkleTree::new(airdrop_leaves.clone());
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: airdrop_tree.root(),
+            total_amount_airdrop: Some(Uint128::new(200)),
+            merkle_root_game: crate::merkle::MerkleTree::new(game_leaves).root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+        open_claims(&mut deps, 200).unwrap();
+
+        // Only vault0000 is a contract.
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::ContractInfo { contract_addr } if contract_addr == "vault0000" => {
+                SystemResult::Ok(ContractResult::Ok(to_binary(&ContractInfoResponse::new(1, "creator")).unwrap()))
+            }
+            WasmQuery::ContractInfo { contract_addr } | WasmQuery::Smart { contract_addr, .. } => {
+                SystemResult::Err(SystemError::NoSuchContract { addr: contract_addr.clone() })
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "wasm".to_string() }),
+        });
+
+        let mut env = mock_env();
+        env.block.height = 203_000;
+        let claim = |i: usize| ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::new(100),
+            proof_airdrop: airdrop_tree.proof(&airdrop_leaves[i]).unwrap(),
+            proof_game: vec![],
+        };
+
+        let res = execute(deps.as_mut(), env.clone(), mock_info("vault0000", &[]), claim(0)).unwrap();
+        let memo = PayoutReceiveMsg::PayoutMemo {
+            campaign_id: "spring".to_string(),
+            claim_id: "airdrop/vault0000".to_string(),
+        };
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(
+                Asset::cw20(Addr::unchecked("random0000"), Uint128::new(100))
+                    .send_msg("vault0000", to_binary(&memo).unwrap())
+                    .unwrap()
+            )]
+        );
+
+        // Other accounts keep receiving a transfer.
+        let res = execute(deps.as_mut(), env, mock_info("player0001", &[]), claim(1)).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(
+                Asset::cw20(Addr::unchecked("random0000"), Uint128::new(100))
+                    .transfer_msg("player0001")
+                    .unwrap()
+            )]
+        );
+    }
+
+    #[test]
+    fn leaf_domain_claims() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: Some(LeafFormat::ContractAddress),
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let info = mock_info("player0000", &[Coin::new(10, TEST_DENOM)]);
+        execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin: 3, pubkey_proof: None, ticket_id: None }).unwrap();
+
+        // The same allocation hashed for the game and for another deployment.
+        let leaves = |domain: &str| {
+            let airdrop = vec![
+                compute_leaf(domain, "player0000", Uint128::new(100)),
+                compute_leaf(domain, "player0001", Uint128::new(100)),
+            ];
+            let game = vec![
+                domain_leaf_hash(domain, "player0000", "3"),
+                domain_leaf_hash(domain, "player0001", "4"),
+            ];
+            (crate::merkle::MerkleTree::new(airdrop), crate::merkle::MerkleTree::new(game))
+        };
+        let register = |(airdrop, game): &(crate::merkle::MerkleTree, crate::merkle::MerkleTree)| ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: airdrop.root(),
+            total_amount_airdrop: Some(Uint128::new(200)),
+            merkle_root_game: game.root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: Some(true),
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        let claim = |(airdrop, game): &(crate::merkle::MerkleTree, crate::merkle::MerkleTree), domain: &str| ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::new(100),
+            proof_airdrop: airdrop.proof(&compute_leaf(domain, "player0000", Uint128::new(100))).unwrap(),
+            proof_game: game.proof(&domain_leaf_hash(domain, "player0000", "3")).unwrap(),
+        };
+
+        env.block.height = 203_000;
+        for domain in ["", "cosmos2contract2"] {
+            let trees = leaves(domain);
+            execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), register(&trees)).unwrap();
+            open_claims(&mut deps, 200).unwrap();
+            let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim(&trees, domain)).unwrap_err();
+            assert_eq!(res, ContractError::VerificationFailed { merkle_root: ROOT_AIRDROP.to_string() });
+        }
+
+        // Only the leaves of the game address verify, for both trees.
+        let trees = leaves(MOCK_CONTRACT_ADDR);
+        execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), register(&trees)).unwrap();
+        open_claims(&mut deps, 200).unwrap();
+        let res = execute(deps.as_mut(), env, mock_info("player0000", &[]), claim(&trees, MOCK_CONTRACT_ADDR)).unwrap();
+        assert_eq!(res.events[0].ty, EVENT_WINNER_REGISTERED);
+    }
+
+    #[test]
+    fn claim_index() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: Some(true),
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let info = mock_info("player0000", &[Coin::new(10, TEST_DENOM)]);
+        execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin: 3, pubkey_proof: None, ticket_id: None }).unwrap();
+
+        // player0000 owns two leaves of the airdrop tree.
+        let airdrop_leaves = vec![
+            compute_leaf("0", "player0000", Uint128::new(100)),
+            compute_leaf("1", "player0001", Uint128::new(100)),
+            compute_leaf("2", "player0000", Uint128::new(50)),
+        ];
+        let game_leaves = vec![leaf_hash("player0000", "3"), leaf_hash("player0001", "4")];
+        let airdrop_tree = crate::merkle::MerkleTree::new(airdrop_leaves.clone());
+        let game_tree = crate::merkle::MerkleTree::new(game_leaves.clone());
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: airdrop_tree.root(),
+            total_amount_airdrop: Some(Uint128::new(250)),
+            merkle_root_game: game_tree.root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+        open_claims(&mut deps, 250).unwrap();
+
+        env.block.height = 203_000;
+        let claim = |index: u32, leaf: usize, amount: u128| ExecuteMsg::ClaimAirdropByIndex {
+            index,
+            amount: Uint128::new(amount),
+            proof_airdrop: airdrop_tree.proof(&airdrop_leaves[leaf]).unwrap(),
+            proof_game: game_tree.proof(&game_leaves[0]).unwrap(),
+        };
+
+        // Claims by address are not tracked in this mode.
+        let msg = ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::new(100),
+            proof_airdrop: airdrop_tree.proof(&airdrop_leaves[0]).unwrap(),
+            proof_game: vec![],
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), msg).unwrap_err();
+        assert_eq!(res, ContractError::ClaimIndexRequired {});
+
+        // The index is part of the leaf.
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim(1, 0, 100)).unwrap_err();
+        assert_eq!(res, ContractError::VerificationFailed { merkle_root: ROOT_AIRDROP.to_string() });
+
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim(0, 0, 100)).unwrap();
+        assert!(res.attributes.contains(&attr(ATTR_INDEX, "0")));
+        assert_eq!(res.events[0].ty, EVENT_WINNER_REGISTERED);
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim(0, 0, 100)).unwrap_err();
+        assert_eq!(res, ContractError::AlreadyClaimed {});
+
+        // The second leaf of the address is claimed too, but wins once.
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim(2, 2, 50)).unwrap();
+        assert!(res.events.is_empty());
+        assert_eq!(COUNTERS.load(&deps.storage).unwrap().winners, Uint128::new(1));
+        assert_eq!(COUNTERS.load(&deps.storage).unwrap().claimed_airdrop_amount, Uint128::new(150));
+
+        for (index, claimed) in [(0, true), (1, false), (2, true)] {
+            let res = query(deps.as_ref(), env.clone(), QueryMsg::IsClaimedByIndex { index }).unwrap();
+            let res: IsClaimedByIndexResponse = from_binary(&res).unwrap();
+            assert_eq!(res.claimed, claimed);
+        }
+        // A single storage entry, and no entry per address.
+        let words: Vec<_> = CLAIMED_BITMAP
+            .range(&deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(words, vec![(0, Uint128::new(0b101))]);
+        assert!(!CLAIM_AIRDROP.has(&deps.storage, &Addr::unchecked("player0000")));
+
+        // Indexes at the boundaries of the words.
+        for index in [127, 128, u32::MAX] {
+            assert!(!is_index_claimed(&deps.storage, index).unwrap());
+            set_index_claimed(&mut deps.storage, index).unwrap();
+            assert!(is_index_claimed(&deps.storage, index).unwrap());
+        }
+        assert!(!is_index_claimed(&deps.storage, 129).unwrap());
+        assert_eq!(CLAIMED_BITMAP.load(&deps.storage, 0).unwrap().u128(), 0b101 | 1 << 127);
+        assert_eq!(CLAIMED_BITMAP.load(&deps.storage, 1).unwrap(), Uint128::new(1));
+        assert_eq!(CLAIMED_BITMAP.load(&deps.storage, u32::MAX / 128).unwrap().u128(), 1 << 127);
+
+        // Indexed claims are rejected outside the mode.
+        CONFIG
+            .update(&mut deps.storage, |mut cfg| -> StdResult<_> {
+                cfg.claim_index = false;
+                Ok(cfg)
+            })
+            .unwrap();
+        let res = execute(deps.as_mut(), env, mock_info("player0001", &[]), claim(1, 1, 100)).unwrap_err();
+        assert_eq!(res, ContractError::ClaimIndexDisabled {});
+    }
+
+    #[test]
+    fn kyc_gate() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: Some(KycGateMsg { registry: "registry0000".to_string(), threshold: Uint128::new(60) }),
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let airdrop_leaves = vec![leaf_hash("player0000", "100"), leaf_hash("player0001", "100")];
+        let game_leaves = vec![leaf_hash("player0002", "4"), leaf_hash("player0003", "4")];
+        let airdrop_tree = crate::merkle::MerkleTree::new(airdrop_leaves.clone());
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: airdrop_tree.root(),
+            total_amount_airdrop: Some(Uint128::new(200)),
+            merkle_root_game: crate::merkle::MerkleTree::new(game_leaves).root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+        open_claims(&mut deps, 200).unwrap();
+
+        // Only player0001 is attested.
+        fn attest(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, attested: &'static [&'static str]) {
+            deps.querier.update_wasm(move |query| match query {
+                WasmQuery::Smart { contract_addr, msg } if contract_addr == "registry0000" => {
+                    let AttestationQueryMsg::Attestation { address } = from_binary(msg).unwrap();
+                    let res = AttestationResponse { valid: attested.contains(&address.as_str()) };
+                    SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+                }
+                _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "wasm".to_string() }),
+            });
+        }
+        attest(&mut deps, &["player0001"]);
+
+        let mut env = mock_env();
+        env.block.height = 203_000;
+        let claim = |i: usize| ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::new(100),
+            proof_airdrop: airdrop_tree.proof(&airdrop_leaves[i]).unwrap(),
+            proof_game: vec![],
+        };
+        let transfer = |recipient: &str, amount: u128| {
+            SubMsg::new(Asset::cw20(Addr::unchecked("random0000"), Uint128::new(amount)).transfer_msg(recipient).unwrap())
+        };
+
+        // The claim is paid up to the threshold without an attestation.
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim(0)).unwrap();
+        assert_eq!(res.messages, vec![transfer("player0000", 60)]);
+        assert!(res.attributes.contains(&attr(ATTR_WITHHELD, "40")));
+        let is_claimed = query_is_claimed(deps.as_ref(), "player0000".to_string()).unwrap();
+        assert!(is_claimed.airdrop_claimed);
+        assert_eq!(is_claimed.withheld_airdrop, Uint128::new(40));
+        let pending = query_pending_actions(deps.as_ref(), env.clone(), "player0000".to_string()).unwrap();
+        assert_eq!(pending.actions[0].action, PendingActionKind::ClaimWithheldAirdrop);
+
+        let msg = ExecuteMsg::ClaimWithheldAirdrop {};
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), msg.clone()).unwrap_err();
+        assert_eq!(res, ContractError::AttestationRequired { registry: "registry0000".to_string() });
+
+        // Attested addresses are paid in full.
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0001", &[]), claim(1)).unwrap();
+        assert_eq!(res.messages, vec![transfer("player0001", 100)]);
+        assert!(!res.attributes.iter().any(|attr| attr.key == ATTR_WITHHELD));
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0001", &[]), msg.clone()).unwrap_err();
+        assert_eq!(res, ContractError::NothingWithheld {});
+
+        // The rest is paid once the address gets attested.
+        attest(&mut deps, &["player0000", "player0001"]);
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), msg.clone()).unwrap();
+        assert_eq!(res.messages, vec![transfer("player0000", 40)]);
+        assert_eq!(COUNTERS.load(&deps.storage).unwrap().claimed_airdrop_amount, Uint128::new(200));
+        let res = execute(deps.as_mut(), env, mock_info("player0000", &[]), msg).unwrap_err();
+        assert_eq!(res, ContractError::NothingWithheld {});
+    }
+
+    #[test]
+    fn no_winners_policy() {
+        let game = |policy: NoWinnersPolicy| {
+            let mut deps = mock_dependencies();
+            let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+            let msg = InstantiateMsg {
+                owner: Some("owner0000".to_string()),
+                cw20_token_address: "random0000".to_string(),
+                airdrop_native_denom: None,
+                eligibility_hook: None,
+                ticket_price: Coin {
+                    denom: TEST_DENOM.into(),
+                    amount: Uint128::new(10)
+                },
+                bins: 10,
+                stage_bid: stage_bid.into(),
+                stage_claim_airdrop: stage_claim_airdrop.into(),
+                stage_claim_prize: stage_claim_prize.into(),
+                metadata: None,
+                prize_mode: None,
+                rounding_policy: None,
+                pubkey_bids: None,
+                approved_proxies: None,
+                claim_rate_limit: None,
+                ticket_vendor: None,
+                max_proof_length: None,
+                badge_contract: None,
+                max_bid_changes: None,
+                late_bid_penalty: None,
+                private_bins: None,
+                hidden_bids: None,
+                minimal_attributes: None,
+                prize_timelock: None,
+                payout_mode: None,
+                no_winners_policy: Some(policy),
+                governance_mode: None,
+                kyc_gate: None,
+                leaf_format: None,
+                claim_index: None,
+                registry: None,
+                test_mode: None,
+                min_prize_pool: None,
+                treasury_timelock: None,
+                withdrawal_guard: None,
+            };
+            instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+            let mut env = mock_env();
+            env.block.height = 200_000;
+            for player in ["player0000", "player0001"] {
+                let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+                execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin: 3, pubkey_proof: None, ticket_id: None }).unwrap();
+            }
+
+            // Nobody bid on the winning bin.
+            let leaves = vec![leaf_hash("player0002", "4"), leaf_hash("player0003", "4")];
+            let root = crate::merkle::MerkleTree::new(leaves).root();
+            let msg = ExecuteMsg::RegisterMerkleRoots {
+                merkle_root_airdrop: root.clone(),
+                total_amount_airdrop: Some(Uint128::new(200)),
+                merkle_root_game: root,
+                total_amount_game: None,
+                tree_metadata: tree_metadata(),
+                overwrite: None,
+                secondary_payout: None,
+                claim_condition: None,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+            deps
+        };
+        let mut env = mock_env();
+        env.block.height = 206_000;
+
+        // The ticket prize goes to the treasury once, after its activation.
+        let mut deps = game(NoWinnersPolicy::Treasury { address: Addr::unchecked("treasury0000") });
+        let mut early_env = mock_env();
+        early_env.block.height = 203_000;
+        let res = execute(deps.as_mut(), early_env, mock_info("anyone", &[]), ExecuteMsg::SettleNoWinners {}).unwrap_err();
+        assert_eq!(res, ContractError::StageNotStarted { stage_name: "claim prize".to_string() });
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), ExecuteMsg::SettleNoWinners {}).unwrap_err();
+        assert_eq!(res, ContractError::TreasuryNotActive { address: "treasury0000".to_string() });
+        set_active_treasury(deps.as_mut().storage, "treasury0000");
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), ExecuteMsg::SettleNoWinners {}).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send { to_address: "treasury0000".to_string(), amount: vec![Coin::new(20, TEST_DENOM)] })]
+        );
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), ExecuteMsg::SettleNoWinners {}).unwrap_err();
+        assert_eq!(res, ContractError::AlreadyClaimed {});
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), ExecuteMsg::ClaimTicketRefund {}).unwrap_err();
+        assert_eq!(res, ContractError::NoWinnersPolicyNotApplicable {});
+
+        // The ticket prize boosts the prize of the following game.
+        let mut deps = game(NoWinnersPolicy::Rollover { game: Addr::unchecked("game0001") });
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), ExecuteMsg::SettleNoWinners {}).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(WasmMsg::Execute {
+                contract_addr: "game0001".to_string(),
+                msg: to_binary(&ExecuteMsg::BoostPrize {}).unwrap(),
+                funds: vec![Coin::new(20, TEST_DENOM)],
+            })]
+        );
+
+        // Every bidder gets back the ticket price once.
+        let mut deps = game(NoWinnersPolicy::Refund);
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), ExecuteMsg::SettleNoWinners {}).unwrap_err();
+        assert_eq!(res, ContractError::NoWinnersPolicyNotApplicable {});
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), ExecuteMsg::ClaimTicketRefund {}).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send { to_address: "player0000".to_string(), amount: vec![Coin::new(10, TEST_DENOM)] })]
+        );
+        for player in ["player0000", "player0002"] {
+            let res = execute(deps.as_mut(), env.clone(), mock_info(player, &[]), ExecuteMsg::ClaimTicketRefund {}).unwrap_err();
+            assert_eq!(res, ContractError::NothingToRefund {});
+        }
+        let amounts: GameAmountsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GameAmounts {}).unwrap()).unwrap();
+        assert_eq!(amounts.total_claimed_prize, Uint128::new(10));
+
+        // Nothing changes for the games with winners.
+        let mut deps = game(NoWinnersPolicy::Refund);
+        set_counter(deps.as_mut().storage, |counters| counters.winners = Uint128::new(1));
+        let res = execute(deps.as_mut(), env, mock_info("player0000", &[]), ExecuteMsg::ClaimTicketRefund {}).unwrap_err();
+        assert_eq!(res, ContractError::GameHasWinners {});
+    }
+
+    #[test]
+    fn min_prize_pool() {
+        let game = |minimum: u128| {
+            let mut deps = mock_dependencies();
+            let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+            let msg = InstantiateMsg {
+                owner: Some("owner0000".to_string()),
+                cw20_token_address: "random0000".to_string(),
+                airdrop_native_denom: None,
+                eligibility_hook: None,
+                ticket_price: Coin {
+                    denom: TEST_DENOM.into(),
+                    amount: Uint128::new(10)
+                },
+                bins: 10,
+                stage_bid: stage_bid.into(),
+                stage_claim_airdrop: stage_claim_airdrop.into(),
+                stage_claim_prize: stage_claim_prize.into(),
+                metadata: None,
+                prize_mode: None,
+                rounding_policy: None,
+                pubkey_bids: None,
+                approved_proxies: None,
+                claim_rate_limit: None,
+                ticket_vendor: None,
+                max_proof_length: None,
+                badge_contract: None,
+                max_bid_changes: None,
+                late_bid_penalty: None,
+                private_bins: None,
+                hidden_bids: None,
+                minimal_attributes: None,
+                prize_timelock: None,
+                payout_mode: None,
+                no_winners_policy: None,
+                governance_mode: None,
+                kyc_gate: None,
+                leaf_format: None,
+                claim_index: None,
+                registry: None,
+                test_mode: None,
+                min_prize_pool: Some(Uint128::new(minimum)),
+                treasury_timelock: None,
+                withdrawal_guard: None,
+            };
+            instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+            let mut env = mock_env();
+            env.block.height = 200_000;
+            for (player, bin) in [("player0000", 3), ("player0001", 5)] {
+                let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+                execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin, pubkey_proof: None, ticket_id: None }).unwrap();
+            }
+
+            // player0000 picked the winning bin.
+            let leaves = vec![leaf_hash("player0000", "3"), leaf_hash("player0001", "4")];
+            let root = crate::merkle::MerkleTree::new(leaves).root();
+            let msg = ExecuteMsg::RegisterMerkleRoots {
+                merkle_root_airdrop: root.clone(),
+                total_amount_airdrop: Some(Uint128::new(200)),
+                merkle_root_game: root,
+                total_amount_game: None,
+                tree_metadata: tree_metadata(),
+                overwrite: None,
+                secondary_payout: None,
+                claim_condition: None,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+            set_counter(deps.as_mut().storage, |counters| counters.winners = Uint128::new(1));
+            deps
+        };
+        let mut env = mock_env();
+        env.block.height = 206_000;
+        let prize_pot = |deps: &OwnedDeps<_, _, _>| -> PrizePotResponse {
+            from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::PrizePot {}).unwrap()).unwrap()
+        };
+
+        // The decision is taken on the tickets alone at the end of the bid stage: a boost
+        // reaching the minimum does not change it, and no boost is accepted afterwards.
+        let mut deps = game(30);
+        let mut bid_env = mock_env();
+        bid_env.block.height = 200_001;
+        let sponsor = mock_info("sponsor0000", &[Coin::new(20, TEST_DENOM)]);
+        execute(deps.as_mut(), bid_env.clone(), sponsor.clone(), ExecuteMsg::BoostPrize {}).unwrap();
+        let mut bid_end_env = bid_env.clone();
+        bid_end_env.block.height = 200_002;
+        let res: PrizePotResponse = from_binary(&query(deps.as_ref(), bid_env, QueryMsg::PrizePot {}).unwrap()).unwrap();
+        assert!(!res.refund_mode);
+        let res: PrizePotResponse =
+            from_binary(&query(deps.as_ref(), bid_end_env.clone(), QueryMsg::PrizePot {}).unwrap()).unwrap();
+        assert!(res.refund_mode);
+        assert_eq!((res.total_ticket_prize, res.total_ticket_payments), (Uint128::new(40), Uint128::new(20)));
+        let below_minimum = ContractError::PrizePoolBelowMinimum { pool: Uint128::new(20), minimum: Uint128::new(30) };
+        let res = execute(deps.as_mut(), bid_end_env, sponsor, ExecuteMsg::BoostPrize {}).unwrap_err();
+        assert_eq!(res, below_minimum);
+
+        // Below the minimum no prize is paid.
+        let msg = ExecuteMsg::ClaimPrize { amount: None, proof: None, then: None };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), msg).unwrap_err();
+        assert_eq!(res, below_minimum);
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), ExecuteMsg::SettleNoWinners {}).unwrap_err();
+        assert_eq!(res, below_minimum);
+        assert!(prize_pot(&deps).refund_mode);
+
+        // Every bidder gets back the ticket price once, the winner included.
+        for player in ["player0000", "player0001"] {
+            let res = execute(deps.as_mut(), env.clone(), mock_info(player, &[]), ExecuteMsg::ClaimTicketRefund {}).unwrap();
+            assert_eq!(
+                res.messages,
+                vec![SubMsg::new(BankMsg::Send { to_address: player.to_string(), amount: vec![Coin::new(10, TEST_DENOM)] })]
+            );
+            let res = execute(deps.as_mut(), env.clone(), mock_info(player, &[]), ExecuteMsg::ClaimTicketRefund {}).unwrap_err();
+            assert_eq!(res, ContractError::NothingToRefund {});
+        }
+
+        // So does the sponsor of the prize.
+        let sponsor = mock_info("sponsor0000", &[]);
+        let res = execute(deps.as_mut(), env.clone(), sponsor.clone(), ExecuteMsg::RefundPrizeBoost {}).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send { to_address: "sponsor0000".to_string(), amount: vec![Coin::new(20, TEST_DENOM)] })]
+        );
+        let res = execute(deps.as_mut(), env.clone(), sponsor, ExecuteMsg::RefundPrizeBoost {}).unwrap_err();
+        assert_eq!(res, ContractError::NothingToRefund {});
+        assert_eq!(prize_pot(&deps).pot, Coin::new(0, TEST_DENOM));
+
+        // A prize reaching the minimum is not refunded, nor are its boosts.
+        let mut deps = game(20);
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), ExecuteMsg::ClaimTicketRefund {}).unwrap_err();
+        assert_eq!(res, ContractError::NoWinnersPolicyNotApplicable {});
+        let res = execute(deps.as_mut(), env.clone(), mock_info("sponsor0000", &[]), ExecuteMsg::RefundPrizeBoost {}).unwrap_err();
+        assert_eq!(res, ContractError::NothingToRefund {});
+        assert!(!prize_pot(&deps).refund_mode);
+    }
+
+    #[test]
+    fn test_mode_disabled() {
+        let mut deps = mock_dependencies();
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin::new(10, TEST_DENOM),
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        // Stages of games not in test mode cannot be moved, whatever the build.
+        let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), ExecuteMsg::FastForwardStage {}).unwrap_err();
+        assert_eq!(res, ContractError::TestModeDisabled {});
+    }
+
+    #[cfg(feature = "test_mode")]
+    #[test]
+    fn fast_forward_stage() {
+        let mut deps = mock_dependencies();
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+        let stage_bid = Stage { start: Scheduled::AtHeight(mock_env().block.height), ..stage_bid };
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin::new(10, TEST_DENOM),
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: Some(true),
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), ExecuteMsg::FastForwardStage {}).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+
+        // Every call ends the stage in progress and starts the next one.
+        let mut fast_forward = |env: &Env, stage: &str| {
+            let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), ExecuteMsg::FastForwardStage {}).unwrap();
+            assert_eq!(res.attributes[1], attr("stage", stage));
+            GAME_PARAMS.load(&deps.storage).unwrap()
+        };
+        env.block.height += 1;
+        let params = fast_forward(&env, "claim airdrop");
+        assert_eq!(params.stage_bid.end().unwrap(), Scheduled::AtHeight(env.block.height));
+        assert_eq!(params.stage_claim_airdrop.start, Scheduled::AtHeight(env.block.height));
+        assert!(params.stage_claim_airdrop.is_active(&env.block).unwrap());
+        assert_eq!(params.stage_claim_airdrop.duration, Duration::Height(2));
+
+        env.block.height += 1;
+        let params = fast_forward(&env, "claim prize");
+        assert_eq!(params.stage_claim_airdrop.end().unwrap(), Scheduled::AtHeight(env.block.height));
+        assert!(params.stage_claim_prize.is_active(&env.block).unwrap());
+
+        let params = fast_forward(&env, "none");
+        assert!(params.stage_claim_prize.has_ended(&env.block).unwrap());
+        let res = execute(deps.as_mut(), env, mock_info("owner0000", &[]), ExecuteMsg::FastForwardStage {}).unwrap_err();
+        assert_eq!(res, ContractError::StageEnded { stage_name: "claim prize".to_string() });
+    }
+
+    #[test]
+    fn update_limits() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let limits = |deps: &OwnedDeps<_, _, _>| {
+            let res: LimitsResponse = from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Limits {}).unwrap()).unwrap();
+            res
+        };
+        let ceilings = Limits {
+            max_batch_size: MAX_BATCH_SIZE,
+            max_addresses_per_call: MAX_ADDRESSES_PER_CALL,
+            max_proof_length: MAX_PROOF_NODES,
+        };
+        assert_eq!(limits(&deps), LimitsResponse { limits: ceilings.clone(), ceilings: ceilings.clone() });
+
+        let update = |max_batch_size, max_addresses_per_call, max_proof_length| ExecuteMsg::UpdateLimits {
+            max_batch_size,
+            max_addresses_per_call,
+            max_proof_length,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("player0000", &[]), update(Some(2), None, None)).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+
+        // Limits cannot be raised above the ceilings, nor disabled.
+        for (msg, err) in [
+            (
+                update(Some(MAX_BATCH_SIZE + 1), None, None),
+                ContractError::InvalidLimit { limit: "max_batch_size".to_string(), max: MAX_BATCH_SIZE },
+            ),
+            (
+                update(None, Some(0), None),
+                ContractError::InvalidLimit { limit: "max_addresses_per_call".to_string(), max: MAX_ADDRESSES_PER_CALL },
+            ),
+            (
+                update(None, None, Some(MAX_PROOF_NODES + 1)),
+                ContractError::InvalidMaxProofLength { max: MAX_PROOF_NODES },
+            ),
+        ] {
+            let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap_err();
+            assert_eq!(res, err);
+        }
+
+        execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), update(Some(2), Some(1), None)).unwrap();
+        let expected = Limits { max_batch_size: 2, max_addresses_per_call: 1, max_proof_length: MAX_PROOF_NODES };
+        assert_eq!(limits(&deps), LimitsResponse { limits: expected, ceilings });
+
+        // The lowered limits apply to the next messages.
+        let msg = ExecuteMsg::ImportBids { source_contract: "legacy0000".to_string(), limit: Some(3) };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap_err();
+        assert_eq!(res, ContractError::BatchTooLarge { max: 2 });
+
+        let proof = |address: &str| ProofToCache {
+            address: address.to_string(),
+            amount: Uint128::new(100),
+            proof_airdrop: vec![],
+            proof_game: vec![],
+        };
+        let msg = ExecuteMsg::CacheProof { proofs: vec![proof("player0000"), proof("player0001"), proof("player0002")] };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap_err();
+        assert_eq!(res, ContractError::BatchTooLarge { max: 2 });
+
+        let msg = ExecuteMsg::ImportExclusions { addresses: vec!["player0000".to_string(), "player0001".to_string()] };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap_err();
+        assert_eq!(res, ContractError::TooManyAddresses { max: 1 });
+        let msg = ExecuteMsg::ImportExclusions { addresses: vec!["player0000".to_string()] };
+        execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    }
+
+    #[test]
+    fn failsafe() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        for player in ["player0000", "player0001"] {
+            let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin: 3, pubkey_proof: None, ticket_id: None }).unwrap();
+        }
+
+        let failsafe = |deps: &OwnedDeps<_, _, _>| {
+            let res: FailsafeResponse = from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Failsafe {}).unwrap()).unwrap();
+            res.failsafe
+        };
+        assert_eq!(failsafe(&deps), None);
+
+        // Claims without the roots during the claim airdrop stage.
+        env.block.height = 203_000;
+        let claim = ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::new(100),
+            proof_airdrop: vec![],
+            proof_game: vec![],
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim.clone()).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr(ATTR_ACTION, "failsafe_triggered"),
+                attr(ATTR_CONDITION, INVARIANT_ROOTS_MISSING),
+                attr(ATTR_LEVEL, "error")
+            ]
+        );
+        assert_eq!(
+            failsafe(&deps),
+            Some(Failsafe { condition: INVARIANT_ROOTS_MISSING.to_string(), triggered_at: 203_000 })
+        );
+
+        // Every operation moving funds is blocked, the others still run.
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim).unwrap_err();
+        assert_eq!(res, ContractError::FailsafeActive { condition: INVARIANT_ROOTS_MISSING.to_string() });
+        let msg = ExecuteMsg::WithdrawPrize { address: Addr::unchecked("owner0000") };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap_err();
+        assert_eq!(res, ContractError::FailsafeActive { condition: INVARIANT_ROOTS_MISSING.to_string() });
+        execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), ExecuteMsg::FreezeClaims { reason: "audit".to_string() }).unwrap();
+        execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), ExecuteMsg::UnfreezeClaims {}).unwrap();
+
+        // Just the owner can clear it.
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), ExecuteMsg::ClearFailsafe {}).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+        execute(deps.as_mut(), env, mock_info("owner0000", &[]), ExecuteMsg::ClearFailsafe {}).unwrap();
+        assert_eq!(failsafe(&deps), None);
+
+        // Prize claimed beyond the prize pool.
+        let mut env = mock_env();
+        env.block.height = 206_002;
+        set_counter(deps.as_mut().storage, |counters| counters.claimed_prize_amount = Uint128::new(21));
+        let msg = ExecuteMsg::WithdrawPrize { address: Addr::unchecked("owner0000") };
+        let res = execute(deps.as_mut(), env, mock_info("owner0000", &[]), msg).unwrap();
+        assert_eq!(res.attributes[1], attr(ATTR_CONDITION, INVARIANT_PRIZE_CLAIMED));
+        assert_eq!(failsafe(&deps).unwrap().condition, INVARIANT_PRIZE_CLAIMED);
+    }
+
+    #[test]
+    fn failsafe_native_airdrop() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: Some("uairdrop".to_string()),
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        // Native roots register no totals, the deposits bound the claims.
+        let tree = crate::merkle::MerkleTree::new(vec![leaf_hash("player0000", "100"), leaf_hash("player0001", "200")]);
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: tree.root(),
+            total_amount_airdrop: None,
+            merkle_root_game: tree.root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+        let info = mock_info("sponsor0000", &[Coin::new(300, "uairdrop")]);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::FundAirdropNative {}).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 203_000;
+        let claim = |player: &str, amount: u128| ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::new(amount),
+            proof_airdrop: tree.proof(&leaf_hash(player, &amount.to_string())).unwrap(),
+            proof_game: vec![],
+        };
+        for (player, amount) in [("player0000", 100), ("player0001", 200)] {
+            let res = execute(deps.as_mut(), env.clone(), mock_info(player, &[]), claim(player, amount)).unwrap();
+            assert_eq!(
+                res.messages,
+                vec![SubMsg::new(BankMsg::Send { to_address: player.to_string(), amount: vec![Coin::new(amount, "uairdrop")] })]
+            );
+        }
+        let res: FailsafeResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Failsafe {}).unwrap()).unwrap();
+        assert_eq!(res.failsafe, None);
+
+        // Claims beyond the deposits trip it.
+        set_counter(deps.as_mut().storage, |counters| counters.claimed_airdrop_amount = Uint128::new(301));
+        let res = execute(deps.as_mut(), env, mock_info("player0000", &[]), claim("player0000", 100)).unwrap();
+        assert_eq!(res.attributes[1..], [attr(ATTR_CONDITION, INVARIANT_AIRDROP_CLAIMED), attr(ATTR_LEVEL, "error")]);
+    }
+
+    #[test]
+    fn payout_amount_overflow() {
+        let mut deps = mock_dependencies();
+        assert_eq!(payout_amount(&deps.storage, Uint128::MAX).unwrap(), Uint128::MAX);
+
+        // Converted allocations that do not fit fail instead of aborting the contract.
+        PAYOUT_RATE.save(&mut deps.storage, &Decimal::percent(150)).unwrap();
+        assert_eq!(payout_amount(&deps.storage, Uint128::new(10)).unwrap(), Uint128::new(15));
+        assert_eq!(
+            payout_amount(&deps.storage, Uint128::MAX).unwrap_err(),
+            ContractError::Overflow(OverflowError::new(OverflowOperation::Mul, Uint128::MAX, "1.5"))
+        );
+    }
+
+    #[test]
+    fn proper_instantiation() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let env = mock_env();
+        let info = mock_info("addr0000", &[]);
+
+        // we can just call .unwrap() to assert this was a success
+        let res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+        assert!(res.messages.is_empty());
+
+        // it worked, let's query the state
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+        let config: ConfigResponse = from_binary(&res).unwrap();
+        assert_eq!("owner0000", config.owner.unwrap().as_str());
+        assert_eq!("random0000", config.cw20_token_address.as_str());
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Stages {}).unwrap();
+        let stages_info: StagesResponse = from_binary(&res).unwrap();
+        assert_eq!(Scheduled::AtHeight(200_000), stages_info.stage_bid.start);
+
+        // The game is announced to the registry.
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { registry: Some("registry0000".to_string()), ..msg };
+        let res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let entry = RegistryEntry {
+            game: env.contract.address.to_string(),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            ticket_price: Coin::new(10, TEST_DENOM),
+            bins: 10,
+            prize_mode: PrizeMode::EqualSplit,
+            stage_bid: stages_info.stage_bid,
+            stage_claim_airdrop: stages_info.stage_claim_airdrop,
+            stage_claim_prize: stages_info.stage_claim_prize,
+            metadata: GameMetadata::default(),
+        };
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(WasmMsg::Execute {
+                contract_addr: "registry0000".to_string(),
+                msg: to_binary(&RegistryExecuteMsg::Register(entry)).unwrap(),
+                funds: vec![],
+            })]
+        );
+    }
+
+    #[test]
+    fn update_config() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+        // Update owner
+        let env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::UpdateConfig {
+            new_owner: Some("owner0001".to_string()),
+        };
+
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // it worked, let's query the state
+        let res = query(deps.as_ref(), env, QueryMsg::Config {}).unwrap();
+        let config: ConfigResponse = from_binary(&res).unwrap();
+        assert_eq!("owner0001", config.owner.unwrap().as_str());
+
+        // Unauthorized err
+        let env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::UpdateConfig { new_owner: None };
+
+        let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+    }
+
+    #[test]
+    fn admin_actions_log() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+        // Register the roots and then hand over the ownership.
+        let env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: "b45c1ea28b26adb13e412933c9e055b01fdf7585304b00cd8f1cb220aa6c5e88".to_string(),
+            total_amount_airdrop: Some(Uint128::new(1000)),
+            merkle_root_game: "14b47be0716eebb3b9e16fb2d06dc3376dd2534705d9a9d38f6fbcc6f4f1c7d2".to_string(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::UpdateConfig {
+            new_owner: Some("owner0001".to_string()),
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // Failed actions are not recorded.
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::UpdateConfig { new_owner: None };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::AdminActions { start_after: None, limit: None },
+        )
+        .unwrap();
+        let actions: AdminActionsResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            actions.actions,
+            vec![
+                AdminActionResponse {
+                    id: 0,
+                    height: env.block.height,
+                    sender: "owner0000".to_string(),
+                    action: "register_merkle_roots".to_string(),
+                    summary: "merkle_root_airdrop: b45c1ea28b26adb13e412933c9e055b01fdf7585304b00cd8f1cb220aa6c5e88, \
+                        total_amount_airdrop: 1000, \
+                        merkle_root_game: 14b47be0716eebb3b9e16fb2d06dc3376dd2534705d9a9d38f6fbcc6f4f1c7d2, \
+                        total_amount_game: 0".to_string(),
+                },
+                AdminActionResponse {
+                    id: 1,
+                    height: env.block.height,
+                    sender: "owner0000".to_string(),
+                    action: "update_config".to_string(),
+                    summary: "new_owner: owner0001".to_string(),
+                },
+            ]
+        );
+
+        // Pagination.
+        let res = query(
+            deps.as_ref(),
+            env,
+            QueryMsg::AdminActions { start_after: Some(0), limit: Some(1) },
+        )
+        .unwrap();
+        let actions: AdminActionsResponse = from_binary(&res).unwrap();
+        assert_eq!(actions.actions.len(), 1);
+        assert_eq!(actions.actions[0].id, 1);
+    }
+
+    #[test]
+    fn update_metadata() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: Some(GameMetadata {
+                title: Some("Game".to_string()),
+                ..GameMetadata::default()
+            }),
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Metadata {}).unwrap();
+        let metadata: GameMetadata = from_binary(&res).unwrap();
+        assert_eq!(Some("Game".to_string()), metadata.title);
+        assert_eq!(None, metadata.terms_hash);
+
+        // Update metadata
+        let metadata = GameMetadata {
+            title: Some("Game".to_string()),
+            description: Some("Guess your bin".to_string()),
+            url: Some("https://example.com".to_string()),
+            terms_hash: Some(
+                "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string()
+            ),
+        };
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::UpdateMetadata { metadata: metadata.clone() };
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Metadata {}).unwrap();
+        let stored: GameMetadata = from_binary(&res).unwrap();
+        assert_eq!(metadata, stored);
+
+        // Unauthorized err
+        let info = mock_info("addr0000", &[]);
+        let msg = ExecuteMsg::UpdateMetadata { metadata: metadata.clone() };
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+
+        // Invalid terms hash
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::UpdateMetadata {
+            metadata: GameMetadata {
+                terms_hash: Some("invalid".to_string()),
+                ..metadata.clone()
+            },
+        };
+        let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert!(matches!(res, ContractError::Hex(_)));
+
+        // Metadata cannot be changed once the bid stage started
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::UpdateMetadata { metadata };
+        let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert_eq!(res, ContractError::StageStarted { stage_name: "bid".to_string() });
+    }
+
+    #[test]
+    fn update_stages() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.clone().into(),
+            stage_claim_airdrop: stage_claim_airdrop.clone().into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let new_stage_claim_prize = Stage {
+            start: Scheduled::AtHeight(210_000),
+            duration: Duration::Height(5),
+        };
+
+        // Unauthorized err
+        let info = mock_info("addr0000", &[]);
+        let msg = ExecuteMsg::UpdateStages {
+            stage_bid: None,
+            stage_claim_airdrop: None,
+            stage_claim_prize: Some(new_stage_claim_prize.clone().into()),
+        };
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+
+        // The kept stages are checked against the new ones
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::UpdateStages {
+            stage_bid: Some(
+                Stage {
+                    start: Scheduled::AtHeight(202_000),
+                    duration: Duration::Height(2_000),
+                }
+                .into(),
+            ),
+            stage_claim_airdrop: None,
+            stage_claim_prize: None,
+        };
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::StagesOverlap { first: "bid".to_string(), second: "claim airdrop".to_string() }
+        );
+
+        // Reschedule the claim prize stage, the other stages are kept
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::UpdateStages {
+            stage_bid: None,
+            stage_claim_airdrop: None,
+            stage_claim_prize: Some(new_stage_claim_prize.clone().into()),
+        };
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(res.attributes, vec![attr("action", "update_stages")]);
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Stages {}).unwrap();
+        let stages: StagesResponse = from_binary(&res).unwrap();
+        assert_eq!(stages.stage_bid, stage_bid);
+        assert_eq!(stages.stage_claim_airdrop, stage_claim_airdrop);
+        assert_eq!(stages.stage_claim_prize, new_stage_claim_prize);
+        assert_eq!(stages.stage_claim_prize_end, Scheduled::AtHeight(210_005));
+
+        // Stages cannot be changed once the bid stage started
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::UpdateStages {
+            stage_bid: None,
+            stage_claim_airdrop: None,
+            stage_claim_prize: None,
+        };
+        let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert_eq!(res, ContractError::StageStarted { stage_name: "bid".to_string() });
+    }
+
+    #[test]
+    fn extend_claim_stages() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.clone().into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let mut env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let extend = |claim_airdrop, claim_prize| ExecuteMsg::ExtendClaimStages { claim_airdrop, claim_prize };
+
+        // Unauthorized err
+        env.block.height = 203_001;
+        let msg = extend(Some(Duration::Height(3_000)), None);
+        let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+
+        // Stages cannot be shortened nor extended by nothing
+        let owner = mock_info("owner0000", &[]);
+        for msg in [extend(None, None), extend(Some(Duration::Height(0)), Some(Duration::Height(5)))].iter() {
+            let res = execute(deps.as_mut(), env.clone(), owner.clone(), msg.clone()).unwrap_err();
+            assert_eq!(res, ContractError::InvalidZeroAmount {});
+        }
+        let msg = extend(Some(Duration::Time(60)), None);
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap_err();
+        assert!(matches!(res, ContractError::Std(_)));
+
+        // Extending the active claim airdrop stage postpones the overlapped claim prize stage
+        let msg = extend(Some(Duration::Height(3_000)), None);
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap();
+        assert_eq!(res.attributes, vec![attr("action", "extend_claim_stages")]);
+        assert_eq!(
+            res.events,
+            vec![
+                Event::new("stage_extended").add_attributes(vec![
+                    ("stage", "claim airdrop"),
+                    ("old_start", "scheduled height: 203000"),
+                    ("old_end", "scheduled height: 203002"),
+                    ("new_start", "scheduled height: 203000"),
+                    ("new_end", "scheduled height: 206002"),
+                ]),
+                Event::new("stage_extended").add_attributes(vec![
+                    ("stage", "claim prize"),
+                    ("old_start", "scheduled height: 206000"),
+                    ("old_end", "scheduled height: 206002"),
+                    ("new_start", "scheduled height: 206002"),
+                    ("new_end", "scheduled height: 206004"),
+                ]),
+            ]
+        );
+
+        // Only the claim prize stage is extended
+        let msg = extend(None, Some(Duration::Height(5)));
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap();
+        assert_eq!(res.events.len(), 1);
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Stages {}).unwrap();
+        let stages: StagesResponse = from_binary(&res).unwrap();
+        assert_eq!(stages.stage_bid, stage_bid);
+        assert_eq!(stages.stage_claim_airdrop_end, Scheduled::AtHeight(206_002));
+        assert_eq!(
+            stages.stage_claim_prize,
+            Stage { start: Scheduled::AtHeight(206_002), duration: Duration::Height(7) }
+        );
+
+        // Ended stages cannot be extended
+        env.block.height = 206_002;
+        let msg = extend(Some(Duration::Height(10)), None);
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap_err();
+        assert_eq!(res, ContractError::StageEnded { stage_name: "claim airdrop".to_string() });
+        env.block.height = 206_009;
+        let msg = extend(None, Some(Duration::Height(10)));
+        let res = execute(deps.as_mut(), env, owner, msg).unwrap_err();
+        assert_eq!(res, ContractError::StageEnded { stage_name: "claim prize".to_string() });
+    }
+
+    #[test]
+    fn treasuries() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: Some(Duration::Height(10)),
+            withdrawal_guard: None,
+        };
+
+        let mut env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // Unauthorized err
+        env.block.height = 206_002;
+        let set_treasury = ExecuteMsg::SetTreasury { addr: "treasury0000".to_string() };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), set_treasury.clone()).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+
+        // Unknown addresses cannot receive the withdrawals
+        let withdraw = ExecuteMsg::WithdrawPrize { address: Addr::unchecked("treasury0000") };
+        let owner = mock_info("owner0000", &[]);
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), withdraw.clone()).unwrap_err();
+        assert_eq!(res, ContractError::TreasuryNotActive { address: "treasury0000".to_string() });
+
+        // Registration starts the timelock
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), set_treasury.clone()).unwrap();
+        assert_eq!(res.attributes, vec![attr("action", "set_treasury")]);
+        assert_eq!(
+            res.events,
+            vec![Event::new("treasury_registered").add_attributes(vec![
+                ("address", "treasury0000"),
+                ("active_at", "scheduled height: 206012"),
+            ])]
+        );
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), set_treasury).unwrap_err();
+        assert_eq!(res, ContractError::TreasuryAlreadyRegistered { address: "treasury0000".to_string() });
+
+        // Pending treasuries cannot receive the withdrawals nor be activated
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), withdraw).unwrap_err();
+        assert_eq!(res, ContractError::TreasuryNotActive { address: "treasury0000".to_string() });
+        let activate = ExecuteMsg::ActivateTreasury { addr: "treasury0000".to_string() };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), activate.clone()).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::TreasuryTimelocked { active_at: "scheduled height: 206012".to_string() }
+        );
+
+        // Anyone activates it once the timelock has passed
+        env.block.height = 206_012;
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), activate).unwrap();
+        assert_eq!(
+            res.events,
+            vec![Event::new("treasury_activated").add_attribute("address", "treasury0000")]
+        );
+
+        let msg = QueryMsg::Treasuries { start_after: None, limit: None };
+        let res: TreasuriesResponse = from_binary(&query(deps.as_ref(), env.clone(), msg.clone()).unwrap()).unwrap();
+        assert_eq!(
+            res.treasuries,
+            vec![TreasuryResponse {
+                address: "treasury0000".to_string(),
+                active_at: Scheduled::AtHeight(206_012),
+                active: true,
+            }]
+        );
+
+        // The owner removes it, then it cannot receive the withdrawals anymore
+        let remove = ExecuteMsg::RemoveTreasury { addr: "treasury0000".to_string() };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), remove.clone()).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), remove.clone()).unwrap();
+        assert_eq!(
+            res.events,
+            vec![Event::new("treasury_removed").add_attribute("address", "treasury0000")]
+        );
+        let withdraw = ExecuteMsg::WithdrawPrize { address: Addr::unchecked("treasury0000") };
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), withdraw).unwrap_err();
+        assert_eq!(res, ContractError::TreasuryNotActive { address: "treasury0000".to_string() });
+        let res = execute(deps.as_mut(), env.clone(), owner, remove).unwrap_err();
+        assert_eq!(res, ContractError::TreasuryNotRegistered { address: "treasury0000".to_string() });
+        let res: TreasuriesResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert!(res.treasuries.is_empty());
+    }
+
+    #[test]
+    fn withdrawal_guard() {
+        let mut deps = mock_dependencies_with_balance(&[Coin::new(50, TEST_DENOM)]);
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: Some(WithdrawalGuardMsg {
+                guardian: "guardian0000".to_string(),
+                threshold: Uint128::new(100),
+                prize_threshold: Uint128::new(100),
+                secondary_threshold: Uint128::new(100),
+                window: Duration::Height(5),
+            }),
+        };
+
+        let mut env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+        set_counter(deps.as_mut().storage, |counters| {
+            counters.total_airdrop_amount = Uint128::new(200);
+            counters.total_ticket_prize = Uint128::new(50);
+        });
+
+        // Two rounds funded with 150 and 50 tokens, nobody claims them.
+        let tree = crate::merkle::MerkleTree::new(vec![leaf_hash("player0000", "100")]);
+        let stage = Stage { start: Scheduled::AtHeight(300_000), duration: Duration::Height(100) };
+        for amount in [150u128, 50].iter() {
+            let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "owner0000".to_string(),
+                amount: Uint128::new(*amount),
+                msg: to_binary(&ReceiveMsg::RegisterRound { merkle_root: tree.root(), stage: stage.clone().into() }).unwrap(),
+            });
+            execute(deps.as_mut(), env.clone(), mock_info("random0000", &[]), msg).unwrap();
+        }
+        set_active_treasury(&mut deps.storage, "treasury0000");
+        env.block.height = 300_100;
+
+        // Small withdrawals need only the owner.
+        let owner = mock_info("owner0000", &[]);
+        let withdraw = |round| ExecuteMsg::WithdrawRound { round, address: Addr::unchecked("treasury0000") };
+        execute(deps.as_mut(), env.clone(), owner.clone(), withdraw(2)).unwrap();
+
+        // Large ones must be approved by the guardian.
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), withdraw(1)).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::WithdrawalNeedsApproval { amount: Uint128::new(150), threshold: Uint128::new(100) }
+        );
+        let propose = ExecuteMsg::ProposeWithdrawal {
+            withdrawal: Withdrawal::Round { round: 1, address: Addr::unchecked("treasury0000") },
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("guardian0000", &[]), propose.clone()).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), propose.clone()).unwrap();
+        assert_eq!(res.attributes[1..], [attr("proposal_id", "1"), attr("expires", "scheduled height: 300105")]);
+
+        let msg = QueryMsg::WithdrawalProposals { start_after: None, limit: None };
+        let res: WithdrawalProposalsResponse = from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(
+            res.proposals,
+            vec![WithdrawalProposalResponse {
+                id: 1,
+                withdrawal: Withdrawal::Round { round: 1, address: Addr::unchecked("treasury0000") },
+                proposer: "owner0000".to_string(),
+                expires: Scheduled::AtHeight(300_105),
+            }]
+        );
+
+        // Only the guardian approves, within the window.
+        let approve = |id| ExecuteMsg::ApproveWithdrawal { id };
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), approve(1)).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+        let guardian = mock_info("guardian0000", &[]);
+        let res = execute(deps.as_mut(), env.clone(), guardian.clone(), approve(2)).unwrap_err();
+        assert_eq!(res, ContractError::WithdrawalProposalNotFound { id: 2 });
+        let mut expired_env = env.clone();
+        expired_env.block.height = 300_105;
+        let res = execute(deps.as_mut(), expired_env, guardian.clone(), approve(1)).unwrap_err();
+        assert_eq!(res, ContractError::WithdrawalProposalExpired { id: 1 });
+
+        let res = execute(deps.as_mut(), env.clone(), guardian.clone(), approve(1)).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(WasmMsg::Execute {
+                contract_addr: "random0000".to_string(),
+                msg: to_binary(&cw20::Cw20ExecuteMsg::Transfer {
+                    recipient: "treasury0000".to_string(),
+                    amount: Uint128::new(150),
+                })
+                .unwrap(),
+                funds: vec![],
+            })]
+        );
+        assert!(res.attributes.contains(&attr("proposal_id", "1")));
+
+        // Approved proposals are removed.
+        let res = execute(deps.as_mut(), env.clone(), guardian, approve(1)).unwrap_err();
+        assert_eq!(res, ContractError::WithdrawalProposalNotFound { id: 1 });
+
+        // The tranches of the airdrop leftover count towards the threshold together.
+        let withdraw = |amount| ExecuteMsg::WithdrawAirdrop {
+            address: Addr::unchecked("treasury0000"),
+            amount: Some(Uint128::new(amount)),
+        };
+        execute(deps.as_mut(), env.clone(), owner.clone(), withdraw(60)).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), withdraw(60)).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::WithdrawalNeedsApproval { amount: Uint128::new(120), threshold: Uint128::new(100) }
+        );
+
+        // The prize leftover is withdrawn once.
+        let withdraw = ExecuteMsg::WithdrawPrize { address: Addr::unchecked("treasury0000") };
+        execute(deps.as_mut(), env.clone(), owner.clone(), withdraw.clone()).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), owner, withdraw).unwrap_err();
+        assert_eq!(res, ContractError::InvalidWithdrawAmount { remaining: Uint128::zero() });
+
+        // The guardian can remove a treasury too, e.g. one registered with a stolen key.
+        let msg = ExecuteMsg::SetTreasury { addr: "treasury0001".to_string() };
+        execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+        let msg = ExecuteMsg::RemoveTreasury { addr: "treasury0001".to_string() };
+        execute(deps.as_mut(), env, mock_info("guardian0000", &[]), msg).unwrap();
+        assert!(!TREASURIES.has(deps.as_ref().storage, &Addr::unchecked("treasury0001")));
+    }
+
+    #[test]
+    fn withdraw_retired_payout() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: Some(WithdrawalGuardMsg {
+                guardian: "guardian0000".to_string(),
+                threshold: Uint128::new(100),
+                prize_threshold: Uint128::new(100),
+                secondary_threshold: Uint128::new(100),
+                window: Duration::Height(5),
+            }),
+        };
+
+        let env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // The balance of a token replaced by a swap, above the guard threshold.
+        RETIRED_PAYOUT_TOKENS.save(&mut deps.storage, &Addr::unchecked("old0000"), &Uint128::new(150)).unwrap();
+        set_active_treasury(&mut deps.storage, "treasury0000");
+
+        let owner = mock_info("owner0000", &[]);
+        let withdraw = |cw20: &str| ExecuteMsg::WithdrawRetiredPayout {
+            cw20_token_address: Addr::unchecked(cw20),
+            address: Addr::unchecked("treasury0000"),
+        };
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), withdraw("old0000")).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::WithdrawalNeedsApproval { amount: Uint128::new(150), threshold: Uint128::new(100) }
+        );
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), withdraw("random0000")).unwrap_err();
+        assert_eq!(res, ContractError::InvalidWithdrawAmount { remaining: Uint128::zero() });
+
+        let propose = ExecuteMsg::ProposeWithdrawal {
+            withdrawal: Withdrawal::RetiredPayout {
+                cw20_token_address: Addr::unchecked("old0000"),
+                address: Addr::unchecked("treasury0000"),
+            },
+        };
+        execute(deps.as_mut(), env.clone(), owner.clone(), propose).unwrap();
+        let approve = ExecuteMsg::ApproveWithdrawal { id: 1 };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("guardian0000", &[]), approve).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(WasmMsg::Execute {
+                contract_addr: "old0000".to_string(),
+                msg: to_binary(&cw20::Cw20ExecuteMsg::Transfer {
+                    recipient: "treasury0000".to_string(),
+                    amount: Uint128::new(150),
+                })
+                .unwrap(),
+                funds: vec![],
+            })]
+        );
+
+        // The retired balance is withdrawn once.
+        let res = execute(deps.as_mut(), env, owner, withdraw("old0000")).unwrap_err();
+        assert_eq!(res, ContractError::InvalidWithdrawAmount { remaining: Uint128::zero() });
+    }
+
+    #[test]
+    fn claim_prize_merkle_amounts() {
+        let mut deps = mock_dependencies_with_balance(&[Coin::new(1_000, TEST_DENOM)]);
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: Some(PrizeMode::MerkleAmounts),
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+        // Two players buy a ticket: the prize pool is 20ujuno.
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        for player in ["player0000", "player0001"] {
+            let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: None }).unwrap();
+        }
+
+        // Prizes assigned off-chain exceed the pool.
+        let leaves = vec![
+            leaf_hash("player0000", "15"),
+            leaf_hash("player0001", "10"),
+            leaf_hash("player0002", "0"),
+        ];
+        let tree = crate::merkle::MerkleTree::new(leaves.clone());
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: "b45c1ea28b26adb13e412933c9e055b01fdf7585304b00cd8f1cb220aa6c5e88".to_string(),
+            total_amount_airdrop: None,
+            merkle_root_game: tree.root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        env.block.height = 206_000;
+
+        // Amount and proof are required.
+        let info = mock_info("player0000", &[]);
+        let msg = ExecuteMsg::ClaimPrize { amount: Some(Uint128::new(15)), proof: None, then: None };
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert_eq!(res, ContractError::InvalidInput {});
+
+        // Wrong amount.
+        let info = mock_info("player0000", &[]);
+        let msg = ExecuteMsg::ClaimPrize {
+            amount: Some(Uint128::new(20)),
+            proof: tree.proof(&leaves[0]),
+            then: None,
+        };
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert_eq!(res, ContractError::VerificationFailed { merkle_root: "game".to_string() });
+
+        // Zero prizes are rejected even if they are in the tree.
+        let info = mock_info("player0002", &[]);
+        let msg = ExecuteMsg::ClaimPrize {
+            amount: Some(Uint128::zero()),
+            proof: tree.proof(&leaves[2]),
+            then: None,
+        };
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert_eq!(res, ContractError::InvalidZeroAmount {});
+
+        let info = mock_info("player0000", &[]);
+        let msg = ExecuteMsg::ClaimPrize {
+            amount: Some(Uint128::new(15)),
+            proof: tree.proof(&leaves[0]),
+            then: None,
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            Asset::native(TEST_DENOM, Uint128::new(15)).transfer_msg(&info.sender).unwrap()
+        );
+        let claimed = query_is_claimed(deps.as_ref(), "player0000".to_string()).unwrap();
+        assert_eq!(claimed.prize_claimed_at, Some(ClaimedAt::new(&env.block)));
+
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert_eq!(res, ContractError::AlreadyClaimed {});
+
+        // Only 5ujuno are left in the pool.
+        let info = mock_info("player0001", &[]);
+        let msg = ExecuteMsg::ClaimPrize {
+            amount: Some(Uint128::new(10)),
+            proof: tree.proof(&leaves[1]),
+            then: None,
+        };
+        let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert_eq!(res, ContractError::PrizePoolExceeded {});
+    }
+
+    #[test]
+    fn boost_prize() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+        // Boosting before the bid stage is not allowed.
+        let mut env = mock_env();
+        let info = mock_info("sponsor0000", &[Coin::new(100, TEST_DENOM)]);
+        let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::BoostPrize {}).unwrap_err();
+        assert_eq!(res, ContractError::StageNotStarted { stage_name: "bid".to_string() });
+
+        // Boosts are accepted between the bid and the claim airdrop stages.
+        env.block.height = 202_000;
+        let info = mock_info("sponsor0000", &[Coin::new(100, TEST_DENOM)]);
+        execute(deps.as_mut(), env.clone(), info, ExecuteMsg::BoostPrize {}).unwrap();
+        let info = mock_info("sponsor0001", &[Coin::new(150, TEST_DENOM)]);
+        execute(deps.as_mut(), env.clone(), info, ExecuteMsg::BoostPrize {}).unwrap();
+        let info = mock_info("sponsor0000", &[Coin::new(70, TEST_DENOM)]);
+        let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::BoostPrize {}).unwrap();
+        assert_eq!(res.attributes[3], attr("total_boost", "170"));
+
+        // Only the ticket denom can be used.
+        let info = mock_info("sponsor0002", &[Coin::new(100, "ubtc")]);
+        let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::BoostPrize {}).unwrap_err();
+        assert_eq!(res, ContractError::Payment(PaymentError::MissingDenom(TEST_DENOM.to_string())));
+
+        assert_eq!(COUNTERS.load(&deps.storage).unwrap().total_ticket_prize, Uint128::new(320));
+
+        // The boosts are in the prize pot, less the prizes claimed.
+        set_counter(deps.as_mut().storage, |counters| counters.claimed_prize_amount = Uint128::new(20));
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::PrizePot {}).unwrap();
+        let pot: PrizePotResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            pot,
+            PrizePotResponse {
+                pot: Coin::new(300, TEST_DENOM),
+                total_ticket_prize: Uint128::new(320),
+                total_ticket_payments: Uint128::zero(),
+                total_claimed_prize: Uint128::new(20),
+                refund_mode: false,
+            }
+        );
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::TopBoosters { limit: None }).unwrap();
+        let top: TopBoostersResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            top.boosters,
+            vec![
+                BoosterResponse { address: "sponsor0000".to_string(), amount: Uint128::new(170) },
+                BoosterResponse { address: "sponsor0001".to_string(), amount: Uint128::new(150) },
+            ]
+        );
+
+        // Boosting after the claim airdrop stage is not allowed.
+        env.block.height = 203_002;
+        let info = mock_info("sponsor0000", &[Coin::new(100, TEST_DENOM)]);
+        let res = execute(deps.as_mut(), env, info, ExecuteMsg::BoostPrize {}).unwrap_err();
+        assert_eq!(res, ContractError::StageEnded { stage_name: "claim airdrop".to_string() });
+    }
+
+    #[test]
+    fn bin_side_pools() {
+        let mut deps = mock_dependencies_with_balance(&[Coin::new(1_000, TEST_DENOM)]);
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+        // Players bid and sponsors boost the bins.
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        for (player, bin) in [("player0000", 3), ("player0001", 5)] {
+            let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin, pubkey_proof: None, ticket_id: None }).unwrap();
+        }
+        for (sponsor, bin, amount) in [("sponsor0000", 3, 50), ("sponsor0001", 5, 40)] {
+            let info = mock_info(sponsor, &[Coin::new(amount, TEST_DENOM)]);
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::BoostBin { bin }).unwrap();
+        }
+
+        let info = mock_info("sponsor0000", &[Coin::new(50, TEST_DENOM)]);
+        let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::BoostBin { bin: 11 }).unwrap_err();
+        assert_eq!(res, ContractError::BinDoesNotExist { bins: 10 });
+
+        // Only player0000 guessed the right bin.
+        let airdrop_leaves = vec![leaf_hash("player0000", "100"), leaf_hash("player0001", "100")];
+        let game_leaves = vec![leaf_hash("player0000", "3"), leaf_hash("player0001", "4")];
+        let airdrop_tree = crate::merkle::MerkleTree::new(airdrop_leaves.clone());
+        let game_tree = crate::merkle::MerkleTree::new(game_leaves.clone());
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: airdrop_tree.root(),
+            total_amount_airdrop: Some(Uint128::new(200)),
+            merkle_root_game: game_tree.root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        open_claims(&mut deps, 200).unwrap();
+
+        env.block.height = 203_000;
+        for (i, player) in ["player0000", "player0001"].iter().enumerate() {
+            let info = mock_info(player, &[]);
+            let msg = ExecuteMsg::ClaimAirdrop {
+                amount: Uint128::new(100),
+                proof_airdrop: airdrop_tree.proof(&airdrop_leaves[i]).unwrap(),
+                proof_game: game_tree.proof(&game_leaves[i]).unwrap(),
+            };
+            execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        }
+
+        // Bins cannot be boosted once winners start to be revealed.
+        let info = mock_info("sponsor0000", &[Coin::new(50, TEST_DENOM)]);
+        let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::BoostBin { bin: 3 }).unwrap_err();
+        assert_eq!(res, ContractError::StageStarted { stage_name: "claim airdrop".to_string() });
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::BinPools {}).unwrap();
+        let pools: BinPoolsResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            pools.pools,
+            vec![
+                BinPoolResponse {
+                    bin: 3,
+                    amount: Uint128::new(50),
+                    winners: Uint128::new(1),
+                    ticket_prize: Uint128::zero(),
+                    claimed: Uint128::zero(),
+                },
+                BinPoolResponse {
+                    bin: 5,
+                    amount: Uint128::new(40),
+                    winners: Uint128::zero(),
+                    ticket_prize: Uint128::zero(),
+                    claimed: Uint128::zero(),
+                },
+            ]
+        );
+
+        // The winner receives the tickets and the side-pool of its bin.
+        env.block.height = 206_000;
+        let info = mock_info("player0000", &[]);
+        let msg = ExecuteMsg::ClaimPrize { amount: None, proof: None, then: None };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            Asset::native(TEST_DENOM, Uint128::new(70)).transfer_msg(&info.sender).unwrap()
+        );
+
+        // Boosts of bins without winners are refunded.
+        let info = mock_info("sponsor0001", &[]);
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::RefundBinBoost { bin: 5 }).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            Asset::native(TEST_DENOM, Uint128::new(40)).transfer_msg(&info.sender).unwrap()
+        );
+        let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::RefundBinBoost { bin: 5 }).unwrap_err();
+        assert_eq!(res, ContractError::NothingToRefund {});
+
+        let info = mock_info("sponsor0000", &[]);
+        let res = execute(deps.as_mut(), env, info, ExecuteMsg::RefundBinBoost { bin: 3 }).unwrap_err();
+        assert_eq!(res, ContractError::BinWon { bin: 3 });
+    }
+
+    #[test]
+    fn claims_page() {
+        let mut deps = mock_dependencies_with_balance(&[Coin::new(1_000, TEST_DENOM)]);
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+        // Players bid and only player0000 guesses the right bin.
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        for (player, bin) in [("player0000", 3), ("player0001", 5)] {
+            let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin, pubkey_proof: None, ticket_id: None }).unwrap();
+        }
+
+        let airdrop_leaves = vec![leaf_hash("player0000", "100"), leaf_hash("player0001", "60")];
+        let game_leaves = vec![leaf_hash("player0000", "3"), leaf_hash("player0001", "4")];
+        let airdrop_tree = crate::merkle::MerkleTree::new(airdrop_leaves.clone());
+        let game_tree = crate::merkle::MerkleTree::new(game_leaves.clone());
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: airdrop_tree.root(),
+            total_amount_airdrop: Some(Uint128::new(160)),
+            merkle_root_game: game_tree.root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        open_claims(&mut deps, 160).unwrap();
+
+        // Nothing has been paid yet.
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::ClaimsPage { start_after: None, limit: None }).unwrap();
+        let page: ClaimsPageResponse = from_binary(&res).unwrap();
+        assert_eq!(page.claims, vec![]);
+
+        env.block.height = 203_000;
+        for (i, (player, amount)) in [("player0001", 60), ("player0000", 100)].iter().enumerate() {
+            let info = mock_info(player, &[]);
+            let msg = ExecuteMsg::ClaimAirdrop {
+                amount: Uint128::new(*amount),
+                proof_airdrop: airdrop_tree.proof(&airdrop_leaves[1 - i]).unwrap(),
+                proof_game: game_tree.proof(&game_leaves[1 - i]).unwrap(),
+            };
+            execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        }
+
+        env.block.height = 206_000;
+        let info = mock_info("player0000", &[]);
+        let msg = ExecuteMsg::ClaimPrize { amount: None, proof: None, then: None };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // Payouts are listed in the order in which they have been made.
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::ClaimsPage { start_after: None, limit: None }).unwrap();
+        let page: ClaimsPageResponse = from_binary(&res).unwrap();
+        let claim = |seq, address: &str, kind, amount, height| ClaimRecordResponse {
+            seq,
+            address: address.to_string(),
+            kind,
+            amount: Uint128::new(amount),
+            height,
+            time: mock_env().block.time,
+        };
+        assert_eq!(
+            page.claims,
+            vec![
+                claim(0, "player0001", ClaimKind::Airdrop, 60, 203_000),
+                claim(1, "player0000", ClaimKind::Airdrop, 100, 203_000),
+                claim(2, "player0000", ClaimKind::Prize, 20, 206_000),
+            ]
+        );
+
+        let res = query(deps.as_ref(), env, QueryMsg::ClaimsPage { start_after: Some(0), limit: Some(1) }).unwrap();
+        let page: ClaimsPageResponse = from_binary(&res).unwrap();
+        assert_eq!(page.claims, vec![claim(1, "player0000", ClaimKind::Airdrop, 100, 203_000)]);
+    }
+
+    #[test]
+    fn airdrop_rounds() {
+        let mut deps = mock_dependencies_with_balance(&[Coin::new(1_000, TEST_DENOM)]);
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+        // Rounds are funded with the cw20 tokens sent by the owner.
+        let leaves = vec![leaf_hash("player0000", "100"), leaf_hash("player0001", "50")];
+        let tree = crate::merkle::MerkleTree::new(leaves.clone());
+        let stage = Stage { start: Scheduled::AtHeight(300_000), duration: Duration::Height(100) };
+        let register = |sender: &str| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: sender.to_string(),
+                amount: Uint128::new(150),
+                msg: to_binary(&ReceiveMsg::RegisterRound { merkle_root: tree.root(), stage: stage.clone().into() }).unwrap(),
+            })
+        };
+        let mut env = mock_env();
+        let res = execute(deps.as_mut(), env.clone(), mock_info("random0000", &[]), register("player0000")).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+        let msg = ExecuteMsg::RegisterRound { merkle_root: tree.root(), stage: stage.clone().into() };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap_err();
+        assert_eq!(res, ContractError::NativeAirdropNotEnabled {});
+        let res = execute(deps.as_mut(), env.clone(), mock_info("random0000", &[]), register("owner0000")).unwrap();
+        assert!(res.attributes.contains(&attr(ATTR_ROUND, "1")));
+
+        let latest: LatestRoundResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::LatestRound {}).unwrap()).unwrap();
+        assert_eq!(latest.latest_round, 1);
+        let round: RoundResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Round { round: 1 }).unwrap()).unwrap();
+        assert_eq!(
+            round,
+            RoundResponse {
+                round: 1,
+                merkle_root: tree.root(),
+                stage,
+                total_amount: Uint128::new(150),
+                claimed_amount: Uint128::zero(),
+                withdrawn: false,
+            }
+        );
+
+        // Allocations are claimed once, during the stage of the round.
+        let claim = |round: u8, amount: u128| ExecuteMsg::ClaimRound {
+            round,
+            amount: Uint128::new(amount),
+            proof: tree.proof(&leaves[0]).unwrap(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim(1, 100)).unwrap_err();
+        assert_eq!(res, ContractError::StageNotStarted { stage_name: "airdrop round 1".to_string() });
+
+        env.block.height = 300_000;
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim(2, 100)).unwrap_err();
+        assert_eq!(res, ContractError::RoundNotFound { round: 2 });
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim(1, 150)).unwrap_err();
+        assert_eq!(res, ContractError::VerificationFailed { merkle_root: "round".to_string() });
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim(1, 100)).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(Asset::cw20(Addr::unchecked("random0000"), Uint128::new(100)).transfer_msg("player0000").unwrap())]
+        );
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim(1, 100)).unwrap_err();
+        assert_eq!(res, ContractError::AlreadyClaimed {});
+        let msg = QueryMsg::IsRoundClaimed { round: 1, address: "player0000".to_string() };
+        let claimed: IsRoundClaimedResponse = from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert!(claimed.is_claimed);
+
+        // The owner withdraws the unclaimed allocations once the round is over.
+        set_active_treasury(&mut deps.storage, "owner0000");
+        let owner = mock_info("owner0000", &[]);
+        let withdraw = ExecuteMsg::WithdrawRound { round: 1, address: Addr::unchecked("owner0000") };
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), withdraw.clone()).unwrap_err();
+        assert_eq!(res, ContractError::RoundNotFinished { round: 1 });
+
+        env.block.height = 300_100;
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim(1, 100)).unwrap_err();
+        assert_eq!(res, ContractError::StageEnded { stage_name: "airdrop round 1".to_string() });
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), withdraw.clone()).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(Asset::cw20(Addr::unchecked("random0000"), Uint128::new(50)).transfer_msg("owner0000").unwrap())]
+        );
+        let res = execute(deps.as_mut(), env, owner, withdraw).unwrap_err();
+        assert_eq!(res, ContractError::InvalidWithdrawAmount { remaining: Uint128::zero() });
+    }
+
+    #[test]
+    fn per_bin_prizes() {
+        let mut deps = mock_dependencies_with_balance(&[Coin::new(1_000, TEST_DENOM)]);
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: Some(PrizeMode::PerBin),
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let info = mock_info("owner0000", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let players = [("player0000", 3), ("player0001", 3), ("player0002", 5), ("player0003", 2)];
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        for (player, bin) in players {
+            let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin, pubkey_proof: None, ticket_id: None }).unwrap();
+        }
+        let info = mock_info("sponsor0000", &[Coin::new(30, TEST_DENOM)]);
+        execute(deps.as_mut(), env.clone(), info, ExecuteMsg::BoostBin { bin: 5 }).unwrap();
+
+        // Bins 3 and 5 are both winning, player0003 guessed the wrong bin.
+        let airdrop_leaves: Vec<_> = players.iter().map(|(player, _)| leaf_hash(player, "100")).collect();
+        let game_leaves = vec![
+            leaf_hash("player0000", "3"),
+            leaf_hash("player0001", "3"),
+            leaf_hash("player0002", "5"),
+            leaf_hash("player0003", "7"),
+        ];
+        let airdrop_tree = crate::merkle::MerkleTree::new(airdrop_leaves.clone());
+        let game_tree = crate::merkle::MerkleTree::new(game_leaves.clone());
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: airdrop_tree.root(),
+            total_amount_airdrop: Some(Uint128::new(400)),
+            merkle_root_game: game_tree.root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+        open_claims(&mut deps, 400).unwrap();
+
+        env.block.height = 203_000;
+        for (i, (player, _)) in players.iter().enumerate() {
+            let msg = ExecuteMsg::ClaimAirdrop {
+                amount: Uint128::new(100),
+                proof_airdrop: airdrop_tree.proof(&airdrop_leaves[i]).unwrap(),
+                proof_game: game_tree.proof(&game_leaves[i]).unwrap(),
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(player, &[]), msg).unwrap();
+        }
+
+        // The 40 tickets are split 2:1 among the winning bins, the remainder is left
+        // to the owner.
+        env.block.height = 206_000;
+        let msg = ExecuteMsg::ClaimPrize { amount: None, proof: None, then: None };
+        let info = mock_info("player0000", &[]);
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            Asset::native(TEST_DENOM, Uint128::new(13)).transfer_msg(&info.sender).unwrap()
+        );
+        let info = mock_info("player0002", &[]);
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            Asset::native(TEST_DENOM, Uint128::new(43)).transfer_msg(&info.sender).unwrap()
+        );
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0003", &[]), msg).unwrap_err();
+        assert_eq!(res, ContractError::NoteEligible {});
+
+        let res = query(deps.as_ref(), env, QueryMsg::BinPools {}).unwrap();
+        let pools: BinPoolsResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            pools.pools,
+            vec![
+                BinPoolResponse {
+                    bin: 3,
+                    amount: Uint128::zero(),
+                    winners: Uint128::new(2),
+                    ticket_prize: Uint128::new(26),
+                    claimed: Uint128::new(13),
+                },
+                BinPoolResponse {
+                    bin: 5,
+                    amount: Uint128::new(30),
+                    winners: Uint128::new(1),
+                    ticket_prize: Uint128::new(13),
+                    claimed: Uint128::new(43),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn dust_to_treasury() {
+        let game = |treasury_active: bool| {
+            let mut deps = mock_dependencies_with_balance(&[Coin::new(1_000, TEST_DENOM)]);
+
+            let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+            let msg = InstantiateMsg {
+                owner: Some("owner0000".to_string()),
+                cw20_token_address: "random0000".to_string(),
+                airdrop_native_denom: None,
+                eligibility_hook: None,
+                ticket_price: Coin {
+                    denom: TEST_DENOM.into(),
+                    amount: Uint128::new(10)
+                },
+                bins: 10,
+                stage_bid: stage_bid.into(),
+                stage_claim_airdrop: stage_claim_airdrop.into(),
+                stage_claim_prize: stage_claim_prize.into(),
+                metadata: None,
+                prize_mode: None,
+                rounding_policy: Some(RoundingPolicy::DustToTreasury { address: Addr::unchecked("treasury0000") }),
+                pubkey_bids: None,
+                approved_proxies: None,
+                claim_rate_limit: None,
+                ticket_vendor: None,
+                max_proof_length: None,
+                badge_contract: None,
+                max_bid_changes: None,
+                late_bid_penalty: None,
+                private_bins: None,
+                hidden_bids: None,
+                minimal_attributes: None,
+                prize_timelock: None,
+                payout_mode: None,
+                no_winners_policy: None,
+                governance_mode: None,
+                kyc_gate: None,
+                leaf_format: None,
+                claim_index: None,
+                registry: None,
+                test_mode: None,
+                min_prize_pool: None,
+                treasury_timelock: None,
+                withdrawal_guard: None,
+            };
+            instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+            if treasury_active {
+                set_active_treasury(deps.as_mut().storage, "treasury0000");
+            }
+
+            // 40 tickets, 3 winners: 1 token of dust.
+            let players = [("player0000", 3), ("player0001", 3), ("player0002", 3), ("player0003", 2)];
+            let mut env = mock_env();
+            env.block.height = 200_000;
+            for (player, bin) in players {
+                let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+                execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin, pubkey_proof: None, ticket_id: None }).unwrap();
+            }
+            let airdrop_leaves: Vec<_> = players.iter().map(|(player, _)| leaf_hash(player, "100")).collect();
+            let game_leaves: Vec<_> = players.iter().map(|(player, _)| leaf_hash(player, "3")).collect();
+            let airdrop_tree = crate::merkle::MerkleTree::new(airdrop_leaves.clone());
+            let game_tree = crate::merkle::MerkleTree::new(game_leaves.clone());
+            let msg = ExecuteMsg::RegisterMerkleRoots {
+                merkle_root_airdrop: airdrop_tree.root(),
+                total_amount_airdrop: Some(Uint128::new(400)),
+                merkle_root_game: game_tree.root(),
+                total_amount_game: None,
+                tree_metadata: tree_metadata(),
+                overwrite: None,
+                secondary_payout: None,
+                claim_condition: None,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+            open_claims(&mut deps, 400).unwrap();
+
+            env.block.height = 203_000;
+            for (i, (player, _)) in players.iter().enumerate() {
+                let msg = ExecuteMsg::ClaimAirdrop {
+                    amount: Uint128::new(100),
+                    proof_airdrop: airdrop_tree.proof(&airdrop_leaves[i]).unwrap(),
+                    proof_game: game_tree.proof(&game_leaves[i]).unwrap(),
+                };
+                execute(deps.as_mut(), env.clone(), mock_info(player, &[]), msg).unwrap();
+            }
+            deps
+        };
+        let mut env = mock_env();
+        env.block.height = 206_000;
+        let msg = ExecuteMsg::ClaimPrize { amount: None, proof: None, then: None };
+        let dust = Asset::native(TEST_DENOM, Uint128::new(1)).transfer_msg("treasury0000").unwrap();
+
+        // The dust is sent to the active treasury along with the first claim.
+        let mut deps = game(true);
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), msg.clone()).unwrap();
+        assert!(res.messages.iter().any(|sub| sub.msg == dust));
+
+        // It stays in the pool while the treasury is not active.
+        let mut deps = game(false);
+        let res = execute(deps.as_mut(), env, mock_info("player0000", &[]), msg).unwrap();
+        assert!(!res.messages.iter().any(|sub| sub.msg == dust));
+        assert_eq!(
+            res.messages[0].msg,
+            Asset::native(TEST_DENOM, Uint128::new(13)).transfer_msg("player0000").unwrap()
+        );
+    }
+
+    #[test]
+    fn closed_snapshot() {
+        let mut deps = mock_dependencies_with_balance(&[Coin::new(1_000, TEST_DENOM)]);
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let info = mock_info("owner0000", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        for (player, bin) in [("player0000", 3), ("player0001", 5)] {
+            let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin, pubkey_proof: None, ticket_id: None }).unwrap();
+        }
+
+        let airdrop_leaves = vec![leaf_hash("player0000", "100"), leaf_hash("player0001", "100")];
+        let game_leaves = vec![leaf_hash("player0000", "3"), leaf_hash("player0001", "4")];
+        let airdrop_tree = crate::merkle::MerkleTree::new(airdrop_leaves.clone());
+        let game_tree = crate::merkle::MerkleTree::new(game_leaves.clone());
+        let register_msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: airdrop_tree.root(),
+            total_amount_airdrop: Some(Uint128::new(200)),
+            merkle_root_game: game_tree.root(),
+            total_amount_game: Some(Uint128::new(50)),
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), register_msg.clone()).unwrap();
+        open_claims(&mut deps, 250).unwrap();
+
+        env.block.height = 203_000;
+        let msg = ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::new(100),
+            proof_airdrop: airdrop_tree.proof(&airdrop_leaves[0]).unwrap(),
+            proof_game: game_tree.proof(&game_leaves[0]).unwrap(),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), msg).unwrap();
+
+        // Queries cannot record the snapshot.
+        env.block.height = 203_002;
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::GameAmounts {}).unwrap();
+        let amounts: GameAmountsResponse = from_binary(&res).unwrap();
+        assert_eq!(amounts.closed_snapshot, None);
+
+        // The first transaction after the claim airdrop stage records it.
+        env.block.height = 206_000;
+        let msg = ExecuteMsg::ClaimPrize { amount: None, proof: None, then: None };
+        execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), msg).unwrap();
+        let expected = ClosedSnapshot {
+            height: 206_000,
+            winners: Uint128::new(1),
+            bin_winners: vec![(3, Uint128::new(1))],
+            total_ticket_prize: Uint128::new(20),
+            total_bin_boost: Uint128::zero(),
+            total_airdrop_amount: Uint128::new(200),
+            total_airdrop_game_amount: Uint128::new(50),
+            claimed_airdrop_amount: Uint128::new(100),
+            refund_mode: false,
+        };
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::GameAmounts {}).unwrap();
+        let amounts: GameAmountsResponse = from_binary(&res).unwrap();
+        assert_eq!(amounts.closed_snapshot, Some(expected.clone()));
+
+        // The roots cannot be replaced anymore.
+        let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), register_msg).unwrap_err();
+        assert_eq!(res, ContractError::ClaimWindowClosed {});
+
+        // Later changes of the live totals do not affect the withdraw.
+        set_counter(&mut deps.storage, |counters| counters.total_ticket_prize = Uint128::new(1_000));
+        set_active_treasury(&mut deps.storage, "owner0000");
+        env.block.height = 206_002;
+        let msg = ExecuteMsg::WithdrawPrize { address: Addr::unchecked("owner0000") };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap_err();
+        assert_eq!(res, ContractError::InvalidWithdrawAmount { remaining: Uint128::zero() });
+        assert_eq!(CLOSED_SNAPSHOT.load(&deps.storage).unwrap(), expected);
+    }
+
+    #[test]
+    fn bid_response() {
+        let mut deps = mock_dependencies_with_balance(&[Coin::new(1_000, TEST_DENOM)]);
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let info = mock_info("owner0000", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let query_bid = |deps: Deps, player: &str| -> BidResponse {
+            let msg = QueryMsg::Bid { address: player.to_string(), viewer_proof: None };
+            from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        for (player, bin) in [("player0000", 3), ("player0001", 5)] {
+            let info = mock_info(player, &[Coin::new(15, TEST_DENOM)]);
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin, pubkey_proof: None, ticket_id: None }).unwrap();
+        }
+
+        // The price paid is recorded without the change.
+        let open_bid = BidResponse {
+            has_bid: true,
+            bin: Some(3),
+            paid: Some(Coin::new(10, TEST_DENOM)),
+            placed_at: Some(200_000),
+            is_winner: None,
+            prize_claimed: false,
+        };
+        assert_eq!(query_bid(deps.as_ref(), "player0000"), open_bid);
+        let msg = QueryMsg::LegacyBid { address: "player0000".to_string() };
+        let legacy: LegacyBidResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(legacy, LegacyBidResponse { bid: Some(3) });
+        let no_bid = BidResponse { has_bid: false, bin: None, paid: None, placed_at: None, is_winner: None, prize_claimed: false };
+        assert_eq!(query_bid(deps.as_ref(), "player0002"), no_bid);
+
+        let airdrop_leaves = vec![leaf_hash("player0000", "100"), leaf_hash("player0001", "100")];
+        let game_leaves = vec![leaf_hash("player0000", "3"), leaf_hash("player0001", "4")];
+        let airdrop_tree = crate::merkle::MerkleTree::new(airdrop_leaves.clone());
+        let game_tree = crate::merkle::MerkleTree::new(game_leaves.clone());
+        let register_msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: airdrop_tree.root(),
+            total_amount_airdrop: Some(Uint128::new(200)),
+            merkle_root_game: game_tree.root(),
+            total_amount_game: Some(Uint128::new(50)),
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), register_msg).unwrap();
+        open_claims(&mut deps, 250).unwrap();
+
+        // A winner is known once it claims the airdrop, the others when the stage ends.
+        env.block.height = 203_000;
+        let msg = ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::new(100),
+            proof_airdrop: airdrop_tree.proof(&airdrop_leaves[0]).unwrap(),
+            proof_game: game_tree.proof(&game_leaves[0]).unwrap(),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), msg).unwrap();
+        assert_eq!(query_bid(deps.as_ref(), "player0000").is_winner, Some(true));
+        assert_eq!(query_bid(deps.as_ref(), "player0001").is_winner, None);
+
+        env.block.height = 206_000;
+        let msg = ExecuteMsg::ClaimPrize { amount: None, proof: None, then: None };
+        execute(deps.as_mut(), env, mock_info("player0000", &[]), msg).unwrap();
+        let winner = query_bid(deps.as_ref(), "player0000");
+        assert_eq!((winner.is_winner, winner.prize_claimed), (Some(true), true));
+        let loser = query_bid(deps.as_ref(), "player0001");
+        assert_eq!((loser.is_winner, loser.prize_claimed), (Some(false), false));
+    }
+
+    #[test]
+    fn ticket_denom() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin::new(10, "uosmo"),
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+        let config = query_config(deps.as_ref()).unwrap();
+        assert_eq!((config.ticket_price, config.bins), (Coin::new(10, "uosmo"), 10));
+
+        // The ticket is paid in the denom of the ticket price, whatever the chain.
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let bid = ExecuteMsg::Bid { bin: 3, pubkey_proof: None, ticket_id: None };
+        let info = mock_info("player0000", &[Coin::new(10, TEST_DENOM)]);
+        let err = execute(deps.as_mut(), env.clone(), info, bid.clone()).unwrap_err();
+        assert_eq!(err, ContractError::TicketPriceNotPaid {});
+
+        let info = mock_info("player0001", &[Coin::new(10, "uosmo")]);
+        execute(deps.as_mut(), env.clone(), info, bid).unwrap();
+
+        // And refunded in the same denom.
+        let res = execute(deps.as_mut(), env, mock_info("player0001", &[]), ExecuteMsg::RemoveBid {}).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "player0001".to_string(),
+                amount: vec![Coin::new(10, "uosmo")],
+            })]
+        );
+    }
+
+    #[test]
+    fn proof_cache() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let info = mock_info("owner0000", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        for (player, bin) in [("player0000", 3), ("player0001", 5)] {
+            let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin, pubkey_proof: None, ticket_id: None }).unwrap();
+        }
+
+        let register = |deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, amount: &str, overwrite| {
+            let airdrop_leaves = vec![leaf_hash("player0000", "100"), leaf_hash("player0001", amount)];
+            let game_leaves = vec![leaf_hash("player0000", "3"), leaf_hash("player0001", "4")];
+            let airdrop_tree = crate::merkle::MerkleTree::new(airdrop_leaves.clone());
+            let game_tree = crate::merkle::MerkleTree::new(game_leaves.clone());
+            let msg = ExecuteMsg::RegisterMerkleRoots {
+                merkle_root_airdrop: airdrop_tree.root(),
+                total_amount_airdrop: Some(Uint128::new(300)),
+                merkle_root_game: game_tree.root(),
+                total_amount_game: None,
+                tree_metadata: tree_metadata(),
+                overwrite,
+                secondary_payout: None,
+                claim_condition: None,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+            (0..2)
+                .map(|i| ProofToCache {
+                    address: format!("player000{}", i),
+                    amount: Uint128::new(if i == 0 { 100 } else { amount.parse().unwrap() }),
+                    proof_airdrop: airdrop_tree.proof(&airdrop_leaves[i]).unwrap(),
+                    proof_game: game_tree.proof(&game_leaves[i]).unwrap(),
+                })
+                .collect::<Vec<_>>()
+        };
+        let eligible = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, player: &str| {
+            let msg = QueryMsg::EligibleAmount { address: player.to_string() };
+            let res: EligibleAmountResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res
+        };
+
+        let proofs = register(&mut deps, "100", None);
+        open_claims(&mut deps, 300).unwrap();
+
+        // Only the owner or an operator can cache the proofs of other addresses.
+        let msg = ExecuteMsg::CacheProof { proofs: proofs.clone() };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), msg.clone()).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+        let mut invalid = proofs[1].clone();
+        invalid.amount = Uint128::new(101);
+        let invalid_msg = ExecuteMsg::CacheProof { proofs: vec![invalid] };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), invalid_msg).unwrap_err();
+        assert_eq!(res, ContractError::VerificationFailed { merkle_root: "airdrop".to_string() });
+
+        execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+        assert_eq!(
+            eligible(&deps, "player0000"),
+            EligibleAmountResponse { amount: Some(Uint128::new(100)), winning_bin: Some(3), cached_at: Some(200_000) }
+        );
+        assert_eq!(eligible(&deps, "player0001").winning_bin, None);
+
+        // Replacing the roots invalidates the cached proofs.
+        let proofs = register(&mut deps, "200", Some(true));
+        open_claims(&mut deps, 300).unwrap();
+        let no_proof = EligibleAmountResponse { amount: None, winning_bin: None, cached_at: None };
+        assert_eq!(eligible(&deps, "player0000"), no_proof);
+
+        // Every address can cache its own proofs.
+        let msg = ExecuteMsg::CacheProof { proofs: vec![proofs[0].clone()] };
+        execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), msg).unwrap();
+
+        let mut zero = proofs[1].clone();
+        zero.amount = Uint128::zero();
+        let msg = ExecuteMsg::CacheProof { proofs: vec![zero] };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0001", &[]), msg).unwrap_err();
+        assert_eq!(res, ContractError::InvalidZeroAmount {});
+
+        env.block.height = 203_000;
+        let msg = ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::zero(),
+            proof_airdrop: proofs[1].proof_airdrop.clone(),
+            proof_game: proofs[1].proof_game.clone(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0001", &[]), msg).unwrap_err();
+        assert_eq!(res, ContractError::InvalidZeroAmount {});
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0001", &[]), ExecuteMsg::ClaimCachedAirdrop {}).unwrap_err();
+        assert_eq!(res, ContractError::ProofNotCached {});
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), ExecuteMsg::ClaimCachedAirdrop {}).unwrap();
+        assert_eq!(res.attributes[3], attr("airdrop_amount", "100"));
+        assert!(CLAIM_PRIZE.has(&deps.storage, &Addr::unchecked("player0000")));
+        assert_eq!(
+            res.events,
+            vec![Event::new("winner_registered").add_attributes(vec![
+                attr("address", "player0000"),
+                attr("bin", "3"),
+                attr("winners", "1"),
+            ])]
+        );
+        assert_eq!(eligible(&deps, "player0000"), no_proof);
+
+        // Proofs cannot be cached after the claim.
+        let msg = ExecuteMsg::CacheProof { proofs: vec![proofs[0].clone()] };
+        let res = execute(deps.as_mut(), env, mock_info("player0000", &[]), msg).unwrap_err();
+        assert_eq!(res, ContractError::AlreadyClaimed {});
+    }
+
+    #[test]
+    fn claim_conditions() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        for (player, bin) in [("player0000", 3), ("player0001", 5)] {
+            let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin, pubkey_proof: None, ticket_id: None }).unwrap();
+        }
+
+        let airdrop_leaves = vec![
+            leaf_hash("player0000", "100"),
+            leaf_hash("player0001", "100"),
+            leaf_hash("player0002", "100"),
+        ];
+        let game_leaves = vec![leaf_hash("player0000", "3"), leaf_hash("player0001", "4")];
+        let airdrop_tree = crate::merkle::MerkleTree::new(airdrop_leaves.clone());
+        let game_tree = crate::merkle::MerkleTree::new(game_leaves.clone());
+        let register = |condition| ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: airdrop_tree.root(),
+            total_amount_airdrop: Some(Uint128::new(300)),
+            merkle_root_game: game_tree.root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: Some(true),
+            secondary_payout: None,
+            claim_condition: Some(condition),
+        };
+
+        // Combinators cannot be empty and expressions are bounded.
+        let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), register(ClaimCondition::And(vec![])))
+            .unwrap_err();
+        assert_eq!(res, ContractError::InvalidInput {});
+        let too_large = ClaimCondition::Or(vec![ClaimCondition::MinBidPlaced {}; MAX_CONDITION_SIZE]);
+        let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), register(too_large)).unwrap_err();
+        assert_eq!(res, ContractError::ConditionTooLarge { max: MAX_CONDITION_SIZE });
+
+        // Bidders holding the nft, or anyone holding enough vip tokens.
+        let condition = ClaimCondition::Or(vec![
+            ClaimCondition::And(vec![
+                ClaimCondition::MinBidPlaced {},
+                ClaimCondition::HoldsNft { contract_addr: "nft0000".to_string() },
+            ]),
+            ClaimCondition::HoldsCw20 { contract_addr: "vip0000".to_string(), amount: Uint128::new(50) },
+        ]);
+        execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), register(condition.clone())).unwrap();
+        let res = query_merkle_root(deps.as_ref()).unwrap();
+        assert_eq!(res.claim_condition, Some(condition));
+        open_claims(&mut deps, 300).unwrap();
+
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "nft0000" => {
+                let Cw721QueryMsg::Tokens { owner, .. } = from_binary(msg).unwrap();
+                let tokens = if owner == "player0000" { vec!["1".to_string()] } else { vec![] };
+                SystemResult::Ok(ContractResult::Ok(to_binary(&TokensResponse { tokens }).unwrap()))
+            }
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "vip0000" => {
+                let vip = to_binary(&Cw20QueryMsg::Balance { address: "player0002".to_string() }).unwrap();
+                let balance = Uint128::new(if *msg == vip { 50 } else { 49 });
+                SystemResult::Ok(ContractResult::Ok(to_binary(&BalanceResponse { balance }).unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "wasm".to_string() }),
+        });
+
+        env.block.height = 203_000;
+        let claim = |i: usize| ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::new(100),
+            proof_airdrop: airdrop_tree.proof(&airdrop_leaves[i]).unwrap(),
+            proof_game: game_leaves.get(i).map(|leaf| game_tree.proof(leaf).unwrap()).unwrap_or_default(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0001", &[]), claim(1)).unwrap_err();
+        assert_eq!(res, ContractError::ConditionNotMet {});
+        execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim(0)).unwrap();
+        execute(deps.as_mut(), env, mock_info("player0002", &[]), claim(2)).unwrap();
+    }
+
+    #[cfg(feature = "cosmwasm_1_1")]
+    #[test]
+    fn prize_balance_invariant() {
+        // The contract lost part of the tickets collected.
+        let mut deps = mock_dependencies_with_balance(&[Coin::new(15, TEST_DENOM)]);
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let info = mock_info("owner0000", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        for player in ["player0000", "player0001"] {
+            let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin: 3, pubkey_proof: None, ticket_id: None }).unwrap();
+        }
+
+        let airdrop_leaves = vec![leaf_hash("player0000", "100")];
+        let game_leaves = vec![leaf_hash("player0000", "3")];
+        let airdrop_tree = crate::merkle::MerkleTree::new(airdrop_leaves.clone());
+        let game_tree = crate::merkle::MerkleTree::new(game_leaves.clone());
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: airdrop_tree.root(),
+            total_amount_airdrop: Some(Uint128::new(100)),
+            merkle_root_game: game_tree.root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        open_claims(&mut deps, 100).unwrap();
+
+        env.block.height = 203_000;
+        let msg = ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::new(100),
+            proof_airdrop: airdrop_tree.proof(&airdrop_leaves[0]).unwrap(),
+            proof_game: game_tree.proof(&game_leaves[0]).unwrap(),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), msg).unwrap();
+
+        env.block.height = 206_000;
+        let msg = ExecuteMsg::ClaimPrize { amount: None, proof: None, then: None };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), msg).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::PrizeBalanceTooLow { balance: Uint128::new(15), required: Uint128::new(20) }
+        );
+
+        env.block.height = 206_002;
+        let msg = ExecuteMsg::WithdrawPrize { address: Addr::unchecked("owner0000") };
+        let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::PrizeBalanceTooLow { balance: Uint128::new(15), required: Uint128::new(20) }
+        );
+    }
+
+    #[test]
+    fn register_merkle_roots_overwrite() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let info = mock_info("owner0000", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let leaves = vec![leaf_hash("player0000", "100"), leaf_hash("player0001", "100")];
+        let tree = crate::merkle::MerkleTree::new(leaves.clone());
+        let register_msg = |total_amount_airdrop: u128, overwrite: Option<bool>| ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: tree.root(),
+            total_amount_airdrop: Some(Uint128::new(total_amount_airdrop)),
+            merkle_root_game: tree.root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), register_msg(200, None)).unwrap();
+
+        // A second submission does not replace the roots by accident.
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), register_msg(300, None)).unwrap_err();
+        assert_eq!(res, ContractError::MerkleRootAlreadyRegistered {});
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), register_msg(300, Some(false))).unwrap_err();
+        assert_eq!(res, ContractError::MerkleRootAlreadyRegistered {});
+
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), register_msg(200, Some(true))).unwrap();
+        assert_eq!(res.attributes.last().unwrap(), &attr("overwrite", "true"));
+
+        // Once claimed, the roots cannot be replaced.
+        open_claims(&mut deps, 200).unwrap();
+        let mut env = mock_env();
+        env.block.height = 203_000;
+        let msg = ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::new(100),
+            proof_airdrop: tree.proof(&leaves[0]).unwrap(),
+            proof_game: vec![],
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), msg).unwrap();
+        let res = execute(deps.as_mut(), env, info, register_msg(300, Some(true))).unwrap_err();
+        assert_eq!(res, ContractError::ClaimsAlreadyStarted {});
+    }
+
+    #[test]
+    fn relative_stages() {
+        let mut deps = mock_dependencies();
+
+        // Absolute stages keep their format.
+        let stage: StageMsg = from_slice(br#"{"start":{"at_height":200000},"duration":{"height":2}}"#).unwrap();
+        assert_eq!(stage, StageMsg::Absolute(valid_stages().0));
+        let stage: StageMsg = from_slice(br#"{"start_after":{"time":60},"duration":{"time":30}}"#).unwrap();
+        assert_eq!(
+            stage,
+            StageMsg::Relative(RelativeStage { start_after: Duration::Time(60), duration: Duration::Time(30) })
+        );
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: StageMsg::Relative(RelativeStage {
+                start_after: Duration::Height(10),
+                duration: Duration::Height(100),
+            }),
+            stage_claim_airdrop: StageMsg::Relative(RelativeStage {
+                start_after: Duration::Height(110),
+                duration: Duration::Height(100),
+            }),
+            stage_claim_prize: Stage {
+                start: Scheduled::AtHeight(300_000),
+                duration: Duration::Height(100),
+            }
+            .into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        // Offsets are resolved from the instantiation block.
+        let env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Stages {}).unwrap();
+        let stages: StagesResponse = from_binary(&res).unwrap();
+        assert_eq!(stages.stage_bid.start, Scheduled::AtHeight(env.block.height + 10));
+        assert_eq!(stages.stage_claim_airdrop.start, Scheduled::AtHeight(env.block.height + 110));
+        assert_eq!(stages.stage_claim_prize.start, Scheduled::AtHeight(300_000));
+        assert_eq!(stages.stage_bid_end, Scheduled::AtHeight(env.block.height + 110));
+        assert_eq!(stages.stage_claim_airdrop_end, Scheduled::AtHeight(env.block.height + 210));
+        assert_eq!(stages.stage_claim_prize_end, Scheduled::AtHeight(300_100));
+    }
+
+    #[test]
+    fn freeze_claims() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let owner = mock_info("owner0000", &[]);
+        instantiate(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+        let msg = ExecuteMsg::AddOperator { address: "operator0000".to_string() };
+        execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+
+        let leaves = vec![leaf_hash("player0000", "100"), leaf_hash("player0001", "100")];
+        let tree = crate::merkle::MerkleTree::new(leaves.clone());
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: tree.root(),
+            total_amount_airdrop: Some(Uint128::new(200)),
+            merkle_root_game: tree.root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+        open_claims(&mut deps, 200).unwrap();
+
+        // Only the owner or an operator can freeze the claims.
+        let freeze_msg = ExecuteMsg::FreezeClaims { reason: "root under review".to_string() };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("player0000", &[]), freeze_msg.clone()).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("operator0000", &[]), freeze_msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::ClaimsFreeze {}).unwrap();
+        let freeze: ClaimsFreezeResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            freeze,
+            ClaimsFreezeResponse { frozen: true, reason: Some("root under review".to_string()), prize_veto: None }
+        );
+
+        // Bids are still accepted.
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let info = mock_info("player0001", &[Coin::new(10, TEST_DENOM)]);
+        execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: None }).unwrap();
+
+        env.block.height = 203_000;
+        let claim_msg = ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::new(100),
+            proof_airdrop: tree.proof(&leaves[0]).unwrap(),
+            proof_game: vec![],
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim_msg.clone()).unwrap_err();
+        assert_eq!(res, ContractError::ClaimsFrozen { reason: "root under review".to_string() });
+
+        // Only the owner can unfreeze them.
+        let res = execute(deps.as_mut(), env.clone(), mock_info("operator0000", &[]), ExecuteMsg::UnfreezeClaims {}).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+        execute(deps.as_mut(), env.clone(), owner, ExecuteMsg::UnfreezeClaims {}).unwrap();
+        execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim_msg).unwrap();
+
+        let res = query(deps.as_ref(), env, QueryMsg::ClaimsFreeze {}).unwrap();
+        let freeze: ClaimsFreezeResponse = from_binary(&res).unwrap();
+        assert_eq!(freeze, ClaimsFreezeResponse { frozen: false, reason: None, prize_veto: None });
+    }
+
+    #[test]
+    fn input_bounds() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: Some(MAX_PROOF_NODES + 1),
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        // The proof length can only be lowered.
+        let info = mock_info("owner0000", &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        assert_eq!(res, ContractError::InvalidMaxProofLength { max: MAX_PROOF_NODES });
+
+        let proxies = (0..=MAX_ADDRESSES_PER_CALL).map(|i| format!("proxy{:04}", i)).collect();
+        let res = instantiate(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            InstantiateMsg { approved_proxies: Some(proxies), max_proof_length: Some(2), ..msg.clone() },
+        )
+        .unwrap_err();
+        assert_eq!(res, ContractError::TooManyAddresses { max: MAX_ADDRESSES_PER_CALL });
+
+        let msg = InstantiateMsg { max_proof_length: Some(2), ..msg };
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // Proofs longer than the configured length are rejected before any check.
+        let proof = vec!["00".repeat(32); 3];
+        let msg = ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::new(100),
+            proof_airdrop: vec![],
+            proof_game: proof.clone(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("player0000", &[]), msg).unwrap_err();
+        assert_eq!(res, ContractError::ProofTooLong { max: 2 });
+
+        let msg = ExecuteMsg::ClaimPrize { amount: Some(Uint128::new(10)), proof: Some(proof), then: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("player0000", &[]), msg).unwrap_err();
+        assert_eq!(res, ContractError::ProofTooLong { max: 2 });
+
+        let msg = ExecuteMsg::ImportBids {
+            source_contract: "game0000".to_string(),
+            limit: Some(MAX_BATCH_SIZE + 1),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(res, ContractError::BatchTooLarge { max: MAX_BATCH_SIZE });
+
+        let msg = ExecuteMsg::UpdateMetadata {
+            metadata: GameMetadata {
+                description: Some("a".repeat(MAX_MEMO_LENGTH + 1)),
+                ..GameMetadata::default()
+            },
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::MemoTooLong { field: "description".to_string(), max: MAX_MEMO_LENGTH }
+        );
+    }
+
+    #[test]
+    fn overflow_errors() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+        // Ticket prize overflow.
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        set_counter(deps.as_mut().storage, |counters| counters.total_ticket_prize = Uint128::MAX);
+        let info = mock_info("player0000", &[Coin::new(10, TEST_DENOM)]);
+        let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin: 3, pubkey_proof: None, ticket_id: None }).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::Overflow(OverflowError::new(OverflowOperation::Add, Uint128::MAX, 10u128))
+        );
+
+        // Mock storage is not reverted on errors: the bid has been saved anyway.
+        set_counter(deps.as_mut().storage, |counters| counters.total_ticket_prize = Uint128::new(10));
+
+        let airdrop_leaves = vec![leaf_hash("player0000", "100"), leaf_hash("player0001", "100")];
+        let game_leaves = vec![leaf_hash("player0000", "3"), leaf_hash("player0001", "4")];
+        let airdrop_tree = crate::merkle::MerkleTree::new(airdrop_leaves.clone());
+        let game_tree = crate::merkle::MerkleTree::new(game_leaves.clone());
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: airdrop_tree.root(),
+            total_amount_airdrop: Some(Uint128::new(200)),
+            merkle_root_game: game_tree.root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        open_claims(&mut deps, 200).unwrap();
+
+        // Winners and claimed airdrop overflow.
+        env.block.height = 203_000;
+        let info = mock_info("player0000", &[]);
+        let msg = ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::new(100),
+            proof_airdrop: airdrop_tree.proof(&airdrop_leaves[0]).unwrap(),
+            proof_game: game_tree.proof(&game_leaves[0]).unwrap(),
+        };
+        set_counter(deps.as_mut().storage, |counters| counters.winners = Uint128::MAX);
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::Overflow(OverflowError::new(OverflowOperation::Add, Uint128::MAX, 1u128))
+        );
+
+        // The claimed airdrop cannot reach the overflow without exceeding its total first,
+        // which triggers the failsafe.
+        set_counter(deps.as_mut().storage, |counters| counters.winners = Uint128::zero());
+        set_counter(deps.as_mut().storage, |counters| counters.claimed_airdrop_amount = Uint128::MAX);
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(res.attributes[1], attr(ATTR_CONDITION, INVARIANT_AIRDROP_CLAIMED));
+        set_counter(deps.as_mut().storage, |counters| counters.claimed_airdrop_amount = Uint128::zero());
+        FAILSAFE.remove(deps.as_mut().storage);
+
+        // Claimed prize higher than the prize pool.
+        env.block.height = 206_002;
+        set_counter(deps.as_mut().storage, |counters| counters.claimed_prize_amount = Uint128::new(11));
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::WithdrawPrize { address: Addr::unchecked("owner0000") };
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(res.attributes[1], attr(ATTR_CONDITION, INVARIANT_PRIZE_CLAIMED));
+    }
+
+    #[test]
+    fn operators() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
 
-    use super::*;
-    use cosmwasm_std::from_binary;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cw_utils::{Duration, Scheduled};
+        let env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
 
-    fn valid_stages() -> (Stage, Stage, Stage) {
-        let stage_bid = Stage {
-            start: Scheduled::AtHeight(200_000),
-            duration: Duration::Height(2),
+        let register_msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: "b45c1ea28b26adb13e412933c9e055b01fdf7585304b00cd8f1cb220aa6c5e88".to_string(),
+            total_amount_airdrop: None,
+            merkle_root_game: "14b47be0716eebb3b9e16fb2d06dc3376dd2534705d9a9d38f6fbcc6f4f1c7d2".to_string(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
         };
 
-        let stage_claim_airdrop = Stage {
-            start: Scheduled::AtHeight(203_000),
-            duration: Duration::Height(2),
+        // Only the owner can manage the operators.
+        let env = mock_env();
+        let info = mock_info("operator0000", &[]);
+        let msg = ExecuteMsg::AddOperator { address: "operator0000".to_string() };
+        let res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+
+        let info = mock_info("owner0000", &[]);
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Operators { start_after: None, limit: None },
+        )
+        .unwrap();
+        let operators: OperatorsResponse = from_binary(&res).unwrap();
+        assert_eq!(operators.operators, vec!["operator0000".to_string()]);
+
+        // Operators can register the roots but cannot withdraw.
+        let info = mock_info("operator0000", &[]);
+        execute(deps.as_mut(), env.clone(), info.clone(), register_msg.clone()).unwrap();
+        let msg = ExecuteMsg::WithdrawPrize { address: Addr::unchecked("operator0000") };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+
+        // Removed operators cannot register the roots anymore.
+        let owner_info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::RemoveOperator { address: "operator0000".to_string() };
+        execute(deps.as_mut(), env.clone(), owner_info, msg).unwrap();
+        let res = execute(deps.as_mut(), env, info, register_msg).unwrap_err();
+        assert_eq!(res, ContractError::Unauthorized {});
+    }
+
+    #[test]
+    fn governance_mode() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: Some(GovernanceMode::Multisig { address: Addr::unchecked("multisig0000") }),
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
         };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
 
-        let stage_claim_prize = Stage {
-            start: Scheduled::AtHeight(206_000),
-            duration: Duration::Height(2),
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+        let config: ConfigResponse = from_binary(&res).unwrap();
+        assert_eq!(config.governance_mode, GovernanceMode::Multisig { address: Addr::unchecked("multisig0000") });
+
+        let msg = ExecuteMsg::AddOperator { address: "operator0000".to_string() };
+        execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let register_msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: "b45c1ea28b26adb13e412933c9e055b01fdf7585304b00cd8f1cb220aa6c5e88".to_string(),
+            total_amount_airdrop: None,
+            merkle_root_game: "14b47be0716eebb3b9e16fb2d06dc3376dd2534705d9a9d38f6fbcc6f4f1c7d2".to_string(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
         };
 
-        return (stage_bid, stage_claim_airdrop, stage_claim_prize);
+        // Neither the owner nor the operators can register the roots.
+        for sender in ["owner0000", "operator0000", "random0000"] {
+            let res = execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), register_msg.clone()).unwrap_err();
+            assert_eq!(res, ContractError::GovernanceRequired {});
+        }
+
+        // The multisig executes the passed proposal.
+        let res = execute(deps.as_mut(), mock_env(), mock_info("multisig0000", &[]), register_msg).unwrap();
+        assert_eq!(res.attributes[0], attr(ATTR_ACTION, "register_merkle_roots"));
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::MerkleRoots {}).unwrap();
+        let roots: MerkleRootsResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            roots.merkle_root_airdrop,
+            Some("b45c1ea28b26adb13e412933c9e055b01fdf7585304b00cd8f1cb220aa6c5e88".to_string())
+        );
     }
+
     #[test]
-    fn proper_instantiation() {
-        let mut deps = mock_dependencies();
+    fn pubkey_bids() {
+        use k256::ecdsa::signature::Signer;
+        use k256::ecdsa::{Signature, SigningKey};
+
+        let mut deps = account_dependencies();
 
         let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
 
         let msg = InstantiateMsg {
             owner: Some("owner0000".to_string()),
             cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
             ticket_price: Coin {
-                denom: "ujuno".into(),
+                denom: TEST_DENOM.into(),
                 amount: Uint128::new(10)
             },
             bins: 10,
-            stage_bid: stage_bid,
-            stage_claim_airdrop: stage_claim_airdrop,
-            stage_claim_prize: stage_claim_prize,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: Some(true),
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
         };
 
         let env = mock_env();
-        let info = mock_info("addr0000", &[]);
+        let info = mock_info("owner0000", &[]);
+        let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
 
-        // we can just call .unwrap() to assert this was a success
-        let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 200_000;
 
-        // it worked, let's query the state
-        let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
-        let config: ConfigResponse = from_binary(&res).unwrap();
-        assert_eq!("owner0000", config.owner.unwrap().as_str());
-        assert_eq!("random0000", config.cw20_token_address.as_str());
+        let signing_key = SigningKey::from_bytes(&[1u8; 32]).unwrap();
+        let other_key = SigningKey::from_bytes(&[2u8; 32]).unwrap();
+        let player = account_address(&signing_key);
+        let other_player = account_address(&other_key);
+        let proof_for = |key: &SigningKey, bidder: &str| {
+            let signature: Signature = key.sign(format!("{}{}", env.contract.address, bidder).as_bytes());
+            PubkeyProof {
+                pubkey: Binary::from(key.verifying_key().to_bytes().as_slice()),
+                signature: Binary::from(signature.as_ref()),
+            }
+        };
 
-        let res = query(deps.as_ref(), env, QueryMsg::Stages {}).unwrap();
-        let stages_info: StagesResponse = from_binary(&res).unwrap();
-        assert_eq!(Scheduled::AtHeight(200_000), stages_info.stage_bid.start);
+        // The proof is mandatory.
+        let info = mock_info(&player, &[Coin::new(10, TEST_DENOM)]);
+        let msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: None };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(res, ContractError::PubkeyProofRequired {});
+
+        // A valid signature of a key which is not the one of the sender is rejected.
+        let msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: Some(proof_for(&other_key, &player)), ticket_id: None };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(res, ContractError::PubkeyNotSender {});
+        let spare = mock_info("player0000", &[Coin::new(10, TEST_DENOM)]);
+        let msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: Some(proof_for(&other_key, "player0000")), ticket_id: None };
+        let res = execute(deps.as_mut(), env.clone(), spare, msg).unwrap_err();
+        assert_eq!(res, ContractError::PubkeyNotSender {});
+
+        // The signature must be the one of the sender address.
+        let msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: Some(proof_for(&signing_key, &other_player)), ticket_id: None };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(res, ContractError::InvalidSignature {});
+
+        let msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: Some(proof_for(&signing_key, &player)), ticket_id: None };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // A signature cannot be reused by another address.
+        let info = mock_info(&other_player, &[Coin::new(10, TEST_DENOM)]);
+        let msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: Some(proof_for(&signing_key, &player)), ticket_id: None };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(res, ContractError::PubkeyNotSender {});
+
+        // The key of the account signing for itself is accepted.
+        let msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: Some(proof_for(&other_key, &other_player)), ticket_id: None };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
     }
 
     #[test]
-    fn update_config() {
+    fn imported_bids_refund() {
         let mut deps = mock_dependencies();
 
         let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
@@ -811,41 +11057,836 @@ mod tests {
         let msg = InstantiateMsg {
             owner: Some("owner0000".to_string()),
             cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
             ticket_price: Coin {
-                denom: "ujuno".into(),
+                denom: TEST_DENOM.into(),
                 amount: Uint128::new(10)
             },
             bins: 10,
-            stage_bid: stage_bid,
-            stage_claim_airdrop: stage_claim_airdrop,
-            stage_claim_prize: stage_claim_prize,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        // The source game has a bid of player0000, which can be removed there until its
+        // bid stage ends at the height `source_end`.
+        let source = |source_end: u64| {
+            move |query: &WasmQuery| {
+                let stage = |start| Stage { start: Scheduled::AtHeight(start), duration: Duration::Height(1) };
+                let res = match query {
+                    WasmQuery::Smart { msg, .. } => match from_binary(msg).unwrap() {
+                        QueryMsg::Stages {} => to_binary(&StagesResponse {
+                            stage_bid: stage(source_end - 1),
+                            stage_claim_airdrop: stage(source_end),
+                            stage_claim_prize: stage(source_end + 1),
+                            stage_bid_end: Scheduled::AtHeight(source_end),
+                            stage_claim_airdrop_end: Scheduled::AtHeight(source_end + 1),
+                            stage_claim_prize_end: Scheduled::AtHeight(source_end + 2),
+                        }),
+                        QueryMsg::AllBids { .. } => to_binary(&AllBidsResponse {
+                            bids: vec![BidInfo { address: "player0000".to_string(), bin: 3 }],
+                        }),
+                        _ => panic!("unexpected query"),
+                    },
+                    _ => panic!("unexpected query"),
+                };
+                SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+            }
         };
+        let import = ExecuteMsg::ImportBids { source_contract: "game0000".to_string(), limit: None };
+        let owner = mock_info("owner0000", &[Coin::new(10, TEST_DENOM)]);
 
+        deps.querier.update_wasm(source(100_000));
         let env = mock_env();
-        let info = mock_info("owner0000", &[]);
-        let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), owner.clone(), import.clone()).unwrap_err();
+        assert_eq!(res, ContractError::SourceBidStageActive {});
+
+        deps.querier.update_wasm(source(env.block.height));
+        execute(deps.as_mut(), env.clone(), owner, import).unwrap();
+        assert_eq!(BID_RECEIPTS.load(deps.as_ref().storage, &Addr::unchecked("player0000")).unwrap().paid, None);
+
+        // The player was refunded by the source: the ticket paid by the owner stays in the
+        // prize.
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        let res = execute(deps.as_mut(), env, mock_info("player0000", &[]), ExecuteMsg::RemoveBid {}).unwrap();
+        assert!(res.messages.is_empty());
+        assert!(!BIDS.has(deps.as_ref().storage, &Addr::unchecked("player0000")));
+        assert_eq!(COUNTERS.load(deps.as_ref().storage).unwrap().total_ticket_prize, Uint128::new(10));
+    }
+
+    #[test]
+    fn claim_rate_limit() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, mut stage_claim_airdrop, stage_claim_prize) = valid_stages();
+        stage_claim_airdrop.duration = Duration::Height(100);
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: Some(ClaimRateLimit {
+                window: 10,
+                max_claims: Some(2),
+                max_amount: Some(Uint128::new(250)),
+            }),
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
 
-        // Update owner
         let env = mock_env();
         let info = mock_info("owner0000", &[]);
-        let msg = ExecuteMsg::UpdateConfig {
-            new_owner: Some("owner0001".to_string()),
+        instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+        let players = [
+            ("player0000", "100"),
+            ("player0001", "100"),
+            ("player0002", "100"),
+            ("player0003", "300"),
+        ];
+        let leaves: Vec<_> = players.iter().map(|(address, amount)| leaf_hash(address, amount)).collect();
+        let tree = crate::merkle::MerkleTree::new(leaves.clone());
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: tree.root(),
+            total_amount_airdrop: Some(Uint128::new(600)),
+            merkle_root_game: tree.root(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
         };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        open_claims(&mut deps, 600).unwrap();
 
-        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+        let claim = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, height: u64, player: usize| {
+            let mut env = mock_env();
+            env.block.height = height;
+            let (address, amount) = players[player];
+            let msg = ExecuteMsg::ClaimAirdrop {
+                amount: Uint128::new(amount.parse().unwrap()),
+                proof_airdrop: tree.proof(&leaves[player]).unwrap(),
+                proof_game: vec![],
+            };
+            execute(deps.as_mut(), env, mock_info(address, &[]), msg)
+        };
 
-        // it worked, let's query the state
-        let res = query(deps.as_ref(), env, QueryMsg::Config {}).unwrap();
-        let config: ConfigResponse = from_binary(&res).unwrap();
-        assert_eq!("owner0001", config.owner.unwrap().as_str());
+        claim(&mut deps, 203_000, 0).unwrap();
+        claim(&mut deps, 203_000, 1).unwrap();
+
+        // Two claims are already in the window: retry once they leave it.
+        let res = claim(&mut deps, 203_003, 2).unwrap_err();
+        assert_eq!(res, ContractError::RetryLater { next_height: 203_010 });
+
+        claim(&mut deps, 203_010, 2).unwrap();
+
+        // The amount would exceed the cap.
+        let res = claim(&mut deps, 203_015, 3).unwrap_err();
+        assert_eq!(res, ContractError::RetryLater { next_height: 203_020 });
+
+        // A claim bigger than the cap is accepted in an empty window.
+        claim(&mut deps, 203_020, 3).unwrap();
+
+        // Only the blocks still in the window are kept.
+        let usages = CLAIM_USAGE
+            .prefix("claim_airdrop")
+            .range(&deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(usages, vec![(203_020, ClaimUsage { claims: 1, amount: Uint128::new(300) })]);
+    }
+
+    #[test]
+    fn commit_reveal_outcome() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
 
-        // Unauthorized err
-        let env = mock_env();
         let info = mock_info("owner0000", &[]);
-        let msg = ExecuteMsg::UpdateConfig { new_owner: None };
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        let merkle_root_game = "b45c1ea28b26adb13e412933c9e055b01fdf7585304b00cd8f1cb220aa6c5e88".to_string();
+        let salt = "salt".to_string();
+        let commitment = hex::encode(Sha256::digest(format!("{}{}", merkle_root_game, salt).as_bytes()));
+        let commit_msg = ExecuteMsg::CommitOutcome { commitment: commitment.clone() };
+
+        let info = mock_info("random0000", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, commit_msg.clone()).unwrap_err();
         assert_eq!(res, ContractError::Unauthorized {});
+
+        // Too late once the bid stage is over.
+        let mut env = mock_env();
+        env.block.height = 200_002;
+        let info = mock_info("owner0000", &[]);
+        let res = execute(deps.as_mut(), env, info, commit_msg.clone()).unwrap_err();
+        assert_eq!(res, ContractError::StageEnded { stage_name: "bid".to_string() });
+
+        let mut env = mock_env();
+        env.block.height = 200_001;
+        let info = mock_info("owner0000", &[]);
+        execute(deps.as_mut(), env.clone(), info.clone(), commit_msg.clone()).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), info, commit_msg).unwrap_err();
+        assert_eq!(res, ContractError::OutcomeAlreadyCommitted {});
+
+        // The outcome cannot be revealed while bidding.
+        let reveal_msg = ExecuteMsg::RevealOutcome {
+            merkle_root_game: merkle_root_game.clone(),
+            salt: salt.clone(),
+        };
+        let info = mock_info("random0000", &[]);
+        let res = execute(deps.as_mut(), env.clone(), info, reveal_msg.clone()).unwrap_err();
+        assert_eq!(res, ContractError::BidStageNotFinished {});
+
+        // Roots cannot be registered before the reveal.
+        env.block.height = 200_002;
+        let register_msg = |merkle_root_game: &str| ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: merkle_root_game.to_string(),
+            total_amount_airdrop: None,
+            merkle_root_game: merkle_root_game.to_string(),
+            total_amount_game: None,
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        let info = mock_info("owner0000", &[]);
+        let res = execute(deps.as_mut(), env.clone(), info, register_msg(&merkle_root_game)).unwrap_err();
+        assert_eq!(res, ContractError::OutcomeNotRevealed {});
+
+        let info = mock_info("random0000", &[]);
+        let msg = ExecuteMsg::RevealOutcome {
+            merkle_root_game: merkle_root_game.clone(),
+            salt: "wrong".to_string(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(res, ContractError::CommitmentMismatch {});
+        execute(deps.as_mut(), env.clone(), info, reveal_msg).unwrap();
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Outcome {}).unwrap();
+        let outcome: OutcomeResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            outcome,
+            OutcomeResponse {
+                commitment: Some(commitment),
+                merkle_root_game: Some(merkle_root_game.clone()),
+            }
+        );
+
+        // Only the revealed game root can be registered.
+        let other_root = "a45c1ea28b26adb13e412933c9e055b01fdf7585304b00cd8f1cb220aa6c5e88";
+        let info = mock_info("owner0000", &[]);
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), register_msg(other_root)).unwrap_err();
+        assert_eq!(res, ContractError::CommitmentMismatch {});
+        execute(deps.as_mut(), env, info, register_msg(&merkle_root_game)).unwrap();
+    }
+
+    #[test]
+    fn open_claims_preflight() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let mut msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let info = mock_info("owner0000", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+
+        let leaves = vec![leaf_hash("player0000", "100"), leaf_hash("player0001", "100")];
+        let tree = crate::merkle::MerkleTree::new(leaves.clone());
+        let register_msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: tree.root(),
+            total_amount_airdrop: Some(Uint128::new(200)),
+            merkle_root_game: tree.root(),
+            total_amount_game: Some(Uint128::new(100)),
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), register_msg).unwrap();
+
+        // Claims are refused until the funds are confirmed.
+        let mut env = mock_env();
+        env.block.height = 203_000;
+        let claim_msg = ExecuteMsg::ClaimAirdrop {
+            amount: Uint128::new(100),
+            proof_airdrop: tree.proof(&leaves[0]).unwrap(),
+            proof_game: vec![],
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim_msg.clone()).unwrap_err();
+        assert_eq!(res, ContractError::ClaimsNotFunded {});
+
+        // Only the tokens held by the game contract count.
+        mock_cw20(&mut deps, "random0000", &[("owner0000", 1_000), (MOCK_CONTRACT_ADDR, 100)]);
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), ExecuteMsg::OpenClaims {});
+        assert_eq!(
+            res.unwrap_err(),
+            ContractError::AirdropUnderfunded { balance: Uint128::new(100), required: Uint128::new(300) }
+        );
+
+        // Both the airdrop and the game airdrop totals must be held.
+        let res = open_claims(&mut deps, 250).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::AirdropUnderfunded { balance: Uint128::new(250), required: Uint128::new(300) }
+        );
+        open_claims(&mut deps, 300).unwrap();
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::GameAmounts {}).unwrap();
+        let amounts: GameAmountsResponse = from_binary(&res).unwrap();
+        assert!(amounts.claims_funded);
+
+        // New totals must be funded again.
+        let overwrite_msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: tree.root(),
+            total_amount_airdrop: Some(Uint128::new(200)),
+            merkle_root_game: tree.root(),
+            total_amount_game: Some(Uint128::new(100)),
+            tree_metadata: tree_metadata(),
+            overwrite: Some(true),
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), overwrite_msg).unwrap();
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::GameAmounts {}).unwrap();
+        let amounts: GameAmountsResponse = from_binary(&res).unwrap();
+        assert!(!amounts.claims_funded);
+        open_claims(&mut deps, 300).unwrap();
+        execute(deps.as_mut(), env.clone(), mock_info("player0000", &[]), claim_msg).unwrap();
+
+        // Claims cannot be opened once the claim airdrop stage is over.
+        env.block.height = 203_002;
+        let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), ExecuteMsg::OpenClaims {}).unwrap_err();
+        assert_eq!(res, ContractError::StageEnded { stage_name: "claim airdrop".to_string() });
+
+        // Native airdrops are checked against the deposits instead.
+        let mut deps = mock_dependencies();
+        msg.airdrop_native_denom = Some("ibc/token".to_string());
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let res = open_claims(&mut deps, 300).unwrap_err();
+        assert_eq!(res, ContractError::OpenClaimsNotRequired {});
+    }
+
+    #[test]
+    fn tree_metadata_verification() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin {
+                denom: TEST_DENOM.into(),
+                amount: Uint128::new(10)
+            },
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+
+        let info = mock_info("owner0000", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let merkle_root_airdrop = "b45c1ea28b26adb13e412933c9e055b01fdf7585304b00cd8f1cb220aa6c5e88".to_string();
+        let merkle_root_game = "14b47be0716eebb3b9e16fb2d06dc3376dd2534705d9a9d38f6fbcc6f4f1c7d2".to_string();
+
+        // The checksum must be a hex-encoded sha256.
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: merkle_root_airdrop.clone(),
+            total_amount_airdrop: None,
+            merkle_root_game: merkle_root_game.clone(),
+            total_amount_game: None,
+            tree_metadata: TreeMetadata {
+                allocation_checksum: "9f86d081".to_string(),
+                ..tree_metadata()
+            },
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(res, ContractError::Hex(FromHexError::InvalidStringLength));
+
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: merkle_root_airdrop.clone(),
+            total_amount_airdrop: None,
+            merkle_root_game: merkle_root_game.clone(),
+            total_amount_game: None,
+            tree_metadata: TreeMetadata {
+                allocation_checksum: tree_metadata().allocation_checksum.to_uppercase(),
+                ..tree_metadata()
+            },
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::MerkleRoots {}).unwrap();
+        let roots: MerkleRootsResponse = from_binary(&res).unwrap();
+        assert_eq!(roots.tree_metadata, Some(tree_metadata()));
+
+        let verify = |merkle_root_game: String, tree_metadata: TreeMetadata| {
+            let msg = QueryMsg::VerifyTreeMetadata {
+                merkle_root_airdrop: merkle_root_airdrop.clone(),
+                merkle_root_game,
+                tree_metadata,
+            };
+            let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+            from_binary::<VerifyTreeMetadataResponse>(&res).unwrap()
+        };
+
+        assert_eq!(
+            verify(merkle_root_game.clone(), tree_metadata()),
+            VerifyTreeMetadataResponse { valid: true, mismatches: vec![] }
+        );
+        assert_eq!(
+            verify(
+                merkle_root_airdrop.clone(),
+                TreeMetadata { game_leaf_count: 4, ..tree_metadata() }
+            ),
+            VerifyTreeMetadataResponse {
+                valid: false,
+                mismatches: vec!["merkle_root_game".to_string(), "game_leaf_count".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn migrate_packs_game_params() {
+        let mut deps = mock_dependencies();
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.12.0").unwrap();
+
+        // Parameters stored by the previous versions under a key each, the bins under
+        // the key of the bids.
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+        let ticket_price = Coin::new(10, TEST_DENOM);
+        let legacy = [
+            (STAGE_BID_KEY, to_vec(&stage_bid).unwrap()),
+            (STAGE_CLAIM_AIRDROP_KEY, to_vec(&stage_claim_airdrop).unwrap()),
+            (STAGE_CLAIM_PRIZE_KEY, to_vec(&stage_claim_prize).unwrap()),
+            (TICKET_PRICE_KEY, to_vec(&ticket_price).unwrap()),
+            (LEGACY_BINS_KEY, to_vec(&7u8).unwrap()),
+        ];
+        for (key, value) in &legacy {
+            deps.storage.set(key.as_bytes(), value);
+        }
+        BIDS.save(&mut deps.storage, &Addr::unchecked("player0000"), &3).unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        let params = GameParams { stage_bid, stage_claim_airdrop, stage_claim_prize, ticket_price, bins: 7 };
+        assert_eq!(GAME_PARAMS.load(&deps.storage).unwrap(), params);
+        for key in [STAGE_BID_KEY, STAGE_CLAIM_AIRDROP_KEY, STAGE_CLAIM_PRIZE_KEY, TICKET_PRICE_KEY, BINS_KEY] {
+            assert_eq!(deps.storage.get(key.as_bytes()), None);
+        }
+        assert_eq!(deps.storage.get(LEGACY_BINS_KEY.as_bytes()), None);
+        assert_eq!(BIDS.load(&deps.storage, &Addr::unchecked("player0000")).unwrap(), 3);
+        assert_eq!(BIN_BIDS.load(&deps.storage, 3).unwrap(), 1);
+
+        // Migrating again keeps the packed parameters and the bids counted once.
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(GAME_PARAMS.load(&deps.storage).unwrap(), params);
+        assert_eq!(BIN_BIDS.load(&deps.storage, 3).unwrap(), 1);
+    }
+
+    /// Storage counting the reads of every key, the writes and the iterations, standing
+    /// for the gas metering of the chain.
+    #[derive(Default)]
+    struct CountingStorage {
+        storage: MockStorage,
+        reads: RefCell<HashMap<Vec<u8>, u32>>,
+        writes: u32,
+        ranges: Cell<u32>,
+    }
+
+    impl CountingStorage {
+        fn reads(&self) -> u32 {
+            self.reads.borrow().values().sum()
+        }
+
+        fn reads_of(&self, key: &str) -> u32 {
+            self.reads.borrow().get(key.as_bytes()).copied().unwrap_or_default()
+        }
+
+        fn reset(&mut self) {
+            self.reads.borrow_mut().clear();
+            self.writes = 0;
+            self.ranges.set(0);
+        }
+    }
+
+    impl Storage for CountingStorage {
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            *self.reads.borrow_mut().entry(key.to_vec()).or_default() += 1;
+            self.storage.get(key)
+        }
+
+        fn range<'a>(
+            &'a self,
+            start: Option<&[u8]>,
+            end: Option<&[u8]>,
+            order: Order,
+        ) -> Box<dyn Iterator<Item = Record> + 'a> {
+            self.ranges.set(self.ranges.get() + 1);
+            self.storage.range(start, end, order)
+        }
+
+        fn set(&mut self, key: &[u8], value: &[u8]) {
+            self.writes += 1;
+            self.storage.set(key, value)
+        }
+
+        fn remove(&mut self, key: &[u8]) {
+            self.writes += 1;
+            self.storage.remove(key)
+        }
+    }
+
+    /// Moves the state of a game set up with the mocks to a storage counting the accesses.
+    fn counting(
+        deps: OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    ) -> OwnedDeps<CountingStorage, MockApi, MockQuerier> {
+        OwnedDeps {
+            storage: CountingStorage { storage: deps.storage, ..CountingStorage::default() },
+            api: deps.api,
+            querier: deps.querier,
+            custom_query_type: PhantomData,
+        }
+    }
+
+    #[test]
+    fn bid_storage_operations() {
+        let mut deps = mock_dependencies();
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin::new(10, TEST_DENOM),
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+        let mut deps = counting(deps);
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        for player in ["player0000", "player0001"] {
+            deps.storage.reset();
+            let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+            let msg = ExecuteMsg::Bid { bin: 3, pubkey_proof: None, ticket_id: None };
+            execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+            // The dispatch reads the closed snapshot and the game parameters, the bid its
+            // config, its previous bid, the bids of its bin and the counters, and iterates
+            // over nothing.
+            let counts = (deps.storage.reads(), deps.storage.writes, deps.storage.ranges.get());
+            assert_eq!(counts, (6, 4, 0));
+            assert_eq!(deps.storage.reads_of(GAME_PARAMS_KEY), 1);
+        }
+    }
+
+    #[test]
+    fn claim_storage_reads() {
+        let mut deps = mock_dependencies_with_balance(&[Coin::new(20, TEST_DENOM)]);
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            cw20_token_address: "random0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin::new(10, TEST_DENOM),
+            bins: 10,
+            stage_bid: stage_bid.into(),
+            stage_claim_airdrop: stage_claim_airdrop.into(),
+            stage_claim_prize: stage_claim_prize.into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 200_000;
+        for (player, bin) in [("player0000", 3), ("player0001", 4)] {
+            let info = mock_info(player, &[Coin::new(10, TEST_DENOM)]);
+            execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Bid { bin, pubkey_proof: None, ticket_id: None }).unwrap();
+        }
+
+        let leaves = [("player0000", 3), ("player0001", 4)];
+        let airdrop = crate::merkle::MerkleTree::new(
+            leaves.iter().map(|(player, _)| compute_leaf("", player, Uint128::new(100))).collect(),
+        );
+        let game = crate::merkle::MerkleTree::new(
+            leaves.iter().map(|(player, bin)| domain_leaf_hash("", player, &bin.to_string())).collect(),
+        );
+        let msg = ExecuteMsg::RegisterMerkleRoots {
+            merkle_root_airdrop: airdrop.root(),
+            total_amount_airdrop: Some(Uint128::new(200)),
+            merkle_root_game: game.root(),
+            total_amount_game: Some(Uint128::new(100)),
+            tree_metadata: tree_metadata(),
+            overwrite: None,
+            secondary_payout: None,
+            claim_condition: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+        open_claims(&mut deps, 300).unwrap();
+        let mut deps = counting(deps);
+
+        // Every claim reads the parameters and the config once, the counters once more
+        // than the failsafe invariants.
+        env.block.height = 203_000;
+        for (player, bin) in leaves {
+            deps.storage.reset();
+            let msg = ExecuteMsg::ClaimAirdrop {
+                amount: Uint128::new(100),
+                proof_airdrop: airdrop.proof(&compute_leaf("", player, Uint128::new(100))).unwrap(),
+                proof_game: game.proof(&domain_leaf_hash("", player, &bin.to_string())).unwrap(),
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(player, &[]), msg).unwrap();
+            assert_eq!(deps.storage.reads_of(GAME_PARAMS_KEY), 1);
+            assert_eq!(deps.storage.reads_of(CONFIG_KEY), 1);
+            assert_eq!(deps.storage.reads_of(COUNTERS_KEY), 2);
+            assert_eq!(deps.storage.ranges.get(), 0);
+        }
+
+        // The first message after the airdrop claims also freezes the totals: leave it out.
+        env.block.height = 206_000;
+        let params = GAME_PARAMS.load(&deps.storage).unwrap();
+        close_claim_window(&mut deps.storage, &params, &env).unwrap();
+        for (player, _) in leaves {
+            deps.storage.reset();
+            let msg = ExecuteMsg::ClaimPrize { amount: None, proof: None, then: None };
+            execute(deps.as_mut(), env.clone(), mock_info(player, &[]), msg).unwrap();
+            assert_eq!(deps.storage.reads_of(GAME_PARAMS_KEY), 1);
+            assert_eq!(deps.storage.reads_of(CONFIG_KEY), 1);
+            assert_eq!(deps.storage.reads_of(COUNTERS_KEY), 2);
+        }
     }
 }
@@ -1,7 +1,10 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{OverflowError, StdError, Uint128};
+use cw_utils::PaymentError;
 use hex::FromHexError;
 use thiserror::Error;
 
+use crate::stage_math::StageMathError;
+
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
     #[error("{0}")]
@@ -10,12 +13,49 @@ pub enum ContractError {
     #[error("{0}")]
     Hex(#[from] FromHexError),
 
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
+    #[error("{0}")]
+    StageMath(#[from] StageMathError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
     #[error("Invalid input")]
     InvalidInput {},
 
+    // Instantiation errors.
+    #[error("Ticket price must have a denom and a non-zero amount")]
+    InvalidTicketPrice {},
+
+    #[error("The game must have at least one bin")]
+    InvalidBins {},
+
+    #[error("Max proof length must be between 1 and {max}")]
+    InvalidMaxProofLength { max: u32 },
+
+    #[error("The {limit} must be between 1 and {max}")]
+    InvalidLimit { limit: String, max: u32 },
+
+    #[error("Claim rate limit window must be at least one block")]
+    InvalidClaimRateLimit {},
+
+    #[error("Late bid penalty must have a window in (0, 1] and a non-zero fee")]
+    InvalidLateBidPenalty {},
+
+    #[error("Prize timelock must end before the claim prize stage")]
+    PrizeTimelockTooLong {},
+
+    #[error("Invalid zero amount")]
+    InvalidZeroAmount {},
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+
     #[error("Already claimed")]
     AlreadyClaimed {},
 
@@ -28,17 +68,97 @@ pub enum ContractError {
     #[error("Cannot migrate from different contract type: {previous_contract}")]
     CannotMigrate { previous_contract: String },
 
+    #[error("Address not eligible for the airdrop according to {contract_addr}")]
+    NotEligibleByHook { contract_addr: String },
+
+    #[error("The airdrop must be claimed with ClaimAirdropByIndex in the claim index mode")]
+    ClaimIndexRequired {},
+
+    #[error("The game is not in the claim index mode")]
+    ClaimIndexDisabled {},
+
+    #[error("A valid attestation of {registry} is required")]
+    AttestationRequired { registry: String },
+
+    #[error("No airdrop withheld for the address")]
+    NothingWithheld {},
+
+    #[error("Claim condition of the airdrop not met")]
+    ConditionNotMet {},
+
+    #[error("Claim condition cannot have more than {max} conditions")]
+    ConditionTooLarge { max: usize },
+
+    #[error("{proxy} is not an approved proxy")]
+    NotApprovedProxy { proxy: String },
+
+    #[error("{address} is not custodied by {proxy}")]
+    NotCustodied { address: String, proxy: String },
+
+    #[error("Eligibility query template must contain the {{address}} placeholder")]
+    InvalidQueryTemplate {},
+
+    #[error("The campaign id of the leaf format cannot be empty")]
+    InvalidLeafFormat {},
+
+    // Outcome commitment errors.
+    #[error("The game outcome has already been committed")]
+    OutcomeAlreadyCommitted {},
+
+    #[error("The game outcome has not been committed")]
+    OutcomeNotCommitted {},
+
+    #[error("The game outcome must be revealed before registering the Merkle roots")]
+    OutcomeNotRevealed {},
+
+    #[error("The game outcome does not match the commitment")]
+    CommitmentMismatch {},
+
+    // Claims funding errors.
+    #[error("Claims must be opened with OpenClaims once the airdrop is funded")]
+    ClaimsNotFunded {},
+
+    #[error("Airdrop balance {balance} is lower than the registered total {required}")]
+    AirdropUnderfunded { balance: Uint128, required: Uint128 },
+
+    #[error("Native airdrops are checked against the deposits on every claim")]
+    OpenClaimsNotRequired {},
+
+    #[error("The bid stage is not over yet")]
+    BidStageNotFinished {},
+
     // Claim prize errors.
     #[error("Not eligible to claim game prize")]
     NoteEligible {},
 
+    #[error("Claimed prize exceeds the prize pool")]
+    PrizePoolExceeded {},
+
+    #[error("Secondary payout exceeds its registered total")]
+    SecondaryPayoutExceeded {},
+
+    #[error("No secondary payout registered")]
+    SecondaryPayoutNotRegistered {},
+
+    #[error("Cw20 funding is not supported by native airdrops or after a payout swap")]
+    Cw20FundingNotSupported {},
+
     #[error("Claim Prize stage is not over yet")]
     ClaimPrizeStageNotFinished {},
 
+    #[error("The amount withdrawn must be positive and at most the {remaining} remaining")]
+    InvalidWithdrawAmount { remaining: Uint128 },
+
     // General stage errors.
     #[error("The {stage_name} has not started")]
     StageNotStarted { stage_name: String },
 
+    #[error("The {stage_name} has already started")]
+    StageStarted { stage_name: String },
+
+    #[error("The bid stage of the source contract has not ended")]
+    SourceBidStageActive {},
+
     #[error("The {stage_name} has ended")]
     StageEnded { stage_name: String },
 
@@ -52,15 +172,168 @@ pub enum ContractError {
     #[error("Fund sent insufficent for paying the bid price")]
     TicketPriceNotPaid {},
 
+    #[error("A pubkey proof is required to bid")]
+    PubkeyProofRequired {},
+
+    #[error("Invalid pubkey signature")]
+    InvalidSignature {},
+
+    #[error("The pubkey does not belong to the bidder address")]
+    PubkeyNotSender {},
+
+    #[error("The pubkey has already been used to bid from another address")]
+    PubkeyAlreadyUsed {},
+
     #[error("Cannot be placed more than one bid per address")]
     CannotBidMoreThanOnce {},
 
+    #[error("No ticket vendor is configured")]
+    TicketVendorNotSet {},
+
+    #[error("Ticket {ticket_id} is not owned by the bidder")]
+    InvalidTicket { ticket_id: u64 },
+
+    #[error("Ticket {ticket_id} has already been used to bid")]
+    TicketAlreadyUsed { ticket_id: u64 },
+
+    #[error("Bid cannot be changed more than {max} times")]
+    TooManyBidChanges { max: u8 },
+
+    #[error("Bins are private until the end of the bid stage")]
+    PrivateBins {},
+
     #[error("A bid must be placed before changing it")]
     BidNotPresent {},
 
     #[error("InsufficientFunds")]
     InsufficientFunds {},
+
+    #[error("The airdrop is not funded with native tokens")]
+    NativeAirdropNotEnabled {},
     
+    #[error("Bins can be boosted only with the equal split prize mode")]
+    BinBoostNotAvailable {},
+
+    #[error("Bin {bin} has winners, its boost cannot be refunded")]
+    BinWon { bin: u8 },
+
+    #[error("Nothing to refund")]
+    NothingToRefund {},
+
+    #[error("Payout shares must be positive, to distinct addresses, and sum to {total} bps")]
+    InvalidPayoutSplit { total: u16 },
+
+    #[error("The prize of a payout split cannot be sent to a hook")]
+    PayoutSplitWithHook {},
+
+    #[error("The game has winners")]
+    GameHasWinners {},
+
+    #[error("The no winners policy does not allow this message")]
+    NoWinnersPolicyNotApplicable {},
+
+    #[error("The no winners policy is not available with the Merkle amounts prize mode")]
+    InvalidNoWinnersPolicy {},
+
+    #[error("The Merkle roots can only be registered by a proposal of the governance multisig")]
+    GovernanceRequired {},
+
+    #[error("Claim rate limit reached, retry at height {next_height}")]
+    RetryLater { next_height: u64 },
+
+    #[error("The claim airdrop stage is closed, the Merkle roots cannot be replaced")]
+    ClaimWindowClosed {},
+
+    #[error("Merkle proofs cannot have more than {max} nodes")]
+    ProofTooLong { max: u32 },
+
+    #[error("At most {max} items can be processed in a single message")]
+    BatchTooLarge { max: u32 },
+
+    #[error("The {field} cannot be longer than {max} bytes")]
+    MemoTooLong { field: String, max: usize },
+
+    #[error("At most {max} addresses can be sent in a single message")]
+    TooManyAddresses { max: u32 },
+
+    #[error("The registered totals ({required}) exceed the supply of the token ({supply})")]
+    ExceedsSupply { supply: Uint128, required: Uint128 },
+
+    #[error("The contract holds {balance} of the prize denom, {required} are still owed")]
+    PrizeBalanceTooLow { balance: Uint128, required: Uint128 },
+
+    #[error("Merkle roots already registered, set overwrite to replace them")]
+    MerkleRootAlreadyRegistered {},
+
+    #[error("The airdrop has already been claimed, the Merkle roots cannot be replaced")]
+    ClaimsAlreadyStarted {},
+
+    #[error("Claims are frozen: {reason}")]
+    ClaimsFrozen { reason: String },
+
+    #[error("Prizes are locked until the end of the veto period")]
+    PrizeTimelocked {},
+
+    #[error("Prizes are vetoed by the owner: {reason}")]
+    PrizeVetoed { reason: String },
+
+    #[error("Prizes cannot be vetoed after the end of the veto period")]
+    VetoPeriodEnded {},
+
+    #[error("Operations moving funds are blocked by the failsafe: {condition}")]
+    FailsafeActive { condition: String },
+
+    #[error("Claims must be frozen to swap the payout token")]
+    ClaimsNotFrozen {},
+
+    #[error("Only airdrops paid with the cw20 token can swap the payout token")]
+    PayoutSwapNotSupported {},
+
+    #[error("No valid proof cached for the address")]
+    ProofNotCached {},
+
     #[error("Bin does not exist. Number of bins: {bins}.")]
     BinDoesNotExist { bins: u8 },
+
+    #[error("Test mode is not enabled")]
+    TestModeDisabled {},
+
+    #[error("The prize pool of {pool} is below the minimum of {minimum}, the tickets are refunded")]
+    PrizePoolBelowMinimum { pool: Uint128, minimum: Uint128 },
+
+    #[error("Airdrop round {round} does not exist")]
+    RoundNotFound { round: u8 },
+
+    #[error("Airdrop round {round} is not over yet")]
+    RoundNotFinished { round: u8 },
+
+    #[error("Claims of airdrop round {round} exceed its funded amount")]
+    RoundAmountExceeded { round: u8 },
+
+    #[error("No more airdrop rounds can be registered")]
+    TooManyRounds {},
+
+    #[error("Treasury {address} is already registered")]
+    TreasuryAlreadyRegistered { address: String },
+
+    #[error("Treasury cannot be activated before {active_at}")]
+    TreasuryTimelocked { active_at: String },
+
+    #[error("Treasury {address} is not registered")]
+    TreasuryNotRegistered { address: String },
+
+    #[error("Withdrawal address {address} is not an active treasury")]
+    TreasuryNotActive { address: String },
+
+    #[error("Withdrawal of {amount} is above the {threshold} threshold and needs the approval of the guardian")]
+    WithdrawalNeedsApproval { amount: Uint128, threshold: Uint128 },
+
+    #[error("Withdrawals do not need the approval of a guardian")]
+    WithdrawalGuardDisabled {},
+
+    #[error("Withdrawal proposal {id} does not exist")]
+    WithdrawalProposalNotFound { id: u64 },
+
+    #[error("Withdrawal proposal {id} has expired")]
+    WithdrawalProposalExpired { id: u64 },
 }
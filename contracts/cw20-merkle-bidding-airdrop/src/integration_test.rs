@@ -2,24 +2,30 @@
 
 use std::borrow::BorrowMut;
 
-use cosmwasm_std::{from_slice, Addr, BlockInfo, Coin, CustomQuery, Empty, Event, Uint128};
+use cosmwasm_std::{
+    from_slice, to_binary, Addr, Binary, BlockInfo, Coin, CustomQuery, Decimal, Deps, DepsMut, Empty,
+    Env, Event, MessageInfo, Response, StdError, StdResult, Uint128,
+};
 use cw20::{Cw20Coin, Cw20Contract};
+use cw_asset_lite::Asset;
 
 use anyhow::Result as AnyResult;
 
 use cw_multi_test::{App, Contract, ContractWrapper, Executor};
-use cw_utils::{Duration, Scheduled};
+use cw_storage_plus::Item;
+use cw_utils::{Duration, Expiration, PaymentError, Scheduled};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::contract::{execute, instantiate, query};
+use crate::constants::{StageName, TEST_DENOM};
+use crate::contract::{execute, instantiate, query, reply};
 use crate::ContractError;
 
 use crate::msg::{
-    BidResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, MerkleRootsResponse,
-    QueryMsg, StagesResponse, GameAmountsResponse,
+    AllBidsResponse, BadgeMintMsg, BidResponse, ConfigResponse, CustodyResponse, EligibilityHookMsg, ProxyQueryMsg, EligibilityResponse, ExecuteMsg, InstantiateMsg, IsClaimedResponse, MerkleRootsResponse,
+    QueryMsg, FundPurpose, MerkleRootInfo, ReceiveMsg, RootKind, SecondaryPayoutMsg, StagesResponse, GameAmountsResponse,
 };
-use crate::state::Stage;
+use crate::state::{BadgeRole, ClaimedAt, Stage, TreeMetadata};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -38,7 +44,55 @@ fn mock_app() -> App {
         time: current_block.time,
         chain_id: current_block.chain_id,
     });
-    return app;
+    app
+}
+
+/// Average block time, so that the time of the blocks moves along with the height.
+const BLOCK_TIME_SECS: u64 = 5;
+
+/// Moves `n` blocks forward.
+fn advance_blocks(router: &mut App, n: u64) {
+    router.update_block(|block| {
+        block.height += n;
+        block.time = block.time.plus_seconds(n * BLOCK_TIME_SECS);
+    });
+}
+
+/// Moves `secs` seconds forward, along with the blocks produced in the meantime.
+fn advance_time(router: &mut App, secs: u64) {
+    router.update_block(|block| {
+        block.height += secs / BLOCK_TIME_SECS;
+        block.time = block.time.plus_seconds(secs);
+    });
+}
+
+/// Moves to the first block of a stage of the game, which must not have started yet.
+fn goto_stage(router: &mut App, contract_addr: &Addr, stage_name: StageName) {
+    let stages = get_stages(router, contract_addr);
+    let stage = match stage_name {
+        StageName::Bid => stages.stage_bid,
+        StageName::ClaimAirdrop => stages.stage_claim_airdrop,
+        StageName::ClaimPrize => stages.stage_claim_prize,
+    };
+    let block = router.block_info();
+    match stage.start {
+        Scheduled::AtHeight(height) => {
+            assert!(height >= block.height, "the {} stage has already started", stage_name.as_str());
+            advance_blocks(router, height - block.height);
+        }
+        Scheduled::AtTime(time) => {
+            assert!(time >= block.time, "the {} stage has already started", stage_name.as_str());
+            advance_time(router, time.seconds() - block.time.seconds());
+        }
+    }
+}
+
+fn tree_metadata() -> TreeMetadata {
+    TreeMetadata {
+        airdrop_leaf_count: 3,
+        game_leaf_count: 3,
+        allocation_checksum: "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08".to_string(),
+    }
 }
 
 fn valid_stages() -> (Stage, Stage, Stage) {
@@ -57,7 +111,7 @@ fn valid_stages() -> (Stage, Stage, Stage) {
         duration: Duration::Height(2),
     };
 
-    return (stage_bid, stage_claim_airdrop, stage_claim_prize);
+    (stage_bid, stage_claim_airdrop, stage_claim_prize)
 }
 
 // ======================================================================================
@@ -65,7 +119,7 @@ fn valid_stages() -> (Stage, Stage, Stage) {
 // ======================================================================================
 /// Create the game contract.
 pub fn contract_game() -> Box<dyn Contract<Empty>> {
-    let contract = ContractWrapper::new(execute, instantiate, query);
+    let contract = ContractWrapper::new(execute, instantiate, query).with_reply(reply);
     Box::new(contract)
 }
 
@@ -79,7 +133,150 @@ pub fn contract_cw20() -> Box<dyn Contract<Empty>> {
     Box::new(contract)
 }
 
+/// Create the ticket vendor contract.
+pub fn contract_ticket_vendor() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw_ticket_vendor::contract::execute,
+        cw_ticket_vendor::contract::instantiate,
+        cw_ticket_vendor::contract::query,
+    );
+    Box::new(contract)
+}
+
+/// Storage of the addresses eligible according to the eligibility contract.
+const ELIGIBLE_ADDRESSES: Item<Vec<String>> = Item::new("eligible_addresses");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EligibilityQueryMsg {
+    IsEligible { address: String },
+}
+
+fn eligibility_instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: Vec<String>,
+) -> StdResult<Response> {
+    ELIGIBLE_ADDRESSES.save(deps.storage, &msg)?;
+    Ok(Response::default())
+}
+
+fn eligibility_execute(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Ok(Response::default())
+}
+
+fn eligibility_query(deps: Deps, _env: Env, msg: EligibilityQueryMsg) -> StdResult<Binary> {
+    match msg {
+        EligibilityQueryMsg::IsEligible { address } => {
+            let eligible = ELIGIBLE_ADDRESSES.load(deps.storage)?.contains(&address);
+            to_binary(&EligibilityResponse { eligible })
+        }
+    }
+}
+
+/// Create a contract answering to the eligibility hook queries.
+pub fn contract_eligibility() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        eligibility_execute,
+        eligibility_instantiate,
+        eligibility_query,
+    );
+    Box::new(contract)
+}
+
+/// Storage of the addresses custodied by the proxy contract.
+const CUSTODIED_ADDRESSES: Item<Vec<String>> = Item::new("custodied_addresses");
+
+fn proxy_instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: Vec<String>,
+) -> StdResult<Response> {
+    CUSTODIED_ADDRESSES.save(deps.storage, &msg)?;
+    Ok(Response::default())
+}
+
+fn proxy_query(deps: Deps, _env: Env, msg: ProxyQueryMsg) -> StdResult<Binary> {
+    match msg {
+        ProxyQueryMsg::Custodies { address } => {
+            let custodied = CUSTODIED_ADDRESSES.load(deps.storage)?.contains(&address);
+            to_binary(&CustodyResponse { custodied })
+        }
+    }
+}
+
+/// Create a contract custodying addresses, like a vesting or a DAO contract.
+pub fn contract_proxy() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(eligibility_execute, proxy_instantiate, proxy_query);
+    Box::new(contract)
+}
+
+/// Storage of the badges minted by the badge contract.
+const MINTED_BADGES: Item<Vec<BadgeMintMsg>> = Item::new("minted_badges");
+/// Storage of the flag making the badge contract reject the mints.
+const BADGES_PAUSED: Item<bool> = Item::new("badges_paused");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BadgeMockExecuteMsg {
+    Mint(BadgeMintMsg),
+    Pause {},
+}
+
+fn badge_instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    MINTED_BADGES.save(deps.storage, &vec![])?;
+    BADGES_PAUSED.save(deps.storage, &false)?;
+    Ok(Response::default())
+}
+
+fn badge_execute(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: BadgeMockExecuteMsg,
+) -> StdResult<Response> {
+    match msg {
+        BadgeMockExecuteMsg::Mint(mint) => {
+            if BADGES_PAUSED.load(deps.storage)? {
+                return Err(StdError::generic_err("badges paused"));
+            }
+            // The contract assigns its own ids, reported in the `token_id` attribute.
+            let mut badges = MINTED_BADGES.load(deps.storage)?;
+            badges.push(mint);
+            MINTED_BADGES.save(deps.storage, &badges)?;
+            Ok(Response::new().add_attribute("token_id", format!("badge{}", badges.len())))
+        }
+        BadgeMockExecuteMsg::Pause {} => {
+            BADGES_PAUSED.save(deps.storage, &true)?;
+            Ok(Response::default())
+        }
+    }
+}
+
+fn badge_query(deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    to_binary(&MINTED_BADGES.load(deps.storage)?)
+}
+
+/// Create a cw721-like contract minting the claim badges.
+pub fn contract_badge() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(badge_execute, badge_instantiate, badge_query);
+    Box::new(contract)
+}
+
 /// Instantiate the game contract.
+#[allow(clippy::too_many_arguments)]
 pub fn create_game(
     router: &mut App,
     owner: &Addr,
@@ -95,11 +292,39 @@ pub fn create_game(
     let msg = InstantiateMsg {
         owner: Some("owner0000".to_string()),
         cw20_token_address: cw20_token.unwrap_or("random0000".to_string()),
+        airdrop_native_denom: None,
+        eligibility_hook: None,
         ticket_price,
         bins,
-        stage_bid,
-        stage_claim_airdrop,
-        stage_claim_prize,
+        stage_bid: stage_bid.into(),
+        stage_claim_airdrop: stage_claim_airdrop.into(),
+        stage_claim_prize: stage_claim_prize.into(),
+        metadata: None,
+        prize_mode: None,
+        rounding_policy: None,
+        pubkey_bids: None,
+        approved_proxies: None,
+        claim_rate_limit: None,
+        ticket_vendor: None,
+        max_proof_length: None,
+        badge_contract: None,
+        max_bid_changes: None,
+        late_bid_penalty: None,
+        private_bins: None,
+        hidden_bids: None,
+        minimal_attributes: None,
+        prize_timelock: None,
+        payout_mode: None,
+        no_winners_policy: None,
+        governance_mode: None,
+        kyc_gate: None,
+        leaf_format: None,
+        claim_index: None,
+        registry: None,
+        test_mode: None,
+        min_prize_pool: None,
+        treasury_timelock: None,
+        withdrawal_guard: None,
     };
     router.instantiate_contract(
         game_id, 
@@ -142,6 +367,30 @@ fn create_cw20(
     Cw20Contract(addr)
 }
 
+/// Confirm the funds of the game contract and open the claims.
+fn open_claims(router: &mut App, contract_addr: &Addr) {
+    router
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            contract_addr.clone(),
+            &ExecuteMsg::OpenClaims {},
+            &[],
+        ).unwrap();
+}
+
+/// Register a withdrawal destination and activate it after the default timelock.
+fn activate_treasury(router: &mut App, contract_addr: &Addr, address: &Addr) {
+    let msg = ExecuteMsg::SetTreasury { addr: address.to_string() };
+    router
+        .execute_contract(Addr::unchecked("owner0000"), contract_addr.clone(), &msg, &[])
+        .unwrap();
+    advance_time(router, 24 * 60 * 60);
+    let msg = ExecuteMsg::ActivateTreasury { addr: address.to_string() };
+    router
+        .execute_contract(Addr::unchecked("anyone"), contract_addr.clone(), &msg, &[])
+        .unwrap();
+}
+
 // ======================================================================================
 // Queries
 // ======================================================================================
@@ -155,7 +404,14 @@ fn get_stages(router: &App, contract_addr: &Addr) -> StagesResponse {
 fn get_bid(router: &App, contract_addr: &Addr, address: String) -> BidResponse {
     router
         .wrap()
-        .query_wasm_smart(contract_addr, &QueryMsg::Bid { address })
+        .query_wasm_smart(contract_addr, &QueryMsg::Bid { address, viewer_proof: None })
+        .unwrap()
+}
+
+fn get_is_claimed(router: &App, contract_addr: &Addr, address: String) -> IsClaimedResponse {
+    router
+        .wrap()
+        .query_wasm_smart(contract_addr, &QueryMsg::IsClaimed { address })
         .unwrap()
 }
 
@@ -192,11 +448,11 @@ fn bank_balance(router: &mut App, addr: &Addr, denom: String) -> Coin {
 // ======================================================================================
 pub fn global_variables() -> (String, Addr, Coin, u8, Vec<Coin>) {
 
-    let native_token_denom = String::from("ujuno");
+    let native_token_denom = TEST_DENOM.to_string();
     // Owner of the game contract.
     let owner: Addr = Addr::unchecked("owner");
     // Ticket of the game.
-    let ticket_price: Coin = Coin {denom: String::from("ujuno"), amount: Uint128::new(10)};
+    let ticket_price: Coin = Coin {denom: TEST_DENOM.to_string(), amount: Uint128::new(10)};
     // Number of bins of the game.
     let bins: u8 = 10;
     // Initial balance of the owner of the game.
@@ -211,7 +467,7 @@ pub fn global_variables() -> (String, Addr, Coin, u8, Vec<Coin>) {
         bins,
         funds
     );
-    return global_variables
+    global_variables
 }
 
 // ======================================================================================
@@ -249,7 +505,7 @@ fn test_instantiate() {
     let mut stage_claim_airdrop_err = stage_claim_airdrop.clone();
     stage_claim_airdrop_err.start = Scheduled::AtHeight(100_000);
     let first = String::from("bid");
-    let second = String::from("Claim airdrop");
+    let second = String::from("claim airdrop");
     let err = create_game(
         &mut router,
         &owner,
@@ -264,12 +520,7 @@ fn test_instantiate() {
     assert_eq!(ContractError::StagesOverlap { first, second }, err.downcast().unwrap());
 
     // Trigger BidStartPassed error.
-    let current_block = router.block_info();
-    router.set_block(BlockInfo {
-        height: 300_000,
-        time: current_block.time,
-        chain_id: current_block.chain_id,
-    });
+    advance_blocks(&mut router, 100_001);
 
     let err = create_game(
         &mut router,
@@ -311,31 +562,30 @@ fn valid_bid_no_change() {
     ).unwrap();
 
     // Cannot bid if bid stage not started.
-    let bid_msg = ExecuteMsg::Bid { bin: 1 };
-    let bid = Coin {denom: native_token_denom.clone().into(),amount: Uint128::new(10)};
+    let bid_msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: None };
+    let bid = Coin {denom: native_token_denom.clone(),amount: Uint128::new(10)};
     let err = router
         .execute_contract(
             owner.clone(),
             game_addr.clone(),
             &bid_msg,
-            &[bid.clone()],
+            std::slice::from_ref(&bid),
         ).unwrap_err();
     let balance: Coin = bank_balance(&mut router, &owner, native_token_denom.clone().to_string());
     assert_eq!(ContractError::StageNotStarted { stage_name: "bid".into() }, err.downcast().unwrap());
     assert_eq!(Uint128::new(1_000_000), balance.amount);
 
     // Trigger bid stage start.
-    let current_block = router.block_info();
-    router.set_block(BlockInfo {height: 200_001, time: current_block.time, chain_id: current_block.chain_id});
+    goto_stage(&mut router, &game_addr, StageName::Bid);
 
     // Make a valid bid without a change.
-    let bid = Coin {denom: native_token_denom.clone().into(),amount: Uint128::new(10)};
+    let bid = Coin {denom: native_token_denom.clone(),amount: Uint128::new(10)};
     let _res = router
         .execute_contract(
             owner.clone(),
             game_addr.clone(),
             &bid_msg,
-            &[bid.clone()],
+            std::slice::from_ref(&bid),
         ).unwrap();
     let balance: Coin = bank_balance(&mut router, &owner, native_token_denom.to_string());
     assert_eq!(Uint128::new(999_990), balance.amount);
@@ -346,12 +596,66 @@ fn valid_bid_no_change() {
             owner.clone(),
             game_addr.clone(),
             &bid_msg,
-            &[bid.clone()],
+            std::slice::from_ref(&bid),
         ).unwrap_err();
 
     assert_eq!(ContractError::CannotBidMoreThanOnce {}, err.downcast().unwrap());
 }
- 
+
+#[test]
+fn time_based_stages() {
+    let mut router = mock_app();
+    let (native_token_denom, owner, ticket_price, bins, funds) = global_variables();
+
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router.bank.init_balance(storage, &owner, funds).unwrap()
+    });
+
+    // One hour of bids, then one hour for each claim.
+    let now = router.block_info().time;
+    let stage = |start: u64| Stage {
+        start: Scheduled::AtTime(now.plus_seconds(start)),
+        duration: Duration::Time(3_600),
+    };
+    let game_addr = create_game(
+        &mut router,
+        &owner,
+        ticket_price,
+        bins,
+        stage(600),
+        stage(4_200),
+        stage(7_800),
+        None,
+    ).unwrap();
+
+    // Blocks move along with the time.
+    let height = router.block_info().height;
+    goto_stage(&mut router, &game_addr, StageName::Bid);
+    assert_eq!(router.block_info().height, height + 600 / BLOCK_TIME_SECS);
+    let bid_msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: None };
+    router
+        .execute_contract(owner.clone(), game_addr.clone(), &bid_msg, &[Coin::new(10, native_token_denom)])
+        .unwrap();
+
+    // The last second of the stage still accepts changes.
+    advance_time(&mut router, 3_599);
+    let change_bid_msg = ExecuteMsg::ChangeBid { bin: 2 };
+    router
+        .execute_contract(owner.clone(), game_addr.clone(), &change_bid_msg, &[])
+        .unwrap();
+
+    advance_time(&mut router, 1);
+    let err = router
+        .execute_contract(owner.clone(), game_addr.clone(), &change_bid_msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::StageEnded { stage_name: "bid".into() }, err.downcast().unwrap());
+
+    // The claim airdrop stage starts as the bid stage ends.
+    goto_stage(&mut router, &game_addr, StageName::ClaimAirdrop);
+    advance_blocks(&mut router, 2);
+    assert_eq!(router.block_info().time, now.plus_seconds(4_200 + 2 * BLOCK_TIME_SECS));
+}
+
 #[test]
 fn valid_bid_with_change() {
     let mut router = mock_app();
@@ -375,18 +679,17 @@ fn valid_bid_with_change() {
     ).unwrap();
 
     // Trigger bid stage start.
-    let current_block = router.block_info();
-    router.set_block(BlockInfo {height: 200_001, time: current_block.time, chain_id: current_block.chain_id});
+    goto_stage(&mut router, &game_addr, StageName::Bid);
 
     // Check that the response has the correct trasnfer message
-    let bid_msg = ExecuteMsg::Bid { bin: 1 };
-    let bid = Coin {denom: native_token_denom.clone().into(),amount: Uint128::new(20)};
+    let bid_msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: None };
+    let bid = Coin {denom: native_token_denom.clone(),amount: Uint128::new(20)};
     let res = router
         .execute_contract(
             owner.clone(),
             game_addr.clone(),
             &bid_msg,
-            &[bid.clone()],
+            std::slice::from_ref(&bid),
         ).unwrap();
     let event_transfer = Event::new("transfer")
         .add_attributes(vec![
@@ -423,12 +726,11 @@ fn invalid_bid() {
     ).unwrap();
 
     // Trigger bid stage start.
-    let current_block = router.block_info();
-    router.set_block(BlockInfo {height: 200_001, time: current_block.time, chain_id: current_block.chain_id});
+    goto_stage(&mut router, &game_addr, StageName::Bid);
 
     // Trigger TicketPriceNotPaid error for insufficient funds.
-    let bid_msg = ExecuteMsg::Bid { bin: 1 };
-    let bid = Coin {denom: native_token_denom.into(), amount: Uint128::new(1)};
+    let bid_msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: None };
+    let bid = Coin {denom: native_token_denom, amount: Uint128::new(1)};
     let err = router
         .execute_contract(
             owner.clone(),
@@ -440,7 +742,7 @@ fn invalid_bid() {
     assert_eq!(ContractError::TicketPriceNotPaid {}, err.downcast().unwrap());
 
     // Trigger TicketPriceNotPaid error for wrong funds.
-    let bid_msg = ExecuteMsg::Bid { bin: 1 };
+    let bid_msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: None };
     let bid = Coin {denom: "ubtc".into(), amount: Uint128::new(10)};
     let err = router
         .execute_contract(
@@ -453,6 +755,70 @@ fn invalid_bid() {
     assert_eq!(ContractError::TicketPriceNotPaid {}, err.downcast().unwrap());
 }
 
+#[test]
+fn bid_with_multiple_denoms() {
+    let mut router = mock_app();
+    let (native_token_denom, owner,ticket_price, bins, funds) = global_variables();
+
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router.bank.init_balance(storage, &owner, funds).unwrap()
+    });
+
+    let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+    let game_addr = create_game(
+        &mut router,
+        &owner,
+        ticket_price,
+        bins,
+        stage_bid.clone(),
+        stage_claim_airdrop.clone(),
+        stage_claim_prize.clone(),
+        None,
+    ).unwrap();
+
+    // Trigger bid stage start.
+    goto_stage(&mut router, &game_addr, StageName::Bid);
+
+    // Repeated ticket coins are summed, and the excess is returned along with the
+    // unrelated coins in a single transfer.
+    let bid_msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: None };
+    let bid = vec![
+        Coin {denom: native_token_denom.clone(), amount: Uint128::new(7)},
+        Coin {denom: "ubtc".into(), amount: Uint128::new(5)},
+        Coin {denom: native_token_denom.clone(), amount: Uint128::new(6)},
+    ];
+    let res = router
+        .execute_contract(
+            owner.clone(),
+            game_addr.clone(),
+            &bid_msg,
+            &bid,
+        ).unwrap();
+    let refund = res
+        .events
+        .iter()
+        .filter(|event| event.ty == "transfer")
+        .count();
+
+    assert_eq!(1, refund);
+    assert_eq!(Uint128::new(1_000_000), bank_balance(&mut router, &owner, "ubtc".to_string()).amount);
+    assert_eq!(Uint128::new(999_990), bank_balance(&mut router, &owner, native_token_denom.clone()).amount);
+    assert_eq!(Uint128::new(10), bank_balance(&mut router, &game_addr, native_token_denom).amount);
+    assert_eq!(Uint128::zero(), bank_balance(&mut router, &game_addr, "ubtc".to_string()).amount);
+    assert_eq!(
+        BidResponse {
+            has_bid: true,
+            bin: Some(1),
+            paid: Some(Coin::new(10, TEST_DENOM)),
+            placed_at: Some(router.block_info().height),
+            is_winner: None,
+            prize_claimed: false,
+        },
+        get_bid(&router, &game_addr, owner.to_string())
+    );
+}
+
 #[test]
 fn change_bid() {
     let mut router = mock_app();
@@ -476,8 +842,7 @@ fn change_bid() {
     ).unwrap();
 
     // Trigger bid stage start.
-    let current_block = router.block_info();
-    router.set_block(BlockInfo {height: 200_001, time: current_block.time, chain_id: current_block.chain_id});
+    goto_stage(&mut router, &game_addr, StageName::Bid);
 
     // Trigger BidNotPresent error.
     let change_bid_msg = ExecuteMsg::ChangeBid { bin: 2 };
@@ -492,8 +857,8 @@ fn change_bid() {
     assert_eq!(ContractError::BidNotPresent {}, err.downcast().unwrap());
 
     // Check correctness on bid modification.
-    let bid_msg = ExecuteMsg::Bid { bin: 1 };
-    let bid = Coin {denom: native_token_denom.into(), amount: Uint128::new(10)};
+    let bid_msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: None };
+    let bid = Coin {denom: native_token_denom, amount: Uint128::new(10)};
     let _res = router
         .execute_contract(
             owner.clone(),
@@ -503,7 +868,7 @@ fn change_bid() {
         ).unwrap();
     let info = get_bid(&router, &game_addr, owner.to_string());
 
-    assert_eq!(BidResponse {bid: Some(1)}, info);
+    assert_eq!(Some(1), info.bin);
 
     let change_bid_msg = ExecuteMsg::ChangeBid { bin: 2 };
     let _res = router
@@ -515,7 +880,7 @@ fn change_bid() {
         ).unwrap();
     let info = get_bid(&router, &game_addr, owner.to_string());
 
-    assert_eq!(BidResponse { bid: Some(2) }, info);
+    assert_eq!(Some(2), info.bin);
 
 }
 
@@ -542,8 +907,7 @@ fn remove_bid() {
     ).unwrap();
 
     // Trigger bid stage start.
-    let current_block = router.block_info();
-    router.set_block(BlockInfo {height: 200_001, time: current_block.time, chain_id: current_block.chain_id});
+    goto_stage(&mut router, &game_addr, StageName::Bid);
 
     // Trigger BidNotPresent error.
     let remove_bid_msg = ExecuteMsg::RemoveBid {};
@@ -558,8 +922,8 @@ fn remove_bid() {
     assert_eq!(ContractError::BidNotPresent {}, err.downcast().unwrap());
 
     // Check that bid is removed and funds returned
-    let bid_msg = ExecuteMsg::Bid { bin: 1 };
-    let valid_bid_no_change = Coin {denom: native_token_denom.clone().into(), amount: Uint128::new(10)};
+    let bid_msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: None };
+    let valid_bid_no_change = Coin {denom: native_token_denom.clone(), amount: Uint128::new(10)};
     let _res = router
         .execute_contract(
             owner.clone(),
@@ -582,7 +946,7 @@ fn remove_bid() {
     let info = get_bid(&router, &game_addr, owner.to_string());
     let balance: Coin = bank_balance(&mut router, &owner, native_token_denom.to_string());
 
-    assert_eq!(BidResponse { bid: None }, info);
+    assert_eq!(None, info.bin);
     assert_eq!(Uint128::new(1_000_000), balance.amount);
 
     // Check that two consecutive remove bid is not possible.
@@ -601,6 +965,278 @@ fn remove_bid() {
 
 }
 
+#[test]
+fn bid_with_vendor_ticket() {
+    let mut router = mock_app();
+    let (native_token_denom, owner, ticket_price, bins, funds) = global_variables();
+    let player = Addr::unchecked("player");
+    let friend = Addr::unchecked("friend");
+
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router.bank.init_balance(storage, &player, funds).unwrap()
+    });
+
+    // Tickets are sold by the vendor, proceeds go to the game owner.
+    let vendor_id = router.store_code(contract_ticket_vendor());
+    let vendor_msg = cw_ticket_vendor::msg::InstantiateMsg {
+        price: Asset::native(native_token_denom.clone(), 10u128),
+        supply: 10,
+        sale_start: Scheduled::AtHeight(100),
+        sale_end: Expiration::Never {},
+        recipient: owner.to_string(),
+    };
+    let vendor_addr = router
+        .instantiate_contract(vendor_id, owner.clone(), &vendor_msg, &[], "vendor", None)
+        .unwrap();
+
+    let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+    let game_id = router.store_code(contract_game());
+    let msg = InstantiateMsg {
+        owner: Some(owner.to_string()),
+        cw20_token_address: "random0000".to_string(),
+        airdrop_native_denom: None,
+        eligibility_hook: None,
+        ticket_price,
+        bins,
+        stage_bid: stage_bid.into(),
+        stage_claim_airdrop: stage_claim_airdrop.into(),
+        stage_claim_prize: stage_claim_prize.into(),
+        metadata: None,
+        prize_mode: None,
+        rounding_policy: None,
+        pubkey_bids: None,
+        approved_proxies: None,
+        claim_rate_limit: None,
+        ticket_vendor: Some(vendor_addr.to_string()),
+        max_proof_length: None,
+        badge_contract: None,
+        max_bid_changes: None,
+        late_bid_penalty: None,
+        private_bins: None,
+        hidden_bids: None,
+        minimal_attributes: None,
+        prize_timelock: None,
+        payout_mode: None,
+        no_winners_policy: None,
+        governance_mode: None,
+        kyc_gate: None,
+        leaf_format: None,
+        claim_index: None,
+        registry: None,
+        test_mode: None,
+        min_prize_pool: None,
+        treasury_timelock: None,
+        withdrawal_guard: None,
+    };
+    let game_addr = router
+        .instantiate_contract(game_id, owner.clone(), &msg, &[], "game", None)
+        .unwrap();
+    assert_eq!(
+        Some(vendor_addr.to_string()),
+        get_config(&router, &game_addr).ticket_vendor
+    );
+    let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+    let other_game = create_game(
+        &mut router,
+        &owner,
+        msg.ticket_price,
+        bins,
+        stage_bid,
+        stage_claim_airdrop,
+        stage_claim_prize,
+        None,
+    ).unwrap();
+
+    router
+        .execute_contract(
+            player.clone(),
+            vendor_addr.clone(),
+            &cw_ticket_vendor::msg::ExecuteMsg::Buy {},
+            &[Coin::new(10, native_token_denom.clone())],
+        )
+        .unwrap();
+    assert_eq!(Uint128::new(10), bank_balance(&mut router, &owner, native_token_denom.clone()).amount);
+
+    // Trigger bid stage start.
+    goto_stage(&mut router, &game_addr, StageName::Bid);
+
+    // Trigger InvalidTicket error for a ticket owned by someone else.
+    let bid_msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: Some(1) };
+    let err = router
+        .execute_contract(friend.clone(), game_addr.clone(), &bid_msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::InvalidTicket { ticket_id: 1 }, err.downcast().unwrap());
+
+    // The ticket replaces the payment and is not added to the prize, funds attached
+    // to a ticket bid are returned.
+    let balance = bank_balance(&mut router, &player, native_token_denom.clone()).amount;
+    router
+        .execute_contract(
+            player.clone(),
+            game_addr.clone(),
+            &bid_msg,
+            &[Coin::new(10, native_token_denom.clone())],
+        )
+        .unwrap();
+    assert_eq!(balance, bank_balance(&mut router, &player, native_token_denom.clone()).amount);
+    let info = get_bid(&router, &game_addr, player.to_string());
+    assert_eq!((Some(1), None), (info.bin, info.paid));
+    assert_eq!(Uint128::zero(), bank_balance(&mut router, &game_addr, native_token_denom.clone()).amount);
+
+    // A used ticket cannot be used again, even after a transfer.
+    let transfer_msg = cw_ticket_vendor::msg::ExecuteMsg::Transfer {
+        ticket_id: 1,
+        recipient: friend.to_string(),
+    };
+    router
+        .execute_contract(player.clone(), vendor_addr.clone(), &transfer_msg, &[])
+        .unwrap();
+    let err = router
+        .execute_contract(friend.clone(), game_addr.clone(), &bid_msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::TicketAlreadyUsed { ticket_id: 1 }, err.downcast().unwrap());
+
+    // Removing the bid frees the ticket without refunds.
+    router
+        .execute_contract(player.clone(), game_addr.clone(), &ExecuteMsg::RemoveBid {}, &[])
+        .unwrap();
+    assert_eq!(
+        Uint128::new(999_990),
+        bank_balance(&mut router, &player, native_token_denom.clone()).amount
+    );
+    router
+        .execute_contract(friend.clone(), game_addr.clone(), &bid_msg, &[])
+        .unwrap();
+    assert_eq!(Some(1), get_bid(&router, &game_addr, friend.to_string()).bin);
+
+    // Games without a vendor do not accept tickets.
+    let err = router
+        .execute_contract(player, other_game, &bid_msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::TicketVendorNotSet {}, err.downcast().unwrap());
+}
+
+#[test]
+fn import_bids() {
+    let mut router = mock_app();
+    let (native_token_denom, owner, ticket_price, bins, funds) = global_variables();
+    let game_owner = Addr::unchecked("owner0000");
+    let players = [Addr::unchecked("player1"), Addr::unchecked("player2"), Addr::unchecked("player3")];
+
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router.bank.init_balance(storage, &game_owner, funds.clone()).unwrap();
+        for player in &players {
+            router.bank.init_balance(storage, player, funds.clone()).unwrap();
+        }
+    });
+
+    // Bids placed in the previous deployment.
+    let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+    let source_addr = create_game(
+        &mut router,
+        &owner,
+        ticket_price.clone(),
+        bins,
+        stage_bid,
+        stage_claim_airdrop,
+        stage_claim_prize,
+        None,
+    ).unwrap();
+
+    goto_stage(&mut router, &source_addr, StageName::Bid);
+    for (bin, player) in players.iter().enumerate() {
+        let bid_msg = ExecuteMsg::Bid { bin: bin as u8 + 1, pubkey_proof: None, ticket_id: None };
+        router
+            .execute_contract(player.clone(), source_addr.clone(), &bid_msg, std::slice::from_ref(&ticket_price))
+            .unwrap();
+    }
+
+    // The new deployment has not started yet.
+    let later = |height: u64| Stage { start: Scheduled::AtHeight(height), duration: Duration::Height(2) };
+    let game_addr = create_game(
+        &mut router,
+        &owner,
+        ticket_price,
+        bins,
+        later(300_000),
+        later(301_000),
+        later(302_000),
+        None,
+    ).unwrap();
+
+    let import_msg = ExecuteMsg::ImportBids { source_contract: source_addr.to_string(), limit: Some(2) };
+    let tickets = |amount: u128| vec![Coin::new(amount, native_token_denom.clone())];
+
+    // Trigger Unauthorized error for a sender different from the owner.
+    let err = router
+        .execute_contract(players[0].clone(), game_addr.clone(), &import_msg, &tickets(20))
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    // Bids are imported once the players cannot remove them from the source anymore.
+    let err = router
+        .execute_contract(game_owner.clone(), game_addr.clone(), &import_msg, &tickets(20))
+        .unwrap_err();
+    assert_eq!(ContractError::SourceBidStageActive {}, err.downcast().unwrap());
+    advance_blocks(&mut router, 2);
+    let err = router
+        .execute_contract(players[0].clone(), source_addr.clone(), &ExecuteMsg::RemoveBid {}, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::StageEnded { stage_name: "bid".to_string() }, err.downcast().unwrap());
+
+    // Trigger TicketPriceNotPaid error if the imported tickets are not paid.
+    let err = router
+        .execute_contract(game_owner.clone(), game_addr.clone(), &import_msg, &tickets(10))
+        .unwrap_err();
+    assert_eq!(ContractError::TicketPriceNotPaid {}, err.downcast().unwrap());
+
+    router
+        .execute_contract(game_owner.clone(), game_addr.clone(), &import_msg, &tickets(20))
+        .unwrap();
+    assert_eq!(Some(1), get_bid(&router, &game_addr, players[0].to_string()).bin);
+    assert_eq!(Some(2), get_bid(&router, &game_addr, players[1].to_string()).bin);
+    assert_eq!(None, get_bid(&router, &game_addr, players[2].to_string()).bin);
+
+    // Replaying the message continues from the last imported bid and returns the change.
+    router
+        .execute_contract(game_owner.clone(), game_addr.clone(), &import_msg, &tickets(20))
+        .unwrap();
+    assert_eq!(Some(3), get_bid(&router, &game_addr, players[2].to_string()).bin);
+    let res = router
+        .execute_contract(game_owner.clone(), game_addr.clone(), &import_msg, &[])
+        .unwrap();
+    let wasm = res.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert!(wasm.attributes.iter().any(|attr| attr.key == "imported" && attr.value == "0"));
+
+    assert_eq!(Uint128::new(30), bank_balance(&mut router, &game_addr, native_token_denom.clone()).amount);
+    assert_eq!(Uint128::new(999_970), bank_balance(&mut router, &game_owner, native_token_denom.clone()).amount);
+    let res: AllBidsResponse = router
+        .wrap()
+        .query_wasm_smart(&game_addr, &QueryMsg::AllBids { start_after: None, limit: None })
+        .unwrap();
+    assert_eq!(3, res.bids.len());
+
+    // The players paid their ticket to the source, so removing an imported bid does not
+    // refund the ticket paid by the owner: it stays in the prize.
+    assert_eq!(None, get_bid(&router, &game_addr, players[0].to_string()).paid);
+    goto_stage(&mut router, &game_addr, StageName::Bid);
+    router
+        .execute_contract(players[0].clone(), game_addr.clone(), &ExecuteMsg::RemoveBid {}, &[])
+        .unwrap();
+    assert_eq!(None, get_bid(&router, &game_addr, players[0].to_string()).bin);
+    assert_eq!(
+        Uint128::new(999_990),
+        bank_balance(&mut router, &players[0], native_token_denom.clone()).amount
+    );
+    assert_eq!(Uint128::new(30), bank_balance(&mut router, &game_addr, native_token_denom.clone()).amount);
+
+    // Bids cannot be imported once the bid stage has started.
+    let err = router
+        .execute_contract(game_owner, game_addr, &import_msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::StageStarted { stage_name: "bid".to_string() }, err.downcast().unwrap());
+}
+
 // ======================================================================================
 // Tests Merkle root
 // ======================================================================================
@@ -626,12 +1262,21 @@ fn register_merkle_root() {
         None,
     ).unwrap();
     
+    // Roots can be queried before their registration.
+    let info = get_merkle_roots(&router, &game_addr);
+    assert_eq!(info.merkle_root_airdrop, None);
+    assert_eq!(info.total_amount, Uint128::zero());
+
     // Check Merkle roots properly saved.
     let register_merkle_root_msg = ExecuteMsg::RegisterMerkleRoots {
         merkle_root_airdrop: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
         total_amount_airdrop: None,
         merkle_root_game: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d38".to_string(),
         total_amount_game: None,
+        tree_metadata: tree_metadata(),
+        overwrite: None,
+        secondary_payout: None,
+        claim_condition: None,
     };
     let _res = router
         .execute_contract(
@@ -644,11 +1289,24 @@ fn register_merkle_root() {
     let info = get_merkle_roots(&router, &game_addr);
     assert_eq!(
         info.merkle_root_airdrop,
-        "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string()
+        Some("634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string())
     );
     assert_eq!(
         info.merkle_root_game,
-        "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d38".to_string()
+        Some("634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d38".to_string())
+    );
+    let root: MerkleRootInfo = router
+        .wrap()
+        .query_wasm_smart(&game_addr, &QueryMsg::MerkleRoot { kind: RootKind::Game })
+        .unwrap();
+    assert_eq!(
+        root,
+        MerkleRootInfo {
+            kind: RootKind::Game,
+            merkle_root: info.merkle_root_game,
+            total_amount: Uint128::zero(),
+            leaf_count: Some(3),
+        }
     );
 
     // Only the game owner can register the roots.
@@ -702,13 +1360,13 @@ fn claim_airdrop() {
         &owner,
         "token".to_string(),
         "CWTOKEN".to_string(),
-        Uint128::new(1_000_000)
+        Uint128::new(2_000_000)
     );
 
     let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
 
     // Create the game contract.
-    let cw20_token_address = Some(cw20_token.addr().to_string()).unwrap();
+    let cw20_token_address = cw20_token.addr().to_string();
     let game_addr = create_game(
         &mut router,
         &owner,
@@ -730,7 +1388,7 @@ fn claim_airdrop() {
         .balance::<App, Addr, MyCustomQuery>(&router, owner.clone())
         .unwrap();
 
-    assert_eq!(owner_balance, Uint128::new(1_000_000));
+    assert_eq!(owner_balance, Uint128::new(2_000_000));
 
     // Register Merkle roots.
     let register_merkle_root_msg = ExecuteMsg::RegisterMerkleRoots {
@@ -738,6 +1396,10 @@ fn claim_airdrop() {
         total_amount_airdrop: Some(Uint128::new(1_000)),
         merkle_root_game: test_data_game.root,
         total_amount_game: Some(Uint128::new(1_000_000)),
+        tree_metadata: tree_metadata(),
+        overwrite: None,
+        secondary_payout: None,
+        claim_condition: None,
     };
     let _res = router
         .execute_contract(
@@ -757,6 +1419,34 @@ fn claim_airdrop() {
 
     // Transfer token to the game contract and verify the balance.
     let send_token_msg = cw20::Cw20ExecuteMsg::Transfer {recipient: game_addr.clone().into(),amount: Uint128::new(110)};
+    let _res = router
+        .execute_contract(
+            owner.clone(),
+            Addr::unchecked(cw20_token_address.clone()),
+            &send_token_msg,
+            &[],
+        ).unwrap();
+    let game_balance = cw20_token
+        .balance::<App, Addr, MyCustomQuery>(&router, game_addr.clone())
+        .unwrap();
+
+    assert_eq!(game_balance, Uint128::new(110));
+
+    // Claims cannot be opened until the registered totals are held by the game.
+    let err = router
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            game_addr.clone(),
+            &ExecuteMsg::OpenClaims {},
+            &[],
+        ).unwrap_err();
+
+    assert_eq!(
+        ContractError::AirdropUnderfunded { balance: Uint128::new(110), required: Uint128::new(1_001_000) },
+        err.downcast().unwrap()
+    );
+
+    let send_token_msg = cw20::Cw20ExecuteMsg::Transfer {recipient: game_addr.clone().into(),amount: Uint128::new(1_000_890)};
     let _res = router
         .execute_contract(
             owner,
@@ -764,13 +1454,635 @@ fn claim_airdrop() {
             &send_token_msg,
             &[],
         ).unwrap();
-    let game_balance = cw20_token
-        .balance::<App, Addr, MyCustomQuery>(&router, game_addr.clone())
-        .unwrap();
+    open_claims(&mut router, &game_addr);
+
+    // Claim not allowed if claiming stage not active.
+    let claim_airdrop_msg = ExecuteMsg::ClaimAirdrop {
+        amount: test_data_airdrop.addresses[0].amount,
+        proof_airdrop: test_data_airdrop.addresses[0].proofs.clone(),
+        proof_game: test_data_game.addresses[0].proofs.clone()
+    };
+    let err = router
+        .execute_contract(
+            Addr::unchecked(game_addr.to_string()),
+            game_addr.clone(),
+            &claim_airdrop_msg,
+            &[],
+        ).unwrap_err();
+
+    assert_eq!(ContractError::StageNotStarted {stage_name: String::from("claim airdrop")},err.downcast().unwrap());
+
+    // Trigger claiming airdrop stage.
+    goto_stage(&mut router, &game_addr, StageName::ClaimAirdrop);
+
+    // Cannot be claimed a different amount than the one in the Merkle tree.
+    let claim_airdrop_msg = ExecuteMsg::ClaimAirdrop {
+        amount: Uint128::new(1_000),
+        proof_airdrop: test_data_airdrop.addresses[0].proofs.clone(),
+        proof_game: test_data_game.addresses[0].proofs.clone()
+    };
+    let err = router
+        .execute_contract(
+            Addr::unchecked(test_data_airdrop.addresses[0].account.clone()),
+            game_addr.clone(),
+            &claim_airdrop_msg,
+            &[],
+        ).unwrap_err();
+
+    assert_eq!(ContractError::VerificationFailed { merkle_root: "airdrop".to_string() }, err.downcast().unwrap());
+
+    // Claim the correct ammount and verify balances.
+    let claim_airdrop_msg = ExecuteMsg::ClaimAirdrop {
+        amount: test_data_airdrop.addresses[0].amount,
+        proof_airdrop: test_data_airdrop.addresses[0].proofs.clone(),
+        proof_game: test_data_game.addresses[0].proofs.clone()
+    };
+
+    let _res = router
+        .execute_contract(
+            Addr::unchecked(test_data_airdrop.addresses[0].account.clone()),
+            game_addr.clone(),
+            &claim_airdrop_msg,
+            &[],
+        ).unwrap();
+    let claimer_balance = cw20_token
+        .balance::<App, Addr, MyCustomQuery>(&router, Addr::unchecked(test_data_airdrop.addresses[0].account.clone()))
+        .unwrap();
+    let game_balance = cw20_token
+        .balance::<App, Addr, MyCustomQuery>(&router, game_addr.clone())
+        .unwrap();
+
+    assert_eq!(claimer_balance, Uint128::new(100));
+    assert_eq!(game_balance, Uint128::new(1_000_900));
+
+    let claim_airdrop_msg = ExecuteMsg::ClaimAirdrop {
+        amount: test_data_airdrop.addresses[0].amount,
+        proof_airdrop: test_data_airdrop.addresses[0].proofs.clone(),
+        proof_game: test_data_game.addresses[0].proofs.clone()
+    };
+
+    // Airdrop cannot be claimed more than once.
+    let err = router
+        .execute_contract(
+            Addr::unchecked(test_data_airdrop.addresses[0].account.clone()),
+            game_addr.clone(),
+            &claim_airdrop_msg,
+            &[],
+        ).unwrap_err();
+
+    assert_eq!(ContractError::AlreadyClaimed {}, err.downcast().unwrap());
+
+    // Verify total claimed amount
+    let info = get_game_amount(&router, &game_addr);
+
+    assert_eq!(info.total_claimed_airdrop, Uint128::new(100));
+}
+
+#[test]
+fn swap_payout_token() {
+    let mut router = mock_app();
+    let (_, owner, ticket_price, bins, _) = global_variables();
+    let game_owner = Addr::unchecked("owner0000");
+
+    let test_data_airdrop: Encoded = from_slice(TEST_DATA_AIRDROP).unwrap();
+    let test_data_game: Encoded = from_slice(TEST_DATA_GAME).unwrap();
+    let claimer = Addr::unchecked(test_data_airdrop.addresses[0].account.clone());
+
+    let old_token = create_cw20(&mut router, &owner, "token".to_string(), "CWTOKEN".to_string(), Uint128::new(2_000_000));
+    let new_token = create_cw20(&mut router, &owner, "new token".to_string(), "NEWTOKEN".to_string(), Uint128::new(4_000_000));
+
+    let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+    let game_addr = create_game(
+        &mut router,
+        &owner,
+        ticket_price,
+        bins,
+        stage_bid,
+        stage_claim_airdrop,
+        stage_claim_prize,
+        Some(old_token.addr().to_string()),
+    ).unwrap();
+
+    let register_merkle_root_msg = ExecuteMsg::RegisterMerkleRoots {
+        merkle_root_airdrop: test_data_airdrop.root,
+        total_amount_airdrop: Some(Uint128::new(1_000)),
+        merkle_root_game: test_data_game.root,
+        total_amount_game: Some(Uint128::new(1_000_000)),
+        tree_metadata: tree_metadata(),
+        overwrite: None,
+        secondary_payout: None,
+        claim_condition: None,
+    };
+    router
+        .execute_contract(game_owner.clone(), game_addr.clone(), &register_merkle_root_msg, &[])
+        .unwrap();
+    let fund = |router: &mut App, token: &Cw20Contract, amount: u128| {
+        let msg = cw20::Cw20ExecuteMsg::Transfer { recipient: game_addr.to_string(), amount: Uint128::new(amount) };
+        router.execute_contract(owner.clone(), token.addr(), &msg, &[]).unwrap();
+    };
+    fund(&mut router, &old_token, 1_001_000);
+    open_claims(&mut router, &game_addr);
+    goto_stage(&mut router, &game_addr, StageName::ClaimAirdrop);
+
+    // The payout token can be swapped only while the claims are frozen.
+    let swap_msg = ExecuteMsg::SwapPayoutToken { new_cw20: new_token.addr().to_string(), rate: Decimal::percent(200) };
+    let err = router
+        .execute_contract(game_owner.clone(), game_addr.clone(), &swap_msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::ClaimsNotFrozen {}, err.downcast().unwrap());
+
+    let freeze_msg = ExecuteMsg::FreezeClaims { reason: "token migration".to_string() };
+    router
+        .execute_contract(game_owner.clone(), game_addr.clone(), &freeze_msg, &[])
+        .unwrap();
+    let err = router
+        .execute_contract(owner.clone(), game_addr.clone(), &swap_msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    // The game must hold the converted balance of the new token.
+    let err = router
+        .execute_contract(game_owner.clone(), game_addr.clone(), &swap_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::AirdropUnderfunded { balance: Uint128::zero(), required: Uint128::new(2_002_000) },
+        err.downcast().unwrap()
+    );
+
+    fund(&mut router, &new_token, 2_002_000);
+    router
+        .execute_contract(game_owner.clone(), game_addr.clone(), &swap_msg, &[])
+        .unwrap();
+    assert_eq!(Uint128::new(1_001_000), old_token.balance::<App, Addr, MyCustomQuery>(&router, game_addr.clone()).unwrap());
+    let config = get_config(&router, &game_addr);
+    assert_eq!(new_token.addr().to_string(), config.cw20_token_address);
+    assert_eq!(Some(Decimal::percent(200)), config.payout_rate);
+
+    // Pending allocations are paid with the new token at the recorded rate.
+    router
+        .execute_contract(game_owner.clone(), game_addr.clone(), &ExecuteMsg::UnfreezeClaims {}, &[])
+        .unwrap();
+    let claim_airdrop_msg = ExecuteMsg::ClaimAirdrop {
+        amount: test_data_airdrop.addresses[0].amount,
+        proof_airdrop: test_data_airdrop.addresses[0].proofs.clone(),
+        proof_game: test_data_game.addresses[0].proofs.clone(),
+    };
+    router
+        .execute_contract(claimer.clone(), game_addr.clone(), &claim_airdrop_msg, &[])
+        .unwrap();
+    assert_eq!(Uint128::new(200), new_token.balance::<App, Addr, MyCustomQuery>(&router, claimer).unwrap());
+    assert_eq!(Uint128::new(100), get_game_amount(&router, &game_addr).total_claimed_airdrop);
+
+    // The old tokens are withdrawn to a treasury, once.
+    let treasury = Addr::unchecked("treasury0000");
+    let withdraw_msg = ExecuteMsg::WithdrawRetiredPayout { cw20_token_address: old_token.addr(), address: treasury.clone() };
+    let err = router
+        .execute_contract(game_owner.clone(), game_addr.clone(), &withdraw_msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::TreasuryNotActive { address: treasury.to_string() }, err.downcast().unwrap());
+    activate_treasury(&mut router, &game_addr, &treasury);
+    router
+        .execute_contract(game_owner.clone(), game_addr.clone(), &withdraw_msg, &[])
+        .unwrap();
+    assert_eq!(Uint128::new(1_001_000), old_token.balance::<App, Addr, MyCustomQuery>(&router, treasury).unwrap());
+    assert_eq!(Uint128::zero(), old_token.balance::<App, Addr, MyCustomQuery>(&router, game_addr.clone()).unwrap());
+    let err = router
+        .execute_contract(game_owner, game_addr, &withdraw_msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::InvalidWithdrawAmount { remaining: Uint128::zero() }, err.downcast().unwrap());
+}
+
+#[test]
+fn claim_airdrop_secondary_payout() {
+    let mut router = mock_app();
+    let (_, owner, ticket_price, bins, _) = global_variables();
+    let game_owner = Addr::unchecked("owner0000");
+
+    let test_data_airdrop: Encoded = from_slice(TEST_DATA_AIRDROP).unwrap();
+    let test_data_game: Encoded = from_slice(TEST_DATA_GAME).unwrap();
+    let claimer = Addr::unchecked(test_data_airdrop.addresses[0].account.clone());
+
+    let token = create_cw20(&mut router, &owner, "token".to_string(), "CWTOKEN".to_string(), Uint128::new(2_000_000));
+    let bonus = create_cw20(&mut router, &owner, "bonus".to_string(), "BONUS".to_string(), Uint128::new(1_000));
+
+    let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+    let game_addr = create_game(
+        &mut router,
+        &owner,
+        ticket_price,
+        bins,
+        stage_bid,
+        stage_claim_airdrop,
+        stage_claim_prize,
+        Some(token.addr().to_string()),
+    ).unwrap();
+
+    // Half a bonus token is paid for every airdropped token.
+    let register_merkle_root_msg = ExecuteMsg::RegisterMerkleRoots {
+        merkle_root_airdrop: test_data_airdrop.root,
+        total_amount_airdrop: Some(Uint128::new(1_000)),
+        merkle_root_game: test_data_game.root,
+        total_amount_game: Some(Uint128::new(1_000_000)),
+        tree_metadata: tree_metadata(),
+        overwrite: None,
+        secondary_payout: Some(SecondaryPayoutMsg {
+            cw20_token_address: bonus.addr().to_string(),
+            rate: Decimal::percent(50),
+            total_amount: Uint128::new(500),
+        }),
+        claim_condition: None,
+    };
+    router
+        .execute_contract(game_owner.clone(), game_addr.clone(), &register_merkle_root_msg, &[])
+        .unwrap();
+    let fund = |router: &mut App, token: &Cw20Contract, amount: u128| {
+        let msg = cw20::Cw20ExecuteMsg::Transfer { recipient: game_addr.to_string(), amount: Uint128::new(amount) };
+        router.execute_contract(owner.clone(), token.addr(), &msg, &[]).unwrap();
+    };
+    fund(&mut router, &token, 1_001_000);
+
+    // Claims cannot be opened until the secondary total is held by the game.
+    let err = router
+        .execute_contract(Addr::unchecked("anyone"), game_addr.clone(), &ExecuteMsg::OpenClaims {}, &[])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::AirdropUnderfunded { balance: Uint128::zero(), required: Uint128::new(500) },
+        err.downcast().unwrap()
+    );
+    fund(&mut router, &bonus, 500);
+    open_claims(&mut router, &game_addr);
+
+    // Both assets are delivered by the same claim.
+    goto_stage(&mut router, &game_addr, StageName::ClaimAirdrop);
+    let claim_airdrop_msg = ExecuteMsg::ClaimAirdrop {
+        amount: test_data_airdrop.addresses[0].amount,
+        proof_airdrop: test_data_airdrop.addresses[0].proofs.clone(),
+        proof_game: test_data_game.addresses[0].proofs.clone(),
+    };
+    router
+        .execute_contract(claimer.clone(), game_addr.clone(), &claim_airdrop_msg, &[])
+        .unwrap();
+    assert_eq!(Uint128::new(100), token.balance::<App, Addr, MyCustomQuery>(&router, claimer.clone()).unwrap());
+    assert_eq!(Uint128::new(50), bonus.balance::<App, Addr, MyCustomQuery>(&router, claimer).unwrap());
+    let secondary = get_game_amount(&router, &game_addr).secondary_payout.unwrap();
+    assert_eq!(Uint128::new(50), secondary.claimed_amount);
+
+    // The unclaimed secondary payout is withdrawn once the claims are over.
+    let withdraw_msg = ExecuteMsg::WithdrawSecondaryPayout { address: owner.clone() };
+    let err = router
+        .execute_contract(game_owner.clone(), game_addr.clone(), &withdraw_msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::ClaimPrizeStageNotFinished {}, err.downcast().unwrap());
+
+    goto_stage(&mut router, &game_addr, StageName::ClaimPrize);
+    advance_blocks(&mut router, 2);
+    let err = router
+        .execute_contract(owner.clone(), game_addr.clone(), &withdraw_msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+    let err = router
+        .execute_contract(game_owner.clone(), game_addr.clone(), &withdraw_msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::TreasuryNotActive { address: owner.to_string() }, err.downcast().unwrap());
+    activate_treasury(&mut router, &game_addr, &owner);
+    router
+        .execute_contract(game_owner, game_addr.clone(), &withdraw_msg, &[])
+        .unwrap();
+    assert_eq!(Uint128::new(950), bonus.balance::<App, Addr, MyCustomQuery>(&router, owner).unwrap());
+    assert_eq!(Uint128::zero(), bonus.balance::<App, Addr, MyCustomQuery>(&router, game_addr).unwrap());
+}
+
+#[test]
+fn fund_with_cw20_hook() {
+    let mut router = mock_app();
+    let (_, owner, ticket_price, bins, _) = global_variables();
+    let game_owner = Addr::unchecked("owner0000");
+    let sponsor = Addr::unchecked("sponsor0000");
+
+    let test_data_airdrop: Encoded = from_slice(TEST_DATA_AIRDROP).unwrap();
+    let test_data_game: Encoded = from_slice(TEST_DATA_GAME).unwrap();
+    let claimer = Addr::unchecked(test_data_airdrop.addresses[0].account.clone());
+
+    let token = create_cw20(&mut router, &owner, "token".to_string(), "CWTOKEN".to_string(), Uint128::new(2_000_000));
+    let other = create_cw20(&mut router, &owner, "other".to_string(), "OTHER".to_string(), Uint128::new(1_000));
+    for (recipient, amount) in [(&game_owner, 1_001_000), (&sponsor, 1_000)] {
+        let msg = cw20::Cw20ExecuteMsg::Transfer { recipient: recipient.to_string(), amount: Uint128::new(amount) };
+        router.execute_contract(owner.clone(), token.addr(), &msg, &[]).unwrap();
+    }
+
+    let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+    let game_addr = create_game(
+        &mut router,
+        &owner,
+        ticket_price,
+        bins,
+        stage_bid,
+        stage_claim_airdrop,
+        stage_claim_prize,
+        Some(token.addr().to_string()),
+    ).unwrap();
+
+    let fund = |router: &mut App, sender: &Addr, token: &Cw20Contract, amount: u128, purpose: FundPurpose| {
+        let msg = cw20::Cw20ExecuteMsg::Send {
+            contract: game_addr.to_string(),
+            amount: Uint128::new(amount),
+            msg: to_binary(&ReceiveMsg::Fund { purpose }).unwrap(),
+        };
+        router.execute_contract(sender.clone(), token.addr(), &msg, &[])
+    };
+
+    // Only the configured token is credited, and only the owner funds the trees.
+    let err = fund(&mut router, &owner, &other, 1_000, FundPurpose::Airdrop).unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+    let err = fund(&mut router, &sponsor, &token, 1_000, FundPurpose::Airdrop).unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+    fund(&mut router, &game_owner, &token, 1_000, FundPurpose::Airdrop).unwrap();
+    fund(&mut router, &game_owner, &token, 1_000_000, FundPurpose::GameBonus).unwrap();
+
+    // Boosts are accepted from the start of the bid stage.
+    let err = fund(&mut router, &sponsor, &token, 500, FundPurpose::Boost).unwrap_err();
+    assert_eq!(ContractError::StageNotStarted { stage_name: "bid".to_string() }, err.downcast().unwrap());
+    goto_stage(&mut router, &game_addr, StageName::Bid);
+    fund(&mut router, &sponsor, &token, 500, FundPurpose::Boost).unwrap();
+
+    // Roots registered without totals keep the funded ones.
+    let register_merkle_root_msg = ExecuteMsg::RegisterMerkleRoots {
+        merkle_root_airdrop: test_data_airdrop.root,
+        total_amount_airdrop: None,
+        merkle_root_game: test_data_game.root,
+        total_amount_game: None,
+        tree_metadata: tree_metadata(),
+        overwrite: None,
+        secondary_payout: None,
+        claim_condition: None,
+    };
+    router
+        .execute_contract(game_owner, game_addr.clone(), &register_merkle_root_msg, &[])
+        .unwrap();
+    let amounts = get_game_amount(&router, &game_addr);
+    assert_eq!(Uint128::new(1_000), amounts.total_airdrop_amount);
+    assert_eq!(Uint128::new(1_000_500), amounts.total_airdrop_game_amount);
+    open_claims(&mut router, &game_addr);
+
+    goto_stage(&mut router, &game_addr, StageName::ClaimAirdrop);
+    let claim_airdrop_msg = ExecuteMsg::ClaimAirdrop {
+        amount: test_data_airdrop.addresses[0].amount,
+        proof_airdrop: test_data_airdrop.addresses[0].proofs.clone(),
+        proof_game: test_data_game.addresses[0].proofs.clone(),
+    };
+    router
+        .execute_contract(claimer.clone(), game_addr, &claim_airdrop_msg, &[])
+        .unwrap();
+    assert_eq!(Uint128::new(100), token.balance::<App, Addr, MyCustomQuery>(&router, claimer).unwrap());
+}
+
+#[test]
+fn claim_airdrop_badge() {
+    let mut router = mock_app();
+    let (_, owner, ticket_price, bins, _) = global_variables();
+    let game_owner = Addr::unchecked("owner0000");
+
+    let test_data_airdrop: Encoded = from_slice(TEST_DATA_AIRDROP).unwrap();
+    let test_data_game: Encoded = from_slice(TEST_DATA_GAME).unwrap();
+
+    let cw20_token = create_cw20(&mut router, &owner, "token".to_string(), "CWTOKEN".to_string(), Uint128::new(2_000));
+    let badge_id = router.store_code(contract_badge());
+    let badge_addr = router
+        .instantiate_contract(badge_id, owner.clone(), &Empty {}, &[], "badge", None)
+        .unwrap();
+
+    let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+    let game_id = router.store_code(contract_game());
+    let msg = InstantiateMsg {
+        owner: Some(game_owner.to_string()),
+        cw20_token_address: cw20_token.addr().to_string(),
+        airdrop_native_denom: None,
+        eligibility_hook: None,
+        ticket_price,
+        bins,
+        stage_bid: stage_bid.into(),
+        stage_claim_airdrop: stage_claim_airdrop.into(),
+        stage_claim_prize: stage_claim_prize.into(),
+        metadata: None,
+        prize_mode: None,
+        rounding_policy: None,
+        pubkey_bids: None,
+        approved_proxies: None,
+        claim_rate_limit: None,
+        ticket_vendor: None,
+        max_proof_length: None,
+        badge_contract: Some(badge_addr.to_string()),
+        max_bid_changes: None,
+        late_bid_penalty: None,
+        private_bins: None,
+        hidden_bids: None,
+        minimal_attributes: None,
+        prize_timelock: None,
+        payout_mode: None,
+        no_winners_policy: None,
+        governance_mode: None,
+        kyc_gate: None,
+        leaf_format: None,
+        claim_index: None,
+        registry: None,
+        test_mode: None,
+        min_prize_pool: None,
+        treasury_timelock: None,
+        withdrawal_guard: None,
+    };
+    let game_addr = router
+        .instantiate_contract(game_id, owner.clone(), &msg, &[], "game", None)
+        .unwrap();
+    assert_eq!(Some(badge_addr.to_string()), get_config(&router, &game_addr).badge_contract);
+
+    let register_merkle_root_msg = ExecuteMsg::RegisterMerkleRoots {
+        merkle_root_airdrop: test_data_airdrop.root.clone(),
+        total_amount_airdrop: Some(Uint128::new(2_000)),
+        merkle_root_game: test_data_game.root.clone(),
+        total_amount_game: None,
+        tree_metadata: tree_metadata(),
+        overwrite: None,
+        secondary_payout: None,
+        claim_condition: None,
+    };
+    router
+        .execute_contract(game_owner, game_addr.clone(), &register_merkle_root_msg, &[])
+        .unwrap();
+    let send_token_msg = cw20::Cw20ExecuteMsg::Transfer { recipient: game_addr.to_string(), amount: Uint128::new(2_000) };
+    router
+        .execute_contract(owner.clone(), cw20_token.addr(), &send_token_msg, &[])
+        .unwrap();
+    open_claims(&mut router, &game_addr);
+    goto_stage(&mut router, &game_addr, StageName::ClaimAirdrop);
+
+    let claim = |router: &mut App, i: usize| {
+        let claim_airdrop_msg = ExecuteMsg::ClaimAirdrop {
+            amount: test_data_airdrop.addresses[i].amount,
+            proof_airdrop: test_data_airdrop.addresses[i].proofs.clone(),
+            proof_game: test_data_game.addresses[i].proofs.clone(),
+        };
+        let claimer = Addr::unchecked(test_data_airdrop.addresses[i].account.clone());
+        router
+            .execute_contract(claimer, game_addr.clone(), &claim_airdrop_msg, &[])
+            .unwrap();
+    };
+
+    // The claim mints a badge and records the id assigned by the badge contract.
+    claim(&mut router, 0);
+    let claimed = get_is_claimed(&router, &game_addr, test_data_airdrop.addresses[0].account.clone());
+    assert_eq!(Some("badge1".to_string()), claimed.airdrop_badge);
+    assert_eq!(None, claimed.prize_badge);
+    let badges: Vec<BadgeMintMsg> = router.wrap().query_wasm_smart(&badge_addr, &Empty {}).unwrap();
+    assert_eq!(test_data_airdrop.addresses[0].account, badges[0].owner);
+    assert_eq!(game_addr.to_string(), badges[0].extension.game);
+    assert_eq!(BadgeRole::Airdrop, badges[0].extension.role);
+    assert_eq!(2, badges[0].extension.amount_band);
+
+    // A failing badge contract does not block the claims.
+    router
+        .execute_contract(owner, badge_addr, &BadgeMockExecuteMsg::Pause {}, &[])
+        .unwrap();
+    claim(&mut router, 1);
+    let claimed = get_is_claimed(&router, &game_addr, test_data_airdrop.addresses[1].account.clone());
+    assert!(claimed.airdrop_claimed);
+    assert_eq!(None, claimed.airdrop_badge);
+}
+
+#[test]
+// The bank supply query checked on registration is not supported by cw-multi-test.
+#[cfg_attr(feature = "cosmwasm_1_1", ignore)]
+fn claim_airdrop_native() {
+    let mut router = mock_app();
+    let (native_token_denom, owner,ticket_price, bins, funds) = global_variables();
+    let ibc_denom = String::from("ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2");
+
+    let test_data_airdrop: Encoded = from_slice(TEST_DATA_AIRDROP).unwrap();
+    let test_data_game: Encoded = from_slice(TEST_DATA_GAME).unwrap();
+    let address_1 = Addr::unchecked(test_data_airdrop.addresses[0].account.to_string());
+
+    let mut owner_funds = funds;
+    owner_funds.push(Coin {denom: ibc_denom.clone(), amount: Uint128::new(1_000_000)});
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router.bank.init_balance(storage, &owner, owner_funds).unwrap()
+    });
+
+    let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+
+    // Game without native airdrop cannot be funded with native tokens.
+    let game_addr = create_game(
+        &mut router,
+        &owner,
+        ticket_price.clone(),
+        bins,
+        stage_bid.clone(),
+        stage_claim_airdrop.clone(),
+        stage_claim_prize.clone(),
+        None,
+    ).unwrap();
+    let fund_msg = ExecuteMsg::FundAirdropNative {};
+    let fund = Coin {denom: ibc_denom.clone(), amount: Uint128::new(50)};
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            game_addr,
+            &fund_msg,
+            std::slice::from_ref(&fund),
+        ).unwrap_err();
+
+    assert_eq!(ContractError::NativeAirdropNotEnabled {}, err.downcast().unwrap());
+
+    // Create the game funded with native tokens.
+    let game_id = router.store_code(contract_game());
+    let msg = InstantiateMsg {
+        owner: Some("owner0000".to_string()),
+        cw20_token_address: "random0000".to_string(),
+        airdrop_native_denom: Some(ibc_denom.clone()),
+        eligibility_hook: None,
+        ticket_price,
+        bins,
+        stage_bid: stage_bid.into(),
+        stage_claim_airdrop: stage_claim_airdrop.into(),
+        stage_claim_prize: stage_claim_prize.into(),
+        metadata: None,
+        prize_mode: None,
+        rounding_policy: None,
+        pubkey_bids: None,
+        approved_proxies: None,
+        claim_rate_limit: None,
+        ticket_vendor: None,
+        max_proof_length: None,
+        badge_contract: None,
+        max_bid_changes: None,
+        late_bid_penalty: None,
+        private_bins: None,
+        hidden_bids: None,
+        minimal_attributes: None,
+        prize_timelock: None,
+        payout_mode: None,
+        no_winners_policy: None,
+        governance_mode: None,
+        kyc_gate: None,
+        leaf_format: None,
+        claim_index: None,
+        registry: None,
+        test_mode: None,
+        min_prize_pool: None,
+        treasury_timelock: None,
+        withdrawal_guard: None,
+    };
+    let game_addr = router
+        .instantiate_contract(game_id, owner.clone(), &msg, &[], "game", None)
+        .unwrap();
+
+    let info = get_config(&router, &game_addr);
+    assert_eq!(info.airdrop_native_denom, Some(ibc_denom.clone()));
+
+    // Only the configured denom is accepted.
+    let wrong_fund = Coin {denom: native_token_denom, amount: Uint128::new(50)};
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            game_addr.clone(),
+            &fund_msg,
+            &[wrong_fund],
+        ).unwrap_err();
+
+    assert_eq!(
+        ContractError::Payment(PaymentError::MissingDenom(ibc_denom.clone())),
+        err.downcast().unwrap()
+    );
+
+    // Fund less than the allocation of the first address.
+    let _res = router
+        .execute_contract(
+            owner.clone(),
+            game_addr.clone(),
+            &fund_msg,
+            &[fund],
+        ).unwrap();
+
+    let register_merkle_root_msg = ExecuteMsg::RegisterMerkleRoots {
+        merkle_root_airdrop: test_data_airdrop.root,
+        total_amount_airdrop: Some(Uint128::new(1_000)),
+        merkle_root_game: test_data_game.root,
+        total_amount_game: None,
+        tree_metadata: tree_metadata(),
+        overwrite: None,
+        secondary_payout: None,
+        claim_condition: None,
+    };
+    let _res = router
+        .execute_contract(
+            Addr::unchecked("owner0000"),
+            game_addr.clone(),
+            &register_merkle_root_msg,
+            &[],
+        ).unwrap();
 
-    assert_eq!(game_balance, Uint128::new(110));
+    // Trigger claiming airdrop stage.
+    goto_stage(&mut router, &game_addr, StageName::ClaimAirdrop);
 
-    // Claim not allowed if claiming stage not active.
+    // Claims cannot exceed the native deposits.
     let claim_airdrop_msg = ExecuteMsg::ClaimAirdrop {
         amount: test_data_airdrop.addresses[0].amount,
         proof_airdrop: test_data_airdrop.addresses[0].proofs.clone(),
@@ -778,79 +2090,354 @@ fn claim_airdrop() {
     };
     let err = router
         .execute_contract(
-            Addr::unchecked(game_addr.to_string()),
+            address_1.clone(),
             game_addr.clone(),
             &claim_airdrop_msg,
             &[],
         ).unwrap_err();
 
-    assert_eq!(ContractError::StageNotStarted {stage_name: String::from("claim airdrop")},err.downcast().unwrap());
+    assert_eq!(ContractError::InsufficientFunds {}, err.downcast().unwrap());
+
+    // Top up the funding and claim in the native denom.
+    let fund = Coin {denom: ibc_denom.clone(), amount: Uint128::new(100)};
+    let _res = router
+        .execute_contract(
+            owner.clone(),
+            game_addr.clone(),
+            &fund_msg,
+            &[fund],
+        ).unwrap();
+    let _res = router
+        .execute_contract(
+            address_1.clone(),
+            game_addr.clone(),
+            &claim_airdrop_msg,
+            &[],
+        ).unwrap();
+    let balance: Coin = bank_balance(&mut router, &address_1, ibc_denom);
+    let info = get_game_amount(&router, &game_addr);
+
+    assert_eq!(balance.amount, Uint128::new(100));
+    assert_eq!(info.total_funded_native_airdrop, Uint128::new(150));
+    assert_eq!(info.total_claimed_airdrop, Uint128::new(100));
+}
+
+#[test]
+fn claim_airdrop_eligibility_hook() {
+    let mut router = mock_app();
+    let (_, owner,ticket_price, bins, funds) = global_variables();
+
+    let test_data_airdrop: Encoded = from_slice(TEST_DATA_AIRDROP).unwrap();
+    let test_data_game: Encoded = from_slice(TEST_DATA_GAME).unwrap();
+    let address_1 = Addr::unchecked(test_data_airdrop.addresses[0].account.to_string());
+    let address_2 = Addr::unchecked(test_data_airdrop.addresses[1].account.to_string());
+
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router.bank.init_balance(storage, &owner, funds).unwrap()
+    });
+
+    let cw20_token = create_cw20(
+        &mut router,
+        &owner,
+        "token".to_string(),
+        "CWTOKEN".to_string(),
+        Uint128::new(1_000_000)
+    );
+
+    // Only the first address is eligible.
+    let eligibility_id = router.store_code(contract_eligibility());
+    let eligibility_addr = router
+        .instantiate_contract(
+            eligibility_id,
+            owner.clone(),
+            &vec![address_1.to_string()],
+            &[],
+            "eligibility",
+            None,
+        ).unwrap();
+
+    let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+    let game_id = router.store_code(contract_game());
+    let mut msg = InstantiateMsg {
+        owner: Some("owner0000".to_string()),
+        cw20_token_address: cw20_token.addr().to_string(),
+        airdrop_native_denom: None,
+        eligibility_hook: Some(EligibilityHookMsg {
+            contract_addr: eligibility_addr.to_string(),
+            query_template: r#"{"is_eligible":{"address":"address"}}"#.to_string(),
+        }),
+        ticket_price,
+        bins,
+        stage_bid: stage_bid.into(),
+        stage_claim_airdrop: stage_claim_airdrop.into(),
+        stage_claim_prize: stage_claim_prize.into(),
+        metadata: None,
+        prize_mode: None,
+        rounding_policy: None,
+        pubkey_bids: None,
+        approved_proxies: None,
+        claim_rate_limit: None,
+        ticket_vendor: None,
+        max_proof_length: None,
+        badge_contract: None,
+        max_bid_changes: None,
+        late_bid_penalty: None,
+        private_bins: None,
+        hidden_bids: None,
+        minimal_attributes: None,
+        prize_timelock: None,
+        payout_mode: None,
+        no_winners_policy: None,
+        governance_mode: None,
+        kyc_gate: None,
+        leaf_format: None,
+        claim_index: None,
+        registry: None,
+        test_mode: None,
+        min_prize_pool: None,
+        treasury_timelock: None,
+        withdrawal_guard: None,
+    };
+
+    // Trigger InvalidQueryTemplate error.
+    let err = router
+        .instantiate_contract(game_id, owner.clone(), &msg, &[], "game", None)
+        .unwrap_err();
+
+    assert_eq!(ContractError::InvalidQueryTemplate {}, err.downcast().unwrap());
+
+    msg.eligibility_hook = Some(EligibilityHookMsg {
+        contract_addr: eligibility_addr.to_string(),
+        query_template: r#"{"is_eligible":{"address":"{address}"}}"#.to_string(),
+    });
+    let game_addr = router
+        .instantiate_contract(game_id, owner.clone(), &msg, &[], "game", None)
+        .unwrap();
+
+    let info = get_config(&router, &game_addr);
+    assert_eq!(info.eligibility_hook.unwrap().contract_addr, eligibility_addr);
+
+    let register_merkle_root_msg = ExecuteMsg::RegisterMerkleRoots {
+        merkle_root_airdrop: test_data_airdrop.root,
+        total_amount_airdrop: Some(Uint128::new(1_000)),
+        merkle_root_game: test_data_game.root,
+        total_amount_game: None,
+        tree_metadata: tree_metadata(),
+        overwrite: None,
+        secondary_payout: None,
+        claim_condition: None,
+    };
+    let _res = router
+        .execute_contract(
+            Addr::unchecked("owner0000"),
+            game_addr.clone(),
+            &register_merkle_root_msg,
+            &[],
+        ).unwrap();
+
+    let send_token_msg = cw20::Cw20ExecuteMsg::Transfer {recipient: game_addr.to_string(), amount: Uint128::new(1_000)};
+    let _res = router
+        .execute_contract(
+            owner,
+            cw20_token.addr(),
+            &send_token_msg,
+            &[],
+        ).unwrap();
+    open_claims(&mut router, &game_addr);
 
     // Trigger claiming airdrop stage.
-    let current_block = router.block_info();
-    router.set_block(BlockInfo {height: 201_001,time: current_block.time,chain_id: current_block.chain_id});
+    goto_stage(&mut router, &game_addr, StageName::ClaimAirdrop);
 
-    // Cannot be claimed a different amount than the one in the Merkle tree.
+    // A valid proof is not enough if the hook rejects the address.
     let claim_airdrop_msg = ExecuteMsg::ClaimAirdrop {
-        amount: Uint128::new(1_000),
-        proof_airdrop: test_data_airdrop.addresses[0].proofs.clone(),
-        proof_game: test_data_game.addresses[0].proofs.clone()
+        amount: test_data_airdrop.addresses[1].amount,
+        proof_airdrop: test_data_airdrop.addresses[1].proofs.clone(),
+        proof_game: test_data_game.addresses[1].proofs.clone()
     };
     let err = router
         .execute_contract(
-            Addr::unchecked(test_data_airdrop.addresses[0].account.clone()),
+            address_2,
             game_addr.clone(),
             &claim_airdrop_msg,
             &[],
         ).unwrap_err();
 
-    assert_eq!(ContractError::VerificationFailed { merkle_root: "airdrop".to_string() }, err.downcast().unwrap());
+    assert_eq!(
+        ContractError::NotEligibleByHook { contract_addr: eligibility_addr.to_string() },
+        err.downcast().unwrap()
+    );
 
-    // Claim the correct ammount and verify balances.
+    // Eligible address can claim.
     let claim_airdrop_msg = ExecuteMsg::ClaimAirdrop {
         amount: test_data_airdrop.addresses[0].amount,
         proof_airdrop: test_data_airdrop.addresses[0].proofs.clone(),
         proof_game: test_data_game.addresses[0].proofs.clone()
     };
-
     let _res = router
         .execute_contract(
-            Addr::unchecked(test_data_airdrop.addresses[0].account.clone()),
-            game_addr.clone(),
+            address_1.clone(),
+            game_addr,
             &claim_airdrop_msg,
             &[],
         ).unwrap();
     let claimer_balance = cw20_token
-        .balance::<App, Addr, MyCustomQuery>(&router, Addr::unchecked(test_data_airdrop.addresses[0].account.clone()))
-        .unwrap();
-    let game_balance = cw20_token
-        .balance::<App, Addr, MyCustomQuery>(&router, game_addr.clone())
+        .balance::<App, Addr, MyCustomQuery>(&router, address_1)
         .unwrap();
 
     assert_eq!(claimer_balance, Uint128::new(100));
-    assert_eq!(game_balance, Uint128::new(10));
+}
 
-    let claim_airdrop_msg = ExecuteMsg::ClaimAirdrop {
-        amount: test_data_airdrop.addresses[0].amount,
-        proof_airdrop: test_data_airdrop.addresses[0].proofs.clone(),
-        proof_game: test_data_game.addresses[0].proofs.clone()
+#[test]
+fn claim_airdrop_for_proxy() {
+    let mut router = mock_app();
+    let (_, owner,ticket_price, bins, funds) = global_variables();
+
+    let test_data_airdrop: Encoded = from_slice(TEST_DATA_AIRDROP).unwrap();
+    let test_data_game: Encoded = from_slice(TEST_DATA_GAME).unwrap();
+    let address_1 = Addr::unchecked(test_data_airdrop.addresses[0].account.to_string());
+    let address_2 = Addr::unchecked(test_data_airdrop.addresses[1].account.to_string());
+
+    router.borrow_mut().init_modules(|router, _, storage| {
+        router.bank.init_balance(storage, &owner, funds).unwrap()
+    });
+
+    let cw20_token = create_cw20(
+        &mut router,
+        &owner,
+        "token".to_string(),
+        "CWTOKEN".to_string(),
+        Uint128::new(1_000_000)
+    );
+
+    // The proxy custodies only the first address.
+    let proxy_id = router.store_code(contract_proxy());
+    let proxy_addr = router
+        .instantiate_contract(
+            proxy_id,
+            owner.clone(),
+            &vec![address_1.to_string()],
+            &[],
+            "proxy",
+            None,
+        ).unwrap();
+
+    let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
+    let game_id = router.store_code(contract_game());
+    let msg = InstantiateMsg {
+        owner: Some("owner0000".to_string()),
+        cw20_token_address: cw20_token.addr().to_string(),
+        airdrop_native_denom: None,
+        eligibility_hook: None,
+        ticket_price,
+        bins,
+        stage_bid: stage_bid.into(),
+        stage_claim_airdrop: stage_claim_airdrop.into(),
+        stage_claim_prize: stage_claim_prize.into(),
+        metadata: None,
+        prize_mode: None,
+        rounding_policy: None,
+        pubkey_bids: None,
+        approved_proxies: Some(vec![proxy_addr.to_string()]),
+        claim_rate_limit: None,
+        ticket_vendor: None,
+        max_proof_length: None,
+        badge_contract: None,
+        max_bid_changes: None,
+        late_bid_penalty: None,
+        private_bins: None,
+        hidden_bids: None,
+        minimal_attributes: None,
+        prize_timelock: None,
+        payout_mode: None,
+        no_winners_policy: None,
+        governance_mode: None,
+        kyc_gate: None,
+        leaf_format: None,
+        claim_index: None,
+        registry: None,
+        test_mode: None,
+        min_prize_pool: None,
+        treasury_timelock: None,
+        withdrawal_guard: None,
     };
+    let game_addr = router
+        .instantiate_contract(game_id, owner.clone(), &msg, &[], "game", None)
+        .unwrap();
 
-    // Airdrop cannot be claimed more than once.
-    let err = router
+    let register_merkle_root_msg = ExecuteMsg::RegisterMerkleRoots {
+        merkle_root_airdrop: test_data_airdrop.root.clone(),
+        total_amount_airdrop: Some(Uint128::new(1_000)),
+        merkle_root_game: test_data_game.root.clone(),
+        total_amount_game: None,
+        tree_metadata: tree_metadata(),
+        overwrite: None,
+        secondary_payout: None,
+        claim_condition: None,
+    };
+    let _res = router
         .execute_contract(
-            Addr::unchecked(test_data_airdrop.addresses[0].account.clone()),
+            Addr::unchecked("owner0000"),
             game_addr.clone(),
-            &claim_airdrop_msg,
+            &register_merkle_root_msg,
             &[],
-        ).unwrap_err();
+        ).unwrap();
 
-    assert_eq!(ContractError::AlreadyClaimed {}, err.downcast().unwrap());
+    let send_token_msg = cw20::Cw20ExecuteMsg::Transfer {recipient: game_addr.to_string(), amount: Uint128::new(1_000)};
+    let _res = router
+        .execute_contract(
+            owner,
+            cw20_token.addr(),
+            &send_token_msg,
+            &[],
+        ).unwrap();
+    open_claims(&mut router, &game_addr);
 
-    // Verify total claimed amount
-    let info = get_game_amount(&router, &game_addr);
+    // Trigger claiming airdrop stage.
+    goto_stage(&mut router, &game_addr, StageName::ClaimAirdrop);
 
-    assert_eq!(info.total_claimed_airdrop, Uint128::new(100));
+    let claim_for = |index: usize| ExecuteMsg::ClaimAirdropFor {
+        address: test_data_airdrop.addresses[index].account.to_string(),
+        amount: test_data_airdrop.addresses[index].amount,
+        proof_airdrop: test_data_airdrop.addresses[index].proofs.clone(),
+        proof_game: test_data_game.addresses[index].proofs.clone()
+    };
+
+    // Only approved proxies can claim for others.
+    let err = router
+        .execute_contract(address_2.clone(), game_addr.clone(), &claim_for(0), &[])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::NotApprovedProxy { proxy: address_2.to_string() },
+        err.downcast().unwrap()
+    );
+
+    // The proxy can claim only for the addresses it custodies.
+    let err = router
+        .execute_contract(proxy_addr.clone(), game_addr.clone(), &claim_for(1), &[])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::NotCustodied { address: address_2.to_string(), proxy: proxy_addr.to_string() },
+        err.downcast().unwrap()
+    );
+
+    let _res = router
+        .execute_contract(proxy_addr.clone(), game_addr.clone(), &claim_for(0), &[])
+        .unwrap();
+    let proxy_balance = cw20_token
+        .balance::<App, Addr, MyCustomQuery>(&router, proxy_addr)
+        .unwrap();
+    assert_eq!(proxy_balance, Uint128::new(100));
+
+    // The allocation cannot be claimed twice.
+    let claim_airdrop_msg = ExecuteMsg::ClaimAirdrop {
+        amount: test_data_airdrop.addresses[0].amount,
+        proof_airdrop: test_data_airdrop.addresses[0].proofs.clone(),
+        proof_game: test_data_game.addresses[0].proofs.clone()
+    };
+    let err = router
+        .execute_contract(address_1, game_addr, &claim_airdrop_msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::AlreadyClaimed {}, err.downcast().unwrap());
 }
 
 #[test]
@@ -891,7 +2478,7 @@ fn claim_prize() {
     let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
 
     // Create the game contract.
-    let cw20_token_address = Some(cw20_token.addr().to_string()).unwrap();
+    let cw20_token_address = cw20_token.addr().to_string();
     let game_addr = create_game(
         &mut router,
         &owner,
@@ -909,6 +2496,10 @@ fn claim_prize() {
         total_amount_airdrop: Some(Uint128::new(1_000)),
         merkle_root_game: test_data_game.root,
         total_amount_game: Some(Uint128::new(1_000_000)),
+        tree_metadata: tree_metadata(),
+        overwrite: None,
+        secondary_payout: None,
+        claim_condition: None,
     };
     let _res = router
         .execute_contract(
@@ -928,6 +2519,7 @@ fn claim_prize() {
             &send_token_msg,
             &[],
         ).unwrap();
+    open_claims(&mut router, &game_addr);
     // The first address
     let send_token_msg = cw20::Cw20ExecuteMsg::Transfer {recipient: address_1.clone().to_string(), amount: Uint128::new(1_000)};
     let _res = router
@@ -962,45 +2554,43 @@ fn claim_prize() {
     assert_eq!(address_2_balance, Uint128::new(100));
 
     // Trigger bid stage start.
-    let current_block = router.block_info();
-    router.set_block(BlockInfo {height: 200_001, time: current_block.time, chain_id: current_block.chain_id});
+    goto_stage(&mut router, &game_addr, StageName::Bid);
 
     // Address 1 winning bid.
-    let bid_msg = ExecuteMsg::Bid { bin: 1 };
-    let bid = Coin {denom: native_token_denom.clone().into(),amount: Uint128::new(10)};
+    let bid_msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: None };
+    let bid = Coin {denom: native_token_denom.clone(),amount: Uint128::new(10)};
     let _res = router
         .execute_contract(
             address_1.clone(),
             game_addr.clone(),
             &bid_msg,
-            &[bid.clone()],
+            std::slice::from_ref(&bid),
         ).unwrap();
 
     // Address 2 losing bid.
-    let bid_msg = ExecuteMsg::Bid { bin: 1 };
-    let bid = Coin {denom: native_token_denom.clone().into(),amount: Uint128::new(10)};
+    let bid_msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: None };
+    let bid = Coin {denom: native_token_denom.clone(),amount: Uint128::new(10)};
     let _res = router
         .execute_contract(
             address_2.clone(),
             game_addr.clone(),
             &bid_msg,
-            &[bid.clone()],
+            std::slice::from_ref(&bid),
         ).unwrap();
 
     // Address 3 winning bid.
-    let bid_msg = ExecuteMsg::Bid { bin: 10 };
-    let bid = Coin {denom: native_token_denom.clone().into(),amount: Uint128::new(10)};
+    let bid_msg = ExecuteMsg::Bid { bin: 10, pubkey_proof: None, ticket_id: None };
+    let bid = Coin {denom: native_token_denom.clone(),amount: Uint128::new(10)};
     let _res = router
         .execute_contract(
             address_3.clone(),
             game_addr.clone(),
             &bid_msg,
-            &[bid.clone()],
+            std::slice::from_ref(&bid),
         ).unwrap();
 
     // Trigger claiming airdrop stage.
-    let current_block = router.block_info();
-    router.set_block(BlockInfo {height: 201_001,time: current_block.time,chain_id: current_block.chain_id});
+    goto_stage(&mut router, &game_addr, StageName::ClaimAirdrop);
 
     // Address 1 claim the correct ammount and verify balances and winners numbers.
     let claim_airdrop_msg = ExecuteMsg::ClaimAirdrop {
@@ -1073,7 +2663,7 @@ fn claim_prize() {
     assert_eq!(info.winners_amount, Uint128::new(2));
 
     // Cannot claim prize if relative stage is not started
-    let claim_prize_msg = ExecuteMsg::ClaimPrize {};
+    let claim_prize_msg = ExecuteMsg::ClaimPrize { amount: None, proof: None, then: None };
     let err = router
         .execute_contract(
             address_2.clone(),
@@ -1085,11 +2675,10 @@ fn claim_prize() {
     assert_eq!(ContractError::StageNotStarted { stage_name: String::from("claim prize") }, err.downcast().unwrap());
 
     // Trigger claim prize stage start.
-    let current_block = router.block_info();
-    router.set_block(BlockInfo {height: 202_001, time: current_block.time, chain_id: current_block.chain_id});
+    goto_stage(&mut router, &game_addr, StageName::ClaimPrize);
 
     // Cannot claim prize if not winning bid.
-    let claim_prize_msg = ExecuteMsg::ClaimPrize {};
+    let claim_prize_msg = ExecuteMsg::ClaimPrize { amount: None, proof: None, then: None };
     let err = router
         .execute_contract(
             address_2.clone(),
@@ -1107,7 +2696,7 @@ fn claim_prize() {
     assert_eq!(bank_balance_address_2.amount, Uint128::new(999_990));
 
     // Can claim prize if winning bid.
-    let claim_prize_msg = ExecuteMsg::ClaimPrize {};
+    let claim_prize_msg = ExecuteMsg::ClaimPrize { amount: None, proof: None, then: None };
     let _res = router
         .execute_contract(
             address_1.clone(),
@@ -1129,8 +2718,18 @@ fn claim_prize() {
     assert_eq!(info.total_claimed_prize, Uint128::new(15));
     assert_eq!(info.total_claimed_airdrop, Uint128::new(500_000) + Uint128::new(100) + Uint128::new(1010) + Uint128::new(10220));
 
+    // Claims are recorded with their block.
+    let info = get_is_claimed(&router, &game_addr, address_1.to_string());
+    assert!(info.airdrop_claimed && info.prize_claimed);
+    assert_eq!(info.airdrop_claimed_at.map(|claimed_at| claimed_at.height), Some(201_000));
+    assert_eq!(info.prize_claimed_at, Some(ClaimedAt { height: 202_000, time: router.block_info().time }));
+
+    let info = get_is_claimed(&router, &game_addr, address_2.to_string());
+    assert!(info.airdrop_claimed && !info.prize_claimed);
+    assert_eq!(info.prize_claimed_at, None);
+
     // Claim more than once the prize is not allowed
-    let claim_prize_msg = ExecuteMsg::ClaimPrize {};
+    let claim_prize_msg = ExecuteMsg::ClaimPrize { amount: None, proof: None, then: None };
     let err = router
         .execute_contract(
             address_1.clone(),
@@ -1183,7 +2782,7 @@ fn withdraw_airdrop_and_prize() {
     let (stage_bid, stage_claim_airdrop, stage_claim_prize) = valid_stages();
 
     // Create the game contract.
-    let cw20_token_address = Some(cw20_token.addr().to_string()).unwrap();
+    let cw20_token_address = cw20_token.addr().to_string();
     let game_addr = create_game(
         &mut router,
         &owner,
@@ -1201,6 +2800,10 @@ fn withdraw_airdrop_and_prize() {
         total_amount_airdrop: Some(Uint128::new(1_000)),
         merkle_root_game: test_data_game.root,
         total_amount_game: Some(Uint128::new(1_000_000)),
+        tree_metadata: tree_metadata(),
+        overwrite: None,
+        secondary_payout: None,
+        claim_condition: None,
     };
     let _res = router
         .execute_contract(
@@ -1220,6 +2823,7 @@ fn withdraw_airdrop_and_prize() {
             &send_token_msg,
             &[],
         ).unwrap();
+    open_claims(&mut router, &game_addr);
     // The first address
     let send_token_msg = cw20::Cw20ExecuteMsg::Transfer {recipient: address_1.clone().to_string(), amount: Uint128::new(1_000)};
     let _res = router
@@ -1240,45 +2844,43 @@ fn withdraw_airdrop_and_prize() {
         ).unwrap();
 
     // Trigger bid stage start.
-    let current_block = router.block_info();
-    router.set_block(BlockInfo {height: 200_001, time: current_block.time, chain_id: current_block.chain_id});
+    goto_stage(&mut router, &game_addr, StageName::Bid);
 
     // Address 1 winning bid.
-    let bid_msg = ExecuteMsg::Bid { bin: 1 };
-    let bid = Coin {denom: native_token_denom.clone().into(),amount: Uint128::new(10)};
+    let bid_msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: None };
+    let bid = Coin {denom: native_token_denom.clone(),amount: Uint128::new(10)};
     let _res = router
         .execute_contract(
             address_1.clone(),
             game_addr.clone(),
             &bid_msg,
-            &[bid.clone()],
+            std::slice::from_ref(&bid),
         ).unwrap();
 
     // Address 2 losing bid.
-    let bid_msg = ExecuteMsg::Bid { bin: 1 };
-    let bid = Coin {denom: native_token_denom.clone().into(),amount: Uint128::new(10)};
+    let bid_msg = ExecuteMsg::Bid { bin: 1, pubkey_proof: None, ticket_id: None };
+    let bid = Coin {denom: native_token_denom.clone(),amount: Uint128::new(10)};
     let _res = router
         .execute_contract(
             address_2.clone(),
             game_addr.clone(),
             &bid_msg,
-            &[bid.clone()],
+            std::slice::from_ref(&bid),
         ).unwrap();
 
     // Address 3 winning bid.
-    let bid_msg = ExecuteMsg::Bid { bin: 10 };
-    let bid = Coin {denom: native_token_denom.clone().into(),amount: Uint128::new(10)};
+    let bid_msg = ExecuteMsg::Bid { bin: 10, pubkey_proof: None, ticket_id: None };
+    let bid = Coin {denom: native_token_denom.clone(),amount: Uint128::new(10)};
     let _res = router
         .execute_contract(
             address_3.clone(),
             game_addr.clone(),
             &bid_msg,
-            &[bid.clone()],
+            std::slice::from_ref(&bid),
         ).unwrap();
 
     // Trigger claiming airdrop stage.
-    let current_block = router.block_info();
-    router.set_block(BlockInfo {height: 201_001,time: current_block.time,chain_id: current_block.chain_id});
+    goto_stage(&mut router, &game_addr, StageName::ClaimAirdrop);
 
     // Address 1 claim the correct ammount and verify balances and winners numbers.
     let claim_airdrop_msg = ExecuteMsg::ClaimAirdrop {
@@ -1323,11 +2925,10 @@ fn withdraw_airdrop_and_prize() {
         ).unwrap();
 
     // Trigger claim prize stage start.
-    let current_block = router.block_info();
-    router.set_block(BlockInfo {height: 202_001, time: current_block.time, chain_id: current_block.chain_id});
+    goto_stage(&mut router, &game_addr, StageName::ClaimPrize);
 
     // Can claim prize if winning bid.
-    let claim_prize_msg = ExecuteMsg::ClaimPrize {};
+    let claim_prize_msg = ExecuteMsg::ClaimPrize { amount: None, proof: None, then: None };
     let _res = router
         .execute_contract(
             address_1.clone(),
@@ -1348,7 +2949,7 @@ fn withdraw_airdrop_and_prize() {
     let withdraw_address = Addr::unchecked("withdraw0000");
 
     // Just the owner can withdraw.
-    let claim_airdrop_msg = ExecuteMsg::WithdrawAirdrop { address: withdraw_address.clone() };
+    let claim_airdrop_msg = ExecuteMsg::WithdrawAirdrop { address: withdraw_address.clone(), amount: None };
     let err = router
         .execute_contract(
             address_1.clone(),
@@ -1360,7 +2961,7 @@ fn withdraw_airdrop_and_prize() {
     assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
 
     // Cannot withdraw if claim prize stage not ended.
-    let claim_airdrop_msg = ExecuteMsg::WithdrawAirdrop { address: withdraw_address.clone() };
+    let claim_airdrop_msg = ExecuteMsg::WithdrawAirdrop { address: withdraw_address.clone(), amount: None };
     let err = router
         .execute_contract(
             Addr::unchecked("owner0000"),
@@ -1381,11 +2982,61 @@ fn withdraw_airdrop_and_prize() {
     assert_eq!(bank_balance_withdraw.amount, Uint128::new(0));
     
     // Trigger claim prize stage end.
-    let current_block = router.block_info();
-    router.set_block(BlockInfo {height: 203_001, time: current_block.time, chain_id: current_block.chain_id});
+    advance_blocks(&mut router, 1_000);
+
+    // Funds are sent only to an activated treasury.
+    let err = router
+        .execute_contract(
+            Addr::unchecked("owner0000"),
+            game_addr.clone(),
+            &claim_airdrop_msg,
+            &[],
+        ).unwrap_err();
+
+    assert_eq!(
+        ContractError::TreasuryNotActive { address: withdraw_address.to_string() },
+        err.downcast().unwrap()
+    );
+    activate_treasury(&mut router, &game_addr, &withdraw_address);
+
+    // Check withdraw leftover airdrop in tranches.
+    let claim_airdrop_msg = ExecuteMsg::WithdrawAirdrop {
+        address: withdraw_address.clone(),
+        amount: Some(Uint128::new(100000)),
+    };
+    let _res = router
+        .execute_contract(
+            Addr::unchecked("owner0000"),
+            game_addr.clone(),
+            &claim_airdrop_msg,
+            &[],
+        ).unwrap();
+    let balance_withdraw = cw20_token
+        .balance::<App, Addr, MyCustomQuery>(&router, withdraw_address.clone())
+        .unwrap();
+
+    assert_eq!(balance_withdraw, Uint128::new(100000));
+    assert_eq!(get_game_amount(&router, &game_addr).total_withdrawn_airdrop, Uint128::new(100000));
+
+    let claim_airdrop_msg = ExecuteMsg::WithdrawAirdrop {
+        address: withdraw_address.clone(),
+        amount: Some(Uint128::new(389671)),
+    };
+    let err = router
+        .execute_contract(
+            Addr::unchecked("owner0000"),
+            game_addr.clone(),
+            &claim_airdrop_msg,
+            &[],
+        ).unwrap_err();
+
+    assert_eq!(
+        ContractError::InvalidWithdrawAmount { remaining: Uint128::new(389670) },
+        err.downcast().unwrap()
+    );
 
-    // Check withdraw leftover airdrop.
-    let claim_airdrop_msg = ExecuteMsg::WithdrawAirdrop { address: withdraw_address.clone() };
+    // The last tranche sweeps the remainder.
+    let claim_airdrop_msg = ExecuteMsg::WithdrawAirdrop { address: withdraw_address.clone(), amount: None };
     let _res = router
         .execute_contract(
             Addr::unchecked("owner0000"),
@@ -1399,6 +3050,19 @@ fn withdraw_airdrop_and_prize() {
     
     assert_eq!(balance_withdraw, Uint128::new(489670));
 
+    let err = router
+        .execute_contract(
+            Addr::unchecked("owner0000"),
+            game_addr.clone(),
+            &claim_airdrop_msg,
+            &[],
+        ).unwrap_err();
+
+    assert_eq!(
+        ContractError::InvalidWithdrawAmount { remaining: Uint128::zero() },
+        err.downcast().unwrap()
+    );
+
     // Check withdraw leftover prize.
     let claim_airdrop_msg = ExecuteMsg::WithdrawPrize { address: withdraw_address.clone() };
     let _res = router
@@ -1,6 +1,24 @@
+pub mod address;
+pub mod constants;
 pub mod contract;
 mod error;
+pub mod limits;
+pub mod math;
+pub mod merkle;
 pub mod msg;
+pub mod stage_math;
 pub mod state;
+pub mod validation;
 mod integration_test;
+mod model_test;
 pub use crate::error::ContractError;
+
+/// Proof checks of `ClaimAirdrop`, for off-chain tools using the crate as a library.
+#[cfg(feature = "library")]
+pub use crate::merkle::{compute_leaf, verify_airdrop_proof, Hash};
+
+/// Marker checked by wasmd on upload, so that the contract is rejected by chains that do
+/// not support the cosmwasm 1.1 queries.
+#[cfg(all(feature = "cosmwasm_1_1", target_arch = "wasm32", not(feature = "library")))]
+#[no_mangle]
+extern "C" fn requires_cosmwasm_1_1() {}
@@ -0,0 +1,86 @@
+use cw_utils::Duration;
+
+use crate::error::ContractError;
+use crate::state::ClaimCondition;
+
+/// Maximum number of nodes of a Merkle proof, enough for trees of 2^32 leaves. The
+/// owner can lower it with `max_proof_length`.
+pub const MAX_PROOF_NODES: u32 = 32;
+
+/// Maximum number of items processed by a single paginated execute message. The owner
+/// can lower it with `max_batch_size`.
+pub const MAX_BATCH_SIZE: u32 = 30;
+
+/// Maximum length in bytes of the free-text fields stored by the contract.
+pub const MAX_MEMO_LENGTH: usize = 512;
+
+/// Time a withdrawal destination waits between its registration and its activation,
+/// if the game does not set `treasury_timelock`.
+pub const DEFAULT_TREASURY_TIMELOCK: Duration = Duration::Time(24 * 60 * 60);
+
+/// Maximum number of addresses accepted by a single admin message. The owner can lower
+/// it with `max_addresses_per_call`.
+pub const MAX_ADDRESSES_PER_CALL: u32 = 20;
+
+/// Maximum number of conditions, combinators included, of a claim condition. Every
+/// external condition costs a query on each claim.
+pub const MAX_CONDITION_SIZE: usize = 8;
+
+/// Fails if the proof has more than `max` nodes.
+pub fn check_proof_length(proof: &[String], max: u32) -> Result<(), ContractError> {
+    if proof.len() > max as usize {
+        return Err(ContractError::ProofTooLong { max });
+    }
+    Ok(())
+}
+
+/// Fails if the requested batch is larger than `max`.
+pub fn check_batch_size(limit: u32, max: u32) -> Result<(), ContractError> {
+    if limit > max {
+        return Err(ContractError::BatchTooLarge { max });
+    }
+    Ok(())
+}
+
+/// Fails if the text of `field` is longer than `MAX_MEMO_LENGTH`.
+pub fn check_memo_length(field: &str, text: &Option<String>) -> Result<(), ContractError> {
+    if text.as_ref().is_some_and(|text| text.len() > MAX_MEMO_LENGTH) {
+        return Err(ContractError::MemoTooLong {
+            field: field.to_string(),
+            max: MAX_MEMO_LENGTH,
+        });
+    }
+    Ok(())
+}
+
+/// Fails if the claim condition has more than `MAX_CONDITION_SIZE` conditions.
+pub fn check_condition_size(condition: &ClaimCondition) -> Result<(), ContractError> {
+    if condition.size() > MAX_CONDITION_SIZE {
+        return Err(ContractError::ConditionTooLarge { max: MAX_CONDITION_SIZE });
+    }
+    Ok(())
+}
+
+/// Fails if more than `max` addresses are sent at once.
+pub fn check_addresses_count(addresses: &[String], max: u32) -> Result<(), ContractError> {
+    if addresses.len() > max as usize {
+        return Err(ContractError::TooManyAddresses { max });
+    }
+    Ok(())
+}
+
+/// Fails if a tunable limit is zero or above its ceiling.
+pub fn check_limit(limit: &str, value: u32, ceiling: u32) -> Result<(), ContractError> {
+    if value == 0 || value > ceiling {
+        return Err(ContractError::InvalidLimit { limit: limit.to_string(), max: ceiling });
+    }
+    Ok(())
+}
+
+/// Fails if the maximum length of the proofs is zero or above `MAX_PROOF_NODES`.
+pub fn check_max_proof_length(max_proof_length: u32) -> Result<(), ContractError> {
+    if max_proof_length == 0 || max_proof_length > MAX_PROOF_NODES {
+        return Err(ContractError::InvalidMaxProofLength { max: MAX_PROOF_NODES });
+    }
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use cosmwasm_std::{StdResult, Uint128};
+
+use crate::state::RoundingPolicy;
+
+/// Amounts resulting from the split of a prize pool for a single claim.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrizeSplit {
+    /// Amount paid to the claimer.
+    pub to_claimer: Uint128,
+    /// Amount paid to the treasury.
+    pub to_treasury: Uint128,
+}
+
+impl PrizeSplit {
+    /// Amount leaving the pool with the claim.
+    pub fn total(&self) -> Uint128 {
+        self.to_claimer + self.to_treasury
+    }
+}
+
+/// Splits `total` evenly among `winners` and returns what a single claim has to pay.
+/// Every winner receives the floor of the division, the remainder (dust) is assigned
+/// according to the rounding policy when `first_claim` is true, otherwise it stays in
+/// the pool and can be withdrawn by the owner.
+pub fn split_evenly(
+    total: Uint128,
+    winners: Uint128,
+    policy: &RoundingPolicy,
+    first_claim: bool,
+) -> StdResult<PrizeSplit> {
+    let share = total.checked_div(winners)?;
+    let dust = total.checked_rem(winners)?;
+
+    let split = match policy {
+        RoundingPolicy::DustToFirstClaimer if first_claim => PrizeSplit {
+            to_claimer: share + dust,
+            to_treasury: Uint128::zero(),
+        },
+        RoundingPolicy::DustToTreasury { .. } if first_claim => PrizeSplit {
+            to_claimer: share,
+            to_treasury: dust,
+        },
+        _ => PrizeSplit {
+            to_claimer: share,
+            to_treasury: Uint128::zero(),
+        },
+    };
+    Ok(split)
+}
+
+/// Order of magnitude of `amount`, so that badges can group the claimers without
+/// publishing the exact amounts. Zero for amounts below 10.
+pub fn amount_band(amount: Uint128) -> u32 {
+    let mut band = 0;
+    let mut amount = amount.u128();
+    while amount >= 10 {
+        amount /= 10;
+        band += 1;
+    }
+    band
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::Addr;
+
+    fn policies() -> Vec<RoundingPolicy> {
+        vec![
+            RoundingPolicy::FloorToWinner,
+            RoundingPolicy::DustToTreasury { address: Addr::unchecked("treasury") },
+            RoundingPolicy::DustToFirstClaimer,
+        ]
+    }
+
+    /// Total paid by the pool once all the winners claimed.
+    fn paid(total: u128, winners: u128, policy: &RoundingPolicy) -> Uint128 {
+        (0..winners)
+            .map(|i| split_evenly(total.into(), winners.into(), policy, i == 0).unwrap().total())
+            .sum()
+    }
+
+    #[test]
+    fn single_winner_takes_all() {
+        for policy in policies() {
+            let split = split_evenly(Uint128::new(101), Uint128::new(1), &policy, true).unwrap();
+            assert_eq!(split.to_claimer, Uint128::new(101));
+            assert_eq!(split.to_treasury, Uint128::zero());
+        }
+    }
+
+    #[test]
+    fn dust_assignment() {
+        let total = Uint128::new(101);
+        let winners = Uint128::new(4);
+
+        let floor = RoundingPolicy::FloorToWinner;
+        assert_eq!(paid(101, 4, &floor), Uint128::new(100));
+
+        let treasury = RoundingPolicy::DustToTreasury { address: Addr::unchecked("treasury") };
+        let first = split_evenly(total, winners, &treasury, true).unwrap();
+        assert_eq!(first, PrizeSplit { to_claimer: Uint128::new(25), to_treasury: Uint128::new(1) });
+        let next = split_evenly(total, winners, &treasury, false).unwrap();
+        assert_eq!(next, PrizeSplit { to_claimer: Uint128::new(25), to_treasury: Uint128::zero() });
+        assert_eq!(paid(101, 4, &treasury), total);
+
+        let first_claimer = RoundingPolicy::DustToFirstClaimer;
+        let first = split_evenly(total, winners, &first_claimer, true).unwrap();
+        assert_eq!(first.to_claimer, Uint128::new(26));
+        assert_eq!(paid(101, 4, &first_claimer), total);
+    }
+
+    #[test]
+    fn prize_lower_than_winners() {
+        for policy in policies() {
+            let next = split_evenly(Uint128::new(3), Uint128::new(5), &policy, false).unwrap();
+            assert_eq!(next.total(), Uint128::zero());
+        }
+        let first_claimer = RoundingPolicy::DustToFirstClaimer;
+        let first = split_evenly(Uint128::new(3), Uint128::new(5), &first_claimer, true).unwrap();
+        assert_eq!(first.to_claimer, Uint128::new(3));
+    }
+
+    #[test]
+    fn huge_pool() {
+        let winners = Uint128::new(7);
+        let share = Uint128::MAX / winners;
+        let dust = Uint128::new(u128::MAX % 7);
+
+        let floor = split_evenly(Uint128::MAX, winners, &RoundingPolicy::FloorToWinner, true).unwrap();
+        assert_eq!(floor.to_claimer, share);
+
+        let first_claimer = RoundingPolicy::DustToFirstClaimer;
+        let first = split_evenly(Uint128::MAX, winners, &first_claimer, true).unwrap();
+        assert_eq!(first.to_claimer, share + dust);
+        assert_eq!(paid(u128::MAX, 7, &first_claimer), Uint128::MAX);
+    }
+
+    #[test]
+    fn no_winners() {
+        let res = split_evenly(Uint128::new(10), Uint128::zero(), &RoundingPolicy::FloorToWinner, true);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn amount_bands() {
+        assert_eq!(0, amount_band(Uint128::zero()));
+        assert_eq!(0, amount_band(Uint128::new(9)));
+        assert_eq!(1, amount_band(Uint128::new(10)));
+        assert_eq!(3, amount_band(Uint128::new(1_500)));
+        assert_eq!(38, amount_band(Uint128::MAX));
+    }
+}
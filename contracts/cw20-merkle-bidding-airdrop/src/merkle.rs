@@ -0,0 +1,226 @@
+use cosmwasm_std::Uint128;
+use sha2::Digest;
+
+use crate::error::ContractError;
+
+/// Node of the Merkle trees used by the contract.
+pub type Hash = [u8; 32];
+
+/// Hash of a leaf, computed over the concatenation of the account address and the
+/// value associated to it (the airdrop amount or the bin).
+pub fn leaf_hash(account: &str, value: &str) -> Hash {
+    domain_leaf_hash("", account, value)
+}
+
+/// Hash of a leaf whose preimage starts with `domain`, e.g. the contract address or a
+/// campaign id, so that the leaves of a deployment do not verify on another one sharing
+/// the allocation file. An empty domain gives the plain `leaf_hash`.
+pub fn domain_leaf_hash(domain: &str, account: &str, value: &str) -> Hash {
+    sha2::Sha256::digest(format!("{}{}{}", domain, account, value).as_bytes()).into()
+}
+
+/// Hash of two sibling nodes. Nodes are sorted before being hashed, so proofs don't
+/// need to carry the position of each node.
+pub fn hash_pair(a: &Hash, b: &Hash) -> Hash {
+    let mut hashes = [*a, *b];
+    hashes.sort_unstable();
+    sha2::Sha256::digest(&hashes.concat()).into()
+}
+
+/// Computes the root reached by walking the hex-encoded proof from the leaf.
+pub fn compute_root(leaf: Hash, proof: Vec<String>) -> Result<Hash, ContractError> {
+    proof.into_iter().try_fold(leaf, |hash, p| {
+        let mut proof_buf = [0; 32];
+        hex::decode_to_slice(p, &mut proof_buf)?;
+        Ok(hash_pair(&hash, &proof_buf))
+    })
+}
+
+/// Checks that the proof links the leaf to the hex-encoded root.
+pub fn verify_proof(leaf: Hash, proof: Vec<String>, root: &str) -> Result<bool, ContractError> {
+    let mut root_buf: Hash = [0; 32];
+    hex::decode_to_slice(root, &mut root_buf)?;
+    Ok(compute_root(leaf, proof)? == root_buf)
+}
+
+/// Leaf of the airdrop tree for the allocation of `amount` to `account`, as hashed by
+/// `ClaimAirdrop` in the leaf `domain` of the game, empty with the plain leaf format.
+pub fn compute_leaf(domain: &str, account: &str, amount: Uint128) -> Hash {
+    domain_leaf_hash(domain, account, &amount.to_string())
+}
+
+/// Checks that the hex-encoded proofs link the airdrop allocation of `account` to the
+/// hex-encoded root, as `ClaimAirdrop` does. Fails if a proof node or the root is not
+/// valid hex of 32 bytes.
+pub fn verify_airdrop_proof(
+    domain: &str,
+    account: &str,
+    amount: Uint128,
+    proofs: &[String],
+    root: &str,
+) -> Result<bool, ContractError> {
+    verify_proof(compute_leaf(domain, account, amount), proofs.to_vec(), root)
+}
+
+/// Merkle tree built off-chain from the list of leaves. Leaves are sorted and an odd
+/// node at the end of a layer is promoted to the next one, matching the trees
+/// generated by the `helpers` client.
+pub struct MerkleTree {
+    layers: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    pub fn new(mut leaves: Vec<Hash>) -> Self {
+        leaves.sort_unstable();
+        let mut layers = vec![leaves];
+        while layers.last().is_some_and(|layer| layer.len() > 1) {
+            let next = layers
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next);
+        }
+        MerkleTree { layers }
+    }
+
+    /// Hex-encoded root of the tree. Empty if the tree has no leaves.
+    pub fn root(&self) -> String {
+        self.layers
+            .last()
+            .and_then(|layer| layer.first())
+            .map(hex::encode)
+            .unwrap_or_default()
+    }
+
+    /// Hex-encoded proof for the given leaf, None if the leaf is not in the tree.
+    pub fn proof(&self, leaf: &Hash) -> Option<Vec<String>> {
+        let mut index = self.layers[0].iter().position(|l| l == leaf)?;
+        let mut proof = vec![];
+        for layer in &self.layers[..self.layers.len() - 1] {
+            let sibling = index ^ 1;
+            if sibling < layer.len() {
+                proof.push(hex::encode(layer[sibling]));
+            }
+            index /= 2;
+        }
+        Some(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::from_slice;
+    use serde::Deserialize;
+
+    const AIRDROP_LIST: &[u8] = include_bytes!("../testdata/airdrop_list.json");
+    const AIRDROP_TEST_DATA: &[u8] = include_bytes!("../testdata/airdrop_test_data.json");
+    const GAME_LIST: &[u8] = include_bytes!("../testdata/airdrop_game_list.json");
+    const GAME_TEST_DATA: &[u8] = include_bytes!("../testdata/airdrop_game_test_data.json");
+
+    #[derive(Deserialize)]
+    struct Entry {
+        address: String,
+        amount: String,
+    }
+
+    #[derive(Deserialize)]
+    struct Account {
+        account: String,
+        amount: String,
+        proofs: Vec<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct Encoded {
+        root: String,
+        addresses: Vec<Account>,
+    }
+
+    fn check_tree(list: &[u8], test_data: &[u8]) {
+        let list: Vec<Entry> = from_slice(list).unwrap();
+        let test_data: Encoded = from_slice(test_data).unwrap();
+
+        let tree = MerkleTree::new(
+            list.iter()
+                .map(|e| leaf_hash(&e.address, &e.amount))
+                .collect(),
+        );
+        assert_eq!(test_data.root, tree.root());
+
+        for account in test_data.addresses {
+            let leaf = leaf_hash(&account.account, &account.amount);
+            assert_eq!(Some(account.proofs.clone()), tree.proof(&leaf));
+            assert!(verify_proof(leaf, account.proofs, &test_data.root).unwrap());
+        }
+    }
+
+    #[test]
+    fn tree_matches_helpers() {
+        check_tree(AIRDROP_LIST, AIRDROP_TEST_DATA);
+        check_tree(GAME_LIST, GAME_TEST_DATA);
+    }
+
+    #[test]
+    fn verify_wrong_leaf() {
+        let test_data: Encoded = from_slice(AIRDROP_TEST_DATA).unwrap();
+        let account = &test_data.addresses[0];
+        let leaf = leaf_hash(&account.account, "1");
+        assert!(!verify_proof(leaf, account.proofs.clone(), &test_data.root).unwrap());
+        assert!(MerkleTree::new(vec![]).proof(&leaf).is_none());
+    }
+
+    #[test]
+    fn verify_airdrop_allocation() {
+        let test_data: Encoded = from_slice(AIRDROP_TEST_DATA).unwrap();
+        for account in &test_data.addresses {
+            let amount: Uint128 = account.amount.parse::<u128>().unwrap().into();
+            assert_eq!(leaf_hash(&account.account, &account.amount), compute_leaf("", &account.account, amount));
+            assert!(verify_airdrop_proof("", &account.account, amount, &account.proofs, &test_data.root).unwrap());
+
+            let wrong_amount = amount + Uint128::new(1);
+            assert!(!verify_airdrop_proof("", &account.account, wrong_amount, &account.proofs, &test_data.root).unwrap());
+        }
+
+        let account = &test_data.addresses[0];
+        assert!(verify_airdrop_proof("", &account.account, Uint128::new(1), &account.proofs, "zz").is_err());
+    }
+
+    #[test]
+    fn domain_separation() {
+        let accounts = [
+            ("wasm1k9hwzxs889jpvd7env8z49gad3a3633vg350tq", 100u128),
+            ("wasm1uy9ucvgerneekxpnfwyfnpxvlsx5dzdpf0mzjd", 1010),
+        ];
+        let tree = |domain: &str| {
+            MerkleTree::new(
+                accounts
+                    .iter()
+                    .map(|(account, amount)| compute_leaf(domain, account, Uint128::new(*amount)))
+                    .collect(),
+            )
+        };
+        let (account, amount) = (accounts[0].0, Uint128::new(accounts[0].1));
+
+        // The preimage is the concatenation of the domain, the account and the amount.
+        let leaf = compute_leaf("spring", account, amount);
+        let expected: Hash = sha2::Sha256::digest(format!("spring{}100", account).as_bytes()).into();
+        assert_eq!(leaf, expected);
+        assert_ne!(leaf, compute_leaf("", account, amount));
+
+        // A proof of a deployment does not verify on another one sharing the allocation.
+        let first = tree("wasm1game0");
+        let proof = first.proof(&compute_leaf("wasm1game0", account, amount)).unwrap();
+        assert!(verify_airdrop_proof("wasm1game0", account, amount, &proof, &first.root()).unwrap());
+        assert!(!verify_airdrop_proof("wasm1game1", account, amount, &proof, &first.root()).unwrap());
+        assert!(!verify_airdrop_proof("", account, amount, &proof, &first.root()).unwrap());
+        assert_ne!(first.root(), tree("wasm1game1").root());
+        assert_ne!(first.root(), tree("").root());
+    }
+}
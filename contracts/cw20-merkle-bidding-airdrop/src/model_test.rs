@@ -0,0 +1,620 @@
+#![cfg(test)]
+
+//! Model-based test of the game. Random sequences of bids, root registrations, claims
+//! and withdraws run against the contract and against a reference model of the game:
+//! after every message the outcome, the payouts and the state of both must match, and
+//! the contract must always hold the funds it still owes.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, BankMsg, Coin, ContractResult, CosmosMsg, Env, Order, OwnedDeps,
+    Storage, SystemError, SystemResult, Uint128, WasmMsg, WasmQuery,
+};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
+use cw_utils::{Duration, Scheduled};
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use crate::constants::TEST_DENOM;
+use crate::contract::{execute, instantiate, query};
+use crate::merkle::{compute_leaf, leaf_hash, Hash, MerkleTree};
+use crate::msg::{BinDistributionResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::state::{Stage, TreeMetadata, BIDS, COUNTERS};
+
+const PLAYERS: usize = 5;
+const BINS: u8 = 3;
+const TICKET_PRICE: u128 = 10;
+const OWNER: &str = "owner0000";
+const TOKEN: &str = "token0000";
+
+type MockDeps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+fn player(index: usize) -> String {
+    format!("player{:04}", index)
+}
+
+/// Stages of the game, one after the other, ten blocks each.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Phase {
+    Bid,
+    ClaimAirdrop,
+    ClaimPrize,
+    Ended,
+}
+
+impl Phase {
+    fn height(self) -> u64 {
+        200_000 + 10 * self as u64
+    }
+
+    fn next(self) -> Phase {
+        match self {
+            Phase::Bid => Phase::ClaimAirdrop,
+            Phase::ClaimAirdrop => Phase::ClaimPrize,
+            Phase::ClaimPrize | Phase::Ended => Phase::Ended,
+        }
+    }
+
+    fn stage(self) -> Stage {
+        Stage {
+            start: Scheduled::AtHeight(self.height()),
+            duration: Duration::Height(10),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Op {
+    Bid { player: usize, bin: u8, extra: u128 },
+    ChangeBid { player: usize, bin: u8 },
+    RemoveBid { player: usize },
+    /// Registers the roots of the current bids, then funds and opens the claims.
+    RegisterRoots { winning_bin: u8 },
+    ClaimAirdrop { player: usize },
+    ClaimPrize { player: usize },
+    WithdrawAirdrop,
+    WithdrawPrize,
+    NextStage,
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let player = 0..PLAYERS;
+    let bin = 0..=BINS + 1;
+    prop_oneof![
+        3 => (player.clone(), bin.clone(), 0..20u128)
+            .prop_map(|(player, bin, extra)| Op::Bid { player, bin, extra }),
+        2 => (player.clone(), bin).prop_map(|(player, bin)| Op::ChangeBid { player, bin }),
+        1 => player.clone().prop_map(|player| Op::RemoveBid { player }),
+        1 => (0..=BINS).prop_map(|winning_bin| Op::RegisterRoots { winning_bin }),
+        2 => player.clone().prop_map(|player| Op::ClaimAirdrop { player }),
+        2 => player.prop_map(|player| Op::ClaimPrize { player }),
+        1 => Just(Op::WithdrawAirdrop),
+        1 => Just(Op::WithdrawPrize),
+        1 => Just(Op::NextStage),
+    ]
+}
+
+/// Coins leaving the contract with a message.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Payout {
+    Native { to: String, amount: u128 },
+    Cw20 { to: String, amount: u128 },
+}
+
+/// Balances of the contract, moved only by the funds attached to the messages and by
+/// the payouts of their responses.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Balances {
+    native: u128,
+    cw20: u128,
+}
+
+struct Roots {
+    /// Leaves `(player, bin)` of the game tree, the bids on the winning bin when the
+    /// roots were registered.
+    game: BTreeSet<(usize, u8)>,
+    game_tree: MerkleTree,
+    total_game: u128,
+}
+
+/// Reference model of the game.
+struct Model {
+    phase: Phase,
+    airdrop: Vec<u128>,
+    game_amount: u128,
+    bids: BTreeMap<usize, u8>,
+    ticket_prize: u128,
+    roots: Option<Roots>,
+    airdrop_claimed: BTreeSet<usize>,
+    /// Winners, with whether they claimed the prize.
+    winners: BTreeMap<usize, bool>,
+    claimed_airdrop: u128,
+    claimed_prize: u128,
+    withdrawn_airdrop: u128,
+    withdrawn_prize: u128,
+    failsafe: bool,
+    balances: Balances,
+}
+
+/// Outcome expected from a message: the payouts or a failure.
+type Outcome = Result<Vec<Payout>, ()>;
+
+impl Model {
+    fn new(airdrop: Vec<u128>, game_amount: u128) -> Self {
+        Model {
+            phase: Phase::Bid,
+            airdrop,
+            game_amount,
+            bids: BTreeMap::new(),
+            ticket_prize: 0,
+            roots: None,
+            airdrop_claimed: BTreeSet::new(),
+            winners: BTreeMap::new(),
+            claimed_airdrop: 0,
+            claimed_prize: 0,
+            withdrawn_airdrop: 0,
+            withdrawn_prize: 0,
+            failsafe: false,
+            balances: Balances::default(),
+        }
+    }
+
+    fn total_airdrop(&self) -> u128 {
+        self.airdrop.iter().sum()
+    }
+
+    /// Airdrop and game amounts of the registered roots.
+    fn registered_totals(&self) -> u128 {
+        self.roots.as_ref().map_or(0, |roots| self.total_airdrop() + roots.total_game)
+    }
+
+    /// Operations moving funds fail once the failsafe is active, and the claims
+    /// without roots trigger it once the claims started.
+    fn check_failsafe(&mut self, claim: bool) -> Option<Outcome> {
+        if self.failsafe {
+            return Some(Err(()));
+        }
+        if claim && self.roots.is_none() && self.phase >= Phase::ClaimAirdrop {
+            self.failsafe = true;
+            return Some(Ok(vec![]));
+        }
+        None
+    }
+
+    fn bid(&mut self, player: usize, bin: u8, extra: u128) -> Outcome {
+        if self.phase != Phase::Bid || self.bids.contains_key(&player) || bin > BINS {
+            return Err(());
+        }
+        self.bids.insert(player, bin);
+        self.ticket_prize += TICKET_PRICE;
+        self.balances.native += TICKET_PRICE;
+        Ok(vec![Payout::Native { to: self::player(player), amount: extra }])
+    }
+
+    fn change_bid(&mut self, player: usize, bin: u8) -> Outcome {
+        if self.phase != Phase::Bid || !self.bids.contains_key(&player) || bin > BINS {
+            return Err(());
+        }
+        self.bids.insert(player, bin);
+        Ok(vec![])
+    }
+
+    fn remove_bid(&mut self, player: usize) -> Outcome {
+        if let Some(outcome) = self.check_failsafe(false) {
+            return outcome;
+        }
+        if self.phase != Phase::Bid || self.bids.remove(&player).is_none() {
+            return Err(());
+        }
+        self.ticket_prize -= TICKET_PRICE;
+        self.balances.native -= TICKET_PRICE;
+        Ok(vec![Payout::Native { to: self::player(player), amount: TICKET_PRICE }])
+    }
+
+    fn register_roots(&mut self, winning_bin: u8) -> Outcome {
+        // The roots are replaced until the first claim, and the claim window closes
+        // with the claim airdrop stage.
+        if self.phase >= Phase::ClaimPrize || (self.roots.is_some() && !self.airdrop_claimed.is_empty()) {
+            return Err(());
+        }
+        let game: BTreeSet<_> = self
+            .bids
+            .iter()
+            .filter(|(_, bin)| **bin == winning_bin)
+            .map(|(player, bin)| (*player, *bin))
+            .collect();
+        // The game tree always holds a leaf, so that its root is valid without winners.
+        let game_tree = MerkleTree::new(
+            game.iter()
+                .map(|(player, bin)| leaf_hash(&self::player(*player), &bin.to_string()))
+                .chain([leaf_hash("nobody0000", &winning_bin.to_string())])
+                .collect(),
+        );
+        self.roots = Some(Roots { game, game_tree, total_game: self.game_amount });
+        Ok(vec![])
+    }
+
+    fn claim_airdrop(&mut self, player: usize) -> Outcome {
+        if let Some(outcome) = self.check_failsafe(true) {
+            return outcome;
+        }
+        let roots = match &self.roots {
+            Some(roots) if self.phase == Phase::ClaimAirdrop => roots,
+            _ => return Err(()),
+        };
+        if !self.airdrop_claimed.insert(player) {
+            return Err(());
+        }
+        if let Some(bin) = self.bids.get(&player) {
+            if roots.game.contains(&(player, *bin)) {
+                self.winners.insert(player, false);
+            }
+        }
+        let amount = self.airdrop[player];
+        self.claimed_airdrop += amount;
+        self.balances.cw20 -= amount;
+        Ok(vec![Payout::Cw20 { to: self::player(player), amount }])
+    }
+
+    fn claim_prize(&mut self, player: usize) -> Outcome {
+        if let Some(outcome) = self.check_failsafe(true) {
+            return outcome;
+        }
+        if self.phase != Phase::ClaimPrize || self.winners.get(&player) != Some(&false) {
+            return Err(());
+        }
+        // Winners share evenly the tickets and the game amount, the dust stays.
+        let winners = self.winners.len() as u128;
+        let ticket_share = self.ticket_prize / winners;
+        let airdrop_share = self.roots.as_ref().unwrap().total_game / winners;
+        self.winners.insert(player, true);
+        self.claimed_prize += ticket_share;
+        self.claimed_airdrop += airdrop_share;
+        self.balances.native -= ticket_share;
+        self.balances.cw20 -= airdrop_share;
+        Ok(vec![
+            Payout::Native { to: self::player(player), amount: ticket_share },
+            Payout::Cw20 { to: self::player(player), amount: airdrop_share },
+        ])
+    }
+
+    fn withdraw_airdrop(&mut self) -> Outcome {
+        if let Some(outcome) = self.check_failsafe(false) {
+            return outcome;
+        }
+        let remaining = self.registered_totals() - self.claimed_airdrop - self.withdrawn_airdrop;
+        if self.phase != Phase::Ended || remaining == 0 {
+            return Err(());
+        }
+        self.withdrawn_airdrop += remaining;
+        self.balances.cw20 -= remaining;
+        Ok(vec![Payout::Cw20 { to: OWNER.to_string(), amount: remaining }])
+    }
+
+    fn withdraw_prize(&mut self) -> Outcome {
+        if let Some(outcome) = self.check_failsafe(false) {
+            return outcome;
+        }
+        let amount = self.ticket_prize - self.claimed_prize - self.withdrawn_prize;
+        if self.phase != Phase::Ended || amount == 0 {
+            return Err(());
+        }
+        self.withdrawn_prize += amount;
+        self.balances.native -= amount;
+        Ok(vec![Payout::Native { to: OWNER.to_string(), amount }])
+    }
+}
+
+/// Game contract, with the balances moved by the messages it executed.
+struct Game {
+    deps: MockDeps,
+    balances: Balances,
+}
+
+impl Game {
+    fn new() -> Self {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            owner: Some(OWNER.to_string()),
+            cw20_token_address: TOKEN.to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin::new(TICKET_PRICE, TEST_DENOM),
+            bins: BINS,
+            stage_bid: Phase::Bid.stage().into(),
+            stage_claim_airdrop: Phase::ClaimAirdrop.stage().into(),
+            stage_claim_prize: Phase::ClaimPrize.stage().into(),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: Some(Duration::Height(0)),
+            withdrawal_guard: None,
+        };
+        let mut env = mock_env();
+        env.block.height = Phase::Bid.height() - 1;
+        instantiate(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), msg).unwrap();
+
+        // The owner withdraws to itself.
+        let msg = ExecuteMsg::SetTreasury { addr: OWNER.to_string() };
+        execute(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), msg).unwrap();
+        let msg = ExecuteMsg::ActivateTreasury { addr: OWNER.to_string() };
+        execute(deps.as_mut(), env, mock_info(OWNER, &[]), msg).unwrap();
+        Game { deps, balances: Balances::default() }
+    }
+
+    /// Executes the message as a transaction: a failing message, or one paying more
+    /// than the contract holds, leaves the storage and the balances untouched.
+    fn execute(&mut self, env: &Env, sender: &str, funds: u128, msg: ExecuteMsg) -> Result<Vec<Payout>, String> {
+        let storage = self.deps.storage.range(None, None, Order::Ascending).collect::<Vec<_>>();
+        let funds = if funds == 0 { vec![] } else { vec![Coin::new(funds, TEST_DENOM)] };
+        let res = execute(self.deps.as_mut(), env.clone(), mock_info(sender, &funds), msg)
+            .map_err(|err| err.to_string())
+            .and_then(|res| {
+                let mut balances = self.balances;
+                balances.native += funds.iter().map(|coin| coin.amount.u128()).sum::<u128>();
+                let payouts = payouts(&res.messages.into_iter().map(|msg| msg.msg).collect::<Vec<_>>());
+                for payout in &payouts {
+                    let (balance, amount) = match payout {
+                        Payout::Native { amount, .. } => (&mut balances.native, amount),
+                        Payout::Cw20 { amount, .. } => (&mut balances.cw20, amount),
+                    };
+                    *balance = balance.checked_sub(*amount).ok_or_else(|| "insufficient funds".to_string())?;
+                }
+                self.balances = balances;
+                Ok(payouts)
+            });
+        if res.is_err() {
+            let keys = self.deps.storage.range(None, None, Order::Ascending).map(|(key, _)| key).collect::<Vec<_>>();
+            for key in keys {
+                self.deps.storage.remove(&key);
+            }
+            for (key, value) in storage {
+                self.deps.storage.set(&key, &value);
+            }
+        }
+        res
+    }
+
+    /// Tops up the cw20 balance of the game to `required`, as the owner funding it.
+    fn fund_cw20(&mut self, required: u128) {
+        self.balances.cw20 = self.balances.cw20.max(required);
+        let balance = Uint128::new(self.balances.cw20);
+        self.deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == TOKEN => {
+                match from_binary(msg) {
+                    Ok(Cw20QueryMsg::Balance { .. }) => {
+                        SystemResult::Ok(ContractResult::Ok(to_binary(&BalanceResponse { balance }).unwrap()))
+                    }
+                    _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "cw20".to_string() }),
+                }
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "wasm".to_string() }),
+        });
+    }
+}
+
+/// Payouts of a response, without the empty ones.
+fn payouts(msgs: &[CosmosMsg]) -> Vec<Payout> {
+    let mut payouts = msgs
+        .iter()
+        .map(|msg| match msg {
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                assert!(amount.iter().all(|coin| coin.denom == TEST_DENOM));
+                let amount = amount.iter().map(|coin| coin.amount.u128()).sum();
+                Payout::Native { to: to_address.clone(), amount }
+            }
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, msg, funds }) if contract_addr == TOKEN && funds.is_empty() => {
+                match from_slice(msg).unwrap() {
+                    Cw20ExecuteMsg::Transfer { recipient, amount } => Payout::Cw20 { to: recipient, amount: amount.u128() },
+                    msg => panic!("unexpected cw20 message {:?}", msg),
+                }
+            }
+            msg => panic!("unexpected message {:?}", msg),
+        })
+        .filter(|payout| !matches!(payout, Payout::Native { amount: 0, .. } | Payout::Cw20 { amount: 0, .. }))
+        .collect::<Vec<_>>();
+    payouts.sort();
+    payouts
+}
+
+fn airdrop_leaf(player: usize, amount: u128) -> Hash {
+    compute_leaf("", &self::player(player), Uint128::new(amount))
+}
+
+fn run(airdrop: Vec<u128>, game_amount: u128, ops: Vec<Op>) -> Result<(), TestCaseError> {
+    let mut model = Model::new(airdrop.clone(), game_amount);
+    let mut game = Game::new();
+    let airdrop_tree = MerkleTree::new(airdrop.iter().enumerate().map(|(i, amount)| airdrop_leaf(i, *amount)).collect());
+
+    for op in ops {
+        let mut env = mock_env();
+        env.block.height = model.phase.height();
+        let (expected, res) = match op.clone() {
+            Op::Bid { player, bin, extra } => {
+                let msg = ExecuteMsg::Bid { bin, pubkey_proof: None, ticket_id: None };
+                (model.bid(player, bin, extra), game.execute(&env, &self::player(player), TICKET_PRICE + extra, msg))
+            }
+            Op::ChangeBid { player, bin } => {
+                let msg = ExecuteMsg::ChangeBid { bin };
+                (model.change_bid(player, bin), game.execute(&env, &self::player(player), 0, msg))
+            }
+            Op::RemoveBid { player } => {
+                (model.remove_bid(player), game.execute(&env, &self::player(player), 0, ExecuteMsg::RemoveBid {}))
+            }
+            Op::RegisterRoots { winning_bin } => {
+                let expected = model.register_roots(winning_bin);
+                let game_root = match (&expected, &model.roots) {
+                    (Ok(_), Some(roots)) => roots.game_tree.root(),
+                    _ => hex::encode([0u8; 32]),
+                };
+                let msg = ExecuteMsg::RegisterMerkleRoots {
+                    merkle_root_airdrop: airdrop_tree.root(),
+                    total_amount_airdrop: Some(Uint128::new(model.total_airdrop())),
+                    merkle_root_game: game_root,
+                    total_amount_game: Some(Uint128::new(game_amount)),
+                    tree_metadata: TreeMetadata {
+                        airdrop_leaf_count: PLAYERS as u64,
+                        game_leaf_count: 1,
+                        allocation_checksum: hex::encode([1u8; 32]),
+                    },
+                    overwrite: Some(true),
+                    secondary_payout: None,
+                    claim_condition: None,
+                };
+                let mut res = game.execute(&env, OWNER, 0, msg);
+                if res.is_ok() {
+                    game.fund_cw20(model.registered_totals());
+                    model.balances.cw20 = game.balances.cw20.max(model.balances.cw20);
+                    res = game.execute(&env, OWNER, 0, ExecuteMsg::OpenClaims {});
+                }
+                (expected, res)
+            }
+            Op::ClaimAirdrop { player } => {
+                let amount = airdrop[player];
+                let proof_game = match (&model.roots, model.bids.get(&player)) {
+                    (Some(roots), Some(bin)) if roots.game.contains(&(player, *bin)) => {
+                        roots.game_tree.proof(&leaf_hash(&self::player(player), &bin.to_string())).unwrap()
+                    }
+                    _ => vec![],
+                };
+                let msg = ExecuteMsg::ClaimAirdrop {
+                    amount: Uint128::new(amount),
+                    proof_airdrop: airdrop_tree.proof(&airdrop_leaf(player, amount)).unwrap(),
+                    proof_game,
+                };
+                (model.claim_airdrop(player), game.execute(&env, &self::player(player), 0, msg))
+            }
+            Op::ClaimPrize { player } => {
+                let msg = ExecuteMsg::ClaimPrize { amount: None, proof: None, then: None };
+                (model.claim_prize(player), game.execute(&env, &self::player(player), 0, msg))
+            }
+            Op::WithdrawAirdrop => {
+                let msg = ExecuteMsg::WithdrawAirdrop { address: cosmwasm_std::Addr::unchecked(OWNER), amount: None };
+                (model.withdraw_airdrop(), game.execute(&env, OWNER, 0, msg))
+            }
+            Op::WithdrawPrize => {
+                let msg = ExecuteMsg::WithdrawPrize { address: cosmwasm_std::Addr::unchecked(OWNER) };
+                (model.withdraw_prize(), game.execute(&env, OWNER, 0, msg))
+            }
+            Op::NextStage => {
+                model.phase = model.phase.next();
+                (Ok(vec![]), Ok(vec![]))
+            }
+        };
+
+        // Same outcome and payouts.
+        let mut expected = expected.map(|payouts| {
+            payouts
+                .into_iter()
+                .filter(|payout| !matches!(payout, Payout::Native { amount: 0, .. } | Payout::Cw20 { amount: 0, .. }))
+                .collect::<Vec<_>>()
+        });
+        if let Ok(payouts) = &mut expected {
+            payouts.sort();
+        }
+        prop_assert_eq!(expected.is_ok(), res.is_ok(), "{:?} in {:?}: {:?}", op, model.phase, res);
+        if let (Ok(expected), Ok(payouts)) = (&expected, &res) {
+            prop_assert_eq!(expected, payouts, "{:?}", op);
+        }
+
+        // Same state.
+        let bids = BIDS
+            .range(&game.deps.storage, None, None, Order::Ascending)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let model_bids = model.bids.iter().map(|(player, bin)| (self::player(*player), *bin)).collect::<Vec<_>>();
+        prop_assert_eq!(bids.into_iter().map(|(address, bin)| (address.to_string(), bin)).collect::<Vec<_>>(), model_bids);
+
+        let res: BinDistributionResponse =
+            from_binary(&query(game.deps.as_ref(), env.clone(), QueryMsg::BinDistribution {}).unwrap()).unwrap();
+        for bin in res.bins {
+            let bids = model.bids.values().filter(|model_bin| **model_bin == bin.bin).count();
+            prop_assert_eq!(bin.bids as usize, bids, "bin {}", bin.bin);
+        }
+        prop_assert_eq!(res.total_bids as usize, model.bids.len());
+
+        let counters = COUNTERS.load(&game.deps.storage).unwrap();
+        prop_assert_eq!(counters.total_ticket_prize.u128(), model.ticket_prize);
+        prop_assert_eq!(counters.winners.u128(), model.winners.len() as u128);
+        prop_assert_eq!(counters.claimed_airdrop_amount.u128(), model.claimed_airdrop);
+        prop_assert_eq!(counters.claimed_prize_amount.u128(), model.claimed_prize);
+        prop_assert_eq!(counters.withdrawn_airdrop_amount.u128(), model.withdrawn_airdrop);
+        prop_assert_eq!(counters.withdrawn_prize_amount.u128(), model.withdrawn_prize);
+        prop_assert_eq!(game.balances, model.balances);
+
+        // Conservation of funds: until the withdraws the game holds the prize not
+        // claimed yet and, once funded, the airdrop not claimed yet.
+        if model.phase < Phase::Ended {
+            prop_assert!(game.balances.native >= model.ticket_prize - model.claimed_prize);
+            if model.roots.is_some() {
+                prop_assert!(game.balances.cw20 >= model.registered_totals() - model.claimed_airdrop);
+            }
+        }
+    }
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(128))]
+
+    #[test]
+    fn game_matches_model(
+        airdrop in vec(1..1_000u128, PLAYERS),
+        game_amount in 0..1_000u128,
+        ops in vec(op(), 1..80),
+    ) {
+        run(airdrop, game_amount, ops)?;
+    }
+}
+
+#[test]
+fn model_full_game() {
+    // A scripted game through every stage, so that the happy path is always covered.
+    let ops = vec![
+        Op::Bid { player: 0, bin: 1, extra: 5 },
+        Op::Bid { player: 1, bin: 2, extra: 0 },
+        Op::Bid { player: 2, bin: 1, extra: 0 },
+        Op::ChangeBid { player: 1, bin: 1 },
+        Op::Bid { player: 3, bin: 3, extra: 0 },
+        Op::RemoveBid { player: 3 },
+        Op::RegisterRoots { winning_bin: 1 },
+        Op::NextStage,
+        Op::ClaimAirdrop { player: 0 },
+        Op::ClaimAirdrop { player: 1 },
+        Op::ClaimAirdrop { player: 3 },
+        Op::NextStage,
+        Op::ClaimPrize { player: 0 },
+        Op::ClaimPrize { player: 1 },
+        Op::ClaimPrize { player: 2 },
+        Op::NextStage,
+        Op::WithdrawAirdrop,
+        Op::WithdrawPrize,
+        Op::WithdrawPrize,
+    ];
+    run(vec![100, 200, 300, 400, 500], 101, ops).unwrap();
+}
@@ -1,8 +1,14 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::state::Stage;
-use cosmwasm_std::{Addr, Uint128, Coin};
+use crate::state::{
+    BadgeRole, ClaimCondition, ClaimKind, CohortStats, ClaimRateLimit, LateBidPenalty, ClaimedAt, ClosedSnapshot, EligibilityHook, Failsafe, GameMetadata, GovernanceMode, KycGate, LeafFormat, NoWinnersPolicy, PayoutMode, PayoutShare, PrizeMode,
+    RoundingPolicy, SecondaryPayout, Stage, TreeMetadata, Withdrawal, WithdrawalGuard,
+};
+use crate::stage_math::after_block;
+use cosmwasm_std::{Addr, Binary, BlockInfo, Coin, Decimal, StdResult, Timestamp, Uint128};
+use cw20::Cw20ReceiveMsg;
+use cw_utils::{Duration, Scheduled};
 
 // ======================================================================================
 // Entrypoints data structures
@@ -13,16 +19,250 @@ pub struct InstantiateMsg {
     pub owner: Option<String>,
     /// Address of the token.
     pub cw20_token_address: String,
+    /// Native denom used to fund and pay the airdrop instead of the cw20 token,
+    /// e.g. the `ibc/...` denom of a token transferred over ICS-20.
+    pub airdrop_native_denom: Option<String>,
+    /// Optional external contract gating the airdrop claims.
+    pub eligibility_hook: Option<EligibilityHookMsg>,
     /// Price of the ticket to bid.
     pub ticket_price: Coin,
     /// The winning probability is associasted to the number of bins.
     pub bins: u8,
     /// Info related to the bidding stage.
-    pub stage_bid: Stage,
+    pub stage_bid: StageMsg,
     /// Info related to the airdrop claiming stage.
-    pub stage_claim_airdrop: Stage,
+    pub stage_claim_airdrop: StageMsg,
     /// Info related to the prize claiming stage.
-    pub stage_claim_prize: Stage,
+    pub stage_claim_prize: StageMsg,
+    /// Info used by front-ends to render the game.
+    pub metadata: Option<GameMetadata>,
+    /// How the prize of each winner is computed. Default to an even split.
+    pub prize_mode: Option<PrizeMode>,
+    /// Where the remainder of the prize divisions goes. Default to the owner withdrawal.
+    pub rounding_policy: Option<RoundingPolicy>,
+    /// Require a `PubkeyProof` with every bid, so that a key cannot bid from many addresses.
+    /// The key must be the account key of the bidder, so contract accounts cannot bid.
+    pub pubkey_bids: Option<bool>,
+    /// Contracts (e.g. vesting or DAO contracts) allowed to claim the airdrop on behalf of
+    /// the addresses they custody.
+    pub approved_proxies: Option<Vec<String>>,
+    /// Optional cap on the claims accepted in a window of blocks, to smooth the token
+    /// unlock velocity.
+    pub claim_rate_limit: Option<ClaimRateLimit>,
+    /// Optional ticket vendor contract whose tickets are accepted in place of the ticket
+    /// price.
+    pub ticket_vendor: Option<String>,
+    /// Maximum number of nodes of the Merkle proofs, at most `MAX_PROOF_NODES`.
+    /// Defaults to `MAX_PROOF_NODES`.
+    pub max_proof_length: Option<u32>,
+    /// Optional cw721 contract minting a non-transferable badge on every airdrop and prize
+    /// claim. The game must be its minter.
+    pub badge_contract: Option<String>,
+    /// Optional number of times every address can change its bid. Unlimited if not set.
+    pub max_bid_changes: Option<u8>,
+    /// Optional fee paid on top of the ticket by the bids placed at the end of the bid
+    /// stage.
+    pub late_bid_penalty: Option<LateBidPenaltyMsg>,
+    /// If set, the bins of the bids are not disclosed by the queries until the bid stage
+    /// ends.
+    pub private_bins: Option<bool>,
+    /// If set, the `Bid` query tells whether an address has a bid but not its bin until
+    /// the bid stage ends, except to the bidder proving the key attached to the bid.
+    pub hidden_bids: Option<bool>,
+    /// If set, the attributes carrying hashes and free text are not emitted, to trim the
+    /// size of the responses.
+    pub minimal_attributes: Option<bool>,
+    /// Optional time after the start of the claim prize stage during which the prizes are
+    /// locked and the owner can veto them.
+    pub prize_timelock: Option<Duration>,
+    /// How the cw20 payouts are delivered, `Transfer` to every recipient if not set.
+    pub payout_mode: Option<PayoutMode>,
+    /// Where the ticket prize goes if nobody picks a winning bin, withdrawn by the owner
+    /// if not set. Not available with the Merkle amounts prize mode.
+    pub no_winners_policy: Option<NoWinnersPolicy>,
+    /// Optional cw3 multisig that alone can register the Merkle roots, through its
+    /// proposals. The owner and the operators register them if not set.
+    pub governance_mode: Option<GovernanceMode>,
+    /// Optional attestation registry and threshold: the airdrop claims above the
+    /// threshold are paid in full only to attested addresses.
+    pub kyc_gate: Option<KycGateMsg>,
+    /// Domain separation of the leaves of both trees, `plain` if not set. With
+    /// `contract_address` or `campaign_id` the trees of a deployment cannot be claimed
+    /// on another one.
+    pub leaf_format: Option<LeafFormat>,
+    /// Whether the airdrop leaves carry a sequential index, `sha256(domain || index ||
+    /// account || amount)`, and are claimed with `ClaimAirdropByIndex`. The claims are
+    /// then tracked in a bitmap, 128 per storage entry. Defaults to false.
+    pub claim_index: Option<bool>,
+    /// Optional registry contract announced the game with a `Register` message, so
+    /// that aggregators can discover it.
+    pub registry: Option<String>,
+    /// Test mode for local devnets, accepted only by builds with the `test_mode`
+    /// feature. The bid stage may start at the instantiation block and the owner can
+    /// move the game to its next stage with `FastForwardStage`.
+    pub test_mode: Option<bool>,
+    /// Minimum ticket prize. If the prize collected is below it when the claim airdrop
+    /// stage ends, no prize is paid and every bidder can claim a ticket refund.
+    pub min_prize_pool: Option<Uint128>,
+    /// Time between the registration of a withdrawal destination with `SetTreasury` and
+    /// its activation, one day if not set.
+    pub treasury_timelock: Option<Duration>,
+    /// Optional guardian approving the withdrawals above a threshold, proposed by the
+    /// owner with `ProposeWithdrawal`.
+    pub withdrawal_guard: Option<WithdrawalGuardMsg>,
+}
+
+/// Share of the native prize of a winner sent to `address`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PayoutShareMsg {
+    pub address: String,
+    /// Basis points of the prize, the shares of a split sum to 10000.
+    pub bps: u16,
+}
+
+/// Airdrop and game proofs of an address, verified and stored by `CacheProof`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProofToCache {
+    pub address: String,
+    pub amount: Uint128,
+    /// Proof is hex-encoded merkle proof.
+    pub proof_airdrop: Vec<String>,
+    pub proof_game: Vec<String>,
+}
+
+/// Proof of the ownership of a secp256k1 key, required to bid when `pubkey_bids` is set.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PubkeyProof {
+    /// Compressed or uncompressed secp256k1 public key of the bidder account.
+    pub pubkey: Binary,
+    /// Signature of the sha256 hash of the game contract address concatenated to the
+    /// bidder address, in the 64 bytes `r || s` format.
+    pub signature: Binary,
+}
+
+/// Hook messages accepted along with the cw20 tokens sent to the contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    /// Credit the tokens to the total of a pool.
+    Fund { purpose: FundPurpose },
+    /// Register an airdrop round funded with the tokens sent (only owner).
+    RegisterRound { merkle_root: String, stage: StageMsg },
+}
+
+/// Pool credited by the cw20 `Fund` hook.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FundPurpose {
+    /// Total of the airdrop tree. Owner or operators only.
+    Airdrop,
+    /// Total of the game airdrop shared among the winners. Owner or operators only.
+    GameBonus,
+    /// Game airdrop top-up, allowed to anyone from the start of the bid stage.
+    Boost,
+}
+
+impl FundPurpose {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FundPurpose::Airdrop => "airdrop",
+            FundPurpose::GameBonus => "game_bonus",
+            FundPurpose::Boost => "boost",
+        }
+    }
+}
+
+/// Second cw20 token paid by `ClaimAirdrop` along with the airdrop of a root.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SecondaryPayoutMsg {
+    pub cw20_token_address: String,
+    /// Secondary tokens paid for every token of the airdrop allocation.
+    pub rate: Decimal,
+    /// Secondary tokens owed to the whole airdrop tree.
+    pub total_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EligibilityHookMsg {
+    /// Address of the contract queried on every airdrop claim.
+    pub contract_addr: String,
+    /// JSON smart query where `{address}` is replaced with the claimer address.
+    pub query_template: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct KycGateMsg {
+    /// Attestation registry queried on the claims above the threshold.
+    pub registry: String,
+    /// Amount of the airdrop paid without an attestation, must be positive.
+    pub threshold: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WithdrawalGuardMsg {
+    /// Address approving the large withdrawals.
+    pub guardian: String,
+    /// Largest airdrop the owner withdraws alone, in the airdrop asset.
+    pub threshold: Uint128,
+    /// Largest ticket prize the owner withdraws alone, in the ticket denom.
+    pub prize_threshold: Uint128,
+    /// Largest secondary payout the owner withdraws alone, in the secondary token.
+    pub secondary_threshold: Uint128,
+    /// Time the guardian has to approve a proposal, must be positive.
+    pub window: Duration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LateBidPenaltyMsg {
+    /// Final part of the bid stage in which the fee is due, e.g. `0.1` for the last 10%.
+    pub window: Decimal,
+    /// Fee in the ticket denom.
+    pub fee: Uint128,
+    /// Address receiving the fees. They are added to the prize if not set.
+    pub treasury: Option<String>,
+}
+
+/// Contract executed with the native prize attached, instead of sending it to the winner.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PrizeHookMsg {
+    pub contract_addr: String,
+    /// Execute message sent to the contract. The sender seen by the contract is the game,
+    /// so the message must name the beneficiary.
+    pub msg: Binary,
+}
+
+/// Stage sent on instantiation, either absolute or relative to the instantiation block.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(untagged)]
+pub enum StageMsg {
+    Absolute(Stage),
+    Relative(RelativeStage),
+}
+
+/// Stage starting `start_after` blocks or seconds after the instantiation, or after the
+/// `UpdateStages` message.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RelativeStage {
+    pub start_after: Duration,
+    pub duration: Duration,
+}
+
+impl StageMsg {
+    /// Resolves the stage into absolute values, given the instantiation block.
+    pub fn resolve(self, block: &BlockInfo) -> StdResult<Stage> {
+        match self {
+            StageMsg::Absolute(stage) => Ok(stage),
+            StageMsg::Relative(RelativeStage { start_after, duration }) => {
+                Ok(Stage { start: after_block(block, start_after)?, duration })
+            }
+        }
+    }
+}
+
+impl From<Stage> for StageMsg {
+    fn from(stage: Stage) -> Self {
+        StageMsg::Absolute(stage)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -34,10 +274,42 @@ pub enum ExecuteMsg {
         /// but owner cannot register new stages.
         new_owner: Option<String>,
     },
+    /// Allow an address to register the Merkle roots (only owner).
+    AddOperator {
+        address: String,
+    },
+    /// Revoke the operator role from an address (only owner).
+    RemoveOperator {
+        address: String,
+    },
+    /// Update the game metadata. Allowed only before the bid stage starts.
+    UpdateMetadata {
+        metadata: GameMetadata,
+    },
+    /// Reschedule the stages of the game (only owner). Allowed only before the bid
+    /// stage starts, stages not sent are kept and relative stages start from the
+    /// current block.
+    UpdateStages {
+        stage_bid: Option<StageMsg>,
+        stage_claim_airdrop: Option<StageMsg>,
+        stage_claim_prize: Option<StageMsg>,
+    },
+    /// Lengthen the claim stages by the durations sent (only owner). Allowed until the
+    /// end of each stage; the claim prize stage is postponed if the claim airdrop stage
+    /// would overlap it.
+    ExtendClaimStages {
+        claim_airdrop: Option<Duration>,
+        claim_prize: Option<Duration>,
+    },
     /// Place a bid.
     Bid {
         /// bidding bin value
         bin: u8,
+        /// Required only if the game has been instantiated with `pubkey_bids`. With
+        /// `hidden_bids` the key lets the bidder read its bin with the `Bid` query.
+        pubkey_proof: Option<PubkeyProof>,
+        /// Ticket of the configured `ticket_vendor` used instead of paying the price.
+        ticket_id: Option<u64>,
     },
     /// Change the value of a previously placed bid.
     ChangeBid {
@@ -46,41 +318,362 @@ pub enum ExecuteMsg {
     },
     /// Remove a previously placed bid.
     RemoveBid {},
-    /// Register Merkle root in the contract.
+    /// Copy a page of the bids of a previous game contract, continuing from the last
+    /// imported address (only owner, before the bid stage starts and after the one of the
+    /// source ends). The ticket price of every imported bid must be attached, the change
+    /// is returned. The players are not refunded the imported tickets.
+    ImportBids {
+        source_contract: String,
+        limit: Option<u32>,
+    },
+    /// Deposit native tokens to fund the airdrop. Only the configured
+    /// `airdrop_native_denom` is accepted.
+    FundAirdropNative {},
+    /// Cw20 tokens sent by the configured token with a `ReceiveMsg` hook.
+    Receive(Cw20ReceiveMsg),
+    /// Add native tokens of the ticket denom to the game prize. Allowed to anyone from the
+    /// start of the bid stage to the end of the claim airdrop stage.
+    BoostPrize {},
+    /// Attach native tokens of the ticket denom to a bin until the claim airdrop stage
+    /// starts. The amount is shared among the winners who bid on the bin.
+    BoostBin {
+        bin: u8,
+    },
+    /// Get back the amount attached to a bin without winners. Allowed during the claim
+    /// prize stage.
+    RefundBinBoost {
+        bin: u8,
+    },
+    /// Get back the `BoostPrize` boosts of the sender when the tickets are refunded
+    /// because of the `min_prize_pool`. Allowed during the claim prize stage.
+    RefundPrizeBoost {},
+    /// Commit to the game outcome before the end of the bid stage. The commitment is the
+    /// hex-encoded sha256 hash of the game Merkle root concatenated to a secret salt.
+    CommitOutcome {
+        commitment: String,
+    },
+    /// Reveal the committed game Merkle root after the end of the bid stage.
+    RevealOutcome {
+        merkle_root_game: String,
+        salt: String,
+    },
+    /// Register Merkle root in the contract. If an outcome was committed, the game root
+    /// must be the revealed one.
     RegisterMerkleRoots {
         /// MerkleRoot is hex-encoded merkle root.
         merkle_root_airdrop: String,
         total_amount_airdrop: Option<Uint128>,
         merkle_root_game: String,
-        total_amount_game: Option<Uint128>
+        total_amount_game: Option<Uint128>,
+        /// Leaf counts and checksum of the allocation file the trees are built from.
+        tree_metadata: TreeMetadata,
+        /// Must be true to replace roots already registered.
+        overwrite: Option<bool>,
+        /// Second asset paid with the airdrop of this root.
+        #[serde(default)]
+        secondary_payout: Option<SecondaryPayoutMsg>,
+        /// Condition checked on every airdrop claim of this root.
+        #[serde(default)]
+        claim_condition: Option<ClaimCondition>,
+    },
+    /// Check that the contract holds the cw20 tokens of the registered airdrop and game
+    /// totals and enable the claims. Allowed to anyone once the Merkle roots are registered.
+    OpenClaims {},
+    /// Block the airdrop and prize claims, e.g. while investigating a suspicious root.
+    /// Bids, refunds and withdraws are still allowed (owner or operator).
+    FreezeClaims {
+        reason: String,
+    },
+    /// Allow the claims again (only owner).
+    UnfreezeClaims {},
+    /// Block the prize claims while the result is disputed. Allowed until the end of the
+    /// `prize_timelock` (only owner).
+    VetoPrize {
+        reason: String,
+    },
+    /// Allow the prize claims again (only owner).
+    LiftPrizeVeto {},
+    /// Allow the operations moving funds again after a storage inconsistency, once
+    /// investigated (only owner).
+    ClearFailsafe {},
+    /// End the stage in progress and start the next one, or start the next stage if
+    /// none is in progress (only owner, test mode only).
+    FastForwardStage {},
+    /// Tune the limits of the messages to the gas limits of the chain, within the
+    /// ceilings of the contract (only owner). Limits not sent are unchanged.
+    UpdateLimits {
+        max_batch_size: Option<u32>,
+        max_addresses_per_call: Option<u32>,
+        max_proof_length: Option<u32>,
+    },
+    /// Mark a batch of addresses as if they had already claimed the airdrop, e.g. users
+    /// compensated off-chain or flagged as exploiters (only owner).
+    ImportExclusions {
+        addresses: Vec<String>,
+    },
+    /// Tag a batch of addresses with a cohort whose claims are aggregated by the
+    /// `CohortStats` query, before the claim airdrop stage starts (only owner).
+    TagCohort {
+        cohort: u8,
+        addresses: Vec<String>,
     },
-    // Claim does not check if contract has enough funds, owner must ensure it.
-    /// Claim airdrop bin.
+    /// Pay the pending allocations with `new_cw20`, converting them at `rate` new tokens
+    /// per allocated token. The claims must be frozen and the game must already hold the
+    /// converted balance of the new token. The old token balance is kept until the owner
+    /// withdraws it with `WithdrawRetiredPayout` (only owner).
+    SwapPayoutToken {
+        new_cw20: String,
+        rate: Decimal,
+    },
+    /// Claim airdrop bin. Airdrops paid with the cw20 token must be opened first.
     ClaimAirdrop {
         amount: Uint128,
         /// Proof is hex-encoded merkle proof.
         proof_airdrop: Vec<String>,
         proof_game: Vec<String>
     },
-    ClaimPrize {},
+    /// Verify the airdrop and game proofs of some addresses until the end of the claim
+    /// airdrop stage, so that they can claim with `ClaimCachedAirdrop`. Anyone can cache
+    /// its own proofs, the owner or an operator the ones of any address.
+    CacheProof {
+        proofs: Vec<ProofToCache>,
+    },
+    /// Claim the airdrop with the proofs cached for the sender.
+    ClaimCachedAirdrop {},
+    /// Claim the airdrop of an address custodied by the sender, which must be an approved
+    /// proxy. Tokens are sent to the proxy.
+    ClaimAirdropFor {
+        address: String,
+        amount: Uint128,
+        /// Proof is hex-encoded merkle proof.
+        proof_airdrop: Vec<String>,
+        proof_game: Vec<String>,
+    },
+    /// Claim the airdrop of the leaf `index`, in the claim index mode.
+    ClaimAirdropByIndex {
+        index: u32,
+        amount: Uint128,
+        /// Proof is hex-encoded merkle proof.
+        proof_airdrop: Vec<String>,
+        proof_game: Vec<String>,
+    },
+    /// Claim the airdrop held back above the KYC threshold, once the sender has a valid
+    /// attestation.
+    ClaimWithheldAirdrop {},
+    /// Split the native prize of the sender among several addresses, e.g. the members
+    /// of a team or syndicate. Allowed to bidders until they claim the prize; an empty
+    /// split pays the whole prize to the winner again.
+    SetPayoutSplit {
+        shares: Vec<PayoutShareMsg>,
+    },
+    /// Claim the game prize. `amount` and `proof` are required only when the prize
+    /// mode is `merkle_amounts`.
+    ClaimPrize {
+        amount: Option<Uint128>,
+        /// Proof is hex-encoded merkle proof of the (address, amount) leaf of the game tree.
+        proof: Option<Vec<String>>,
+        /// Optional contract receiving the native prize along with a message, e.g. to stake
+        /// it in the same transaction.
+        #[serde(default)]
+        then: Option<PrizeHookMsg>,
+    },
+    /// Send the ticket prize of a game without winners to the treasury or to the
+    /// following game, as set by the no winners policy. Allowed to anyone during the
+    /// claim prize stage.
+    SettleNoWinners {},
+    /// Get back the ticket price paid for the bid in a game without winners, if the no
+    /// winners policy is `refund`. Allowed during the claim prize stage.
+    ClaimTicketRefund {},
+    /// Register a new airdrop round with its own Merkle root and claim stage, funded
+    /// with the native airdrop coins sent (only owner). Airdrops paid with the cw20
+    /// token register their rounds with the `RegisterRound` hook instead.
+    RegisterRound {
+        merkle_root: String,
+        stage: StageMsg,
+    },
+    /// Claim the allocation of the sender in an airdrop round, during its stage.
+    ClaimRound {
+        round: u8,
+        amount: Uint128,
+        proof: Vec<String>,
+    },
+    /// Register a destination of the withdrawals (only owner). It can receive them once
+    /// activated with `ActivateTreasury`, after the `treasury_timelock`.
+    SetTreasury {
+        addr: String,
+    },
+    /// Activate a registered treasury whose timelock has passed. Allowed to anyone.
+    ActivateTreasury {
+        addr: String,
+    },
+    /// Remove a registered treasury, pending or active (only owner or withdrawal guardian).
+    RemoveTreasury {
+        addr: String,
+    },
     // Withdraw the remaining Airdrop tokens after expire time (only owner)
+    // The amount, in allocation units, defaults to all the remainder.
     WithdrawAirdrop {
         address: Addr,
+        #[serde(default)]
+        amount: Option<Uint128>,
+    },
+    // Withdraw the unclaimed secondary payout after expire time (only owner)
+    WithdrawSecondaryPayout {
+        address: Addr,
     },
     // Withdraw the remaining Prize tokens after expire time (only owner)
     WithdrawPrize {
         address: Addr,
     },
+    // Withdraw the unclaimed allocations of an airdrop round after its stage (only owner)
+    WithdrawRound {
+        round: u8,
+        address: Addr,
+    },
+    // Withdraw the balance of a payout token replaced by `SwapPayoutToken` (only owner)
+    WithdrawRetiredPayout {
+        cw20_token_address: Addr,
+        address: Addr,
+    },
+    /// Propose a withdrawal above the threshold of the withdrawal guard (only owner). It
+    /// is paid when the guardian approves it, within the approval window.
+    ProposeWithdrawal {
+        withdrawal: Withdrawal,
+    },
+    /// Approve and pay a proposed withdrawal (only guardian).
+    ApproveWithdrawal {
+        id: u64,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
+    /// Contract configuration. Returns `ConfigResponse`.
     Config {},
+    /// Game metadata. Returns `GameMetadata`.
+    Metadata {},
+    /// Stages of the game. Returns `StagesResponse`.
     Stages {},
-    Bid { address: String },
+    /// Bid of an address, with its payment and outcome. Returns `BidResponse`, a
+    /// superset of the one of the `GameQueryMsg` interface.
+    Bid {
+        address: String,
+        /// Proof of the key attached to the bid, disclosing the bin while the bids are
+        /// hidden. The signed message is `view` followed by the game contract address
+        /// and the bidder address.
+        viewer_proof: Option<PubkeyProof>,
+    },
+    /// Whether an address won the game, from the `GameQueryMsg` interface. Returns
+    /// `cw_game_interface::IsWinnerResponse`.
+    IsWinner { address: String },
+    /// Phase of the game and stage in progress, from the `GameQueryMsg` interface.
+    /// Returns `cw_game_interface::StageStatusResponse`.
+    StageStatus {},
+    /// Deprecated: bin chosen by an address in the shape returned by `Bid` before
+    /// `BidResponse` carried the payment and the outcome. Returns `LegacyBidResponse`.
+    /// It will be removed in the next major release.
+    LegacyBid { address: String },
+    /// Airdrop and prize claims of an address, with the block in which they have been
+    /// made. Returns `IsClaimedResponse`.
+    IsClaimed { address: String },
+    /// Whether the airdrop leaf `index` has been claimed, in the claim index mode.
+    /// Returns `IsClaimedByIndexResponse`.
+    IsClaimedByIndex { index: u32 },
+    /// Airdrop amount and winning bin of an address verified with `CacheProof`. Returns
+    /// `EligibleAmountResponse`.
+    EligibleAmount { address: String },
+    /// Compare the roots and the tree metadata recomputed by the caller with the
+    /// registered ones. Returns `VerifyTreeMetadataResponse`.
+    VerifyTreeMetadata {
+        merkle_root_airdrop: String,
+        merkle_root_game: String,
+        tree_metadata: TreeMetadata,
+    },
+    /// Hex-encoded leaf hashed by the contract for `account` and `amount`, in its leaf
+    /// format, to check the encoding of a tree builder. Returns `LeafHashResponse`.
+    LeafHash { account: String, amount: Uint128 },
+    /// Paginated list of the bids, ordered by address. Returns `AllBidsResponse`.
+    AllBids {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Registered Merkle roots of both trees. Returns `MerkleRootsResponse`. Kept for
+    /// existing clients, `MerkleRoot` returns a single tree.
     MerkleRoots {},
+    /// Registered Merkle root of a tree. Returns `MerkleRootInfo`.
+    MerkleRoot { kind: RootKind },
+    /// Id of the last airdrop round registered. Returns `LatestRoundResponse`.
+    LatestRound {},
+    /// Airdrop round with its totals. Returns `RoundResponse`.
+    Round { round: u8 },
+    /// Whether an address has claimed its allocation of a round. Returns
+    /// `IsRoundClaimedResponse`.
+    IsRoundClaimed { round: u8, address: String },
+    /// Whether the claims are frozen and why. Returns `ClaimsFreezeResponse`.
+    ClaimsFreeze {},
+    /// Prize, airdrop and claimed amounts. Returns `GameAmountsResponse`.
     GameAmounts {},
+    /// Native prize still held for the winners: the tickets paid and the boosts, less
+    /// the refunds and the prizes claimed. Returns `PrizePotResponse`.
+    PrizePot {},
+    /// Side-pool, ticket prize and number of winners of every boosted or winning bin.
+    /// Returns `BinPoolsResponse`.
+    BinPools {},
+    /// Number of bids placed on every bin, to show how crowded the bins are. Hidden
+    /// until the bid stage ends if the bins are private. Returns `BinDistributionResponse`.
+    BinDistribution {},
+    /// Amount attached to a bin by a sponsor. Returns `BinBoostResponse`.
+    BinBoost { bin: u8, address: String },
+    /// Sponsors that added the most to the game prize, highest first.
+    /// Returns `TopBoostersResponse`.
+    TopBoosters { limit: Option<u32> },
+    /// Paginated list of the addresses allowed to register the Merkle roots.
+    /// Returns `OperatorsResponse`.
+    Operators {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Paginated list of the withdrawal destinations, active or waiting for their
+    /// timelock. Returns `TreasuriesResponse`.
+    Treasuries {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Paginated list of the withdrawals waiting for the approval of the guardian,
+    /// expired ones included. Returns `WithdrawalProposalsResponse`.
+    WithdrawalProposals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Paginated list of the actions performed by the owner, oldest first.
+    /// Returns `AdminActionsResponse`.
+    AdminActions {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Paginated list of the payouts made by the contract, ordered by claim sequence
+    /// number. Returns `ClaimsPageResponse`.
+    ClaimsPage {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Committed and revealed game outcome. Returns `OutcomeResponse`.
+    Outcome {},
+    /// Number of addresses excluded from the airdrop claims. Returns `ExclusionsResponse`.
+    Exclusions {},
+    /// Claims aggregated per cohort of tagged addresses. Returns `CohortStatsResponse`.
+    CohortStats {},
+    /// Storage inconsistency blocking the operations moving funds. Returns
+    /// `FailsafeResponse`.
+    Failsafe {},
+    /// Limits of the messages and their ceilings. Returns `LimitsResponse`.
+    Limits {},
+    /// Claims still open to `address` with their deadlines, for notification services
+    /// and keeper bots. Returns `PendingActionsResponse`.
+    PendingActions { address: String },
+    /// Split of the native prize registered by a bidder. Returns `PayoutSplitResponse`.
+    PayoutSplit { address: String },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -94,6 +687,37 @@ pub struct MigrateMsg {}
 pub struct ConfigResponse {
     pub owner: Option<String>,
     pub cw20_token_address: String,
+    pub airdrop_native_denom: Option<String>,
+    /// Price of a ticket, to be paid in its denom by the bids.
+    pub ticket_price: Coin,
+    pub bins: u8,
+    pub eligibility_hook: Option<EligibilityHook>,
+    pub prize_mode: PrizeMode,
+    pub rounding_policy: RoundingPolicy,
+    pub pubkey_bids: bool,
+    pub approved_proxies: Vec<Addr>,
+    pub claim_rate_limit: Option<ClaimRateLimit>,
+    pub ticket_vendor: Option<String>,
+    pub max_proof_length: u32,
+    pub badge_contract: Option<String>,
+    pub max_bid_changes: Option<u8>,
+    pub late_bid_penalty: Option<LateBidPenalty>,
+    pub private_bins: bool,
+    pub hidden_bids: bool,
+    pub minimal_attributes: bool,
+    pub prize_timelock: Option<Duration>,
+    pub payout_mode: PayoutMode,
+    pub no_winners_policy: NoWinnersPolicy,
+    pub governance_mode: GovernanceMode,
+    pub kyc_gate: Option<KycGate>,
+    pub leaf_format: LeafFormat,
+    pub claim_index: bool,
+    pub test_mode: bool,
+    pub min_prize_pool: Option<Uint128>,
+    pub treasury_timelock: Duration,
+    pub withdrawal_guard: Option<WithdrawalGuard>,
+    /// New tokens paid per allocated token, set once the payout token has been swapped.
+    pub payout_rate: Option<Decimal>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -101,20 +725,176 @@ pub struct StagesResponse {
     pub stage_bid: Stage,
     pub stage_claim_airdrop: Stage,
     pub stage_claim_prize: Stage,
+    /// Event ending each stage, i.e. its start plus its duration.
+    pub stage_bid_end: Scheduled,
+    pub stage_claim_airdrop_end: Scheduled,
+    pub stage_claim_prize_end: Scheduled,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BidResponse {
+    /// Whether the address has a bid.
+    pub has_bid: bool,
+    /// Bin chosen by the address, `None` if it has no bid or if the bids are hidden.
+    pub bin: Option<u8>,
+    /// Ticket price paid, `None` for vendor tickets, imported bids and bids placed before
+    /// it was recorded.
+    pub paid: Option<Coin>,
+    /// Height of the block in which the bid has been placed.
+    pub placed_at: Option<u64>,
+    /// `None` until the outcome of the bid is known.
+    pub is_winner: Option<bool>,
+    pub prize_claimed: bool,
+}
+
+/// Deprecated shape of `BidResponse`, returned by `QueryMsg::LegacyBid`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyBidResponse {
     pub bid: Option<u8>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EligibleAmountResponse {
+    /// `None` if no proof is cached for the registered roots.
+    pub amount: Option<Uint128>,
+    pub winning_bin: Option<u8>,
+    /// Height of the block in which the proofs have been cached.
+    pub cached_at: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidInfo {
+    pub address: String,
+    pub bin: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllBidsResponse {
+    pub bids: Vec<BidInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsClaimedResponse {
+    pub airdrop_claimed: bool,
+    /// Whether the address has been excluded from the airdrop claims by the owner.
+    pub excluded: bool,
+    pub prize_claimed: bool,
+    /// Block of the airdrop claim, if recorded.
+    pub airdrop_claimed_at: Option<ClaimedAt>,
+    /// Block of the prize claim, if recorded.
+    pub prize_claimed_at: Option<ClaimedAt>,
+    /// Token id of the badge minted for the airdrop claim.
+    pub airdrop_badge: Option<String>,
+    /// Token id of the badge minted for the prize claim.
+    pub prize_badge: Option<String>,
+    /// Airdrop held back until the address gets a KYC attestation.
+    pub withheld_airdrop: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsClaimedByIndexResponse {
+    pub claimed: bool,
+}
+
+/// Merkle tree registered with `RegisterMerkleRoots`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RootKind {
+    Airdrop,
+    Game,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MerkleRootInfo {
+    pub kind: RootKind,
+    /// Hex-encoded Merkle root, `None` until registered.
+    pub merkle_root: Option<String>,
+    /// Tokens distributed with the tree.
+    pub total_amount: Uint128,
+    /// Number of leaves published with the tree metadata.
+    pub leaf_count: Option<u64>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MerkleRootsResponse {
-    /// MerkleRoot is hex-encoded merkle root.
-    pub merkle_root_airdrop: String,
+    /// MerkleRoot is hex-encoded merkle root, `None` until registered.
+    pub merkle_root_airdrop: Option<String>,
+    pub total_amount: Uint128,
+    pub merkle_root_game: Option<String>,
+    pub tree_metadata: Option<TreeMetadata>,
+    pub claim_condition: Option<ClaimCondition>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LatestRoundResponse {
+    /// 0, the game airdrop, until a round is registered.
+    pub latest_round: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoundResponse {
+    pub round: u8,
+    pub merkle_root: String,
+    pub stage: Stage,
     pub total_amount: Uint128,
-    pub merkle_root_game: String
+    pub claimed_amount: Uint128,
+    pub withdrawn: bool,
+}
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsRoundClaimedResponse {
+    pub is_claimed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimsFreezeResponse {
+    pub frozen: bool,
+    pub reason: Option<String>,
+    /// Reason of the owner veto on the prizes, if any.
+    pub prize_veto: Option<String>,
+}
+
+/// Limits of the messages tunable by the owner.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Limits {
+    /// Items processed by `ImportBids` and proofs cached by `CacheProof` per message.
+    pub max_batch_size: u32,
+    /// Addresses accepted by `ImportExclusions` per message.
+    pub max_addresses_per_call: u32,
+    /// Nodes of a Merkle proof.
+    pub max_proof_length: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LimitsResponse {
+    pub limits: Limits,
+    /// Highest values the owner can set.
+    pub ceilings: Limits,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PayoutSplitResponse {
+    /// Empty if the whole prize is paid to the winner.
+    pub shares: Vec<PayoutShare>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FailsafeResponse {
+    /// Invariant found violated, `None` while the failsafe is not triggered.
+    pub failsafe: Option<Failsafe>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VerifyTreeMetadataResponse {
+    /// True if every value matches the registered one.
+    pub valid: bool,
+    /// Names of the fields that do not match.
+    pub mismatches: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LeafHashResponse {
+    pub leaf: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -122,7 +902,326 @@ pub struct GameAmountsResponse {
     pub total_ticket_prize: Uint128,
     pub total_airdrop_amount: Uint128,
     pub total_airdrop_game_amount: Uint128,
+    pub total_funded_native_airdrop: Uint128,
     pub winners_amount: Uint128,
     pub total_claimed_airdrop: Uint128,
     pub total_claimed_prize: Uint128,
+    /// Leftover airdrop already withdrawn by the owner.
+    pub total_withdrawn_airdrop: Uint128,
+    pub claims_funded: bool,
+    /// Totals frozen at the end of the claim airdrop stage, once recorded.
+    pub closed_snapshot: Option<ClosedSnapshot>,
+    /// Second asset paid with the airdrop, with its totals.
+    pub secondary_payout: Option<SecondaryPayout>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PrizePotResponse {
+    /// Prize not claimed yet, in the ticket denom.
+    pub pot: Coin,
+    /// Tickets paid and boosts, less the refunds of the removed bids.
+    pub total_ticket_prize: Uint128,
+    /// Ticket prices alone, without late fees and boosts, compared to the minimum pool.
+    pub total_ticket_payments: Uint128,
+    /// Prizes and ticket refunds claimed, and the prize settled without winners.
+    pub total_claimed_prize: Uint128,
+    /// Whether the ticket payments were below the minimum when the bid stage ended, so
+    /// that the tickets are refunded instead.
+    pub refund_mode: bool,
+}
+
+/// Query sent to an approved proxy to check that it custodies the address it claims for.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyQueryMsg {
+    Custodies { address: String },
+}
+
+/// Response expected from an approved proxy to the `Custodies` query.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CustodyResponse {
+    pub custodied: bool,
+}
+
+/// Query sent to the attestation registry of the KYC gate.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AttestationQueryMsg {
+    Attestation { address: String },
+}
+
+/// Response expected from the attestation registry to the `Attestation` query.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AttestationResponse {
+    pub valid: bool,
+}
+
+/// Query of the cw721 contracts used by the `HoldsNft` claim condition.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw721QueryMsg {
+    Tokens {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+/// Response of the cw721 `Tokens` query.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokensResponse {
+    pub tokens: Vec<String>,
+}
+
+/// Mint message of the badge contract, the cw721-base `Mint` with the badge metadata as
+/// extension.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BadgeExecuteMsg {
+    Mint(BadgeMintMsg),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BadgeMintMsg {
+    pub token_id: String,
+    pub owner: String,
+    pub token_uri: Option<String>,
+    pub extension: BadgeMetadata,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BadgeMetadata {
+    /// Address of the game contract.
+    pub game: String,
+    pub role: BadgeRole,
+    /// Order of magnitude of the amount claimed.
+    pub amount_band: u32,
+}
+
+/// Message executed on the configured registry by the instantiation of the game.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistryExecuteMsg {
+    Register(RegistryEntry),
+}
+
+/// Parameters of the game announced to the registry.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RegistryEntry {
+    /// Address of the game contract.
+    pub game: String,
+    pub cw20_token_address: String,
+    pub airdrop_native_denom: Option<String>,
+    pub ticket_price: Coin,
+    pub bins: u8,
+    pub prize_mode: PrizeMode,
+    pub stage_bid: Stage,
+    pub stage_claim_airdrop: Stage,
+    pub stage_claim_prize: Stage,
+    pub metadata: GameMetadata,
+}
+
+/// Query sent to the ticket vendor to check that the bidder owns the ticket.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TicketVendorQueryMsg {
+    VerifyTicket { ticket_id: u64, owner: String },
+}
+
+/// Response expected from the ticket vendor to the `VerifyTicket` query.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VerifyTicketResponse {
+    pub valid: bool,
+}
+
+/// Bank supply query of cosmwasm 1.1, mirrored here since the `BankQuery` of the pinned
+/// cosmwasm-std does not expose it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SupplyQuery {
+    Bank(BankSupplyQuery),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BankSupplyQuery {
+    Supply { denom: String },
+}
+
+/// Response of the bank `Supply` query.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SupplyResponse {
+    pub amount: Coin,
+}
+
+/// Response expected from the contract configured as eligibility hook.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EligibilityResponse {
+    pub eligible: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdminActionResponse {
+    pub id: u64,
+    pub height: u64,
+    pub sender: String,
+    pub action: String,
+    pub summary: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdminActionsResponse {
+    pub actions: Vec<AdminActionResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimRecordResponse {
+    pub seq: u64,
+    pub address: String,
+    pub kind: ClaimKind,
+    pub amount: Uint128,
+    pub height: u64,
+    pub time: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimsPageResponse {
+    pub claims: Vec<ClaimRecordResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BoosterResponse {
+    pub address: String,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TopBoostersResponse {
+    pub boosters: Vec<BoosterResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BinPoolResponse {
+    pub bin: u8,
+    /// Side-pool attached by the sponsors.
+    pub amount: Uint128,
+    pub winners: Uint128,
+    /// Share of the ticket prize, only in the `per_bin` prize mode once the claim airdrop
+    /// stage is closed.
+    pub ticket_prize: Uint128,
+    /// Prize already claimed by the winners of the bin.
+    pub claimed: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BinPoolsResponse {
+    pub pools: Vec<BinPoolResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BinBidsResponse {
+    pub bin: u8,
+    pub bids: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BinDistributionResponse {
+    /// Every bin of the game in ascending order, the ones without bids included.
+    pub bins: Vec<BinBidsResponse>,
+    pub total_bids: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BinBoostResponse {
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OperatorsResponse {
+    pub operators: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TreasuryResponse {
+    pub address: String,
+    /// Event from which the treasury can be activated.
+    pub active_at: Scheduled,
+    pub active: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TreasuriesResponse {
+    pub treasuries: Vec<TreasuryResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WithdrawalProposalResponse {
+    pub id: u64,
+    pub withdrawal: Withdrawal,
+    pub proposer: String,
+    /// Event from which the proposal cannot be approved anymore.
+    pub expires: Scheduled,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WithdrawalProposalsResponse {
+    pub proposals: Vec<WithdrawalProposalResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OutcomeResponse {
+    pub commitment: Option<String>,
+    pub merkle_root_game: Option<String>,
+}
+
+/// Message of the cw20 `Send` delivering a payout to a contract in the `send_with_memo`
+/// payout mode.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutReceiveMsg {
+    PayoutMemo {
+        campaign_id: String,
+        /// `airdrop/{address}` or `prize/{address}`, unique for each claim.
+        claim_id: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExclusionsResponse {
+    pub count: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CohortResponse {
+    pub cohort: u8,
+    pub stats: CohortStats,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CohortStatsResponse {
+    /// Every cohort with tagged addresses, in ascending order.
+    pub cohorts: Vec<CohortResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingActionKind {
+    ClaimAirdrop,
+    ClaimWithheldAirdrop,
+    ClaimPrize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingAction {
+    pub action: PendingActionKind,
+    /// Event from which the action can be sent.
+    pub opens_at: Scheduled,
+    /// Event from which the action can no longer be sent.
+    pub deadline: Scheduled,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingActionsResponse {
+    /// Ordered by deadline.
+    pub actions: Vec<PendingAction>,
 }
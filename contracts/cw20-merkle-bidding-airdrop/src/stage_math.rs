@@ -0,0 +1,151 @@
+use cosmwasm_std::{BlockInfo, StdError, Timestamp};
+use cw_utils::{Duration, Scheduled};
+use thiserror::Error;
+
+use crate::state::Stage;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+/// Errors of the arithmetic on the stage events.
+#[derive(Error, Debug, PartialEq)]
+pub enum StageMathError {
+    #[error("Cannot add a {duration} duration to a {start} event")]
+    KindMismatch { start: &'static str, duration: &'static str },
+
+    #[error("Stage event overflows the {kind} range")]
+    Overflow { kind: &'static str },
+}
+
+impl From<StageMathError> for StdError {
+    fn from(err: StageMathError) -> Self {
+        StdError::generic_err(err.to_string())
+    }
+}
+
+fn scheduled_kind(scheduled: &Scheduled) -> &'static str {
+    match scheduled {
+        Scheduled::AtHeight(_) => "height",
+        Scheduled::AtTime(_) => "time",
+    }
+}
+
+fn duration_kind(duration: &Duration) -> &'static str {
+    match duration {
+        Duration::Height(_) => "height",
+        Duration::Time(_) => "time",
+    }
+}
+
+/// Event `duration` after `start`. Unlike `Scheduled + Duration` it does not panic on
+/// overflow, and reports which kinds were mixed.
+pub fn checked_add(start: Scheduled, duration: Duration) -> Result<Scheduled, StageMathError> {
+    match (start, duration) {
+        (Scheduled::AtHeight(height), Duration::Height(blocks)) => height
+            .checked_add(blocks)
+            .map(Scheduled::AtHeight)
+            .ok_or(StageMathError::Overflow { kind: "height" }),
+        (Scheduled::AtTime(time), Duration::Time(seconds)) => seconds
+            .checked_mul(NANOS_PER_SECOND)
+            .and_then(|nanos| time.nanos().checked_add(nanos))
+            .map(|nanos| Scheduled::AtTime(Timestamp::from_nanos(nanos)))
+            .ok_or(StageMathError::Overflow { kind: "time" }),
+        (start, duration) => Err(StageMathError::KindMismatch {
+            start: scheduled_kind(&start),
+            duration: duration_kind(&duration),
+        }),
+    }
+}
+
+/// Event `duration` after the block, in the same kind as the duration.
+pub fn after_block(block: &BlockInfo, duration: Duration) -> Result<Scheduled, StageMathError> {
+    let now = match duration {
+        Duration::Height(_) => Scheduled::AtHeight(block.height),
+        Duration::Time(_) => Scheduled::AtTime(block.time),
+    };
+    checked_add(now, duration)
+}
+
+/// Duration lengthened by `extension`, of the same kind.
+pub fn checked_extend(duration: Duration, extension: Duration) -> Result<Duration, StageMathError> {
+    match (duration, extension) {
+        (Duration::Height(blocks), Duration::Height(more)) => blocks
+            .checked_add(more)
+            .map(Duration::Height)
+            .ok_or(StageMathError::Overflow { kind: "height" }),
+        (Duration::Time(seconds), Duration::Time(more)) => seconds
+            .checked_add(more)
+            .map(Duration::Time)
+            .ok_or(StageMathError::Overflow { kind: "time" }),
+        (duration, extension) => Err(StageMathError::KindMismatch {
+            start: duration_kind(&duration),
+            duration: duration_kind(&extension),
+        }),
+    }
+}
+
+/// Event ending the stage.
+pub fn stage_end(stage: &Stage) -> Result<Scheduled, StageMathError> {
+    checked_add(stage.start, stage.duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stage(start: Scheduled, duration: Duration) -> Stage {
+        Stage { start, duration }
+    }
+
+    #[test]
+    fn same_kind() {
+        let end = stage_end(&stage(Scheduled::AtHeight(100), Duration::Height(20))).unwrap();
+        assert_eq!(end, Scheduled::AtHeight(120));
+
+        let start = Scheduled::AtTime(Timestamp::from_seconds(100));
+        let end = stage_end(&stage(start, Duration::Time(20))).unwrap();
+        assert_eq!(end, Scheduled::AtTime(Timestamp::from_seconds(120)));
+
+        let end = stage_end(&stage(Scheduled::AtHeight(100), Duration::Height(0))).unwrap();
+        assert_eq!(end, Scheduled::AtHeight(100));
+    }
+
+    #[test]
+    fn kind_mismatch() {
+        let start = Scheduled::AtTime(Timestamp::from_seconds(100));
+        let err = stage_end(&stage(start, Duration::Height(20))).unwrap_err();
+        assert_eq!(err, StageMathError::KindMismatch { start: "time", duration: "height" });
+
+        let err = stage_end(&stage(Scheduled::AtHeight(100), Duration::Time(20))).unwrap_err();
+        assert_eq!(err, StageMathError::KindMismatch { start: "height", duration: "time" });
+        assert_eq!(
+            StdError::from(err),
+            StdError::generic_err("Cannot add a time duration to a height event")
+        );
+    }
+
+    #[test]
+    fn height_overflow() {
+        let end = stage_end(&stage(Scheduled::AtHeight(u64::MAX - 1), Duration::Height(1))).unwrap();
+        assert_eq!(end, Scheduled::AtHeight(u64::MAX));
+
+        let err = stage_end(&stage(Scheduled::AtHeight(u64::MAX), Duration::Height(1))).unwrap_err();
+        assert_eq!(err, StageMathError::Overflow { kind: "height" });
+    }
+
+    #[test]
+    fn time_overflow() {
+        let max_seconds = u64::MAX / NANOS_PER_SECOND;
+        let start = Scheduled::AtTime(Timestamp::from_nanos(0));
+        let end = stage_end(&stage(start, Duration::Time(max_seconds))).unwrap();
+        assert_eq!(end, Scheduled::AtTime(Timestamp::from_seconds(max_seconds)));
+
+        // The duration alone does not fit in nanoseconds.
+        let err = stage_end(&stage(start, Duration::Time(max_seconds + 1))).unwrap_err();
+        assert_eq!(err, StageMathError::Overflow { kind: "time" });
+
+        // The end does not fit in nanoseconds.
+        let start = Scheduled::AtTime(Timestamp::from_nanos(u64::MAX));
+        let err = stage_end(&stage(start, Duration::Time(1))).unwrap_err();
+        assert_eq!(err, StageMathError::Overflow { kind: "time" });
+    }
+}
@@ -1,19 +1,633 @@
-use cosmwasm_std::{Addr, Uint128, Coin};
+use cosmwasm_std::{Addr, BlockInfo, Coin, Decimal, Empty, StdResult, Storage, Timestamp, Uint128};
+use cw_asset_lite::AssetInfo;
 use cw_storage_plus::{Item, Map};
 use cw_utils::{Duration, Scheduled};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::constants::{
+    ADMIN_ACTIONS_COUNT_KEY, ADMIN_ACTIONS_PREFIX, AIRDROP_CLAIMED_AT_PREFIX, BIDS_PREFIX,
+    BID_CHANGES_PREFIX, BID_IMPORTS_PREFIX, BID_KEYS_PREFIX, BID_RECEIPTS_PREFIX, BIN_BIDS_PREFIX, BIN_BOOSTS_PREFIX,
+    BIN_CLAIMED_PRIZE_PREFIX, BIN_POOLS_PREFIX, BIN_WINNERS_PREFIX,
+    BOOSTERS_BY_AMOUNT_PREFIX, BOOSTS_PREFIX, CACHED_PROOFS_PREFIX,
+    CLAIMS_FROZEN_KEY, PRIZE_VETO_KEY, FAILSAFE_KEY, CLAIMS_FUNDED_KEY, CLAIM_AIRDROP_PREFIX, CLAIMED_BITMAP_PREFIX, COHORTS_PREFIX, COHORT_STATS_PREFIX, EXCLUDED_PREFIX, EXCLUDED_COUNT_KEY, CLAIM_PRIZE_PREFIX,
+    CLAIM_CONDITION_KEY, CLAIM_HISTORY_PREFIX, CLAIM_LOG_COUNT_KEY, CLAIM_LOG_PREFIX, LATEST_ROUND_KEY, ROUNDS_PREFIX, ROUND_CLAIMED_PREFIX, TREASURIES_PREFIX, WITHDRAWAL_PROPOSALS_PREFIX, WITHDRAWAL_PROPOSAL_COUNT_KEY, RETIRED_PAYOUT_TOKENS_PREFIX, CLAIM_USAGE_PREFIX, CLOSED_SNAPSHOT_KEY, CONFIG_KEY, DUST_PAID_PREFIX,
+    GAME_METADATA_KEY, GAME_PARAMS_KEY, COUNTERS_KEY, MERKLE_ROOT_AIRDROP_PREFIX,
+    MERKLE_ROOT_GAME_PREFIX, OPERATORS_PREFIX, OUTCOME_COMMITMENT_KEY, PAYOUT_RATE_KEY,
+    PENDING_BADGE_KEY, PAYOUT_SPLITS_PREFIX, WITHHELD_AIRDROP_PREFIX,
+    PRIZE_CLAIMED_AT_PREFIX, PUBKEY_BIDS_PREFIX, REVEALED_OUTCOME_KEY, SECONDARY_PAYOUT_KEY, StageName, TICKET_BIDS_PREFIX,
+    TREE_METADATA_KEY, USED_TICKETS_PREFIX, };
+use crate::error::ContractError;
+use crate::limits::{DEFAULT_TREASURY_TIMELOCK, MAX_ADDRESSES_PER_CALL, MAX_BATCH_SIZE, MAX_PROOF_NODES};
+use crate::stage_math::{checked_add, checked_extend, stage_end};
+
 /// Struct to manage the contract configuration.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     /// Owner If None set, contract is frozen.
     pub owner: Option<Addr>,
     pub cw20_token_address: Addr,
+    /// Native denom (e.g. an ICS-20 `ibc/...` denom) used to fund and pay the airdrop.
+    /// If None, the airdrop is paid with the cw20 token.
+    pub airdrop_native_denom: Option<String>,
+    /// External contract queried to check if an address can claim the airdrop.
+    pub eligibility_hook: Option<EligibilityHook>,
+    /// How the prize of each winner is computed.
+    #[serde(default)]
+    pub prize_mode: PrizeMode,
+    /// Where the remainder of the prize divisions goes.
+    #[serde(default)]
+    pub rounding_policy: RoundingPolicy,
+    /// If true, bidders must prove the ownership of a secp256k1 key and every key can be
+    /// used by a single address.
+    #[serde(default)]
+    pub pubkey_bids: bool,
+    /// Contracts allowed to claim the airdrop on behalf of the addresses they custody.
+    #[serde(default)]
+    pub approved_proxies: Vec<Addr>,
+    /// Cap on the claims accepted in a window of blocks, applied to each claim stage.
+    #[serde(default)]
+    pub claim_rate_limit: Option<ClaimRateLimit>,
+    /// Ticket vendor contract whose tickets can be used to bid instead of paying.
+    #[serde(default)]
+    pub ticket_vendor: Option<Addr>,
+    /// Maximum number of nodes accepted in a Merkle proof.
+    #[serde(default = "default_max_proof_length")]
+    pub max_proof_length: u32,
+    /// cw721 contract minting a badge to every claimer.
+    #[serde(default)]
+    pub badge_contract: Option<Addr>,
+    /// Number of times every address can change its bid, unlimited if None.
+    #[serde(default)]
+    pub max_bid_changes: Option<u8>,
+    /// Fee paid on top of the ticket by the bids placed at the end of the bid stage.
+    #[serde(default)]
+    pub late_bid_penalty: Option<LateBidPenalty>,
+    /// Whether the bins are hidden from the queries until the bid stage ends.
+    #[serde(default)]
+    pub private_bins: bool,
+    /// Whether the `Bid` query hides the bins from everyone but the bidder until the bid
+    /// stage ends.
+    #[serde(default)]
+    pub hidden_bids: bool,
+    /// Whether the attributes carrying hashes and free text are omitted.
+    #[serde(default)]
+    pub minimal_attributes: bool,
+    /// Time after the start of the claim prize stage during which the owner can veto
+    /// the prizes.
+    #[serde(default)]
+    pub prize_timelock: Option<Duration>,
+    /// How the cw20 payouts of the claims are delivered.
+    #[serde(default)]
+    pub payout_mode: PayoutMode,
+    /// Maximum number of items processed by a single paginated execute message.
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: u32,
+    /// Maximum number of addresses accepted by a single admin message.
+    #[serde(default = "default_max_addresses_per_call")]
+    pub max_addresses_per_call: u32,
+    /// Where the ticket prize goes if nobody picked a winning bin.
+    #[serde(default)]
+    pub no_winners_policy: NoWinnersPolicy,
+    /// Who can register the Merkle roots.
+    #[serde(default)]
+    pub governance_mode: GovernanceMode,
+    /// Attestation required to claim the airdrop above a threshold.
+    #[serde(default)]
+    pub kyc_gate: Option<KycGate>,
+    /// Guardian approving the withdrawals above a threshold.
+    #[serde(default)]
+    pub withdrawal_guard: Option<WithdrawalGuard>,
+    /// Domain prepended to the preimage of the leaves of both trees.
+    #[serde(default)]
+    pub leaf_format: LeafFormat,
+    /// Whether the airdrop leaves carry a sequential index and the claims are tracked
+    /// in the claimed bitmap instead of by address.
+    #[serde(default)]
+    pub claim_index: bool,
+    /// Whether the owner can fast forward the stages, on local devnets.
+    #[serde(default)]
+    pub test_mode: bool,
+    /// Ticket prize below which the tickets are refunded instead of paying a prize.
+    #[serde(default)]
+    pub min_prize_pool: Option<Uint128>,
+    /// Time between the registration of a withdrawal destination and its activation.
+    #[serde(default = "default_treasury_timelock")]
+    pub treasury_timelock: Duration,
+}
+
+fn default_max_proof_length() -> u32 {
+    MAX_PROOF_NODES
+}
+
+fn default_treasury_timelock() -> Duration {
+    DEFAULT_TREASURY_TIMELOCK
+}
+
+fn default_max_batch_size() -> u32 {
+    MAX_BATCH_SIZE
+}
+
+fn default_max_addresses_per_call() -> u32 {
+    MAX_ADDRESSES_PER_CALL
+}
+
+impl Config {
+    /// Asset used to pay the airdrop: the native denom if configured, the cw20 token otherwise.
+    pub fn airdrop_asset_info(&self) -> AssetInfo {
+        match &self.airdrop_native_denom {
+            Some(denom) => AssetInfo::Native(denom.clone()),
+            None => AssetInfo::Cw20(self.cw20_token_address.clone()),
+        }
+    }
+
+    /// Prefix of the preimage of the leaves of the game deployed at `contract`.
+    pub fn leaf_domain(&self, contract: &Addr) -> String {
+        match &self.leaf_format {
+            LeafFormat::Plain => String::new(),
+            LeafFormat::ContractAddress => contract.to_string(),
+            LeafFormat::CampaignId { campaign_id } => campaign_id.clone(),
+        }
+    }
 }
 
+/// Strategy used to compute the prize of each winner.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PrizeMode {
+    /// Leaves of the game tree are (address, bin). Winners are detected when claiming
+    /// the airdrop and the prizes are split evenly among them.
+    #[default]
+    EqualSplit,
+    /// Leaves of the game tree are (address, prize amount), computed off-chain by the
+    /// owner. Winners claim the ticket prize with the proof of their leaf.
+    MerkleAmounts,
+    /// Leaves of the game tree are (address, bin) and several bins can be winning. The
+    /// ticket prize is split among the winning bins proportionally to their winners, and
+    /// every bin sub-pool is split evenly among the winners who bid on it.
+    PerBin,
+}
+
+/// Policy applied to the remainder (dust) of the even split of a prize pool.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingPolicy {
+    /// Winners receive the floor of the division, the dust is withdrawn by the owner.
+    #[default]
+    FloorToWinner,
+    /// The dust is sent to the treasury along with the first claim.
+    DustToTreasury { address: Addr },
+    /// The first claimer receives the dust on top of its share.
+    DustToFirstClaimer,
+}
+
+/// Preimage of the leaves of the Merkle trees.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LeafFormat {
+    /// `sha256(account || amount)`.
+    #[default]
+    Plain,
+    /// `sha256(contract_address || account || amount)`.
+    ContractAddress,
+    /// `sha256(campaign_id || account || amount)`.
+    CampaignId { campaign_id: String },
+}
+
+/// Delivery of the cw20 payouts of the claims.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutMode {
+    /// Every recipient receives a cw20 `Transfer`.
+    #[default]
+    Transfer,
+    /// Recipients that are contracts receive a cw20 `Send` carrying a `PayoutMemo`,
+    /// the other accounts a `Transfer`.
+    SendWithMemo { campaign_id: String },
+}
+
+/// Part of the native prize of a winner paid to an address, in basis points.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-/// Struct to manage start and end of static stages.
+pub struct PayoutShare {
+    pub address: Addr,
+    pub bps: u16,
+}
+
+/// Destination of the ticket prize of a game without winners.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NoWinnersPolicy {
+    /// The owner withdraws the ticket prize with `WithdrawPrize`.
+    #[default]
+    Withdraw,
+    /// Anyone can send the ticket prize to the treasury with `SettleNoWinners`.
+    Treasury { address: Addr },
+    /// Anyone can boost the prize of the following game with `SettleNoWinners`.
+    Rollover { game: Addr },
+    /// Every bidder gets back the ticket price paid with `ClaimTicketRefund`.
+    Refund,
+}
+
+/// Compliance check of the airdrop claims above a threshold.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct KycGate {
+    /// Attestation registry queried with `{"attestation": {"address": ...}}`. It must
+    /// reply with `{"valid": bool}`.
+    pub registry: Addr,
+    /// Amount of the airdrop of every address paid without an attestation.
+    pub threshold: Uint128,
+}
+
+/// Second signer of the large withdrawals.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WithdrawalGuard {
+    /// Address approving the withdrawals proposed by the owner.
+    pub guardian: Addr,
+    /// Largest airdrop withdrawn by the owner alone, in the airdrop asset. The tranches of
+    /// the leftover count towards it together.
+    pub threshold: Uint128,
+    /// Largest ticket prize withdrawn by the owner alone, in the ticket denom.
+    pub prize_threshold: Uint128,
+    /// Largest secondary payout withdrawn by the owner alone, in the secondary token.
+    pub secondary_threshold: Uint128,
+    /// Time the guardian has to approve a proposal.
+    pub window: Duration,
+}
+
+/// Custody of the registration of the Merkle roots.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GovernanceMode {
+    /// The owner and the operators register the roots.
+    #[default]
+    Disabled,
+    /// Only the cw3 multisig registers the roots, by executing a passed proposal.
+    Multisig { address: Addr },
+}
+
+/// Maximum throughput of the claims over a sliding window of blocks.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimRateLimit {
+    /// Number of blocks of the sliding window.
+    pub window: u64,
+    /// Maximum number of claims accepted in the window.
+    pub max_claims: Option<u64>,
+    /// Maximum amount of airdropped tokens released in the window.
+    pub max_amount: Option<Uint128>,
+}
+
+/// Claims accepted in a single block of a stage.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ClaimUsage {
+    pub claims: u64,
+    pub amount: Uint128,
+}
+
+/// Block in which a claim has been made.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimedAt {
+    pub height: u64,
+    pub time: Timestamp,
+}
+
+impl ClaimedAt {
+    pub fn new(block: &BlockInfo) -> Self {
+        ClaimedAt {
+            height: block.height,
+            time: block.time,
+        }
+    }
+}
+
+/// Invariant found violated by an operation moving funds.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Failsafe {
+    pub condition: String,
+    /// Height of the block of the detection.
+    pub triggered_at: u64,
+}
+
+/// Totals frozen by the first transaction executed after the end of the claim airdrop
+/// stage. Every later prize and withdraw computation is based on these values.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClosedSnapshot {
+    /// Height of the block in which the snapshot has been recorded.
+    pub height: u64,
+    pub winners: Uint128,
+    /// Number of winners of every bin with at least one winner.
+    pub bin_winners: Vec<(u8, Uint128)>,
+    pub total_ticket_prize: Uint128,
+    pub total_bin_boost: Uint128,
+    pub total_airdrop_amount: Uint128,
+    pub total_airdrop_game_amount: Uint128,
+    pub claimed_airdrop_amount: Uint128,
+    /// Whether the ticket payments are below the minimum prize pool, in which case every
+    /// bidder and prize sponsor is refunded and no prize is paid.
+    #[serde(default)]
+    pub refund_mode: bool,
+}
+
+impl ClosedSnapshot {
+    pub fn bin_winners(&self, bin: u8) -> Option<Uint128> {
+        self.bin_winners
+            .iter()
+            .find(|(winning_bin, _)| *winning_bin == bin)
+            .map(|(_, winners)| *winners)
+    }
+
+    /// Share of the ticket prize of a bin in the `PerBin` prize mode.
+    pub fn bin_ticket_prize(&self, bin: u8) -> Uint128 {
+        match self.bin_winners(bin) {
+            Some(bin_winners) if !self.winners.is_zero() => {
+                self.total_ticket_prize.multiply_ratio(bin_winners, self.winners)
+            }
+            _ => Uint128::zero(),
+        }
+    }
+}
+
+/// Payment and block of a bid. Missing for the bids placed before it was recorded.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidReceipt {
+    /// Ticket price paid for the bid, `None` if a vendor ticket has been used or if the
+    /// bid has been imported.
+    pub paid: Option<Coin>,
+    pub placed_at: u64,
+}
+
+/// Airdrop eligibility verified in advance with `CacheProof`. It is valid only as long
+/// as the registered roots are the ones the proofs have been verified against.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CachedProof {
+    pub amount: Uint128,
+    /// Bin proven by the game proof, `None` if the address did not win.
+    pub winning_bin: Option<u8>,
+    pub merkle_root_airdrop: String,
+    pub merkle_root_game: String,
+    pub cached_at: u64,
+}
+
+/// Fee discouraging the bids that wait for the distribution of the other bids.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LateBidPenalty {
+    /// Final part of the bid stage in which the fee is due.
+    pub window: Decimal,
+    /// Fee in the ticket denom.
+    pub fee: Uint128,
+    /// Address receiving the fees, added to the prize if None.
+    pub treasury: Option<Addr>,
+}
+
+impl LateBidPenalty {
+    /// Whether the fee is due by the bids placed in the block.
+    pub fn is_due(&self, stage_bid: &Stage, block: &BlockInfo) -> StdResult<bool> {
+        Ok(stage_bid.final_part_start(self.window)?.is_triggered(block))
+    }
+}
+
+/// Struct to manage the external eligibility check performed on airdrop claims.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EligibilityHook {
+    /// Contract queried on every airdrop claim.
+    pub contract_addr: Addr,
+    /// JSON smart query sent to the contract. Every occurrence of `{address}` is replaced
+    /// with the claimer address. The contract must reply with `{"eligible": bool}`.
+    pub query_template: String,
+}
+
+/// Published description of the allocation the Merkle trees are built from, so that
+/// anyone can rebuild and cross-check the trees.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TreeMetadata {
+    /// Number of leaves of the airdrop tree.
+    pub airdrop_leaf_count: u64,
+    /// Number of leaves of the game tree.
+    pub game_leaf_count: u64,
+    /// Hex-encoded sha256 checksum of the allocation file.
+    pub allocation_checksum: String,
+}
+
+/// Claim rewarded with a badge.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BadgeRole {
+    Airdrop,
+    Prize,
+}
+
+impl BadgeRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BadgeRole::Airdrop => "airdrop",
+            BadgeRole::Prize => "prize",
+        }
+    }
+}
+
+/// Badge sent to the badge contract and waiting for its reply.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingBadge {
+    pub address: Addr,
+    pub role: BadgeRole,
+    pub token_id: String,
+}
+
+/// Badges minted to an address for its claims.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ClaimHistory {
+    pub airdrop_badge: Option<String>,
+    pub prize_badge: Option<String>,
+}
+
+/// Second cw20 token paid with the airdrop of the registered root.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SecondaryPayout {
+    pub cw20_token_address: Addr,
+    /// Secondary tokens paid for every token of the airdrop allocation.
+    pub rate: Decimal,
+    /// Secondary tokens owed to the whole airdrop tree.
+    pub total_amount: Uint128,
+    /// Secondary tokens paid so far.
+    pub claimed_amount: Uint128,
+}
+
+/// Condition a claimer must satisfy to claim the airdrop of the registered root.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimCondition {
+    /// The claimer placed a bid in this game.
+    MinBidPlaced {},
+    /// The claimer owns at least one token of the cw721 contract.
+    HoldsNft { contract_addr: String },
+    /// The claimer holds at least `amount` tokens of the cw20 contract.
+    HoldsCw20 { contract_addr: String, amount: Uint128 },
+    /// All the conditions are satisfied.
+    And(Vec<ClaimCondition>),
+    /// At least one of the conditions is satisfied.
+    Or(Vec<ClaimCondition>),
+}
+
+impl ClaimCondition {
+    /// Number of conditions of the expression, combinators included.
+    pub fn size(&self) -> usize {
+        match self {
+            ClaimCondition::And(conditions) | ClaimCondition::Or(conditions) => {
+                1 + conditions.iter().map(ClaimCondition::size).sum::<usize>()
+            }
+            _ => 1,
+        }
+    }
+}
+
+/// Struct to manage the parameters of the game fixed at instantiation. They are packed
+/// in a single item so that a bid costs one read to check its stage, bin and payment.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GameParams {
+    /// Bid stage info.
+    pub stage_bid: Stage,
+    /// Airdrop stage info.
+    pub stage_claim_airdrop: Stage,
+    /// Claiming prize stage info.
+    pub stage_claim_prize: Stage,
+    /// Price of a game ticket.
+    pub ticket_price: Coin,
+    /// Number of allowed bins for the game.
+    pub bins: u8,
+}
+
+/// Struct to manage the running totals of the game. Unlike the `GameParams` they change
+/// with the bids and the claims, which update them with a single write.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Counters {
+    /// Number of winning addresses.
+    pub winners: Uint128,
+    /// Total prize from game tickets.
+    pub total_ticket_prize: Uint128,
+    /// Total amount attached to the bins side-pools.
+    pub total_bin_boost: Uint128,
+    /// Amount of the bins side-pools paid to winners or refunded to sponsors.
+    pub claimed_bin_boost: Uint128,
+    /// Total amount of tokens for the plain airdrop.
+    pub total_airdrop_amount: Uint128,
+    /// Total amount of tokens for the airdrop of the game winners.
+    pub total_airdrop_game_amount: Uint128,
+    /// Amount of airdropped tokens claimed, from the plain airdrop and the airdrop to
+    /// the winners.
+    pub claimed_airdrop_amount: Uint128,
+    /// Amount of the prize coming from the tickets claimed.
+    pub claimed_prize_amount: Uint128,
+    /// Amount of native tokens deposited to fund the airdrop.
+    pub funded_airdrop_native_amount: Uint128,
+    /// Leftover airdrop already withdrawn by the owner, in allocation units.
+    pub withdrawn_airdrop_amount: Uint128,
+    /// Leftover ticket prize and bin boosts already withdrawn by the owner.
+    #[serde(default)]
+    pub withdrawn_prize_amount: Uint128,
+    /// Leftover secondary payout already withdrawn by the owner.
+    #[serde(default)]
+    pub withdrawn_secondary_amount: Uint128,
+    /// Ticket prices paid by the bids, and by the owner for the imported ones, less the
+    /// refunds of the removed bids. Unlike the `total_ticket_prize` it leaves out the
+    /// late fees and the boosts: it is the pool compared to the `min_prize_pool`.
+    #[serde(default)]
+    pub total_ticket_payments: Uint128,
+}
+
+/// Struct to manage the information used by front-ends and explorers to render the game.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct GameMetadata {
+    /// Title of the campaign.
+    pub title: Option<String>,
+    /// Description of the campaign.
+    pub description: Option<String>,
+    /// Image or website associated to the campaign.
+    pub url: Option<String>,
+    /// Hex-encoded sha256 hash of the terms and conditions document.
+    pub terms_hash: Option<String>,
+}
+
+/// Struct to record an action performed by the contract owner.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdminAction {
+    /// Block height at which the action has been executed.
+    pub height: u64,
+    /// Address that executed the action.
+    pub sender: Addr,
+    /// Name of the executed action, e.g. `register_merkle_roots`.
+    pub action: String,
+    /// Human readable summary of the action payload.
+    pub summary: String,
+}
+
+/// Airdrop round with its own tree and claim stage, funded on registration. Amounts are
+/// in allocation units, like the ones of the game airdrop.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AirdropRound {
+    pub merkle_root: String,
+    pub stage: Stage,
+    pub total_amount: Uint128,
+    pub claimed_amount: Uint128,
+    /// Whether the owner has withdrawn the unclaimed allocations.
+    pub withdrawn: bool,
+}
+
+/// Withdrawal destination of the owner. The `Withdraw*` messages pay only the active
+/// ones, activated with `ActivateTreasury` once `active_at` is reached.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Treasury {
+    pub active_at: Scheduled,
+    pub active: bool,
+}
+
+/// Withdrawal of the owner, as proposed to the guardian. Each one is run like the
+/// `Withdraw*` message of the same name.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Withdrawal {
+    Airdrop { address: Addr, amount: Option<Uint128> },
+    SecondaryPayout { address: Addr },
+    Prize { address: Addr },
+    Round { round: u8, address: Addr },
+    RetiredPayout { cw20_token_address: Addr, address: Addr },
+}
+
+/// Withdrawal waiting for the approval of the guardian until `expires`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WithdrawalProposal {
+    pub withdrawal: Withdrawal,
+    pub proposer: Addr,
+    pub expires: Scheduled,
+}
+
+/// Kind of payout recorded in the claim log.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimKind {
+    Airdrop,
+    WithheldAirdrop,
+    Prize,
+    PrizeAirdrop,
+    TicketRefund,
+    RoundAirdrop,
+}
+
+/// Struct to record a payout made by the contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimRecord {
+    /// Address entitled to the payout, which may have routed it to another recipient.
+    pub address: Addr,
+    pub kind: ClaimKind,
+    /// Amount paid, in the airdrop token for airdrop kinds and in the ticket denom otherwise.
+    pub amount: Uint128,
+    pub height: u64,
+    pub time: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+/// Struct to manage start and end of static stages. A stage is active from the block of
+/// its start, included, to the block of its end, excluded: a stage of height `start` and
+/// duration `n` accepts messages in blocks `start..start + n`, and the next stage can
+/// start at `start + n`. Every handler checks the stages through these helpers.
 pub struct Stage {
     /// Starting event for the stage.
     pub start: Scheduled,
@@ -21,73 +635,372 @@ pub struct Stage {
     pub duration: Duration,
 }
 
+/// Position of a block relative to a stage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StageStatus {
+    NotStarted,
+    Active,
+    Ended,
+}
+
+impl Stage {
+    /// Event ending the stage.
+    pub fn end(&self) -> StdResult<Scheduled> {
+        Ok(stage_end(self)?)
+    }
+
+    /// Event starting the final `fraction` of the stage, at most 1.
+    pub fn final_part_start(&self, fraction: Decimal) -> StdResult<Scheduled> {
+        let remaining = |length: u64| length - (Uint128::from(length) * fraction).u128() as u64;
+        let offset = match self.duration {
+            Duration::Height(height) => Duration::Height(remaining(height)),
+            Duration::Time(secs) => Duration::Time(remaining(secs)),
+        };
+        Ok(checked_add(self.start, offset)?)
+    }
+
+    /// True from the block of the start, included.
+    pub fn has_started(&self, block: &BlockInfo) -> bool {
+        self.start.is_triggered(block)
+    }
+
+    /// True from the block of the end, included.
+    pub fn has_ended(&self, block: &BlockInfo) -> StdResult<bool> {
+        Ok(self.end()?.is_triggered(block))
+    }
+
+    pub fn status(&self, block: &BlockInfo) -> StdResult<StageStatus> {
+        if !self.has_started(block) {
+            Ok(StageStatus::NotStarted)
+        } else if self.has_ended(block)? {
+            Ok(StageStatus::Ended)
+        } else {
+            Ok(StageStatus::Active)
+        }
+    }
+
+    pub fn is_active(&self, block: &BlockInfo) -> StdResult<bool> {
+        Ok(self.status(block)? == StageStatus::Active)
+    }
+
+    /// Fails with `StageNotStarted` or `StageEnded` unless the block is within the stage.
+    pub fn ensure_active(
+        &self,
+        block: &BlockInfo,
+        stage_name: StageName,
+    ) -> Result<(), ContractError> {
+        match self.status(block)? {
+            StageStatus::NotStarted => {
+                Err(ContractError::StageNotStarted { stage_name: stage_name.into() })
+            }
+            StageStatus::Ended => Err(ContractError::StageEnded { stage_name: stage_name.into() }),
+            StageStatus::Active => Ok(()),
+        }
+    }
+
+    /// Moves the start of the stage to the block, keeping its duration.
+    pub fn start_at(&mut self, block: &BlockInfo) {
+        self.start = match self.start {
+            Scheduled::AtHeight(_) => Scheduled::AtHeight(block.height),
+            Scheduled::AtTime(_) => Scheduled::AtTime(block.time),
+        };
+    }
+
+    /// Shortens the stage, started by the block, so that it ends at the block.
+    pub fn end_at(&mut self, block: &BlockInfo) {
+        self.duration = match self.start {
+            Scheduled::AtHeight(height) => Duration::Height(block.height.saturating_sub(height)),
+            Scheduled::AtTime(time) => Duration::Time(block.time.seconds().saturating_sub(time.seconds())),
+        };
+    }
+
+    /// Lengthens the stage by `extension`, of the same kind as its duration.
+    pub fn extend(&mut self, extension: Duration) -> StdResult<()> {
+        self.duration = checked_extend(self.duration, extension)?;
+        Ok(())
+    }
+}
+
 /// Storage to manage contract configuration.
-pub const CONFIG_KEY: &str = "config";
 pub const CONFIG: Item<Config> = Item::new(CONFIG_KEY);
 
-/// Storage for the bid stage info.
-pub const STAGE_BID_KEY: &str = "stage_bid";
-pub const STAGE_BID: Item<Stage> = Item::new(STAGE_BID_KEY);
+/// Storage for the addresses allowed to register the Merkle roots on behalf of the owner.
+pub const OPERATORS: Map<&Addr, Empty> = Map::new(OPERATORS_PREFIX);
 
-/// Storage for the airdrop stage info.
-pub const STAGE_CLAIM_AIRDROP_KEY: &str = "stage_claim_airdrop";
-pub const STAGE_CLAIM_AIRDROP: Item<Stage> = Item::new(STAGE_CLAIM_AIRDROP_KEY);
+/// Storage to manage the game metadata.
+pub const GAME_METADATA: Item<GameMetadata> = Item::new(GAME_METADATA_KEY);
 
-/// Storage for the claiming prize stage info.
-pub const STAGE_CLAIM_PRIZE_KEY: &str = "stage_claim_prize";
-pub const STAGE_CLAIM_PRIZE: Item<Stage> = Item::new(STAGE_CLAIM_PRIZE_KEY);
+/// Storage for the parameters of the game, read together by most messages.
+pub const GAME_PARAMS: Item<GameParams> = Item::new(GAME_PARAMS_KEY);
 
-/// Storage to save the first game ticket price.
-pub const TICKET_PRICE_KEY: &str = "ticket_price";
-pub const TICKET_PRICE: Item<Coin> = Item::new(TICKET_PRICE_KEY);
+/// Storage to save the address that placed a bid with each key, indexed by the sha256
+/// hash of the compressed secp256k1 public key.
+pub const PUBKEY_BIDS: Map<&[u8], Addr> = Map::new(PUBKEY_BIDS_PREFIX);
 
-/// Storage to save the number of allowed bins for the game.
-pub const BINS_PREFIX: &str = "bins";
-pub const BINS: Item<u8> = Item::new(BIDS_PREFIX);
+/// Storage for the sha256 hash of the key attached to the bid of each address, which
+/// discloses the bin to its owner while the bids are hidden.
+pub const BID_KEYS: Map<&Addr, Vec<u8>> = Map::new(BID_KEYS_PREFIX);
 
 /// Storage to manage the bid of each address.
-pub const BIDS_PREFIX: &str = "bids";
-pub const BIDS: Map<&Addr, u8> = Map::new("bids");
+pub const BIDS: Map<&Addr, u8> = Map::new(BIDS_PREFIX);
+
+/// Storage for the number of times each address changed its bid.
+pub const BID_CHANGES: Map<&Addr, u8> = Map::new(BID_CHANGES_PREFIX);
+
+/// Storage for the number of bids placed on each bin, kept along with `BIDS`.
+pub const BIN_BIDS: Map<u8, u32> = Map::new(BIN_BIDS_PREFIX);
+
+/// Counts a bid placed on `bin`.
+pub fn add_bin_bid(storage: &mut dyn Storage, bin: u8) -> StdResult<()> {
+    BIN_BIDS.update(storage, bin, |bids| -> StdResult<_> {
+        Ok(bids.unwrap_or_default().saturating_add(1))
+    })?;
+    Ok(())
+}
+
+/// Stops counting a bid removed from `bin`.
+pub fn remove_bin_bid(storage: &mut dyn Storage, bin: u8) -> StdResult<()> {
+    let bids = BIN_BIDS.may_load(storage, bin)?.unwrap_or_default().saturating_sub(1);
+    match bids {
+        0 => BIN_BIDS.remove(storage, bin),
+        bids => BIN_BIDS.save(storage, bin, &bids)?,
+    }
+    Ok(())
+}
+
+/// Storage for the payment and the block of each bid.
+pub const BID_RECEIPTS: Map<&Addr, BidReceipt> = Map::new(BID_RECEIPTS_PREFIX);
+
+/// Storage for the last address imported from each previous game contract, so that
+/// every page of its bids is imported once.
+pub const BID_IMPORTS: Map<&Addr, String> = Map::new(BID_IMPORTS_PREFIX);
+
+/// Storage for the vendor tickets used to bid, and the bidder using each of them.
+pub const USED_TICKETS: Map<u64, Addr> = Map::new(USED_TICKETS_PREFIX);
+
+/// Storage for the vendor ticket used by each bidder, if any.
+pub const TICKET_BIDS: Map<&Addr, u64> = Map::new(TICKET_BIDS_PREFIX);
 
 /// Storage for the Merkle root of the airdrop.
-pub const MERKLE_ROOT_AIRDROP_PREFIX: &str = "merkle_root_airdrop";
 pub const MERKLE_ROOT_AIRDROP: Item<String> = Item::new(MERKLE_ROOT_AIRDROP_PREFIX);
 
+/// Storage for the airdrop rounds registered after the game airdrop, which is round 0.
+pub const ROUNDS: Map<u8, AirdropRound> = Map::new(ROUNDS_PREFIX);
+
+/// Storage for the id of the last round registered, 0 if there is none.
+pub const LATEST_ROUND: Item<u8> = Item::new(LATEST_ROUND_KEY);
+
+/// Storage to save if an address has claimed its allocation of a round.
+pub const ROUND_CLAIMED: Map<(u8, &Addr), bool> = Map::new(ROUND_CLAIMED_PREFIX);
+
+/// Storage for the withdrawal destinations registered by the owner.
+pub const TREASURIES: Map<&Addr, Treasury> = Map::new(TREASURIES_PREFIX);
+
+/// Storage for the withdrawals proposed to the guardian, by id.
+pub const WITHDRAWAL_PROPOSALS: Map<u64, WithdrawalProposal> = Map::new(WITHDRAWAL_PROPOSALS_PREFIX);
+
+/// Storage for the id of the last withdrawal proposed, 0 if there is none.
+pub const WITHDRAWAL_PROPOSAL_COUNT: Item<u64> = Item::new(WITHDRAWAL_PROPOSAL_COUNT_KEY);
+
+/// Storage for the balances of the payout tokens replaced by `SwapPayoutToken`, held until
+/// the owner withdraws them.
+pub const RETIRED_PAYOUT_TOKENS: Map<&Addr, Uint128> = Map::new(RETIRED_PAYOUT_TOKENS_PREFIX);
+
+/// Storage for the hex-encoded sha256 hash of the game Merkle root concatenated to a
+/// salt, committed before the end of the bid stage.
+pub const OUTCOME_COMMITMENT: Item<String> = Item::new(OUTCOME_COMMITMENT_KEY);
+
+/// Storage for the game Merkle root matching the commitment.
+pub const REVEALED_OUTCOME: Item<String> = Item::new(REVEALED_OUTCOME_KEY);
+
+/// Storage for the flag set by `OpenClaims` once the contract holds the registered
+/// cw20 totals.
+pub const CLAIMS_FUNDED: Item<bool> = Item::new(CLAIMS_FUNDED_KEY);
+
+/// Storage for the reason of the claims freeze. Claims are frozen while it is set.
+pub const CLAIMS_FROZEN: Item<String> = Item::new(CLAIMS_FROZEN_KEY);
+
+/// Storage for the reason of the owner veto on the prizes. Prizes cannot be claimed while
+/// it is set.
+pub const PRIZE_VETO: Item<String> = Item::new(PRIZE_VETO_KEY);
+
+/// Storage for the storage inconsistency detected by an operation moving funds. Such
+/// operations are blocked while it is set.
+pub const FAILSAFE: Item<Failsafe> = Item::new(FAILSAFE_KEY);
+
+/// Storage for the rate converting the allocations of the Merkle trees into the cw20
+/// token paid since `SwapPayoutToken`. Allocations are paid one to one while it is unset.
+pub const PAYOUT_RATE: Item<Decimal> = Item::new(PAYOUT_RATE_KEY);
+
+/// Storage for the badge whose mint is waiting for the reply of the badge contract.
+pub const PENDING_BADGE: Item<PendingBadge> = Item::new(PENDING_BADGE_KEY);
+
+/// Storage for the split of the native prize registered by each bidder.
+pub const PAYOUT_SPLITS: Map<&Addr, Vec<PayoutShare>> = Map::new(PAYOUT_SPLITS_PREFIX);
+
+/// Storage for the airdrop above the KYC threshold held back from the claims of the
+/// addresses without an attestation.
+pub const WITHHELD_AIRDROP: Map<&Addr, Uint128> = Map::new(WITHHELD_AIRDROP_PREFIX);
+
+/// Storage for the badges minted to every claimer.
+pub const CLAIM_HISTORY: Map<&Addr, ClaimHistory> = Map::new(CLAIM_HISTORY_PREFIX);
+
+/// Storage for the second asset paid with the airdrop, replaced with the roots.
+pub const SECONDARY_PAYOUT: Item<SecondaryPayout> = Item::new(SECONDARY_PAYOUT_KEY);
+
+/// Storage for the condition gating the airdrop claims, replaced with the roots.
+pub const CLAIM_CONDITION: Item<ClaimCondition> = Item::new(CLAIM_CONDITION_KEY);
+
+/// Storage for the metadata of the registered Merkle trees.
+pub const TREE_METADATA: Item<TreeMetadata> = Item::new(TREE_METADATA_KEY);
+
 /// Storage for the Merkle root of the game.
-pub const MERKLE_ROOT_GAME_PREFIX: &str = "merkle_root_game";
 pub const MERKLE_ROOT_GAME: Item<String> = Item::new(MERKLE_ROOT_GAME_PREFIX);
 
-/// Storage for the amount of airdropped tokens claimed.
-/// This variable will consider:
-/// - Amount from simple airdrop.
-/// - Amount airdropped to winners of the first game.
-pub const CLAIMED_AIRDROP_AMOUNT_PREFIX: &str = "claimed_amount";
-pub const CLAIMED_AIRDROP_AMOUNT: Item<Uint128> = Item::new(CLAIMED_AIRDROP_AMOUNT_PREFIX);
+/// Storage for the running totals of the game, read once by every claim.
+pub const COUNTERS: Item<Counters> = Item::new(COUNTERS_KEY);
+
+/// Storage to keep track of the amount added to the ticket prize by each sponsor.
+pub const BOOSTS: Map<&Addr, Uint128> = Map::new(BOOSTS_PREFIX);
+
+/// Sponsors indexed by boosted amount, used to list the top boosters.
+pub const BOOSTERS_BY_AMOUNT: Map<(u128, &Addr), Empty> = Map::new(BOOSTERS_BY_AMOUNT_PREFIX);
+
+/// Storage to keep track of the amount attached to a bin by each sponsor.
+pub const BIN_BOOSTS: Map<(u8, &Addr), Uint128> = Map::new(BIN_BOOSTS_PREFIX);
+
+/// Storage for the side-pool of each bin, shared among the winners who bid on it.
+pub const BIN_POOLS: Map<u8, Uint128> = Map::new(BIN_POOLS_PREFIX);
 
-/// Storage for the amount of the prize coming from the tickets claimed.
-pub const CLAIMED_PRIZE_AMOUNT_PREFIX: &str = "claimed_prize";
-pub const CLAIMED_PRIZE_AMOUNT: Item<Uint128> = Item::new(CLAIMED_PRIZE_AMOUNT_PREFIX);
+/// Storage for the number of winners who bid on each bin.
+pub const BIN_WINNERS: Map<u8, Uint128> = Map::new(BIN_WINNERS_PREFIX);
 
-/// Storage to save the number of winning addresses.
-pub const WINNERS_PREFIX: &str = "winners";
-pub const WINNERS: Item<Uint128> = Item::new(WINNERS_PREFIX);
+/// Storage for the prize claimed by the winners of each bin, tickets and side-pool.
+pub const BIN_CLAIMED_PRIZE: Map<u8, Uint128> = Map::new(BIN_CLAIMED_PRIZE_PREFIX);
 
-/// Storage to keep track of the total prize from game tickets.
-pub const TOTAL_TICKET_PRIZE_KEY: &str = "total_ticket_prize";
-pub const TOTAL_TICKET_PRIZE: Item<Uint128> = Item::new(TOTAL_TICKET_PRIZE_KEY);
+/// Storage to save the prize pools whose dust has already been assigned.
+pub const DUST_PAID: Map<&str, bool> = Map::new(DUST_PAID_PREFIX);
 
-/// Total amount of tokens for the plain airdrop.
-pub const TOTAL_AIRDROP_AMOUNT_PREFIX: &str = "total_amount_airdrop";
-pub const TOTAL_AIRDROP_AMOUNT: Item<Uint128> = Item::new(TOTAL_AIRDROP_AMOUNT_PREFIX);
+/// Storage for the totals frozen at the end of the claim airdrop stage.
+pub const CLOSED_SNAPSHOT: Item<ClosedSnapshot> = Item::new(CLOSED_SNAPSHOT_KEY);
 
-/// Total amount of tokens for the airdrop of the game winners.
-pub const TOTAL_AIRDROP_GAME_AMOUNT_PREFIX: &str = "total_amount_game";
-pub const TOTAL_AIRDROP_GAME_AMOUNT: Item<Uint128> = Item::new(TOTAL_AIRDROP_GAME_AMOUNT_PREFIX);
+/// Storage for the proofs verified in advance, removed once the airdrop is claimed.
+pub const CACHED_PROOFS: Map<&Addr, CachedProof> = Map::new(CACHED_PROOFS_PREFIX);
 
 /// Storage to save if an address has claimed the airdrop or not.
-pub const CLAIM_AIRDROP_PREFIX: &str = "claim_airdrop";
 pub const CLAIM_AIRDROP: Map<&Addr, bool> = Map::new(CLAIM_AIRDROP_PREFIX);
 
+/// Number of leaves tracked by a word of the claimed bitmap.
+pub const BITMAP_WORD_BITS: u32 = 128;
+
+/// Storage for the airdrop claims of the claim index mode: bit `index % 128` of the
+/// word `index / 128` is set once the leaf `index` has been claimed, so that a single
+/// entry tracks 128 claims.
+pub const CLAIMED_BITMAP: Map<u32, Uint128> = Map::new(CLAIMED_BITMAP_PREFIX);
+
+/// Whether the airdrop leaf `index` has been claimed.
+pub fn is_index_claimed(storage: &dyn Storage, index: u32) -> StdResult<bool> {
+    let word = CLAIMED_BITMAP.may_load(storage, index / BITMAP_WORD_BITS)?.unwrap_or_default();
+    Ok(word.u128() & (1 << (index % BITMAP_WORD_BITS)) != 0)
+}
+
+/// Marks the airdrop leaf `index` as claimed.
+pub fn set_index_claimed(storage: &mut dyn Storage, index: u32) -> StdResult<()> {
+    CLAIMED_BITMAP.update(storage, index / BITMAP_WORD_BITS, |word| -> StdResult<_> {
+        Ok(Uint128::new(word.unwrap_or_default().u128() | (1 << (index % BITMAP_WORD_BITS))))
+    })?;
+    Ok(())
+}
+
+/// Storage for the addresses excluded by the owner from the airdrop claims, e.g. users
+/// compensated off-chain. They are treated as if they had already claimed.
+pub const EXCLUDED: Map<&Addr, Empty> = Map::new(EXCLUDED_PREFIX);
+
+/// Number of addresses in `EXCLUDED`.
+pub const EXCLUDED_COUNT: Item<u64> = Item::new(EXCLUDED_COUNT_KEY);
+
+/// Claims of the addresses tagged with a cohort, aggregated as they are made.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct CohortStats {
+    /// Addresses tagged with the cohort.
+    pub tagged: u32,
+    /// Airdrop claims, several per address in the claim index mode.
+    pub airdrop_claims: u32,
+    /// Airdropped tokens claimed, withheld amounts and shares of the winners included.
+    pub claimed_airdrop_amount: Uint128,
+    /// Prize claims of the winners.
+    pub prize_claims: u32,
+    /// Prize claimed from the tickets and the bin side-pools.
+    pub claimed_prize_amount: Uint128,
+}
+
+/// Storage for the cohort of the addresses tagged by the owner, for the analytics.
+pub const COHORTS: Map<&Addr, u8> = Map::new(COHORTS_PREFIX);
+
+/// Storage for the claims of each cohort.
+pub const COHORT_STATS: Map<u8, CohortStats> = Map::new(COHORT_STATS_PREFIX);
+
+/// Adds a claim of `address` to the statistics of its cohort, if it has been tagged.
+/// `record` fails on overflow rather than saturating, so the statistics never drift.
+pub fn record_cohort_claim(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    record: impl FnOnce(&mut CohortStats) -> StdResult<()>,
+) -> StdResult<()> {
+    if let Some(cohort) = COHORTS.may_load(storage, address)? {
+        let mut stats = COHORT_STATS.may_load(storage, cohort)?.unwrap_or_default();
+        record(&mut stats)?;
+        COHORT_STATS.save(storage, cohort, &stats)?;
+    }
+    Ok(())
+}
+
 /// Storage to save if a winning address has claimed the prize or not.
-pub const CLAIM_PRIZE_PREFIX: &str = "claim_prize";
-pub const CLAIM_PRIZE: Map<&Addr, bool> = Map::new(CLAIM_PRIZE_PREFIX);
\ No newline at end of file
+pub const CLAIM_PRIZE: Map<&Addr, bool> = Map::new(CLAIM_PRIZE_PREFIX);
+
+/// Storage for the block in which each address claimed the airdrop.
+pub const AIRDROP_CLAIMED_AT: Map<&Addr, ClaimedAt> = Map::new(AIRDROP_CLAIMED_AT_PREFIX);
+
+/// Storage for the block in which each winner claimed the prize.
+pub const PRIZE_CLAIMED_AT: Map<&Addr, ClaimedAt> = Map::new(PRIZE_CLAIMED_AT_PREFIX);
+
+/// Claims accepted in each block, indexed by stage name and height.
+pub const CLAIM_USAGE: Map<(&str, u64), ClaimUsage> = Map::new(CLAIM_USAGE_PREFIX);
+
+/// Append-only log of the actions performed by the contract owner.
+pub const ADMIN_ACTIONS: Map<u64, AdminAction> = Map::new(ADMIN_ACTIONS_PREFIX);
+
+/// Number of actions recorded in the admin log, used as id of the next one.
+pub const ADMIN_ACTIONS_COUNT: Item<u64> = Item::new(ADMIN_ACTIONS_COUNT_KEY);
+
+/// Append-only log of the payouts made by the contract, indexed by claim sequence number.
+pub const CLAIM_LOG: Map<u64, ClaimRecord> = Map::new(CLAIM_LOG_PREFIX);
+
+/// Number of payouts recorded in the claim log, used as sequence number of the next one.
+pub const CLAIM_LOG_COUNT: Item<u64> = Item::new(CLAIM_LOG_COUNT_KEY);
+
+/// Appends a payout to the claim log. Zero amounts are not recorded.
+pub fn record_claim(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    kind: ClaimKind,
+    amount: Uint128,
+    claimed_at: &ClaimedAt,
+) -> StdResult<()> {
+    if amount.is_zero() {
+        return Ok(());
+    }
+    let seq = CLAIM_LOG_COUNT.may_load(storage)?.unwrap_or_default();
+    CLAIM_LOG.save(
+        storage,
+        seq,
+        &ClaimRecord {
+            address: address.clone(),
+            kind,
+            amount,
+            height: claimed_at.height,
+            time: claimed_at.time,
+        },
+    )?;
+    CLAIM_LOG_COUNT.save(storage, &(seq + 1))
+}
@@ -0,0 +1,517 @@
+use cosmwasm_std::{Addr, Api, Decimal, Env, StdResult};
+use cw_utils::Duration;
+
+use crate::constants::{StageName, ADDRESS_PLACEHOLDER};
+use crate::error::ContractError;
+use crate::limits::{
+    check_addresses_count, check_max_proof_length, DEFAULT_TREASURY_TIMELOCK, check_memo_length, MAX_ADDRESSES_PER_CALL,
+    MAX_BATCH_SIZE, MAX_PROOF_NODES,
+};
+use crate::msg::{
+    EligibilityHookMsg, InstantiateMsg, KycGateMsg, LateBidPenaltyMsg, StageMsg, WithdrawalGuardMsg,
+};
+use crate::stage_math::checked_add;
+use crate::state::{
+    Config, EligibilityHook, GameMetadata, GameParams, GovernanceMode, KycGate, LateBidPenalty, LeafFormat, NoWinnersPolicy, PayoutMode, PrizeMode,
+    RoundingPolicy, Stage, WithdrawalGuard,
+};
+
+/// Parameters of the game checked by `InstantiateMsg::validate`, ready to be stored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidatedInstantiateMsg {
+    /// The owner is None if the message does not set it: the sender of the
+    /// instantiation becomes the owner.
+    pub config: Config,
+    pub metadata: GameMetadata,
+    pub params: GameParams,
+    /// Registry contract to announce the game to.
+    pub registry: Option<Addr>,
+}
+
+impl InstantiateMsg {
+    /// Checks the whole message against the block of `env`, where the relative stages
+    /// start. Used by `instantiate`, and by the contracts deploying games to reject a
+    /// message before sending it.
+    pub fn validate(&self, api: &dyn Api, env: &Env) -> Result<ValidatedInstantiateMsg, ContractError> {
+        let config = self.validate_config(api)?;
+
+        let metadata = self.metadata.clone().unwrap_or_default();
+        validate_metadata(&metadata)?;
+
+        if self.ticket_price.denom.is_empty() || self.ticket_price.amount.is_zero() {
+            return Err(ContractError::InvalidTicketPrice {});
+        }
+        if self.bins == 0 {
+            return Err(ContractError::InvalidBins {});
+        }
+
+        let (stage_bid, stage_claim_airdrop, stage_claim_prize) = validate_stages(
+            &config,
+            env,
+            self.stage_bid.clone(),
+            self.stage_claim_airdrop.clone(),
+            self.stage_claim_prize.clone(),
+        )?;
+
+        Ok(ValidatedInstantiateMsg {
+            config,
+            metadata,
+            params: GameParams {
+                stage_bid,
+                stage_claim_airdrop,
+                stage_claim_prize,
+                ticket_price: self.ticket_price.clone(),
+                bins: self.bins,
+            },
+            registry: self.registry.as_ref().map(|registry| api.addr_validate(registry)).transpose()?,
+        })
+    }
+
+    fn validate_config(&self, api: &dyn Api) -> Result<Config, ContractError> {
+        if let Some(proxies) = &self.approved_proxies {
+            check_addresses_count(proxies, MAX_ADDRESSES_PER_CALL)?;
+        }
+
+        let config = Config {
+            owner: self.owner.as_ref().map(|owner| api.addr_validate(owner)).transpose()?,
+            cw20_token_address: api.addr_validate(&self.cw20_token_address)?,
+            airdrop_native_denom: self.airdrop_native_denom.clone(),
+            eligibility_hook: self
+                .eligibility_hook
+                .clone()
+                .map(|hook| validate_eligibility_hook(api, hook))
+                .transpose()?,
+            prize_mode: self.prize_mode.clone().unwrap_or_default(),
+            rounding_policy: self
+                .rounding_policy
+                .clone()
+                .map(|policy| validate_rounding_policy(api, policy))
+                .transpose()?
+                .unwrap_or_default(),
+            pubkey_bids: self.pubkey_bids.unwrap_or(false),
+            approved_proxies: self
+                .approved_proxies
+                .iter()
+                .flatten()
+                .map(|proxy| api.addr_validate(proxy))
+                .collect::<StdResult<_>>()?,
+            claim_rate_limit: self.claim_rate_limit.clone(),
+            ticket_vendor: self
+                .ticket_vendor
+                .as_ref()
+                .map(|vendor| api.addr_validate(vendor))
+                .transpose()?,
+            max_proof_length: self.max_proof_length.unwrap_or(MAX_PROOF_NODES),
+            badge_contract: self
+                .badge_contract
+                .as_ref()
+                .map(|badge| api.addr_validate(badge))
+                .transpose()?,
+            max_bid_changes: self.max_bid_changes,
+            late_bid_penalty: self
+                .late_bid_penalty
+                .clone()
+                .map(|penalty| validate_late_bid_penalty(api, penalty))
+                .transpose()?,
+            private_bins: self.private_bins.unwrap_or(false),
+            hidden_bids: self.hidden_bids.unwrap_or(false),
+            minimal_attributes: self.minimal_attributes.unwrap_or(false),
+            prize_timelock: self.prize_timelock,
+            payout_mode: self.payout_mode.clone().unwrap_or_default(),
+            no_winners_policy: self
+                .no_winners_policy
+                .clone()
+                .map(|policy| validate_no_winners_policy(api, policy))
+                .transpose()?
+                .unwrap_or_default(),
+            governance_mode: self
+                .governance_mode
+                .clone()
+                .map(|mode| validate_governance_mode(api, mode))
+                .transpose()?
+                .unwrap_or_default(),
+            kyc_gate: self
+                .kyc_gate
+                .clone()
+                .map(|gate| validate_kyc_gate(api, gate))
+                .transpose()?,
+            leaf_format: self.leaf_format.clone().unwrap_or_default(),
+            claim_index: self.claim_index.unwrap_or(false),
+            test_mode: self.test_mode.unwrap_or(false),
+            min_prize_pool: self.min_prize_pool,
+            treasury_timelock: self.treasury_timelock.unwrap_or(DEFAULT_TREASURY_TIMELOCK),
+            withdrawal_guard: self
+                .withdrawal_guard
+                .clone()
+                .map(|guard| validate_withdrawal_guard(api, guard))
+                .transpose()?,
+            max_batch_size: MAX_BATCH_SIZE,
+            max_addresses_per_call: MAX_ADDRESSES_PER_CALL,
+        };
+
+        if matches!(config.min_prize_pool, Some(minimum) if minimum.is_zero()) {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+
+        // Release builds cannot be deployed in test mode.
+        if config.test_mode && !cfg!(feature = "test_mode") {
+            return Err(ContractError::TestModeDisabled {});
+        }
+        check_max_proof_length(config.max_proof_length)?;
+        if let Some(limit) = &config.claim_rate_limit {
+            if limit.window == 0 {
+                return Err(ContractError::InvalidClaimRateLimit {});
+            }
+        }
+        if let PayoutMode::SendWithMemo { campaign_id } = &config.payout_mode {
+            check_memo_length("campaign_id", &Some(campaign_id.clone()))?;
+        }
+        // An empty campaign id would hash the plain leaves.
+        if let LeafFormat::CampaignId { campaign_id } = &config.leaf_format {
+            if campaign_id.is_empty() {
+                return Err(ContractError::InvalidLeafFormat {});
+            }
+            check_memo_length("campaign_id", &Some(campaign_id.clone()))?;
+        }
+        // Winners of the Merkle amounts mode are only known from their prize claims.
+        if config.prize_mode == PrizeMode::MerkleAmounts
+            && config.no_winners_policy != NoWinnersPolicy::Withdraw
+        {
+            return Err(ContractError::InvalidNoWinnersPolicy {});
+        }
+        Ok(config)
+    }
+}
+
+/// Resolves the three stages of the game against the block of `env` and checks their
+/// order. Used at instantiation and when the owner reschedules the game.
+pub fn validate_stages(
+    config: &Config,
+    env: &Env,
+    stage_bid: StageMsg,
+    stage_claim_airdrop: StageMsg,
+    stage_claim_prize: StageMsg,
+) -> Result<(Stage, Stage, Stage), ContractError> {
+    // Relative stages start from the current block.
+    let stage_bid = stage_bid.resolve(&env.block)?;
+    let stage_claim_airdrop = stage_claim_airdrop.resolve(&env.block)?;
+    let stage_claim_prize = stage_claim_prize.resolve(&env.block)?;
+
+    // Every stage must have an end, i.e. a start and a duration of the same kind.
+    let stage_bid_end = stage_bid.end()?;
+    let stage_claim_airdrop_end = stage_claim_airdrop.end()?;
+    stage_claim_prize.end()?;
+
+    // Bid stage has to start after the current block, but on a devnet the
+    // game can be played right away.
+    if stage_bid.has_started(&env.block) && !config.test_mode {
+        return Err(ContractError::BidStartPassed {});
+    }
+
+    // Airdrop claim stage has to start after bidding stage end.
+    if stage_bid_end > stage_claim_airdrop.start {
+        let first = StageName::Bid.into();
+        let second = StageName::ClaimAirdrop.into();
+        return Err(ContractError::StagesOverlap { first, second });
+    }
+
+    // Game prize claim has to start after airdrop claim stage end.
+    if stage_claim_airdrop_end > stage_claim_prize.start {
+        let first = StageName::ClaimAirdrop.into();
+        let second = StageName::ClaimPrize.into();
+        return Err(ContractError::StagesOverlap { first, second });
+    }
+
+    // Prizes must be released before the end of the claim prize stage.
+    if let Some(timelock) = config.prize_timelock {
+        if checked_add(stage_claim_prize.start, timelock)? >= stage_claim_prize.end()? {
+            return Err(ContractError::PrizeTimelockTooLong {});
+        }
+    }
+
+    Ok((stage_bid, stage_claim_airdrop, stage_claim_prize))
+}
+
+pub fn validate_metadata(metadata: &GameMetadata) -> Result<(), ContractError> {
+    check_memo_length("title", &metadata.title)?;
+    check_memo_length("description", &metadata.description)?;
+    check_memo_length("url", &metadata.url)?;
+
+    // Terms hash must be a valid sha256 hex string.
+    if let Some(terms_hash) = &metadata.terms_hash {
+        let mut hash_buf: [u8; 32] = [0; 32];
+        hex::decode_to_slice(terms_hash, &mut hash_buf)?;
+    }
+    Ok(())
+}
+
+fn validate_rounding_policy(
+    api: &dyn Api,
+    policy: RoundingPolicy,
+) -> Result<RoundingPolicy, ContractError> {
+    if let RoundingPolicy::DustToTreasury { address } = &policy {
+        api.addr_validate(address.as_str())?;
+    }
+    Ok(policy)
+}
+
+fn validate_kyc_gate(api: &dyn Api, gate: KycGateMsg) -> Result<KycGate, ContractError> {
+    // A zero threshold would withhold every claim entirely: use the eligibility hook.
+    if gate.threshold.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    Ok(KycGate {
+        registry: api.addr_validate(&gate.registry)?,
+        threshold: gate.threshold,
+    })
+}
+
+fn validate_withdrawal_guard(
+    api: &dyn Api,
+    guard: WithdrawalGuardMsg,
+) -> Result<WithdrawalGuard, ContractError> {
+    // Without a window no proposal could ever be approved.
+    if matches!(guard.window, Duration::Height(0) | Duration::Time(0)) {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    Ok(WithdrawalGuard {
+        guardian: api.addr_validate(&guard.guardian)?,
+        threshold: guard.threshold,
+        prize_threshold: guard.prize_threshold,
+        secondary_threshold: guard.secondary_threshold,
+        window: guard.window,
+    })
+}
+
+fn validate_governance_mode(
+    api: &dyn Api,
+    mode: GovernanceMode,
+) -> Result<GovernanceMode, ContractError> {
+    if let GovernanceMode::Multisig { address } = &mode {
+        api.addr_validate(address.as_str())?;
+    }
+    Ok(mode)
+}
+
+fn validate_no_winners_policy(
+    api: &dyn Api,
+    policy: NoWinnersPolicy,
+) -> Result<NoWinnersPolicy, ContractError> {
+    match &policy {
+        NoWinnersPolicy::Treasury { address } => {
+            api.addr_validate(address.as_str())?;
+        }
+        NoWinnersPolicy::Rollover { game } => {
+            api.addr_validate(game.as_str())?;
+        }
+        NoWinnersPolicy::Withdraw | NoWinnersPolicy::Refund => {}
+    }
+    Ok(policy)
+}
+
+fn validate_late_bid_penalty(
+    api: &dyn Api,
+    penalty: LateBidPenaltyMsg,
+) -> Result<LateBidPenalty, ContractError> {
+    if penalty.window.is_zero() || penalty.window > Decimal::one() || penalty.fee.is_zero() {
+        return Err(ContractError::InvalidLateBidPenalty {});
+    }
+    Ok(LateBidPenalty {
+        window: penalty.window,
+        fee: penalty.fee,
+        treasury: penalty
+            .treasury
+            .map(|treasury| api.addr_validate(&treasury))
+            .transpose()?,
+    })
+}
+
+fn validate_eligibility_hook(
+    api: &dyn Api,
+    hook: EligibilityHookMsg,
+) -> Result<EligibilityHook, ContractError> {
+    if !hook.query_template.contains(ADDRESS_PLACEHOLDER) {
+        return Err(ContractError::InvalidQueryTemplate {});
+    }
+    Ok(EligibilityHook {
+        contract_addr: api.addr_validate(&hook.contract_addr)?,
+        query_template: hook.query_template,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_env, MockApi};
+    use cosmwasm_std::{Addr, Coin, Uint128};
+    use cw_utils::{Duration, Scheduled};
+
+    use crate::constants::TEST_DENOM;
+    use crate::msg::{RelativeStage, StageMsg};
+    use crate::state::ClaimRateLimit;
+
+    fn stage(start_after: u64) -> StageMsg {
+        StageMsg::Relative(RelativeStage {
+            start_after: Duration::Height(start_after),
+            duration: Duration::Height(100),
+        })
+    }
+
+    fn msg() -> InstantiateMsg {
+        InstantiateMsg {
+            owner: None,
+            cw20_token_address: "token0000".to_string(),
+            airdrop_native_denom: None,
+            eligibility_hook: None,
+            ticket_price: Coin::new(10, TEST_DENOM),
+            bins: 10,
+            stage_bid: stage(10),
+            stage_claim_airdrop: stage(110),
+            stage_claim_prize: stage(210),
+            metadata: None,
+            prize_mode: None,
+            rounding_policy: None,
+            pubkey_bids: None,
+            approved_proxies: None,
+            claim_rate_limit: None,
+            ticket_vendor: None,
+            max_proof_length: None,
+            badge_contract: None,
+            max_bid_changes: None,
+            late_bid_penalty: None,
+            private_bins: None,
+            hidden_bids: None,
+            minimal_attributes: None,
+            prize_timelock: None,
+            payout_mode: None,
+            no_winners_policy: None,
+            governance_mode: None,
+            kyc_gate: None,
+            leaf_format: None,
+            claim_index: None,
+            registry: None,
+            test_mode: None,
+            min_prize_pool: None,
+            treasury_timelock: None,
+            withdrawal_guard: None,
+        }
+    }
+
+    #[test]
+    fn valid_msg() {
+        let env = mock_env();
+        let validated = msg().validate(&MockApi::default(), &env).unwrap();
+
+        // The owner is left to the caller, relative stages start from the block.
+        assert_eq!(validated.config.owner, None);
+        assert_eq!(validated.config.cw20_token_address, Addr::unchecked("token0000"));
+        assert_eq!(validated.config.max_proof_length, MAX_PROOF_NODES);
+        let params = validated.params;
+        assert_eq!(params.stage_bid.start, Scheduled::AtHeight(env.block.height + 10));
+        assert_eq!(params.stage_claim_prize.start, Scheduled::AtHeight(env.block.height + 210));
+        assert_eq!((params.ticket_price, params.bins), (Coin::new(10, TEST_DENOM), 10));
+
+        let msg = InstantiateMsg { owner: Some("owner0000".to_string()), ..msg() };
+        let validated = msg.validate(&MockApi::default(), &env).unwrap();
+        assert_eq!(validated.config.owner, Some(Addr::unchecked("owner0000")));
+    }
+
+    #[test]
+    fn invalid_msg() {
+        let api = MockApi::default();
+        let env = mock_env();
+        let invalid = [
+            (InstantiateMsg { ticket_price: Coin::new(0, TEST_DENOM), ..msg() }, ContractError::InvalidTicketPrice {}),
+            (InstantiateMsg { ticket_price: Coin::new(10, ""), ..msg() }, ContractError::InvalidTicketPrice {}),
+            (InstantiateMsg { bins: 0, ..msg() }, ContractError::InvalidBins {}),
+            (
+                InstantiateMsg { max_proof_length: Some(0), ..msg() },
+                ContractError::InvalidMaxProofLength { max: MAX_PROOF_NODES },
+            ),
+            (
+                InstantiateMsg {
+                    claim_rate_limit: Some(ClaimRateLimit { window: 0, max_claims: Some(1), max_amount: None }),
+                    ..msg()
+                },
+                ContractError::InvalidClaimRateLimit {},
+            ),
+            (
+                InstantiateMsg {
+                    late_bid_penalty: Some(LateBidPenaltyMsg {
+                        window: Decimal::percent(10),
+                        fee: Uint128::zero(),
+                        treasury: None,
+                    }),
+                    ..msg()
+                },
+                ContractError::InvalidLateBidPenalty {},
+            ),
+            (
+                InstantiateMsg { prize_timelock: Some(Duration::Height(100)), ..msg() },
+                ContractError::PrizeTimelockTooLong {},
+            ),
+            (
+                InstantiateMsg {
+                    prize_mode: Some(PrizeMode::MerkleAmounts),
+                    no_winners_policy: Some(NoWinnersPolicy::Refund),
+                    registry: None,
+                    test_mode: None,
+                    min_prize_pool: None,
+                    treasury_timelock: None,
+                    withdrawal_guard: None,
+                    ..msg()
+                },
+                ContractError::InvalidNoWinnersPolicy {},
+            ),
+            (
+                InstantiateMsg {
+                    leaf_format: Some(LeafFormat::CampaignId { campaign_id: "".to_string() }),
+                    ..msg()
+                },
+                ContractError::InvalidLeafFormat {},
+            ),
+            (
+                InstantiateMsg {
+                    kyc_gate: Some(KycGateMsg { registry: "registry0000".to_string(), threshold: Uint128::zero() }),
+                    ..msg()
+                },
+                ContractError::InvalidZeroAmount {},
+            ),
+            (InstantiateMsg { min_prize_pool: Some(Uint128::zero()), ..msg() }, ContractError::InvalidZeroAmount {}),
+            (
+                InstantiateMsg {
+                    withdrawal_guard: Some(WithdrawalGuardMsg {
+                        guardian: "guardian0000".to_string(),
+                        threshold: Uint128::new(100),
+                        prize_threshold: Uint128::new(100),
+                        secondary_threshold: Uint128::new(100),
+                        window: Duration::Time(0),
+                    }),
+                    ..msg()
+                },
+                ContractError::InvalidZeroAmount {},
+            ),
+            (InstantiateMsg { stage_bid: stage(0), ..msg() }, ContractError::BidStartPassed {}),
+            (
+                InstantiateMsg { stage_claim_airdrop: stage(100), ..msg() },
+                ContractError::StagesOverlap { first: "bid".to_string(), second: "claim airdrop".to_string() },
+            ),
+        ];
+        for (msg, err) in invalid {
+            assert_eq!(msg.validate(&api, &env).unwrap_err(), err);
+        }
+
+        // Only the builds for devnets accept the test mode, where the bid stage can
+        // start right away.
+        let test_mode = InstantiateMsg { test_mode: Some(true), stage_bid: stage(0), ..msg() };
+        if cfg!(feature = "test_mode") {
+            assert!(test_mode.validate(&api, &env).unwrap().config.test_mode);
+        } else {
+            assert_eq!(test_mode.validate(&api, &env).unwrap_err(), ContractError::TestModeDisabled {});
+        }
+
+        // Addresses are validated with the api.
+        let msg = InstantiateMsg { cw20_token_address: "".to_string(), ..msg() };
+        assert!(matches!(msg.validate(&api, &env).unwrap_err(), ContractError::Std(_)));
+    }
+}
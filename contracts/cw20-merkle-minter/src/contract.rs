@@ -0,0 +1,461 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    attr, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128,
+    WasmMsg,
+};
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
+use cw_utils::{Expiration, Scheduled};
+use sha2::Digest;
+use std::convert::TryInto;
+
+use crate::error::ContractError;
+use crate::msg::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, IsClaimedResponse, LatestStageResponse,
+    MerkleRootResponse, QueryMsg, TotalClaimedResponse,
+};
+use crate::state::{
+    Config, CLAIM, CONFIG, LATEST_STAGE, MERKLE_ROOT, STAGE_AMOUNT, STAGE_AMOUNT_CLAIMED,
+    STAGE_EXPIRATION, STAGE_START,
+};
+
+// Version info, for migration info
+const CONTRACT_NAME: &str = "crates.io:cw20-merkle-minter";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let owner = msg
+        .owner
+        .map_or(Ok(info.sender), |o| deps.api.addr_validate(&o))?;
+
+    let config = Config {
+        owner: Some(owner),
+        cw20_token_address: None,
+    };
+    CONFIG.save(deps.storage, &config)?;
+
+    let stage = 0;
+    LATEST_STAGE.save(deps.storage, &stage)?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::UpdateConfig { new_owner } => execute_update_config(deps, info, new_owner),
+        ExecuteMsg::SetToken { address } => execute_set_token(deps, env, info, address),
+        ExecuteMsg::RegisterMerkleRoot {
+            merkle_root,
+            expiration,
+            start,
+            total_amount,
+        } => execute_register_merkle_root(deps, info, merkle_root, expiration, start, total_amount),
+        ExecuteMsg::Claim {
+            stage,
+            amount,
+            proof,
+        } => execute_claim(deps, env, info, stage, amount, proof),
+    }
+}
+
+/// Fails unless the sender is the owner.
+fn ensure_owner(cfg: &Config, sender: &Addr) -> Result<(), ContractError> {
+    match &cfg.owner {
+        Some(owner) if owner == sender => Ok(()),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_owner: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+    ensure_owner(&cfg, &info.sender)?;
+
+    // if owner some validated to addr, otherwise set to none
+    cfg.owner = new_owner
+        .map(|owner| deps.api.addr_validate(&owner))
+        .transpose()?;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new().add_attribute("action", "update_config"))
+}
+
+pub fn execute_set_token(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+    ensure_owner(&cfg, &info.sender)?;
+    if cfg.cw20_token_address.is_some() {
+        return Err(ContractError::TokenAlreadySet {});
+    }
+
+    // Claims would fail at minting: check the token once here.
+    let token = deps.api.addr_validate(&address)?;
+    let minter: Option<MinterResponse> = deps
+        .querier
+        .query_wasm_smart(&token, &Cw20QueryMsg::Minter {})?;
+    if minter.map(|minter| minter.minter) != Some(env.contract.address.to_string()) {
+        return Err(ContractError::NotMinter { token: address });
+    }
+
+    cfg.cw20_token_address = Some(token);
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_token"),
+        attr("cw20_token_address", address),
+    ]))
+}
+
+pub fn execute_register_merkle_root(
+    deps: DepsMut,
+    info: MessageInfo,
+    merkle_root: String,
+    expiration: Option<Expiration>,
+    start: Option<Scheduled>,
+    total_amount: Uint128,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    ensure_owner(&cfg, &info.sender)?;
+
+    // The total amount caps what the stage can mint.
+    if total_amount.is_zero() {
+        return Err(ContractError::InvalidTotalAmount {});
+    }
+
+    // check merkle root length
+    let mut root_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(&merkle_root, &mut root_buf)?;
+
+    let stage = LATEST_STAGE.update(deps.storage, |stage| -> StdResult<_> { Ok(stage + 1) })?;
+
+    MERKLE_ROOT.save(deps.storage, stage, &merkle_root)?;
+
+    // save expiration
+    let exp = expiration.unwrap_or(Expiration::Never {});
+    STAGE_EXPIRATION.save(deps.storage, stage, &exp)?;
+
+    // save start
+    if let Some(start) = start {
+        STAGE_START.save(deps.storage, stage, &start)?;
+    }
+
+    STAGE_AMOUNT.save(deps.storage, stage, &total_amount)?;
+    STAGE_AMOUNT_CLAIMED.save(deps.storage, stage, &Uint128::zero())?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_merkle_root"),
+        attr("stage", stage.to_string()),
+        attr("merkle_root", merkle_root),
+        attr("total_amount", total_amount),
+    ]))
+}
+
+pub fn execute_claim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u8,
+    amount: Uint128,
+    proof: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let token = config
+        .cw20_token_address
+        .ok_or(ContractError::TokenNotSet {})?;
+
+    // airdrop begun
+    let start = STAGE_START.may_load(deps.storage, stage)?;
+    if let Some(start) = start {
+        if !start.is_triggered(&env.block) {
+            return Err(ContractError::StageNotBegun { stage, start });
+        }
+    }
+    // not expired
+    let expiration = STAGE_EXPIRATION.load(deps.storage, stage)?;
+    if expiration.is_expired(&env.block) {
+        return Err(ContractError::StageExpired { stage, expiration });
+    }
+
+    // verify not claimed
+    if CLAIM.has(deps.storage, (&info.sender, stage)) {
+        return Err(ContractError::Claimed {});
+    }
+
+    let merkle_root = MERKLE_ROOT.load(deps.storage, stage)?;
+    verify_proof(&merkle_root, &info.sender, amount, proof)?;
+
+    // A wrong tree cannot mint more than the amount registered for the stage.
+    let total_amount = STAGE_AMOUNT.load(deps.storage, stage)?;
+    let claimed_amount = STAGE_AMOUNT_CLAIMED.load(deps.storage, stage)?;
+    let remaining = total_amount.checked_sub(claimed_amount)?;
+    if amount > remaining {
+        return Err(ContractError::StageAmountExceeded { stage, remaining });
+    }
+
+    CLAIM.save(deps.storage, (&info.sender, stage), &true)?;
+    STAGE_AMOUNT_CLAIMED.save(deps.storage, stage, &claimed_amount.checked_add(amount)?)?;
+
+    let res = Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: info.sender.to_string(),
+                amount,
+            })?,
+        })
+        .add_attributes(vec![
+            attr("action", "claim"),
+            attr("stage", stage.to_string()),
+            attr("address", info.sender),
+            attr("amount", amount),
+        ]);
+    Ok(res)
+}
+
+/// Checks the proof of the `{address}{amount}` leaf against the hex-encoded root, the
+/// leaves of cw20-merkle-airdrop.
+fn verify_proof(
+    merkle_root: &str,
+    address: &Addr,
+    amount: Uint128,
+    proof: Vec<String>,
+) -> Result<(), ContractError> {
+    let user_input = format!("{}{}", address, amount);
+    let hash = sha2::Sha256::digest(user_input.as_bytes())
+        .as_slice()
+        .try_into()
+        .map_err(|_| ContractError::WrongLength {})?;
+
+    let hash = proof.into_iter().try_fold(hash, |hash, p| {
+        let mut proof_buf = [0; 32];
+        hex::decode_to_slice(p, &mut proof_buf)?;
+        let mut hashes = [hash, proof_buf];
+        hashes.sort_unstable();
+        sha2::Sha256::digest(&hashes.concat())
+            .as_slice()
+            .try_into()
+            .map_err(|_| ContractError::WrongLength {})
+    })?;
+
+    let mut root_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(merkle_root, &mut root_buf)?;
+    if root_buf != hash {
+        return Err(ContractError::VerificationFailed {});
+    }
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::MerkleRoot { stage } => to_binary(&query_merkle_root(deps, stage)?),
+        QueryMsg::LatestStage {} => to_binary(&query_latest_stage(deps)?),
+        QueryMsg::IsClaimed { stage, address } => {
+            to_binary(&query_is_claimed(deps, stage, address)?)
+        }
+        QueryMsg::TotalClaimed { stage } => to_binary(&query_total_claimed(deps, stage)?),
+    }
+}
+
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: cfg.owner.map(|o| o.to_string()),
+        cw20_token_address: cfg.cw20_token_address.map(|token| token.to_string()),
+    })
+}
+
+pub fn query_merkle_root(deps: Deps, stage: u8) -> StdResult<MerkleRootResponse> {
+    Ok(MerkleRootResponse {
+        stage,
+        merkle_root: MERKLE_ROOT.load(deps.storage, stage)?,
+        expiration: STAGE_EXPIRATION.load(deps.storage, stage)?,
+        start: STAGE_START.may_load(deps.storage, stage)?,
+        total_amount: STAGE_AMOUNT.load(deps.storage, stage)?,
+    })
+}
+
+pub fn query_latest_stage(deps: Deps) -> StdResult<LatestStageResponse> {
+    let latest_stage = LATEST_STAGE.load(deps.storage)?;
+    Ok(LatestStageResponse { latest_stage })
+}
+
+pub fn query_is_claimed(deps: Deps, stage: u8, address: String) -> StdResult<IsClaimedResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let is_claimed = CLAIM.has(deps.storage, (&address, stage));
+    Ok(IsClaimedResponse { is_claimed })
+}
+
+pub fn query_total_claimed(deps: Deps, stage: u8) -> StdResult<TotalClaimedResponse> {
+    let total_claimed = STAGE_AMOUNT_CLAIMED.load(deps.storage, stage)?;
+    Ok(TotalClaimedResponse { total_claimed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+        MOCK_CONTRACT_ADDR,
+    };
+    use cosmwasm_std::{
+        from_slice, ContractResult, CosmosMsg, OwnedDeps, SubMsg, SystemResult, WasmQuery,
+    };
+    use serde::Deserialize;
+
+    const TEST_DATA_1: &[u8] = include_bytes!("../testdata/airdrop_stage_1_test_data.json");
+
+    #[derive(Deserialize, Debug)]
+    struct Encoded {
+        account: String,
+        amount: Uint128,
+        root: String,
+        proofs: Vec<String>,
+    }
+
+    // Token "token0000" minted by `minter`.
+    fn mock_token(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, minter: &'static str) {
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, .. } if contract_addr == "token0000" => {
+                let minter = Some(MinterResponse {
+                    minter: minter.to_string(),
+                    cap: None,
+                });
+                SystemResult::Ok(ContractResult::Ok(to_binary(&minter).unwrap()))
+            }
+            _ => panic!("unexpected query"),
+        });
+    }
+
+    fn set_token(deps: DepsMut) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::SetToken {
+            address: "token0000".to_string(),
+        };
+        execute(deps, mock_env(), mock_info("owner0000", &[]), msg)
+    }
+
+    #[test]
+    fn set_token_once() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg { owner: None };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+        // Only the owner, and only a token minted by the contract
+        let msg = ExecuteMsg::SetToken {
+            address: "token0000".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("other0000", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        mock_token(&mut deps, "other0000");
+        let err = set_token(deps.as_mut()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NotMinter {
+                token: "token0000".to_string()
+            }
+        );
+
+        mock_token(&mut deps, MOCK_CONTRACT_ADDR);
+        set_token(deps.as_mut()).unwrap();
+        let config = query_config(deps.as_ref()).unwrap();
+        assert_eq!(config.cw20_token_address, Some("token0000".to_string()));
+        let err = set_token(deps.as_mut()).unwrap_err();
+        assert_eq!(err, ContractError::TokenAlreadySet {});
+    }
+
+    #[test]
+    fn claim_mints() {
+        let mut deps = mock_dependencies();
+        let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+        let register = |total_amount: u128| ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: test_data.root.clone(),
+            expiration: None,
+            start: None,
+            total_amount: Uint128::new(total_amount),
+        };
+        let info = mock_info("owner0000", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), register(0)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidTotalAmount {});
+        execute(deps.as_mut(), mock_env(), info.clone(), register(1000)).unwrap();
+        execute(deps.as_mut(), mock_env(), info, register(99)).unwrap();
+
+        let claim = |stage| ExecuteMsg::Claim {
+            stage,
+            amount: test_data.amount,
+            proof: test_data.proofs.clone(),
+        };
+        let info = mock_info(test_data.account.as_str(), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), claim(1)).unwrap_err();
+        assert_eq!(err, ContractError::TokenNotSet {});
+        mock_token(&mut deps, MOCK_CONTRACT_ADDR);
+        set_token(deps.as_mut()).unwrap();
+
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), claim(1)).unwrap();
+        let expected = SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "token0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: test_data.account.clone(),
+                amount: test_data.amount,
+            })
+            .unwrap(),
+        }));
+        assert_eq!(res.messages, vec![expected]);
+        let total_claimed = query_total_claimed(deps.as_ref(), 1).unwrap().total_claimed;
+        assert_eq!(total_claimed, test_data.amount);
+        let is_claimed = query_is_claimed(deps.as_ref(), 1, test_data.account.clone()).unwrap();
+        assert!(is_claimed.is_claimed);
+
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), claim(1)).unwrap_err();
+        assert_eq!(err, ContractError::Claimed {});
+
+        // The second stage cannot mint the whole allocation
+        let err = execute(deps.as_mut(), mock_env(), info, claim(2)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::StageAmountExceeded {
+                stage: 2,
+                remaining: Uint128::new(99)
+            }
+        );
+
+        // Wrong amount
+        let msg = ExecuteMsg::Claim {
+            stage: 2,
+            amount: Uint128::new(1),
+            proof: test_data.proofs,
+        };
+        let info = mock_info(test_data.account.as_str(), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::VerificationFailed {});
+    }
+}
@@ -0,0 +1,49 @@
+use cosmwasm_std::{OverflowError, StdError, Uint128};
+use cw_utils::{Expiration, Scheduled};
+use hex::FromHexError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Hex(#[from] FromHexError),
+
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Already claimed")]
+    Claimed {},
+
+    #[error("Wrong length")]
+    WrongLength {},
+
+    #[error("Verification failed")]
+    VerificationFailed {},
+
+    #[error("The token is already set")]
+    TokenAlreadySet {},
+
+    #[error("The token is not set yet")]
+    TokenNotSet {},
+
+    #[error("The minter of token {token} is not this contract")]
+    NotMinter { token: String },
+
+    #[error("The total amount of a stage must be greater than zero")]
+    InvalidTotalAmount {},
+
+    #[error("Airdrop stage {stage} can mint only {remaining} more tokens")]
+    StageAmountExceeded { stage: u8, remaining: Uint128 },
+
+    #[error("Airdrop stage {stage} expired at {expiration}")]
+    StageExpired { stage: u8, expiration: Expiration },
+
+    #[error("Airdrop stage {stage} begins at {start}")]
+    StageNotBegun { stage: u8, start: Scheduled },
+}
@@ -0,0 +1,117 @@
+#![cfg(test)]
+
+use cosmwasm_std::{from_slice, Addr, Empty, Uint128};
+use cw20::{Cw20Contract, MinterResponse};
+use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+use serde::Deserialize;
+
+use crate::msg::{ExecuteMsg, InstantiateMsg};
+
+const TEST_DATA_1_MULTI: &[u8] = include_bytes!("../testdata/airdrop_stage_1_test_multi_data.json");
+
+#[derive(Deserialize, Debug)]
+struct Proof {
+    account: String,
+    amount: Uint128,
+    proofs: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct MultipleData {
+    total_amount: Uint128,
+    root: String,
+    accounts: Vec<Proof>,
+}
+
+pub fn contract_merkle_minter() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+pub fn contract_cw20() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    Box::new(contract)
+}
+
+#[test]
+// mint the allocations of a tree from a token without any supply
+fn claims_mint_the_supply() {
+    let owner = Addr::unchecked("owner");
+    let test_data: MultipleData = from_slice(TEST_DATA_1_MULTI).unwrap();
+    let mut router = App::default();
+
+    let minter_id = router.store_code(contract_merkle_minter());
+    let minter_addr = router
+        .instantiate_contract(
+            minter_id,
+            owner.clone(),
+            &InstantiateMsg { owner: None },
+            &[],
+            "MINTER",
+            None,
+        )
+        .unwrap();
+
+    // the token is created with the minter contract as its only minter
+    let cw20_id = router.store_code(contract_cw20());
+    let msg = cw20_base::msg::InstantiateMsg {
+        name: "Drop Token".to_string(),
+        symbol: "DROP".to_string(),
+        decimals: 6,
+        initial_balances: vec![],
+        mint: Some(MinterResponse {
+            minter: minter_addr.to_string(),
+            cap: Some(test_data.total_amount),
+        }),
+        marketing: None,
+    };
+    let token_addr = router
+        .instantiate_contract(cw20_id, owner.clone(), &msg, &[], "DROP", None)
+        .unwrap();
+    let token = Cw20Contract(token_addr.clone());
+
+    let msg = ExecuteMsg::SetToken {
+        address: token_addr.to_string(),
+    };
+    router
+        .execute_contract(owner.clone(), minter_addr.clone(), &msg, &[])
+        .unwrap();
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: test_data.root,
+        expiration: None,
+        start: None,
+        total_amount: test_data.total_amount,
+    };
+    router
+        .execute_contract(owner, minter_addr.clone(), &msg, &[])
+        .unwrap();
+
+    // every claim mints the allocation of the claimer
+    let mut minted = Uint128::zero();
+    for account in test_data.accounts {
+        let msg = ExecuteMsg::Claim {
+            stage: 1,
+            amount: account.amount,
+            proof: account.proofs,
+        };
+        let claimer = Addr::unchecked(&account.account);
+        router
+            .execute_contract(claimer.clone(), minter_addr.clone(), &msg, &[])
+            .unwrap();
+        assert_eq!(
+            token.balance::<_, _, Empty>(&router, claimer).unwrap(),
+            account.amount
+        );
+        minted += account.amount;
+    }
+    let info = token.meta::<_, Empty>(&router).unwrap();
+    assert_eq!(info.total_supply, minted);
+}
@@ -0,0 +1,79 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::Uint128;
+use cw_utils::{Expiration, Scheduled};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    /// Owner if none set to info.sender.
+    pub owner: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    UpdateConfig {
+        /// NewOwner if non sent, contract gets locked. Recipients can claim their
+        /// airdrops but owner cannot register new stages.
+        new_owner: Option<String>,
+    },
+    /// Sets once the cw20 token minted, whose minter must be this contract.
+    SetToken { address: String },
+    RegisterMerkleRoot {
+        /// MerkleRoot is hex-encoded merkle root.
+        merkle_root: String,
+        expiration: Option<Expiration>,
+        start: Option<Scheduled>,
+        /// Maximum amount minted by the claims of the stage.
+        total_amount: Uint128,
+    },
+    /// Mints the amount allocated to the sender.
+    Claim {
+        stage: u8,
+        amount: Uint128,
+        /// Proof is hex-encoded merkle proof.
+        proof: Vec<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    MerkleRoot { stage: u8 },
+    LatestStage {},
+    IsClaimed { stage: u8, address: String },
+    TotalClaimed { stage: u8 },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ConfigResponse {
+    pub owner: Option<String>,
+    pub cw20_token_address: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MerkleRootResponse {
+    pub stage: u8,
+    /// MerkleRoot is hex-encoded merkle root.
+    pub merkle_root: String,
+    pub expiration: Expiration,
+    pub start: Option<Scheduled>,
+    pub total_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LatestStageResponse {
+    pub latest_stage: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsClaimedResponse {
+    pub is_claimed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TotalClaimedResponse {
+    pub total_claimed: Uint128,
+}
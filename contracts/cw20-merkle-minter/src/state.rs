@@ -0,0 +1,40 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, Uint128};
+use cw_storage_plus::{Item, Map};
+use cw_utils::{Expiration, Scheduled};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// Owner If None set, contract is frozen.
+    pub owner: Option<Addr>,
+    /// Token minted by the claims, set once after the instantiation since the token
+    /// must be created with this contract as minter.
+    pub cw20_token_address: Option<Addr>,
+}
+
+pub const CONFIG_KEY: &str = "config";
+pub const CONFIG: Item<Config> = Item::new(CONFIG_KEY);
+
+pub const LATEST_STAGE_KEY: &str = "stage";
+pub const LATEST_STAGE: Item<u8> = Item::new(LATEST_STAGE_KEY);
+
+pub const STAGE_EXPIRATION_KEY: &str = "stage_exp";
+pub const STAGE_EXPIRATION: Map<u8, Expiration> = Map::new(STAGE_EXPIRATION_KEY);
+
+pub const STAGE_START_KEY: &str = "stage_start";
+pub const STAGE_START: Map<u8, Scheduled> = Map::new(STAGE_START_KEY);
+
+/// Maximum amount minted by the claims of each stage.
+pub const STAGE_AMOUNT_KEY: &str = "stage_amount";
+pub const STAGE_AMOUNT: Map<u8, Uint128> = Map::new(STAGE_AMOUNT_KEY);
+
+pub const STAGE_AMOUNT_CLAIMED_KEY: &str = "stage_claimed_amount";
+pub const STAGE_AMOUNT_CLAIMED: Map<u8, Uint128> = Map::new(STAGE_AMOUNT_CLAIMED_KEY);
+
+pub const MERKLE_ROOT_PREFIX: &str = "merkle_root";
+pub const MERKLE_ROOT: Map<u8, String> = Map::new(MERKLE_ROOT_PREFIX);
+
+pub const CLAIM_PREFIX: &str = "claim";
+pub const CLAIM: Map<(&Addr, u8), bool> = Map::new(CLAIM_PREFIX);
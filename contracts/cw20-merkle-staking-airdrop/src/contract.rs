@@ -0,0 +1,832 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    attr, coin, coins, to_binary, Addr, BankMsg, Binary, Deps, DepsMut, DistributionMsg, Env,
+    MessageInfo, Order, Response, StakingMsg, StdResult, Uint128,
+};
+use cw2::{get_contract_version, set_contract_version};
+use cw_utils::{Expiration, Scheduled};
+use sha2::Digest;
+use std::convert::TryInto;
+
+use crate::error::ContractError;
+use crate::msg::{
+    ConfigResponse, DelegationResponse, DelegationsResponse, ExecuteMsg, InstantiateMsg,
+    IsClaimedResponse, LatestStageResponse, MerkleRootResponse, MigrateMsg, QueryMsg,
+    TotalClaimedResponse,
+};
+use crate::state::{
+    Config, CLAIM, CLAIMS, CONFIG, DELEGATIONS, LATEST_STAGE, MERKLE_ROOT, STAGE_AMOUNT,
+    STAGE_AMOUNT_CLAIMED, STAGE_EXPIRATION, STAGE_START,
+};
+
+// Version info, for migration info
+const CONTRACT_NAME: &str = "crates.io:cw20-merkle-staking-airdrop";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let owner = msg
+        .owner
+        .map_or(Ok(info.sender), |o| deps.api.addr_validate(&o))?;
+    let rewards_address = msg
+        .rewards_address
+        .map_or(Ok(owner.clone()), |r| deps.api.addr_validate(&r))?;
+
+    let config = Config {
+        owner: Some(owner),
+        bond_denom: deps.querier.query_bonded_denom()?,
+        unbonding_period: msg.unbonding_period,
+    };
+    CONFIG.save(deps.storage, &config)?;
+
+    let stage = 0;
+    LATEST_STAGE.save(deps.storage, &stage)?;
+
+    // Rewards of the delegations held by the contract cannot be attributed to the
+    // claimers, they are sent to the rewards address.
+    Ok(Response::new().add_message(DistributionMsg::SetWithdrawAddress {
+        address: rewards_address.to_string(),
+    }))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::UpdateConfig { new_owner } => execute_update_config(deps, env, info, new_owner),
+        ExecuteMsg::RegisterMerkleRoot {
+            merkle_root,
+            expiration,
+            start,
+            total_amount,
+        } => execute_register_merkle_root(
+            deps,
+            env,
+            info,
+            merkle_root,
+            expiration,
+            start,
+            total_amount,
+        ),
+        ExecuteMsg::Claim {
+            stage,
+            amount,
+            proof,
+            delegate_to,
+        } => execute_claim(deps, env, info, stage, amount, proof, delegate_to),
+        ExecuteMsg::Undelegate { validator, amount } => {
+            execute_undelegate(deps, env, info, validator, amount)
+        }
+        ExecuteMsg::ClaimUnbonded {} => execute_claim_unbonded(deps, env, info),
+        ExecuteMsg::Burn { stage } => execute_burn(deps, env, info, stage),
+        ExecuteMsg::Withdraw { stage, address } => {
+            execute_withdraw(deps, env, info, stage, address)
+        }
+    }
+}
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    new_owner: Option<String>,
+) -> Result<Response, ContractError> {
+    // authorize owner
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // if owner some validated to addr, otherwise set to none
+    let mut tmp_owner = None;
+    if let Some(addr) = new_owner {
+        tmp_owner = Some(deps.api.addr_validate(&addr)?)
+    }
+
+    CONFIG.update(deps.storage, |mut exists| -> StdResult<_> {
+        exists.owner = tmp_owner;
+        Ok(exists)
+    })?;
+
+    Ok(Response::new().add_attribute("action", "update_config"))
+}
+
+pub fn execute_register_merkle_root(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    merkle_root: String,
+    expiration: Option<Expiration>,
+    start: Option<Scheduled>,
+    total_amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    // if owner set validate, otherwise unauthorized
+    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // check merkle root length
+    let mut root_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(&merkle_root, &mut root_buf)?;
+
+    let stage = LATEST_STAGE.update(deps.storage, |stage| -> StdResult<_> { Ok(stage + 1) })?;
+
+    MERKLE_ROOT.save(deps.storage, stage, &merkle_root)?;
+
+    // save expiration
+    let exp = expiration.unwrap_or(Expiration::Never {});
+    STAGE_EXPIRATION.save(deps.storage, stage, &exp)?;
+
+    // save start
+    if let Some(start) = start {
+        STAGE_START.save(deps.storage, stage, &start)?;
+    }
+
+    // save total airdropped amount
+    let amount = total_amount.unwrap_or_else(Uint128::zero);
+    STAGE_AMOUNT.save(deps.storage, stage, &amount)?;
+    STAGE_AMOUNT_CLAIMED.save(deps.storage, stage, &Uint128::zero())?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_merkle_root"),
+        attr("stage", stage.to_string()),
+        attr("merkle_root", merkle_root),
+        attr("total_amount", amount),
+    ]))
+}
+
+pub fn execute_claim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u8,
+    amount: Uint128,
+    proof: Vec<String>,
+    delegate_to: Option<String>,
+) -> Result<Response, ContractError> {
+    // airdrop begun
+    let start = STAGE_START.may_load(deps.storage, stage)?;
+    if let Some(start) = start {
+        if !start.is_triggered(&env.block) {
+            return Err(ContractError::StageNotBegun { stage, start });
+        }
+    }
+    // not expired
+    let expiration = STAGE_EXPIRATION.load(deps.storage, stage)?;
+    if expiration.is_expired(&env.block) {
+        return Err(ContractError::StageExpired { stage, expiration });
+    }
+
+    // verify not claimed
+    let claimed = CLAIM.may_load(deps.storage, (&info.sender, stage))?;
+    if claimed.is_some() {
+        return Err(ContractError::Claimed {});
+    }
+
+    if let Some(validator) = &delegate_to {
+        if deps.querier.query_validator(validator)?.is_none() {
+            return Err(ContractError::NotInValidatorSet {
+                validator: validator.clone(),
+            });
+        }
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let merkle_root = MERKLE_ROOT.load(deps.storage, stage)?;
+
+    let user_input = format!("{}{}", info.sender, amount);
+    let hash = sha2::Sha256::digest(user_input.as_bytes())
+        .as_slice()
+        .try_into()
+        .map_err(|_| ContractError::WrongLength {})?;
+
+    let hash = proof.into_iter().try_fold(hash, |hash, p| {
+        let mut proof_buf = [0; 32];
+        hex::decode_to_slice(p, &mut proof_buf)?;
+        let mut hashes = [hash, proof_buf];
+        hashes.sort_unstable();
+        sha2::Sha256::digest(&hashes.concat())
+            .as_slice()
+            .try_into()
+            .map_err(|_| ContractError::WrongLength {})
+    })?;
+
+    let mut root_buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(merkle_root, &mut root_buf)?;
+    if root_buf != hash {
+        return Err(ContractError::VerificationFailed {});
+    }
+
+    // Update claim index to the current stage
+    CLAIM.save(deps.storage, (&info.sender, stage), &true)?;
+
+    // Update total claimed to reflect
+    STAGE_AMOUNT_CLAIMED.update(deps.storage, stage, |claimed| -> Result<_, ContractError> {
+        Ok(claimed.unwrap_or_default().checked_add(amount)?)
+    })?;
+
+    let res = match delegate_to {
+        Some(validator) => {
+            // The contract is the delegator, the claimer can only undelegate through it.
+            DELEGATIONS.update(
+                deps.storage,
+                (&info.sender, &validator),
+                |delegated| -> Result<_, ContractError> {
+                    Ok(delegated.unwrap_or_default().checked_add(amount)?)
+                },
+            )?;
+
+            Response::new()
+                .add_message(StakingMsg::Delegate {
+                    validator: validator.clone(),
+                    amount: coin(amount.u128(), &config.bond_denom),
+                })
+                .add_attribute("delegated_to", validator)
+        }
+        None => Response::new().add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(amount.u128(), &config.bond_denom),
+        }),
+    };
+
+    Ok(res.add_attributes(vec![
+        attr("action", "claim"),
+        attr("stage", stage.to_string()),
+        attr("address", info.sender),
+        attr("amount", amount),
+    ]))
+}
+
+pub fn execute_undelegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    validator: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let delegated = DELEGATIONS
+        .may_load(deps.storage, (&info.sender, &validator))?
+        .unwrap_or_default();
+    if amount.is_zero() || amount > delegated {
+        return Err(ContractError::InsufficientDelegation {
+            validator,
+            delegated,
+        });
+    }
+
+    let remaining = delegated.checked_sub(amount)?;
+    if remaining.is_zero() {
+        DELEGATIONS.remove(deps.storage, (&info.sender, &validator));
+    } else {
+        DELEGATIONS.save(deps.storage, (&info.sender, &validator), &remaining)?;
+    }
+
+    // Tokens undelegated by the contract are released after the unbonding period.
+    CLAIMS.create_claim(
+        deps.storage,
+        &info.sender,
+        amount,
+        cfg.unbonding_period.after(&env.block),
+    )?;
+
+    let res = Response::new()
+        .add_message(StakingMsg::Undelegate {
+            validator: validator.clone(),
+            amount: coin(amount.u128(), &cfg.bond_denom),
+        })
+        .add_attributes(vec![
+            attr("action", "undelegate"),
+            attr("address", info.sender),
+            attr("validator", validator),
+            attr("amount", amount),
+        ]);
+    Ok(res)
+}
+
+pub fn execute_claim_unbonded(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, &cfg.bond_denom)?;
+
+    // Only send what the contract holds, the rest stays claimable.
+    let to_send =
+        CLAIMS.claim_tokens(deps.storage, &info.sender, &env.block, Some(balance.amount))?;
+    if to_send.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    let res = Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(to_send.u128(), &cfg.bond_denom),
+        })
+        .add_attributes(vec![
+            attr("action", "claim_unbonded"),
+            attr("address", info.sender),
+            attr("amount", to_send),
+        ]);
+    Ok(res)
+}
+
+pub fn execute_burn(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u8,
+) -> Result<Response, ContractError> {
+    // authorize owner
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let balance_to_burn = stage_leftover(deps.as_ref(), &env, stage)?;
+
+    // Burn the tokens and response
+    let res = Response::new()
+        .add_message(BankMsg::Burn {
+            amount: coins(balance_to_burn.u128(), &cfg.bond_denom),
+        })
+        .add_attributes(vec![
+            attr("action", "burn"),
+            attr("stage", stage.to_string()),
+            attr("address", info.sender),
+            attr("amount", balance_to_burn),
+        ]);
+    Ok(res)
+}
+
+pub fn execute_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u8,
+    address: String,
+) -> Result<Response, ContractError> {
+    // authorize owner
+    let cfg = CONFIG.load(deps.storage)?;
+    let owner = cfg.owner.ok_or(ContractError::Unauthorized {})?;
+    if info.sender != owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let balance_to_withdraw = stage_leftover(deps.as_ref(), &env, stage)?;
+
+    // Validate address
+    let recipient = deps.api.addr_validate(&address)?;
+
+    // Withdraw the tokens and response
+    let res = Response::new()
+        .add_message(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(balance_to_withdraw.u128(), &cfg.bond_denom),
+        })
+        .add_attributes(vec![
+            attr("action", "withdraw"),
+            attr("stage", stage.to_string()),
+            attr("address", info.sender),
+            attr("amount", balance_to_withdraw),
+            attr("recipient", address),
+        ]);
+    Ok(res)
+}
+
+/// Unclaimed amount of an expired stage.
+fn stage_leftover(deps: Deps, env: &Env, stage: u8) -> Result<Uint128, ContractError> {
+    // make sure is expired
+    let expiration = STAGE_EXPIRATION.load(deps.storage, stage)?;
+    if !expiration.is_expired(&env.block) {
+        return Err(ContractError::StageNotExpired { stage, expiration });
+    }
+
+    // Get total amount per stage and total claimed
+    let total_amount = STAGE_AMOUNT.load(deps.storage, stage)?;
+    let claimed_amount = STAGE_AMOUNT_CLAIMED.load(deps.storage, stage)?;
+
+    // impossible but who knows
+    if claimed_amount > total_amount {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(total_amount - claimed_amount)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::MerkleRoot { stage } => to_binary(&query_merkle_root(deps, stage)?),
+        QueryMsg::LatestStage {} => to_binary(&query_latest_stage(deps)?),
+        QueryMsg::IsClaimed { stage, address } => {
+            to_binary(&query_is_claimed(deps, stage, address)?)
+        }
+        QueryMsg::TotalClaimed { stage } => to_binary(&query_total_claimed(deps, stage)?),
+        QueryMsg::Delegations { address } => to_binary(&query_delegations(deps, address)?),
+        QueryMsg::Claims { address } => {
+            to_binary(&CLAIMS.query_claims(deps, &deps.api.addr_validate(&address)?)?)
+        }
+    }
+}
+
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: cfg.owner.map(|o| o.to_string()),
+        bond_denom: cfg.bond_denom,
+        unbonding_period: cfg.unbonding_period,
+    })
+}
+
+pub fn query_merkle_root(deps: Deps, stage: u8) -> StdResult<MerkleRootResponse> {
+    let merkle_root = MERKLE_ROOT.load(deps.storage, stage)?;
+    let expiration = STAGE_EXPIRATION.load(deps.storage, stage)?;
+    let start = STAGE_START.may_load(deps.storage, stage)?;
+    let total_amount = STAGE_AMOUNT.load(deps.storage, stage)?;
+
+    let resp = MerkleRootResponse {
+        stage,
+        merkle_root,
+        expiration,
+        start,
+        total_amount,
+    };
+
+    Ok(resp)
+}
+
+pub fn query_latest_stage(deps: Deps) -> StdResult<LatestStageResponse> {
+    let latest_stage = LATEST_STAGE.load(deps.storage)?;
+    let resp = LatestStageResponse { latest_stage };
+
+    Ok(resp)
+}
+
+pub fn query_is_claimed(deps: Deps, stage: u8, address: String) -> StdResult<IsClaimedResponse> {
+    let key: (&Addr, u8) = (&deps.api.addr_validate(&address)?, stage);
+    let is_claimed = CLAIM.may_load(deps.storage, key)?.unwrap_or(false);
+    let resp = IsClaimedResponse { is_claimed };
+
+    Ok(resp)
+}
+
+pub fn query_total_claimed(deps: Deps, stage: u8) -> StdResult<TotalClaimedResponse> {
+    let total_claimed = STAGE_AMOUNT_CLAIMED.load(deps.storage, stage)?;
+    let resp = TotalClaimedResponse { total_claimed };
+
+    Ok(resp)
+}
+
+pub fn query_delegations(deps: Deps, address: String) -> StdResult<DelegationsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let delegations = DELEGATIONS
+        .prefix(&address)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (validator, amount) = item?;
+            Ok(DelegationResponse { validator, amount })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(DelegationsResponse { delegations })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let version = get_contract_version(deps.storage)?;
+    if version.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate {
+            previous_contract: version.contract,
+        });
+    }
+    Ok(Response::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+        MOCK_CONTRACT_ADDR,
+    };
+    use cosmwasm_std::{from_binary, from_slice, Decimal, OwnedDeps, SubMsg, Validator};
+    use cw_controllers::{Claim, ClaimsResponse};
+    use cw_utils::Duration;
+    use serde::Deserialize;
+
+    const TEST_DATA_1: &[u8] = include_bytes!("../testdata/airdrop_stage_1_test_data.json");
+
+    const DEFAULT_VALIDATOR: &str = "default-validator";
+
+    #[derive(Deserialize, Debug)]
+    struct Encoded {
+        account: String,
+        amount: Uint128,
+        root: String,
+        proofs: Vec<String>,
+    }
+
+    fn sample_validator(addr: &str) -> Validator {
+        Validator {
+            address: addr.into(),
+            commission: Decimal::percent(3),
+            max_commission: Decimal::percent(10),
+            max_change_rate: Decimal::percent(1),
+        }
+    }
+
+    /// Instantiates the contract and registers the root of the test data as stage 1.
+    fn setup(test_data: &Encoded) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        deps.querier
+            .update_staking("ustake", &[sample_validator(DEFAULT_VALIDATOR)], &[]);
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            unbonding_period: Duration::Height(10),
+            rewards_address: None,
+        };
+        let info = mock_info("addr0000", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: test_data.root.clone(),
+            expiration: None,
+            start: None,
+            total_amount: Some(Uint128::new(1000)),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        deps
+    }
+
+    #[test]
+    fn proper_instantiation() {
+        let mut deps = mock_dependencies();
+        deps.querier
+            .update_staking("ustake", &[sample_validator(DEFAULT_VALIDATOR)], &[]);
+
+        let msg = InstantiateMsg {
+            owner: None,
+            unbonding_period: Duration::Height(10),
+            rewards_address: Some("rewards0000".to_string()),
+        };
+
+        let env = mock_env();
+        let info = mock_info("owner0000", &[]);
+        let res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(DistributionMsg::SetWithdrawAddress {
+                address: "rewards0000".to_string()
+            })]
+        );
+
+        let res = query(deps.as_ref(), env, QueryMsg::Config {}).unwrap();
+        let config: ConfigResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            config,
+            ConfigResponse {
+                owner: Some("owner0000".to_string()),
+                bond_denom: "ustake".to_string(),
+                unbonding_period: Duration::Height(10),
+            }
+        );
+    }
+
+    #[test]
+    fn claim() {
+        let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+        let mut deps = setup(&test_data);
+
+        let msg = ExecuteMsg::Claim {
+            amount: test_data.amount,
+            stage: 1u8,
+            proof: test_data.proofs.clone(),
+            delegate_to: None,
+        };
+        let info = mock_info(test_data.account.as_str(), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: test_data.account.clone(),
+                amount: coins(100, "ustake"),
+            })]
+        );
+
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(res, ContractError::Claimed {});
+    }
+
+    #[test]
+    fn claim_and_delegate() {
+        let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+        let mut deps = setup(&test_data);
+
+        let info = mock_info(test_data.account.as_str(), &[]);
+        let msg = ExecuteMsg::Claim {
+            amount: test_data.amount,
+            stage: 1u8,
+            proof: test_data.proofs.clone(),
+            delegate_to: Some("unknown-validator".to_string()),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::NotInValidatorSet {
+                validator: "unknown-validator".to_string()
+            }
+        );
+
+        let msg = ExecuteMsg::Claim {
+            amount: test_data.amount,
+            stage: 1u8,
+            proof: test_data.proofs.clone(),
+            delegate_to: Some(DEFAULT_VALIDATOR.to_string()),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(StakingMsg::Delegate {
+                validator: DEFAULT_VALIDATOR.to_string(),
+                amount: coin(100, "ustake"),
+            })]
+        );
+
+        let query_msg = QueryMsg::Delegations {
+            address: test_data.account,
+        };
+        let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+        let delegations: DelegationsResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            delegations.delegations,
+            vec![DelegationResponse {
+                validator: DEFAULT_VALIDATOR.to_string(),
+                amount: Uint128::new(100),
+            }]
+        );
+    }
+
+    #[test]
+    fn undelegate_and_claim_unbonded() {
+        let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+        let mut deps = setup(&test_data);
+
+        let info = mock_info(test_data.account.as_str(), &[]);
+        let msg = ExecuteMsg::Claim {
+            amount: test_data.amount,
+            stage: 1u8,
+            proof: test_data.proofs.clone(),
+            delegate_to: Some(DEFAULT_VALIDATOR.to_string()),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // Only the delegated amount can be undelegated.
+        let msg = ExecuteMsg::Undelegate {
+            validator: DEFAULT_VALIDATOR.to_string(),
+            amount: Uint128::new(101),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::InsufficientDelegation {
+                validator: DEFAULT_VALIDATOR.to_string(),
+                delegated: Uint128::new(100),
+            }
+        );
+
+        let env = mock_env();
+        let msg = ExecuteMsg::Undelegate {
+            validator: DEFAULT_VALIDATOR.to_string(),
+            amount: Uint128::new(60),
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(StakingMsg::Undelegate {
+                validator: DEFAULT_VALIDATOR.to_string(),
+                amount: coin(60, "ustake"),
+            })]
+        );
+
+        let query_msg = QueryMsg::Claims {
+            address: test_data.account.clone(),
+        };
+        let res = query(deps.as_ref(), env.clone(), query_msg).unwrap();
+        let claims: ClaimsResponse = from_binary(&res).unwrap();
+        let release_at = Duration::Height(10).after(&env.block);
+        assert_eq!(
+            claims.claims,
+            vec![Claim {
+                amount: Uint128::new(60),
+                release_at
+            }]
+        );
+
+        // Nothing is released during the unbonding period.
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(60, "ustake"));
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::ClaimUnbonded {},
+        )
+        .unwrap_err();
+        assert_eq!(res, ContractError::NothingToClaim {});
+
+        let mut env = env;
+        env.block.height += 10;
+        let res = execute(deps.as_mut(), env, info, ExecuteMsg::ClaimUnbonded {}).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: test_data.account,
+                amount: coins(60, "ustake"),
+            })]
+        );
+    }
+
+    #[test]
+    fn withdraw() {
+        let test_data: Encoded = from_slice(TEST_DATA_1).unwrap();
+        let mut deps = mock_dependencies();
+        deps.querier
+            .update_staking("ustake", &[sample_validator(DEFAULT_VALIDATOR)], &[]);
+
+        let msg = InstantiateMsg {
+            owner: Some("owner0000".to_string()),
+            unbonding_period: Duration::Height(10),
+            rewards_address: None,
+        };
+        let info = mock_info("owner0000", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let env = mock_env();
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: test_data.root,
+            expiration: Some(Expiration::AtHeight(env.block.height + 10)),
+            start: None,
+            total_amount: Some(Uint128::new(1000)),
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Claim {
+            amount: test_data.amount,
+            stage: 1u8,
+            proof: test_data.proofs,
+            delegate_to: Some(DEFAULT_VALIDATOR.to_string()),
+        };
+        let claimer = mock_info(test_data.account.as_str(), &[]);
+        execute(deps.as_mut(), env.clone(), claimer, msg).unwrap();
+
+        let msg = ExecuteMsg::Withdraw {
+            stage: 1,
+            address: "recipient0000".to_string(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+        assert_eq!(
+            res,
+            ContractError::StageNotExpired {
+                stage: 1,
+                expiration: Expiration::AtHeight(env.block.height + 10)
+            }
+        );
+
+        // Delegated tokens are not part of the leftover.
+        let mut env = env;
+        env.block.height += 10;
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "recipient0000".to_string(),
+                amount: coins(900, "ustake"),
+            })]
+        );
+    }
+}
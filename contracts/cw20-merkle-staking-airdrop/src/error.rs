@@ -0,0 +1,52 @@
+use cosmwasm_std::{OverflowError, StdError, Uint128};
+use cw_utils::{Expiration, Scheduled};
+use hex::FromHexError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Hex(#[from] FromHexError),
+
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Invalid input")]
+    InvalidInput {},
+
+    #[error("Already claimed")]
+    Claimed {},
+
+    #[error("Wrong length")]
+    WrongLength {},
+
+    #[error("Verification failed")]
+    VerificationFailed {},
+
+    #[error("Cannot migrate from different contract type: {previous_contract}")]
+    CannotMigrate { previous_contract: String },
+
+    #[error("Airdrop stage {stage} expired at {expiration}")]
+    StageExpired { stage: u8, expiration: Expiration },
+
+    #[error("Airdrop stage {stage} not expired yet")]
+    StageNotExpired { stage: u8, expiration: Expiration },
+
+    #[error("Airdrop stage {stage} begins at {start}")]
+    StageNotBegun { stage: u8, start: Scheduled },
+
+    #[error("Validator '{validator}' not in current validator set")]
+    NotInValidatorSet { validator: String },
+
+    #[error("Cannot undelegate more than the {delegated} delegated to {validator}")]
+    InsufficientDelegation { validator: String, delegated: Uint128 },
+
+    #[error("No claims that can be released currently")]
+    NothingToClaim {},
+}
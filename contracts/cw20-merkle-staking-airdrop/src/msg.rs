@@ -0,0 +1,115 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::Uint128;
+use cw_utils::{Duration, Expiration, Scheduled};
+
+pub use cw_controllers::ClaimsResponse;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    /// Owner if none set to info.sender.
+    pub owner: Option<String>,
+    /// Unbonding period of the chain. Undelegated tokens are released after it.
+    pub unbonding_period: Duration,
+    /// Address receiving the staking rewards of the delegations held by the contract.
+    /// Default to the owner.
+    pub rewards_address: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    UpdateConfig {
+        /// NewOwner if non sent, contract gets locked. Recipients can receive airdrops
+        /// but owner cannot register new stages.
+        new_owner: Option<String>,
+    },
+    RegisterMerkleRoot {
+        /// MerkleRoot is hex-encoded merkle root.
+        merkle_root: String,
+        expiration: Option<Expiration>,
+        start: Option<Scheduled>,
+        total_amount: Option<Uint128>,
+    },
+    /// Claim does not check if contract has enough funds, owner must ensure it.
+    Claim {
+        stage: u8,
+        amount: Uint128,
+        /// Proof is hex-encoded merkle proof.
+        proof: Vec<String>,
+        /// If set, the claimed tokens are delegated to this validator on behalf of the
+        /// claimer instead of being sent.
+        delegate_to: Option<String>,
+    },
+    /// Undelegate tokens delegated on behalf of the sender. They can be claimed with
+    /// `ClaimUnbonded` after the unbonding period.
+    Undelegate { validator: String, amount: Uint128 },
+    /// Send to the sender the undelegated tokens whose unbonding period is over.
+    ClaimUnbonded {},
+    /// Burn the remaining tokens after expire time (only owner)
+    Burn { stage: u8 },
+    /// Withdraw the remaining tokens after expire time (only owner)
+    Withdraw { stage: u8, address: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    MerkleRoot { stage: u8 },
+    LatestStage {},
+    IsClaimed { stage: u8, address: String },
+    TotalClaimed { stage: u8 },
+    /// Tokens delegated on behalf of an address. Returns `DelegationsResponse`.
+    Delegations { address: String },
+    /// Undelegated tokens waiting to be released. Returns `ClaimsResponse`.
+    Claims { address: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct ConfigResponse {
+    pub owner: Option<String>,
+    pub bond_denom: String,
+    pub unbonding_period: Duration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MerkleRootResponse {
+    pub stage: u8,
+    /// MerkleRoot is hex-encoded merkle root.
+    pub merkle_root: String,
+    pub expiration: Expiration,
+    pub start: Option<Scheduled>,
+    pub total_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LatestStageResponse {
+    pub latest_stage: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsClaimedResponse {
+    pub is_claimed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TotalClaimedResponse {
+    pub total_claimed: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DelegationResponse {
+    pub validator: String,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DelegationsResponse {
+    pub delegations: Vec<DelegationResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
@@ -0,0 +1,48 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, Uint128};
+use cw_controllers::Claims;
+use cw_storage_plus::{Item, Map};
+use cw_utils::{Duration, Expiration, Scheduled};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// Owner If None set, contract is frozen.
+    pub owner: Option<Addr>,
+    /// Staking denom of the chain, used to pay the airdrop.
+    pub bond_denom: String,
+    /// Time after which undelegated tokens can be released, matching the chain setting.
+    pub unbonding_period: Duration,
+}
+
+pub const CONFIG_KEY: &str = "config";
+pub const CONFIG: Item<Config> = Item::new(CONFIG_KEY);
+
+pub const LATEST_STAGE_KEY: &str = "stage";
+pub const LATEST_STAGE: Item<u8> = Item::new(LATEST_STAGE_KEY);
+
+pub const STAGE_EXPIRATION_KEY: &str = "stage_exp";
+pub const STAGE_EXPIRATION: Map<u8, Expiration> = Map::new(STAGE_EXPIRATION_KEY);
+
+pub const STAGE_START_KEY: &str = "stage_start";
+pub const STAGE_START: Map<u8, Scheduled> = Map::new(STAGE_START_KEY);
+
+pub const STAGE_AMOUNT_KEY: &str = "stage_amount";
+pub const STAGE_AMOUNT: Map<u8, Uint128> = Map::new(STAGE_AMOUNT_KEY);
+
+pub const STAGE_AMOUNT_CLAIMED_KEY: &str = "stage_claimed_amount";
+pub const STAGE_AMOUNT_CLAIMED: Map<u8, Uint128> = Map::new(STAGE_AMOUNT_CLAIMED_KEY);
+
+pub const MERKLE_ROOT_PREFIX: &str = "merkle_root";
+pub const MERKLE_ROOT: Map<u8, String> = Map::new(MERKLE_ROOT_PREFIX);
+
+pub const CLAIM_PREFIX: &str = "claim";
+pub const CLAIM: Map<(&Addr, u8), bool> = Map::new(CLAIM_PREFIX);
+
+/// Tokens delegated by the contract on behalf of each claimer, by validator.
+pub const DELEGATIONS_PREFIX: &str = "delegations";
+pub const DELEGATIONS: Map<(&Addr, &str), Uint128> = Map::new(DELEGATIONS_PREFIX);
+
+/// Undelegated tokens waiting for the end of the unbonding period.
+pub const CLAIMS: Claims = Claims::new("claims");
@@ -141,8 +141,7 @@ fn get_bonded(querier: &QuerierWrapper, contract: &Addr) -> Result<Uint128, Cont
         return Ok(Uint128::zero());
     }
     let denom = bonds[0].amount.denom.as_str();
-    bonds.iter().fold(Ok(Uint128::zero()), |racc, d| {
-        let acc = racc?;
+    bonds.iter().try_fold(Uint128::zero(), |acc, d| {
         if d.amount.denom.as_str() != denom {
             Err(ContractError::DifferentBondDenom {
                 denom1: denom.into(),
@@ -0,0 +1,231 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use cosmwasm_std::{
+    to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, MessageInfo, StdError, StdResult, Uint128,
+    WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Kind of asset: a native coin identified by its denom or a cw20 token identified by
+/// its contract address.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetInfo {
+    Native(String),
+    Cw20(Addr),
+}
+
+impl AssetInfo {
+    /// Returns true if the asset is a native coin.
+    pub fn is_native(&self) -> bool {
+        matches!(self, AssetInfo::Native(_))
+    }
+
+    /// Amount of this asset contained in the funds attached to a message. Always zero
+    /// for cw20 tokens, which cannot be sent as funds.
+    pub fn amount_sent(&self, info: &MessageInfo) -> Uint128 {
+        match self {
+            AssetInfo::Native(denom) => info
+                .funds
+                .iter()
+                .filter(|c| &c.denom == denom)
+                .map(|c| c.amount)
+                .sum(),
+            AssetInfo::Cw20(_) => Uint128::zero(),
+        }
+    }
+}
+
+impl fmt::Display for AssetInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetInfo::Native(denom) => write!(f, "{}", denom),
+            AssetInfo::Cw20(addr) => write!(f, "{}", addr),
+        }
+    }
+}
+
+/// Amount of a given asset.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Asset {
+    pub info: AssetInfo,
+    pub amount: Uint128,
+}
+
+impl Asset {
+    pub fn new(info: AssetInfo, amount: Uint128) -> Self {
+        Asset { info, amount }
+    }
+
+    pub fn native(denom: impl Into<String>, amount: impl Into<Uint128>) -> Self {
+        Asset::new(AssetInfo::Native(denom.into()), amount.into())
+    }
+
+    pub fn cw20(contract_addr: Addr, amount: impl Into<Uint128>) -> Self {
+        Asset::new(AssetInfo::Cw20(contract_addr), amount.into())
+    }
+
+    /// Message transferring the asset from the contract to the recipient.
+    pub fn transfer_msg(&self, recipient: impl Into<String>) -> StdResult<CosmosMsg> {
+        match &self.info {
+            AssetInfo::Native(denom) => Ok(BankMsg::Send {
+                to_address: recipient.into(),
+                amount: vec![Coin {
+                    denom: denom.clone(),
+                    amount: self.amount,
+                }],
+            }
+            .into()),
+            AssetInfo::Cw20(contract_addr) => Ok(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: recipient.into(),
+                    amount: self.amount,
+                })?,
+                funds: vec![],
+            }
+            .into()),
+        }
+    }
+
+    /// Message sending the asset to a recipient contract along with `msg`, delivered
+    /// with the cw20 `Send`. Native coins cannot carry a message and are transferred.
+    pub fn send_msg(&self, contract: impl Into<String>, msg: Binary) -> StdResult<CosmosMsg> {
+        match &self.info {
+            AssetInfo::Native(_) => self.transfer_msg(contract),
+            AssetInfo::Cw20(contract_addr) => Ok(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: contract.into(),
+                    amount: self.amount,
+                    msg,
+                })?,
+                funds: vec![],
+            }
+            .into()),
+        }
+    }
+}
+
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.amount, self.info)
+    }
+}
+
+impl From<Coin> for Asset {
+    fn from(coin: Coin) -> Self {
+        Asset::native(coin.denom, coin.amount)
+    }
+}
+
+impl From<&Coin> for Asset {
+    fn from(coin: &Coin) -> Self {
+        Asset::native(coin.denom.clone(), coin.amount)
+    }
+}
+
+impl TryFrom<Asset> for Coin {
+    type Error = StdError;
+
+    fn try_from(asset: Asset) -> StdResult<Self> {
+        match asset.info {
+            AssetInfo::Native(denom) => Ok(Coin {
+                denom,
+                amount: asset.amount,
+            }),
+            AssetInfo::Cw20(addr) => Err(StdError::generic_err(format!(
+                "Cannot convert cw20 {} into a native coin",
+                addr
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::{coin, coins};
+
+    #[test]
+    fn native_transfer_msg() {
+        let asset = Asset::native("ujuno", 100u128);
+        let msg = asset.transfer_msg("recipient").unwrap();
+        assert_eq!(
+            msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "recipient".to_string(),
+                amount: coins(100, "ujuno"),
+            })
+        );
+    }
+
+    #[test]
+    fn cw20_transfer_msg() {
+        let asset = Asset::cw20(Addr::unchecked("token"), 100u128);
+        let msg = asset.transfer_msg(Addr::unchecked("recipient")).unwrap();
+        assert_eq!(
+            msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "token".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "recipient".to_string(),
+                    amount: Uint128::new(100),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn send_msg() {
+        let msg = to_binary("memo").unwrap();
+        let asset = Asset::cw20(Addr::unchecked("token"), 100u128);
+        assert_eq!(
+            asset.send_msg("receiver", msg.clone()).unwrap(),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "token".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: "receiver".to_string(),
+                    amount: Uint128::new(100),
+                    msg: msg.clone(),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+
+        let asset = Asset::native("ujuno", 100u128);
+        assert_eq!(asset.send_msg("receiver", msg).unwrap(), asset.transfer_msg("receiver").unwrap());
+    }
+
+    #[test]
+    fn amount_sent() {
+        let info = mock_info("sender", &[coin(10, "ujuno"), coin(5, "ubtc"), coin(3, "ujuno")]);
+        assert_eq!(
+            AssetInfo::Native("ujuno".to_string()).amount_sent(&info),
+            Uint128::new(13)
+        );
+        assert_eq!(
+            AssetInfo::Cw20(Addr::unchecked("token")).amount_sent(&info),
+            Uint128::zero()
+        );
+    }
+
+    #[test]
+    fn coin_conversions() {
+        let asset: Asset = coin(10, "ujuno").into();
+        assert_eq!(asset, Asset::native("ujuno", 10u128));
+        assert_eq!(asset.to_string(), "10ujuno");
+        assert_eq!(Coin::try_from(asset).unwrap(), coin(10, "ujuno"));
+
+        let err = Coin::try_from(Asset::cw20(Addr::unchecked("token"), 10u128)).unwrap_err();
+        assert!(matches!(err, StdError::GenericErr { .. }));
+    }
+}
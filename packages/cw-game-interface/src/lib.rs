@@ -0,0 +1,79 @@
+use cosmwasm_std::{Addr, QuerierWrapper, StdResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Queries answered by every game. The games add these variants to their `QueryMsg`, so
+/// a game answers them whatever other queries it supports.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GameQueryMsg {
+    /// Whether an address won the game. Returns `IsWinnerResponse`.
+    IsWinner { address: String },
+    /// Bid of an address. Returns `BidResponse`.
+    Bid { address: String },
+    /// Phase of the game. Returns `StageStatusResponse`.
+    StageStatus {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsWinnerResponse {
+    /// `None` until the outcome of the bid of the address is known.
+    pub is_winner: Option<bool>,
+}
+
+/// Fields of the bid every game returns. Games may return more fields, ignored when
+/// deserializing this response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidResponse {
+    /// Whether the address has a bid.
+    pub has_bid: bool,
+    /// Bin chosen by the address, `None` if it has no bid or if the game hides it.
+    pub bin: Option<u8>,
+}
+
+/// Phase of a game, common to all the games whatever their stages.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GamePhase {
+    /// The bids are not accepted yet.
+    NotStarted,
+    /// The bids are accepted.
+    Bidding,
+    /// The bids are closed and the outcome is not claimable yet.
+    Settling,
+    /// Winners and airdrop recipients can claim.
+    Claiming,
+    /// Nothing can be claimed anymore.
+    Ended,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StageStatusResponse {
+    pub phase: GamePhase,
+    /// Name of the stage of the game in progress, `None` between the stages.
+    pub stage: Option<String>,
+}
+
+/// Address of a game, with helpers to send the queries of the interface.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GameContract(pub Addr);
+
+impl GameContract {
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    pub fn is_winner(&self, querier: &QuerierWrapper, address: impl Into<String>) -> StdResult<IsWinnerResponse> {
+        let msg = GameQueryMsg::IsWinner { address: address.into() };
+        querier.query_wasm_smart(&self.0, &msg)
+    }
+
+    pub fn bid(&self, querier: &QuerierWrapper, address: impl Into<String>) -> StdResult<BidResponse> {
+        let msg = GameQueryMsg::Bid { address: address.into() };
+        querier.query_wasm_smart(&self.0, &msg)
+    }
+
+    pub fn stage_status(&self, querier: &QuerierWrapper) -> StdResult<StageStatusResponse> {
+        querier.query_wasm_smart(&self.0, &GameQueryMsg::StageStatus {})
+    }
+}